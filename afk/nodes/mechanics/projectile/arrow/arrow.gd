@@ -115,14 +115,12 @@ func on_hit(target: Node2D) -> void:
 				var target_ulid = target.stats.ulid
 
 				# Call Rust to handle projectile hit (calculates damage, applies it, returns events)
-				var events_json = NPCDataWarehouse.projectile_hit(attacker_ulid, target_ulid)
+				var events = NPCDataWarehouse.projectile_hit_events(attacker_ulid, target_ulid)
 
 				# Process damage/death events through NPCManager
 				if NPCManager:
-					for event_json in events_json:
-						var event = JSON.parse_string(event_json)
-						if event:
-							NPCManager._handle_combat_event(event)
+					for event in events:
+						NPCManager._handle_combat_event(event)
 
 	# Return to pool (deferred to avoid physics callback issues)
 	call_deferred("_return_to_pool")
@@ -26,10 +26,9 @@ func _init() -> void:
 	# Set eyebeast-specific properties
 	walk_speed = 35.0  # Faster than goblin (flies)
 
-	# Set state flags: MELEE combat type + MONSTER faction
-	# Set state flags: MELEE combat type + MONSTER faction (static, never changes)
-	# TODO: Change to RANGED when ranged monster attacks are implemented
-	static_state = NPCManager.NPCStaticState.MELEE | NPCManager.NPCStaticState.MONSTER
+	# Set state flags: MAGIC combat type + MONSTER faction (static, never changes)
+	# Eye beams cost mana - out of mana it falls back to a weak physical attack
+	static_state = NPCManager.NPCStaticState.MAGIC | NPCManager.NPCStaticState.MONSTER
 	# Behavioral state (dynamic, changes during gameplay)
 	current_state = NPCManager.NPCState.IDLE
 
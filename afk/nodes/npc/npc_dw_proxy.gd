@@ -38,6 +38,31 @@ func _ready() -> void:
 	# This allows NPCDataWarehouse.connect("npc_died", ...) to work
 	if _warehouse.has_signal("npc_died"):
 		_warehouse.connect("npc_died", _on_warehouse_npc_died)
+	if _warehouse.has_signal("npc_attacked"):
+		_warehouse.connect("npc_attacked", npc_attacked.emit)
+		_warehouse.connect("npc_damaged", npc_damaged.emit)
+		_warehouse.connect("npc_killed", npc_killed.emit)
+		_warehouse.connect("projectile_fired", projectile_fired.emit)
+	if _warehouse.has_signal("npc_healed"):
+		_warehouse.connect("npc_healed", npc_healed.emit)
+	if _warehouse.has_signal("faction_relation_changed"):
+		_warehouse.connect("faction_relation_changed", faction_relation_changed.emit)
+		_warehouse.connect("npc_faction_changed", npc_faction_changed.emit)
+	if _warehouse.has_signal("status_effect_applied"):
+		_warehouse.connect("status_effect_applied", status_effect_applied.emit)
+		_warehouse.connect("status_effect_ticked", status_effect_ticked.emit)
+		_warehouse.connect("status_effect_expired", status_effect_expired.emit)
+	if _warehouse.has_signal("npc_resources_changed"):
+		_warehouse.connect("npc_resources_changed", npc_resources_changed.emit)
+	if _warehouse.has_signal("npc_hunger_changed"):
+		_warehouse.connect("npc_hunger_changed", npc_hunger_changed.emit)
+		_warehouse.connect("food_stockpile_changed", food_stockpile_changed.emit)
+	if _warehouse.has_signal("npc_emotion_changed"):
+		_warehouse.connect("npc_emotion_changed", npc_emotion_changed.emit)
+	if _warehouse.has_signal("squad_arrived"):
+		_warehouse.connect("squad_arrived", squad_arrived.emit)
+		_warehouse.connect("squad_leader_changed", squad_leader_changed.emit)
+		_warehouse.connect("squad_disbanded", squad_disbanded.emit)
 
 	# Initialize NPC pools immediately (before combat tick can run)
 	# This prevents race conditions where combat tries to spawn before pools exist
@@ -46,12 +71,15 @@ func _ready() -> void:
 	# Allies
 	_warehouse.initialize_npc_pool("warrior", 10, "res://nodes/npc/warrior/warrior.tscn")
 	_warehouse.initialize_npc_pool("archer", 10, "res://nodes/npc/archer/archer.tscn")
+	_warehouse.initialize_npc_pool("cleric", 4, "res://nodes/npc/cleric/cleric.tscn")
 
-	# Monsters
-	_warehouse.initialize_npc_pool("goblin", 10, "res://nodes/npc/goblin/goblin.tscn")
-	_warehouse.initialize_npc_pool("mushroom", 10, "res://nodes/npc/mushroom/mushroom.tscn")
-	_warehouse.initialize_npc_pool("skeleton", 10, "res://nodes/npc/skeleton/skeleton.tscn")
-	_warehouse.initialize_npc_pool("eyebeast", 10, "res://nodes/npc/eyebeast/eyebeast.tscn")
+	# Monsters - a few nodes now, the rest instanced a couple per frame
+	# (keeps a full wave warm without a hitch on start or on the first wave)
+	for monster in ["goblin", "mushroom", "skeleton", "eyebeast", "mushroom_shaman"]:
+		var scene_path := "res://nodes/npc/%s/%s.tscn" % [monster, monster]
+		_warehouse.register_pool(monster, 10, scene_path)
+		_warehouse.set_pool_policy(monster, 8, 8, 30000)
+		_warehouse.initialize_npc_pool(monster, 4, scene_path)
 
 	# Passive
 	_warehouse.initialize_npc_pool("chicken", 5, "res://nodes/npc/chicken/chicken.tscn")
@@ -68,6 +96,47 @@ func register_pool(npc_type: String, max_size: int, scene_path: String) -> void:
 		push_error("NPCDataWarehouse: Warehouse not initialized!")
 
 
+## What happens to spawns when a pool is at capacity: "reject" or "queue"
+func set_pool_overflow(npc_type: String, policy: String) -> bool:
+	if _warehouse:
+		return _warehouse.set_pool_overflow(npc_type, policy)
+	return false
+
+
+## Warm/idle policy: keep min_warm nodes ready (instanced a few per frame) and
+## free nodes above max_idle once they sat idle for idle_timeout_ms (-1 = never)
+func set_pool_policy(npc_type: String, min_warm: int, max_idle: int, idle_timeout_ms: int) -> bool:
+	if _warehouse:
+		return _warehouse.set_pool_policy(npc_type, min_warm, max_idle, idle_timeout_ms)
+	return false
+
+
+## Cap the pooled nodes of every type together (0 = no budget)
+func set_node_budget(budget: int) -> void:
+	if _warehouse:
+		_warehouse.set_node_budget(budget)
+
+
+## How many pooled nodes are instanced per frame while pools warm up
+func set_pool_prewarm_rate(per_frame: int) -> void:
+	if _warehouse:
+		_warehouse.set_pool_prewarm_rate(per_frame)
+
+
+## Pool definition with live counts (current_active, available, queued)
+func get_pool(npc_type: String) -> Dictionary:
+	if _warehouse:
+		return _warehouse.get_pool(npc_type)
+	return {}
+
+
+## Statistics for every pool plus node totals and the node budget
+func get_pool_stats() -> Dictionary:
+	if _warehouse:
+		return _warehouse.get_pool_stats()
+	return {}
+
+
 # ===== Rust NPC Pool System Methods =====
 
 ## Initialize an NPC pool (pre-create NPCs)
@@ -79,6 +148,35 @@ func initialize_npc_pool(npc_type: String, pool_size: int, scene_path: String) -
 		push_error("NPCDataWarehouse: Warehouse not initialized!")
 
 
+## Load an archetype definitions file ({"archetypes": [...]})
+## Returns how many archetypes were registered, or -1 on error
+func load_archetypes(path: String) -> int:
+	if _warehouse:
+		return _warehouse.load_archetypes(path)
+	return -1
+
+
+## Register (or replace) a single archetype from its JSON object
+func register_archetype(archetype_json: String) -> bool:
+	if _warehouse:
+		return _warehouse.register_archetype(archetype_json)
+	return false
+
+
+## Get an archetype definition as JSON (empty string if unknown)
+func get_archetype_json(npc_type: String) -> String:
+	if _warehouse:
+		return _warehouse.get_archetype_json(npc_type)
+	return ""
+
+
+## Get the names of every registered archetype
+func get_archetype_names() -> PackedStringArray:
+	if _warehouse:
+		return _warehouse.get_archetype_names()
+	return PackedStringArray()
+
+
 ## Set the scene container where NPCs will be added as children
 func set_scene_container(container: Node2D) -> void:
 	if _warehouse:
@@ -246,6 +344,350 @@ func state_to_string(state: int) -> String:
 	return ""
 
 
+# ===== Damage =====
+# Every hit uses attack / attack_divisor - defense / defense_divisor
+# (at least min_damage), then variance, crits and resistances
+
+## Set the damage formula (keeps the current variance)
+func set_damage_formula(attack_divisor: float, defense_divisor: float, min_damage: float) -> bool:
+	if _warehouse:
+		return _warehouse.set_damage_formula(attack_divisor, defense_divisor, min_damage)
+	return false
+
+
+## Set the random spread of hits (0.1 = +/-10%, 0 = none)
+func set_damage_variance(variance: float) -> bool:
+	if _warehouse:
+		return _warehouse.set_damage_variance(variance)
+	return false
+
+
+## Current damage formula (attack_divisor, defense_divisor, min_damage, variance)
+func get_damage_config() -> Dictionary:
+	if _warehouse:
+		return _warehouse.get_damage_config()
+	return {}
+
+
+## Breakdowns of the hits since the last call, oldest first
+func get_damage_log() -> Array[Dictionary]:
+	if _warehouse:
+		return _warehouse.get_damage_log()
+	return []
+
+
+# ===== Status Effects =====
+# Built-in effects: "poison", "burn", "slow", "stun", "regen"
+
+## Register (or replace) a status effect from its JSON object
+func register_status_effect(effect_json: String) -> bool:
+	if _warehouse:
+		return _warehouse.register_status_effect(effect_json)
+	return false
+
+
+## Registered status effect names
+func get_status_effect_names() -> PackedStringArray:
+	if _warehouse:
+		return _warehouse.get_status_effect_names()
+	return PackedStringArray()
+
+
+## Apply a status effect (source is credited with kills, empty = none) - returns stacks, -1 on error
+func apply_status_effect(ulid: PackedByteArray, effect: String, source: PackedByteArray = PackedByteArray()) -> int:
+	if _warehouse:
+		return _warehouse.apply_status_effect(ulid, effect, source)
+	return -1
+
+
+## Remove a status effect from an NPC (false if it didn't have it)
+func remove_status_effect(ulid: PackedByteArray, effect: String) -> bool:
+	if _warehouse:
+		return _warehouse.remove_status_effect(ulid, effect)
+	return false
+
+
+## Status effects running on an NPC (name, stacks, remaining_ms - -1 until removed)
+func get_status_effects(ulid: PackedByteArray) -> Array[Dictionary]:
+	if _warehouse:
+		return _warehouse.get_status_effects(ulid)
+	return []
+
+
+# ===== Hunger / Food =====
+# Kingdom NPCs (allies and pets) eat from the food stockpile when hungry
+
+## Food units in the kingdom's stockpile
+func get_food_stockpile() -> int:
+	if _warehouse:
+		return _warehouse.get_food_stockpile()
+	return 0
+
+
+## Put food into the stockpile (emits food_stockpile_changed) - returns the new total
+func add_food(units: int) -> int:
+	if _warehouse:
+		return _warehouse.add_food(units)
+	return 0
+
+
+## Feed an NPC one unit from the stockpile - returns its hunger, -1 on error
+func feed_npc(ulid: PackedByteArray) -> float:
+	if _warehouse:
+		return _warehouse.feed_npc(ulid)
+	return -1.0
+
+
+# ===== Emotions =====
+# Hits, kills, deaths nearby, heals, hunger and energy move each NPC's emotion
+
+## Current emotion of an NPC ("neutral", "happy", "enraged", "frightened", "sad", "weary")
+func get_npc_emotion(ulid: PackedByteArray) -> String:
+	if _warehouse:
+		return _warehouse.get_npc_emotion(ulid)
+	return "neutral"
+
+
+# ===== Threat / Targeting =====
+# Damage, heals and taunts build threat; archetypes pick targets by strategy
+
+## Make an NPC go after a hostile target (empty target = pick its own again)
+func force_npc_target(ulid: PackedByteArray, target: PackedByteArray = PackedByteArray()) -> bool:
+	if _warehouse:
+		return _warehouse.force_npc_target(ulid, target)
+	return false
+
+
+## Taunt every hostile near an NPC - returns how many were taunted, -1 on error
+func taunt_npc(ulid: PackedByteArray) -> int:
+	if _warehouse:
+		return _warehouse.taunt_npc(ulid)
+	return -1
+
+
+## ULID of the NPC's current target (empty when it has none)
+func get_npc_target(ulid: PackedByteArray) -> PackedByteArray:
+	if _warehouse:
+		return _warehouse.get_npc_target(ulid)
+	return PackedByteArray()
+
+
+## Threat table of an NPC, highest first ({source, threat})
+func get_npc_threat(ulid: PackedByteArray) -> Array[Dictionary]:
+	if _warehouse:
+		return _warehouse.get_npc_threat(ulid)
+	return []
+
+
+# ===== Squads =====
+# Named groups that march in formation ("line", "wedge", "column", "ranked")
+
+## Form a squad around a leader (single-word name)
+func create_squad(squad_name: String, leader: PackedByteArray, formation: String = "line") -> bool:
+	if _warehouse:
+		return _warehouse.create_squad(squad_name, leader, formation)
+	return false
+
+
+## Add an NPC to a squad (it leaves its current squad)
+func assign_to_squad(squad_name: String, ulid: PackedByteArray) -> bool:
+	if _warehouse:
+		return _warehouse.assign_to_squad(squad_name, ulid)
+	return false
+
+
+## Take an NPC out of its squad
+func remove_from_squad(ulid: PackedByteArray) -> bool:
+	if _warehouse:
+		return _warehouse.remove_from_squad(ulid)
+	return false
+
+
+## Disband a squad - its members act on their own again
+func disband_squad(squad_name: String) -> bool:
+	if _warehouse:
+		return _warehouse.disband_squad(squad_name)
+	return false
+
+
+## Order a squad to march to a point (simulation coordinates), keeping formation
+func order_squad(squad_name: String, target: Vector2) -> bool:
+	if _warehouse:
+		return _warehouse.order_squad(squad_name, target)
+	return false
+
+
+## Change a squad's formation
+func set_squad_formation(squad_name: String, formation: String) -> bool:
+	if _warehouse:
+		return _warehouse.set_squad_formation(squad_name, formation)
+	return false
+
+
+## Squad names, in name order
+func get_squad_names() -> PackedStringArray:
+	if _warehouse:
+		return _warehouse.get_squad_names()
+	return PackedStringArray()
+
+
+## Name of the squad an NPC belongs to (empty if none)
+func get_npc_squad(ulid: PackedByteArray) -> String:
+	if _warehouse:
+		return _warehouse.get_npc_squad(ulid)
+	return ""
+
+
+## A squad's layout ({leader, formation, members, slots, anchor, facing, marching, target})
+func get_squad(squad_name: String) -> Dictionary:
+	if _warehouse:
+		return _warehouse.get_squad(squad_name)
+	return {}
+
+
+# ===== Factions =====
+# Relations are "hostile", "neutral" or "friendly" and apply both ways.
+# Built-in factions: "ally", "monster", "passive"
+
+## Register a faction copying the relations of inherit ("" = neutral to all), -1 on error
+func register_faction(faction_name: String, inherit: String = "") -> int:
+	if _warehouse:
+		return _warehouse.register_faction(faction_name, inherit)
+	return -1
+
+
+## Set the relation between two factions (emits faction_relation_changed)
+func set_faction_relation(faction_a: String, faction_b: String, relation: String) -> bool:
+	if _warehouse:
+		return _warehouse.set_faction_relation(faction_a, faction_b, relation)
+	return false
+
+
+## Relation between two factions ("" if either is unknown)
+func get_faction_relation(faction_a: String, faction_b: String) -> String:
+	if _warehouse:
+		return _warehouse.get_faction_relation(faction_a, faction_b)
+	return ""
+
+
+## Registered faction names
+func get_faction_names() -> PackedStringArray:
+	if _warehouse:
+		return _warehouse.get_faction_names()
+	return PackedStringArray()
+
+
+## Band idle members wander in, as fractions of the world width (negative = whole world)
+func set_faction_wander_zone(faction_name: String, min_x: float, max_x: float) -> bool:
+	if _warehouse:
+		return _warehouse.set_faction_wander_zone(faction_name, min_x, max_x)
+	return false
+
+
+## Move an NPC to another faction until it despawns (emits npc_faction_changed)
+func set_npc_faction(ulid: PackedByteArray, faction_name: String) -> bool:
+	if _warehouse:
+		return _warehouse.set_npc_faction(ulid, faction_name)
+	return false
+
+
+## Faction name of an NPC ("" if unknown)
+func get_npc_faction(ulid: PackedByteArray) -> String:
+	if _warehouse:
+		return _warehouse.get_npc_faction(ulid)
+	return ""
+
+
+# ===== NPC Queries =====
+# Positions are simulation (container-local) coordinates, like get_npc_position.
+# Masks come from get_state / get_static_state (0 = any), an empty npc_type
+# matches every type and an empty faction every faction; dead NPCs are skipped
+# unless the state mask includes DEAD. Results are ULIDs (PackedByteArray each)
+
+## NPCs within radius of center, nearest first
+func query_npcs_in_radius(center: Vector2, radius: float, npc_type: String = "", faction_name: String = "", static_mask: int = 0) -> Array[PackedByteArray]:
+	if _warehouse:
+		return _warehouse.query_npcs_in_radius(center, radius, npc_type, faction_name, static_mask)
+	return []
+
+
+## NPCs inside a rectangle (e.g. a drag selection)
+func query_npcs_in_rect(rect: Rect2, npc_type: String = "", faction_name: String = "", static_mask: int = 0) -> Array[PackedByteArray]:
+	if _warehouse:
+		return _warehouse.query_npcs_in_rect(rect, npc_type, faction_name, static_mask)
+	return []
+
+
+## Up to count NPCs nearest to center
+func query_nearest_npcs(center: Vector2, count: int, npc_type: String = "", faction_name: String = "", static_mask: int = 0) -> Array[PackedByteArray]:
+	if _warehouse:
+		return _warehouse.query_nearest_npcs(center, count, npc_type, faction_name, static_mask)
+	return []
+
+
+## NPCs whose behavioral and static flags contain both masks
+func query_npcs_by_state(state_mask: int, npc_type: String = "", faction_name: String = "", static_mask: int = 0) -> Array[PackedByteArray]:
+	if _warehouse:
+		return _warehouse.query_npcs_by_state(state_mask, npc_type, faction_name, static_mask)
+	return []
+
+
+## NPC in range with the lowest HP fraction (empty if none)
+func query_lowest_hp_in_radius(center: Vector2, radius: float, npc_type: String = "", faction_name: String = "", static_mask: int = 0) -> PackedByteArray:
+	if _warehouse:
+		return _warehouse.query_lowest_hp_in_radius(center, radius, npc_type, faction_name, static_mask)
+	return PackedByteArray()
+
+
+# ===== Save / Load =====
+
+## Snapshot the whole battle as JSON (write it to user:// to persist)
+func save_snapshot() -> String:
+	if _warehouse:
+		return _warehouse.save_snapshot()
+	return ""
+
+
+## Restore a battle from save_snapshot() - NPC pools must be initialized first
+## Returns how many NPCs were restored, or -1 if the snapshot is invalid
+func load_snapshot(json: String) -> int:
+	if _warehouse:
+		return _warehouse.load_snapshot(json)
+	return -1
+
+
+# ===== Input Recording / Replay =====
+
+## Start recording external inputs and per-tick checksums
+## Pass a negative seed to pick a random one - returns the seed in use
+func start_recording(seed: int = -1) -> int:
+	if _warehouse:
+		return _warehouse.start_recording(seed)
+	return -1
+
+
+## Stop recording and write the replay file (e.g. "user://battle.replay")
+func stop_recording(path: String) -> bool:
+	if _warehouse:
+		return _warehouse.stop_recording(path)
+	return false
+
+
+## Check if inputs are currently being recorded
+func is_recording() -> bool:
+	if _warehouse:
+		return _warehouse.is_recording()
+	return false
+
+
+## Re-run a replay file headlessly and report the first divergent tick
+## Keys: ok, ticks, inputs, diverged_at, expected_hash, actual_hash, error
+func replay_recording(path: String) -> Dictionary:
+	if _warehouse:
+		return _warehouse.replay_recording(path)
+	return {"ok": false, "error": "warehouse not initialized"}
+
+
 # ===== ULID Generation (Binary Format for Performance) =====
 
 ## Generate a new ULID as raw bytes (16 bytes / 128 bits)
@@ -377,13 +819,85 @@ func stop_combat_system() -> void:
 	if _warehouse:
 		_warehouse.stop_combat_system()
 
+## Set battle speed (1.0 = normal, 2.0 / 4.0 = fast, clamped to 0..16)
+func set_time_scale(scale: float) -> void:
+	if _warehouse:
+		_warehouse.set_time_scale(scale)
+
+## Current battle speed multiplier
+func get_time_scale() -> float:
+	if _warehouse:
+		return _warehouse.get_time_scale()
+	return 1.0
+
+## Freeze the battle - tick_combat() runs no steps until resume()
+func pause() -> void:
+	if _warehouse:
+		_warehouse.pause()
+
+## Continue a paused battle
+func resume() -> void:
+	if _warehouse:
+		_warehouse.resume()
+
+## Check if the battle is paused
+func is_paused() -> bool:
+	if _warehouse:
+		return _warehouse.is_paused()
+	return false
+
+## Run exactly n fixed simulation steps now (works while paused)
+func step(n: int = 1) -> int:
+	if _warehouse:
+		return _warehouse.step(n)
+	return 0
+
+## Simulate seconds of battle right away (AFK catch-up) - returns steps run
+func fast_forward(seconds: float) -> int:
+	if _warehouse:
+		return _warehouse.fast_forward(seconds)
+	return 0
+
+## Run the simulation on a worker thread at a fixed rate (tick_hz <= 0 uses 60)
+## tick_combat() then only applies what the worker produced each frame
+## Returns false if already running or threads are unavailable (web export)
+func start_simulation_thread(tick_hz: float = 60.0) -> bool:
+	if _warehouse:
+		return _warehouse.start_simulation_thread(tick_hz)
+	return false
+
+## Stop the worker thread - tick_combat() ticks inline again
+func stop_simulation_thread() -> void:
+	if _warehouse:
+		_warehouse.stop_simulation_thread()
+
+## Check if the simulation is running on its worker thread
+func is_simulation_threaded() -> bool:
+	if _warehouse:
+		return _warehouse.is_simulation_threaded()
+	return false
+
+## Number of full simulation ticks run so far
+func get_simulation_tick() -> int:
+	if _warehouse:
+		return _warehouse.get_simulation_tick()
+	return 0
+
 ## Tick combat logic (call every frame from _process)
-## Returns Array of JSON strings (CombatEvent)
+## Returns Array of JSON strings (CombatEvent) - kept for compatibility
 func tick_combat(delta: float) -> Array:
 	if _warehouse:
 		return _warehouse.tick_combat(delta)
 	return []
 
+## Tick combat logic and get events as Dictionaries
+## Keys: type, attacker, target (ULID bytes), position (global Vector2),
+## amount (damage/death), projectile, origin, speed (projectile)
+func tick_combat_events(delta: float) -> Array:
+	if _warehouse:
+		return _warehouse.tick_combat_events(delta)
+	return []
+
 ## Tick ONLY the combat phase (damage calculations and state changes)
 ## Returns Array of JSON strings (CombatEvent)
 func tick_combat_phase() -> Array:
@@ -490,12 +1004,21 @@ func projectile_hit(attacker_ulid_bytes: PackedByteArray, target_ulid_bytes: Pac
 		return _warehouse.projectile_hit(attacker_ulid_bytes, target_ulid_bytes)
 	return []
 
+## Handle projectile hit - returns the damage/death event as Dictionaries
+## (same keys as tick_combat_events), empty if the target was already dead
+func projectile_hit_events(attacker_ulid_bytes: PackedByteArray, target_ulid_bytes: PackedByteArray) -> Array:
+	if _warehouse:
+		return _warehouse.projectile_hit_events(attacker_ulid_bytes, target_ulid_bytes)
+	return []
+
 ## Set world bounds for waypoint clamping (from BackgroundManager)
 ## Called when background loads to set safe zone boundaries
 ## min_x, max_x, min_y, max_y: floats defining the playable rectangle
-func set_world_bounds(min_x: float, max_x: float, min_y: float, max_y: float) -> void:
+## Returns false (bounds unchanged) unless min < max on both axes
+func set_world_bounds(min_x: float, max_x: float, min_y: float, max_y: float) -> bool:
 	if _warehouse:
-		_warehouse.set_world_bounds(min_x, max_x, min_y, max_y)
+		return _warehouse.set_world_bounds(min_x, max_x, min_y, max_y)
+	return false
 
 ## Get NPC stats as a Dictionary (for UI display)
 ## ulid_bytes: PackedByteArray (16 bytes) - raw ULID bytes
@@ -570,6 +1093,57 @@ func active_healthbar_count() -> int:
 ## Parameters: (position_x: float, position_y: float)
 signal npc_died(position_x: float, position_y: float)
 
+## Emitted when an NPC starts an attack
+signal npc_attacked(attacker: PackedByteArray, target: PackedByteArray)
+
+## Emitted when a hit damages an NPC without killing it
+signal npc_damaged(attacker: PackedByteArray, target: PackedByteArray, amount: float, position: Vector2)
+
+## Emitted when a hit kills an NPC (npc_died follows once the body is removed)
+signal npc_killed(attacker: PackedByteArray, target: PackedByteArray, amount: float, position: Vector2)
+
+## Emitted when a healer's channeled heal lands
+signal npc_healed(healer: PackedByteArray, target: PackedByteArray, amount: float, position: Vector2)
+
+## Emitted when a ranged NPC fires a projectile
+signal projectile_fired(attacker: PackedByteArray, target: PackedByteArray, projectile: String, origin: Vector2, target_position: Vector2, speed: float)
+
+## Emitted when the relation between two factions changes
+signal faction_relation_changed(faction_a: String, faction_b: String, relation: String)
+
+## Emitted when an NPC is moved to another faction
+signal npc_faction_changed(ulid: PackedByteArray, faction: String)
+
+## Emitted when a status effect is applied, refreshed or stacked
+signal status_effect_applied(ulid: PackedByteArray, effect: String, stacks: int)
+
+## Emitted when a status effect ticks (hp_change is negative for damage)
+signal status_effect_ticked(ulid: PackedByteArray, effect: String, hp_change: float, hp: float)
+
+## Emitted when a status effect runs out or is removed
+signal status_effect_expired(ulid: PackedByteArray, effect: String)
+
+## Emitted when an NPC's mana or energy is spent or regenerates
+signal npc_resources_changed(ulid: PackedByteArray, mana: float, max_mana: float, energy: float, max_energy: float)
+
+## Emitted when an NPC's hunger crosses into another level ("fed", "hungry", "starving")
+signal npc_hunger_changed(ulid: PackedByteArray, level: String, hunger: float, max_hunger: float)
+
+## Emitted when the kingdom's food stockpile changes
+signal food_stockpile_changed(food: int)
+
+## Emitted when an NPC's emotion changes ("neutral", "happy", "enraged", "frightened", "sad", "weary")
+signal npc_emotion_changed(ulid: PackedByteArray, emotion: String, previous: String)
+
+## Emitted when a squad reaches the point it was ordered to
+signal squad_arrived(squad_name: String)
+
+## Emitted when a squad's leader leaves and the next member takes over
+signal squad_leader_changed(squad_name: String, leader: PackedByteArray)
+
+## Emitted when a squad is disbanded or loses its last member
+signal squad_disbanded(squad_name: String)
+
 ## Forward npc_died signal from Rust warehouse to this proxy
 func _on_warehouse_npc_died(position_x: float, position_y: float) -> void:
 	npc_died.emit(position_x, position_y)
@@ -51,6 +51,7 @@ var cat_scene: PackedScene = preload("res://nodes/npc/cat/cat.tscn")
 
 # Combat system tracking
 var combat_system_started: bool = false
+var _last_combat_tick_usec: int = 0  # Time.get_ticks_usec() of the previous combat tick
 
 ## ===== STATE HISTORY SYSTEM =====
 ## Centralized state tracking for all NPCs
@@ -288,86 +289,53 @@ func _on_combat_tick() -> void:
 		return
 
 	# RUST COMBAT: Tick all combat phases (combat, movement, animation) in one unified call
-	# Pass fixed delta of 0.016 (60 ticks per second)
-	# Rust internally manages attack cooldowns (3.5 seconds) to prevent rapid-fire attacks
-	var events_json = NPCDataWarehouse.tick_combat(0.016)
-
-	for event_json in events_json:
-		var event = JSON.parse_string(event_json)
-		if event:
-			_handle_combat_event(event)
-		else:
-			push_error("[COMBAT ERROR] Failed to parse combat event JSON: %s" % event_json)
+	# Pass the real time since the last tick - Rust turns it into fixed 60 Hz steps
+	# (scaled by the battle speed, none while paused)
+	var now_usec := Time.get_ticks_usec()
+	var delta := 0.016 if _last_combat_tick_usec == 0 else (now_usec - _last_combat_tick_usec) / 1000000.0
+	_last_combat_tick_usec = now_usec
+	var events = NPCDataWarehouse.tick_combat_events(delta)
+
+	for event in events:
+		_handle_combat_event(event)
 
 
 ## Handle combat event from Rust (animations, damage numbers, VFX)
+## Event is a Dictionary from tick_combat_events() / projectile_hit_events()
 func _handle_combat_event(event: Dictionary) -> void:
 	# DEFENSIVE: Validate event has required fields
-	if not "event_type" in event:
-		push_error("[COMBAT ERROR] Event missing event_type field")
+	if not "type" in event:
+		push_error("[COMBAT ERROR] Event missing type field")
 		return
 
-	# Special handling for spawn events - they don't have ULIDs yet
-	if event.event_type == "spawn":
-		if "attacker_ulid" in event and event.attacker_ulid != "":
-			var npc_type = event.attacker_ulid
-			# Check if it's an ally or monster to determine spawn position
-			var is_ally = (npc_type == "warrior" or npc_type == "archer")
-			var spawn_pos: Vector2
-			var initial_target: Vector2
-
-			if is_ally:
-				# Allies spawn on left side
-				spawn_pos = _calculate_ally_spawn_position()
-				var viewport_size = get_viewport().get_visible_rect().size
-				initial_target = Vector2(viewport_size.x / 2, spawn_pos.y)
-			else:
-				# Monsters spawn on right side
-				spawn_pos = _calculate_spawn_position()
-				var viewport_size = get_viewport().get_visible_rect().size
-				initial_target = Vector2(viewport_size.x / 2, spawn_pos.y)
-
-			# Spawn the NPC
-			_spawn_monster(npc_type, spawn_pos, initial_target)
-		return  # Spawn event handled, exit early
-
 	# RUST-SPAWNED NPCs: All combat is handled by Rust
 	# Events are only used for optional VFX/SFX on GDScript-spawned NPCs
 	# For Rust-spawned NPCs, this function does nothing (Rust handles everything)
 
 	# Only process events for GDScript-spawned NPCs (legacy support)
 	# Try to find NPCs - if not found, Rust is handling them, so skip
-	var attacker: Node2D = null
-	var target: Node2D = null
-
-	if "attacker_ulid" in event:
-		attacker = _find_npc_by_ulid(event.attacker_ulid)
-	if "target_ulid" in event:
-		target = _find_npc_by_ulid(event.target_ulid)
+	var attacker: Node2D = _find_npc_by_ulid(ULID.to_hex(event.attacker))
+	var target: Node2D = _find_npc_by_ulid(ULID.to_hex(event.target))
 
 	# If NPCs not found, they're Rust-managed - skip silently
-	if ("attacker_ulid" in event and not attacker) or ("target_ulid" in event and not target):
+	if not attacker or not target:
 		return
 
 	# LEGACY GDScript-SPAWNED NPCs ONLY: Emit EventManager signals for VFX/SFX
-	match event.event_type:
+	match event.type:
 		"attack":
-			if attacker and target:
-				EventManager.combat_started.emit(attacker, target)
+			EventManager.combat_started.emit(attacker, target)
 		"damage":
-			if attacker and target and "amount" in event:
-				EventManager.damage_dealt.emit(attacker, target, event.amount)
+			EventManager.damage_dealt.emit(attacker, target, event.amount)
 		"death":
-			if attacker and target:
-				EventManager.target_killed.emit(attacker, target)
+			EventManager.target_killed.emit(attacker, target)
 		"projectile":
-			if attacker and target:
-				attacker.set_meta("pending_projectile", {
-					"type": event.attacker_animation,
-					"target": target,
-					"target_pos": Vector2(event.target_x, event.target_y),
-					"speed": 300.0
-				})
+			attacker.set_meta("pending_projectile", {
+				"type": event.projectile,
+				"target": target,
+				"target_pos": event.position,
+				"speed": event.speed
+			})
 
 
 ## Despawn a dead NPC and return to pool
@@ -803,6 +771,10 @@ func set_layer4_container(container: Node2D) -> void:
 			NPCDataWarehouse.connect("npc_died", _on_npc_died)
 			print("[NPCManager] Connected to NPC death signal")
 
+		# Connect to npc_emotion_changed signal for emotion emojis
+		if not NPCDataWarehouse.is_connected("npc_emotion_changed", _on_npc_emotion_changed):
+			NPCDataWarehouse.connect("npc_emotion_changed", _on_npc_emotion_changed)
+
 		# Start combat tick timer
 		var combat_timer = get_node_or_null("CombatTickTimer")
 		if combat_timer:
@@ -1795,7 +1767,7 @@ func get_generic_npc(npc_type: String, position: Vector2, initial_target: Vector
 		static_state = NPCStaticState.MELEE | NPCStaticState.MONSTER
 		behavioral_state = NPCState.IDLE
 	elif npc_type == "eyebeast":
-		static_state = NPCStaticState.RANGED | NPCStaticState.MONSTER
+		static_state = NPCStaticState.MAGIC | NPCStaticState.MONSTER
 		behavioral_state = NPCState.IDLE
 	elif npc_type == "chicken":
 		static_state = NPCStaticState.MELEE | NPCStaticState.PASSIVE
@@ -2091,6 +2063,17 @@ func get_npc_type(npc: Node2D) -> String:
 	return ""
 
 
+## ===== EMOTION EMOJIS =====
+
+## Handle NPC emotion change signal from Rust (shows the emotion's emoji)
+func _on_npc_emotion_changed(ulid: PackedByteArray, emotion: String, _previous: String) -> void:
+	if not emoji_manager:
+		return
+	var npc = _find_npc_by_ulid(ULID.to_hex(ulid))
+	if npc:
+		emoji_manager.show_emotion_emoji(npc, emotion)
+
+
 ## ===== DEATH EFFECT HANDLING =====
 
 ## Handle NPC death signal from Rust (triggers release effect)
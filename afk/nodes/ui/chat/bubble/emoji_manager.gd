@@ -15,6 +15,15 @@ const STATE_EMOJIS: Dictionary = {
 	NPCManager.NPCState.DEAD: "💀",       # Skull
 }
 
+## Emotion-to-Emoji mapping (emotions come from the Rust warehouse, "neutral" has none)
+const EMOTION_EMOJIS: Dictionary = {
+	"happy": "😄",       # Grinning face
+	"enraged": "😡",     # Pouting face
+	"frightened": "😱",  # Screaming in fear
+	"sad": "😢",         # Crying face
+	"weary": "😩",       # Weary face
+}
+
 ## Default emoji set (fallback if NPC doesn't have EMOJIS constant)
 const DEFAULT_EMOJIS: Array[String] = ["😊", "🎯", "⭐", "💫"]
 
@@ -57,6 +66,13 @@ func try_show_state_emoji(npc: Node2D, old_state: int, new_state: int) -> void:
 	show_emoji(npc, emoji)
 
 
+## Show the emoji for an NPC's new emotion
+## Called by NPCManager when the warehouse emits npc_emotion_changed
+func show_emotion_emoji(npc: Node2D, emotion: String) -> void:
+	if EMOTION_EMOJIS.has(emotion):
+		show_emoji(npc, EMOTION_EMOJIS[emotion])
+
+
 ## Show a specific emoji for an NPC
 func show_emoji(npc: Node2D, emoji: String) -> void:
 	if not is_instance_valid(npc):
@@ -106,7 +122,13 @@ func _on_bubble_hidden(npc: Node2D, bubble: Node) -> void:
 
 ## Get appropriate emoji for NPC state
 func _get_emoji_for_state(npc: Node2D, state: int) -> String:
-	# Try state-based emoji first
+	# What the NPC feels beats what it is doing
+	if npc and "ulid" in npc and npc.ulid.size() > 0:
+		var emotion: String = NPCDataWarehouse.get_npc_emotion(npc.ulid)
+		if EMOTION_EMOJIS.has(emotion):
+			return EMOTION_EMOJIS[emotion]
+
+	# Try state-based emoji next
 	if STATE_EMOJIS.has(state):
 		return STATE_EMOJIS[state]
 
@@ -202,20 +202,16 @@ func _update_stats_display() -> void:
 		var max_energy = data.get("max_energy", 100)
 		energy_label.text = "Energy: %.0f/%.0f" % [current_energy, max_energy]
 
-	# Update Hunger (placeholder - not yet in Rust)
+	# Update Hunger
 	if hunger_label:
-		hunger_label.text = "Hunger: --/100"
+		var current_hunger = data.get("hunger", 0)
+		var max_hunger = data.get("max_hunger", 100)
+		hunger_label.text = "Hunger: %.0f/%.0f" % [current_hunger, max_hunger]
 
 	# Update Emotion
 	if emotion_label:
-		var emotional_state = data.get("emotional_state", 0)
-		# Map emotional_state integer to string (can be expanded later)
-		var emotion_text = "Neutral"
-		if emotional_state > 0:
-			emotion_text = "Positive"
-		elif emotional_state < 0:
-			emotion_text = "Negative"
-		emotion_label.text = "Emotion: %s" % emotion_text
+		var emotion: String = data.get("emotion", "neutral")
+		emotion_label.text = "Emotion: %s" % emotion.capitalize()
 
 	# Update Attack
 	if attack_label:
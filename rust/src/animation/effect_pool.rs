@@ -1,9 +1,11 @@
-use godot::prelude::*;
-use godot::classes::{PackedScene, Node, Node2D, Object};
 use dashmap::DashMap;
+use godot::classes::{Node, Node2D, Object, PackedScene};
+use godot::prelude::*;
+use parking_lot::RwLock;
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use parking_lot::RwLock;
 
 /// Generic animation/effect pool
 /// Pre-instantiates scenes to avoid runtime overhead
@@ -27,7 +29,7 @@ pub struct EffectPool {
     animation_duration_ms: RwLock<u64>,
 
     /// Scene container for adding effects to the scene tree
-    scene_container: Arc<RwLock<Option<Gd<Node2D>>>>,
+    scene_container: Rc<RefCell<Option<Gd<Node2D>>>>,
 
     /// Scene path for this pool (wrapped for interior mutability)
     scene_path: RwLock<String>,
@@ -38,7 +40,7 @@ pub struct EffectPool {
 
 impl EffectPool {
     /// Create a new empty effect pool
-    pub fn new(scene_container: Arc<RwLock<Option<Gd<Node2D>>>>, pool_name: &str) -> Self {
+    pub fn new(scene_container: Rc<RefCell<Option<Gd<Node2D>>>>, pool_name: &str) -> Self {
         Self {
             pool: DashMap::new(),
             availability: DashMap::new(),
@@ -56,26 +58,39 @@ impl EffectPool {
     pub fn initialize(&self, pool_size: usize, scene_path: &str, animation_duration_ms: u64) {
         *self.scene_path.write() = scene_path.to_string();
         *self.animation_duration_ms.write() = animation_duration_ms;
-        godot_print!("[{} POOL] Initializing: size={}, scene={}, duration={}ms",
-            self.pool_name, pool_size, scene_path, animation_duration_ms);
+        godot_print!(
+            "[{} POOL] Initializing: size={}, scene={}, duration={}ms",
+            self.pool_name,
+            pool_size,
+            scene_path,
+            animation_duration_ms
+        );
 
         // Load the effect scene
         let scene: Gd<PackedScene> = match try_load::<PackedScene>(scene_path) {
             Ok(scene) => scene,
             Err(err) => {
-                godot_error!("[{} POOL] Failed to load scene: {} - {:?}", self.pool_name, scene_path, err);
+                godot_error!(
+                    "[{} POOL] Failed to load scene: {} - {:?}",
+                    self.pool_name,
+                    scene_path,
+                    err
+                );
                 return;
             }
         };
 
         // Get the scene container
         let container_opt = {
-            let container_guard = self.scene_container.read();
+            let container_guard = self.scene_container.borrow();
             container_guard.clone()
         };
 
         if container_opt.is_none() {
-            godot_error!("[{} POOL] Cannot initialize - scene container not set!", self.pool_name);
+            godot_error!(
+                "[{} POOL] Cannot initialize - scene container not set!",
+                self.pool_name
+            );
             return;
         }
 
@@ -88,7 +103,7 @@ impl EffectPool {
             // Configure effect BEFORE adding to scene tree
             if let Ok(mut node2d_effect) = effect.clone().try_cast::<Node2D>() {
                 node2d_effect.set_visible(false); // Hide until triggered
-                node2d_effect.set_z_index(50);     // Render between NPCs and healthbars
+                node2d_effect.set_z_index(50); // Render between NPCs and healthbars
             }
 
             // Add to scene tree synchronously (with internal_mode to skip _ready initially if possible)
@@ -101,7 +116,11 @@ impl EffectPool {
             self.availability.insert(index, false); // false = available
         }
 
-        godot_print!("[{} POOL] Initialized {} effects", self.pool_name, pool_size);
+        godot_print!(
+            "[{} POOL] Initialized {} effects",
+            self.pool_name,
+            pool_size
+        );
     }
 
     /// Get an available effect from the pool
@@ -141,10 +160,18 @@ impl EffectPool {
             // Mark as available
             self.availability.insert(pool_index, false);
 
-            godot_print!("[{} POOL] Returned effect {} to pool", self.pool_name, pool_index);
+            godot_print!(
+                "[{} POOL] Returned effect {} to pool",
+                self.pool_name,
+                pool_index
+            );
             true
         } else {
-            godot_warn!("[{} POOL] Invalid pool index: {}", self.pool_name, pool_index);
+            godot_warn!(
+                "[{} POOL] Invalid pool index: {}",
+                self.pool_name,
+                pool_index
+            );
             false
         }
     }
@@ -165,13 +192,25 @@ impl EffectPool {
     /// Trigger an effect at a specific position
     /// Returns true if effect was triggered, false if pool is full
     pub fn trigger_at_position(&self, position: Vector2) -> bool {
-        godot_print!("[{} POOL] trigger_at_position called at {:?}", self.pool_name, position);
+        godot_print!(
+            "[{} POOL] trigger_at_position called at {:?}",
+            self.pool_name,
+            position
+        );
 
         let effect_opt = self.get_effect();
-        godot_print!("[{} POOL] get_effect returned: {}", self.pool_name, effect_opt.is_some());
+        godot_print!(
+            "[{} POOL] get_effect returned: {}",
+            self.pool_name,
+            effect_opt.is_some()
+        );
 
         if let Some((mut effect, pool_index)) = effect_opt {
-            godot_print!("[{} POOL] Got effect {}, recording trigger time", self.pool_name, pool_index);
+            godot_print!(
+                "[{} POOL] Got effect {}, recording trigger time",
+                self.pool_name,
+                pool_index
+            );
 
             // Record when this effect was triggered
             let now_ms = std::time::SystemTime::now()
@@ -180,11 +219,17 @@ impl EffectPool {
                 .as_millis() as u64;
             self.trigger_times.insert(pool_index, now_ms);
 
-            godot_print!("[{} POOL] Trigger time recorded, trying to cast to Node2D", self.pool_name);
+            godot_print!(
+                "[{} POOL] Trigger time recorded, trying to cast to Node2D",
+                self.pool_name
+            );
 
             // Position the effect
             if let Ok(mut node2d_effect) = effect.clone().try_cast::<Node2D>() {
-                godot_print!("[{} POOL] Successfully cast to Node2D, setting position", self.pool_name);
+                godot_print!(
+                    "[{} POOL] Successfully cast to Node2D, setting position",
+                    self.pool_name
+                );
                 node2d_effect.set_global_position(position);
                 godot_print!("[{} POOL] Position set, setting visibility", self.pool_name);
                 node2d_effect.set_visible(true);
@@ -199,17 +244,32 @@ impl EffectPool {
             let play_method = StringName::from("play");
             match effect.try_call_deferred(&play_method, &[]) {
                 Ok(_) => {
-                    godot_print!("[{} POOL] Deferred play() call succeeded for effect {}", self.pool_name, pool_index);
+                    godot_print!(
+                        "[{} POOL] Deferred play() call succeeded for effect {}",
+                        self.pool_name,
+                        pool_index
+                    );
                 }
                 Err(e) => {
-                    godot_error!("[{} POOL] Failed to call deferred play() on effect {}: {:?}", self.pool_name, pool_index, e);
+                    godot_error!(
+                        "[{} POOL] Failed to call deferred play() on effect {}: {:?}",
+                        self.pool_name,
+                        pool_index,
+                        e
+                    );
                 }
             }
 
-            godot_print!("[{} POOL] Returning true from trigger_at_position", self.pool_name);
+            godot_print!(
+                "[{} POOL] Returning true from trigger_at_position",
+                self.pool_name
+            );
             true
         } else {
-            godot_print!("[{} POOL] No effects available (all in use)", self.pool_name);
+            godot_print!(
+                "[{} POOL] No effects available (all in use)",
+                self.pool_name
+            );
             false
         }
     }
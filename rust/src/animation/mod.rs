@@ -2,7 +2,6 @@
 ///
 /// This module handles pooled visual effects like death animations,
 /// particles, and other reusable animation assets.
-
 pub mod effect_pool;
 
 pub use effect_pool::EffectPool;
@@ -1,12 +1,20 @@
+#[cfg(feature = "godot")]
 use godot::prelude::*;
 
 mod name_generator;
 //mod inventory_data_warehouse;
-mod npc_data_warehouse;
+#[cfg(feature = "godot")]
 mod animation;
+#[cfg(feature = "godot")]
+mod npc_data_warehouse;
+#[cfg(feature = "godot")]
+mod npc_node_layer;
+pub mod simulation;
 
+#[cfg(feature = "godot")]
 struct Godo;
 
+#[cfg(feature = "godot")]
 #[gdextension]
 unsafe impl ExtensionLibrary for Godo {
     fn on_level_init(level: InitLevel) {
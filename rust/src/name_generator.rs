@@ -101,47 +101,54 @@ const CHICKEN_NAMES: &[&str] = &[
 ];
 
 /// Generate a fantasy name for an NPC based on their type
-pub fn generate_name(npc_type: &str) -> String {
-    let mut rng = rand::thread_rng();
-
+/// Draws from the caller's RNG so seeded simulations name NPCs identically
+pub fn generate_name<R: Rng + ?Sized>(npc_type: &str, rng: &mut R) -> String {
     match npc_type {
         "warrior" => {
-            let first = FIRST_NAMES[rng.gen_range(0..FIRST_NAMES.len())];
-            let title = WARRIOR_TITLES[rng.gen_range(0..WARRIOR_TITLES.len())];
+            let first = FIRST_NAMES[rng.random_range(0..FIRST_NAMES.len())];
+            let title = WARRIOR_TITLES[rng.random_range(0..WARRIOR_TITLES.len())];
             format!("{} {}", first, title)
         }
         "archer" => {
-            let first = FIRST_NAMES[rng.gen_range(0..FIRST_NAMES.len())];
-            let title = ARCHER_TITLES[rng.gen_range(0..ARCHER_TITLES.len())];
+            let first = FIRST_NAMES[rng.random_range(0..FIRST_NAMES.len())];
+            let title = ARCHER_TITLES[rng.random_range(0..ARCHER_TITLES.len())];
             format!("{} {}", first, title)
         }
+        "cleric" => {
+            let first = FIRST_NAMES[rng.random_range(0..FIRST_NAMES.len())];
+            format!("{} the Cleric", first)
+        }
         "goblin" => {
-            let prefix = GOBLIN_PREFIXES[rng.gen_range(0..GOBLIN_PREFIXES.len())];
-            let suffix = GOBLIN_SUFFIXES[rng.gen_range(0..GOBLIN_SUFFIXES.len())];
+            let prefix = GOBLIN_PREFIXES[rng.random_range(0..GOBLIN_PREFIXES.len())];
+            let suffix = GOBLIN_SUFFIXES[rng.random_range(0..GOBLIN_SUFFIXES.len())];
             format!("{}{}", prefix, suffix)
         }
         "skeleton" => {
-            let first = FIRST_NAMES[rng.gen_range(0..FIRST_NAMES.len())];
-            let last = LAST_NAMES[rng.gen_range(0..LAST_NAMES.len())];
+            let first = FIRST_NAMES[rng.random_range(0..FIRST_NAMES.len())];
+            let last = LAST_NAMES[rng.random_range(0..LAST_NAMES.len())];
             format!("Skeletal {} {}", first, last)
         }
         "mushroom" => {
-            let mushroom_type = MUSHROOM_TYPES[rng.gen_range(0..MUSHROOM_TYPES.len())];
+            let mushroom_type = MUSHROOM_TYPES[rng.random_range(0..MUSHROOM_TYPES.len())];
             format!("{} Mushroom", mushroom_type)
         }
+        "mushroom_shaman" => {
+            let mushroom_type = MUSHROOM_TYPES[rng.random_range(0..MUSHROOM_TYPES.len())];
+            format!("{} Shaman", mushroom_type)
+        }
         "eyebeast" => {
-            let first = FIRST_NAMES[rng.gen_range(0..FIRST_NAMES.len())];
+            let first = FIRST_NAMES[rng.random_range(0..FIRST_NAMES.len())];
             format!("{} the Watcher", first)
         }
-        "chicken" => CHICKEN_NAMES[rng.gen_range(0..CHICKEN_NAMES.len())].to_string(),
+        "chicken" => CHICKEN_NAMES[rng.random_range(0..CHICKEN_NAMES.len())].to_string(),
         "cat" => {
-            let first = FIRST_NAMES[rng.gen_range(0..FIRST_NAMES.len())];
+            let first = FIRST_NAMES[rng.random_range(0..FIRST_NAMES.len())];
             format!("{} the Cat", first)
         }
         _ => {
             // Default: generate a full fantasy name
-            let first = FIRST_NAMES[rng.gen_range(0..FIRST_NAMES.len())];
-            let last = LAST_NAMES[rng.gen_range(0..LAST_NAMES.len())];
+            let first = FIRST_NAMES[rng.random_range(0..FIRST_NAMES.len())];
+            let last = LAST_NAMES[rng.random_range(0..LAST_NAMES.len())];
             format!("{} {}", first, last)
         }
     }
@@ -1,5086 +1,2643 @@
-use bitflags::bitflags;
-use crossbeam_queue::SegQueue;
-use dashmap::DashMap;
-use godot::classes::{AnimatedSprite2D, Control, Node2D, PackedScene};
+use bevy::math::Vec2;
+use godot::classes::Node2D;
 use godot::prelude::*;
-use parking_lot::RwLock;
-use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
 
-// Import the animation module
-use crate::animation::EffectPool;
+use crate::npc_node_layer::NpcNodeLayer;
+use crate::simulation::log::{set_log_sink, LogLevel};
+use crate::simulation::{
+    bytes_to_hex, CombatEvent, DamageConfig, Emotion, FactionEvent, FactionId, Formation,
+    HungerEvent, InputRecording, ManualClock, NPCDataWarehouse, NPCState, NPCStaticState,
+    NpcFilter, PoolOverflow, Relation, SimulationRunner, SquadEvent, StatusEffectEvent,
+    SystemClock, UlidBytes, DEFAULT_TICK_HZ,
+};
+
+/// Forward simulation log lines to the Godot console
+fn godot_log_sink(level: LogLevel, message: &str) {
+    match level {
+        LogLevel::Info => godot_print!("{}", message),
+        LogLevel::Warn => godot_warn!("{}", message),
+        LogLevel::Error => godot_error!("{}", message),
+    }
+}
+
+/// Helper: Convert PackedByteArray to raw ULID bytes for component map keys
+/// This is the FFI boundary optimization - GDScript passes raw bytes instead of strings
+fn packed_bytes_to_ulid(bytes: &PackedByteArray) -> Result<[u8; 16], String> {
+    UlidBytes::from_bytes(bytes.as_slice()).map(|ulid| *ulid.as_bytes())
+}
+
+/// Helper: Build a query filter from GDScript arguments
+/// An empty npc_type / faction matches every type / faction; masks are
+/// NPCState / NPCStaticState bits
+fn npc_filter(
+    warehouse: &NPCDataWarehouse,
+    npc_type: &GString,
+    faction: &GString,
+    state_mask: i32,
+    static_mask: i32,
+) -> Result<NpcFilter, String> {
+    let faction = if faction.is_empty() {
+        None
+    } else {
+        Some(warehouse.faction_id(&faction.to_string())?)
+    };
+    Ok(NpcFilter {
+        npc_type: (!npc_type.is_empty()).then(|| npc_type.to_string()),
+        faction,
+        state: NPCState::from_bits_truncate(state_mask as u32),
+        static_state: NPCStaticState::from_bits_truncate(static_mask as u32),
+    })
+}
 
-// ============================================================================
-// ULID CONVERSION HELPERS
-// ============================================================================
+/// Helper: ULID list as an Array of 16-byte PackedByteArrays
+fn ulid_array(ulids: &[[u8; 16]]) -> Array<PackedByteArray> {
+    ulids
+        .iter()
+        .map(|ulid| PackedByteArray::from(&ulid[..]))
+        .collect()
+}
 
-/// Convert byte ULID to hex string (32 chars)
-fn bytes_to_hex(bytes: &[u8; 16]) -> String {
-    format!("{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
-        bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
-        bytes[8], bytes[9], bytes[10], bytes[11], bytes[12], bytes[13], bytes[14], bytes[15])
+/// Godot FFI wrapper for NPCDataWarehouse
+///
+/// This will be registered as an autoload singleton in Godot.
+/// Replaces Dictionary-based pools in NPCManager.
+///
+/// Usage in GDScript:
+/// ```gdscript
+/// # Register a pool
+/// NPCDataWarehouse.register_pool("warrior", 10, "res://nodes/npc/warrior/warrior.tscn")
+///
+/// # Store NPC data
+/// NPCDataWarehouse.store_npc(ulid, npc_json_data)
+///
+/// # Get NPC data
+/// var npc_data = NPCDataWarehouse.get_npc(ulid)
+///
+/// # Check pool health
+/// var read_count = NPCDataWarehouse.read_store_count()
+/// var write_count = NPCDataWarehouse.write_store_count()
+/// ```
+#[derive(GodotClass)]
+#[class(base=Node)]
+pub struct GodotNPCDataWarehouse {
+    warehouse: Arc<NPCDataWarehouse>,
+    /// Headless Bevy world running the tick phases, inline or on its worker thread
+    simulation: SimulationRunner,
+    nodes: NpcNodeLayer,
+    /// Handle to the manual (simulation) clock while one is installed (None = wall clock)
+    manual_clock: Option<Arc<ManualClock>>,
+    base: Base<Node>,
 }
 
-/// Convert hex string (32 chars) to byte ULID
-fn hex_to_bytes(hex: &str) -> Result<[u8; 16], String> {
-    if hex.len() != 32 {
-        return Err(format!("Invalid hex length: {}, expected 32", hex.len()));
+#[godot_api]
+impl INode for GodotNPCDataWarehouse {
+    fn init(base: Base<Node>) -> Self {
+        godot_print!("=== NPCDataWarehouse Initializing ===");
+        set_log_sink(godot_log_sink);
+
+        // 1 second sync interval
+        let warehouse = Arc::new(NPCDataWarehouse::new(1000));
+        // The node layer below drains spawn/despawn/HP commands after each call
+        warehouse.set_record_node_commands(true);
+        // Simulation time starts at wall time and moves with the fixed steps
+        let manual_clock = warehouse.use_manual_clock(warehouse.get_current_time_ms());
+
+        Self {
+            simulation: SimulationRunner::new(warehouse.clone()),
+            warehouse,
+            nodes: NpcNodeLayer::new(),
+            manual_clock: Some(manual_clock),
+            base,
+        }
     }
 
-    let mut bytes = [0u8; 16];
-    for i in 0..16 {
-        let byte_str = &hex[i * 2..i * 2 + 2];
-        bytes[i] = u8::from_str_radix(byte_str, 16)
-            .map_err(|e| format!("Invalid hex at position {}: {}", i, e))?;
+    fn ready(&mut self) {
+        godot_print!("NPCDataWarehouse: Ready! High-performance NPC pool active.");
     }
-    Ok(bytes)
 }
 
-// ============================================================================
-// RUST NPC SPAWNER - Controls PackedScene instantiation and animation
-// ============================================================================
-
-/// NPC combat stats - single source of truth for all NPC stats
-/// Stored in ByteMap for efficient lookup by ULID
-/// Used during initialization, combat, and UI display
-#[derive(Clone, Copy, Serialize, Deserialize)]
-pub struct NPCCombatStats {
-    pub hp: f32,
-    pub max_hp: f32,
-    pub attack: f32,
-    pub defense: f32,
-    pub static_state: i32, // Combat type + faction bitflags (immutable)
-    pub emotional_state: i32,
-    pub mana: f32,
-    pub max_mana: f32,
-    pub energy: f32,
-    pub max_energy: f32,
-    #[serde(default = "default_hunger")]
-    pub hunger: f32,
-    #[serde(default = "default_max_hunger")]
-    pub max_hunger: f32,
-}
+#[godot_api]
+impl GodotNPCDataWarehouse {
+    /// Emitted when an NPC is stored in the warehouse
+    /// Parameters: (ulid: String, npc_type: String)
+    #[signal]
+    fn npc_stored(ulid: GString, npc_type: GString);
 
-// Default values for backwards compatibility with old saved data
-fn default_hunger() -> f32 {
-    100.0
-}
+    /// Emitted when an NPC is removed from the warehouse
+    /// Parameters: (ulid: String, npc_type: String)
+    #[signal]
+    fn npc_removed(ulid: GString, npc_type: GString);
 
-fn default_max_hunger() -> f32 {
-    100.0
-}
+    /// Emitted when AI state changes
+    /// Parameters: (ulid: String, ai_data: String)
+    #[signal]
+    fn ai_state_changed(ulid: GString, ai_data: GString);
 
-/// Rust-controlled NPC instance with direct scene node access
-/// This replaces GDScript pool management - Rust owns the NPCs
-struct RustNPC {
-    /// The root Node2D of the NPC scene
-    node: Gd<Node2D>,
-    /// Reference to the AnimatedSprite2D child for animation control
-    animated_sprite: Option<Gd<AnimatedSprite2D>>,
-    /// NPC type (warrior, archer, goblin, etc.)
-    npc_type: String,
-    /// Generated name for this NPC
-    name: String,
-    /// ULID for combat tracking (128-bit / 16 bytes)
-    ulid: [u8; 16],
-    /// Is this NPC currently active (spawned) or in pool (inactive)?
-    is_active: bool,
-    /// Stats for this NPC (extracted during instantiation)
-    stats: NPCCombatStats,
-}
+    /// Emitted when combat state changes
+    /// Parameters: (ulid: String, combat_data: String)
+    #[signal]
+    fn combat_state_changed(ulid: GString, combat_data: GString);
 
-impl RustNPC {
-    /// Generate a fantasy name for an NPC based on their type
-    fn generate_name(npc_type: &str) -> String {
-        crate::name_generator::generate_name(npc_type)
-    }
+    /// Emitted when a pool is registered
+    /// Parameters: (npc_type: String, max_size: int)
+    #[signal]
+    fn pool_registered(npc_type: GString, max_size: i32);
 
-    /// Create a new NPC by instantiating a PackedScene
-    fn from_scene(scene_path: &str, npc_type: &str, ulid: [u8; 16]) -> Option<Self> {
-        // Load the packed scene
-        let scene = match try_load::<PackedScene>(scene_path) {
-            Ok(s) => s,
-            Err(e) => {
-                godot_error!("[RUST NPC] Failed to load scene {}: {:?}", scene_path, e);
-                return None;
-            }
-        };
+    /// Emitted when an NPC dies (for death effects)
+    /// Parameters: (position_x: float, position_y: float)
+    #[signal]
+    fn npc_died(position_x: f32, position_y: f32);
 
-        // Instantiate as Node2D
-        let mut node = match scene.try_instantiate_as::<Node2D>() {
-            Some(n) => n,
-            None => {
-                godot_error!(
-                    "[RUST NPC] Failed to instantiate scene as Node2D: {}",
-                    scene_path
-                );
-                return None;
-            }
-        };
+    /// Emitted when an NPC starts an attack
+    /// Parameters: (attacker: PackedByteArray, target: PackedByteArray)
+    #[signal]
+    fn npc_attacked(attacker: PackedByteArray, target: PackedByteArray);
 
-        // Make invisible initially (will be made visible on spawn)
-        node.set_visible(false);
+    /// Emitted when a hit damages an NPC without killing it
+    /// Parameters: (attacker: PackedByteArray, target: PackedByteArray, amount: float, position: Vector2)
+    #[signal]
+    fn npc_damaged(
+        attacker: PackedByteArray,
+        target: PackedByteArray,
+        amount: f32,
+        position: Vector2,
+    );
+
+    /// Emitted when a hit kills an NPC (npc_died follows once the body is removed)
+    /// Parameters: (attacker: PackedByteArray, target: PackedByteArray, amount: float, position: Vector2)
+    #[signal]
+    fn npc_killed(
+        attacker: PackedByteArray,
+        target: PackedByteArray,
+        amount: f32,
+        position: Vector2,
+    );
+
+    /// Emitted when a healer's channeled heal lands
+    /// Parameters: (healer: PackedByteArray, target: PackedByteArray, amount: float, position: Vector2)
+    #[signal]
+    fn npc_healed(healer: PackedByteArray, target: PackedByteArray, amount: f32, position: Vector2);
 
-        // Find the AnimatedSprite2D child node
-        let animated_sprite = node.try_get_node_as::<AnimatedSprite2D>("AnimatedSprite2D");
+    /// Emitted when a ranged NPC fires a projectile
+    /// Parameters: (attacker, target, projectile: String, origin: Vector2, target_position: Vector2, speed: float)
+    #[signal]
+    fn projectile_fired(
+        attacker: PackedByteArray,
+        target: PackedByteArray,
+        projectile: GString,
+        origin: Vector2,
+        target_position: Vector2,
+        speed: f32,
+    );
+
+    /// Emitted when the relation between two factions changes
+    /// Parameters: (faction_a: String, faction_b: String, relation: String)
+    #[signal]
+    fn faction_relation_changed(faction_a: GString, faction_b: GString, relation: GString);
 
-        // Extract stats from the NPC by calling create_stats() static method
-        let stats = Self::extract_stats(&mut node, npc_type);
+    /// Emitted when an NPC is moved to another faction
+    /// Parameters: (ulid: PackedByteArray, faction: String)
+    #[signal]
+    fn npc_faction_changed(ulid: PackedByteArray, faction: GString);
 
-        // Generate a unique name for this NPC
-        let name = Self::generate_name(npc_type);
+    /// Emitted when a status effect is applied, refreshed or stacked
+    /// Parameters: (ulid: PackedByteArray, effect: String, stacks: int)
+    #[signal]
+    fn status_effect_applied(ulid: PackedByteArray, effect: GString, stacks: i32);
 
-        // Convert first 8 bytes to hex for logging
-        let ulid_hex = format!(
-            "{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
-            ulid[0], ulid[1], ulid[2], ulid[3], ulid[4], ulid[5], ulid[6], ulid[7]
-        );
-        godot_print!(
-            "[RUST NPC] Created {} '{}' with ULID: {}",
-            npc_type,
-            name,
-            ulid_hex
-        );
+    /// Emitted when a status effect ticks (hp_change is negative for damage)
+    /// Parameters: (ulid: PackedByteArray, effect: String, hp_change: float, hp: float)
+    #[signal]
+    fn status_effect_ticked(ulid: PackedByteArray, effect: GString, hp_change: f32, hp: f32);
 
-        Some(Self {
-            node,
-            animated_sprite,
-            npc_type: npc_type.to_string(),
-            name,
-            ulid,
-            is_active: false,
-            stats,
-        })
-    }
-
-    /// Extract stats from NPC scene - now uses hardcoded stats by type
-    /// GDScript create_stats() has been removed - Rust is the single source of truth
-    fn extract_stats(node: &mut Gd<Node2D>, npc_type: &str) -> NPCCombatStats {
-        // Get stats based on NPC type (hardcoded for now - could be config file later)
-        let stats = Self::get_stats_for_type(npc_type);
-        stats
-    }
-
-    /// Get stats based on NPC type (hardcoded for performance)
-    fn get_stats_for_type(npc_type: &str) -> NPCCombatStats {
-        match npc_type {
-            // Allies
-            "warrior" => NPCCombatStats {
-                hp: 200.0,
-                max_hp: 200.0,
-                attack: 25.0,
-                defense: 20.0,
-                static_state: (NPCStaticState::MELEE.bits() | NPCStaticState::ALLY.bits()) as i32,
-                emotional_state: 0, // Neutral
-                mana: 50.0,
-                max_mana: 50.0,
-                energy: 100.0,
-                max_energy: 100.0,
-                hunger: 100.0,
-                max_hunger: 100.0,
-            },
-            "archer" => NPCCombatStats {
-                hp: 150.0,
-                max_hp: 150.0,
-                attack: 12.0,
-                defense: 15.0,
-                static_state: (NPCStaticState::RANGED.bits() | NPCStaticState::ALLY.bits()) as i32,
-                emotional_state: 0,
-                mana: 30.0,
-                max_mana: 30.0,
-                energy: 120.0,
-                max_energy: 120.0,
-                hunger: 100.0,
-                max_hunger: 100.0,
-            },
-
-            // Monsters
-            "goblin" => NPCCombatStats {
-                hp: 100.0,
-                max_hp: 100.0,
-                attack: 15.0,
-                defense: 8.0,
-                static_state: (NPCStaticState::MELEE.bits() | NPCStaticState::MONSTER.bits())
-                    as i32,
-                emotional_state: 0,
-                mana: 0.0,
-                max_mana: 0.0,
-                energy: 80.0,
-                max_energy: 80.0,
-                hunger: 100.0,
-                max_hunger: 100.0,
-            },
-            "skeleton" => NPCCombatStats {
-                hp: 120.0,
-                max_hp: 120.0,
-                attack: 18.0,
-                defense: 10.0,
-                static_state: (NPCStaticState::MELEE.bits() | NPCStaticState::MONSTER.bits())
-                    as i32,
-                emotional_state: 0,
-                mana: 0.0,
-                max_mana: 0.0,
-                energy: 70.0,
-                max_energy: 70.0,
-                hunger: 100.0,
-                max_hunger: 100.0,
-            },
-            "mushroom" => NPCCombatStats {
-                hp: 80.0,
-                max_hp: 80.0,
-                attack: 10.0,
-                defense: 5.0,
-                static_state: (NPCStaticState::MELEE.bits() | NPCStaticState::MONSTER.bits())
-                    as i32,
-                emotional_state: 0,
-                mana: 20.0,
-                max_mana: 20.0,
-                energy: 60.0,
-                max_energy: 60.0,
-                hunger: 100.0,
-                max_hunger: 100.0,
-            },
-            "eyebeast" => NPCCombatStats {
-                hp: 150.0,
-                max_hp: 150.0,
-                attack: 20.0,
-                defense: 12.0,
-                static_state: (NPCStaticState::RANGED.bits() | NPCStaticState::MONSTER.bits())
-                    as i32,
-                emotional_state: 0,
-                mana: 100.0,
-                max_mana: 100.0,
-                energy: 90.0,
-                max_energy: 90.0,
-                hunger: 100.0,
-                max_hunger: 100.0,
-            },
-
-            // Passive
-            "chicken" => NPCCombatStats {
-                hp: 1000.0,
-                max_hp: 1000.0,
-                attack: 0.0,
-                defense: 2.0,
-                static_state: NPCStaticState::PASSIVE.bits() as i32,
-                emotional_state: 0,
-                mana: 0.0,
-                max_mana: 0.0,
-                energy: 50.0,
-                max_energy: 50.0,
-                hunger: 100.0,
-                max_hunger: 100.0,
-            },
-            "cat" => NPCCombatStats {
-                hp: 100.0,
-                max_hp: 100.0,
-                attack: 5.0,
-                defense: 5.0,
-                static_state: NPCStaticState::PASSIVE.bits() as i32,
-                emotional_state: 0,
-                mana: 0.0,
-                max_mana: 0.0,
-                energy: 80.0,
-                max_energy: 80.0,
-                hunger: 100.0,
-                max_hunger: 100.0,
-            },
-
-            // Default to basic stats if unknown type
-            _ => NPCCombatStats {
-                hp: 100.0,
-                max_hp: 100.0,
-                attack: 10.0,
-                defense: 5.0,
-                static_state: NPCStaticState::PASSIVE.bits() as i32,
-                emotional_state: 0,
-                mana: 0.0,
-                max_mana: 0.0,
-                energy: 100.0,
-                max_energy: 100.0,
-                hunger: 100.0,
-                max_hunger: 100.0,
-            },
-        }
-    }
+    /// Emitted when a status effect runs out or is removed
+    /// Parameters: (ulid: PackedByteArray, effect: String)
+    #[signal]
+    fn status_effect_expired(ulid: PackedByteArray, effect: GString);
 
-    /// Get static state flags based on NPC type
-    fn get_static_state_for_type(npc_type: &str) -> i32 {
-        match npc_type {
-            // Allies (MELEE or RANGED + ALLY)
-            "warrior" => NPCStaticState::MELEE.bits() as i32 | NPCStaticState::ALLY.bits() as i32,
-            "archer" => NPCStaticState::RANGED.bits() as i32 | NPCStaticState::ALLY.bits() as i32,
+    /// Emitted when an NPC's mana or energy changes (spent or regenerated)
+    /// Parameters: (ulid: PackedByteArray, mana: float, max_mana: float, energy: float, max_energy: float)
+    #[signal]
+    fn npc_resources_changed(
+        ulid: PackedByteArray,
+        mana: f32,
+        max_mana: f32,
+        energy: f32,
+        max_energy: f32,
+    );
+
+    /// Emitted when an NPC's hunger crosses into another level ("fed", "hungry", "starving")
+    /// Parameters: (ulid: PackedByteArray, level: String, hunger: float, max_hunger: float)
+    #[signal]
+    fn npc_hunger_changed(ulid: PackedByteArray, level: GString, hunger: f32, max_hunger: f32);
 
-            // Monsters (various combat types + MONSTER)
-            "goblin" => NPCStaticState::MELEE.bits() as i32 | NPCStaticState::MONSTER.bits() as i32,
-            "skeleton" => {
-                NPCStaticState::MELEE.bits() as i32 | NPCStaticState::MONSTER.bits() as i32
-            }
-            "mushroom" => {
-                NPCStaticState::RANGED.bits() as i32 | NPCStaticState::MONSTER.bits() as i32
-            }
-            "eyebeast" => {
-                NPCStaticState::MAGIC.bits() as i32 | NPCStaticState::MONSTER.bits() as i32
-            }
+    /// Emitted when the kingdom's food stockpile changes
+    /// Parameters: (food: int)
+    #[signal]
+    fn food_stockpile_changed(food: i64);
+
+    /// Emitted when an NPC's emotion changes
+    /// ("neutral", "happy", "enraged", "frightened", "sad", "weary")
+    /// Parameters: (ulid: PackedByteArray, emotion: String, previous: String)
+    #[signal]
+    fn npc_emotion_changed(ulid: PackedByteArray, emotion: GString, previous: GString);
 
-            // Passive
-            "chicken" => NPCStaticState::PASSIVE.bits() as i32,
-            "cat" => NPCStaticState::PASSIVE.bits() as i32,
+    /// Emitted when a squad reaches the point it was ordered to
+    /// Parameters: (squad: String)
+    #[signal]
+    fn squad_arrived(squad: GString);
 
-            // Default to PASSIVE if unknown
-            _ => NPCStaticState::PASSIVE.bits() as i32,
-        }
-    }
+    /// Emitted when a squad's leader leaves and the next member takes over
+    /// Parameters: (squad: String, leader: PackedByteArray)
+    #[signal]
+    fn squad_leader_changed(squad: GString, leader: PackedByteArray);
 
-    /// Activate this NPC (spawn it into the world)
-    fn activate(&mut self, position: Vector2) {
-        self.is_active = true;
-        self.node.set_visible(true);
-        // Use local position (relative to parent Layer4Objects) not global
-        self.node.set_position(position);
+    /// Emitted when a squad is disbanded or loses its last member
+    /// Parameters: (squad: String)
+    #[signal]
+    fn squad_disbanded(squad: GString);
 
-        // Start with idle animation when spawning
-        if let Some(ref sprite) = self.animated_sprite {
-            let mut sprite_mut = sprite.clone();
-            sprite_mut.set_animation(&StringName::from("idle"));
-            sprite_mut.play();
-            godot_print!("[RUST NPC] Set animation to 'idle' and playing");
-        } else {
-            godot_warn!(
-                "[RUST NPC] No AnimatedSprite2D found for {}!",
-                self.npc_type
-            );
-        }
+    /// Emitted when sync completes
+    /// Parameters: (synced_count: int)
+    #[signal]
+    fn sync_completed(synced_count: i32);
 
-        godot_print!(
-            "[RUST NPC] Activated {} at local position {:?}, visible={}",
-            self.npc_type,
-            position,
-            self.node.is_visible()
+    // ===== Pool Management Methods =====
+    /// Register (or update) an NPC pool - max_size caps the nodes and the
+    /// NPCs alive of that type. Register before initialize_npc_pool to size it
+    /// Usage: NPCDataWarehouse.register_pool("warrior", 10, "res://nodes/npc/warrior/warrior.tscn")
+    #[func]
+    pub fn register_pool(&mut self, npc_type: GString, max_size: i32, scene_path: GString) {
+        {
+            let _step = self.warehouse.hold_steps();
+            self.warehouse
+                .register_pool(&npc_type.to_string(), max_size, &scene_path.to_string());
+        }
+        // Emit signal
+        self.base_mut().emit_signal(
+            "pool_registered",
+            &[npc_type.to_variant(), max_size.to_variant()],
         );
     }
 
-    /// Deactivate this NPC (return to pool)
-    fn deactivate(&mut self) {
-        self.is_active = false;
-        self.node.set_visible(false);
+    /// What happens to spawns when a pool is at capacity: "reject" or "queue"
+    /// (queued spawns run as soon as an NPC of that type is despawned)
+    /// Returns false for an unknown pool or policy
+    /// Usage: NPCDataWarehouse.set_pool_overflow("goblin", "queue")
+    #[func]
+    pub fn set_pool_overflow(&self, npc_type: GString, policy: GString) -> bool {
+        let _step = self.warehouse.hold_steps();
+        let Some(overflow) = PoolOverflow::parse(&policy.to_string()) else {
+            godot_error!(
+                "NPCDataWarehouse: Unknown pool overflow policy '{}' (use reject or queue)",
+                policy
+            );
+            return false;
+        };
+        self.warehouse
+            .set_pool_overflow(&npc_type.to_string(), overflow)
+    }
+
+    /// Warm/idle policy of a pool: keep min_warm slots ready (instanced a few
+    /// per frame) and, once more than max_idle slots have sat idle for
+    /// idle_timeout_ms, free the extra nodes. A negative max_idle never shrinks
+    /// Returns false for an unknown pool
+    /// Usage: NPCDataWarehouse.set_pool_policy("goblin", 8, 12, 30000)
+    #[func]
+    pub fn set_pool_policy(
+        &self,
+        npc_type: GString,
+        min_warm: i32,
+        max_idle: i32,
+        idle_timeout_ms: i64,
+    ) -> bool {
+        let _step = self.warehouse.hold_steps();
+        let max_idle = (max_idle >= 0).then_some(max_idle);
+        self.warehouse.set_pool_policy(
+            &npc_type.to_string(),
+            min_warm,
+            max_idle,
+            idle_timeout_ms.max(0) as u64,
+        )
     }
 
-    /// Reset NPC for reuse (called when returning to pool)
-    /// Resets stats to max but keeps ULID and name
-    fn reset(&mut self) {
-        // Note: ULID and name are preserved for pool reuse
-        // Only reset dynamic state and stats
-        self.is_active = false;
-        self.node.set_visible(false);
+    /// Cap the pooled nodes of every type together (0 = no budget)
+    /// Usage: NPCDataWarehouse.set_node_budget(120)
+    #[func]
+    pub fn set_node_budget(&self, budget: i32) {
+        let _step = self.warehouse.hold_steps();
+        self.warehouse.set_node_budget(budget.max(0) as usize);
+    }
 
-        // Stats will be reset in the ByteMaps by the warehouse
-        // The RustNPC just stores the template stats, actual HP is in ByteMaps
+    /// How many pooled nodes are instanced per frame while pools warm up
+    /// Usage: NPCDataWarehouse.set_pool_prewarm_rate(2)
+    #[func]
+    pub fn set_pool_prewarm_rate(&self, per_frame: i32) {
+        self.nodes.set_prewarm_per_frame(per_frame.max(0) as usize);
     }
 
-    /// Play an animation
-    fn play_animation(&mut self, animation_name: &str) {
-        if let Some(ref mut sprite) = self.animated_sprite {
-            sprite.set_animation(animation_name);
-            sprite.play();
+    /// Pool definition with live counts
+    /// Keys: npc_type, max_size, scene_path, overflow, min_warm, max_idle
+    /// (-1 = never shrink), idle_timeout_ms, current_active, available, queued
+    /// Returns an empty Dictionary for an unknown pool
+    /// Usage: var pool = NPCDataWarehouse.get_pool("goblin")
+    #[func]
+    pub fn get_pool(&self, npc_type: GString) -> Dictionary {
+        let mut dict = Dictionary::new();
+        if let Some(pool) = self.warehouse.get_pool(&npc_type.to_string()) {
+            dict.set("npc_type", pool.npc_type.as_str());
+            dict.set("max_size", pool.max_size);
+            dict.set("scene_path", pool.scene_path.as_str());
+            dict.set("overflow", pool.overflow.as_str());
+            dict.set("min_warm", pool.min_warm);
+            dict.set("max_idle", pool.max_idle.unwrap_or(-1));
+            dict.set("idle_timeout_ms", pool.idle_timeout_ms as i64);
+            dict.set("current_active", pool.current_active);
+            dict.set("available", pool.available);
+            dict.set("queued", pool.queued);
         }
+        dict
     }
 
-    /// Set sprite flip (for facing direction)
-    fn set_flip_h(&mut self, flip: bool) {
-        if let Some(ref mut sprite) = self.animated_sprite {
-            sprite.set_flip_h(flip);
+    /// Statistics for every pool and the node budget
+    /// Keys: node_budget, total_slots, active_nodes, idle_nodes, nodes_instanced,
+    /// nodes_freed, prewarm_per_frame, pools (npc_type -> get_pool Dictionary)
+    /// Usage: var stats = NPCDataWarehouse.get_pool_stats()
+    #[func]
+    pub fn get_pool_stats(&self) -> Dictionary {
+        let mut dict = self.nodes.pool_stats();
+        dict.set("node_budget", self.warehouse.node_budget() as i64);
+        dict.set("total_slots", self.warehouse.total_pool_slots() as i64);
+        let mut pools = Dictionary::new();
+        for npc_type in self.warehouse.pool_names() {
+            pools.set(npc_type.as_str(), self.get_pool(GString::from(&npc_type)));
         }
+        dict.set("pools", pools);
+        dict
     }
-}
 
-// ============================================================================
-// NPCState bitflags - BEHAVIORAL states only (dynamic, changes during gameplay)
-bitflags! {
-    /// NPC behavioral state flags - these change dynamically during gameplay
-    ///
-    /// Matches the GDScript NPCState enum in npc_manager.gd
-    /// Each NPC can have multiple behavioral states combined via bitwise OR
-    ///
-    /// Example: IDLE (idle behavior)
-    ///          WALKING | COMBAT (moving during combat)
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-    pub struct NPCState: u32 {
-        // Behavioral States (bits 0-5) - Change during gameplay (SIMPLIFIED)
-        const IDLE       = 1 << 0;   // 1:  Idle (not moving, not attacking)
-        const WALKING    = 1 << 1;   // 2:  Walking/moving
-        const ATTACKING  = 1 << 2;   // 4:  Currently attacking (animation playing)
-        const COMBAT     = 1 << 3;   // 8:  Engaged in combat
-        const DAMAGED    = 1 << 4;   // 16: Taking damage - plays hurt/damage animation
-        const DEAD       = 1 << 5;   // 32: Dead - plays death animation
+    // ===== Rust NPC Pool System Methods =====
+
+    /// Initialize an NPC pool (exposed to GDScript)
+    /// Call this on game start for each NPC type
+    /// An empty scene_path uses the archetype's scene_path
+    /// Never instances more nodes than the pool's max_size
+    #[func]
+    pub fn initialize_npc_pool(&self, npc_type: GString, pool_size: i32, scene_path: GString) {
+        let _step = self.warehouse.hold_steps();
+        let npc_type = npc_type.to_string();
+        let mut scene_path = scene_path.to_string();
+        if scene_path.is_empty() {
+            match self.warehouse.get_archetype(&npc_type) {
+                Some(archetype) if !archetype.scene_path.is_empty() => {
+                    scene_path = archetype.scene_path;
+                }
+                _ => {
+                    godot_error!(
+                        "NPCDataWarehouse: No scene path for '{}' (pass one or set it in the archetype)",
+                        npc_type
+                    );
+                    return;
+                }
+            }
+        }
+
+        // First initialization sizes the pool; later ones only fill it up to max_size
+        if self.warehouse.get_pool(&npc_type).is_none() {
+            self.warehouse
+                .register_pool(&npc_type, pool_size.max(0), &scene_path);
+        }
+        let requested = pool_size.max(0) as usize;
+        let pool_size = self.warehouse.pool_room(&npc_type, requested);
+        if pool_size < requested {
+            godot_warn!(
+                "NPCDataWarehouse: Pool '{}' is at capacity - creating {} of {} nodes",
+                npc_type,
+                pool_size,
+                requested
+            );
+        }
+        self.nodes
+            .initialize_npc_pool(&self.warehouse, &npc_type, pool_size, &scene_path);
     }
-}
 
-// Implement Send + Sync for thread safety
-unsafe impl Send for NPCState {}
-unsafe impl Sync for NPCState {}
-
-// NPCStaticState bitflags - IMMUTABLE properties set once at spawn
-bitflags! {
-    /// Static/immutable NPC properties (set once, never change during gameplay)
-    ///
-    /// These flags determine the NPC's core identity and should NEVER be modified.
-    /// Matches the GDScript NPCStaticState enum in npc_manager.gd
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-    pub struct NPCStaticState: u32 {
-        // Combat Types (0-3) - Each NPC has exactly ONE combat type (immutable)
-        const MELEE   = 1 << 0;  // 1:   Melee attacker (warriors, knights, goblins)
-        const RANGED  = 1 << 1;  // 2:   Ranged attacker (archers, crossbowmen)
-        const MAGIC   = 1 << 2;  // 4:   Magic attacker (mages, wizards)
-        const HEALER  = 1 << 3;  // 8:   Healer/support (clerics, druids)
-
-        // Factions (4-6) - Determines who can attack whom (immutable)
-        const ALLY    = 1 << 4;  // 16:  Player's allies - don't attack each other
-        const MONSTER = 1 << 5;  // 32:  Hostile creatures - attack allies
-        const PASSIVE = 1 << 6;  // 64:  Doesn't attack/do damage (chickens, critters)
+    // ===== Archetype Methods =====
+
+    /// Load an archetype definitions file (e.g. "res://data/npc_archetypes.json")
+    /// Returns how many archetypes were registered, or -1 if the file could not be read
+    #[func]
+    pub fn load_archetypes(&self, path: GString) -> i32 {
+        let json = godot::classes::FileAccess::get_file_as_string(&path);
+        if json.is_empty() {
+            godot_error!("NPCDataWarehouse: Could not read archetype file '{}'", path);
+            return -1;
+        }
+        let _step = self.warehouse.hold_steps();
+        match self.warehouse.load_archetypes_json(&json.to_string()) {
+            Ok(registered) => registered as i32,
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {} ({})", e, path);
+                -1
+            }
+        }
     }
-}
 
-// Thread-safe for HolyMap
-unsafe impl Send for NPCStaticState {}
-unsafe impl Sync for NPCStaticState {}
+    /// Register (or replace) a single archetype from its JSON object
+    #[func]
+    pub fn register_archetype(&self, archetype_json: GString) -> bool {
+        let _step = self.warehouse.hold_steps();
+        match self
+            .warehouse
+            .register_archetype_json(&archetype_json.to_string())
+        {
+            Ok(()) => true,
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                false
+            }
+        }
+    }
 
-impl NPCStaticState {
-    /// Convert to i32 for GDScript interop
-    pub fn to_i32(self) -> i32 {
-        self.bits() as i32
+    /// Get an archetype definition as JSON (empty string if unknown)
+    #[func]
+    pub fn get_archetype_json(&self, npc_type: GString) -> GString {
+        self.warehouse
+            .get_archetype(&npc_type.to_string())
+            .and_then(|archetype| serde_json::to_string(&archetype).ok())
+            .map(|json| GString::from(&json))
+            .unwrap_or_default()
     }
 
-    /// Convert from i32 (from GDScript)
-    pub fn from_i32(value: i32) -> Option<Self> {
-        NPCStaticState::from_bits(value as u32)
+    /// Get the names of every registered archetype
+    #[func]
+    pub fn get_archetype_names(&self) -> PackedStringArray {
+        self.warehouse
+            .archetype_names()
+            .iter()
+            .map(GString::from)
+            .collect()
     }
 
-    /// Convert from i32 with fallback to ALLY
-    pub fn from_i32_or_ally(value: i32) -> Self {
-        NPCStaticState::from_bits(value as u32).unwrap_or(NPCStaticState::ALLY)
+    /// Set the scene container where NPCs will be added as children
+    #[func]
+    pub fn set_scene_container(&self, container: Gd<Node2D>) {
+        self.nodes.set_scene_container(container);
+        // NPCs can be shown now - let the core start spawning
+        self.warehouse.set_spawning_enabled(true);
     }
-}
 
-// Helper methods for NPCState
-impl NPCState {
-    /// Convert to i32 for GDScript interop (Godot uses int for enums)
-    pub fn to_i32(self) -> i32 {
-        self.bits() as i32
+    /// Apply pending core commands to the scene, warm pools below their
+    /// min_warm and mirror the latest frame onto nodes
+    fn sync_nodes(&self) {
+        self.nodes.apply_commands(&self.warehouse);
+        self.nodes.warm_pools(&self.warehouse);
+        let frame = self.simulation.frame();
+        self.nodes.sync_positions(&frame);
+        self.nodes.sync_animations(&frame);
     }
 
-    /// Convert from i32 (from GDScript)
-    pub fn from_i32(value: i32) -> Option<Self> {
-        NPCState::from_bits(value as u32)
+    /// Take every combat event queued by the simulation since the last call
+    fn drain_combat_events(&self) -> Vec<CombatEvent> {
+        std::iter::from_fn(|| self.warehouse.pop_combat_event()).collect()
     }
 
-    /// Convert from i32 (from GDScript) with fallback to IDLE
-    pub fn from_i32_or_idle(value: i32) -> Self {
-        NPCState::from_bits(value as u32).unwrap_or(NPCState::IDLE)
+    /// Run `f` with the simulation thread stopped (restarted afterwards at the same rate)
+    /// Keeps host-side batch changes (snapshots, starting and stopping a
+    /// recording) from racing a running tick
+    fn with_simulation_paused<R>(&mut self, f: impl FnOnce(&Self) -> R) -> R {
+        let tick_hz = self.simulation.tick_hz();
+        self.simulation.stop_thread();
+        let result = f(self);
+        self.simulation.publish_frame();
+        if let Some(tick_hz) = tick_hz {
+            self.simulation.start_thread(tick_hz);
+        }
+        result
     }
-}
 
-// World bounds constants - NPCs stay within these coordinates during combat
-// These bounds match the typical viewport size (1280x720) with margins for parallax
-const WORLD_MIN_X: f32 = 50.0; // Left edge with margin
-const WORLD_MAX_X: f32 = 1230.0; // Right edge with margin
-const WORLD_MIN_Y: f32 = 100.0; // Top of playable area
-const WORLD_MAX_Y: f32 = 650.0; // Below bottom of screen
-
-/// ULID wrapper for binary storage (16 bytes)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct UlidBytes([u8; 16]);
-
-impl UlidBytes {
-    /// Create from ulid crate's Ulid
-    pub fn from_ulid(ulid: ulid::Ulid) -> Self {
-        UlidBytes(ulid.to_bytes())
-    }
-
-    /// Create from raw bytes (16 bytes)
-    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
-        if bytes.len() != 16 {
-            return Err(format!(
-                "Invalid ULID length: expected 16 bytes, got {}",
-                bytes.len()
-            ));
+    /// Convert a combat event to a Dictionary (positions are global)
+    /// Keys: type, attacker, target, position, plus amount (damage/death/heal)
+    /// or projectile, origin, speed (projectile)
+    fn combat_event_to_dict(&self, event: &CombatEvent) -> Dictionary {
+        let mut dict = Dictionary::new();
+        dict.set("type", event.name());
+        dict.set("attacker", PackedByteArray::from(&event.attacker()[..]));
+        dict.set("target", PackedByteArray::from(&event.target()[..]));
+        dict.set("position", self.nodes.to_global(event.target_pos()));
+        match *event {
+            CombatEvent::Damage { amount, .. }
+            | CombatEvent::Death { amount, .. }
+            | CombatEvent::Heal { amount, .. } => {
+                dict.set("amount", amount);
+            }
+            CombatEvent::Projectile {
+                kind,
+                origin,
+                speed,
+                ..
+            } => {
+                dict.set("projectile", kind.as_str());
+                dict.set("origin", self.nodes.to_global(origin));
+                dict.set("speed", speed);
+            }
+            CombatEvent::Attack { .. } => {}
         }
-        let mut arr = [0u8; 16];
-        arr.copy_from_slice(bytes);
-        Ok(UlidBytes(arr))
+        dict
     }
 
-    /// Convert to ulid crate's Ulid
-    pub fn to_ulid(&self) -> ulid::Ulid {
-        ulid::Ulid::from_bytes(self.0)
+    /// Emit the per-event signals (npc_attacked, npc_damaged, npc_killed, npc_healed,
+    /// projectile_fired)
+    fn emit_combat_signals(&mut self, events: &[CombatEvent]) {
+        for event in events {
+            let attacker = PackedByteArray::from(&event.attacker()[..]).to_variant();
+            let target = PackedByteArray::from(&event.target()[..]).to_variant();
+            let position = self.nodes.to_global(event.target_pos()).to_variant();
+            match *event {
+                CombatEvent::Attack { .. } => {
+                    self.base_mut()
+                        .emit_signal("npc_attacked", &[attacker, target]);
+                }
+                CombatEvent::Damage { amount, .. } => {
+                    self.base_mut().emit_signal(
+                        "npc_damaged",
+                        &[attacker, target, amount.to_variant(), position],
+                    );
+                }
+                CombatEvent::Death { amount, .. } => {
+                    self.base_mut().emit_signal(
+                        "npc_killed",
+                        &[attacker, target, amount.to_variant(), position],
+                    );
+                }
+                CombatEvent::Heal { amount, .. } => {
+                    self.base_mut().emit_signal(
+                        "npc_healed",
+                        &[attacker, target, amount.to_variant(), position],
+                    );
+                }
+                CombatEvent::Projectile {
+                    kind,
+                    origin,
+                    speed,
+                    ..
+                } => {
+                    let origin = self.nodes.to_global(origin).to_variant();
+                    self.base_mut().emit_signal(
+                        "projectile_fired",
+                        &[
+                            attacker,
+                            target,
+                            GString::from(kind.as_str()).to_variant(),
+                            origin,
+                            position,
+                            speed.to_variant(),
+                        ],
+                    );
+                }
+            }
+        }
     }
 
-    /// Convert to lowercase hex string (32 chars) for HolyMap keys
-    pub fn to_hex_string(&self) -> String {
-        self.0.iter().map(|b| format!("{:02x}", b)).collect()
+    /// Emit faction_relation_changed / npc_faction_changed for queued faction events
+    fn emit_faction_signals(&mut self) {
+        while let Some(event) = self.warehouse.pop_faction_event() {
+            match event {
+                FactionEvent::RelationChanged { a, b, relation } => {
+                    self.base_mut().emit_signal(
+                        "faction_relation_changed",
+                        &[
+                            GString::from(&a).to_variant(),
+                            GString::from(&b).to_variant(),
+                            GString::from(relation.as_str()).to_variant(),
+                        ],
+                    );
+                }
+                FactionEvent::NpcFactionChanged { ulid, faction } => {
+                    self.base_mut().emit_signal(
+                        "npc_faction_changed",
+                        &[
+                            PackedByteArray::from(&ulid[..]).to_variant(),
+                            GString::from(&faction).to_variant(),
+                        ],
+                    );
+                }
+            }
+        }
     }
 
-    /// Parse from hex string (from GDScript - legacy support)
-    pub fn from_hex_string(hex: &str) -> Result<Self, String> {
-        if hex.len() != 32 {
-            return Err(format!(
-                "Invalid hex ULID length: expected 32 chars, got {}",
-                hex.len()
-            ));
+    /// Emit status_effect_applied / _ticked / _expired for queued status events
+    fn emit_status_signals(&mut self) {
+        while let Some(event) = self.warehouse.pop_status_event() {
+            match event {
+                StatusEffectEvent::Applied {
+                    ulid,
+                    effect,
+                    stacks,
+                } => {
+                    self.base_mut().emit_signal(
+                        "status_effect_applied",
+                        &[
+                            PackedByteArray::from(&ulid[..]).to_variant(),
+                            GString::from(&effect).to_variant(),
+                            (stacks as i32).to_variant(),
+                        ],
+                    );
+                }
+                StatusEffectEvent::Ticked {
+                    ulid,
+                    effect,
+                    hp_change,
+                    hp,
+                } => {
+                    self.base_mut().emit_signal(
+                        "status_effect_ticked",
+                        &[
+                            PackedByteArray::from(&ulid[..]).to_variant(),
+                            GString::from(&effect).to_variant(),
+                            hp_change.to_variant(),
+                            hp.to_variant(),
+                        ],
+                    );
+                }
+                StatusEffectEvent::Expired { ulid, effect } => {
+                    self.base_mut().emit_signal(
+                        "status_effect_expired",
+                        &[
+                            PackedByteArray::from(&ulid[..]).to_variant(),
+                            GString::from(&effect).to_variant(),
+                        ],
+                    );
+                }
+            }
         }
+    }
 
-        let mut bytes = [0u8; 16];
-        for i in 0..16 {
-            let byte_str = &hex[i * 2..i * 2 + 2];
-            bytes[i] = u8::from_str_radix(byte_str, 16)
-                .map_err(|e| format!("Invalid hex at position {}: {}", i * 2, e))?;
+    /// Emit npc_resources_changed for queued mana/energy changes
+    fn emit_resource_signals(&mut self) {
+        while let Some(event) = self.warehouse.pop_resource_event() {
+            self.base_mut().emit_signal(
+                "npc_resources_changed",
+                &[
+                    PackedByteArray::from(&event.ulid[..]).to_variant(),
+                    event.mana.to_variant(),
+                    event.max_mana.to_variant(),
+                    event.energy.to_variant(),
+                    event.max_energy.to_variant(),
+                ],
+            );
         }
-        Ok(UlidBytes(bytes))
     }
 
-    /// Get raw bytes
-    pub fn as_bytes(&self) -> &[u8; 16] {
-        &self.0
+    /// Emit npc_hunger_changed / food_stockpile_changed for queued hunger events
+    fn emit_hunger_signals(&mut self) {
+        while let Some(event) = self.warehouse.pop_hunger_event() {
+            match event {
+                HungerEvent::Level {
+                    ulid,
+                    level,
+                    hunger,
+                    max_hunger,
+                } => {
+                    self.base_mut().emit_signal(
+                        "npc_hunger_changed",
+                        &[
+                            PackedByteArray::from(&ulid[..]).to_variant(),
+                            GString::from(level.as_str()).to_variant(),
+                            hunger.to_variant(),
+                            max_hunger.to_variant(),
+                        ],
+                    );
+                }
+                HungerEvent::Stockpile { food } => {
+                    self.base_mut()
+                        .emit_signal("food_stockpile_changed", &[(food as i64).to_variant()]);
+                }
+            }
+        }
     }
-}
 
-// Thread-safe for HolyMap
-unsafe impl Send for UlidBytes {}
-unsafe impl Sync for UlidBytes {}
+    /// Emit npc_emotion_changed for queued emotion changes
+    fn emit_emotion_signals(&mut self) {
+        while let Some(event) = self.warehouse.pop_emotion_event() {
+            self.base_mut().emit_signal(
+                "npc_emotion_changed",
+                &[
+                    PackedByteArray::from(&event.ulid[..]).to_variant(),
+                    GString::from(event.emotion.as_str()).to_variant(),
+                    GString::from(event.previous.as_str()).to_variant(),
+                ],
+            );
+        }
+    }
 
-/// Helper: Convert PackedByteArray to hex string for HolyMap keys
-/// This is the FFI boundary optimization - GDScript passes raw bytes instead of strings
-fn packed_bytes_to_hex(bytes: &PackedByteArray) -> Result<String, String> {
-    let slice = bytes.as_slice();
-    if slice.len() != 16 {
-        return Err(format!(
-            "Invalid ULID length: expected 16 bytes, got {}",
-            slice.len()
-        ));
+    /// Emit squad_arrived / squad_leader_changed / squad_disbanded for queued squad events
+    fn emit_squad_signals(&mut self) {
+        while let Some(event) = self.warehouse.pop_squad_event() {
+            match event {
+                SquadEvent::Arrived { name } => {
+                    self.base_mut()
+                        .emit_signal("squad_arrived", &[GString::from(&name).to_variant()]);
+                }
+                SquadEvent::LeaderChanged { name, leader } => {
+                    self.base_mut().emit_signal(
+                        "squad_leader_changed",
+                        &[
+                            GString::from(&name).to_variant(),
+                            PackedByteArray::from(&leader[..]).to_variant(),
+                        ],
+                    );
+                }
+                SquadEvent::Disbanded { name } => {
+                    self.base_mut()
+                        .emit_signal("squad_disbanded", &[GString::from(&name).to_variant()]);
+                }
+            }
+        }
     }
 
-    Ok(slice.iter().map(|b| format!("{:02x}", b)).collect())
-}
+    /// Legacy JSON form of a batch of events
+    fn combat_events_to_json(events: &[CombatEvent]) -> Array<GString> {
+        let mut godot_array = Array::new();
+        for event in events {
+            godot_array.push(&GString::from(&event.to_json()));
+        }
+        godot_array
+    }
 
-/// NPCStats - Structured stat data matching GDScript NPCStats class
-///
-/// This struct mirrors the NPCStats Resource in GDScript and provides
-/// automatic JSON serialization via serde.
-///
-/// Matches: afk/nodes/npc/npc_stats.gd
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NPCStats {
-    /// Unique identifier (hex string format for JSON compatibility)
-    pub ulid: String,
+    /// Full tick shared by tick_combat / tick_combat_events
+    /// Runs the fixed steps due for `delta` real seconds inline - or, while the
+    /// simulation thread runs, just collects what it produced since the last call
+    fn run_full_tick(&mut self, delta: f32) -> Vec<CombatEvent> {
+        self.simulation.advance(delta);
+        self.collect_tick_results()
+    }
+
+    /// Apply what the simulation produced: node commands, the latest frame,
+    /// the event, faction, status effect, resource, hunger, emotion and squad
+    /// signals and npc_died for each removed body
+    fn collect_tick_results(&mut self) -> Vec<CombatEvent> {
+        let events = self.drain_combat_events();
+        self.sync_nodes();
+        self.emit_combat_signals(&events);
+        self.emit_faction_signals();
+        self.emit_status_signals();
+        self.emit_resource_signals();
+        self.emit_hunger_signals();
+        self.emit_emotion_signals();
+        self.emit_squad_signals();
 
-    /// NPC's generated name (e.g., "Aldric Ironwood")
-    pub npc_name: String,
+        // Emit npc_died signal for each death position (for GDScript to spawn effects)
+        // Simulated positions are container-local, the signal carries global positions
+        let signal_name = StringName::from("npc_died");
+        while let Some((x, y)) = self.warehouse.pop_death_position() {
+            let global = self.nodes.to_global(Vec2::new(x, y));
+            self.base_mut().emit_signal(
+                &signal_name,
+                &[global.x.to_variant(), global.y.to_variant()],
+            );
+        }
 
-    /// NPC type (e.g., "warrior", "archer")
-    pub npc_type: String,
-
-    /// Current health points
-    pub hp: f32,
-
-    /// Maximum health points
-    pub max_hp: f32,
-
-    /// Current mana
-    pub mana: f32,
-
-    /// Maximum mana
-    pub max_mana: f32,
-
-    /// Current energy/stamina
-    pub energy: f32,
-
-    /// Maximum energy/stamina
-    pub max_energy: f32,
-
-    /// Hunger level (0 = starving, 100 = full)
-    pub hunger: f32,
-
-    /// Physical attack power
-    pub attack: f32,
-
-    /// Physical defense/damage reduction
-    pub defense: f32,
-
-    /// Current emotional state (0-7, maps to Emotion enum)
-    pub emotion: i32,
-}
-
-impl NPCStats {
-    /// Create new NPCStats from JSON string
-    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
-        serde_json::from_str(json)
-    }
-
-    /// Serialize to JSON string
-    pub fn to_json(&self) -> Result<String, serde_json::Error> {
-        serde_json::to_string(self)
-    }
-}
-
-/// CombatEvent - Events generated by Rust combat system for GDScript to render
-///
-/// Rust determines all combat logic and outputs these events.
-/// GDScript subscribes to events and handles visual presentation only.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CombatEvent {
-    /// Event type: "attack", "damage", "death", "heal", etc.
-    pub event_type: String,
-
-    /// Attacker/source NPC ULID (as hex for GDScript compatibility)
-    pub attacker_ulid: String,
-
-    /// Target/victim NPC ULID (as hex for GDScript compatibility)
-    pub target_ulid: String,
-
-    /// Damage/heal amount
-    pub amount: f32,
-
-    /// Animation to play on attacker ("attack_melee", "attack_ranged", "cast_spell")
-    pub attacker_animation: String,
-
-    /// Animation to play on target ("hurt", "death", "heal")
-    pub target_animation: String,
-
-    /// Target position (x, y) for projectiles/effects
-    pub target_x: f32,
-    pub target_y: f32,
-}
-
-impl CombatEvent {
-    /// Serialize to JSON for GDScript
-    pub fn to_json(&self) -> String {
-        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
-    }
-}
-
-/// NPCDataWarehouse - High-performance NPC pool and state management
-///
-/// This is the Rust-based replacement for NPCManager's Dictionary-based pools.
-/// Uses HolyMap for lock-free reads and fast concurrent writes.
-///
-/// Key Design:
-/// - Lock-free reads for combat/AI queries (90%+ of operations)
-/// - Fast concurrent writes for spawn/despawn
-/// - WASM-safe with threading support
-/// - Migrates logic from GDScript to Rust for better performance
-///
-/// Storage Keys:
-/// - "pool:{npc_type}" → Pool definition (max size, scene path, etc.)
-/// - "active:{ulid}" → Active NPC data (state, position, etc.)
-/// - "ai:{ulid}" → AI behavior state
-/// - "combat:{ulid}" → Combat stats and state
-
-// Thread-safe String wrapper for HolyMap keys/values
-// SafeString and SafeValue removed - using String directly with DashMap
-
-/// Pool definition for an NPC type
-#[derive(Clone, Debug)]
-pub struct PoolDefinition {
-    pub npc_type: String,
-    pub max_size: i32,
-    pub scene_path: String,
-    pub current_active: i32,
-}
-
-impl PoolDefinition {
-    /// Serialize to JSON string for storage
-    pub fn to_json(&self) -> String {
-        format!(
-            r#"{{"npc_type":"{}","max_size":{},"scene_path":"{}","current_active":{}}}"#,
-            self.npc_type, self.max_size, self.scene_path, self.current_active
-        )
-    }
-
-    /// Deserialize from JSON string (basic implementation)
-    pub fn from_json(json: &str) -> Option<Self> {
-        // TODO: Proper JSON parsing - for now using simple parse
-        // This is a placeholder - in production use serde_json
-        Some(PoolDefinition {
-            npc_type: String::from("warrior"),
-            max_size: 10,
-            scene_path: String::from("res://nodes/npc/warrior/warrior.tscn"),
-            current_active: 0,
-        })
+        events
     }
-}
-
-/// Core NPC data warehouse
-pub struct NPCDataWarehouse {
-    /// Main storage using DashMap for concurrent access
-    storage: DashMap<String, String>,
-
-    /// Sync interval in milliseconds
-    sync_interval_ms: u64,
-
-    /// Combat event queue - lock-free MPMC queue for Rust → GDScript communication
-    combat_event_queue: Arc<SegQueue<CombatEvent>>,
-
-    /// Combat thread running flag
-    combat_thread_running: Arc<AtomicBool>,
-
-    /// Active NPCs in combat system - Set of ULIDs for iteration
-    active_combat_npcs: DashMap<[u8; 16], ()>,
-
-    /// Error tracking - prevents spam by logging each error type once per NPC
-    /// Key format: "error_type:ulid" -> "1"
-    error_log: DashMap<String, String>,
-
-    /// Spawn wave management - track last spawn time and counts
-    /// Rust manages ALL spawning (allies + monsters) with gradual ramp-up
-    last_spawn_time_ms: Arc<AtomicU64>,
-    last_ally_spawn_time_ms: Arc<AtomicU64>, // Separate timer for allies
-
-    /// Monster spawn configuration
-    spawn_interval_ms: u64, // Time between monster waves (default 10 seconds)
-    min_wave_size: i32,       // Minimum monsters per wave (8 monsters)
-    max_wave_size: i32,       // Maximum monsters per wave (8 monsters)
-    min_active_monsters: i32, // Spawn new wave when below this count (3 monsters)
-
-    /// Ally spawn configuration (warriors, archers) - 12 total allies max
-    ally_spawn_interval_ms: u64, // Time between ally spawns (3 seconds for gradual ramp-up)
-    max_warriors: i32, // Max warrior count (6 warriors)
-    max_archers: i32,  // Max archer count (6 archers)
-
-    /// Spawn tracking (defensive programming)
-    spawn_requests: Arc<AtomicU64>, // Total spawn requests sent
-    spawn_confirmations: Arc<AtomicU64>, // Total spawns confirmed by GDScript
-
-    /// Initial spawn flag (spawn minimal entities on first tick)
-    initial_spawn_done: Arc<AtomicBool>,
-
-    /// World bounds for clamping waypoints (set by GDScript from BackgroundManager)
-    /// These can be updated dynamically when background changes
-    world_min_x: Arc<std::sync::atomic::AtomicU32>, // Stored as f32 bits
-    world_max_x: Arc<std::sync::atomic::AtomicU32>,
-    world_min_y: Arc<std::sync::atomic::AtomicU32>,
-    world_max_y: Arc<std::sync::atomic::AtomicU32>,
-
-    // ============================================================================
-    // RUST NPC POOL SYSTEM - Replaces GDScript pool management
-    // ============================================================================
-    /// Active NPCs (currently spawned and visible)
-    /// Key: ULID bytes (Vec for HashMap compatibility) -> RustNPC instance
-    active_npc_pool: DashMap<[u8; 16], RustNPC>,
-
-    /// Inactive NPCs (in pool, ready to spawn)
-    /// Key: NPC type -> Vec of inactive NPCs
-    inactive_npc_pool: DashMap<String, Vec<RustNPC>>,
-
-    /// PackedScene cache (NPC type -> PackedScene) - NO LONGER USED, kept for compatibility
-    /// Cached to avoid reloading .tscn files repeatedly
-    scene_cache: DashMap<String, String>,
-
-    /// Scene tree container node (set by GDScript, NPCs are added as children)
-    /// This is the Layer4Objects container from the background
-    /// Wrapped in Arc<RwLock> for thread-safe sharing with animation pools
-    scene_container: Arc<RwLock<Option<Gd<Node2D>>>>,
-
-    // ============================================================================
-    // HEALTHBAR POOL SYSTEM - Managed by Rust alongside NPC pools
-    // ============================================================================
-    /// Inactive healthbars (in pool, ready to be assigned)
-    /// These are pre-instantiated HealthBar nodes that get connected/disconnected from NPCs
-    healthbar_pool: DashMap<usize, Gd<Node>>, // Pool index -> HealthBar node
-
-    /// Active healthbar assignments (ULID bytes -> pool index)
-    /// Tracks which healthbar is assigned to which NPC
-    healthbar_assignments: DashMap<[u8; 16], usize>,
-
-    /// Next available healthbar pool index (atomic for thread safety)
-    healthbar_pool_size: Arc<AtomicUsize>,
-
-    // ============================================================================
-    // RELEASE EFFECT POOL SYSTEM - Death animation/particle effects
-    // ============================================================================
-    /// Release effect pool for death animations (managed by animation module)
-    release_pool: EffectPool,
-
-    // ============================================================================
-    // BYTE-KEYED STORAGE - ByteMap now uses DashMap internally for strong consistency
-    // ============================================================================
-    /// NPC positions (ULID bytes -> "x,y")
-    npc_positions: DashMap<[u8; 16], String>,
-
-    /// NPC metadata (ULID bytes -> value string)
-    npc_names: DashMap<[u8; 16], String>, // ULID -> generated name
-    npc_types: DashMap<[u8; 16], String>, // ULID -> npc_type (warrior, archer, etc.)
-
-    /// NPC combat stats (ULID bytes -> NPCCombatStats struct serialized as JSON)
-    /// Contains: hp, max_hp, attack, defense, static_state
-    npc_combat_stats: DashMap<[u8; 16], String>,
-
-    /// NPC dynamic state (ULID bytes -> value string)
-    npc_behavioral_state: DashMap<[u8; 16], String>,
-    npc_cooldown: DashMap<[u8; 16], String>,
-    npc_state_timestamps: DashMap<[u8; 16], String>, // Timestamp when ATTACKING/DAMAGED states were set
-    npc_aggro_targets: DashMap<[u8; 16], String>, // Target ULID (hex) that this NPC should attack
-
-    /// NPC movement data (ULID bytes -> "x,y" coordinates)
-    npc_waypoints: DashMap<[u8; 16], String>, // Target position for movement
-    npc_move_directions: DashMap<[u8; 16], String>, // Calculated movement direction
-}
 
-impl NPCDataWarehouse {
-    /// Helper to track all state writes for debugging
-    fn set_behavioral_state(&self, ulid: &[u8; 16], new_state: i32, caller: &str) {
-        static mut STATE_WRITE_COUNT: u32 = 0;
-        unsafe {
-            STATE_WRITE_COUNT += 1;
-            if STATE_WRITE_COUNT <= 30 {
-                let ulid_hex = bytes_to_hex(ulid);
-                godot_print!(
-                    "[STATE WRITE] {} - ULID {} - setting to {}",
-                    caller,
-                    &ulid_hex[..8],
-                    new_state
-                );
+    /// Spawn an NPC from the Rust pool
+    /// Returns the ULID bytes of the spawned NPC, or empty array if failed
+    #[func]
+    pub fn rust_spawn_npc(&self, npc_type: GString, position: Vector2) -> PackedByteArray {
+        let spawned = {
+            let _step = self.warehouse.hold_steps();
+            self.warehouse
+                .rust_spawn_npc(&npc_type.to_string(), Vec2::new(position.x, position.y))
+        };
+        if let Some(ulid_bytes) = spawned {
+            self.nodes.apply_commands(&self.warehouse);
+            // The node layer hands the slot back if it could not show the NPC
+            if self.warehouse.active_npcs.contains_key(&ulid_bytes) {
+                return PackedByteArray::from(&ulid_bytes[..]);
             }
         }
-        self.npc_behavioral_state
-            .insert(*ulid, new_state.to_string());
-    }
-
-    /// Create a new NPCDataWarehouse with the specified sync interval
-    pub fn new(sync_interval_ms: u64) -> Self {
-        godot_print!(
-            "NPCDataWarehouse: Initializing with {}ms sync interval",
-            sync_interval_ms
-        );
-
-        // Create scene container (shared between warehouse and effect pools)
-        let scene_container = Arc::new(RwLock::new(None));
-
-        // Create release effect pool with shared scene container
-        let release_pool = EffectPool::new(Arc::clone(&scene_container), "RELEASE");
-
-        Self {
-            storage: DashMap::new(),
-            sync_interval_ms,
-            combat_event_queue: Arc::new(SegQueue::new()),
-            combat_thread_running: Arc::new(AtomicBool::new(false)),
-            active_combat_npcs: DashMap::new(),
-            error_log: DashMap::new(),
-            last_spawn_time_ms: Arc::new(AtomicU64::new(0)),
-            last_ally_spawn_time_ms: Arc::new(AtomicU64::new(0)),
-            spawn_interval_ms: 10000, // 10 seconds between monster waves
-            min_wave_size: 8,         // Start with 8 monsters per wave
-            max_wave_size: 8,         // Fixed at 8 monsters per wave
-            min_active_monsters: 3,   // Spawn new wave when below 3 monsters
-            ally_spawn_interval_ms: 3000, // 3 seconds between ally spawns (gradual ramp-up)
-            max_warriors: 6,          // Cap at 6 warriors
-            max_archers: 6,           // Cap at 6 archers (12 total allies)
-            spawn_requests: Arc::new(AtomicU64::new(0)),
-            spawn_confirmations: Arc::new(AtomicU64::new(0)),
-            initial_spawn_done: Arc::new(AtomicBool::new(false)),
-            // Initialize with default world bounds (will be updated by GDScript from BackgroundManager)
-            world_min_x: Arc::new(std::sync::atomic::AtomicU32::new(WORLD_MIN_X.to_bits())),
-            world_max_x: Arc::new(std::sync::atomic::AtomicU32::new(WORLD_MAX_X.to_bits())),
-            world_min_y: Arc::new(std::sync::atomic::AtomicU32::new(WORLD_MIN_Y.to_bits())),
-            world_max_y: Arc::new(std::sync::atomic::AtomicU32::new(WORLD_MAX_Y.to_bits())),
-            // Initialize Rust NPC pool system
-            active_npc_pool: DashMap::new(),
-            inactive_npc_pool: DashMap::new(),
-            scene_cache: DashMap::new(),
-            scene_container,
-
-            // Initialize healthbar pool system
-            healthbar_pool: DashMap::new(),
-            healthbar_assignments: DashMap::new(),
-            healthbar_pool_size: Arc::new(AtomicUsize::new(0)),
-
-            // Initialize release effect pool (created above)
-            release_pool,
-
-            // Initialize DashMap storage directly (no wrappers)
-            npc_positions: DashMap::new(),
-            npc_names: DashMap::new(),
-            npc_types: DashMap::new(),
-            npc_combat_stats: DashMap::new(),
-            npc_behavioral_state: DashMap::new(),
-            npc_cooldown: DashMap::new(),
-            npc_state_timestamps: DashMap::new(),
-            npc_aggro_targets: DashMap::new(),
-            npc_waypoints: DashMap::new(),
-            npc_move_directions: DashMap::new(),
-        }
+        PackedByteArray::new()
     }
 
-    /// Register a new NPC pool
-    pub fn register_pool(&self, npc_type: &str, max_size: i32, scene_path: &str) {
-        let pool_def = PoolDefinition {
-            npc_type: npc_type.to_string(),
-            max_size,
-            scene_path: scene_path.to_string(),
-            current_active: 0,
+    /// Despawn an NPC and return it to the pool
+    #[func]
+    pub fn rust_despawn_npc(&self, ulid: PackedByteArray) -> bool {
+        let despawned = {
+            let _step = self.warehouse.hold_steps();
+            self.warehouse.rust_despawn_npc(ulid.as_slice())
         };
+        self.nodes.apply_commands(&self.warehouse);
+        despawned
+    }
 
-        let key = format!("pool:{}", npc_type);
-        let value = pool_def.to_json();
+    // ===== Healthbar Pool System Methods =====
 
-        self.storage.insert(key, value);
-        godot_print!(
-            "NPCDataWarehouse: Registered pool '{}' (max: {}, scene: {})",
-            npc_type,
-            max_size,
-            scene_path
-        );
+    /// Initialize the healthbar pool by instantiating packed scenes in Rust
+    /// pool_size: Number of healthbars to pre-allocate
+    /// scene_path: Path to the healthbar.tscn file
+    #[func]
+    pub fn initialize_healthbar_pool(&self, pool_size: i32, scene_path: GString) {
+        self.nodes
+            .initialize_healthbar_pool(pool_size as usize, &scene_path.to_string());
     }
 
-    /// Get pool definition for an NPC type
-    pub fn get_pool(&self, npc_type: &str) -> Option<PoolDefinition> {
-        let key = format!("pool:{}", npc_type);
-        self.storage
-            .get(&key)
-            .and_then(|v| PoolDefinition::from_json(v.value()))
+    /// Get the number of healthbars in the pool
+    #[func]
+    pub fn healthbar_pool_count(&self) -> i64 {
+        self.nodes.healthbar_pool_count() as i64
     }
 
-    /// Store active NPC data
-    pub fn store_npc(&self, ulid: &str, npc_data: &str) {
-        let key = format!("active:{}", ulid);
-        let value = npc_data.to_string();
-        self.storage.insert(key, value);
+    /// Get the number of active healthbar assignments
+    #[func]
+    pub fn active_healthbar_count(&self) -> i64 {
+        self.nodes.active_healthbar_count() as i64
     }
 
-    /// Get active NPC data
-    pub fn get_npc(&self, ulid: &str) -> Option<String> {
-        let key = format!("active:{}", ulid);
-        self.storage.get(&key).map(|v| v.value().clone())
-    }
+    // ===== Release Effect Pool System Methods =====
 
-    /// Remove NPC from active pool
-    pub fn remove_npc(&self, ulid: &str) -> bool {
-        let key = format!("active:{}", ulid);
-        self.storage.remove(&key).is_some()
+    /// Initialize the release effect pool for death animations
+    /// pool_size: Number of release effects to pre-allocate
+    /// scene_path: Path to the release.tscn file
+    /// animation_duration_ms: Duration of the release animation in milliseconds
+    #[func]
+    pub fn initialize_release_pool(
+        &self,
+        pool_size: i32,
+        scene_path: GString,
+        animation_duration_ms: i32,
+    ) {
+        self.nodes.release_pool.initialize(
+            pool_size as usize,
+            &scene_path.to_string(),
+            animation_duration_ms as u64,
+        );
     }
 
-    /// Store AI state for an NPC
-    pub fn store_ai_state(&self, ulid: &str, ai_data: &str) {
-        let key = format!("ai:{}", ulid);
-        let value = ai_data.to_string();
-        self.storage.insert(key, value);
+    /// Return a release effect to the pool (called from GDScript when animation finishes)
+    /// pool_index: The index of the release effect to return
+    #[func]
+    pub fn return_release_to_pool(&self, pool_index: i64) -> bool {
+        self.nodes.release_pool.return_to_pool(pool_index as usize)
     }
 
-    /// Get AI state for an NPC
-    pub fn get_ai_state(&self, ulid: &str) -> Option<String> {
-        let key = format!("ai:{}", ulid);
-        self.storage.get(&key).map(|v| v.value().clone())
+    /// Get the number of release effects in the pool
+    #[func]
+    pub fn release_pool_count(&self) -> i64 {
+        self.nodes.release_pool.count() as i64
     }
 
-    /// Store combat state for an NPC
-    pub fn store_combat_state(&self, ulid: &str, combat_data: &str) {
-        let key = format!("combat:{}", ulid);
-        let value = combat_data.to_string();
-        self.storage.insert(key, value);
+    /// Get the number of release effects currently in use
+    #[func]
+    pub fn active_release_count(&self) -> i64 {
+        self.nodes.release_pool.active_count() as i64
     }
 
-    /// Get combat state for an NPC
-    pub fn get_combat_state(&self, ulid: &str) -> Option<String> {
-        let key = format!("combat:{}", ulid);
-        self.storage.get(&key).map(|v| v.value().clone())
+    /// Get NPC name by ULID bytes
+    #[func]
+    pub fn get_npc_name(&self, ulid: PackedByteArray) -> GString {
+        if ulid.len() != 16 {
+            return GString::from("");
+        }
+        let ulid_bytes: [u8; 16] = ulid.as_slice().try_into().unwrap_or([0u8; 16]);
+        self.warehouse
+            .npc_names
+            .get(&ulid_bytes)
+            .map(|v| v.value().clone())
+            .map(|name| GString::from(name))
+            .unwrap_or_else(|| GString::from(""))
     }
 
-    // ============================================================================
-    // RUST NPC POOL MANAGEMENT - Replaces GDScript pool system
-    // ============================================================================
-
-    /// Pre-populate the inactive pool with NPCs of a given type
-    /// This loads and instantiates PackedScenes, creating a pool ready for spawning
-    pub fn initialize_npc_pool(&self, npc_type: &str, pool_size: usize, scene_path: &str) {
-        godot_print!(
-            "[RUST POOL] Initializing pool for {} (size: {})",
-            npc_type,
-            pool_size
-        );
-
-        // Cache the PackedScene for this NPC type
-        let scene_key = format!("scene:{}", npc_type);
-        let scene_value = scene_path.to_string();
-        self.scene_cache.insert(scene_key, scene_value);
-
-        // Create pool of inactive NPCs
-        let mut pool_vec = Vec::with_capacity(pool_size);
-        for i in 0..pool_size {
-            // Generate ULID as 16 bytes
-            let ulid = ulid::Ulid::new().to_bytes();
-            if let Some(npc) = RustNPC::from_scene(scene_path, npc_type, ulid) {
-                pool_vec.push(npc);
-            } else {
-                godot_error!(
-                    "[RUST POOL] Failed to create NPC {}/{} for type {}",
-                    i + 1,
-                    pool_size,
-                    npc_type
-                );
-            }
+    /// Get NPC type by ULID bytes
+    #[func]
+    pub fn get_npc_type(&self, ulid: PackedByteArray) -> GString {
+        if ulid.len() != 16 {
+            return GString::from("");
         }
-
-        let created_count = pool_vec.len();
-        self.inactive_npc_pool
-            .insert(npc_type.to_string(), pool_vec);
-        godot_print!(
-            "[RUST POOL] Created {} inactive NPCs for type {}",
-            created_count,
-            npc_type
-        );
+        let ulid_bytes: [u8; 16] = ulid.as_slice().try_into().unwrap_or([0u8; 16]);
+        self.warehouse
+            .npc_types
+            .get(&ulid_bytes)
+            .map(|v| v.value().clone())
+            .map(|npc_type| GString::from(npc_type))
+            .unwrap_or_else(|| GString::from(""))
     }
 
-    /// Set the scene container (Layer4Objects) where NPCs will be added
-    pub fn set_scene_container(&self, container: Gd<Node2D>) {
-        godot_print!("[RUST POOL] Setting scene container");
-        *self.scene_container.write() = Some(container);
-
-        // Auto-initialize release effect pool now that we have a container
-        // Use smaller pool size (3) to avoid blocking the main thread
-        godot_print!("[RUST POOL] Auto-initializing release effect pool...");
-        self.release_pool.initialize(3, "res://nodes/npc/common/release.tscn", 2000);
-        godot_print!("[RUST POOL] Release effect pool initialization complete");
-
-        // Auto-initialize healthbar pool now that we have a container
-        // Use smaller pool size to avoid blocking on init
-        godot_print!("[RUST POOL] Auto-initializing healthbar pool...");
-        self.initialize_healthbar_pool(20, "res://nodes/ui/healthbar/healthbar.tscn");
-        godot_print!("[RUST POOL] Healthbar pool initialization complete");
-    }
-
-    /// Spawn an NPC from the inactive pool
-    /// Returns the ULID bytes of the spawned NPC, or None if pool is empty
-    pub fn rust_spawn_npc(&self, npc_type: &str, position: Vector2) -> Option<[u8; 16]> {
-        godot_print!(
-            "[RUST SPAWN DEBUG] rust_spawn_npc called for type: {}",
-            npc_type
-        );
-
-        // Get an inactive NPC from the pool
-        let mut npc = {
-            let mut pool_entry = match self.inactive_npc_pool.get_mut(npc_type) {
-                Some(entry) => entry,
-                None => {
-                    // Pool doesn't exist yet - might be during initialization
-                    godot_warn!(
-                        "[RUST POOL] No pool found for NPC type: {} (may not be initialized yet)",
-                        npc_type
-                    );
-                    return None;
-                }
-            };
-
-            if pool_entry.is_empty() {
-                godot_warn!("[RUST POOL] Pool empty for NPC type: {} (all NPCs in use, consider increasing pool size)", npc_type);
-                return None;
-            }
-
-            pool_entry.pop().unwrap()
-        };
-
-        let ulid = npc.ulid;
-
-        // Set ULID as a property on the NPC node BEFORE adding to tree
-        // Use the existing 'ulid' property defined in npc.gd base class
-        let ulid_bytes = PackedByteArray::from(&ulid[..]);
-        let ulid_variant = ulid_bytes.to_variant();
-        let _ = npc.node.set("ulid", &ulid_variant);
+    /// Get NPC stats dictionary by ULID bytes
+    /// Returns a Dictionary with keys: name, type, hp, max_hp, attack, defense, mana, max_mana,
+    /// energy, max_energy, hunger, max_hunger, hunger_level, emotional_state, emotion
+    #[func]
+    pub fn get_npc_stats_dict(&self, ulid: PackedByteArray) -> Dictionary {
+        let mut dict = Dictionary::new();
+        if ulid.len() != 16 {
+            return dict;
+        }
+        let ulid_bytes: [u8; 16] = ulid.as_slice().try_into().unwrap_or([0u8; 16]);
 
-        // Add NPC to scene tree if container is set
+        // Get name
+        if let Some(name) = self
+            .warehouse
+            .npc_names
+            .get(&ulid_bytes)
+            .map(|v| v.value().clone())
         {
-            let mut container_guard = self.scene_container.write();
-            if let Some(ref mut container) = *container_guard {
-                let container_path = container.get_path();
-                container.add_child(&npc.node);
-                let node_path = npc.node.get_path();
-                godot_print!(
-                    "[RUST POOL] Added {} to scene tree: container={}, node_path={}",
-                    npc_type,
-                    container_path,
-                    node_path
-                );
-            } else {
-                godot_error!("[RUST POOL] Cannot spawn NPC - scene container not set!");
-                return None;
-            }
+            dict.set("name", name);
         }
 
-        // Activate the NPC AFTER adding to tree (important for visibility to work)
-        npc.activate(position);
-
-        // Store NPC metadata (name, type) in ByteMaps
-        let npc_name = npc.name.clone();
-        let npc_type_str = npc.npc_type.clone();
-        self.npc_names.insert(*&ulid, npc_name.clone());
-        self.npc_types.insert(*&ulid, npc_type_str.clone());
-
-        // Register for combat using the stats extracted during pool initialization
-        let npc_stats = npc.stats;
-        let ulid_hex = bytes_to_hex(&ulid);
-        godot_print!(
-            "[RUST SPAWN DEBUG] About to register NPC {} with ULID: {}",
-            npc_type,
-            ulid_hex
-        );
-        self.register_npc_with_stats(&ulid, &npc_stats);
-
-        // Store position for combat system using ByteMap (no hex conversion!)
-        self.npc_positions
-            .insert(*&ulid, format!("{},{}", position.x, position.y));
-
-        // Set initial wander cooldown so NPC stays idle for a bit after spawning (5-10 seconds)
-        use rand::Rng;
-        let mut rng = rand::rng();
-        let initial_idle_time = rng.random_range(5000..10000); // 5-10 seconds in milliseconds
-        let ulid_hex = bytes_to_hex(&ulid);
-        let now_ms = Self::get_current_time_ms();
-        self.storage.insert(
-            format!("wander_cooldown:{}", ulid_hex),
-            (now_ms + initial_idle_time).to_string(),
-        );
-
-        // Move to active pool (use [u8; 16] directly as key)
-        self.active_npc_pool.insert(ulid, npc);
-
-        // Get and assign a healthbar from the pool
-        if let Some((mut healthbar, _index)) = self.get_healthbar_for_npc(&ulid) {
-            // Call connect_to_entity on the healthbar to attach it to the NPC
-            // Pass the NPC node as a Variant
-            if let Some(active_npc) = self.active_npc_pool.get(&ulid) {
-                let npc_variant = active_npc.node.to_variant();
-                let _ = healthbar.call("connect_to_entity", &[npc_variant]);
-            }
+        // Get type
+        if let Some(npc_type) = self
+            .warehouse
+            .npc_types
+            .get(&ulid_bytes)
+            .map(|v| v.value().clone())
+        {
+            dict.set("type", npc_type);
         }
 
-        // Log spawn with first 8 bytes in hex and static_state
-        let ulid_hex_short = format!(
-            "{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
-            ulid[0], ulid[1], ulid[2], ulid[3], ulid[4], ulid[5], ulid[6], ulid[7]
-        );
-        let is_ally = (npc_stats.static_state & NPCStaticState::ALLY.bits() as i32) != 0;
-        let is_monster = (npc_stats.static_state & NPCStaticState::MONSTER.bits() as i32) != 0;
-        godot_print!(
-            "[RUST POOL] Spawned {} at {:?} with ULID {} (ally={}, monster={}, static_state={})",
-            npc_type,
-            position,
-            ulid_hex_short,
-            is_ally,
-            is_monster,
-            npc_stats.static_state
-        );
+        // Get combat stats (hp, max_hp, attack, defense, mana, energy) from single struct
+        if let Some(combat_stats) = self.warehouse.get_combat_stats(&ulid_bytes) {
+            dict.set("hp", combat_stats.hp);
+            dict.set("max_hp", combat_stats.max_hp);
+            dict.set("attack", combat_stats.attack);
+            dict.set("defense", combat_stats.defense);
+            dict.set("mana", combat_stats.mana);
+            dict.set("max_mana", combat_stats.max_mana);
+            dict.set("energy", combat_stats.energy);
+            dict.set("max_energy", combat_stats.max_energy);
+            dict.set("hunger", combat_stats.hunger);
+            dict.set("max_hunger", combat_stats.max_hunger);
+            dict.set("emotional_state", combat_stats.emotional_state);
+            dict.set(
+                "emotion",
+                Emotion::from_id(combat_stats.emotional_state).as_str(),
+            );
+        }
+        if let Some(level) = self.warehouse.hunger_level(&ulid_bytes) {
+            dict.set("hunger_level", level.as_str());
+        }
 
-        Some(ulid)
+        dict
     }
 
-    /// Despawn an NPC and return it to the inactive pool
-    pub fn rust_despawn_npc(&self, ulid: &[u8]) -> bool {
-        // Convert ulid slice to array for ByteMap access
-        let ulid_array: [u8; 16] = if ulid.len() == 16 {
-            ulid.try_into().unwrap_or([0u8; 16])
-        } else {
-            [0u8; 16]
-        };
-
-        // Return healthbar to pool BEFORE removing NPC
-        if let Some((_, pool_index)) = self.healthbar_assignments.remove(&ulid_array) {
-            // Get the healthbar and disconnect it from the NPC
-            if let Some(mut healthbar) = self.healthbar_pool.get(&pool_index).map(|e| e.value().clone()) {
-                let _ = healthbar.call("disconnect_from_entity", &[]);
-            }
+    /// Get NPC data as JSON string (name, type, stats)
+    #[func]
+    pub fn get_npc_data_json(&self, ulid: PackedByteArray) -> GString {
+        if ulid.len() != 16 {
+            return GString::from("{}");
         }
+        let ulid_bytes: [u8; 16] = ulid.as_slice().try_into().unwrap_or([0u8; 16]);
 
-        // Remove from active pool
-        let mut npc = match self.active_npc_pool.remove(ulid) {
-            Some((_, npc)) => npc,
-            None => {
-                let ulid_hex = ulid
-                    .iter()
-                    .take(8)
-                    .map(|b| format!("{:02x}", b))
-                    .collect::<String>();
-                godot_warn!("[RUST POOL] Cannot despawn - NPC not found: {}", ulid_hex);
-                return false;
-            }
-        };
+        let mut name = String::from("");
+        let mut npc_type = String::from("");
+        let mut hp = 0.0_f32;
+        let mut max_hp = 0.0_f32;
+        let mut attack = 0.0_f32;
+        let mut defense = 0.0_f32;
+        let mut emotional_state = 0_i32;
+        let mut mana = 0.0_f32;
+        let mut max_mana = 0.0_f32;
+        let mut energy = 0.0_f32;
+        let mut max_energy = 0.0_f32;
+        let mut hunger = 0.0_f32;
+        let mut max_hunger = 0.0_f32;
 
-        // Remove from scene tree
+        // Get name
+        if let Some(n) = self
+            .warehouse
+            .npc_names
+            .get(&ulid_bytes)
+            .map(|v| v.value().clone())
         {
-            let mut container_guard = self.scene_container.write();
-            if let Some(ref mut container) = *container_guard {
-                container.remove_child(&npc.node);
-            }
+            name = n;
         }
 
-        // Reset NPC stats in ByteMaps (HP back to max, remove DEAD state)
-        // Get current combat stats and reset HP to max
-        if let Some(stats_json) = self
-            .npc_combat_stats
-            .get(&ulid_array)
+        // Get type
+        if let Some(t) = self
+            .warehouse
+            .npc_types
+            .get(&ulid_bytes)
             .map(|v| v.value().clone())
         {
-            if let Ok(mut combat_stats) = serde_json::from_str::<NPCCombatStats>(&stats_json) {
-                combat_stats.hp = combat_stats.max_hp; // Reset HP to max
-                if let Ok(updated_json) = serde_json::to_string(&combat_stats) {
-                    self.npc_combat_stats.insert(*&ulid_array, updated_json);
-                }
-            }
+            npc_type = t;
         }
 
-        // Behavioral state: reset to IDLE (0)
-        self.npc_behavioral_state
-            .insert(*&ulid_array, "0".to_string());
-
-        // Cooldown: reset to 0
-        self.npc_cooldown.insert(*&ulid_array, "0".to_string());
-
-        // Note: Keep name and type - they don't change when pooled NPCs respawn
-        // Note: Static state (faction, combat type) never changes
-
-        // Reset the NPC node
-        npc.reset();
-
-        // Return to inactive pool
-        let npc_type = npc.npc_type.clone();
-        if let Some(mut pool_entry) = self.inactive_npc_pool.get_mut(&npc_type) {
-            pool_entry.push(npc);
-            let ulid_hex = ulid
-                .iter()
-                .take(8)
-                .map(|b| format!("{:02x}", b))
-                .collect::<String>();
-            godot_print!(
-                "[RUST POOL] Despawned {} '{}' (ULID: {}) - Reset and returned to pool",
-                npc_type,
-                self.npc_names
-                    .get(&ulid_array)
-                    .map(|v| v.value().clone())
-                    .unwrap_or_else(|| "Unknown".to_string()),
-                ulid_hex
-            );
-            true
-        } else {
-            godot_error!(
-                "[RUST POOL] Cannot return NPC to pool - pool not found for type: {}",
-                npc_type
-            );
-            false
+        // Get combat stats
+        if let Some(combat_stats) = self.warehouse.get_combat_stats(&ulid_bytes) {
+            hp = combat_stats.hp;
+            max_hp = combat_stats.max_hp;
+            attack = combat_stats.attack;
+            defense = combat_stats.defense;
+            emotional_state = combat_stats.emotional_state;
+            mana = combat_stats.mana;
+            max_mana = combat_stats.max_mana;
+            energy = combat_stats.energy;
+            max_energy = combat_stats.max_energy;
+            hunger = combat_stats.hunger;
+            max_hunger = combat_stats.max_hunger;
         }
-    }
 
-    // ============================================================================
-    // HEALTHBAR POOL MANAGEMENT
-    // ============================================================================
-
-    /// Initialize the healthbar pool by loading and instantiating the packed scene
-    /// Similar to how NPC pools work - load scene once, instantiate multiple times
-    pub fn initialize_healthbar_pool(&self, pool_size: usize, scene_path: &str) {
-        godot_print!("[RUST HEALTHBAR] Initializing healthbar pool: size={}, scene={}", pool_size, scene_path);
+        // Build JSON string manually (simple and fast)
+        let json = format!(
+            r#"{{"name":"{}","type":"{}","hp":{},"max_hp":{},"attack":{},"defense":{},"emotional_state":{},"emotion":"{}","mana":{},"max_mana":{},"energy":{},"max_energy":{},"hunger":{},"max_hunger":{}}}"#,
+            name,
+            npc_type,
+            hp,
+            max_hp,
+            attack,
+            defense,
+            emotional_state,
+            Emotion::from_id(emotional_state).as_str(),
+            mana,
+            max_mana,
+            energy,
+            max_energy,
+            hunger,
+            max_hunger
+        );
 
-        // Load the healthbar scene
-        let scene: Gd<PackedScene> = match try_load::<PackedScene>(scene_path) {
-            Ok(scene) => scene,
-            Err(err) => {
-                godot_error!("[RUST HEALTHBAR] Failed to load healthbar scene: {} - {:?}", scene_path, err);
-                return;
-            }
-        };
+        GString::from(json)
+    }
 
-        // Get the foreground container (scene parent)
-        let container_opt = {
-            let container_guard = self.scene_container.read();
-            container_guard.clone()
-        };
-
-        if container_opt.is_none() {
-            godot_error!("[RUST HEALTHBAR] Cannot initialize healthbar pool - scene container not set!");
-            return;
-        }
-
-        let mut container = container_opt.unwrap();
-
-        // Instantiate healthbars and add to pool
-        for _i in 0..pool_size {
-            let healthbar = scene.instantiate_as::<Node>();
-
-            // Add to scene tree
-            container.add_child(&healthbar);
-
-            // Configure healthbar (clone to avoid move)
-            if let Ok(mut control_healthbar) = healthbar.clone().try_cast::<Control>() {
-                control_healthbar.set_visible(false); // Hide until assigned
-                control_healthbar.set_z_index(100);   // Render above NPCs
-            }
+    /// Store active NPC data
+    /// npc_data should be JSON with at minimum {"type":"npc_type_here"}
+    #[func]
+    pub fn store_npc(&mut self, ulid: GString, npc_data: GString) {
+        self.warehouse
+            .store_npc(&ulid.to_string(), &npc_data.to_string());
 
-            // Add to pool
-            let index = self.healthbar_pool_size.fetch_add(1, Ordering::Relaxed);
-            self.healthbar_pool.insert(index, healthbar);
-        }
+        // Extract npc_type from JSON (simple parse - assumes format {"type":"value"})
+        let npc_type = GString::from("unknown"); // TODO: Parse JSON properly
 
-        godot_print!("[RUST HEALTHBAR] Initialized {} healthbars in pool", pool_size);
+        // Emit signal
+        self.base_mut()
+            .emit_signal("npc_stored", &[ulid.to_variant(), npc_type.to_variant()]);
     }
 
-    /// Get an available healthbar from the pool and assign it to an NPC
-    /// Returns the healthbar node and its pool index, or None if pool is empty
-    pub fn get_healthbar_for_npc(&self, ulid: &[u8; 16]) -> Option<(Gd<Node>, usize)> {
-        // Find first healthbar not currently assigned
-        for entry in self.healthbar_pool.iter() {
-            let index = *entry.key();
-
-            // Check if this healthbar is already assigned
-            let is_assigned = self.healthbar_assignments.iter().any(|a| *a.value() == index);
+    /// Get active NPC data
+    #[func]
+    pub fn get_npc(&self, ulid: GString) -> GString {
+        self.warehouse
+            .get_npc(&ulid.to_string())
+            .map(|s| GString::from(s))
+            .unwrap_or_else(|| GString::from(""))
+    }
 
-            if !is_assigned {
-                let healthbar = entry.value().clone();
-                // Assign this healthbar to the NPC
-                self.healthbar_assignments.insert(*ulid, index);
-                godot_print!(
-                    "[RUST HEALTHBAR] Assigned healthbar {} to NPC {:02x}{:02x}{:02x}{:02x}",
-                    index,
-                    ulid[0], ulid[1], ulid[2], ulid[3]
-                );
-                return Some((healthbar, index));
-            }
+    /// Remove NPC from active pool
+    #[func]
+    pub fn remove_npc(&mut self, ulid: GString) -> bool {
+        let removed = self.warehouse.remove_npc(&ulid.to_string());
+        if removed {
+            // Emit signal
+            self.base_mut().emit_signal(
+                "npc_removed",
+                &[ulid.to_variant(), GString::from("unknown").to_variant()],
+            );
         }
+        removed
+    }
 
-        godot_warn!("[RUST HEALTHBAR] No available healthbars in pool!");
-        None
+    /// Store NPC state flags (raw integer, not JSON)
+    /// Much faster than storing in AI state JSON
+    #[func]
+    pub fn store_npc_state(&mut self, ulid: GString, state: i32) {
+        // Store as simple integer string for fast access
+        self.warehouse
+            .store_combat_state(&format!("{}_state", ulid.to_string()), &state.to_string());
     }
 
-    /// Return a healthbar to the pool when NPC is despawned
-    pub fn return_healthbar(&self, ulid: &[u8; 16]) -> bool {
-        if let Some((_, pool_index)) = self.healthbar_assignments.remove(ulid) {
-            // Hide the healthbar when returning to pool
-            if let Some(healthbar) = self.healthbar_pool.get(&pool_index) {
-                if let Ok(mut control_healthbar) = healthbar.value().clone().try_cast::<Control>() {
-                    control_healthbar.set_visible(false);
-                }
-            }
+    /// Get NPC state flags (raw integer)
+    #[func]
+    pub fn get_npc_state(&self, ulid: GString) -> i32 {
+        self.warehouse
+            .get_combat_state(&format!("{}_state", ulid.to_string()))
+            .and_then(|s| s.parse::<i32>().ok())
+            .unwrap_or(NPCState::IDLE.to_i32())
+    }
 
-            godot_print!(
-                "[RUST HEALTHBAR] Returned healthbar {} to pool from NPC {:02x}{:02x}{:02x}{:02x}",
-                pool_index,
-                ulid[0], ulid[1], ulid[2], ulid[3]
-            );
-            true
-        } else {
-            godot_warn!(
-                "[RUST HEALTHBAR] No healthbar assigned to NPC {:02x}{:02x}{:02x}{:02x}",
-                ulid[0], ulid[1], ulid[2], ulid[3]
-            );
-            false
-        }
+    /// Store AI state
+    #[func]
+    pub fn store_ai_state(&mut self, ulid: GString, ai_data: GString) {
+        self.warehouse
+            .store_ai_state(&ulid.to_string(), &ai_data.to_string());
+        // Emit signal
+        self.base_mut().emit_signal(
+            "ai_state_changed",
+            &[ulid.to_variant(), ai_data.to_variant()],
+        );
     }
 
-    /// Get the number of healthbars in the pool
-    pub fn healthbar_pool_count(&self) -> usize {
-        self.healthbar_pool.len()
+    /// Get AI state
+    #[func]
+    pub fn get_ai_state(&self, ulid: GString) -> GString {
+        self.warehouse
+            .get_ai_state(&ulid.to_string())
+            .map(|s| GString::from(s))
+            .unwrap_or_else(|| GString::from(""))
     }
 
-    /// Get the number of active healthbar assignments
-    pub fn active_healthbar_count(&self) -> usize {
-        self.healthbar_assignments.len()
-    }
-
-    // ============================================================================
-    // RELEASE EFFECT POOL MANAGEMENT - Delegated to animation::EffectPool
-    // ============================================================================
-    // All release pool functionality is now handled by the animation module.
-    // See self.release_pool methods: initialize(), trigger_at_position(), etc.
-
-    /// Register NPC for combat using pre-extracted combat stats
-    fn register_npc_with_stats(&self, ulid: &[u8; 16], stats: &NPCCombatStats) {
-        self.register_npc_for_combat_internal(
-            ulid,
-            stats.static_state,
-            NPCState::IDLE.bits() as i32, // behavioral_state starts as IDLE
-            stats.max_hp,
-            stats.attack,
-            stats.defense,
+    /// Store combat state
+    #[func]
+    pub fn store_combat_state(&mut self, ulid: GString, combat_data: GString) {
+        self.warehouse
+            .store_combat_state(&ulid.to_string(), &combat_data.to_string());
+        // Emit signal
+        self.base_mut().emit_signal(
+            "combat_state_changed",
+            &[ulid.to_variant(), combat_data.to_variant()],
         );
     }
 
-    /// Check if NPC exists in active pool
-    pub fn has_npc(&self, ulid: &str) -> bool {
-        let key = format!("active:{}", ulid);
-        self.storage.contains_key(&key)
+    /// Get combat state
+    #[func]
+    pub fn get_combat_state(&self, ulid: GString) -> GString {
+        self.warehouse
+            .get_combat_state(&ulid.to_string())
+            .map(|s| GString::from(s))
+            .unwrap_or_else(|| GString::from(""))
     }
 
-    /// Get total number of entries in storage
-    pub fn total_entries(&self) -> usize {
-        self.storage.len()
+    /// Check if NPC exists
+    #[func]
+    pub fn has_npc(&self, ulid: GString) -> bool {
+        self.warehouse.has_npc(&ulid.to_string())
     }
 
-    /// Get number of entries in storage (DashMap)
-    pub fn read_store_count(&self) -> usize {
-        self.storage.len()
+    /// Get total entries
+    #[func]
+    pub fn total_entries(&self) -> i32 {
+        self.warehouse.total_entries() as i32
     }
 
-    /// Get number of entries in write store (DashMap)
-    pub fn write_store_count(&self) -> usize {
-        self.storage.len()
+    /// Get read store count (Papaya - lock-free)
+    #[func]
+    pub fn read_store_count(&self) -> i32 {
+        self.warehouse.read_store_count() as i32
     }
 
-    /// Manually trigger sync from write store to read store - no-op for DashMap
-    pub fn sync(&self) {
-        // DashMap doesn't need syncing - it's always consistent
+    /// Get write store count (DashMap - concurrent)
+    #[func]
+    pub fn write_store_count(&self) -> i32 {
+        self.warehouse.write_store_count() as i32
     }
 
-    /// Clear all data (use with caution!)
-    pub fn clear_all(&self) {
-        self.storage.clear();
-        godot_print!("NPCDataWarehouse: All data cleared");
-    }
-
-    // ============================================================================
-    // RUST-FIRST COMBAT SYSTEM - Internal Implementation
-    // ============================================================================
-    // These methods are called by GodotNPCDataWarehouse wrapper (below)
-    // Rust owns all combat logic. GDScript only renders visual feedback.
-
-    /// Update NPC position - public for Arc access
-    pub fn update_npc_position_internal(&self, ulid: &str, x: f32, y: f32) {
-        // DEFENSIVE: Validate position values are finite
-        if !x.is_finite() {
-            self.log_error_once(
-                "invalid_position_x",
-                ulid,
-                &format!(
-                    "[COMBAT ERROR] Cannot update position for {} - invalid x: {}",
-                    ulid, x
-                ),
-            );
-            return;
-        }
-        if !y.is_finite() {
-            self.log_error_once(
-                "invalid_position_y",
-                ulid,
-                &format!(
-                    "[COMBAT ERROR] Cannot update position for {} - invalid y: {}",
-                    ulid, y
-                ),
-            );
-            return;
-        }
+    /// Manually trigger sync
+    #[func]
+    pub fn sync(&mut self) {
+        self.warehouse.sync();
+        let count_after = self.warehouse.read_store_count();
 
-        let key = format!("pos:{}", ulid);
-        let value = format!("{},{}", x, y);
-        self.storage.insert(key, value);
+        // Emit signal with count of synced entries
+        self.base_mut()
+            .emit_signal("sync_completed", &[(count_after as i32).to_variant()]);
     }
 
-    /// Get NPC position - public for Arc access (accepts hex ULID for backward compat)
-    pub fn get_npc_position_internal(&self, ulid: &str) -> Option<(f32, f32)> {
-        // Convert hex to bytes, then lookup in DashMap
-        let ulid_bytes = hex_to_bytes(ulid).ok()?;
-        if let Some(pos_str) = self
-            .npc_positions
-            .get(&ulid_bytes)
-            .map(|v| v.value().clone())
-        {
-            let parts: Vec<&str> = pos_str.split(',').collect();
-            if parts.len() == 2 {
-                if let (Ok(x), Ok(y)) = (parts[0].parse::<f32>(), parts[1].parse::<f32>()) {
-                    // DEFENSIVE: Validate parsed values are finite
-                    if !x.is_finite() || !y.is_finite() {
-                        self.log_error_once(
-                            "nan_position",
-                            ulid,
-                            &format!(
-                                "[COMBAT ERROR] NPC {} has invalid position: ({}, {})",
-                                ulid, x, y
-                            ),
-                        );
-                        return None;
-                    }
-                    return Some((x, y));
-                }
-            }
-        }
-        None
+    /// Clear all data
+    #[func]
+    pub fn clear_all(&self) {
+        self.warehouse.clear_all();
     }
 
-    // ============================================================================
-    // COMBAT REGISTRATION - Track NPCs in combat system
-    // ============================================================================
+    // ===== State Helper Methods (MINIMAL - Rust handles combat) =====
+
+    /// Get NPCState constant value by name (behavioral states only)
+    /// Usage in GDScript: NPCDataWarehouse.get_state("IDLE") returns 1
+    #[func]
+    pub fn get_state(&self, state_name: GString) -> i32 {
+        match state_name.to_string().to_uppercase().as_str() {
+            "IDLE" => NPCState::IDLE.to_i32(),
+            "WALKING" => NPCState::WALKING.to_i32(),
+            "ATTACKING" => NPCState::ATTACKING.to_i32(),
+            "COMBAT" => NPCState::COMBAT.to_i32(),
+            "DAMAGED" => NPCState::DAMAGED.to_i32(),
+            "DEAD" => NPCState::DEAD.to_i32(),
+            _ => 0,
+        }
+    }
 
-    /// Register NPC for combat tracking
-    /// Stores combat-relevant data in HolyMap for combat tick access
-    pub fn register_npc_for_combat_internal(
+    /// Confirm spawn completed successfully (called by GDScript after spawn)
+    /// Defensive programming: warns when the spawned NPC's state doesn't match
+    /// Usage: NPCDataWarehouse.confirm_spawn(ulid_bytes, monster_type, static_state, behavioral_state)
+    #[func]
+    pub fn confirm_spawn(
         &self,
-        ulid: &[u8; 16],
+        ulid_bytes: PackedByteArray,
+        monster_type: GString,
         static_state: i32,
         behavioral_state: i32,
-        max_hp: f32,
-        attack: f32,
-        defense: f32,
     ) {
-        // Convert to hex only for error messages (combat system uses bytes internally)
-        let ulid_hex_for_logging = format!("{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
-            ulid[0], ulid[1], ulid[2], ulid[3], ulid[4], ulid[5], ulid[6], ulid[7],
-            ulid[8], ulid[9], ulid[10], ulid[11], ulid[12], ulid[13], ulid[14], ulid[15]);
-
-        let ulid_str = &ulid_hex_for_logging;
-
-        // DEFENSIVE: Validate stats are finite and non-negative
-        if !max_hp.is_finite() || max_hp < 0.0 {
-            self.log_error_once(
-                "invalid_hp",
-                ulid_str,
-                &format!(
-                    "[COMBAT ERROR] Cannot register NPC {} - invalid max_hp: {}",
-                    ulid_str, max_hp
-                ),
-            );
-            return;
-        }
-        if !attack.is_finite() || attack < 0.0 {
-            self.log_error_once(
-                "invalid_attack",
-                ulid_str,
-                &format!(
-                    "[COMBAT ERROR] Cannot register NPC {} - invalid attack: {}",
-                    ulid_str, attack
-                ),
-            );
-            return;
-        }
-        if !defense.is_finite() || defense < 0.0 {
-            self.log_error_once(
-                "invalid_defense",
-                ulid_str,
-                &format!(
-                    "[COMBAT ERROR] Cannot register NPC {} - invalid defense: {}",
-                    ulid_str, defense
-                ),
-            );
-            return;
-        }
+        match packed_bytes_to_ulid(&ulid_bytes) {
+            Ok(ulid) => {
+                let ulid_hex = bytes_to_hex(&ulid);
 
-        // DEFENSIVE: Validate exactly one combat type is set
-        let combat_type_bits = [
-            NPCStaticState::MELEE.bits() as i32,
-            NPCStaticState::RANGED.bits() as i32,
-            NPCStaticState::MAGIC.bits() as i32,
-        ];
-        let combat_type_count = combat_type_bits
-            .iter()
-            .filter(|&&bit| (static_state & bit) != 0)
-            .count();
+                // Verify the spawned NPC has correct state
+                if let Some(actual_static) = self
+                    .warehouse
+                    .get_combat_stats(&ulid)
+                    .map(|stats| stats.static_state)
+                {
+                    if actual_static != static_state {
+                        godot_warn!("[RUST SPAWN] NPC {} spawned with incorrect static_state! Expected: {}, Got: {}",
+                            &ulid_hex[0..8], static_state, actual_static);
+                    }
+                }
 
-        if combat_type_count != 1 {
-            self.log_error_once("invalid_combat_type", ulid_str, &format!("[COMBAT ERROR] Cannot register NPC {} - must have exactly one combat type (MELEE/RANGED/MAGIC), found: {}", ulid_str, combat_type_count));
-            return;
+                if let Some(actual_behavioral) = self.warehouse.get_behavioral_state(&ulid) {
+                    if actual_behavioral.bits() as i32 != behavioral_state {
+                        godot_warn!("[RUST SPAWN] NPC {} spawned with incorrect behavioral_state! Expected: {}, Got: {}",
+                            &ulid_hex[0..8], behavioral_state, actual_behavioral.bits());
+                    }
+                }
+            }
+            Err(e) => {
+                godot_error!("[RUST SPAWN] Invalid ULID in confirm_spawn: {}", e);
+            }
         }
+    }
 
-        // DEFENSIVE: Validate exactly one faction is set (ALLY, MONSTER, or PASSIVE)
-        let faction_bits = [
-            NPCStaticState::ALLY.bits() as i32,
-            NPCStaticState::MONSTER.bits() as i32,
-            NPCStaticState::PASSIVE.bits() as i32,
-        ];
-        let faction_count = faction_bits
-            .iter()
-            .filter(|&&bit| (static_state & bit) != 0)
-            .count();
-
-        if faction_count != 1 {
-            self.log_error_once("invalid_faction", ulid_str, &format!("[COMBAT ERROR] Cannot register NPC {} - must have exactly one faction (ALLY/MONSTER/PASSIVE), found: {}", ulid_str, faction_count));
-            return;
+    /// Resolve a projectile hit in the core, update the scene and emit the event signals
+    fn resolve_projectile_hit(
+        &mut self,
+        attacker_ulid_bytes: &PackedByteArray,
+        target_ulid_bytes: &PackedByteArray,
+    ) -> Option<CombatEvent> {
+        // Convert PackedByteArray to [u8; 16]
+        if attacker_ulid_bytes.len() != 16 || target_ulid_bytes.len() != 16 {
+            godot_error!("[PROJECTILE] Invalid ULID bytes length in projectile_hit");
+            return None;
         }
 
-        // All validations passed - register NPC for combat using ByteMap
-        let combat_stats = NPCCombatStats {
-            hp: max_hp,
-            max_hp,
-            attack,
-            defense,
-            static_state,
-            emotional_state: 0, // Default neutral emotion
-            mana: 0.0,          // Will be set by NPC type later if needed
-            max_mana: 0.0,
-            energy: 100.0, // Default full energy
-            max_energy: 100.0,
-            hunger: 100.0, // Default full hunger
-            max_hunger: 100.0,
-        };
-        self.npc_combat_stats
-            .insert(*ulid, serde_json::to_string(&combat_stats).unwrap());
-        let ulid_hex = bytes_to_hex(ulid);
-        godot_print!(
-            "[RUST STATE] register_npc_for_combat_internal ULID {} - setting state to {}",
-            ulid_hex,
-            behavioral_state
-        );
-        self.npc_behavioral_state
-            .insert(*ulid, behavioral_state.to_string());
-        self.npc_cooldown.insert(*ulid, "0".to_string());
-
-        // Use bytes for active_combat_npcs DashMap
-        self.active_combat_npcs.insert(*ulid, ());
-    }
-
-    /// Unregister NPC from combat (on death/despawn)
-    /// Cleans up all combat-related data from HolyMaps
-    pub fn unregister_npc_from_combat_internal(&self, ulid: &str) {
-        // Remove combat data from storage HolyMap
-        self.storage.remove(&format!("combat:{}", ulid));
-        self.storage.remove(&format!("pos:{}", ulid));
-        self.storage.remove(&format!("hp:{}", ulid));
-        self.storage.remove(&format!("static_state:{}", ulid));
-        self.storage.remove(&format!("behavioral_state:{}", ulid));
-        self.storage.remove(&format!("attack:{}", ulid));
-        self.storage.remove(&format!("defense:{}", ulid));
-        self.storage.remove(&format!("cooldown:{}", ulid));
-
-        // Clean up error_log entries for this NPC
-        // We need to remove all error types for this ulid
-        let error_types = [
-            "invalid_ulid",
-            "invalid_hp",
-            "invalid_attack",
-            "invalid_defense",
-            "invalid_combat_type",
-            "invalid_faction",
-            "invalid_position_x",
-            "invalid_position_y",
-            "nan_position",
-            "missing_position",
-            "dead_attacker",
-            "dead_target",
-            "missing_hp",
-            "self_attack",
-            "self_attack_pair",
-        ];
-
-        for error_type in error_types {
-            self.error_log.remove(&format!("{}:{}", error_type, ulid));
-        }
+        let attacker_bytes: [u8; 16] = attacker_ulid_bytes.as_slice().try_into().unwrap();
+        let target_bytes: [u8; 16] = target_ulid_bytes.as_slice().try_into().unwrap();
 
-        // Remove from active NPCs set (convert hex string to bytes)
-        if let Ok(ulid_bytes) = hex_to_bytes(ulid) {
-            self.active_combat_npcs.remove(&ulid_bytes);
-        }
+        // Damage, death and DAMAGED state are resolved by the core
+        // None = target already dead (or gone), no damage
+        let event = {
+            let _step = self.warehouse.hold_steps();
+            self.warehouse
+                .projectile_hit(&attacker_bytes, &target_bytes)?
+        };
+        // Healthbar follows via the node layer
+        self.nodes.apply_commands(&self.warehouse);
+        self.emit_combat_signals(&[event]);
+        Some(event)
     }
 
-    /// Log error once per error_type:ulid combination using error_log HolyMap
-    /// This prevents spam by tracking which errors have been logged
-    fn log_error_once(&self, error_type: &str, ulid: &str, message: &str) {
-        let key = format!("{}:{}", error_type, ulid);
-
-        // Check if we've already logged this error
-        if self.error_log.get(&key).is_none() {
-            // First time seeing this error - log it and mark as seen
-            godot_error!("{}", message);
-            self.error_log.insert(key, "1".to_string());
-        }
-        // If already logged, silently skip (no spam)
+    /// Handle projectile hit - called by GDScript when arrow/projectile collides with target
+    /// Calculates damage, applies it, and returns events (damage or death) as JSON strings
+    /// Kept for compatibility - prefer projectile_hit_events()
+    /// Usage: var events_json = NPCDataWarehouse.projectile_hit(attacker_ulid, target_ulid)
+    #[func]
+    pub fn projectile_hit(
+        &mut self,
+        attacker_ulid_bytes: PackedByteArray,
+        target_ulid_bytes: PackedByteArray,
+    ) -> Array<GString> {
+        let events: Vec<CombatEvent> = self
+            .resolve_projectile_hit(&attacker_ulid_bytes, &target_ulid_bytes)
+            .into_iter()
+            .collect();
+        Self::combat_events_to_json(&events)
     }
 
-    /// Get NPC current HP
-    pub fn get_npc_hp_internal(&self, ulid: &str) -> Option<f32> {
-        // Convert hex to bytes (legacy interface, should be refactored to take bytes)
-        if let Ok(ulid_bytes) = hex_to_bytes(ulid) {
-            // Get combat stats from ByteMap
-            if let Some(stats_json) = self
-                .npc_combat_stats
-                .get(&ulid_bytes)
-                .map(|v| v.value().clone())
-            {
-                if let Ok(combat_stats) = serde_json::from_str::<NPCCombatStats>(&stats_json) {
-                    return Some(combat_stats.hp);
-                }
-            }
+    /// Handle projectile hit and return the damage/death event as a Dictionary
+    /// Returns an empty array if the target was already dead
+    /// Usage: var events = NPCDataWarehouse.projectile_hit_events(attacker_ulid, target_ulid)
+    #[func]
+    pub fn projectile_hit_events(
+        &mut self,
+        attacker_ulid_bytes: PackedByteArray,
+        target_ulid_bytes: PackedByteArray,
+    ) -> Array<Dictionary> {
+        let mut godot_array = Array::new();
+        if let Some(event) = self.resolve_projectile_hit(&attacker_ulid_bytes, &target_ulid_bytes) {
+            godot_array.push(&self.combat_event_to_dict(&event));
         }
-        None
+        godot_array
     }
 
-    /// Combat tick - public for Arc access
-    /// Called by autonomous thread every 16ms (60fps)
-    // ============================================================================
-    // THREE-PHASE TICK SYSTEM
-    // ============================================================================
-    // Split into separate phases to prevent state corruption:
-    // 1. Combat Phase: Calculate damage, update HP, set states (ATTACKING, DAMAGED, DEAD)
-    // 2. Movement Phase: Handle wandering, calculate directions, update positions
-    // 3. Animation Phase: Update sprites based on states, clear temporary states
-
-    /// PHASE 1: COMBAT - Calculate damage, update HP, set behavioral states
-    /// This phase ONLY handles combat logic and state changes
-    /// Returns combat events for GDScript to handle VFX/sounds
-    pub fn tick_combat_phase(&self) -> Vec<CombatEvent> {
-        let mut events = Vec::new();
-        let now_ms = Self::get_current_time_ms();
-
-        // Get all active NPCs with positions
-        let active_npcs = self.get_active_npcs_with_positions();
-        if active_npcs.is_empty() {
-            return events;
+    /// Apply healing to an NPC
+    /// target_ulid_bytes: ULID of NPC to heal
+    /// heal_amount: Amount of HP to restore
+    /// hunger_gain: Hunger increase (hidden stat)
+    /// energy_gain: Energy increase (hidden stat)
+    /// Returns: New HP value
+    #[func]
+    pub fn apply_healing(
+        &self,
+        target_ulid_bytes: PackedByteArray,
+        heal_amount: f32,
+        hunger_gain: f32,
+        energy_gain: f32,
+    ) -> f32 {
+        if target_ulid_bytes.len() != 16 {
+            godot_error!("[HEALING] Invalid ULID bytes length");
+            return 0.0;
         }
 
-        // Debug: Count NPCs by faction (disabled - too spammy)
-        // static mut DEBUG_COUNT: u32 = 0;
-        // unsafe {
-        //     DEBUG_COUNT += 1;
-        //     if DEBUG_COUNT % 300 == 1 { // Log once every 5 seconds
-        //         let mut ally_count = 0;
-        //         let mut monster_count = 0;
-        //         for (_ulid, _x, _y, static_state, _behavioral_state, _hp, _max_hp, _attack) in &active_npcs {
-        //             let is_ally = (*static_state & NPCStaticState::ALLY.bits() as i32) != 0;
-        //             let is_monster = (*static_state & NPCStaticState::MONSTER.bits() as i32) != 0;
-        //             if is_ally { ally_count += 1; }
-        //             if is_monster { monster_count += 1; }
-        //         }
-        //         godot_print!("[COMBAT] {} active NPCs ({} allies, {} monsters)",
-        //             active_npcs.len(), ally_count, monster_count);
-        //     }
-        // }
-
-        // Find combat pairs (proximity + hostility checks)
-        let combat_pairs = self.find_combat_pairs(&active_npcs);
-
-        if combat_pairs.is_empty() {
-            return events; // No combat happening (removed spam logging)
-        }
+        let target_bytes: [u8; 16] = target_ulid_bytes.as_slice().try_into().unwrap();
 
-        // Log when we first find combat pairs
-        static mut FIRST_COMBAT_LOG: bool = false;
-        unsafe {
-            if !FIRST_COMBAT_LOG {
-                godot_print!("[PHASE 1: COMBAT] *** FIRST COMBAT PAIRS FOUND! Processing {} combat pairs ***", combat_pairs.len());
-                FIRST_COMBAT_LOG = true;
-            }
-        }
+        let new_hp = {
+            let _step = self.warehouse.hold_steps();
+            self.warehouse
+                .apply_healing(&target_bytes, heal_amount, hunger_gain, energy_gain)
+        };
+        self.nodes.apply_commands(&self.warehouse);
+        new_hp
+    }
 
-        // Process each combat pair
-        for (attacker_ulid_bytes, target_ulid_bytes, _distance) in combat_pairs {
-            // DEFENSIVE: Validate attacker and target are different
-            if attacker_ulid_bytes == target_ulid_bytes {
-                let attacker_hex = bytes_to_hex(&attacker_ulid_bytes);
-                self.log_error_once(
-                    "self_attack",
-                    &attacker_hex,
-                    &format!(
-                        "[COMBAT ERROR] NPC {} is attacking itself! Skipping.",
-                        &attacker_hex[0..8.min(attacker_hex.len())]
-                    ),
-                );
-                continue;
-            }
-
-            // DEFENSIVE: Validate both NPCs exist and are alive
-            // Check DEAD flag first (most reliable - updated immediately when NPC dies)
-            if let Some(state_str) = self
-                .npc_behavioral_state
-                .get(&attacker_ulid_bytes)
-                .map(|v| v.value().clone())
-            {
-                if let Ok(state) = state_str.parse::<i32>() {
-                    if (state & NPCState::DEAD.bits() as i32) != 0 {
-                        // Silently skip - NPC died this tick or earlier
-                        continue;
-                    }
-                }
-            }
-
-            // Convert to hex for legacy functions that still need it (will refactor later)
-            let attacker_ulid_hex = bytes_to_hex(&attacker_ulid_bytes);
-            let target_ulid_hex = bytes_to_hex(&target_ulid_bytes);
-
-            // Also check HP as fallback
-            if let Some(attacker_hp) = self.get_stat_value(&attacker_ulid_hex, "hp") {
-                if attacker_hp <= 0.0 {
-                    // HP is 0 but DEAD flag not set yet - skip silently
-                    continue;
-                }
-            } else {
-                self.log_error_once(
-                    "missing_hp",
-                    &attacker_ulid_hex,
-                    &format!(
-                        "[COMBAT ERROR] Attacker {} has no HP stat. Skipping.",
-                        &attacker_ulid_hex[0..8.min(attacker_ulid_hex.len())]
-                    ),
-                );
-                continue;
-            }
+    /// Get NPCStaticState constant value by name (combat types + factions)
+    /// Usage in GDScript: NPCDataWarehouse.get_static_state("MELEE") returns 1
+    #[func]
+    pub fn get_static_state(&self, state_name: GString) -> i32 {
+        match state_name.to_string().to_uppercase().as_str() {
+            "MELEE" => NPCStaticState::MELEE.to_i32(),
+            "RANGED" => NPCStaticState::RANGED.to_i32(),
+            "MAGIC" => NPCStaticState::MAGIC.to_i32(),
+            "HEALER" => NPCStaticState::HEALER.to_i32(),
+            "ALLY" => NPCStaticState::ALLY.to_i32(),
+            "MONSTER" => NPCStaticState::MONSTER.to_i32(),
+            "PASSIVE" => NPCStaticState::PASSIVE.to_i32(),
+            _ => 0,
+        }
+    }
 
-            if let Some(target_hp) = self.get_stat_value(&target_ulid_hex, "hp") {
-                if target_hp <= 0.0 {
-                    // Target already dead, skip silently (common case during combat)
-                    continue;
-                }
-            } else {
-                self.log_error_once(
-                    "missing_hp",
-                    &target_ulid_hex,
-                    &format!(
-                        "[COMBAT ERROR] Target {} has no HP stat. Skipping.",
-                        &target_ulid_hex[0..8.min(target_ulid_hex.len())]
-                    ),
+    /// Set world bounds for waypoint clamping (from BackgroundManager safe_rectangle)
+    /// Usage: NPCDataWarehouse.set_world_bounds(min_x, max_x, min_y, max_y)
+    /// Should be called when background loads/changes
+    /// Returns false (bounds unchanged) unless min < max on both axes
+    #[func]
+    pub fn set_world_bounds(&self, min_x: f32, max_x: f32, min_y: f32, max_y: f32) -> bool {
+        let _step = self.warehouse.hold_steps();
+        match self.warehouse.set_world_bounds(min_x, max_x, min_y, max_y) {
+            Ok(()) => {
+                godot_print!(
+                    "[RUST BOUNDS] Updated world bounds: X({} to {}), Y({} to {})",
+                    min_x,
+                    max_x,
+                    min_y,
+                    max_y
                 );
-                continue;
+                true
             }
-
-            // Check cooldown - use bytes directly
-            if !self.check_attack_cooldown(&attacker_ulid_bytes, now_ms) {
-                continue; // Still on cooldown
-            }
-
-            // Get attacker static state to check if RANGED
-            let attacker_static_state = self
-                .get_stat_value(&attacker_ulid_hex, "static_state")
-                .unwrap_or(0.0) as i32;
-            let is_ranged = (attacker_static_state & NPCStaticState::RANGED.bits() as i32) != 0;
-            let is_magic = (attacker_static_state & NPCStaticState::MAGIC.bits() as i32) != 0;
-
-            // Get attacker and target positions
-            let (attacker_x, attacker_y) = self
-                .get_npc_position_internal(&attacker_ulid_hex)
-                .unwrap_or((0.0, 0.0));
-            let (target_x, target_y) = self
-                .get_npc_position_internal(&target_ulid_hex)
-                .unwrap_or((0.0, 0.0));
-
-            // Update attacker cooldown - use bytes directly
-            self.update_cooldown(&attacker_ulid_bytes, now_ms);
-
-            // Set ATTACKING state on attacker (Rust manages all states)
-            self.add_attacking_state(&attacker_ulid_bytes);
-
-            // Generate attack event (for animation)
-            events.push(CombatEvent {
-                event_type: "attack".to_string(),
-                attacker_ulid: attacker_ulid_hex.clone(),
-                target_ulid: target_ulid_hex.clone(),
-                amount: 0.0,
-                attacker_animation: "attack".to_string(),
-                target_animation: "".to_string(),
-                target_x,
-                target_y,
-            });
-
-            // RANGED attacks (archers) use projectiles - GDScript handles collision and calls back
-            if is_ranged && !is_magic {
-                // Generate projectile event for GDScript to spawn arrow
-                // GDScript will read attacker position from attacker NPC node
-                events.push(CombatEvent {
-                    event_type: "projectile".to_string(),
-                    attacker_ulid: attacker_ulid_hex.clone(),
-                    target_ulid: target_ulid_hex.clone(),
-                    amount: 0.0, // Damage will be calculated on hit
-                    attacker_animation: "arrow".to_string(), // Projectile type
-                    target_animation: format!("{},{}", attacker_y, 300.0), // Encode attacker_y and arrow speed
-                    target_x,                                              // Target position
-                    target_y,
-                });
-                // Damage will be applied when GDScript calls projectile_hit()
-            } else {
-                // MELEE and MAGIC attacks: Apply damage instantly
-                let attacker_attack = self
-                    .get_stat_value(&attacker_ulid_hex, "attack")
-                    .unwrap_or(10.0);
-                let target_defense = self
-                    .get_stat_value(&target_ulid_hex, "defense")
-                    .unwrap_or(5.0);
-
-                // Calculate damage (heavily reduced formula for much slower, strategic combat)
-                // Formula: (attack / 6) - (defense / 8), minimum 1.5 damage
-                // This makes fights last much longer and animations more visible
-                let damage = ((attacker_attack / 6.0) - (target_defense / 8.0)).max(1.5);
-
-                // Log damage calculation for first few attacks
-                static mut DAMAGE_LOG_COUNT: i32 = 0;
-                unsafe {
-                    if DAMAGE_LOG_COUNT < 10 {
-                        godot_print!(
-                            "[COMBAT] Attacker ATK: {:.1} vs Target DEF: {:.1} = {:.1} damage",
-                            attacker_attack,
-                            target_defense,
-                            damage
-                        );
-                        DAMAGE_LOG_COUNT += 1;
-                    }
-                }
-
-                // Apply damage and get new HP
-                let target_hp = self.apply_damage(&target_ulid_hex, damage);
-
-                // Handle target state based on HP
-                if target_hp <= 0.0 {
-                    // Mark target as dead (Rust manages all states)
-                    self.mark_dead(&target_ulid_hex);
-
-                    // Generate death event
-                    events.push(CombatEvent {
-                        event_type: "death".to_string(),
-                        attacker_ulid: attacker_ulid_hex.clone(),
-                        target_ulid: target_ulid_hex.clone(),
-                        amount: damage,
-                        attacker_animation: "".to_string(),
-                        target_animation: "death".to_string(),
-                        target_x,
-                        target_y,
-                    });
-                } else {
-                    // Set DAMAGED state on target (Rust manages all states)
-                    self.add_damaged_state(&target_ulid_bytes);
-
-                    // Set aggro: target should now attack their attacker
-                    self.set_aggro_target(&target_ulid_bytes, &attacker_ulid_bytes);
-
-                    // Generate damage event
-                    events.push(CombatEvent {
-                        event_type: "damage".to_string(),
-                        attacker_ulid: attacker_ulid_hex.clone(),
-                        target_ulid: target_ulid_hex.clone(),
-                        amount: damage,
-                        attacker_animation: "".to_string(),
-                        target_animation: "hurt".to_string(),
-                        target_x,
-                        target_y,
-                    });
-                }
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                false
             }
         }
-
-        // Removed: Too spammy
-        // godot_print!("[PHASE 1: COMBAT] Generated {} combat events", events.len());
-        events
     }
 
-    /// PHASE 2: MOVEMENT - Handle wandering, calculate directions, update positions
-    /// This phase ONLY handles movement and position updates
-    /// Returns spawn events for new NPCs
-    pub fn tick_movement_phase(&self, delta: f32) -> Vec<CombatEvent> {
-        let mut events = Vec::new();
-        let now_ms = Self::get_current_time_ms();
-
-        // 0. INITIAL SPAWN: Check if this is the first tick and spawn minimal entities
-        let initial_spawn_events = self.check_initial_spawn(now_ms);
-        events.extend(initial_spawn_events);
-
-        // Get all active NPCs with positions
-        let active_npcs = self.get_active_npcs_with_positions();
-        if active_npcs.is_empty() {
-            return events;
-        }
-
-        // Reduced logging
-        // Removed: Too spammy
-        // static mut MOVE_LOG_COUNT: u32 = 0;
-        // unsafe {
-        //     MOVE_LOG_COUNT += 1;
-        //     if MOVE_LOG_COUNT % 60 == 1 {
-        //         godot_print!("[PHASE 2: MOVEMENT] Processing {} NPCs", active_npcs.len());
-        //     }
-        // }
-
-        // 1. Handle idle wandering (NPCs that are IDLE and not in combat will get random waypoints)
-        self.handle_idle_wandering(&active_npcs);
-
-        // 2. Calculate movement directions for all NPCs (pursue nearest hostile)
-        self.calculate_movement_directions(&active_npcs);
-
-        // 3. Apply waypoint movement (move NPCs towards their waypoints)
-        self.apply_waypoint_movement(&active_npcs, delta);
-
-        // 4. Check if we need to spawn monsters (Rust manages monster spawn timing)
-        let monster_spawn_events = self.check_spawn_wave(now_ms);
-        events.extend(monster_spawn_events);
+    /// Check if a state has a specific flag set
+    /// Usage: NPCDataWarehouse.has_state_flag(npc_state, "IDLE")
+    #[func]
+    pub fn has_state_flag(&self, state: i32, flag_name: GString) -> bool {
+        let flag_value = self.get_state(flag_name);
+        (state & flag_value) != 0
+    }
 
-        // 5. Check if we need to spawn allies (gradual ramp-up)
-        let ally_spawn_events = self.check_ally_spawn(now_ms);
-        events.extend(ally_spawn_events);
+    /// Combine multiple states using bitwise OR
+    /// Usage: NPCDataWarehouse.combine_states(state1, state2)
+    #[func]
+    pub fn combine_states(&self, state1: i32, state2: i32) -> i32 {
+        state1 | state2
+    }
 
-        // Removed: Too spammy
-        events
+    /// Remove a flag from a state using bitwise AND NOT
+    /// Usage: NPCDataWarehouse.remove_state_flag(npc_state, "IDLE")
+    #[func]
+    pub fn remove_state_flag(&self, state: i32, flag_name: GString) -> i32 {
+        let flag_value = self.get_state(flag_name);
+        state & !flag_value
     }
 
-    /// PHASE 3: ANIMATION - Update sprites based on states, clear temporary states
-    /// This phase ONLY handles visual updates and state cleanup
-    /// No events are returned as animations are updated directly
-    pub fn tick_animation_phase(&self) {
-        // Get all active NPCs INCLUDING dead ones (for death animation)
-        let active_npcs = self.get_active_npcs_for_animation();
-        if active_npcs.is_empty() {
-            return;
-        }
+    /// Add a flag to a state using bitwise OR
+    /// Usage: NPCDataWarehouse.add_state_flag(npc_state, "WALKING")
+    #[func]
+    pub fn add_state_flag(&self, state: i32, flag_name: GString) -> i32 {
+        let flag_value = self.get_state(flag_name);
+        state | flag_value
+    }
 
-        // DEBUG: Count dead NPCs in animation phase
-        let mut dead_count = 0;
-        for (ulid_bytes, _, _, _, _, _, _, _) in &active_npcs {
-            if let Some(state_str) = self
-                .npc_behavioral_state
-                .get(ulid_bytes)
-                .map(|v| v.value().clone())
-            {
-                if let Ok(state) = state_str.parse::<i32>() {
-                    if (state & NPCState::DEAD.bits() as i32) != 0 {
-                        dead_count += 1;
-                        let ulid_hex = bytes_to_hex(ulid_bytes);
-                        godot_print!(
-                            "[ANIM PHASE] Found DEAD NPC: {} (state={})",
-                            ulid_hex,
-                            state
-                        );
-                    }
-                }
+    /// Remove ATTACKING state flag after attack animation finishes
+    /// Called by GDScript animation_finished handler
+    /// Usage: NPCDataWarehouse.clear_attacking_state(ulid_bytes)
+    #[func]
+    pub fn clear_attacking_state(&self, ulid_bytes: PackedByteArray) {
+        let _step = self.warehouse.hold_steps();
+        if ulid_bytes.len() == 16 {
+            if let Ok(ulid_array) = TryInto::<[u8; 16]>::try_into(ulid_bytes.as_slice()) {
+                self.warehouse.remove_attacking_state(&ulid_array);
+            } else {
+                godot_error!("[COMBAT ERROR] Failed to convert PackedByteArray to [u8; 16]");
             }
-        }
-        if dead_count > 0 {
-            godot_print!(
-                "[ANIM PHASE] Processing {} NPCs, {} are DEAD",
-                active_npcs.len(),
-                dead_count
+        } else {
+            godot_error!(
+                "[COMBAT ERROR] Invalid ULID bytes length for clear_attacking_state: {} (expected 16)",
+                ulid_bytes.len()
             );
         }
-
-        // Update animations based on behavioral state (Rust controls animations)
-        self.update_npc_animations(&active_npcs);
-
-        // Clear temporary states (ATTACKING, DAMAGED) after animation duration
-        self.clear_expired_animation_states();
     }
 
-    /// Legacy tick_combat_internal - now calls three phases sequentially
-    /// Returns (events, death_positions) where death_positions is Vec<(x, y)>
-    pub fn tick_combat_internal(&self, delta: f32) -> (Vec<CombatEvent>, Vec<(f32, f32)>) {
-        let mut all_events = Vec::new();
-
-        // Reduced logging
-        static mut TICK_LOG_COUNT: u32 = 0;
-        unsafe {
-            TICK_LOG_COUNT += 1;
-            if TICK_LOG_COUNT % 60 == 1 {
-                // Log once per second
-                godot_print!("[TICK] === Starting three-phase tick ===");
-            }
-        }
-
-        // Phase 1: Combat (damage calculations and state changes)
-        let combat_events = self.tick_combat_phase();
-        all_events.extend(combat_events);
-
-        // Phase 2: Movement (position updates and spawning)
-        let movement_events = self.tick_movement_phase(delta);
-        all_events.extend(movement_events);
-
-        // Phase 3: Animation (visual updates)
-        self.tick_animation_phase();
-
-        // Phase 3.5: Global Regeneration (collect allies for healing every 5 seconds)
-        // DISABLED: Causing grey screen freeze
-        let now_ms = Self::get_current_time_ms();
-        // if self.check_regen_tick(now_ms) {
-        //     self.apply_regen_to_allies();
-        // }
-
-        // Phase 3.6: Process Regen Queue (heal one NPC per tick to avoid freezing)
-        // DISABLED: Causing grey screen freeze
-        // self.process_regen_queue();
-
-        // Phase 4: Cleanup (despawn dead NPCs AFTER they've played death animation)
-        // Returns death positions for GDScript to spawn effects
-        let death_positions = self.cleanup_dead_npcs(now_ms);
-
-        // Phase 4.5: Tick release effect pool (auto-return expired animations)
-        // DISABLED: Release effects moved to GDScript for thread safety
-        // self.release_pool.tick(now_ms);
-
-        // Reduced logging
-        static mut TICK_END_LOG: u32 = 0;
-        unsafe {
-            TICK_END_LOG += 1;
-            if TICK_END_LOG % 60 == 1 {
-                // Log once per second
-                godot_print!(
-                    "[TICK] === Completed three-phase tick with {} total events ===",
-                    all_events.len()
-                );
-            }
-        }
-
-        (all_events, death_positions)
-    }
-
-    /// Handle idle wandering for NPCs that are IDLE and not in COMBAT
-    /// Sets random waypoints within world bounds for NPCs to wander around
-    fn handle_idle_wandering(&self, npcs: &[([u8; 16], f32, f32, i32, i32, f32, f32, f32)]) {
-        use rand::Rng;
-        let mut rng = rand::rng();
-        let now_ms = Self::get_current_time_ms();
-
-        // Load world bounds
-        let min_x = f32::from_bits(self.world_min_x.load(Ordering::Relaxed));
-        let max_x = f32::from_bits(self.world_max_x.load(Ordering::Relaxed));
-        let min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
-        let max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
-
-        for (ulid_bytes, x, y, static_state, _behavioral_state, _, _, _) in npcs {
-            // Skip if scheduled for despawn (check first - most important)
-            let ulid_hex = bytes_to_hex(ulid_bytes);
-            let despawn_key = format!("despawn_at:{}", ulid_hex);
-            if self.storage.contains_key(&despawn_key) {
-                // Scheduled for despawn - skip all processing
-                continue;
-            }
-
-            // Get current behavioral state from ByteMap
-            let behavioral_state = if let Some(state_str) = self
-                .npc_behavioral_state
-                .get(ulid_bytes)
-                .map(|v| v.value().clone())
-            {
-                state_str
-                    .parse::<i32>()
-                    .unwrap_or(NPCState::IDLE.bits() as i32)
+    /// Remove DAMAGED state flag after hurt animation finishes
+    /// Called by GDScript animation_finished handler
+    /// Usage: NPCDataWarehouse.clear_damaged_state(ulid_bytes)
+    #[func]
+    pub fn clear_damaged_state(&self, ulid_bytes: PackedByteArray) {
+        let _step = self.warehouse.hold_steps();
+        if ulid_bytes.len() == 16 {
+            if let Ok(ulid_array) = TryInto::<[u8; 16]>::try_into(ulid_bytes.as_slice()) {
+                self.warehouse.remove_damaged_state(&ulid_array);
             } else {
-                NPCState::IDLE.bits() as i32
-            };
-
-            // Skip if DEAD
-            let is_dead = (behavioral_state & NPCState::DEAD.bits() as i32) != 0;
-            if is_dead {
-                continue;
-            }
-
-            // Only wander if IDLE and NOT in COMBAT
-            let is_idle = (behavioral_state & NPCState::IDLE.bits() as i32) != 0;
-            let in_combat = (behavioral_state & NPCState::COMBAT.bits() as i32) != 0;
-            let is_passive = (*static_state & NPCStaticState::PASSIVE.bits() as i32) != 0;
-
-            if is_idle && !in_combat && !is_passive {
-                // Convert ULID to hex for storage key
-                let ulid_hex = bytes_to_hex(ulid_bytes);
-
-                // Check if NPC already has a waypoint
-                let has_waypoint = self.npc_waypoints.contains_key(ulid_bytes);
-
-                // Check wander cooldown (don't wander too frequently)
-                let cooldown_key = format!("wander_cooldown:{}", ulid_hex);
-                let can_wander = if let Some(cooldown_str) = self.storage.get(&cooldown_key) {
-                    if let Ok(cooldown_until_ms) = cooldown_str.value().parse::<u64>() {
-                        now_ms >= cooldown_until_ms // Can wander if current time is past the cooldown time
-                    } else {
-                        true
-                    }
-                } else {
-                    true
-                };
-
-                if !has_waypoint && can_wander {
-                    // Determine faction-specific bounds (allies on left, monsters on right)
-                    let is_ally = (*static_state & NPCStaticState::ALLY.bits() as i32) != 0;
-                    let is_monster = (*static_state & NPCStaticState::MONSTER.bits() as i32) != 0;
-
-                    let (wander_min_x, wander_max_x) = if is_ally {
-                        // Allies wander on left side (friendly area)
-                        (min_x, min_x + (max_x - min_x) * 0.4)
-                    } else if is_monster {
-                        // Monsters wander on right side
-                        (min_x + (max_x - min_x) * 0.6, max_x)
-                    } else {
-                        // Default to full bounds
-                        (min_x, max_x)
-                    };
-
-                    // Generate random waypoint within faction bounds
-                    let target_x = rng.random_range(wander_min_x..wander_max_x);
-                    let target_y = rng.random_range(min_y..max_y);
-
-                    // Store waypoint in ByteMap
-                    self.npc_waypoints
-                        .insert(*ulid_bytes, format!("{},{}", target_x, target_y));
-
-                    // Update wander cooldown - set to 3 seconds in the future
-                    self.storage
-                        .insert(cooldown_key, (now_ms + 3000).to_string());
-
-                    // Set state to WALKING (remove IDLE, add WALKING, keep other flags like COMBAT if present)
-                    let new_state = (behavioral_state
-                        & !(NPCState::IDLE.bits() as i32 | NPCState::ATTACKING.bits() as i32))
-                        | NPCState::WALKING.bits() as i32;
-                    godot_print!(
-                        "[RUST WANDER] ULID {} - Setting waypoint: old_state={}, new_state={}",
-                        ulid_hex,
-                        behavioral_state,
-                        new_state
-                    );
-                    self.set_behavioral_state(ulid_bytes, new_state, "handle_idle_wandering");
-
-                    // Verify the write worked
-                    if let Some(verify_str) = self
-                        .npc_behavioral_state
-                        .get(ulid_bytes)
-                        .map(|v| v.value().clone())
-                    {
-                        let verify_state = verify_str.parse::<i32>().unwrap_or(0);
-                        godot_print!(
-                            "[RUST WANDER VERIFY] ULID {} - verified state after write: {}",
-                            ulid_hex,
-                            verify_state
-                        );
-                    }
-                }
+                godot_error!("[COMBAT ERROR] Failed to convert PackedByteArray to [u8; 16]");
             }
+        } else {
+            godot_error!(
+                "[COMBAT ERROR] Invalid ULID bytes length for clear_damaged_state: {} (expected 16)",
+                ulid_bytes.len()
+            );
         }
     }
 
-    /// Calculate movement directions for all NPCs (pursue nearest hostile)
-    /// Stores movement direction in HolyMap as "move_dir:ulid" -> "x,y"
-    fn calculate_movement_directions(
-        &self,
-        npcs: &[([u8; 16], f32, f32, i32, i32, f32, f32, f32)],
-    ) {
-        // static mut DIR_LOG_COUNT: u32 = 0;
-        // unsafe {
-        //     DIR_LOG_COUNT += 1;
-        //     if DIR_LOG_COUNT == 1 {
-        //         godot_print!("[RUST CALC] calculate_movement_directions called with {} NPCs", npcs.len());
-        //     }
-        // }
-
-        for (ulid_bytes_a, x_a, y_a, static_state_a, behavioral_state_a, _, _, _) in npcs {
-            // Skip if dead
-            if (*behavioral_state_a & NPCState::DEAD.bits() as i32) != 0 {
-                continue;
-            }
-
-            // Skip if PASSIVE
-            if (*static_state_a & NPCStaticState::PASSIVE.bits() as i32) != 0 {
-                continue;
-            }
-
-            // Convert ULID bytes to hex for storage keys (only once)
-            let ulid_hex_a = bytes_to_hex(ulid_bytes_a);
-
-            let mut nearest_hostile: Option<(f32, f32, f32)> = None; // (x, y, distance)
-            let mut nearest_distance = f32::MAX;
-
-            // AGGRO SYSTEM: Check if this NPC has an aggro target (from being attacked)
-            if let Some(aggro_target_hex) = self
-                .npc_aggro_targets
-                .get(ulid_bytes_a)
-                .map(|v| v.value().clone())
-            {
-                // Find the aggro target in the NPC list
-                for (ulid_bytes_b, x_b, y_b, static_state_b, behavioral_state_b, _, _, _) in npcs {
-                    let ulid_hex_b = bytes_to_hex(ulid_bytes_b);
-
-                    // Check if this is our aggro target
-                    if ulid_hex_b == aggro_target_hex {
-                        // Verify target is still alive
-                        if (*behavioral_state_b & NPCState::DEAD.bits() as i32) == 0 {
-                            let distance = Self::distance(*x_a, *y_a, *x_b, *y_b);
-                            nearest_hostile = Some((*x_b, *y_b, distance));
-                            nearest_distance = distance;
-                            break; // Prioritize aggro target
-                        } else {
-                            // Aggro target is dead, clear it
-                            self.npc_aggro_targets.remove(ulid_bytes_a).map(|(_, v)| v);
-                        }
-                    }
-                }
-            }
-
-            // If no aggro target (or aggro target is dead), find nearest hostile NPC
-            if nearest_hostile.is_none() {
-                for (ulid_bytes_b, x_b, y_b, static_state_b, behavioral_state_b, _, _, _) in npcs {
-                    if ulid_bytes_a == ulid_bytes_b {
-                        continue; // Skip self
-                    }
-
-                    // Skip if dead
-                    if (*behavioral_state_b & NPCState::DEAD.bits() as i32) != 0 {
-                        continue;
-                    }
-
-                    // Check if hostile
-                    if !Self::are_factions_hostile(*static_state_a, *static_state_b) {
-                        continue;
-                    }
-
-                    let distance = Self::distance(*x_a, *y_a, *x_b, *y_b);
-                    if distance < nearest_distance {
-                        nearest_distance = distance;
-                        nearest_hostile = Some((*x_b, *y_b, distance));
-                    }
-                }
-            }
-
-            // Calculate waypoint (target position to move toward)
-            // static mut HOSTILE_LOG_COUNT: u32 = 0;
-            // unsafe {
-            //     HOSTILE_LOG_COUNT += 1;
-            //     if HOSTILE_LOG_COUNT < 5 {
-            //         if nearest_hostile.is_some() {
-            //             godot_print!("[RUST HOSTILE] Found hostile target");
-            //         } else {
-            //             godot_print!("[RUST HOSTILE] No hostile target found");
-            //         }
-            //     }
-            // }
-
-            if let Some((target_x, target_y, distance)) = nearest_hostile {
-                // COMBAT ENGAGEMENT RANGE: Only enter combat if enemy is within 400px
-                // This prevents NPCs from permanently being in combat state when enemies are far away
-                const COMBAT_DETECTION_RANGE: f32 = 400.0;
-
-                if distance > COMBAT_DETECTION_RANGE {
-                    // Enemy too far - clear combat state and let idle wandering take over
-                    if let Some(state_str) = self
-                        .npc_behavioral_state
-                        .get(ulid_bytes_a)
-                        .map(|v| v.value().clone())
-                    {
-                        if let Ok(current_state) = state_str.parse::<i32>() {
-                            if (current_state & NPCState::COMBAT.bits() as i32) != 0 {
-                                // Remove COMBAT flag
-                                let new_state = current_state & !(NPCState::COMBAT.bits() as i32);
-                                self.npc_behavioral_state
-                                    .insert(*ulid_bytes_a, new_state.to_string());
-                            }
-                        }
-                    }
-                    continue; // Skip movement calculation for distant enemies
-                }
-
-                // Get attack range for this NPC
-                let attack_range = Self::get_attack_range(*static_state_a);
-
-                // Check if this is a RANGED unit (archer)
-                let is_ranged = (*static_state_a & NPCStaticState::RANGED.bits() as i32) != 0;
-
-                // RANGED units (archers) use kiting behavior
-                if is_ranged {
-                    let min_safe_distance = 100.0; // Archers want to keep at least 100px from enemies
-
-                    if distance < min_safe_distance {
-                        // TOO CLOSE - Retreat away from enemy (kiting)
-                        // Calculate retreat position: move away from target
-                        let dir_x = *x_a - target_x;
-                        let dir_y = *y_a - target_y;
-                        let dir_len = (dir_x * dir_x + dir_y * dir_y).sqrt();
-
-                        if dir_len > 0.01 {
-                            // Normalize and scale to retreat distance
-                            let retreat_distance = 150.0; // Retreat 150 pixels away
-                            let retreat_x = *x_a + (dir_x / dir_len) * retreat_distance;
-                            let retreat_y = *y_a + (dir_y / dir_len) * retreat_distance;
-
-                            // Clamp waypoint to world bounds (prevent NPCs from going off-screen)
-                            // Read bounds atomically (can be updated by GDScript from BackgroundManager)
-                            let min_x = f32::from_bits(self.world_min_x.load(Ordering::Relaxed));
-                            let max_x = f32::from_bits(self.world_max_x.load(Ordering::Relaxed));
-                            let min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
-                            let max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
-
-                            let clamped_x = retreat_x.clamp(min_x, max_x);
-                            let clamped_y = retreat_y.clamp(min_y, max_y);
-
-                            // Store retreat waypoint (clamped)
-                            self.npc_waypoints
-                                .insert(*ulid_bytes_a, format!("{},{}", clamped_x, clamped_y));
-
-                            // Update behavioral state to COMBAT only (remove IDLE)
-                            // WALKING will be set in apply_waypoint_movement when actually moving
-                            let current_state = *behavioral_state_a;
-                            let new_state = (current_state & !(NPCState::IDLE.bits() as i32))
-                                | NPCState::COMBAT.bits() as i32;
-
-                            self.npc_behavioral_state
-                                .insert(*ulid_bytes_a, new_state.to_string());
-                        }
-                    } else if distance > attack_range {
-                        // TOO FAR - Move toward target to get in range
-                        // Clamp waypoint to world bounds
-                        let min_x = f32::from_bits(self.world_min_x.load(Ordering::Relaxed));
-                        let max_x = f32::from_bits(self.world_max_x.load(Ordering::Relaxed));
-                        let min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
-                        let max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
-
-                        let clamped_x = target_x.clamp(min_x, max_x);
-                        let clamped_y = target_y.clamp(min_y, max_y);
-
-                        self.npc_waypoints
-                            .insert(*ulid_bytes_a, format!("{},{}", clamped_x, clamped_y));
-
-                        // Update behavioral state to COMBAT only (remove IDLE)
-                        // WALKING will be set in apply_waypoint_movement when actually moving
-                        let current_state = *behavioral_state_a;
-                        let new_state = (current_state & !(NPCState::IDLE.bits() as i32))
-                            | NPCState::COMBAT.bits() as i32;
-                        self.npc_behavioral_state
-                            .insert(*ulid_bytes_a, new_state.to_string());
-                    } else {
-                        // OPTIMAL RANGE (100-200px) - Stop and shoot
-                        self.npc_waypoints.remove(ulid_bytes_a).map(|(_, v)| v);
-
-                        // Update behavioral state to COMBAT only (remove WALKING, remove IDLE)
-                        let current_state = *behavioral_state_a;
-                        let new_state = (current_state
-                            & !(NPCState::IDLE.bits() as i32)
-                            & !(NPCState::WALKING.bits() as i32))
-                            | NPCState::COMBAT.bits() as i32;
-                        self.npc_behavioral_state
-                            .insert(*ulid_bytes_a, new_state.to_string());
-                    }
-                } else {
-                    // MELEE/MAGIC units: Simple pursue behavior (original logic)
-                    if distance > attack_range {
-                        // Move toward target (clamp to world bounds)
-                        let min_x = f32::from_bits(self.world_min_x.load(Ordering::Relaxed));
-                        let max_x = f32::from_bits(self.world_max_x.load(Ordering::Relaxed));
-                        let min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
-                        let max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
-
-                        let clamped_x = target_x.clamp(min_x, max_x);
-                        let clamped_y = target_y.clamp(min_y, max_y);
-
-                        self.npc_waypoints
-                            .insert(*ulid_bytes_a, format!("{},{}", clamped_x, clamped_y));
-
-                        // Update behavioral state to COMBAT only (remove IDLE)
-                        // WALKING will be set in apply_waypoint_movement when actually moving
-                        let current_state = *behavioral_state_a;
-                        let new_state = (current_state & !(NPCState::IDLE.bits() as i32))
-                            | NPCState::COMBAT.bits() as i32;
-                        self.npc_behavioral_state
-                            .insert(*ulid_bytes_a, new_state.to_string());
-                    } else {
-                        // In range - stop moving
-                        self.npc_waypoints.remove(ulid_bytes_a).map(|(_, v)| v);
-
-                        // Update behavioral state: IDLE | COMBAT (remove WALKING)
-                        let current_state = *behavioral_state_a;
-                        let had_walking = (current_state & NPCState::WALKING.bits() as i32) != 0;
-                        let new_state = (current_state & !(NPCState::WALKING.bits() as i32))
-                            | NPCState::IDLE.bits() as i32
-                            | NPCState::COMBAT.bits() as i32;
-
-                        if had_walking {
-                            static mut MELEE_IN_RANGE_LOG: u32 = 0;
-                            unsafe {
-                                MELEE_IN_RANGE_LOG += 1;
-                                if MELEE_IN_RANGE_LOG <= 10 {
-                                    godot_print!("[COMBAT MOVEMENT] ULID {} - Melee in attack range, removing WALKING: {} -> {}",
-                                        ulid_hex_a, current_state, new_state);
-                                }
-                            }
-                        }
-
-                        self.npc_behavioral_state
-                            .insert(*ulid_bytes_a, new_state.to_string());
-                    }
-                }
-            } else {
-                // No enemies - only clear COMBAT state, don't touch idle wandering waypoints!
-                // Idle wandering waypoints are managed by handle_idle_wandering()
-                // We should only clear combat-related state here
-                let current_state = *behavioral_state_a;
-
-                // Only modify state if NPC was in COMBAT
-                if (current_state & NPCState::COMBAT.bits() as i32) != 0 {
-                    // Remove COMBAT flag, but keep WALKING/IDLE as-is (for idle wandering)
-                    let mut new_state = current_state & !(NPCState::COMBAT.bits() as i32);
-
-                    // IMPORTANT: If no movement state flags are set, add IDLE to prevent NONE state
-                    // This prevents archers/ranged units from going into NONE state after combat
-                    let has_walking = (new_state & NPCState::WALKING.bits() as i32) != 0;
-                    let has_idle = (new_state & NPCState::IDLE.bits() as i32) != 0;
-                    let has_attacking = (new_state & NPCState::ATTACKING.bits() as i32) != 0;
-
-                    if !has_walking && !has_idle && !has_attacking {
-                        new_state |= NPCState::IDLE.bits() as i32;
-                    }
-
-                    self.npc_behavioral_state
-                        .insert(*ulid_bytes_a, new_state.to_string());
+    /// Get NPC waypoint (target position calculated by Rust AI)
+    /// Returns PackedFloat32Array [x, y] world position, or empty array if no waypoint
+    /// Usage: var waypoint = NPCDataWarehouse.get_npc_waypoint(ulid_bytes)
+    #[func]
+    pub fn get_npc_waypoint(&self, ulid_bytes: PackedByteArray) -> PackedFloat32Array {
+        if ulid_bytes.len() == 16 {
+            if let Ok(ulid_array) = TryInto::<[u8; 16]>::try_into(ulid_bytes.as_slice()) {
+                if let Some(waypoint) = self
+                    .warehouse
+                    .npc_waypoints
+                    .get(&ulid_array)
+                    .map(|v| *v.value())
+                {
+                    return PackedFloat32Array::from(&[waypoint.x, waypoint.y]);
                 }
-                // If not in combat, leave state alone (might be idle wandering with WALKING state)
             }
         }
+        // No waypoint or invalid ULID - return empty array
+        PackedFloat32Array::new()
     }
 
-    /// Apply waypoint movement - move NPCs towards their waypoints
-    /// Called every combat tick with delta time
-    /// Rust directly updates both the position data AND the Node2D visual position
-    fn apply_waypoint_movement(
+    // ===== COMBAT SYSTEM FUNCTIONS =====
+
+    /// Register an NPC for combat processing
+    /// Usage: NPCDataWarehouse.register_npc_for_combat(ulid_bytes, static_state, behavioral_state, max_hp, attack, defense)
+    /// ulid_bytes: PackedByteArray (16 bytes) - raw ULID bytes from stats.ulid
+    #[func]
+    pub fn register_npc_for_combat(
         &self,
-        npcs: &[([u8; 16], f32, f32, i32, i32, f32, f32, f32)],
-        delta_time: f32,
+        ulid_bytes: PackedByteArray,
+        static_state: i32,
+        behavioral_state: i32,
+        max_hp: f32,
+        attack: f32,
+        defense: f32,
     ) {
-        const MOVEMENT_SPEED: f32 = 80.0; // pixels per second (increased for smoother visible movement)
-        const LERP_WEIGHT: f32 = 0.15; // Smoothing factor (0.0 = no movement, 1.0 = instant)
-
-        static mut MOVEMENT_LOG_COUNT: u32 = 0;
-        unsafe {
-            MOVEMENT_LOG_COUNT += 1;
-            if MOVEMENT_LOG_COUNT <= 5 {
-                // Removed spam log
-            }
-        }
-
-        for (ulid_bytes, _x, _y, _static_state, _behavioral_state, _, _, _) in npcs {
-            // Convert bytes to hex for storage key lookup
-            let ulid_hex = bytes_to_hex(ulid_bytes);
-
-            // Get current position from npc_positions ByteMap (stored as "x,y")
-            let pos_str = self
-                .npc_positions
-                .get(ulid_bytes)
-                .map(|v| v.value().clone())
-                .unwrap_or_default();
-            let coords: Vec<&str> = pos_str.split(',').collect();
-            if coords.len() != 2 {
-                // static mut POS_FAIL_COUNT: u32 = 0;
-                // unsafe {
-                //     POS_FAIL_COUNT += 1;
-                //     if POS_FAIL_COUNT < 3 {
-                //         godot_print!("[RUST MOVEMENT] Invalid position for NPC: '{}'", pos_str);
-                //     }
-                // }
-                continue;
-            }
-            let current_x: f32 = coords[0].parse().unwrap_or(0.0);
-            let current_y: f32 = coords[1].parse().unwrap_or(0.0);
-
-            // Get waypoint from ByteMap (ULID bytes -> "x,y")
-            if let Some(waypoint_value) = self
-                .npc_waypoints
-                .get(ulid_bytes)
-                .map(|v| v.value().clone())
-            {
-                // Removed spam logging
-                let waypoint_coords: Vec<&str> = waypoint_value.split(',').collect();
-                if waypoint_coords.len() != 2 {
-                    continue;
-                }
-                let target_x: f32 = waypoint_coords[0].parse().unwrap_or(0.0);
-                let target_y: f32 = waypoint_coords[1].parse().unwrap_or(0.0);
-
-                // Calculate direction to waypoint
-                let dx = target_x - current_x;
-                let dy = target_y - current_y;
-                let distance = (dx * dx + dy * dy).sqrt();
-
-                if distance > 1.0 {
-                    // Calculate target position with smooth movement
-                    let move_distance = MOVEMENT_SPEED * delta_time;
-                    let move_ratio = (move_distance / distance).min(1.0);
-
-                    let unclamped_x = current_x + dx * move_ratio;
-                    let unclamped_y = current_y + dy * move_ratio;
-
-                    // Load world bounds for clamping
-                    let world_min_x = f32::from_bits(self.world_min_x.load(Ordering::Relaxed));
-                    let world_max_x = f32::from_bits(self.world_max_x.load(Ordering::Relaxed));
-                    let world_min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
-                    let world_max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
-
-                    // Clamp position to world bounds to prevent NPCs from leaving viewport
-                    let target_x = unclamped_x.clamp(world_min_x, world_max_x);
-                    let target_y = unclamped_y.clamp(world_min_y, world_max_y);
-
-                    // Store normalized movement direction for sprite flipping
-                    let dir_x = dx / distance;
-                    let dir_y = dy / distance;
-                    self.npc_move_directions
-                        .insert(*ulid_bytes, format!("{},{}", dir_x, dir_y));
-
-                    // Update position in npc_positions ByteMap (data store)
-                    self.npc_positions
-                        .insert(*ulid_bytes, format!("{},{}", target_x, target_y));
-
-                    // Set WALKING state since NPC is actually moving
-                    // CRITICAL: Remove IDLE when adding WALKING (mutually exclusive)
-                    if let Some(state_str) = self
-                        .npc_behavioral_state
-                        .get(ulid_bytes)
-                        .map(|v| v.value().clone())
-                    {
-                        if let Ok(current_state) = state_str.parse::<i32>() {
-                            let has_combat = (current_state & NPCState::COMBAT.bits() as i32) != 0;
-                            let has_walking =
-                                (current_state & NPCState::WALKING.bits() as i32) != 0;
-
-                            // Add WALKING flag and remove IDLE (mutually exclusive)
-                            if !has_walking {
-                                let new_state = (current_state & !(NPCState::IDLE.bits() as i32))
-                                    | NPCState::WALKING.bits() as i32;
-                                self.npc_behavioral_state
-                                    .insert(*ulid_bytes, new_state.to_string());
-
-                                // Removed spam logging
-                            }
-                        }
-                    }
-
-                    // Update Node2D visual position with lerp for smooth interpolation
-                    for pool_entry in self.active_npc_pool.iter() {
-                        let npc = pool_entry.value();
-                        if &npc.ulid == ulid_bytes {
-                            // Clone the Gd handle (creates new reference to same node)
-                            let mut node = npc.node.clone();
-                            let current_visual_pos = node.get_position();
-
-                            // Lerp from current visual position to target position for smooth movement
-                            let unclamped_x = current_visual_pos.x
-                                + (target_x - current_visual_pos.x) * LERP_WEIGHT;
-                            let unclamped_y = current_visual_pos.y
-                                + (target_y - current_visual_pos.y) * LERP_WEIGHT;
-
-                            // Clamp the lerped visual position to world bounds as well
-                            let final_x = unclamped_x.clamp(world_min_x, world_max_x);
-                            let final_y = unclamped_y.clamp(world_min_y, world_max_y);
-
-                            node.set_position(Vector2::new(final_x, final_y));
-                            break;
-                        }
-                    }
-                    // Removed spam logging
-                } else {
-                    // Reached waypoint! Clear it and movement direction
-                    self.npc_waypoints.remove(ulid_bytes).map(|(_, v)| v);
-                    self.npc_move_directions.remove(ulid_bytes).map(|(_, v)| v);
-
-                    // Set state to IDLE (remove WALKING and ATTACKING flags, add IDLE, keep other flags like COMBAT)
-                    if let Some(state_str) = self
-                        .npc_behavioral_state
-                        .get(ulid_bytes)
-                        .map(|v| v.value().clone())
-                    {
-                        if let Ok(current_state) = state_str.parse::<i32>() {
-                            // Remove WALKING and ATTACKING, add IDLE (keep COMBAT flag if present)
-                            let new_state = (current_state
-                                & !(NPCState::WALKING.bits() as i32
-                                    | NPCState::ATTACKING.bits() as i32))
-                                | NPCState::IDLE.bits() as i32;
-                            self.npc_behavioral_state
-                                .insert(*ulid_bytes, new_state.to_string());
-                        }
-                    }
-                }
-            }
+        let _step = self.warehouse.hold_steps();
+        // Convert PackedByteArray to [u8; 16]
+        let ulid_slice = ulid_bytes.as_slice();
+        if ulid_slice.len() != 16 {
+            godot_error!("[COMBAT ERROR] Invalid ULID bytes in register_npc_for_combat: expected 16 bytes, got {}", ulid_slice.len());
+            return;
         }
-    }
-
-    /// Update NPC animations based on behavioral state (Rust controls animations)
-    /// Called every frame during combat tick
-    /// Animation names match SpriteFrames: "idle", "walking", "attacking", "hurt", "dead"
-    fn update_npc_animations(&self, npcs: &[([u8; 16], f32, f32, i32, i32, f32, f32, f32)]) {
-        for (ulid_bytes, _, _, _static_state, _old_behavioral_state, _, _, _) in npcs {
-            // Fetch the CURRENT behavioral state (not the stale one from npcs array)
-            // This is important because apply_waypoint_movement() may have updated it
-            let behavioral_state = if let Some(state_str) = self
-                .npc_behavioral_state
-                .get(ulid_bytes)
-                .map(|v| v.value().clone())
-            {
-                let state = state_str
-                    .parse::<i32>()
-                    .unwrap_or(NPCState::IDLE.bits() as i32);
-
-                // Removed spam logging
-                state
-            } else {
-                NPCState::IDLE.bits() as i32
-            };
-
-            // Determine animation based on behavioral state (priority order)
-            let animation_name = if (behavioral_state & NPCState::DEAD.bits() as i32) != 0 {
-                "dead"
-            } else if (behavioral_state & NPCState::DAMAGED.bits() as i32) != 0 {
-                "hurt"
-            } else if (behavioral_state & NPCState::ATTACKING.bits() as i32) != 0 {
-                "attacking"
-            } else if (behavioral_state & NPCState::WALKING.bits() as i32) != 0 {
-                "walking"
-            } else {
-                "idle"
-            };
-
-            // Log death animation (always log, not just first few times)
-            if animation_name == "dead" {
-                let ulid_hex = bytes_to_hex(ulid_bytes);
-                godot_print!(
-                    "[DEATH ANIM] Playing death animation for ULID {} (state={})",
-                    ulid_hex,
-                    behavioral_state
-                );
-            }
-
-            // Removed spam logging
-
-            // Find the NPC in the active pool and update its animation
-            for pool_entry in self.active_npc_pool.iter() {
-                let npc = pool_entry.value();
-                if &npc.ulid == ulid_bytes {
-                    // Update animation and sprite direction
-                    if let Some(ref sprite) = npc.animated_sprite {
-                        let mut sprite_mut = sprite.clone();
-                        let new_anim = StringName::from(animation_name);
-
-                        // Log when setting death animation
-                        if animation_name == "dead" {
-                            let ulid_hex = bytes_to_hex(ulid_bytes);
-                            let current_anim = sprite_mut.get_animation();
-                            godot_print!("[DEATH ANIM SET] ULID {} - Setting animation from '{}' to 'dead', playing={}",
-                                ulid_hex, current_anim, sprite_mut.is_playing());
-                        }
-
-                        // Check if animation changed
-                        let current_anim = sprite_mut.get_animation();
-                        let animation_changed = current_anim != new_anim;
-
-                        // Set animation (Godot is smart about redundant calls)
-                        sprite_mut.set_animation(&new_anim);
-
-                        // Only call play() if:
-                        // 1. Animation changed (including to "dead")
-                        // 2. Animation is not currently playing (for non-death animations)
-                        // IMPORTANT: Never replay death animation once it's already playing/finished
-                        if animation_name == "dead" {
-                            // Only play death animation if it just changed from something else
-                            if animation_changed {
-                                sprite_mut.play();
-                            }
-                            // Otherwise, let it finish naturally (loop=false handles staying on last frame)
-                        } else if animation_changed {
-                            // Non-death animation changed - play it
-                            sprite_mut.play();
-                        } else if !sprite_mut.is_playing() {
-                            // Animation didn't change but stopped playing (non-death only)
-                            sprite_mut.play();
-                        }
-                        // For death animation: only play once when it first changes to "dead"
-                        // After that, let it finish and stay on last frame (loop=false in SpriteFrames)
-
-                        // SPRITE FLIPPING: Determine which way the sprite should face
-                        // Skip flipping for dead NPCs (death animation should stay as-is)
-                        let is_dead = (behavioral_state & NPCState::DEAD.bits() as i32) != 0;
-
-                        let should_flip = if is_dead {
-                            // Keep current flip state for dead NPCs
-                            sprite_mut.is_flipped_h()
-                        } else if (behavioral_state & NPCState::COMBAT.bits() as i32) != 0 {
-                            // PRIORITY: In combat - face the combat target
-                            if let Some(aggro_target_hex) = self
-                                .npc_aggro_targets
-                                .get(ulid_bytes)
-                                .map(|v| v.value().clone())
-                            {
-                                // Find the target's position
-                                if let Ok(target_ulid_bytes) = hex_to_bytes(&aggro_target_hex) {
-                                    if let Some(target_pos_str) = self
-                                        .npc_positions
-                                        .get(&target_ulid_bytes)
-                                        .map(|v| v.value().clone())
-                                    {
-                                        // Get current position
-                                        if let Some(current_pos_str) = self
-                                            .npc_positions
-                                            .get(ulid_bytes)
-                                            .map(|v| v.value().clone())
-                                        {
-                                            let current_parts: Vec<&str> = current_pos_str.split(',').collect();
-                                            let target_parts: Vec<&str> = target_pos_str.split(',').collect();
-
-                                            if current_parts.len() == 2 && target_parts.len() == 2 {
-                                                if let (Ok(curr_x), Ok(target_x)) = (
-                                                    current_parts[0].parse::<f32>(),
-                                                    target_parts[0].parse::<f32>(),
-                                                ) {
-                                                    target_x < curr_x // Flip if target is to the left
-                                                } else {
-                                                    false
-                                                }
-                                            } else {
-                                                false
-                                            }
-                                        } else {
-                                            false
-                                        }
-                                    } else {
-                                        // Target position not found, fall through to move_dir logic
-                                        if let Some(move_dir) = self
-                                            .npc_move_directions
-                                            .get(ulid_bytes)
-                                            .map(|v| v.value().clone())
-                                        {
-                                            let parts: Vec<&str> = move_dir.split(',').collect();
-                                            if parts.len() == 2 {
-                                                if let Ok(dir_x) = parts[0].parse::<f32>() {
-                                                    dir_x < 0.0
-                                                } else {
-                                                    false
-                                                }
-                                            } else {
-                                                false
-                                            }
-                                        } else {
-                                            false
-                                        }
-                                    }
-                                } else {
-                                    false
-                                }
-                            } else {
-                                // No aggro target but in combat - use move direction
-                                if let Some(move_dir) = self
-                                    .npc_move_directions
-                                    .get(ulid_bytes)
-                                    .map(|v| v.value().clone())
-                                {
-                                    let parts: Vec<&str> = move_dir.split(',').collect();
-                                    if parts.len() == 2 {
-                                        if let Ok(dir_x) = parts[0].parse::<f32>() {
-                                            dir_x < 0.0
-                                        } else {
-                                            false
-                                        }
-                                    } else {
-                                        false
-                                    }
-                                } else {
-                                    false
-                                }
-                            }
-                        } else if let Some(move_dir) = self
-                            .npc_move_directions
-                            .get(ulid_bytes)
-                            .map(|v| v.value().clone())
-                        {
-                            // Has movement direction - parse it
-                            let parts: Vec<&str> = move_dir.split(',').collect();
-                            if parts.len() == 2 {
-                                if let Ok(dir_x) = parts[0].parse::<f32>() {
-                                    dir_x < 0.0 // Flip if moving left (negative x)
-                                } else {
-                                    false
-                                }
-                            } else {
-                                false
-                            }
-                        } else if let Some(waypoint) = self
-                            .npc_waypoints
-                            .get(ulid_bytes)
-                            .map(|v| v.value().clone())
-                        {
-                            // Has waypoint - compare with current position
-                            let current_pos = self
-                                .npc_positions
-                                .get(ulid_bytes)
-                                .map(|v| v.value().clone());
-                            if let Some(current_pos_str) = current_pos {
-                                let current_parts: Vec<&str> = current_pos_str.split(',').collect();
-                                let waypoint_parts: Vec<&str> = waypoint.split(',').collect();
-                                if current_parts.len() == 2 && waypoint_parts.len() == 2 {
-                                    if let (Ok(curr_x), Ok(target_x)) = (
-                                        current_parts[0].parse::<f32>(),
-                                        waypoint_parts[0].parse::<f32>(),
-                                    ) {
-                                        target_x < curr_x // Flip if target is to the left
-                                    } else {
-                                        false
-                                    }
-                                } else {
-                                    false
-                                }
-                            } else {
-                                false
-                            }
-                        } else {
-                            // Default: face right (don't flip) for allies, face left (flip) for monsters
-                            let static_state = if let Some(stats_json) = self
-                                .npc_combat_stats
-                                .get(ulid_bytes)
-                                .map(|v| v.value().clone())
-                            {
-                                if let Ok(combat_stats) =
-                                    serde_json::from_str::<NPCCombatStats>(&stats_json)
-                                {
-                                    combat_stats.static_state
-                                } else {
-                                    0
-                                }
-                            } else {
-                                0
-                            };
-
-                            let is_monster =
-                                (static_state & NPCStaticState::MONSTER.bits() as i32) != 0;
-                            is_monster // Monsters face left by default (flip), allies face right
-                        };
-
-                        // Apply horizontal flip
-                        sprite_mut.set_flip_h(should_flip);
-                    }
 
-                    // Sync behavioral state to GDScript property (for chat UI and other systems)
-                    let mut node = npc.node.clone();
-                    let state_variant = Variant::from(behavioral_state);
-                    let _ = node.set("current_state", &state_variant);
+        let mut ulid_array = [0u8; 16];
+        ulid_array.copy_from_slice(ulid_slice);
 
-                    break;
-                }
-            }
-        }
+        self.warehouse.register_npc_for_combat_internal(
+            &ulid_array,
+            static_state,
+            behavioral_state,
+            max_hp,
+            attack,
+            defense,
+        );
     }
 
-    /// Get all active NPCs with their positions
-    /// Returns: Vec<(ulid_bytes, x, y, static_state, behavioral_state, hp, attack, defense)>
-    /// Get active NPCs for animation phase (includes DEAD NPCs for death animation)
-    fn get_active_npcs_for_animation(&self) -> Vec<([u8; 16], f32, f32, i32, i32, f32, f32, f32)> {
-        let mut npcs = Vec::new();
-
-        // Iterate over active NPC pool (not active_combat_npcs, which filters out dead)
-        for entry in self.active_npc_pool.iter() {
-            let npc = entry.value();
-            let ulid_bytes = npc.ulid;
-
-            // Get position using ByteMap
-            let pos = self
-                .npc_positions
-                .get(&ulid_bytes)
-                .map(|v| v.value().clone())
-                .and_then(|pos_str| {
-                    let parts: Vec<&str> = pos_str.split(',').collect();
-                    if parts.len() == 2 {
-                        if let (Ok(x), Ok(y)) = (parts[0].parse::<f32>(), parts[1].parse::<f32>()) {
-                            return Some((x, y));
-                        }
-                    }
-                    None
-                });
-
-            if pos.is_none() {
-                continue; // Skip NPCs without position
+    /// Unregister an NPC from combat processing
+    /// Usage: NPCDataWarehouse.unregister_npc_from_combat(ulid_bytes)
+    /// ulid_bytes: PackedByteArray (16 bytes) - raw ULID bytes from stats.ulid
+    #[func]
+    pub fn unregister_npc_from_combat(&self, ulid_bytes: PackedByteArray) {
+        let _step = self.warehouse.hold_steps();
+        match packed_bytes_to_ulid(&ulid_bytes) {
+            Ok(ulid) => {
+                self.warehouse.unregister_npc_from_combat_internal(&ulid);
             }
-            let (x, y) = pos.unwrap();
-
-            // Get behavioral state from ByteMap
-            let behavioral_state = self
-                .npc_behavioral_state
-                .get(&ulid_bytes)
-                .map(|v| v.value().clone())
-                .and_then(|s| s.parse::<i32>().ok())
-                .unwrap_or(0);
-
-            // Get combat stats from ByteMap
-            let (hp, attack, defense, static_state) = if let Some(stats_json) = self
-                .npc_combat_stats
-                .get(&ulid_bytes)
-                .map(|v| v.value().clone())
-            {
-                if let Ok(combat_stats) = serde_json::from_str::<NPCCombatStats>(&stats_json) {
-                    (
-                        combat_stats.hp,
-                        combat_stats.attack,
-                        combat_stats.defense,
-                        combat_stats.static_state,
-                    )
-                } else {
-                    continue;
-                }
-            } else {
-                continue;
-            };
-
-            let max_hp = hp; // For animation phase, we don't need accurate max_hp
-            npcs.push((
-                ulid_bytes,
-                x,
-                y,
-                static_state,
-                behavioral_state,
-                hp,
-                max_hp,
-                attack,
-            ));
-        }
-
-        npcs
-    }
-
-    /// Get active NPCs for combat/movement (excludes DEAD NPCs)
-    fn get_active_npcs_with_positions(&self) -> Vec<([u8; 16], f32, f32, i32, i32, f32, f32, f32)> {
-        let mut npcs = Vec::new();
-
-        // Iterate over active combat NPCs DashMap (now uses bytes as keys)
-        for entry in self.active_combat_npcs.iter() {
-            let ulid_bytes = *entry.key();
-
-            // Get position using DashMap (takes bytes directly)
-            let pos = self
-                .npc_positions
-                .get(&ulid_bytes)
-                .map(|v| v.value().clone())
-                .and_then(|pos_str| {
-                    let parts: Vec<&str> = pos_str.split(',').collect();
-                    if parts.len() == 2 {
-                        if let (Ok(x), Ok(y)) = (parts[0].parse::<f32>(), parts[1].parse::<f32>()) {
-                            return Some((x, y));
-                        }
-                    }
-                    None
-                });
-
-            if pos.is_none() {
-                let ulid_hex = bytes_to_hex(&ulid_bytes);
-                self.log_error_once(
-                    "missing_position",
-                    &ulid_hex,
-                    &format!(
-                        "[COMBAT ERROR] NPC {} has no position - skipping from combat",
-                        &ulid_hex[0..8.min(ulid_hex.len())]
-                    ),
+            Err(e) => {
+                godot_error!(
+                    "[COMBAT ERROR] Invalid ULID bytes in unregister_npc_from_combat: {}",
+                    e
                 );
-                continue;
-            }
-            let (x, y) = pos.unwrap();
-
-            // Get behavioral state from ByteMap
-            let behavioral_state = self
-                .npc_behavioral_state
-                .get(&ulid_bytes)
-                .map(|v| v.value().clone())
-                .and_then(|s| s.parse::<i32>().ok())
-                .unwrap_or(0);
-
-            // Skip if dead
-            if (behavioral_state & NPCState::DEAD.bits() as i32) != 0 {
-                continue;
             }
-
-            // Get combat stats from ByteMap
-            let (hp, attack, defense, static_state) = if let Some(stats_json) = self
-                .npc_combat_stats
-                .get(&ulid_bytes)
-                .map(|v| v.value().clone())
-            {
-                if let Ok(combat_stats) = serde_json::from_str::<NPCCombatStats>(&stats_json) {
-                    (
-                        combat_stats.hp,
-                        combat_stats.attack,
-                        combat_stats.defense,
-                        combat_stats.static_state,
-                    )
-                } else {
-                    (100.0, 10.0, 5.0, 0)
-                }
-            } else {
-                (100.0, 10.0, 5.0, 0)
-            };
-
-            npcs.push((
-                ulid_bytes,
-                x,
-                y,
-                static_state,
-                behavioral_state,
-                hp,
-                attack,
-                defense,
-            ));
-        }
-
-        npcs
-    }
-
-    /// Find combat pairs based on proximity and faction hostility
-    /// Returns: Vec<(attacker_ulid_hex, target_ulid_hex, distance)>
-    fn find_combat_pairs(
-        &self,
-        npcs: &[([u8; 16], f32, f32, i32, i32, f32, f32, f32)],
-    ) -> Vec<([u8; 16], [u8; 16], f32)> {
-        let mut pairs = Vec::new();
-
-        // Debug: Log first close encounter
-        static mut FIRST_CLOSE_LOG: bool = false;
-
-        for i in 0..npcs.len() {
-            let (ulid_a, x_a, y_a, static_state_a, behavioral_state_a, _, _, _) = &npcs[i];
-
-            // Skip if dead (check behavioral state)
-            if (*behavioral_state_a & NPCState::DEAD.bits() as i32) != 0 {
-                continue;
-            }
-
-            for j in (i + 1)..npcs.len() {
-                let (ulid_b, x_b, y_b, static_state_b, behavioral_state_b, _, _, _) = &npcs[j];
-
-                // Skip if dead (check behavioral state)
-                if (*behavioral_state_b & NPCState::DEAD.bits() as i32) != 0 {
-                    continue;
-                }
-
-                // Check if hostile factions (use static state)
-                if !Self::are_factions_hostile(*static_state_a, *static_state_b) {
-                    continue;
-                }
-
-                // Calculate distance
-                let distance = Self::distance(*x_a, *y_a, *x_b, *y_b);
-
-                // Get attack range based on combat type (use static state)
-                let range_a = Self::get_attack_range(*static_state_a);
-                let range_b = Self::get_attack_range(*static_state_b);
-
-                // Debug: Log when hostile NPCs are close but not in range yet
-                unsafe {
-                    if !FIRST_CLOSE_LOG && distance < 300.0 {
-                        let ulid_hex_a = bytes_to_hex(ulid_a);
-                        let ulid_hex_b = bytes_to_hex(ulid_b);
-                        godot_print!("[COMBAT PAIRS] Found hostile NPCs: {} at ({:.1},{:.1}) vs {} at ({:.1},{:.1}), distance: {:.1}, range_a: {}, range_b: {}",
-                            ulid_hex_a, x_a, y_a, ulid_hex_b, x_b, y_b, distance, range_a, range_b);
-                        FIRST_CLOSE_LOG = true;
-                    }
-                }
-
-                // If in range, add to pairs (both directions possible)
-                // Keep as bytes - no hex conversion needed
-                if distance <= range_a {
-                    pairs.push((*ulid_a, *ulid_b, distance));
-                }
-                if distance <= range_b {
-                    pairs.push((*ulid_b, *ulid_a, distance));
-                }
-            }
-        }
-
-        pairs
-    }
-
-    /// Check if two faction states are hostile
-    fn are_factions_hostile(static_state1: i32, static_state2: i32) -> bool {
-        let ally1 = (static_state1 & NPCStaticState::ALLY.bits() as i32) != 0;
-        let monster1 = (static_state1 & NPCStaticState::MONSTER.bits() as i32) != 0;
-        let passive1 = (static_state1 & NPCStaticState::PASSIVE.bits() as i32) != 0;
-
-        let ally2 = (static_state2 & NPCStaticState::ALLY.bits() as i32) != 0;
-        let monster2 = (static_state2 & NPCStaticState::MONSTER.bits() as i32) != 0;
-        let passive2 = (static_state2 & NPCStaticState::PASSIVE.bits() as i32) != 0;
-
-        // Passive never hostile
-        if passive1 || passive2 {
-            return false;
-        }
-
-        // Ally vs Monster = hostile
-        (ally1 && monster2) || (monster1 && ally2)
-    }
-
-    /// Get attack range based on combat type (from static_state)
-    fn get_attack_range(static_state: i32) -> f32 {
-        if (static_state & NPCStaticState::MELEE.bits() as i32) != 0 {
-            30.0 // Melee range - close combat
-        } else if (static_state & NPCStaticState::RANGED.bits() as i32) != 0 {
-            200.0 // Ranged range
-        } else if (static_state & NPCStaticState::MAGIC.bits() as i32) != 0 {
-            150.0 // Magic range
-        } else {
-            30.0 // Default - close combat
-        }
-    }
-
-    /// Helper to get stat value from HolyMap
-    fn get_stat_value(&self, ulid: &str, stat_name: &str) -> Option<f32> {
-        // Convert hex to bytes (legacy interface, should be refactored to take bytes)
-        let ulid_bytes = hex_to_bytes(ulid).ok()?;
-
-        // For combat stats, read from the struct
-        match stat_name {
-            "hp" | "max_hp" | "attack" | "defense" | "static_state" => {
-                let stats_json = self
-                    .npc_combat_stats
-                    .get(&ulid_bytes)
-                    .map(|v| v.value().clone())?;
-                let combat_stats = serde_json::from_str::<NPCCombatStats>(&stats_json).ok()?;
-                match stat_name {
-                    "hp" => Some(combat_stats.hp),
-                    "max_hp" => Some(combat_stats.max_hp),
-                    "attack" => Some(combat_stats.attack),
-                    "defense" => Some(combat_stats.defense),
-                    "static_state" => Some(combat_stats.static_state as f32),
-                    _ => None,
-                }
-            }
-            // For other stats, use ByteMap lookup
-            _ => {
-                let value_str = match stat_name {
-                    "behavioral_state" => self
-                        .npc_behavioral_state
-                        .get(&ulid_bytes)
-                        .map(|v| v.value().clone())?,
-                    "cooldown" => self
-                        .npc_cooldown
-                        .get(&ulid_bytes)
-                        .map(|v| v.value().clone())?,
-                    _ => return None,
-                };
-                value_str.parse::<f32>().ok()
-            }
-        }
-    }
-
-    /// Calculate distance between two points
-    fn distance(x1: f32, y1: f32, x2: f32, y2: f32) -> f32 {
-        let dx = x2 - x1;
-        let dy = y2 - y1;
-        (dx * dx + dy * dy).sqrt()
-    }
-
-    /// Check if attacker can attack (cooldown expired)
-    /// Takes ULID as bytes for zero-copy performance
-    fn check_attack_cooldown(&self, ulid_bytes: &[u8; 16], now_ms: u64) -> bool {
-        if let Some(last_attack_str) = self.npc_cooldown.get(ulid_bytes).map(|v| v.clone()) {
-            if let Ok(last_attack_ms) = last_attack_str.parse::<u64>() {
-                let cooldown_duration_ms = 3500; // 1 attack per 3.5 seconds (much slower, more tactical combat)
-                return now_ms >= last_attack_ms + cooldown_duration_ms;
-            }
-        }
-        true // No cooldown record = can attack
-    }
-
-    /// Update attack cooldown
-    /// Takes ULID as bytes for zero-copy performance
-    fn update_cooldown(&self, ulid_bytes: &[u8; 16], now_ms: u64) {
-        self.npc_cooldown.insert(*ulid_bytes, now_ms.to_string());
-    }
-
-    /// Apply damage to target, return new HP
-    fn apply_damage(&self, target_ulid: &str, damage: f32) -> f32 {
-        // Convert hex to bytes (legacy interface, should be refactored to take bytes)
-        if let Ok(ulid_bytes) = hex_to_bytes(target_ulid) {
-            // Get current combat stats
-            if let Some(stats_json) = self
-                .npc_combat_stats
-                .get(&ulid_bytes)
-                .map(|v| v.value().clone())
-            {
-                if let Ok(mut combat_stats) = serde_json::from_str::<NPCCombatStats>(&stats_json) {
-                    let old_hp = combat_stats.hp;
-                    let max_hp = combat_stats.max_hp;
-
-                    // Apply damage
-                    combat_stats.hp = (combat_stats.hp - damage).max(0.0);
-                    let new_hp = combat_stats.hp;
-
-                    // Store updated stats
-                    if let Ok(updated_json) = serde_json::to_string(&combat_stats) {
-                        self.npc_combat_stats.insert(*&ulid_bytes, updated_json);
-                    }
-
-                    // Update healthbar if one is assigned to this NPC
-                    self.update_healthbar_hp(&ulid_bytes, damage, new_hp, max_hp);
-
-                    return combat_stats.hp;
-                }
-            }
-        }
-
-        // Fallback if ULID not found
-        0.0
-    }
-
-    /// Apply healing to target, return new HP
-    /// Increases HP without exceeding max_hp
-    /// Also increases hunger and energy (hidden stats)
-    fn apply_healing(&self, target_ulid: &str, heal_amount: f32, hunger_gain: f32, energy_gain: f32) -> f32 {
-        // Convert hex to bytes (legacy interface, should be refactored to take bytes)
-        if let Ok(ulid_bytes) = hex_to_bytes(target_ulid) {
-            // Get current combat stats
-            if let Some(stats_json) = self
-                .npc_combat_stats
-                .get(&ulid_bytes)
-                .map(|v| v.value().clone())
-            {
-                if let Ok(mut combat_stats) = serde_json::from_str::<NPCCombatStats>(&stats_json) {
-                    let old_hp = combat_stats.hp;
-                    let max_hp = combat_stats.max_hp;
-
-                    // Apply healing (cap at max_hp)
-                    combat_stats.hp = (combat_stats.hp + heal_amount).min(max_hp);
-                    let new_hp = combat_stats.hp;
-
-                    // Apply hunger and energy gains (cap at max values)
-                    combat_stats.energy = (combat_stats.energy + energy_gain).min(combat_stats.max_energy);
-                    combat_stats.hunger = (combat_stats.hunger + hunger_gain).min(combat_stats.max_hunger);
-
-                    // Store updated stats
-                    if let Ok(updated_json) = serde_json::to_string(&combat_stats) {
-                        self.npc_combat_stats.insert(*&ulid_bytes, updated_json);
-                    }
-
-                    // Update healthbar with healing (use negative damage to indicate healing)
-                    self.update_healthbar_healing(&ulid_bytes, heal_amount, new_hp, max_hp);
-
-                    return combat_stats.hp;
-                }
-            }
-        }
-
-        // Fallback if ULID not found
-        0.0
-    }
-
-    /// Update the healthbar for an NPC when they take damage
-    fn update_healthbar_hp(&self, ulid: &[u8; 16], damage: f32, current_hp: f32, max_hp: f32) {
-        // Get the healthbar assigned to this NPC
-        if let Some(pool_index_ref) = self.healthbar_assignments.get(ulid) {
-            let pool_index = *pool_index_ref.value();
-
-            // Get the healthbar from the pool
-            if let Some(healthbar_ref) = self.healthbar_pool.get(&pool_index) {
-                let mut healthbar = healthbar_ref.value().clone();
-
-                // Call _on_entity_damage_taken directly to update health AND spawn damage text
-                // This mimics what the signal would do
-                let _ = healthbar.call(
-                    "_on_entity_damage_taken",
-                    &[
-                        damage.to_variant(),
-                        current_hp.to_variant(),
-                        max_hp.to_variant(),
-                    ],
-                );
-            }
-        }
-    }
-
-    /// Update the healthbar for an NPC when they are healed
-    fn update_healthbar_healing(&self, ulid: &[u8; 16], heal_amount: f32, current_hp: f32, max_hp: f32) {
-        // Get the healthbar assigned to this NPC
-        if let Some(pool_index_ref) = self.healthbar_assignments.get(ulid) {
-            let pool_index = *pool_index_ref.value();
-
-            // Get the healthbar from the pool (clone to avoid holding lock during Godot call)
-            if let Some(healthbar_ref) = self.healthbar_pool.get(&pool_index) {
-                let mut healthbar = healthbar_ref.value().clone();
-
-                // Call _on_entity_healed to update health AND spawn green healing text
-                let _ = healthbar.call(
-                    "_on_entity_healed",
-                    &[
-                        heal_amount.to_variant(),
-                        current_hp.to_variant(),
-                        max_hp.to_variant(),
-                    ],
-                );
-            }
-        }
-    }
-
-    /// Cleanup dead NPCs that have finished their death animation
-    /// Returns Vec of death positions (x, y) for GDScript to spawn effects
-    fn cleanup_dead_npcs(&self, now_ms: u64) -> Vec<(f32, f32)> {
-        // Collect ULIDs of NPCs to despawn
-        let mut to_despawn = Vec::new();
-
-        // Check all active NPCs for scheduled despawn
-        for pool_entry in self.active_npc_pool.iter() {
-            let npc = pool_entry.value();
-            let ulid_hex = bytes_to_hex(&npc.ulid);
-            let despawn_key = format!("despawn_at:{}", ulid_hex);
-
-            if let Some(despawn_time_str) = self.storage.get(&despawn_key) {
-                if let Ok(despawn_time) = despawn_time_str.value().parse::<u64>() {
-                    if now_ms >= despawn_time {
-                        to_despawn.push(npc.ulid);
-                    }
-                }
-            }
-        }
-
-        // Despawn scheduled NPCs and collect death positions for GDScript
-        let mut death_positions = Vec::new();
-
-        for ulid_bytes in to_despawn {
-            godot_print!("[RUST CLEANUP] Despawning dead NPC: {:?}", &ulid_bytes[..8]);
-
-            // Get the NPC's position before despawning (store for signal emission)
-            if let Some(npc_ref) = self.active_npc_pool.get(&ulid_bytes) {
-                let pos = npc_ref.node.get_global_position();
-                death_positions.push((pos.x, pos.y));
-            }
-
-            // Despawn the NPC
-            self.rust_despawn_npc(&ulid_bytes);
-        }
-
-        // Return death positions so GodotNPCDataWarehouse can emit signals
-        death_positions
-    }
-
-    /// Mark NPC as dead and remove from active combat
-    fn mark_dead(&self, ulid: &str) {
-        // Convert hex to bytes for ByteMap storage
-        if let Ok(ulid_bytes) = hex_to_bytes(ulid) {
-            // Set behavioral state to DEAD only (clear all other flags)
-            let dead_state = NPCState::DEAD.bits() as i32;
-            self.npc_behavioral_state
-                .insert(*&ulid_bytes, dead_state.to_string());
-
-            godot_print!("[DEATH] Marked NPC {} as DEAD (state={})", ulid, dead_state);
-
-            // IMPORTANT: Remove from active combat immediately
-            // This prevents dead NPCs from being included in combat processing next tick
-            self.active_combat_npcs.remove(&ulid_bytes);
-
-            // Schedule despawn after death animation (2 seconds)
-            let now_ms = Self::get_current_time_ms();
-            let despawn_time = now_ms + 2000; // 2 seconds for death animation
-            self.storage
-                .insert(format!("despawn_at:{}", ulid), despawn_time.to_string());
-        }
-    }
-
-    /// Add ATTACKING state flag (set during attack)
-    /// Takes ULID as bytes for zero-copy performance
-    fn add_attacking_state(&self, ulid_bytes: &[u8; 16]) {
-        let current = if let Some(state_str) = self
-            .npc_behavioral_state
-            .get(ulid_bytes)
-            .map(|v| v.value().clone())
-        {
-            state_str.parse::<i32>().unwrap_or(0)
-        } else {
-            0
-        };
-        // Add ATTACKING and remove IDLE (can't be idle while attacking)
-        let mut new_state = current | NPCState::ATTACKING.bits() as i32;
-        new_state &= !(NPCState::IDLE.bits() as i32);
-        self.npc_behavioral_state
-            .insert(*ulid_bytes, new_state.to_string());
-
-        // Record timestamp for auto-clearing (format: "attacking:timestamp,damaged:timestamp")
-        // Preserve existing damaged timestamp if present
-        let now_ms = Self::get_current_time_ms();
-        let existing_timestamps = self
-            .npc_state_timestamps
-            .get(ulid_bytes)
-            .map(|v| v.value().clone())
-            .unwrap_or_default();
-
-        // Parse existing timestamps to preserve damaged timestamp
-        let mut damaged_part = String::new();
-        for part in existing_timestamps.split(',') {
-            if part.starts_with("damaged:") {
-                damaged_part = format!(",{}", part);
-                break;
-            }
-        }
-
-        let new_timestamps = format!("attacking:{}{}", now_ms, damaged_part);
-        self.npc_state_timestamps
-            .insert(*ulid_bytes, new_timestamps);
-
-        let ulid_hex = bytes_to_hex(ulid_bytes);
-        godot_print!(
-            "[ATTACK STATE] NPC {} - Setting ATTACKING flag (old_state={}, new_state={})",
-            &ulid_hex[0..8.min(ulid_hex.len())],
-            current,
-            new_state
-        );
-    }
-
-    /// Add DAMAGED state flag (set when taking damage)
-    /// Takes ULID as bytes for zero-copy performance
-    fn add_damaged_state(&self, ulid_bytes: &[u8; 16]) {
-        let current = if let Some(state_str) = self
-            .npc_behavioral_state
-            .get(ulid_bytes)
-            .map(|v| v.value().clone())
-        {
-            state_str.parse::<i32>().unwrap_or(0)
-        } else {
-            0
-        };
-        // Add DAMAGED and remove IDLE (can't be idle while damaged)
-        let mut new_state = current | NPCState::DAMAGED.bits() as i32;
-        new_state &= !(NPCState::IDLE.bits() as i32);
-        self.npc_behavioral_state
-            .insert(*ulid_bytes, new_state.to_string());
-
-        // Record timestamp for auto-clearing (format: "attacking:timestamp,damaged:timestamp")
-        // Preserve existing attacking timestamp if present
-        let now_ms = Self::get_current_time_ms();
-        let existing_timestamps = self
-            .npc_state_timestamps
-            .get(ulid_bytes)
-            .map(|v| v.value().clone())
-            .unwrap_or_default();
-
-        // Parse existing timestamps to preserve attacking timestamp
-        let mut attacking_part = String::new();
-        for part in existing_timestamps.split(',') {
-            if part.starts_with("attacking:") {
-                attacking_part = format!("{},", part);
-                break;
-            }
-        }
-
-        let new_timestamps = format!("{}damaged:{}", attacking_part, now_ms);
-        self.npc_state_timestamps
-            .insert(*ulid_bytes, new_timestamps);
-
-        let ulid_hex = bytes_to_hex(ulid_bytes);
-        godot_print!(
-            "[DAMAGED STATE] NPC {} - Setting DAMAGED flag (old_state={}, new_state={})",
-            &ulid_hex[0..8.min(ulid_hex.len())],
-            current,
-            new_state
-        );
-    }
-
-    /// Remove ATTACKING state flag (called by GDScript after attack animation finishes)
-    /// Takes ULID as bytes for zero-copy performance
-    pub fn remove_attacking_state(&self, ulid_bytes: &[u8; 16]) {
-        let current = if let Some(state_str) = self
-            .npc_behavioral_state
-            .get(ulid_bytes)
-            .map(|v| v.value().clone())
-        {
-            state_str.parse::<i32>().unwrap_or(0)
-        } else {
-            0
-        };
-        // Remove ATTACKING
-        let mut new_state = current & !(NPCState::ATTACKING.bits() as i32);
-
-        // Add IDLE back if not DAMAGED, DEAD, or in other active states
-        // Note: IDLE | COMBAT is valid - it means "in combat stance but waiting between attacks"
-        let is_damaged = (new_state & NPCState::DAMAGED.bits() as i32) != 0;
-        let is_dead = (new_state & NPCState::DEAD.bits() as i32) != 0;
-        let is_walking = (new_state & NPCState::WALKING.bits() as i32) != 0;
-
-        if !is_damaged && !is_dead && !is_walking {
-            new_state |= NPCState::IDLE.bits() as i32;
-        }
-
-        self.npc_behavioral_state
-            .insert(*ulid_bytes, new_state.to_string());
-    }
-
-    /// Remove DAMAGED state flag (called by GDScript after hurt animation finishes)
-    /// Takes ULID as bytes for zero-copy performance
-    pub fn remove_damaged_state(&self, ulid_bytes: &[u8; 16]) {
-        let current = if let Some(state_str) = self
-            .npc_behavioral_state
-            .get(ulid_bytes)
-            .map(|v| v.value().clone())
-        {
-            state_str.parse::<i32>().unwrap_or(0)
-        } else {
-            0
-        };
-        // Remove DAMAGED
-        let mut new_state = current & !(NPCState::DAMAGED.bits() as i32);
-
-        // Add IDLE back if not ATTACKING, DEAD, or in other active states
-        // Note: IDLE | COMBAT is valid - it means "in combat stance but waiting between attacks"
-        let is_attacking = (new_state & NPCState::ATTACKING.bits() as i32) != 0;
-        let is_dead = (new_state & NPCState::DEAD.bits() as i32) != 0;
-        let is_walking = (new_state & NPCState::WALKING.bits() as i32) != 0;
-
-        if !is_attacking && !is_dead && !is_walking {
-            new_state |= NPCState::IDLE.bits() as i32;
-        }
-
-        self.npc_behavioral_state
-            .insert(*ulid_bytes, new_state.to_string());
-    }
-
-    /// Set aggro target for an NPC (makes them prioritize attacking this target)
-    /// Takes ULIDs as bytes for zero-copy performance
-    fn set_aggro_target(&self, npc_ulid_bytes: &[u8; 16], target_ulid_bytes: &[u8; 16]) {
-        let target_ulid_hex = bytes_to_hex(target_ulid_bytes);
-        self.npc_aggro_targets
-            .insert(*npc_ulid_bytes, target_ulid_hex.clone());
-
-        let npc_ulid_hex = bytes_to_hex(npc_ulid_bytes);
-        godot_print!(
-            "[AGGRO] NPC {} now targets {} (retaliation)",
-            &npc_ulid_hex[0..8.min(npc_ulid_hex.len())],
-            &target_ulid_hex[0..8.min(target_ulid_hex.len())]
-        );
-    }
-
-    /// Clear ATTACKING and DAMAGED states after animation duration
-    fn clear_expired_animation_states(&self) {
-        let now_ms = Self::get_current_time_ms();
-        const ATTACK_ANIM_DURATION_MS: u64 = 800; // Attack animation duration (800ms)
-        const DAMAGED_ANIM_DURATION_MS: u64 = 500; // Hurt animation duration (500ms)
-
-        // Iterate through all active NPCs
-        for entry in self.active_npc_pool.iter() {
-            let npc = entry.value();
-            let ulid_bytes = &npc.ulid;
-
-            // Check if this NPC has timestamp records
-            if let Some(timestamp_str) = self
-                .npc_state_timestamps
-                .get(ulid_bytes)
-                .map(|v| v.value().clone())
-            {
-                let mut should_clear_attacking = false;
-                let mut should_clear_damaged = false;
-
-                // Parse timestamps (format: "attacking:123456,damaged:789012" or just one of them)
-                for part in timestamp_str.split(',') {
-                    if part.starts_with("attacking:") {
-                        if let Ok(set_at_ms) = part[10..].parse::<u64>() {
-                            if now_ms >= set_at_ms + ATTACK_ANIM_DURATION_MS {
-                                should_clear_attacking = true;
-                            }
-                        }
-                    } else if part.starts_with("damaged:") {
-                        if let Ok(set_at_ms) = part[8..].parse::<u64>() {
-                            if now_ms >= set_at_ms + DAMAGED_ANIM_DURATION_MS {
-                                should_clear_damaged = true;
-                            }
-                        }
-                    }
-                }
-
-                // Clear expired states
-                if should_clear_attacking || should_clear_damaged {
-                    if let Some(state_str) = self
-                        .npc_behavioral_state
-                        .get(ulid_bytes)
-                        .map(|v| v.value().clone())
-                    {
-                        if let Ok(current_state) = state_str.parse::<i32>() {
-                            let mut new_state = current_state;
-                            let mut remaining_timestamps = Vec::new();
-
-                            if should_clear_attacking {
-                                // Use the proper remove function that also adds IDLE back if needed
-                                self.remove_attacking_state(ulid_bytes);
-                                let ulid_hex = bytes_to_hex(ulid_bytes);
-                                godot_print!(
-                                    "[ANIM CLEAR] NPC {} - Clearing ATTACKING flag after {}ms",
-                                    &ulid_hex[0..8.min(ulid_hex.len())],
-                                    ATTACK_ANIM_DURATION_MS
-                                );
-                                // Note: state already updated by remove_attacking_state
-                                new_state = self.npc_behavioral_state
-                                    .get(ulid_bytes)
-                                    .map(|v| v.value().clone())
-                                    .and_then(|s| s.parse::<i32>().ok())
-                                    .unwrap_or(new_state);
-                            } else {
-                                // Keep attacking timestamp if not expired
-                                for part in timestamp_str.split(',') {
-                                    if part.starts_with("attacking:") {
-                                        remaining_timestamps.push(part.to_string());
-                                    }
-                                }
-                            }
-
-                            if should_clear_damaged {
-                                // Use the proper remove function that also adds IDLE back if needed
-                                self.remove_damaged_state(ulid_bytes);
-                                let ulid_hex = bytes_to_hex(ulid_bytes);
-                                godot_print!(
-                                    "[ANIM CLEAR] NPC {} - Clearing DAMAGED flag after {}ms",
-                                    &ulid_hex[0..8.min(ulid_hex.len())],
-                                    DAMAGED_ANIM_DURATION_MS
-                                );
-                                // Note: state already updated by remove_damaged_state
-                                new_state = self.npc_behavioral_state
-                                    .get(ulid_bytes)
-                                    .map(|v| v.value().clone())
-                                    .and_then(|s| s.parse::<i32>().ok())
-                                    .unwrap_or(new_state);
-                            } else {
-                                // Keep damaged timestamp if not expired
-                                for part in timestamp_str.split(',') {
-                                    if part.starts_with("damaged:") {
-                                        remaining_timestamps.push(part.to_string());
-                                    }
-                                }
-                            }
-
-                            // State already updated by remove_attacking_state/remove_damaged_state
-                            // No need to update again here
-
-                            // Update or remove timestamp based on remaining timestamps
-                            if remaining_timestamps.is_empty() {
-                                self.npc_state_timestamps.remove(ulid_bytes).map(|(_, v)| v);
-                            } else {
-                                self.npc_state_timestamps
-                                    .insert(*ulid_bytes, remaining_timestamps.join(","));
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    /// Check if this is the first combat tick and spawn minimal entities for debugging
-    /// Spawns: 1 warrior, 1 archer, 2 random monsters
-    fn check_initial_spawn(&self, now_ms: u64) -> Vec<CombatEvent> {
-        use std::sync::atomic::Ordering;
-
-        // Check if initial spawn already done
-        if self.initial_spawn_done.load(Ordering::Relaxed) {
-            return Vec::new(); // Already spawned
         }
+    }
 
-        godot_print!("[RUST SPAWN] check_initial_spawn called - checking prerequisites...");
-
-        // Check if scene container is set (required for spawning)
-        {
-            let container_guard = self.scene_container.read();
-            if container_guard.is_none() {
-                godot_print!("[RUST SPAWN] Scene container not set yet - skipping spawn");
-                // Container not set yet - likely still in title/intro scene
-                // Don't mark as done, just skip this tick
-                return Vec::new();
-            }
-        }
-
-        godot_print!("[RUST SPAWN] Prerequisites met - starting initial spawn!");
-
-        // Mark initial spawn as done FIRST to prevent race conditions
-        self.initial_spawn_done.store(true, Ordering::Relaxed);
-
-        // Set the timers so regular spawning doesn't trigger immediately
-        self.last_spawn_time_ms.store(now_ms, Ordering::Relaxed);
-        self.last_ally_spawn_time_ms
-            .store(now_ms, Ordering::Relaxed);
-
-        godot_print!("[RUST SPAWN] Initial spawn starting...");
-
-        // Calculate spawn positions and center point
-        let world_min_x = f32::from_bits(self.world_min_x.load(Ordering::Relaxed));
-        let world_max_x = f32::from_bits(self.world_max_x.load(Ordering::Relaxed));
-        let world_min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
-        let world_max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
-        let center_x = (world_min_x + world_max_x) / 2.0;
-        let center_y = (world_min_y + world_max_y) / 2.0;
-
-        // Calculate spawn positions based on world bounds
-        let ally_spawn_x = world_min_x + (world_max_x - world_min_x) * 0.25; // 25% from left
-        let archer_spawn_x = world_min_x + (world_max_x - world_min_x) * 0.20; // 20% from left (behind warriors)
-        let monster_spawn_x = world_min_x + (world_max_x - world_min_x) * 0.75; // 75% from left (right side)
-
-        // Random number generator for scatter
-        use rand::Rng;
-        let mut rng = rand::rng();
-
-        // Spawn 6 warriors on left side - scattered vertically to avoid stacking
-        for i in 0..6 {
-            let base_y = world_min_y + (world_max_y - world_min_y) * (i as f32 / 5.0); // Evenly distribute
-            let scatter = rng.random_range(-15.0..15.0); // Add random scatter
-            let warrior_y = (base_y + scatter).clamp(world_min_y + 20.0, world_max_y - 20.0);
-            let scatter_x = rng.random_range(-10.0..10.0); // Small horizontal scatter
-            let warrior_pos = Vector2::new(ally_spawn_x + scatter_x, warrior_y);
-            let warrior_ulid = self.rust_spawn_npc("warrior", warrior_pos);
-
-            // Give warrior initial waypoint toward center-right (to meet monsters)
-            if let Some(ulid_bytes) = warrior_ulid {
-                self.npc_waypoints.insert(
-                    *&ulid_bytes,
-                    format!("{},{}", center_x - 100.0, warrior_y),
-                );
-            }
-        }
-        godot_print!("[RUST SPAWN] 6 warriors spawned (scattered) at x≈{} with waypoints toward center", ally_spawn_x);
-
-        // Spawn 6 archers on left side - scattered to avoid stacking with warriors
-        for i in 0..6 {
-            let base_y = world_min_y + (world_max_y - world_min_y) * (i as f32 / 5.0); // Evenly distribute
-            let scatter = rng.random_range(-15.0..15.0); // Add random scatter
-            let archer_y = (base_y + scatter).clamp(world_min_y + 20.0, world_max_y - 20.0);
-            let scatter_x = rng.random_range(-10.0..10.0); // Small horizontal scatter
-            let archer_pos = Vector2::new(archer_spawn_x + scatter_x, archer_y);
-            let archer_ulid = self.rust_spawn_npc("archer", archer_pos);
-
-            // Give archer waypoint toward center-right (stays behind warriors)
-            if let Some(ulid_bytes) = archer_ulid {
-                self.npc_waypoints.insert(
-                    *&ulid_bytes,
-                    format!("{},{}", center_x - 150.0, archer_y),
-                );
-            }
-        }
-        godot_print!("[RUST SPAWN] 6 archers spawned (scattered) at x≈{} with waypoints toward center", archer_spawn_x);
-
-        // Spawn 8 random monsters on right side - scattered to avoid stacking
-        let monster_types = vec!["goblin", "mushroom", "skeleton", "eyebeast"];
-
-        for i in 0..8 {
-            let monster_type = monster_types[rng.random_range(0..monster_types.len())];
-            let base_y = world_min_y + (world_max_y - world_min_y) * (i as f32 / 7.0); // Evenly distribute
-            let scatter = rng.random_range(-15.0..15.0); // Add random scatter
-            let monster_y = (base_y + scatter).clamp(world_min_y + 20.0, world_max_y - 20.0);
-            let scatter_x = rng.random_range(-10.0..10.0); // Small horizontal scatter
-            let monster_pos = Vector2::new(monster_spawn_x + scatter_x, monster_y);
-
-            let monster_ulid = self.rust_spawn_npc(monster_type, monster_pos);
-
-            // Give monster waypoint toward center-left (to meet allies)
-            if let Some(ulid_bytes) = monster_ulid {
-                self.npc_waypoints
-                    .insert(*&ulid_bytes, format!("{},{}", center_x + 100.0, monster_y));
-            }
-        }
-        godot_print!("[RUST SPAWN] 8 monsters spawned (scattered) at x≈{} with waypoints toward center", monster_spawn_x);
-
-        godot_print!("[RUST SPAWN] Initial spawn complete: 6 warriors, 6 archers, 8 monsters (12 vs 8)");
-
-        Vec::new() // No events needed - NPCs are spawned directly
-    }
-
-    /// Check if we should spawn a new wave of monsters
-    /// Returns spawn events for GDScript to handle
-    fn check_spawn_wave(&self, now_ms: u64) -> Vec<CombatEvent> {
-        use std::sync::atomic::Ordering;
-        let events = Vec::new();
-
-        // Check if scene container is set (required for spawning)
-        {
-            let container_guard = self.scene_container.read();
-            if container_guard.is_none() {
-                return events; // Container not set yet
-            }
-        }
-
-        // Count active monsters (NPCs with MONSTER faction)
-        let monster_count = self
-            .active_combat_npcs
-            .iter()
-            .filter(|entry| {
-                let ulid_bytes = entry.key();
-                let ulid_hex = bytes_to_hex(ulid_bytes);
-                if let Some(static_state) = self.get_stat_value(&ulid_hex, "static_state") {
-                    let state = static_state as i32;
-                    (state & NPCStaticState::MONSTER.bits() as i32) != 0
-                } else {
-                    false
-                }
-            })
-            .count();
-
-        // Check if we need a new wave (low monster count or interval elapsed)
-        let last_spawn = self.last_spawn_time_ms.load(Ordering::Relaxed);
-        let time_since_spawn = now_ms - last_spawn;
-
-        let should_spawn = (monster_count < self.min_active_monsters as usize)
-            || (time_since_spawn >= self.spawn_interval_ms);
-
-        if should_spawn {
-            // Update last spawn time
-            self.last_spawn_time_ms.store(now_ms, Ordering::Relaxed);
-
-            // Generate wave size (random between min and max)
-            use rand::Rng;
-            let mut rng = rand::rng();
-            let wave_size = rng.random_range(self.min_wave_size..=self.max_wave_size);
-
-            // Monster types to spawn from (weighted random)
-            let monster_types = vec!["goblin", "mushroom", "skeleton", "eyebeast"];
-
-            godot_print!(
-                "[RUST SPAWN] Spawning wave of {} monsters (current: {})",
-                wave_size,
-                monster_count
-            );
-
-            // Get spawn positions
-            let world_max_x = f32::from_bits(self.world_max_x.load(Ordering::Relaxed));
-            let world_min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
-            let world_max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
-
-            // Spawn each monster directly (right side of visible screen)
-            for _ in 0..wave_size {
-                let monster_type = monster_types[rng.random_range(0..monster_types.len())];
-                let spawn_pos = Vector2::new(
-                    1050.0, // Right side of screen
-                    rng.random_range(world_min_y..world_max_y),
-                );
-
-                if let Some(_ulid) = self.rust_spawn_npc(monster_type, spawn_pos) {
-                    // Note: rust_spawn_npc already registers for combat via register_npc_with_stats
-                }
-            }
-        }
-
-        events
-    }
-
-    /// Check if we should spawn allies (warriors, archers)
-    /// Returns spawn events for GDScript to handle
-    /// Spawns gradually to ramp up (one ally every 3 seconds until cap reached)
-    fn check_ally_spawn(&self, now_ms: u64) -> Vec<CombatEvent> {
-        use std::sync::atomic::Ordering;
-        let events = Vec::new();
-
-        // Check if scene container is set (required for spawning)
-        {
-            let container_guard = self.scene_container.read();
-            if container_guard.is_none() {
-                return events; // Container not set yet
-            }
-        }
-
-        // Count active warriors and archers
-        let mut warrior_count = 0;
-        let mut archer_count = 0;
-
-        for entry in self.active_combat_npcs.iter() {
-            let ulid_bytes = entry.key();
-            let ulid_hex = bytes_to_hex(ulid_bytes);
-            if let Some(static_state) = self.get_stat_value(&ulid_hex, "static_state") {
-                let state = static_state as i32;
-                // Check if ALLY faction
-                if (state & NPCStaticState::ALLY.bits() as i32) != 0 {
-                    // Check combat type
-                    if (state & NPCStaticState::MELEE.bits() as i32) != 0 {
-                        warrior_count += 1;
-                    } else if (state & NPCStaticState::RANGED.bits() as i32) != 0 {
-                        archer_count += 1;
-                    }
-                }
-            }
-        }
-
-        // Check if enough time has passed since last ally spawn
-        let last_ally_spawn = self.last_ally_spawn_time_ms.load(Ordering::Relaxed);
-        let time_since_spawn = now_ms - last_ally_spawn;
-
-        // Debug logging every 5 seconds
-        static mut LAST_DEBUG_LOG: u64 = 0;
-        unsafe {
-            if now_ms - LAST_DEBUG_LOG > 5000 {
-                godot_print!(
-                    "[ALLY SPAWN CHECK] Warriors: {}/{}, Archers: {}/{}, Time since spawn: {}ms (need {}ms)",
-                    warrior_count,
-                    self.max_warriors,
-                    archer_count,
-                    self.max_archers,
-                    time_since_spawn,
-                    self.ally_spawn_interval_ms
-                );
-                LAST_DEBUG_LOG = now_ms;
-            }
-        }
-
-        if time_since_spawn < self.ally_spawn_interval_ms {
-            return events; // Not time yet
-        }
-
-        // Spawn one ally at a time (warrior or archer, alternating priority)
-        // Prioritize whichever is further from cap
-        let warrior_deficit = self.max_warriors - warrior_count;
-        let archer_deficit = self.max_archers - archer_count;
-
-        if warrior_deficit > 0 || archer_deficit > 0 {
-            // Update last spawn time
-            self.last_ally_spawn_time_ms
-                .store(now_ms, Ordering::Relaxed);
-
-            // Decide which to spawn (prioritize bigger deficit)
-            let ally_type = if warrior_deficit >= archer_deficit && warrior_deficit > 0 {
-                "warrior"
-            } else if archer_deficit > 0 {
-                "archer"
-            } else {
-                return events; // Both at cap
-            };
-
-            // Get spawn positions
-            let world_min_x = f32::from_bits(self.world_min_x.load(Ordering::Relaxed));
-            let world_min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
-            let world_max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
-
-            // Spawn ally on left side (visible screen area)
-            use rand::Rng;
-            let mut rng = rand::rng();
-            let spawn_pos = Vector2::new(
-                150.0, // Left side of screen
-                rng.random_range(world_min_y..world_max_y),
-            );
-
-            if let Some(_ulid) = self.rust_spawn_npc(ally_type, spawn_pos) {
-                // Note: rust_spawn_npc already registers for combat via register_npc_with_stats
-                godot_print!(
-                    "[RUST SPAWN] Spawned {} (Warriors: {}/{}, Archers: {}/{})",
-                    ally_type,
-                    warrior_count,
-                    self.max_warriors,
-                    archer_count,
-                    self.max_archers
-                );
-            }
-        }
-
-        events
-    }
-
-    /// Get current time in milliseconds
-    fn get_current_time_ms() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64
-    }
-
-    // ============================================================================
-    // COMBAT THREAD LIFECYCLE
-    // ============================================================================
-
-    /// Enable combat system (no-op, kept for API compatibility)
-    /// Combat is now ticked manually from GDScript to avoid threading issues
-    pub fn start_combat_thread(self: &Arc<Self>) {
-        self.combat_thread_running.store(true, Ordering::Relaxed);
-    }
-
-    /// Disable combat system (no-op, kept for API compatibility)
-    pub fn stop_combat_thread(&self) {
-        self.combat_thread_running.store(false, Ordering::Relaxed);
-    }
-
-    /// Check if combat system is enabled
-    pub fn is_combat_enabled(&self) -> bool {
-        self.combat_thread_running.load(Ordering::Relaxed)
-    }
-}
-
-/// Godot FFI wrapper for NPCDataWarehouse
-///
-/// This will be registered as an autoload singleton in Godot.
-/// Replaces Dictionary-based pools in NPCManager.
-///
-/// Usage in GDScript:
-/// ```gdscript
-/// # Register a pool
-/// NPCDataWarehouse.register_pool("warrior", 10, "res://nodes/npc/warrior/warrior.tscn")
-///
-/// # Store NPC data
-/// NPCDataWarehouse.store_npc(ulid, npc_json_data)
-///
-/// # Get NPC data
-/// var npc_data = NPCDataWarehouse.get_npc(ulid)
-///
-/// # Check pool health
-/// var read_count = NPCDataWarehouse.read_store_count()
-/// var write_count = NPCDataWarehouse.write_store_count()
-/// ```
-#[derive(GodotClass)]
-#[class(base=Node)]
-pub struct GodotNPCDataWarehouse {
-    warehouse: Arc<NPCDataWarehouse>,
-    base: Base<Node>,
-}
-
-#[godot_api]
-impl INode for GodotNPCDataWarehouse {
-    fn init(base: Base<Node>) -> Self {
-        godot_print!("=== NPCDataWarehouse Initializing ===");
-        Self {
-            warehouse: Arc::new(NPCDataWarehouse::new(1000)), // 1 second sync interval
-            base,
-        }
-    }
-
-    fn ready(&mut self) {
-        godot_print!("NPCDataWarehouse: Ready! High-performance NPC pool active.");
-    }
-}
-
-#[godot_api]
-impl GodotNPCDataWarehouse {
-    /// Emitted when an NPC is stored in the warehouse
-    /// Parameters: (ulid: String, npc_type: String)
-    #[signal]
-    fn npc_stored(ulid: GString, npc_type: GString);
-
-    /// Emitted when an NPC is removed from the warehouse
-    /// Parameters: (ulid: String, npc_type: String)
-    #[signal]
-    fn npc_removed(ulid: GString, npc_type: GString);
-
-    /// Emitted when AI state changes
-    /// Parameters: (ulid: String, ai_data: String)
-    #[signal]
-    fn ai_state_changed(ulid: GString, ai_data: GString);
-
-    /// Emitted when combat state changes
-    /// Parameters: (ulid: String, combat_data: String)
-    #[signal]
-    fn combat_state_changed(ulid: GString, combat_data: GString);
-
-    /// Emitted when a pool is registered
-    /// Parameters: (npc_type: String, max_size: int)
-    #[signal]
-    fn pool_registered(npc_type: GString, max_size: i32);
-
-    /// Emitted when an NPC dies (for death effects)
-    /// Parameters: (position_x: float, position_y: float)
-    #[signal]
-    fn npc_died(position_x: f32, position_y: f32);
+    /// Update NPC position for combat calculations
+    /// Usage: NPCDataWarehouse.update_npc_position(ulid_bytes, x, y)
+    /// ulid_bytes: PackedByteArray (16 bytes) - raw ULID bytes from stats.ulid
+    #[func]
+    pub fn update_npc_position(&self, ulid_bytes: PackedByteArray, x: f32, y: f32) {
+        let _step = self.warehouse.hold_steps();
+        match packed_bytes_to_ulid(&ulid_bytes) {
+            Ok(ulid) => {
+                self.warehouse.update_npc_position_internal(&ulid, x, y);
+            }
+            Err(e) => {
+                godot_error!(
+                    "[COMBAT ERROR] Invalid ULID bytes in update_npc_position: {}",
+                    e
+                );
+            }
+        }
+    }
 
-    /// Emitted when sync completes
-    /// Parameters: (synced_count: int)
-    #[signal]
-    fn sync_completed(synced_count: i32);
+    // ===== DETERMINISM (CLOCK + RNG) =====
 
-    // ===== Pool Management Methods =====
-    /// Register a new NPC pool
+    /// Seed the simulation RNG (wandering, spawn scatter, waves, names, ULIDs)
+    /// Same seed + same inputs + same tick deltas = same battle
+    /// Usage: NPCDataWarehouse.set_simulation_seed(12345)
     #[func]
-    pub fn register_pool(&mut self, npc_type: GString, max_size: i32, scene_path: GString) {
-        self.warehouse
-            .register_pool(&npc_type.to_string(), max_size, &scene_path.to_string());
-        // Emit signal
-        self.base_mut().emit_signal(
-            "pool_registered",
-            &[npc_type.to_variant(), max_size.to_variant()],
-        );
+    pub fn set_simulation_seed(&self, seed: i64) {
+        let _step = self.warehouse.hold_steps();
+        self.warehouse.set_rng_seed(seed as u64);
     }
 
-    // ===== Rust NPC Pool System Methods =====
-
-    /// Initialize an NPC pool (exposed to GDScript)
-    /// Call this on game start for each NPC type
+    /// Restart simulation time on a fresh manual clock at start_ms
+    /// (the default clock is already manual - fixed steps and advance_clock() move it)
+    /// Usage: NPCDataWarehouse.use_manual_clock(0)
     #[func]
-    pub fn initialize_npc_pool(&self, npc_type: GString, pool_size: i32, scene_path: GString) {
-        self.warehouse.initialize_npc_pool(
-            &npc_type.to_string(),
-            pool_size as usize,
-            &scene_path.to_string(),
-        );
+    pub fn use_manual_clock(&mut self, start_ms: i64) {
+        let _step = self.warehouse.hold_steps();
+        self.manual_clock = Some(self.warehouse.use_manual_clock(start_ms.max(0) as u64));
     }
 
-    /// Set the scene container where NPCs will be added as children
+    /// Switch the simulation to wall-clock time
+    /// Timers then ignore time scale, pause and fast_forward
+    /// Usage: NPCDataWarehouse.use_system_clock()
     #[func]
-    pub fn set_scene_container(&self, container: Gd<Node2D>) {
-        self.warehouse.set_scene_container(container);
+    pub fn use_system_clock(&mut self) {
+        let _step = self.warehouse.hold_steps();
+        self.manual_clock = None;
+        self.warehouse.set_clock(Arc::new(SystemClock));
     }
 
-    /// Spawn an NPC from the Rust pool
-    /// Returns the ULID bytes of the spawned NPC, or empty array if failed
+    /// Jump the manual clock forward by delta_ms without running any steps
+    /// (timers expire, nothing moves - use fast_forward() to simulate the time)
+    /// Returns the new simulation time, or -1 if no manual clock is installed
+    /// Usage: NPCDataWarehouse.advance_clock(16)
     #[func]
-    pub fn rust_spawn_npc(&self, npc_type: GString, position: Vector2) -> PackedByteArray {
-        if let Some(ulid_bytes) = self
-            .warehouse
-            .rust_spawn_npc(&npc_type.to_string(), position)
-        {
-            return PackedByteArray::from(&ulid_bytes[..]);
+    pub fn advance_clock(&self, delta_ms: i64) -> i64 {
+        let _step = self.warehouse.hold_steps();
+        match self.manual_clock {
+            Some(ref clock) => clock.advance_ms(delta_ms.max(0) as u64) as i64,
+            None => {
+                godot_warn!("[RUST CLOCK] advance_clock called without a manual clock");
+                -1
+            }
         }
-        PackedByteArray::new()
     }
 
-    /// Despawn an NPC and return it to the pool
+    /// Current simulation time in milliseconds (from the installed clock)
+    /// Usage: var now = NPCDataWarehouse.get_simulation_time_ms()
     #[func]
-    pub fn rust_despawn_npc(&self, ulid: PackedByteArray) -> bool {
-        let ulid_slice = ulid.as_slice();
-        self.warehouse.rust_despawn_npc(ulid_slice)
+    pub fn get_simulation_time_ms(&self) -> i64 {
+        self.warehouse.get_current_time_ms() as i64
     }
 
-    // ===== Healthbar Pool System Methods =====
+    // ===== TIME CONTROL =====
 
-    /// Initialize the healthbar pool by instantiating packed scenes in Rust
-    /// pool_size: Number of healthbars to pre-allocate
-    /// scene_path: Path to the healthbar.tscn file
+    /// Set battle speed (1.0 = normal, 2.0/4.0 = fast, clamped to 0..16)
+    /// Usage: NPCDataWarehouse.set_time_scale(2.0)
     #[func]
-    pub fn initialize_healthbar_pool(&self, pool_size: i32, scene_path: GString) {
-        self.warehouse.initialize_healthbar_pool(pool_size as usize, &scene_path.to_string());
+    pub fn set_time_scale(&self, scale: f32) {
+        self.warehouse.set_time_scale(scale);
     }
 
-    /// Get the number of healthbars in the pool
+    /// Current battle speed multiplier
+    /// Usage: var speed = NPCDataWarehouse.get_time_scale()
     #[func]
-    pub fn healthbar_pool_count(&self) -> i64 {
-        self.warehouse.healthbar_pool_count() as i64
+    pub fn get_time_scale(&self) -> f32 {
+        self.warehouse.time_scale()
     }
 
-    /// Get the number of active healthbar assignments
+    /// Freeze the battle - tick_combat() runs no steps until resume()
+    /// Usage: NPCDataWarehouse.pause()
     #[func]
-    pub fn active_healthbar_count(&self) -> i64 {
-        self.warehouse.active_healthbar_count() as i64
+    pub fn pause(&self) {
+        self.warehouse.pause();
     }
 
-    // ===== Release Effect Pool System Methods =====
-
-    /// Initialize the release effect pool for death animations
-    /// pool_size: Number of release effects to pre-allocate
-    /// scene_path: Path to the release.tscn file
-    /// animation_duration_ms: Duration of the release animation in milliseconds
+    /// Continue a paused battle
+    /// Usage: NPCDataWarehouse.resume()
     #[func]
-    pub fn initialize_release_pool(&self, pool_size: i32, scene_path: GString, animation_duration_ms: i32) {
-        self.warehouse.release_pool.initialize(
-            pool_size as usize,
-            &scene_path.to_string(),
-            animation_duration_ms as u64
-        );
+    pub fn resume(&self) {
+        self.warehouse.resume();
     }
 
-    /// Return a release effect to the pool (called from GDScript when animation finishes)
-    /// pool_index: The index of the release effect to return
+    /// Check if the battle is paused
+    /// Usage: NPCDataWarehouse.is_paused()
     #[func]
-    pub fn return_release_to_pool(&self, pool_index: i64) -> bool {
-        self.warehouse.release_pool.return_to_pool(pool_index as usize)
+    pub fn is_paused(&self) -> bool {
+        self.warehouse.is_paused()
     }
 
-    /// Get the number of release effects in the pool
+    /// Run exactly n fixed steps now (works while paused - frame-by-frame debugging)
+    /// Events are delivered through the signals; returns the steps run
+    /// Usage: NPCDataWarehouse.step(1)
     #[func]
-    pub fn release_pool_count(&self) -> i64 {
-        self.warehouse.release_pool.count() as i64
+    pub fn step(&mut self, n: i32) -> i32 {
+        let steps = self.simulation.step(n.max(0) as u32);
+        self.collect_tick_results();
+        steps as i32
     }
 
-    /// Get the number of release effects currently in use
+    /// Simulate `seconds` of battle right away (AFK catch-up)
+    /// Same fixed steps as live play, so the outcome matches; the events of the
+    /// skipped time are dropped. Returns the steps run
+    /// Usage: NPCDataWarehouse.fast_forward(300.0)
     #[func]
-    pub fn active_release_count(&self) -> i64 {
-        self.warehouse.release_pool.active_count() as i64
+    pub fn fast_forward(&mut self, seconds: f32) -> i32 {
+        let steps = self.simulation.fast_forward(seconds);
+        self.collect_tick_results();
+        steps as i32
     }
 
-    /// Get NPC name by ULID bytes
+    /// Start the combat system (sets flag, no actual thread)
+    /// Usage: NPCDataWarehouse.start_combat_system()
     #[func]
-    pub fn get_npc_name(&self, ulid: PackedByteArray) -> GString {
-        if ulid.len() != 16 {
-            return GString::from("");
-        }
-        let ulid_bytes: [u8; 16] = ulid.as_slice().try_into().unwrap_or([0u8; 16]);
-        self.warehouse
-            .npc_names
-            .get(&ulid_bytes)
-            .map(|v| v.value().clone())
-            .map(|name| GString::from(name))
-            .unwrap_or_else(|| GString::from(""))
+    pub fn start_combat_system(&self) {
+        self.warehouse.start_combat_thread();
     }
 
-    /// Get NPC type by ULID bytes
+    /// Stop the combat system
+    /// Usage: NPCDataWarehouse.stop_combat_system()
     #[func]
-    pub fn get_npc_type(&self, ulid: PackedByteArray) -> GString {
-        if ulid.len() != 16 {
-            return GString::from("");
-        }
-        let ulid_bytes: [u8; 16] = ulid.as_slice().try_into().unwrap_or([0u8; 16]);
-        self.warehouse
-            .npc_types
-            .get(&ulid_bytes)
-            .map(|v| v.value().clone())
-            .map(|npc_type| GString::from(npc_type))
-            .unwrap_or_else(|| GString::from(""))
+    pub fn stop_combat_system(&self) {
+        self.warehouse.stop_combat_thread();
     }
 
-    /// Get NPC stats dictionary by ULID bytes
-    /// Returns a Dictionary with keys: name, type, max_hp, attack, defense
+    /// Check if combat system is running
+    /// Usage: NPCDataWarehouse.is_combat_enabled()
     #[func]
-    pub fn get_npc_stats_dict(&self, ulid: PackedByteArray) -> Dictionary {
-        let mut dict = Dictionary::new();
-        if ulid.len() != 16 {
-            return dict;
-        }
-        let ulid_bytes: [u8; 16] = ulid.as_slice().try_into().unwrap_or([0u8; 16]);
-
-        // Get name
-        if let Some(name) = self
-            .warehouse
-            .npc_names
-            .get(&ulid_bytes)
-            .map(|v| v.value().clone())
-        {
-            dict.set("name", name);
-        }
-
-        // Get type
-        if let Some(npc_type) = self
-            .warehouse
-            .npc_types
-            .get(&ulid_bytes)
-            .map(|v| v.value().clone())
-        {
-            dict.set("type", npc_type);
-        }
-
-        // Get combat stats (hp, max_hp, attack, defense) from single struct
-        if let Some(stats_json) = self
-            .warehouse
-            .npc_combat_stats
-            .get(&ulid_bytes)
-            .map(|v| v.value().clone())
-        {
-            if let Ok(combat_stats) = serde_json::from_str::<NPCCombatStats>(&stats_json) {
-                dict.set("hp", combat_stats.hp);
-                dict.set("max_hp", combat_stats.max_hp);
-                dict.set("attack", combat_stats.attack);
-                dict.set("defense", combat_stats.defense);
-            }
-        }
-
-        dict
+    pub fn is_combat_enabled(&self) -> bool {
+        self.warehouse.is_combat_enabled()
     }
 
-    /// Get NPC data as JSON string (name, type, stats)
+    /// Tick combat logic and get events
+    /// delta = real seconds since the last call; runs the fixed steps it covers
+    /// at the current time scale (none while paused)
+    /// Returns array of JSON strings representing combat events (legacy format)
+    /// Emits the combat event signals, and npc_died for each death position
+    /// Usage: var events = NPCDataWarehouse.tick_combat(delta)
     #[func]
-    pub fn get_npc_data_json(&self, ulid: PackedByteArray) -> GString {
-        if ulid.len() != 16 {
-            return GString::from("{}");
-        }
-        let ulid_bytes: [u8; 16] = ulid.as_slice().try_into().unwrap_or([0u8; 16]);
-
-        let mut name = String::from("");
-        let mut npc_type = String::from("");
-        let mut hp = 0.0_f32;
-        let mut max_hp = 0.0_f32;
-        let mut attack = 0.0_f32;
-        let mut defense = 0.0_f32;
-        let mut emotional_state = 0_i32;
-        let mut mana = 0.0_f32;
-        let mut max_mana = 0.0_f32;
-        let mut energy = 0.0_f32;
-        let mut max_energy = 0.0_f32;
-
-        // Get name
-        if let Some(n) = self
-            .warehouse
-            .npc_names
-            .get(&ulid_bytes)
-            .map(|v| v.value().clone())
-        {
-            name = n;
-        }
-
-        // Get type
-        if let Some(t) = self
-            .warehouse
-            .npc_types
-            .get(&ulid_bytes)
-            .map(|v| v.value().clone())
-        {
-            npc_type = t;
-        }
+    pub fn tick_combat(&mut self, delta: f32) -> Array<GString> {
+        let events = self.run_full_tick(delta);
+        Self::combat_events_to_json(&events)
+    }
 
-        // Get combat stats
-        if let Some(stats_json) = self
-            .warehouse
-            .npc_combat_stats
-            .get(&ulid_bytes)
-            .map(|v| v.value().clone())
-        {
-            if let Ok(combat_stats) = serde_json::from_str::<NPCCombatStats>(&stats_json) {
-                hp = combat_stats.hp;
-                max_hp = combat_stats.max_hp;
-                attack = combat_stats.attack;
-                defense = combat_stats.defense;
-                emotional_state = combat_stats.emotional_state;
-                mana = combat_stats.mana;
-                max_mana = combat_stats.max_mana;
-                energy = combat_stats.energy;
-                max_energy = combat_stats.max_energy;
-            }
+    /// Tick combat logic and get events as Dictionaries
+    /// Same as tick_combat, but each event is a Dictionary with keys:
+    /// type ("attack", "projectile", "damage", "death"), attacker, target (ULID bytes),
+    /// position (global Vector2), amount (damage/death), projectile, origin, speed (projectile)
+    /// Usage: var events = NPCDataWarehouse.tick_combat_events(delta)
+    #[func]
+    pub fn tick_combat_events(&mut self, delta: f32) -> Array<Dictionary> {
+        let events = self.run_full_tick(delta);
+        let mut godot_array = Array::new();
+        for event in &events {
+            godot_array.push(&self.combat_event_to_dict(event));
         }
-
-        // Build JSON string manually (simple and fast)
-        let json = format!(
-            r#"{{"name":"{}","type":"{}","hp":{},"max_hp":{},"attack":{},"defense":{},"emotional_state":{},"mana":{},"max_mana":{},"energy":{},"max_energy":{}}}"#,
-            name,
-            npc_type,
-            hp,
-            max_hp,
-            attack,
-            defense,
-            emotional_state,
-            mana,
-            max_mana,
-            energy,
-            max_energy
-        );
-
-        GString::from(json)
+        godot_array
     }
 
-    /// Store active NPC data
-    /// npc_data should be JSON with at minimum {"type":"npc_type_here"}
+    /// Tick ONLY the combat phase (damage calculations and state changes)
+    /// Returns array of JSON strings representing combat events
+    /// Does nothing while the simulation thread is running
+    /// Usage: var events = NPCDataWarehouse.tick_combat_phase()
     #[func]
-    pub fn store_npc(&mut self, ulid: GString, npc_data: GString) {
-        self.warehouse
-            .store_npc(&ulid.to_string(), &npc_data.to_string());
-
-        // Extract npc_type from JSON (simple parse - assumes format {"type":"value"})
-        let npc_type = GString::from("unknown"); // TODO: Parse JSON properly
-
-        // Emit signal
-        self.base_mut()
-            .emit_signal("npc_stored", &[ulid.to_variant(), npc_type.to_variant()]);
+    pub fn tick_combat_phase(&mut self) -> Array<GString> {
+        let Some(world) = self.simulation.world_mut() else {
+            return Array::new();
+        };
+        let events = world.run_combat_phase();
+        self.simulation.publish_frame();
+        self.nodes.apply_commands(&self.warehouse);
+        self.emit_combat_signals(&events);
+        Self::combat_events_to_json(&events)
     }
 
-    /// Get active NPC data
+    /// Tick ONLY the movement phase (position updates and spawning)
+    /// Returns array of JSON strings representing movement events
+    /// Does nothing while the simulation thread is running
+    /// Usage: var events = NPCDataWarehouse.tick_movement_phase(delta)
     #[func]
-    pub fn get_npc(&self, ulid: GString) -> GString {
-        self.warehouse
-            .get_npc(&ulid.to_string())
-            .map(|s| GString::from(s))
-            .unwrap_or_else(|| GString::from(""))
+    pub fn tick_movement_phase(&mut self, delta: f32) -> Array<GString> {
+        let Some(world) = self.simulation.world_mut() else {
+            return Array::new();
+        };
+        let events = world.run_movement_phase(delta);
+        self.simulation.publish_frame();
+        self.nodes.apply_commands(&self.warehouse);
+        self.nodes.sync_positions(&self.simulation.frame());
+        self.emit_combat_signals(&events);
+        Self::combat_events_to_json(&events)
     }
 
-    /// Remove NPC from active pool
-    #[func]
-    pub fn remove_npc(&mut self, ulid: GString) -> bool {
-        let removed = self.warehouse.remove_npc(&ulid.to_string());
-        if removed {
-            // Emit signal
-            self.base_mut().emit_signal(
-                "npc_removed",
-                &[ulid.to_variant(), GString::from("unknown").to_variant()],
-            );
+    /// Tick ONLY the animation phase (visual updates), then fire due timers
+    /// (transient state clears, despawns of finished death animations)
+    /// Does not return events - just updates animation states
+    /// Does nothing while the simulation thread is running
+    /// Usage: NPCDataWarehouse.tick_animation_phase()
+    #[func]
+    pub fn tick_animation_phase(&mut self) {
+        if self.simulation.is_threaded() {
+            return;
         }
-        removed
+        self.nodes.sync_animations(&self.simulation.frame());
+        if let Some(world) = self.simulation.world_mut() {
+            world.run_animation_phase();
+        }
+        self.simulation.publish_frame();
+        self.nodes.apply_commands(&self.warehouse);
     }
 
-    /// Store NPC state flags (raw integer, not JSON)
-    /// Much faster than storing in AI state JSON
+    // ===== SIMULATION THREAD =====
+
+    /// Run the simulation on a worker thread at a fixed rate (tick_hz <= 0 uses 60)
+    /// tick_combat() then stops ticking and only applies what the worker produced:
+    /// queued events, node commands and the latest frame
+    /// Returns false if already running or threads are unavailable (web export)
+    /// Usage: NPCDataWarehouse.start_simulation_thread(60.0)
     #[func]
-    pub fn store_npc_state(&mut self, ulid: GString, state: i32) {
-        // Store as simple integer string for fast access
-        self.warehouse
-            .store_combat_state(&format!("{}_state", ulid.to_string()), &state.to_string());
+    pub fn start_simulation_thread(&mut self, tick_hz: f32) -> bool {
+        let tick_hz = if tick_hz > 0.0 {
+            tick_hz
+        } else {
+            DEFAULT_TICK_HZ
+        };
+        self.simulation.start_thread(tick_hz)
     }
 
-    /// Get NPC state flags (raw integer)
+    /// Stop the worker thread - tick_combat() ticks inline again
+    /// Usage: NPCDataWarehouse.stop_simulation_thread()
     #[func]
-    pub fn get_npc_state(&self, ulid: GString) -> i32 {
-        self.warehouse
-            .get_combat_state(&format!("{}_state", ulid.to_string()))
-            .and_then(|s| s.parse::<i32>().ok())
-            .unwrap_or(NPCState::IDLE.to_i32())
+    pub fn stop_simulation_thread(&mut self) {
+        self.simulation.stop_thread();
     }
 
-    /// Store AI state
+    /// Check if the simulation is running on its worker thread
+    /// Usage: NPCDataWarehouse.is_simulation_threaded()
     #[func]
-    pub fn store_ai_state(&mut self, ulid: GString, ai_data: GString) {
-        self.warehouse
-            .store_ai_state(&ulid.to_string(), &ai_data.to_string());
-        // Emit signal
-        self.base_mut().emit_signal(
-            "ai_state_changed",
-            &[ulid.to_variant(), ai_data.to_variant()],
-        );
+    pub fn is_simulation_threaded(&self) -> bool {
+        self.simulation.is_threaded()
     }
 
-    /// Get AI state
+    /// Number of full ticks run so far (inline or threaded)
+    /// Usage: var ticks = NPCDataWarehouse.get_simulation_tick()
     #[func]
-    pub fn get_ai_state(&self, ulid: GString) -> GString {
-        self.warehouse
-            .get_ai_state(&ulid.to_string())
-            .map(|s| GString::from(s))
-            .unwrap_or_else(|| GString::from(""))
+    pub fn get_simulation_tick(&self) -> i64 {
+        self.simulation.ticks() as i64
     }
 
-    /// Store combat state
+    /// Get NPC current HP
+    /// Usage: var hp = NPCDataWarehouse.get_npc_hp(ulid_bytes)
     #[func]
-    pub fn store_combat_state(&mut self, ulid: GString, combat_data: GString) {
-        self.warehouse
-            .store_combat_state(&ulid.to_string(), &combat_data.to_string());
-        // Emit signal
-        self.base_mut().emit_signal(
-            "combat_state_changed",
-            &[ulid.to_variant(), combat_data.to_variant()],
-        );
+    pub fn get_npc_hp(&self, ulid_bytes: PackedByteArray) -> f32 {
+        match packed_bytes_to_ulid(&ulid_bytes) {
+            Ok(ulid) => self.warehouse.get_npc_hp_internal(&ulid).unwrap_or(0.0),
+            Err(_) => 0.0,
+        }
     }
 
-    /// Get combat state
+    /// Get NPC behavioral state (IDLE, WALKING, ATTACKING, DAMAGED, DEAD, etc.)
+    /// Usage: var state = NPCDataWarehouse.get_npc_behavioral_state(ulid_bytes)
     #[func]
-    pub fn get_combat_state(&self, ulid: GString) -> GString {
-        self.warehouse
-            .get_combat_state(&ulid.to_string())
-            .map(|s| GString::from(s))
-            .unwrap_or_else(|| GString::from(""))
+    pub fn get_npc_behavioral_state(&self, ulid_bytes: PackedByteArray) -> i32 {
+        match packed_bytes_to_ulid(&ulid_bytes) {
+            Ok(ulid) => self
+                .warehouse
+                .get_behavioral_state(&ulid)
+                .map_or(0, |state| state.bits() as i32),
+            Err(_) => 0,
+        }
     }
 
-    /// Check if NPC exists
+    /// Get NPC position
+    /// Usage: var pos = NPCDataWarehouse.get_npc_position(ulid)
+    /// Accepts ULID as PackedByteArray (16 bytes)
     #[func]
-    pub fn has_npc(&self, ulid: GString) -> bool {
-        self.warehouse.has_npc(&ulid.to_string())
+    pub fn get_npc_position(&self, ulid: PackedByteArray) -> PackedFloat32Array {
+        if ulid.len() != 16 {
+            return PackedFloat32Array::new();
+        }
+        let ulid_bytes: [u8; 16] = ulid.as_slice().try_into().unwrap_or([0u8; 16]);
+
+        match self.warehouse.get_npc_position_internal(&ulid_bytes) {
+            Some(pos) => PackedFloat32Array::from(&[pos.x, pos.y]),
+            None => PackedFloat32Array::new(),
+        }
     }
 
-    /// Get total entries
+    // ===== DAMAGE =====
+    // Every hit uses attack / attack_divisor - defense / defense_divisor
+    // (at least min_damage), then variance, crits and resistances
+
+    /// Set the damage formula (keeps the current variance)
+    /// Usage: NPCDataWarehouse.set_damage_formula(6.0, 8.0, 1.5)
     #[func]
-    pub fn total_entries(&self) -> i32 {
-        self.warehouse.total_entries() as i32
+    pub fn set_damage_formula(
+        &self,
+        attack_divisor: f32,
+        defense_divisor: f32,
+        min_damage: f32,
+    ) -> bool {
+        let _step = self.warehouse.hold_steps();
+        let config = DamageConfig {
+            attack_divisor,
+            defense_divisor,
+            min_damage,
+            ..self.warehouse.damage_config()
+        };
+        self.apply_damage_config(config)
     }
 
-    /// Get read store count (Papaya - lock-free)
+    /// Set the random spread of hits (0.1 = +/-10%, 0 = none)
+    /// Usage: NPCDataWarehouse.set_damage_variance(0.1)
     #[func]
-    pub fn read_store_count(&self) -> i32 {
-        self.warehouse.read_store_count() as i32
+    pub fn set_damage_variance(&self, variance: f32) -> bool {
+        let _step = self.warehouse.hold_steps();
+        let config = DamageConfig {
+            variance,
+            ..self.warehouse.damage_config()
+        };
+        self.apply_damage_config(config)
     }
 
-    /// Get write store count (DashMap - concurrent)
-    #[func]
-    pub fn write_store_count(&self) -> i32 {
-        self.warehouse.write_store_count() as i32
+    fn apply_damage_config(&self, config: DamageConfig) -> bool {
+        match self.warehouse.set_damage_config(config) {
+            Ok(()) => true,
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                false
+            }
+        }
     }
 
-    /// Manually trigger sync
+    /// Get the damage formula as a Dictionary
+    /// Usage: var config = NPCDataWarehouse.get_damage_config()
     #[func]
-    pub fn sync(&mut self) {
-        self.warehouse.sync();
-        let count_after = self.warehouse.read_store_count();
-
-        // Emit signal with count of synced entries
-        self.base_mut()
-            .emit_signal("sync_completed", &[(count_after as i32).to_variant()]);
+    pub fn get_damage_config(&self) -> Dictionary {
+        let config = self.warehouse.damage_config();
+        let mut dict = Dictionary::new();
+        dict.set("attack_divisor", config.attack_divisor);
+        dict.set("defense_divisor", config.defense_divisor);
+        dict.set("min_damage", config.min_damage);
+        dict.set("variance", config.variance);
+        dict
     }
 
-    /// Clear all data
+    /// Take the breakdowns of the hits since the last call, oldest first
+    /// (attacker, target, damage_type, attack, defense, base, variance, crit,
+    /// crit_multiplier, resistance, modifiers {name: change}, amount, time_ms)
+    /// Usage: for hit in NPCDataWarehouse.get_damage_log(): print(hit)
     #[func]
-    pub fn clear_all(&self) {
-        self.warehouse.clear_all();
+    pub fn get_damage_log(&self) -> Array<Dictionary> {
+        let mut godot_array = Array::new();
+        for hit in self.warehouse.take_damage_log() {
+            let mut modifiers = Dictionary::new();
+            for (name, change) in &hit.modifiers {
+                modifiers.set(name.as_str(), *change);
+            }
+            let mut dict = Dictionary::new();
+            dict.set("attacker", PackedByteArray::from(&hit.attacker[..]));
+            dict.set("target", PackedByteArray::from(&hit.target[..]));
+            dict.set("damage_type", hit.damage_type.as_str());
+            dict.set("attack", hit.attack);
+            dict.set("defense", hit.defense);
+            dict.set("base", hit.base);
+            dict.set("variance", hit.variance);
+            dict.set("crit", hit.crit);
+            dict.set("crit_multiplier", hit.crit_multiplier);
+            dict.set("resistance", hit.resistance);
+            dict.set("modifiers", modifiers);
+            dict.set("amount", hit.amount);
+            dict.set("time_ms", hit.time_ms as i64);
+            godot_array.push(&dict);
+        }
+        godot_array
     }
 
-    // ===== State Helper Methods (MINIMAL - Rust handles combat) =====
+    // ===== STATUS EFFECTS =====
+    // Built-in effects: "poison", "burn", "slow", "stun", "regen"
 
-    /// Get NPCState constant value by name (behavioral states only)
-    /// Usage in GDScript: NPCDataWarehouse.get_state("IDLE") returns 1
+    /// Register (or replace) a status effect from its JSON object
+    /// Usage: NPCDataWarehouse.register_status_effect('{"name": "spores", "duration_ms": 4000, ...}')
     #[func]
-    pub fn get_state(&self, state_name: GString) -> i32 {
-        match state_name.to_string().to_uppercase().as_str() {
-            "IDLE" => NPCState::IDLE.to_i32(),
-            "WALKING" => NPCState::WALKING.to_i32(),
-            "ATTACKING" => NPCState::ATTACKING.to_i32(),
-            "COMBAT" => NPCState::COMBAT.to_i32(),
-            "DAMAGED" => NPCState::DAMAGED.to_i32(),
-            "DEAD" => NPCState::DEAD.to_i32(),
-            _ => 0,
+    pub fn register_status_effect(&self, effect_json: GString) -> bool {
+        let _step = self.warehouse.hold_steps();
+        match self
+            .warehouse
+            .register_status_effect_json(&effect_json.to_string())
+        {
+            Ok(()) => true,
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                false
+            }
         }
     }
 
-    /// Confirm spawn completed successfully (called by GDScript after spawn)
-    /// Defensive programming: tracks spawn success rate
-    /// Usage: NPCDataWarehouse.confirm_spawn(ulid_bytes, monster_type, static_state, behavioral_state)
+    /// Get the names of every registered status effect
     #[func]
-    pub fn confirm_spawn(
-        &self,
-        ulid_bytes: PackedByteArray,
-        monster_type: GString,
-        static_state: i32,
-        behavioral_state: i32,
-    ) {
-        use std::sync::atomic::Ordering;
-
-        match packed_bytes_to_hex(&ulid_bytes) {
-            Ok(ulid_hex) => {
-                // Increment confirmation counter
-                self.warehouse
-                    .spawn_confirmations
-                    .fetch_add(1, Ordering::Relaxed);
-
-                // Log confirmation (every 10th spawn)
-                let confirmations = self.warehouse.spawn_confirmations.load(Ordering::Relaxed);
-                let requests = self.warehouse.spawn_requests.load(Ordering::Relaxed);
-
-                if confirmations % 10 == 0 {
-                    let success_rate = if requests > 0 {
-                        (confirmations as f32 / requests as f32 * 100.0) as u64
-                    } else {
-                        100
-                    };
-                    godot_print!(
-                        "[RUST SPAWN] Spawn stats: {} requests, {} confirmed ({}% success)",
-                        requests,
-                        confirmations,
-                        success_rate
-                    );
-                }
-
-                // Verify the spawned NPC has correct state
-                if let Some(actual_static) =
-                    self.warehouse.get_stat_value(&ulid_hex, "static_state")
-                {
-                    if (actual_static as i32) != static_state {
-                        godot_warn!("[RUST SPAWN] NPC {} spawned with incorrect static_state! Expected: {}, Got: {}",
-                            &ulid_hex[0..8], static_state, actual_static as i32);
-                    }
-                }
+    pub fn get_status_effect_names(&self) -> PackedStringArray {
+        self.warehouse
+            .status_effect_defs()
+            .iter()
+            .map(|def| GString::from(&def.name))
+            .collect()
+    }
 
-                if let Some(actual_behavioral) =
-                    self.warehouse.get_stat_value(&ulid_hex, "behavioral_state")
-                {
-                    if (actual_behavioral as i32) != behavioral_state {
-                        godot_warn!("[RUST SPAWN] NPC {} spawned with incorrect behavioral_state! Expected: {}, Got: {}",
-                            &ulid_hex[0..8], behavioral_state, actual_behavioral as i32);
-                    }
-                }
-            }
+    /// Apply a status effect to an NPC (source: ULID credited with kills,
+    /// empty = none). Returns the effect's stacks, or -1 on error
+    /// Usage: NPCDataWarehouse.apply_status_effect(target_ulid, "poison", attacker_ulid)
+    #[func]
+    pub fn apply_status_effect(
+        &self,
+        ulid: PackedByteArray,
+        effect: GString,
+        source: PackedByteArray,
+    ) -> i32 {
+        let _step = self.warehouse.hold_steps();
+        let result = packed_bytes_to_ulid(&ulid).and_then(|ulid| {
+            let source = if source.is_empty() {
+                None
+            } else {
+                Some(packed_bytes_to_ulid(&source)?)
+            };
+            self.warehouse
+                .apply_status_effect(&ulid, &effect.to_string(), source)
+        });
+        match result {
+            Ok(stacks) => stacks as i32,
             Err(e) => {
-                godot_error!("[RUST SPAWN] Invalid ULID in confirm_spawn: {}", e);
+                godot_error!("NPCDataWarehouse: {}", e);
+                -1
             }
         }
     }
 
-    /// Handle projectile hit - called by GDScript when arrow/projectile collides with target
-    /// Calculates damage, applies it, and returns events (damage or death)
-    /// Usage: var events_json = NPCDataWarehouse.projectile_hit(attacker_ulid, target_ulid)
+    /// Remove a status effect from an NPC (false if it didn't have it)
+    /// Usage: NPCDataWarehouse.remove_status_effect(ulid, "poison")
     #[func]
-    pub fn projectile_hit(
-        &self,
-        attacker_ulid_bytes: PackedByteArray,
-        target_ulid_bytes: PackedByteArray,
-    ) -> Array<GString> {
-        use std::sync::atomic::Ordering;
+    pub fn remove_status_effect(&self, ulid: PackedByteArray, effect: GString) -> bool {
+        let _step = self.warehouse.hold_steps();
+        packed_bytes_to_ulid(&ulid)
+            .map(|ulid| {
+                self.warehouse
+                    .remove_status_effect(&ulid, &effect.to_string())
+            })
+            .unwrap_or(false)
+    }
 
-        // Convert PackedByteArray to [u8; 16]
-        if attacker_ulid_bytes.len() != 16 || target_ulid_bytes.len() != 16 {
-            godot_error!("[PROJECTILE] Invalid ULID bytes length in projectile_hit");
-            return Array::new();
+    /// Get the status effects running on an NPC
+    /// (name, stacks, remaining_ms - -1 for effects that last until removed)
+    /// Usage: for effect in NPCDataWarehouse.get_status_effects(ulid): print(effect.name)
+    #[func]
+    pub fn get_status_effects(&self, ulid: PackedByteArray) -> Array<Dictionary> {
+        let mut godot_array = Array::new();
+        let Ok(ulid) = packed_bytes_to_ulid(&ulid) else {
+            return godot_array;
+        };
+        let now_ms = self.warehouse.get_current_time_ms();
+        for effect in self.warehouse.npc_status_effects(&ulid) {
+            let remaining_ms = effect
+                .expires_ms
+                .map_or(-1, |at_ms| at_ms.saturating_sub(now_ms) as i64);
+            let mut dict = Dictionary::new();
+            dict.set("name", effect.name.as_str());
+            dict.set("stacks", effect.stacks as i64);
+            dict.set("remaining_ms", remaining_ms);
+            godot_array.push(&dict);
         }
+        godot_array
+    }
 
-        let attacker_bytes: [u8; 16] = attacker_ulid_bytes.as_slice().try_into().unwrap();
-        let target_bytes: [u8; 16] = target_ulid_bytes.as_slice().try_into().unwrap();
+    // ===== HUNGER / FOOD =====
+    // Kingdom NPCs (allies and pets) eat from the food stockpile when hungry
+
+    /// Food units in the kingdom's stockpile
+    /// Usage: var food = NPCDataWarehouse.get_food_stockpile()
+    #[func]
+    pub fn get_food_stockpile(&self) -> i64 {
+        self.warehouse.food_stockpile() as i64
+    }
 
-        // Convert to hex for legacy functions
-        let attacker_ulid = bytes_to_hex(&attacker_bytes);
-        let target_ulid = bytes_to_hex(&target_bytes);
+    /// Put food into the stockpile, returns the new total
+    /// Usage: NPCDataWarehouse.add_food(10)
+    #[func]
+    pub fn add_food(&self, units: i64) -> i64 {
+        let _step = self.warehouse.hold_steps();
+        self.warehouse
+            .add_food(units.clamp(0, u32::MAX as i64) as u32) as i64
+    }
 
-        // Validate target is alive
-        let target_hp = self
-            .warehouse
-            .get_stat_value(&target_ulid, "hp")
-            .unwrap_or(0.0);
-        if target_hp <= 0.0 {
-            // Target already dead, no damage
-            return Array::new();
+    /// Feed an NPC one unit from the stockpile
+    /// Returns its hunger afterwards, or -1 on error (dead NPC, empty stockpile)
+    /// Usage: NPCDataWarehouse.feed_npc(ulid)
+    #[func]
+    pub fn feed_npc(&self, ulid: PackedByteArray) -> f32 {
+        let _step = self.warehouse.hold_steps();
+        match packed_bytes_to_ulid(&ulid).and_then(|ulid| self.warehouse.feed_npc(&ulid)) {
+            Ok(hunger) => hunger,
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                -1.0
+            }
         }
+    }
 
-        // Get attacker and target stats
-        let attacker_attack = self
-            .warehouse
-            .get_stat_value(&attacker_ulid, "attack")
-            .unwrap_or(10.0);
-        let target_defense = self
-            .warehouse
-            .get_stat_value(&target_ulid, "defense")
-            .unwrap_or(5.0);
+    // ===== EMOTIONS =====
+    // Hits, kills, deaths nearby, heals, hunger and energy move each NPC's
+    // emotion; it decays back to "neutral" over time
 
-        // Calculate damage
-        let damage = (attacker_attack - (target_defense / 2.0)).max(1.0);
+    /// Current emotion of an NPC ("neutral" if it isn't registered for combat)
+    /// Usage: var emotion = NPCDataWarehouse.get_npc_emotion(ulid)
+    #[func]
+    pub fn get_npc_emotion(&self, ulid: PackedByteArray) -> GString {
+        match packed_bytes_to_ulid(&ulid) {
+            Ok(ulid) => GString::from(self.warehouse.npc_emotion(&ulid).as_str()),
+            Err(_) => GString::from(Emotion::Neutral.as_str()),
+        }
+    }
 
-        // Apply damage
-        let new_target_hp = self.warehouse.apply_damage(&target_ulid, damage);
+    // ===== THREAT / TARGETING =====
+    // Damage, heals and taunts build threat; each archetype picks its target
+    // by "highest_threat", "lowest_hp", "nearest" or "preferred" types
 
-        // Get target position for event
-        let (target_x, target_y) = self
-            .warehouse
-            .get_npc_position_internal(&target_ulid)
-            .unwrap_or((0.0, 0.0));
-
-        // Generate event based on result
-        let event = if new_target_hp <= 0.0 {
-            // Mark target as dead
-            self.warehouse.mark_dead(&target_ulid);
-
-            CombatEvent {
-                event_type: "death".to_string(),
-                attacker_ulid,
-                target_ulid,
-                amount: damage,
-                attacker_animation: "".to_string(),
-                target_animation: "death".to_string(),
-                target_x,
-                target_y,
-            }
-        } else {
-            // Set DAMAGED state on target (use bytes)
-            self.warehouse.add_damaged_state(&target_bytes);
-
-            CombatEvent {
-                event_type: "damage".to_string(),
-                attacker_ulid,
-                target_ulid,
-                amount: damage,
-                attacker_animation: "".to_string(),
-                target_animation: "hurt".to_string(),
-                target_x,
-                target_y,
+    /// Make an NPC go after a hostile target until it dies (player command),
+    /// or pass an empty target to let the NPC pick its own again
+    /// Usage: NPCDataWarehouse.force_npc_target(archer_ulid, eyebeast_ulid)
+    #[func]
+    pub fn force_npc_target(&self, ulid: PackedByteArray, target: PackedByteArray) -> bool {
+        let _step = self.warehouse.hold_steps();
+        let result = packed_bytes_to_ulid(&ulid).and_then(|ulid| {
+            let target = if target.is_empty() {
+                None
+            } else {
+                Some(packed_bytes_to_ulid(&target)?)
+            };
+            self.warehouse.force_target(&ulid, target)
+        });
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                false
             }
-        };
-
-        // Return event as JSON array
-        let mut godot_array = Array::new();
-        let json = serde_json::to_string(&event).unwrap_or_default();
-        godot_array.push(&GString::from(&json));
-        godot_array
+        }
     }
 
-    /// Apply healing to an NPC
-    /// target_ulid_bytes: ULID of NPC to heal
-    /// heal_amount: Amount of HP to restore
-    /// hunger_gain: Hunger increase (hidden stat)
-    /// energy_gain: Energy increase (hidden stat)
-    /// Returns: New HP value
+    /// Taunt every hostile near an NPC so they turn on it
+    /// Returns how many NPCs were taunted, or -1 on error
+    /// Usage: NPCDataWarehouse.taunt_npc(warrior_ulid)
     #[func]
-    pub fn apply_healing(
-        &self,
-        target_ulid_bytes: PackedByteArray,
-        heal_amount: f32,
-        hunger_gain: f32,
-        energy_gain: f32,
-    ) -> f32 {
-        if target_ulid_bytes.len() != 16 {
-            godot_error!("[HEALING] Invalid ULID bytes length");
-            return 0.0;
+    pub fn taunt_npc(&self, ulid: PackedByteArray) -> i32 {
+        let _step = self.warehouse.hold_steps();
+        match packed_bytes_to_ulid(&ulid).and_then(|ulid| self.warehouse.taunt(&ulid)) {
+            Ok(taunted) => taunted as i32,
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                -1
+            }
         }
+    }
 
-        let target_bytes: [u8; 16] = target_ulid_bytes.as_slice().try_into().unwrap();
-        let target_ulid = bytes_to_hex(&target_bytes);
-
-        self.warehouse.apply_healing(&target_ulid, heal_amount, hunger_gain, energy_gain)
+    /// Get the ULID of the NPC's current target (empty when it has none)
+    /// Usage: var target = NPCDataWarehouse.get_npc_target(ulid)
+    #[func]
+    pub fn get_npc_target(&self, ulid: PackedByteArray) -> PackedByteArray {
+        packed_bytes_to_ulid(&ulid)
+            .ok()
+            .and_then(|ulid| self.warehouse.npc_target(&ulid))
+            .map_or_else(PackedByteArray::new, |target| {
+                PackedByteArray::from(&target[..])
+            })
     }
 
-    /// Get NPCStaticState constant value by name (combat types + factions)
-    /// Usage in GDScript: NPCDataWarehouse.get_static_state("MELEE") returns 1
+    /// Get an NPC's threat table, highest threat first (source, threat)
+    /// Usage: for entry in NPCDataWarehouse.get_npc_threat(ulid): print(entry.threat)
     #[func]
-    pub fn get_static_state(&self, state_name: GString) -> i32 {
-        match state_name.to_string().to_uppercase().as_str() {
-            "MELEE" => NPCStaticState::MELEE.to_i32(),
-            "RANGED" => NPCStaticState::RANGED.to_i32(),
-            "MAGIC" => NPCStaticState::MAGIC.to_i32(),
-            "HEALER" => NPCStaticState::HEALER.to_i32(),
-            "ALLY" => NPCStaticState::ALLY.to_i32(),
-            "MONSTER" => NPCStaticState::MONSTER.to_i32(),
-            "PASSIVE" => NPCStaticState::PASSIVE.to_i32(),
-            _ => 0,
+    pub fn get_npc_threat(&self, ulid: PackedByteArray) -> Array<Dictionary> {
+        let mut godot_array = Array::new();
+        let Ok(ulid) = packed_bytes_to_ulid(&ulid) else {
+            return godot_array;
+        };
+        for (source, threat) in self.warehouse.npc_threat(&ulid) {
+            let mut dict = Dictionary::new();
+            dict.set("source", PackedByteArray::from(&source[..]));
+            dict.set("threat", threat);
+            godot_array.push(&dict);
         }
+        godot_array
     }
 
-    /// Set world bounds for waypoint clamping (from BackgroundManager safe_rectangle)
-    /// Usage: NPCDataWarehouse.set_world_bounds(min_x, max_x, min_y, max_y)
-    /// Should be called when background loads/changes
+    // ===== SQUADS =====
+    // Named groups that march in formation: "line", "wedge", "column" or
+    // "ranked" (melee in front, ranged and healers behind)
+
+    /// Form a squad around a leader (the squad name is a single word)
+    /// Usage: NPCDataWarehouse.create_squad("vanguard", warrior_ulid, "ranked")
     #[func]
-    pub fn set_world_bounds(&self, min_x: f32, max_x: f32, min_y: f32, max_y: f32) {
-        use std::sync::atomic::Ordering;
-        self.warehouse
-            .world_min_x
-            .store(min_x.to_bits(), Ordering::Relaxed);
-        self.warehouse
-            .world_max_x
-            .store(max_x.to_bits(), Ordering::Relaxed);
-        self.warehouse
-            .world_min_y
-            .store(min_y.to_bits(), Ordering::Relaxed);
-        self.warehouse
-            .world_max_y
-            .store(max_y.to_bits(), Ordering::Relaxed);
-        godot_print!(
-            "[RUST BOUNDS] Updated world bounds: X({} to {}), Y({} to {})",
-            min_x,
-            max_x,
-            min_y,
-            max_y
-        );
+    pub fn create_squad(&self, name: GString, leader: PackedByteArray, formation: GString) -> bool {
+        let _step = self.warehouse.hold_steps();
+        let Some(formation) = Formation::parse(&formation.to_string()) else {
+            godot_error!(
+                "NPCDataWarehouse: Unknown formation '{}' (line/wedge/column/ranked)",
+                formation
+            );
+            return false;
+        };
+        let result = packed_bytes_to_ulid(&leader).and_then(|leader| {
+            self.warehouse
+                .create_squad(&name.to_string(), &leader, formation)
+        });
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                false
+            }
+        }
     }
 
-    /// Check if a state has a specific flag set
-    /// Usage: NPCDataWarehouse.has_state_flag(npc_state, "IDLE")
+    /// Add an NPC to a squad (it leaves its current squad)
+    /// Usage: NPCDataWarehouse.assign_to_squad("vanguard", archer_ulid)
     #[func]
-    pub fn has_state_flag(&self, state: i32, flag_name: GString) -> bool {
-        let flag_value = self.get_state(flag_name);
-        (state & flag_value) != 0
+    pub fn assign_to_squad(&self, name: GString, ulid: PackedByteArray) -> bool {
+        let _step = self.warehouse.hold_steps();
+        let result = packed_bytes_to_ulid(&ulid)
+            .and_then(|ulid| self.warehouse.assign_to_squad(&name.to_string(), &ulid));
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                false
+            }
+        }
     }
 
-    /// Combine multiple states using bitwise OR
-    /// Usage: NPCDataWarehouse.combine_states(state1, state2)
+    /// Take an NPC out of its squad (false if it wasn't in one)
     #[func]
-    pub fn combine_states(&self, state1: i32, state2: i32) -> i32 {
-        state1 | state2
+    pub fn remove_from_squad(&self, ulid: PackedByteArray) -> bool {
+        let _step = self.warehouse.hold_steps();
+        packed_bytes_to_ulid(&ulid).is_ok_and(|ulid| self.warehouse.remove_from_squad(&ulid))
     }
 
-    /// Remove a flag from a state using bitwise AND NOT
-    /// Usage: NPCDataWarehouse.remove_state_flag(npc_state, "IDLE")
+    /// Disband a squad (emits squad_disbanded)
     #[func]
-    pub fn remove_state_flag(&self, state: i32, flag_name: GString) -> i32 {
-        let flag_value = self.get_state(flag_name);
-        state & !flag_value
+    pub fn disband_squad(&self, name: GString) -> bool {
+        let _step = self.warehouse.hold_steps();
+        self.warehouse.disband_squad(&name.to_string())
     }
 
-    /// Add a flag to a state using bitwise OR
-    /// Usage: NPCDataWarehouse.add_state_flag(npc_state, "WALKING")
+    /// Order a squad to march to a point, keeping formation (emits squad_arrived)
+    /// Usage: NPCDataWarehouse.order_squad("vanguard", Vector2(640, 360))
     #[func]
-    pub fn add_state_flag(&self, state: i32, flag_name: GString) -> i32 {
-        let flag_value = self.get_state(flag_name);
-        state | flag_value
+    pub fn order_squad(&self, name: GString, target: Vector2) -> bool {
+        let _step = self.warehouse.hold_steps();
+        match self
+            .warehouse
+            .order_squad(&name.to_string(), Vec2::new(target.x, target.y))
+        {
+            Ok(()) => true,
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                false
+            }
+        }
     }
 
-    /// Remove ATTACKING state flag after attack animation finishes
-    /// Called by GDScript animation_finished handler
-    /// Usage: NPCDataWarehouse.clear_attacking_state(ulid_bytes)
+    /// Change a squad's formation
+    /// Usage: NPCDataWarehouse.set_squad_formation("vanguard", "wedge")
     #[func]
-    pub fn clear_attacking_state(&self, ulid_bytes: PackedByteArray) {
-        if ulid_bytes.len() == 16 {
-            if let Ok(ulid_array) = TryInto::<[u8; 16]>::try_into(ulid_bytes.as_slice()) {
-                self.warehouse.remove_attacking_state(&ulid_array);
-            } else {
-                godot_error!("[COMBAT ERROR] Failed to convert PackedByteArray to [u8; 16]");
-            }
-        } else {
+    pub fn set_squad_formation(&self, name: GString, formation: GString) -> bool {
+        let _step = self.warehouse.hold_steps();
+        let Some(formation) = Formation::parse(&formation.to_string()) else {
             godot_error!(
-                "[COMBAT ERROR] Invalid ULID bytes length for clear_attacking_state: {} (expected 16)",
-                ulid_bytes.len()
+                "NPCDataWarehouse: Unknown formation '{}' (line/wedge/column/ranked)",
+                formation
             );
+            return false;
+        };
+        match self
+            .warehouse
+            .set_squad_formation(&name.to_string(), formation)
+        {
+            Ok(()) => true,
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                false
+            }
         }
     }
 
-    /// Remove DAMAGED state flag after hurt animation finishes
-    /// Called by GDScript animation_finished handler
-    /// Usage: NPCDataWarehouse.clear_damaged_state(ulid_bytes)
+    /// Squad names, in name order
     #[func]
-    pub fn clear_damaged_state(&self, ulid_bytes: PackedByteArray) {
-        if ulid_bytes.len() == 16 {
-            if let Ok(ulid_array) = TryInto::<[u8; 16]>::try_into(ulid_bytes.as_slice()) {
-                self.warehouse.remove_damaged_state(&ulid_array);
-            } else {
-                godot_error!("[COMBAT ERROR] Failed to convert PackedByteArray to [u8; 16]");
-            }
-        } else {
-            godot_error!(
-                "[COMBAT ERROR] Invalid ULID bytes length for clear_damaged_state: {} (expected 16)",
-                ulid_bytes.len()
-            );
+    pub fn get_squad_names(&self) -> PackedStringArray {
+        self.warehouse
+            .squad_names()
+            .iter()
+            .map(GString::from)
+            .collect()
+    }
+
+    /// Name of the squad an NPC belongs to (empty string if none)
+    #[func]
+    pub fn get_npc_squad(&self, ulid: PackedByteArray) -> GString {
+        packed_bytes_to_ulid(&ulid)
+            .ok()
+            .and_then(|ulid| self.warehouse.npc_squad(&ulid))
+            .map(|name| GString::from(&name))
+            .unwrap_or_default()
+    }
+
+    /// A squad's layout (empty if there is no such squad)
+    /// Keys: leader, formation, members (slot order), slots (member slot
+    /// positions), anchor, facing, marching, plus target while marching
+    /// Usage: var squad = NPCDataWarehouse.get_squad("vanguard")
+    #[func]
+    pub fn get_squad(&self, name: GString) -> Dictionary {
+        let mut dict = Dictionary::new();
+        let Some(squad) = self.warehouse.squad(&name.to_string()) else {
+            return dict;
+        };
+        let members: Vec<[u8; 16]> = squad.members.iter().map(|(ulid, _)| *ulid).collect();
+        let slots: Array<Vector2> = squad
+            .members
+            .iter()
+            .map(|(_, offset)| {
+                let pos = squad.slot_position(*offset);
+                Vector2::new(pos.x, pos.y)
+            })
+            .collect();
+        dict.set("leader", PackedByteArray::from(&squad.leader[..]));
+        dict.set("formation", squad.formation.as_str());
+        dict.set("members", ulid_array(&members));
+        dict.set("slots", slots);
+        dict.set("anchor", Vector2::new(squad.anchor.x, squad.anchor.y));
+        dict.set("facing", Vector2::new(squad.facing.x, squad.facing.y));
+        dict.set("marching", squad.order.is_some());
+        if let Some(target) = squad.order {
+            dict.set("target", Vector2::new(target.x, target.y));
         }
+        dict
     }
 
-    /// Get NPC waypoint (target position calculated by Rust AI)
-    /// Returns PackedFloat32Array [x, y] world position, or empty array if no waypoint
-    /// Usage: var waypoint = NPCDataWarehouse.get_npc_waypoint(ulid_bytes)
+    // ===== FACTIONS =====
+    // Relations are "hostile", "neutral" or "friendly" and apply both ways.
+    // Built-in factions: "ally", "monster", "passive"
+
+    /// Register a faction, copying the relations and wander zone of `inherit`
+    /// (empty = neutral to everyone). Returns its id, or -1 on error
+    /// Usage: NPCDataWarehouse.register_faction("goblin_tribe", "monster")
     #[func]
-    pub fn get_npc_waypoint(&self, ulid_bytes: PackedByteArray) -> PackedFloat32Array {
-        if ulid_bytes.len() == 16 {
-            if let Ok(ulid_array) = TryInto::<[u8; 16]>::try_into(ulid_bytes.as_slice()) {
-                if let Some(pos_str) = self
-                    .warehouse
-                    .npc_waypoints
-                    .get(&ulid_array)
-                    .map(|v| v.value().clone())
-                {
-                    if let Some((x_str, y_str)) = pos_str.split_once(',') {
-                        if let (Ok(x), Ok(y)) = (x_str.parse::<f32>(), y_str.parse::<f32>()) {
-                            return PackedFloat32Array::from(&[x, y]);
-                        }
-                    }
-                }
+    pub fn register_faction(&self, name: GString, inherit: GString) -> i32 {
+        let _step = self.warehouse.hold_steps();
+        let inherit = inherit.to_string();
+        let base = (!inherit.is_empty()).then_some(inherit.as_str());
+        match self.warehouse.register_faction(&name.to_string(), base) {
+            Ok(FactionId(id)) => id as i32,
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                -1
             }
         }
-        // No waypoint or invalid ULID - return empty array
-        PackedFloat32Array::new()
     }
 
-    // ===== COMBAT SYSTEM FUNCTIONS =====
-
-    /// Register an NPC for combat processing
-    /// Usage: NPCDataWarehouse.register_npc_for_combat(ulid_bytes, static_state, behavioral_state, max_hp, attack, defense)
-    /// ulid_bytes: PackedByteArray (16 bytes) - raw ULID bytes from stats.ulid
+    /// Set the relation between two factions (emits faction_relation_changed)
+    /// Usage: NPCDataWarehouse.set_faction_relation("goblin_tribe", "monster", "hostile")
     #[func]
-    pub fn register_npc_for_combat(
+    pub fn set_faction_relation(
         &self,
-        ulid_bytes: PackedByteArray,
-        static_state: i32,
-        behavioral_state: i32,
-        max_hp: f32,
-        attack: f32,
-        defense: f32,
-    ) {
-        // Convert PackedByteArray to [u8; 16]
-        let ulid_slice = ulid_bytes.as_slice();
-        if ulid_slice.len() != 16 {
-            godot_error!("[COMBAT ERROR] Invalid ULID bytes in register_npc_for_combat: expected 16 bytes, got {}", ulid_slice.len());
-            return;
+        faction_a: GString,
+        faction_b: GString,
+        relation: GString,
+    ) -> bool {
+        let _step = self.warehouse.hold_steps();
+        let Some(relation) = Relation::parse(&relation.to_string()) else {
+            godot_error!(
+                "NPCDataWarehouse: Unknown relation '{}' (hostile/neutral/friendly)",
+                relation
+            );
+            return false;
+        };
+        match self.warehouse.set_faction_relation(
+            &faction_a.to_string(),
+            &faction_b.to_string(),
+            relation,
+        ) {
+            Ok(()) => true,
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                false
+            }
         }
+    }
 
-        let mut ulid_array = [0u8; 16];
-        ulid_array.copy_from_slice(ulid_slice);
+    /// Relation between two factions (empty string if either is unknown)
+    #[func]
+    pub fn get_faction_relation(&self, faction_a: GString, faction_b: GString) -> GString {
+        self.warehouse
+            .faction_relation(&faction_a.to_string(), &faction_b.to_string())
+            .map(|relation| GString::from(relation.as_str()))
+            .unwrap_or_default()
+    }
 
-        self.warehouse.register_npc_for_combat_internal(
-            &ulid_array,
-            static_state,
-            behavioral_state,
-            max_hp,
-            attack,
-            defense,
-        );
+    /// Registered faction names, in id order
+    #[func]
+    pub fn get_faction_names(&self) -> PackedStringArray {
+        self.warehouse
+            .faction_names()
+            .iter()
+            .map(GString::from)
+            .collect()
     }
 
-    /// Unregister an NPC from combat processing
-    /// Usage: NPCDataWarehouse.unregister_npc_from_combat(ulid_bytes)
-    /// ulid_bytes: PackedByteArray (16 bytes) - raw ULID bytes from stats.ulid
+    /// Set the horizontal band idle members wander in, as fractions of the
+    /// world width (a negative value = the whole world)
+    /// Usage: NPCDataWarehouse.set_faction_wander_zone("goblin_tribe", 0.4, 0.7)
     #[func]
-    pub fn unregister_npc_from_combat(&self, ulid_bytes: PackedByteArray) {
-        match packed_bytes_to_hex(&ulid_bytes) {
-            Ok(ulid_hex) => {
-                self.warehouse
-                    .unregister_npc_from_combat_internal(&ulid_hex);
-            }
+    pub fn set_faction_wander_zone(&self, faction: GString, min: f32, max: f32) -> bool {
+        let _step = self.warehouse.hold_steps();
+        let zone = (min >= 0.0 && max >= 0.0).then_some((min, max));
+        let result = self
+            .warehouse
+            .set_faction_wander_zone(&faction.to_string(), zone);
+        match result {
+            Ok(()) => true,
             Err(e) => {
-                godot_error!(
-                    "[COMBAT ERROR] Invalid ULID bytes in unregister_npc_from_combat: {}",
-                    e
-                );
+                godot_error!("NPCDataWarehouse: {}", e);
+                false
             }
         }
     }
 
-    /// Update NPC position for combat calculations
-    /// Usage: NPCDataWarehouse.update_npc_position(ulid_bytes, x, y)
-    /// ulid_bytes: PackedByteArray (16 bytes) - raw ULID bytes from stats.ulid
+    /// Move an NPC to another faction until it despawns (emits npc_faction_changed)
+    /// Usage: NPCDataWarehouse.set_npc_faction(ulid, "ally")
     #[func]
-    pub fn update_npc_position(&self, ulid_bytes: PackedByteArray, x: f32, y: f32) {
-        match packed_bytes_to_hex(&ulid_bytes) {
-            Ok(ulid_hex) => {
-                self.warehouse.update_npc_position_internal(&ulid_hex, x, y);
-            }
+    pub fn set_npc_faction(&self, ulid: PackedByteArray, faction: GString) -> bool {
+        let _step = self.warehouse.hold_steps();
+        let result = packed_bytes_to_ulid(&ulid)
+            .and_then(|ulid| self.warehouse.set_npc_faction(&ulid, &faction.to_string()));
+        match result {
+            Ok(()) => true,
             Err(e) => {
-                godot_error!(
-                    "[COMBAT ERROR] Invalid ULID bytes in update_npc_position: {}",
-                    e
-                );
+                godot_error!("NPCDataWarehouse: {}", e);
+                false
             }
         }
     }
 
-    /// Start the combat system (sets flag, no actual thread)
-    /// Usage: NPCDataWarehouse.start_combat_system()
+    /// Faction name of an NPC (empty string if unknown)
     #[func]
-    pub fn start_combat_system(&self) {
-        self.warehouse.start_combat_thread();
+    pub fn get_npc_faction(&self, ulid: PackedByteArray) -> GString {
+        packed_bytes_to_ulid(&ulid)
+            .ok()
+            .and_then(|ulid| self.warehouse.npc_faction_name(&ulid))
+            .map(|faction| GString::from(&faction))
+            .unwrap_or_default()
     }
 
-    /// Stop the combat system
-    /// Usage: NPCDataWarehouse.stop_combat_system()
-    #[func]
-    pub fn stop_combat_system(&self) {
-        self.warehouse.stop_combat_thread();
-    }
+    // ===== NPC QUERIES =====
+    // Positions are simulation (container-local) coordinates, like get_npc_position.
+    // Masks come from get_state / get_static_state (0 = any), an empty npc_type
+    // matches every type and an empty faction every faction; dead NPCs are
+    // skipped unless the state mask includes DEAD. An unknown faction logs an
+    // error and matches nothing
 
-    /// Check if combat system is running
-    /// Usage: NPCDataWarehouse.is_combat_enabled()
+    /// NPCs within `radius` of `center`, nearest first
+    /// Usage: var allies = NPCDataWarehouse.query_npcs_in_radius(pos, 120.0, "", "ally", 0)
     #[func]
-    pub fn is_combat_enabled(&self) -> bool {
-        self.warehouse.is_combat_enabled()
+    pub fn query_npcs_in_radius(
+        &self,
+        center: Vector2,
+        radius: f32,
+        npc_type: GString,
+        faction: GString,
+        static_mask: i32,
+    ) -> Array<PackedByteArray> {
+        match npc_filter(&self.warehouse, &npc_type, &faction, 0, static_mask) {
+            Ok(filter) => ulid_array(&self.warehouse.query_radius(
+                Vec2::new(center.x, center.y),
+                radius,
+                &filter,
+            )),
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                Array::new()
+            }
+        }
     }
 
-    /// Tick combat logic and get events
-    /// Returns array of JSON strings representing combat events
-    /// Emits npc_died signal for each death position
-    /// Usage: var events = NPCDataWarehouse.tick_combat(delta)
+    /// NPCs inside a rectangle, in ULID order
+    /// Usage: var selected = NPCDataWarehouse.query_npcs_in_rect(drag_rect, "", "", 0)
     #[func]
-    pub fn tick_combat(&mut self, delta: f32) -> Array<GString> {
-        let (events, death_positions) = self.warehouse.tick_combat_internal(delta);
-        let mut godot_array = Array::new();
-
-        for event in events {
-            let json = serde_json::to_string(&event).unwrap_or_default();
-            let gstring = GString::from(&json);
-            godot_array.push(&gstring);
+    pub fn query_npcs_in_rect(
+        &self,
+        rect: Rect2,
+        npc_type: GString,
+        faction: GString,
+        static_mask: i32,
+    ) -> Array<PackedByteArray> {
+        let end = rect.end();
+        match npc_filter(&self.warehouse, &npc_type, &faction, 0, static_mask) {
+            Ok(filter) => ulid_array(&self.warehouse.query_rect(
+                Vec2::new(rect.position.x, rect.position.y),
+                Vec2::new(end.x, end.y),
+                &filter,
+            )),
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                Array::new()
+            }
         }
+    }
 
-        // Emit npc_died signal for each death position (for GDScript to spawn effects)
-        let signal_name = StringName::from("npc_died");
-        for (x, y) in death_positions {
-            self.base_mut().emit_signal(&signal_name, &[x.to_variant(), y.to_variant()]);
+    /// Up to `count` NPCs nearest to `center`, nearest first
+    /// Usage: var targets = NPCDataWarehouse.query_nearest_npcs(pos, 3, "goblin", "", 0)
+    #[func]
+    pub fn query_nearest_npcs(
+        &self,
+        center: Vector2,
+        count: i32,
+        npc_type: GString,
+        faction: GString,
+        static_mask: i32,
+    ) -> Array<PackedByteArray> {
+        match npc_filter(&self.warehouse, &npc_type, &faction, 0, static_mask) {
+            Ok(filter) => ulid_array(&self.warehouse.query_nearest(
+                Vec2::new(center.x, center.y),
+                count.max(0) as usize,
+                &filter,
+            )),
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                Array::new()
+            }
         }
-
-        godot_array
     }
 
-    /// Tick ONLY the combat phase (damage calculations and state changes)
-    /// Returns array of JSON strings representing combat events
-    /// Usage: var events = NPCDataWarehouse.tick_combat_phase()
+    /// NPCs whose behavioral and static flags contain both masks, in ULID order
+    /// Usage: var fighting = NPCDataWarehouse.query_npcs_by_state(NPCDataWarehouse.get_state("COMBAT"), "", "", 0)
     #[func]
-    pub fn tick_combat_phase(&self) -> Array<GString> {
-        let events = self.warehouse.tick_combat_phase();
-        let mut godot_array = Array::new();
-
-        for event in events {
-            let json = serde_json::to_string(&event).unwrap_or_default();
-            let gstring = GString::from(&json);
-            godot_array.push(&gstring);
+    pub fn query_npcs_by_state(
+        &self,
+        state_mask: i32,
+        npc_type: GString,
+        faction: GString,
+        static_mask: i32,
+    ) -> Array<PackedByteArray> {
+        match npc_filter(
+            &self.warehouse,
+            &npc_type,
+            &faction,
+            state_mask,
+            static_mask,
+        ) {
+            Ok(filter) => ulid_array(&self.warehouse.query_matching(&filter)),
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                Array::new()
+            }
         }
-
-        godot_array
     }
 
-    /// Tick ONLY the movement phase (position updates and spawning)
-    /// Returns array of JSON strings representing movement events
-    /// Usage: var events = NPCDataWarehouse.tick_movement_phase(delta)
+    /// NPC within `radius` of `center` with the lowest HP fraction
+    /// Returns an empty array if none match
+    /// Usage: var patient = NPCDataWarehouse.query_lowest_hp_in_radius(pos, 200.0, "", "ally", 0)
     #[func]
-    pub fn tick_movement_phase(&self, delta: f32) -> Array<GString> {
-        let events = self.warehouse.tick_movement_phase(delta);
-        let mut godot_array = Array::new();
-
-        for event in events {
-            let json = serde_json::to_string(&event).unwrap_or_default();
-            let gstring = GString::from(&json);
-            godot_array.push(&gstring);
+    pub fn query_lowest_hp_in_radius(
+        &self,
+        center: Vector2,
+        radius: f32,
+        npc_type: GString,
+        faction: GString,
+        static_mask: i32,
+    ) -> PackedByteArray {
+        match npc_filter(&self.warehouse, &npc_type, &faction, 0, static_mask) {
+            Ok(filter) => self
+                .warehouse
+                .query_lowest_hp(Vec2::new(center.x, center.y), radius, &filter)
+                .map(|ulid| PackedByteArray::from(&ulid[..]))
+                .unwrap_or_default(),
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: {}", e);
+                PackedByteArray::new()
+            }
         }
-
-        godot_array
     }
 
-    /// Tick ONLY the animation phase (visual updates)
-    /// Does not return events - just updates animation states
-    /// Usage: NPCDataWarehouse.tick_animation_phase()
+    // ===== SAVE / LOAD =====
+
+    /// Snapshot the whole battle (active NPCs, spawn timers, world bounds) as JSON
+    /// Usage: var json = NPCDataWarehouse.save_snapshot()
     #[func]
-    pub fn tick_animation_phase(&self) {
-        self.warehouse.tick_animation_phase();
+    pub fn save_snapshot(&mut self) -> GString {
+        let json = self.with_simulation_paused(|this| this.warehouse.save_snapshot_json());
+        GString::from(&json)
     }
 
-    /// Get NPC current HP
-    /// Usage: var hp = NPCDataWarehouse.get_npc_hp(ulid_bytes)
+    /// Replace the current battle with a snapshot from save_snapshot()
+    /// NPC pools must be initialized first; restored NPCs are shown right away
+    /// Returns how many NPCs were restored, or -1 if the snapshot is invalid
+    /// Usage: NPCDataWarehouse.load_snapshot(json)
     #[func]
-    pub fn get_npc_hp(&self, ulid_bytes: PackedByteArray) -> f32 {
-        match packed_bytes_to_hex(&ulid_bytes) {
-            Ok(ulid_hex) => self.warehouse.get_npc_hp_internal(&ulid_hex).unwrap_or(0.0),
-            Err(_) => 0.0,
+    pub fn load_snapshot(&mut self, json: GString) -> i32 {
+        let result = self
+            .with_simulation_paused(|this| this.warehouse.load_snapshot_json(&json.to_string()));
+        match result {
+            Ok(restored) => {
+                self.nodes.apply_commands(&self.warehouse);
+                self.nodes.sync_healthbars(&self.warehouse);
+                restored as i32
+            }
+            Err(e) => {
+                godot_error!("NPCDataWarehouse: Cannot load snapshot - {}", e);
+                -1
+            }
         }
     }
 
-    /// Get NPC behavioral state (IDLE, WALKING, ATTACKING, DAMAGED, DEAD, etc.)
-    /// Usage: var state = NPCDataWarehouse.get_npc_behavioral_state(ulid_bytes)
+    // ===== INPUT RECORDING / REPLAY =====
+
+    /// Start recording every external input (ticks, spawns, hits, healing, ...)
+    /// The RNG is reseeded so the run can be replayed; seed < 0 picks a random one
+    /// Works with the simulation thread running: host calls land between steps
+    /// Returns the seed used
+    /// Usage: NPCDataWarehouse.start_recording(-1)
     #[func]
-    pub fn get_npc_behavioral_state(&self, ulid_bytes: PackedByteArray) -> i32 {
-        match packed_bytes_to_hex(&ulid_bytes) {
-            Ok(ulid_hex) => self
-                .warehouse
-                .get_stat_value(&ulid_hex, "behavioral_state")
-                .unwrap_or(0.0) as i32,
-            Err(_) => 0,
-        }
+    pub fn start_recording(&mut self, seed: i64) -> i64 {
+        let seed = (seed >= 0).then_some(seed as u64);
+        self.with_simulation_paused(|this| this.warehouse.start_recording(seed)) as i64
     }
 
-    /// Get NPC position
-    /// Usage: var pos = NPCDataWarehouse.get_npc_position(ulid)
-    /// Accepts ULID as PackedByteArray (16 bytes)
+    /// Stop recording and write the log to `path` (e.g. "user://battle.replay")
+    /// Returns false if nothing was being recorded or the file could not be written
+    /// Usage: NPCDataWarehouse.stop_recording("user://battle.replay")
     #[func]
-    pub fn get_npc_position(&self, ulid: PackedByteArray) -> PackedFloat32Array {
-        if ulid.len() != 16 {
-            return PackedFloat32Array::new();
-        }
-        let ulid_bytes: [u8; 16] = ulid.as_slice().try_into().unwrap_or([0u8; 16]);
-
-        // Get position from npc_positions ByteMap (stored as "x,y")
-        if let Some(pos_str) = self
-            .warehouse
-            .npc_positions
-            .get(&ulid_bytes)
-            .map(|v| v.value().clone())
-        {
-            let coords: Vec<&str> = pos_str.split(',').collect();
-            if coords.len() == 2 {
-                if let (Ok(x), Ok(y)) = (coords[0].parse::<f32>(), coords[1].parse::<f32>()) {
-                    let mut arr = PackedFloat32Array::new();
-                    arr.push(x);
-                    arr.push(y);
-                    return arr;
+    pub fn stop_recording(&mut self, path: GString) -> bool {
+        let Some(recording) = self.with_simulation_paused(|this| this.warehouse.stop_recording())
+        else {
+            godot_warn!("[REPLAY] stop_recording called while not recording");
+            return false;
+        };
+        let Some(mut file) =
+            godot::classes::FileAccess::open(&path, godot::classes::file_access::ModeFlags::WRITE)
+        else {
+            godot_error!("[REPLAY] Cannot open '{}' for writing", path);
+            return false;
+        };
+        let written = file.store_string(&GString::from(&recording.to_text()));
+        file.close();
+        written
+    }
+
+    /// Check if inputs are being recorded
+    #[func]
+    pub fn is_recording(&self) -> bool {
+        self.warehouse.is_recording()
+    }
+
+    /// Replay a recording file in a fresh headless warehouse
+    /// Returns { "ok", "ticks", "inputs", "diverged_at" (-1 if none),
+    ///           "expected_hash", "actual_hash", "error" }
+    /// Usage: var report = NPCDataWarehouse.replay_recording("user://battle.replay")
+    #[func]
+    pub fn replay_recording(&self, path: GString) -> Dictionary {
+        let mut report = Dictionary::new();
+        let text = godot::classes::FileAccess::get_file_as_string(&path).to_string();
+        match InputRecording::from_text(&text).and_then(|recording| recording.replay()) {
+            Ok(result) => {
+                report.set("ok", true);
+                report.set("ticks", result.ticks as i64);
+                report.set("inputs", result.inputs as i64);
+                match result.divergence {
+                    Some(divergence) => {
+                        report.set("diverged_at", divergence.tick as i64);
+                        report.set("expected_hash", format!("{:016x}", divergence.expected));
+                        report.set("actual_hash", format!("{:016x}", divergence.actual));
+                    }
+                    None => report.set("diverged_at", -1),
                 }
             }
+            Err(e) => {
+                godot_error!("[REPLAY] Cannot replay '{}': {}", path, e);
+                report.set("ok", false);
+                report.set("error", e);
+            }
         }
-        PackedFloat32Array::new()
+        report
     }
 
     // ===== ULID FUNCTIONS =====
@@ -5165,21 +2722,23 @@ impl GodotNPCDataWarehouse {
 
     // ===== COMBAT HELPER FUNCTIONS (old API, kept for compatibility) =====
 
-    /// Calculate damage (basic formula)
+    /// Calculate damage (base damage of the shared formula - see get_damage_config)
     #[func]
     pub fn calculate_damage(&self, attacker_attack: f32, victim_defense: f32) -> f32 {
-        (attacker_attack - victim_defense * 0.5).max(1.0)
+        self.warehouse
+            .preview_damage(attacker_attack, victim_defense)
     }
 
-    /// Check if two NPCs are hostile (uses static_state for faction)
+    /// Check if two NPCs are hostile (the built-in factions of their static_state
+    /// in the faction table - use get_faction_relation for named factions)
     #[func]
     pub fn are_hostile(&self, static_state1: i32, static_state2: i32) -> bool {
-        let ally1 = (static_state1 & NPCStaticState::ALLY.bits() as i32) != 0;
-        let monster1 = (static_state1 & NPCStaticState::MONSTER.bits() as i32) != 0;
-        let ally2 = (static_state2 & NPCStaticState::ALLY.bits() as i32) != 0;
-        let monster2 = (static_state2 & NPCStaticState::MONSTER.bits() as i32) != 0;
-
-        (ally1 && monster2) || (monster1 && ally2)
+        let faction = |static_state: i32| {
+            FactionId::from_static(NPCStaticState::from_bits_truncate(static_state as u32))
+        };
+        self.warehouse
+            .faction_table()
+            .is_hostile(faction(static_state1), faction(static_state2))
     }
 
     /// Check if NPC can attack (not passive)
@@ -5269,10 +2828,11 @@ impl GodotNPCDataWarehouse {
         (state & NPCState::COMBAT.bits() as i32) != 0
     }
 
-    /// Poll combat events (deprecated - use tick_combat instead)
+    /// Drain combat events queued by the simulation as JSON strings
+    /// tick_combat() drains the same queue, so this only returns events for
+    /// hosts that poll instead (e.g. while the simulation thread runs)
     #[func]
     pub fn poll_combat_events(&self) -> Array<GString> {
-        // Returns empty array - events are now returned from tick_combat
-        Array::new()
+        Self::combat_events_to_json(&self.drain_combat_events())
     }
 }
@@ -0,0 +1,804 @@
+use bevy::math::Vec2;
+use dashmap::DashMap;
+use godot::classes::{AnimatedSprite2D, Control, Node2D, PackedScene};
+use godot::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+// Import the animation module
+use crate::animation::EffectPool;
+use crate::simulation::{bytes_to_hex, NPCDataWarehouse, NodeCommand, SimFrame};
+
+// ============================================================================
+// NPC SCENE NODES - Godot side of the simulation
+// ============================================================================
+
+/// Rust-controlled NPC instance with direct scene node access
+/// The simulation core owns the NPC data; this only holds the visual node
+struct RustNPC {
+    /// The root Node2D of the NPC scene
+    node: Gd<Node2D>,
+    /// Reference to the AnimatedSprite2D child for animation control
+    animated_sprite: Option<Gd<AnimatedSprite2D>>,
+    /// NPC type (warrior, archer, goblin, etc.)
+    npc_type: String,
+    /// Is this NPC currently active (spawned) or in pool (inactive)?
+    is_active: bool,
+}
+
+impl RustNPC {
+    /// Create a new NPC by instantiating a PackedScene
+    fn from_scene(scene_path: &str, npc_type: &str) -> Option<Self> {
+        // Load the packed scene
+        let scene = match try_load::<PackedScene>(scene_path) {
+            Ok(s) => s,
+            Err(e) => {
+                godot_error!("[RUST NPC] Failed to load scene {}: {:?}", scene_path, e);
+                return None;
+            }
+        };
+
+        // Instantiate as Node2D
+        let mut node = match scene.try_instantiate_as::<Node2D>() {
+            Some(n) => n,
+            None => {
+                godot_error!(
+                    "[RUST NPC] Failed to instantiate scene as Node2D: {}",
+                    scene_path
+                );
+                return None;
+            }
+        };
+
+        // Make invisible initially (will be made visible on spawn)
+        node.set_visible(false);
+
+        // Find the AnimatedSprite2D child node
+        let animated_sprite = node.try_get_node_as::<AnimatedSprite2D>("AnimatedSprite2D");
+
+        Some(Self {
+            node,
+            animated_sprite,
+            npc_type: npc_type.to_string(),
+            is_active: false,
+        })
+    }
+
+    /// Activate this NPC (spawn it into the world)
+    fn activate(&mut self, position: Vector2) {
+        self.is_active = true;
+        self.node.set_visible(true);
+        // Use local position (relative to parent Layer4Objects) not global
+        self.node.set_position(position);
+
+        // Start with idle animation when spawning
+        if let Some(ref sprite) = self.animated_sprite {
+            let mut sprite_mut = sprite.clone();
+            sprite_mut.set_animation(&StringName::from("idle"));
+            sprite_mut.play();
+            godot_print!("[RUST NPC] Set animation to 'idle' and playing");
+        } else {
+            godot_warn!(
+                "[RUST NPC] No AnimatedSprite2D found for {}!",
+                self.npc_type
+            );
+        }
+
+        godot_print!(
+            "[RUST NPC] Activated {} at local position {:?}, visible={}",
+            self.npc_type,
+            position,
+            self.node.is_visible()
+        );
+    }
+
+    /// Deactivate this NPC (return to pool)
+    fn deactivate(&mut self) {
+        self.is_active = false;
+        self.node.set_visible(false);
+    }
+
+    /// Reset NPC for reuse (called when returning to pool)
+    /// Stats are reset by the simulation core, the node only needs hiding
+    fn reset(&mut self) {
+        self.is_active = false;
+        self.node.set_visible(false);
+    }
+
+    /// Play an animation
+    fn play_animation(&mut self, animation_name: &str) {
+        if let Some(ref mut sprite) = self.animated_sprite {
+            sprite.set_animation(animation_name);
+            sprite.play();
+        }
+    }
+
+    /// Set sprite flip (for facing direction)
+    fn set_flip_h(&mut self, flip: bool) {
+        if let Some(ref mut sprite) = self.animated_sprite {
+            sprite.set_flip_h(flip);
+        }
+    }
+}
+
+/// Godot node layer for the NPC simulation
+///
+/// Owns the pooled scene nodes, healthbars and release effects. It never
+/// decides anything: it drains `NodeCommand`s from the core warehouse and
+/// mirrors the simulated positions and states onto the nodes.
+pub struct NpcNodeLayer {
+    // ============================================================================
+    // RUST NPC POOL SYSTEM - Scene nodes bound to pooled ULIDs
+    // ============================================================================
+    /// Active NPCs (currently spawned and visible)
+    /// Key: ULID bytes -> RustNPC instance
+    active_npc_pool: DashMap<[u8; 16], RustNPC>,
+
+    /// Inactive NPCs (in pool, ready to spawn)
+    /// Key: ULID bytes of the pooled slot -> RustNPC instance
+    inactive_npc_pool: DashMap<[u8; 16], RustNPC>,
+
+    /// Scene path cache ("scene:<npc type>" -> scene path)
+    /// Fallback for warming pools registered without a scene path
+    scene_cache: DashMap<String, String>,
+
+    /// Pooled nodes instanced per frame while pools warm toward min_warm
+    prewarm_per_frame: AtomicUsize,
+
+    /// Nodes instanced and freed over the layer's lifetime (pool statistics)
+    nodes_instanced: AtomicUsize,
+    nodes_freed: AtomicUsize,
+
+    /// Scene tree container node (set by GDScript, NPCs are added as children)
+    /// This is the Layer4Objects container from the background
+    /// Shared with the animation pools through Rc<RefCell> - Gd handles are
+    /// main-thread only, so this never crosses to the simulation thread
+    scene_container: Rc<RefCell<Option<Gd<Node2D>>>>,
+
+    // ============================================================================
+    // HEALTHBAR POOL SYSTEM - Managed by Rust alongside NPC pools
+    // ============================================================================
+    /// Inactive healthbars (in pool, ready to be assigned)
+    /// These are pre-instantiated HealthBar nodes that get connected/disconnected from NPCs
+    healthbar_pool: DashMap<usize, Gd<Node>>, // Pool index -> HealthBar node
+
+    /// Active healthbar assignments (ULID bytes -> pool index)
+    /// Tracks which healthbar is assigned to which NPC
+    healthbar_assignments: DashMap<[u8; 16], usize>,
+
+    /// Next available healthbar pool index (atomic for thread safety)
+    healthbar_pool_size: Arc<AtomicUsize>,
+
+    // ============================================================================
+    // RELEASE EFFECT POOL SYSTEM - Death animation/particle effects
+    // ============================================================================
+    /// Release effect pool for death animations (managed by animation module)
+    pub release_pool: EffectPool,
+}
+
+impl NpcNodeLayer {
+    /// Create an empty node layer (no container yet)
+    pub fn new() -> Self {
+        // Create scene container (shared between node layer and effect pools)
+        let scene_container = Rc::new(RefCell::new(None));
+
+        // Create release effect pool with shared scene container
+        let release_pool = EffectPool::new(Rc::clone(&scene_container), "RELEASE");
+
+        Self {
+            active_npc_pool: DashMap::new(),
+            inactive_npc_pool: DashMap::new(),
+            scene_cache: DashMap::new(),
+            prewarm_per_frame: AtomicUsize::new(2),
+            nodes_instanced: AtomicUsize::new(0),
+            nodes_freed: AtomicUsize::new(0),
+            scene_container,
+            healthbar_pool: DashMap::new(),
+            healthbar_assignments: DashMap::new(),
+            healthbar_pool_size: Arc::new(AtomicUsize::new(0)),
+            release_pool,
+        }
+    }
+
+    // ============================================================================
+    // RUST NPC POOL MANAGEMENT - Replaces GDScript pool system
+    // ============================================================================
+
+    /// Pre-populate the inactive pool with NPC nodes of a given type
+    /// This loads and instantiates PackedScenes, then asks the core for one
+    /// pooled ULID per created node
+    pub fn initialize_npc_pool(
+        &self,
+        warehouse: &NPCDataWarehouse,
+        npc_type: &str,
+        pool_size: usize,
+        scene_path: &str,
+    ) {
+        godot_print!(
+            "[RUST POOL] Initializing pool for {} (size: {})",
+            npc_type,
+            pool_size
+        );
+
+        // Cache the PackedScene for this NPC type
+        let scene_key = format!("scene:{}", npc_type);
+        let scene_value = scene_path.to_string();
+        self.scene_cache.insert(scene_key, scene_value);
+
+        self.instance_pool_nodes(warehouse, npc_type, pool_size, scene_path);
+    }
+
+    /// Instance `count` nodes and bind each to a new pooled ULID from the core
+    fn instance_pool_nodes(
+        &self,
+        warehouse: &NPCDataWarehouse,
+        npc_type: &str,
+        count: usize,
+        scene_path: &str,
+    ) {
+        // Create pool of inactive NPC nodes
+        let mut nodes = Vec::with_capacity(count);
+        for i in 0..count {
+            if let Some(npc) = RustNPC::from_scene(scene_path, npc_type) {
+                nodes.push(npc);
+            } else {
+                godot_error!(
+                    "[RUST POOL] Failed to create NPC {}/{} for type {}",
+                    i + 1,
+                    count,
+                    npc_type
+                );
+            }
+        }
+        self.nodes_instanced
+            .fetch_add(nodes.len(), Ordering::Relaxed);
+
+        // Bind each node to a pooled ULID from the core
+        let ulids = warehouse.initialize_npc_pool(npc_type, nodes.len());
+        for (ulid, npc) in ulids.into_iter().zip(nodes) {
+            self.inactive_npc_pool.insert(ulid, npc);
+        }
+    }
+
+    /// Set how many pooled nodes are instanced per frame while pools warm up
+    pub fn set_prewarm_per_frame(&self, per_frame: usize) {
+        self.prewarm_per_frame.store(per_frame, Ordering::Relaxed);
+    }
+
+    /// Instance this frame's share of nodes for pools below their min_warm
+    /// Spreads a pool's growth over frames so a wave doesn't hitch
+    pub fn warm_pools(&self, warehouse: &NPCDataWarehouse) {
+        let per_frame = self.prewarm_per_frame.load(Ordering::Relaxed);
+        for (npc_type, count) in warehouse.pools_to_warm(per_frame) {
+            let scene_path = warehouse
+                .get_pool(&npc_type)
+                .map(|pool| pool.scene_path)
+                .filter(|path| !path.is_empty())
+                .or_else(|| {
+                    self.scene_cache
+                        .get(&format!("scene:{}", npc_type))
+                        .map(|v| v.value().clone())
+                });
+            match scene_path {
+                Some(scene_path) => {
+                    self.instance_pool_nodes(warehouse, &npc_type, count, &scene_path)
+                }
+                None => godot_warn!(
+                    "[RUST POOL] Cannot warm pool '{}' - no scene path registered",
+                    npc_type
+                ),
+            }
+        }
+    }
+
+    /// Node pool statistics
+    /// Keys: active_nodes, idle_nodes, nodes_instanced, nodes_freed, prewarm_per_frame
+    pub fn pool_stats(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+        dict.set("active_nodes", self.active_npc_pool.len() as i64);
+        dict.set("idle_nodes", self.inactive_npc_pool.len() as i64);
+        dict.set(
+            "nodes_instanced",
+            self.nodes_instanced.load(Ordering::Relaxed) as i64,
+        );
+        dict.set(
+            "nodes_freed",
+            self.nodes_freed.load(Ordering::Relaxed) as i64,
+        );
+        dict.set(
+            "prewarm_per_frame",
+            self.prewarm_per_frame.load(Ordering::Relaxed) as i64,
+        );
+        dict
+    }
+
+    /// Set the scene container (Layer4Objects) where NPCs will be added
+    pub fn set_scene_container(&self, container: Gd<Node2D>) {
+        godot_print!("[RUST POOL] Setting scene container");
+        *self.scene_container.borrow_mut() = Some(container);
+
+        // Auto-initialize release effect pool now that we have a container
+        // Use smaller pool size (3) to avoid blocking the main thread
+        godot_print!("[RUST POOL] Auto-initializing release effect pool...");
+        self.release_pool
+            .initialize(3, "res://nodes/npc/common/release.tscn", 2000);
+        godot_print!("[RUST POOL] Release effect pool initialization complete");
+
+        // Auto-initialize healthbar pool now that we have a container
+        // Use smaller pool size to avoid blocking on init
+        godot_print!("[RUST POOL] Auto-initializing healthbar pool...");
+        self.initialize_healthbar_pool(20, "res://nodes/ui/healthbar/healthbar.tscn");
+        godot_print!("[RUST POOL] Healthbar pool initialization complete");
+    }
+
+    /// Convert a simulated (container-local) position to global coordinates
+    pub fn to_global(&self, position: Vec2) -> Vector2 {
+        let local = Vector2::new(position.x, position.y);
+        match *self.scene_container.borrow() {
+            Some(ref container) => container.to_global(local),
+            None => local,
+        }
+    }
+
+    // ============================================================================
+    // NODE COMMANDS - Apply core spawn/despawn/HP changes to the scene
+    // ============================================================================
+
+    /// Drain the core's node command queue and apply each command to the scene
+    pub fn apply_commands(&self, warehouse: &NPCDataWarehouse) {
+        while let Some(command) = warehouse.pop_node_command() {
+            match command {
+                NodeCommand::Spawned {
+                    ulid,
+                    npc_type,
+                    position,
+                } => {
+                    if !self.spawn_node(&ulid, &npc_type, position) {
+                        // No node to show - hand the slot back to the pool
+                        let _step = warehouse.hold_steps();
+                        warehouse.rust_despawn_npc(&ulid);
+                    }
+                }
+                NodeCommand::Despawned { ulid } => self.despawn_node(&ulid),
+                NodeCommand::Rekeyed { from, to } => {
+                    if let Some((_, npc)) = self.inactive_npc_pool.remove(&from) {
+                        self.inactive_npc_pool.insert(to, npc);
+                    }
+                }
+                NodeCommand::Freed { ulid } => {
+                    // Pooled nodes are out of the scene tree - free them right away
+                    if let Some((_, npc)) = self.inactive_npc_pool.remove(&ulid) {
+                        npc.node.free();
+                        self.nodes_freed.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                NodeCommand::Damaged {
+                    ulid,
+                    amount,
+                    hp,
+                    max_hp,
+                } => self.update_healthbar_hp(&ulid, amount, hp, max_hp),
+                NodeCommand::Healed {
+                    ulid,
+                    amount,
+                    hp,
+                    max_hp,
+                } => self.update_healthbar_healing(&ulid, amount, hp, max_hp),
+                NodeCommand::HealthBars { updates } => {
+                    for (ulid, hp, max_hp) in updates {
+                        self.set_healthbar_hp(&ulid, hp, max_hp);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Move a pooled node into the scene tree for a spawned NPC
+    fn spawn_node(&self, ulid: &[u8; 16], npc_type: &str, position: Vec2) -> bool {
+        let mut npc = match self.inactive_npc_pool.remove(ulid) {
+            Some((_, npc)) => npc,
+            None => {
+                godot_error!(
+                    "[RUST POOL] Cannot spawn {} - no pooled node for ULID {}",
+                    npc_type,
+                    &bytes_to_hex(ulid)[..16]
+                );
+                return false;
+            }
+        };
+
+        // Set ULID as a property on the NPC node BEFORE adding to tree
+        // Use the existing 'ulid' property defined in npc.gd base class
+        let ulid_bytes = PackedByteArray::from(&ulid[..]);
+        let ulid_variant = ulid_bytes.to_variant();
+        npc.node.set("ulid", &ulid_variant);
+
+        // Add NPC to scene tree if container is set
+        {
+            let mut container_guard = self.scene_container.borrow_mut();
+            if let Some(ref mut container) = *container_guard {
+                let container_path = container.get_path();
+                container.add_child(&npc.node);
+                let node_path = npc.node.get_path();
+                godot_print!(
+                    "[RUST POOL] Added {} to scene tree: container={}, node_path={}",
+                    npc_type,
+                    container_path,
+                    node_path
+                );
+            } else {
+                godot_error!("[RUST POOL] Cannot spawn NPC - scene container not set!");
+                self.inactive_npc_pool.insert(*ulid, npc);
+                return false;
+            }
+        }
+
+        // Activate the NPC AFTER adding to tree (important for visibility to work)
+        npc.activate(Vector2::new(position.x, position.y));
+
+        // Move to active pool (use [u8; 16] directly as key)
+        self.active_npc_pool.insert(*ulid, npc);
+
+        // Get and assign a healthbar from the pool
+        if let Some((mut healthbar, _index)) = self.get_healthbar_for_npc(ulid) {
+            // Call connect_to_entity on the healthbar to attach it to the NPC
+            // Pass the NPC node as a Variant
+            if let Some(active_npc) = self.active_npc_pool.get(ulid) {
+                let npc_variant = active_npc.node.to_variant();
+                let _ = healthbar.call("connect_to_entity", &[npc_variant]);
+            }
+        }
+
+        true
+    }
+
+    /// Remove a despawned NPC's node from the scene and return it to the pool
+    fn despawn_node(&self, ulid: &[u8; 16]) {
+        // Return healthbar to pool BEFORE removing NPC
+        if let Some((_, pool_index)) = self.healthbar_assignments.remove(ulid) {
+            // Get the healthbar and disconnect it from the NPC
+            if let Some(mut healthbar) = self
+                .healthbar_pool
+                .get(&pool_index)
+                .map(|e| e.value().clone())
+            {
+                let _ = healthbar.call("disconnect_from_entity", &[]);
+            }
+        }
+
+        // Remove from active pool (a node that never spawned has nothing to undo)
+        let mut npc = match self.active_npc_pool.remove(ulid) {
+            Some((_, npc)) => npc,
+            None => return,
+        };
+
+        // Remove from scene tree
+        {
+            let mut container_guard = self.scene_container.borrow_mut();
+            if let Some(ref mut container) = *container_guard {
+                container.remove_child(&npc.node);
+            }
+        }
+
+        // Reset the NPC node and return it to the inactive pool
+        npc.reset();
+        self.inactive_npc_pool.insert(*ulid, npc);
+    }
+
+    // ============================================================================
+    // SCENE SYNC - Mirror simulated positions and states onto nodes
+    // ============================================================================
+
+    /// Interpolate each active node toward its position in the latest frame
+    pub fn sync_positions(&self, frame: &SimFrame) {
+        const LERP_WEIGHT: f32 = 0.15; // Smoothing factor (0.0 = no movement, 1.0 = instant)
+
+        for entry in self.active_npc_pool.iter() {
+            let target = match frame.npcs.get(entry.key()) {
+                Some(npc) => npc.position,
+                None => continue,
+            };
+
+            // Clone the Gd handle (creates new reference to same node)
+            let mut node = entry.value().node.clone();
+            let current_visual_pos = node.get_position();
+            let current = Vec2::new(current_visual_pos.x, current_visual_pos.y);
+
+            // Lerp from current visual position to target position for smooth movement
+            // and clamp the lerped visual position to world bounds as well
+            let visual = current
+                .lerp(target, LERP_WEIGHT)
+                .clamp(frame.world_min, frame.world_max);
+
+            node.set_position(Vector2::new(visual.x, visual.y));
+        }
+    }
+
+    /// Update NPC animations from the latest frame (Rust controls animations)
+    /// Called every frame after the simulation tick
+    /// Animation names match SpriteFrames: "idle", "walking", "attacking", "hurt", "dead"
+    pub fn sync_animations(&self, frame: &SimFrame) {
+        for npc in self.active_npc_pool.iter() {
+            let ulid_bytes = npc.key();
+
+            // Skip NPCs the frame doesn't know (no position or stats)
+            let Some(npc_frame) = frame.npcs.get(ulid_bytes) else {
+                continue;
+            };
+            let behavioral_state = npc_frame.state;
+            let animation_name = npc_frame.animation();
+
+            // Log death animation (always log, not just first few times)
+            if animation_name == "dead" {
+                let ulid_hex = bytes_to_hex(ulid_bytes);
+                godot_print!(
+                    "[DEATH ANIM] Playing death animation for ULID {} (state={})",
+                    ulid_hex,
+                    behavioral_state.bits()
+                );
+            }
+
+            // Update animation and sprite direction
+            if let Some(ref sprite) = npc.animated_sprite {
+                let mut sprite_mut = sprite.clone();
+                let new_anim = StringName::from(animation_name);
+
+                // Log when setting death animation
+                if animation_name == "dead" {
+                    let ulid_hex = bytes_to_hex(ulid_bytes);
+                    let current_anim = sprite_mut.get_animation();
+                    godot_print!("[DEATH ANIM SET] ULID {} - Setting animation from '{}' to 'dead', playing={}",
+                        ulid_hex, current_anim, sprite_mut.is_playing());
+                }
+
+                // Check if animation changed
+                let current_anim = sprite_mut.get_animation();
+                let animation_changed = current_anim != new_anim;
+
+                // Set animation (Godot is smart about redundant calls)
+                sprite_mut.set_animation(&new_anim);
+
+                // Only call play() if:
+                // 1. Animation changed (including to "dead")
+                // 2. Animation is not currently playing (for non-death animations)
+                // IMPORTANT: Never replay death animation once it's already playing/finished
+                if animation_name == "dead" {
+                    // Only play death animation if it just changed from something else
+                    if animation_changed {
+                        sprite_mut.play();
+                    }
+                    // Otherwise, let it finish naturally (loop=false handles staying on last frame)
+                } else if animation_changed || !sprite_mut.is_playing() {
+                    // Non-death animation changed, or stopped playing - play it
+                    sprite_mut.play();
+                }
+
+                // SPRITE FLIPPING: facing is decided by the core (None = keep, e.g. dead)
+                if let Some(face_left) = npc_frame.face_left {
+                    sprite_mut.set_flip_h(face_left);
+                }
+            }
+
+            // Sync behavioral state to GDScript property (for chat UI and other systems)
+            let mut node = npc.node.clone();
+            let state_variant = Variant::from(behavioral_state.bits() as i32);
+            node.set("current_state", &state_variant);
+        }
+    }
+
+    // ============================================================================
+    // HEALTHBAR POOL MANAGEMENT
+    // ============================================================================
+
+    /// Initialize the healthbar pool by loading and instantiating the packed scene
+    /// Similar to how NPC pools work - load scene once, instantiate multiple times
+    pub fn initialize_healthbar_pool(&self, pool_size: usize, scene_path: &str) {
+        godot_print!(
+            "[RUST HEALTHBAR] Initializing healthbar pool: size={}, scene={}",
+            pool_size,
+            scene_path
+        );
+
+        // Load the healthbar scene
+        let scene: Gd<PackedScene> = match try_load::<PackedScene>(scene_path) {
+            Ok(scene) => scene,
+            Err(err) => {
+                godot_error!(
+                    "[RUST HEALTHBAR] Failed to load healthbar scene: {} - {:?}",
+                    scene_path,
+                    err
+                );
+                return;
+            }
+        };
+
+        // Get the foreground container (scene parent)
+        let container_opt = {
+            let container_guard = self.scene_container.borrow();
+            container_guard.clone()
+        };
+
+        if container_opt.is_none() {
+            godot_error!(
+                "[RUST HEALTHBAR] Cannot initialize healthbar pool - scene container not set!"
+            );
+            return;
+        }
+
+        let mut container = container_opt.unwrap();
+
+        // Instantiate healthbars and add to pool
+        for _i in 0..pool_size {
+            let healthbar = scene.instantiate_as::<Node>();
+
+            // Add to scene tree
+            container.add_child(&healthbar);
+
+            // Configure healthbar (clone to avoid move)
+            if let Ok(mut control_healthbar) = healthbar.clone().try_cast::<Control>() {
+                control_healthbar.set_visible(false); // Hide until assigned
+                control_healthbar.set_z_index(100); // Render above NPCs
+            }
+
+            // Add to pool
+            let index = self.healthbar_pool_size.fetch_add(1, Ordering::Relaxed);
+            self.healthbar_pool.insert(index, healthbar);
+        }
+
+        godot_print!(
+            "[RUST HEALTHBAR] Initialized {} healthbars in pool",
+            pool_size
+        );
+    }
+
+    /// Get an available healthbar from the pool and assign it to an NPC
+    /// Returns the healthbar node and its pool index, or None if pool is empty
+    pub fn get_healthbar_for_npc(&self, ulid: &[u8; 16]) -> Option<(Gd<Node>, usize)> {
+        // Find first healthbar not currently assigned
+        for entry in self.healthbar_pool.iter() {
+            let index = *entry.key();
+
+            // Check if this healthbar is already assigned
+            let is_assigned = self
+                .healthbar_assignments
+                .iter()
+                .any(|a| *a.value() == index);
+
+            if !is_assigned {
+                let healthbar = entry.value().clone();
+                // Assign this healthbar to the NPC
+                self.healthbar_assignments.insert(*ulid, index);
+                godot_print!(
+                    "[RUST HEALTHBAR] Assigned healthbar {} to NPC {:02x}{:02x}{:02x}{:02x}",
+                    index,
+                    ulid[0],
+                    ulid[1],
+                    ulid[2],
+                    ulid[3]
+                );
+                return Some((healthbar, index));
+            }
+        }
+
+        godot_warn!("[RUST HEALTHBAR] No available healthbars in pool!");
+        None
+    }
+
+    /// Return a healthbar to the pool when NPC is despawned
+    pub fn return_healthbar(&self, ulid: &[u8; 16]) -> bool {
+        if let Some((_, pool_index)) = self.healthbar_assignments.remove(ulid) {
+            // Hide the healthbar when returning to pool (cloned out of the guard first)
+            let healthbar = self
+                .healthbar_pool
+                .get(&pool_index)
+                .map(|healthbar| healthbar.value().clone());
+            if let Some(healthbar) = healthbar {
+                if let Ok(mut control_healthbar) = healthbar.try_cast::<Control>() {
+                    control_healthbar.set_visible(false);
+                }
+            }
+
+            godot_print!(
+                "[RUST HEALTHBAR] Returned healthbar {} to pool from NPC {:02x}{:02x}{:02x}{:02x}",
+                pool_index,
+                ulid[0],
+                ulid[1],
+                ulid[2],
+                ulid[3]
+            );
+            true
+        } else {
+            godot_warn!(
+                "[RUST HEALTHBAR] No healthbar assigned to NPC {:02x}{:02x}{:02x}{:02x}",
+                ulid[0],
+                ulid[1],
+                ulid[2],
+                ulid[3]
+            );
+            false
+        }
+    }
+
+    /// Get the number of healthbars in the pool
+    pub fn healthbar_pool_count(&self) -> usize {
+        self.healthbar_pool.len()
+    }
+
+    /// Get the number of active healthbar assignments
+    pub fn active_healthbar_count(&self) -> usize {
+        self.healthbar_assignments.len()
+    }
+
+    /// Set every assigned healthbar to its NPC's current HP (no floating text)
+    /// Used after restoring a snapshot, where NPCs come back already damaged
+    pub fn sync_healthbars(&self, warehouse: &NPCDataWarehouse) {
+        // Collect first - Godot calls can re-enter the layer, so no guard may be held
+        let assigned: Vec<[u8; 16]> = self
+            .healthbar_assignments
+            .iter()
+            .map(|entry| *entry.key())
+            .collect();
+        for ulid in assigned {
+            if let Some(stats) = warehouse.get_combat_stats(&ulid) {
+                self.set_healthbar_hp(&ulid, stats.hp, stats.max_hp);
+            }
+        }
+    }
+
+    /// Healthbar node assigned to an NPC, cloned out of the pool maps
+    /// (never call into Godot while a DashMap guard is held)
+    fn assigned_healthbar(&self, ulid: &[u8; 16]) -> Option<Gd<Node>> {
+        let pool_index = *self.healthbar_assignments.get(ulid)?.value();
+        let healthbar = self.healthbar_pool.get(&pool_index)?.value().clone();
+        Some(healthbar)
+    }
+
+    /// Set an NPC's healthbar to its HP without floating text (regeneration)
+    fn set_healthbar_hp(&self, ulid: &[u8; 16], current_hp: f32, max_hp: f32) {
+        if let Some(mut healthbar) = self.assigned_healthbar(ulid) {
+            let _ = healthbar.call(
+                "set_health",
+                &[current_hp.to_variant(), max_hp.to_variant()],
+            );
+        }
+    }
+
+    /// Update the healthbar for an NPC when they take damage
+    fn update_healthbar_hp(&self, ulid: &[u8; 16], damage: f32, current_hp: f32, max_hp: f32) {
+        if let Some(mut healthbar) = self.assigned_healthbar(ulid) {
+            // Call _on_entity_damage_taken directly to update health AND spawn damage text
+            // This mimics what the signal would do
+            let _ = healthbar.call(
+                "_on_entity_damage_taken",
+                &[
+                    damage.to_variant(),
+                    current_hp.to_variant(),
+                    max_hp.to_variant(),
+                ],
+            );
+        }
+    }
+
+    /// Update the healthbar for an NPC when they are healed
+    fn update_healthbar_healing(
+        &self,
+        ulid: &[u8; 16],
+        heal_amount: f32,
+        current_hp: f32,
+        max_hp: f32,
+    ) {
+        if let Some(mut healthbar) = self.assigned_healthbar(ulid) {
+            // Call _on_entity_healed to update health AND spawn green healing text
+            let _ = healthbar.call(
+                "_on_entity_healed",
+                &[
+                    heal_amount.to_variant(),
+                    current_hp.to_variant(),
+                    max_hp.to_variant(),
+                ],
+            );
+        }
+    }
+}
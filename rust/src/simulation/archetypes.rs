@@ -0,0 +1,475 @@
+// ============================================================================
+// NPC ARCHETYPES - Data-driven type definitions (stats, faction, spawning)
+// ============================================================================
+// Every NPC type is an archetype loaded from JSON. The built-in set ships in
+// npc_archetypes.json next to this file; hosts can load more files or
+// register single archetypes at runtime, so a new monster needs no rebuild.
+//
+// File format:
+//   { "archetypes": [ { "name": "goblin", "faction": "monster",
+//                       "combat_type": "melee", "max_hp": 100.0, ... } ] }
+
+use parking_lot::RwLock;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::damage::{DamageType, Resistances, DEFAULT_CRIT_MULTIPLIER};
+use super::log::sim_error;
+use super::stats::{NPCCombatStats, NPCStaticState};
+use super::threat::TargetStrategy;
+
+/// Built-in archetypes (compiled in so headless runs work without files)
+const DEFAULT_ARCHETYPES_JSON: &str = include_str!("npc_archetypes.json");
+
+/// Default attack cooldown when an archetype doesn't set one (1 attack per 3.5s)
+pub const DEFAULT_ATTACK_COOLDOWN_MS: u64 = 3500;
+
+/// Default movement speed in pixels per second
+pub const DEFAULT_MOVE_SPEED: f32 = 80.0;
+
+/// Default time a healer channels a heal before it lands
+pub const DEFAULT_HEAL_CHANNEL_MS: u64 = 1000;
+
+/// Faction an archetype belongs to (maps to NPCStaticState faction flags)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Faction {
+    Ally,
+    Monster,
+    Passive,
+}
+
+impl Faction {
+    /// Name of the matching built-in faction in the faction table
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Faction::Ally => "ally",
+            Faction::Monster => "monster",
+            Faction::Passive => "passive",
+        }
+    }
+}
+
+/// Combat type of an archetype (maps to NPCStaticState combat flags)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CombatType {
+    Melee,
+    Ranged,
+    Magic,
+    /// Support - heals friendlies instead of attacking (see healing.rs)
+    Healer,
+}
+
+/// One NPC type definition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NPCArchetype {
+    /// Type key used by pools and spawning (e.g. "goblin")
+    pub name: String,
+    pub faction: Faction,
+    /// Named faction in the relationship matrix (defaults to `faction`);
+    /// unknown names are registered inheriting from `faction`
+    #[serde(default)]
+    pub faction_name: Option<String>,
+    /// Required for ALLY/MONSTER, optional for PASSIVE critters
+    #[serde(default)]
+    pub combat_type: Option<CombatType>,
+
+    pub max_hp: f32,
+    pub attack: f32,
+    pub defense: f32,
+    #[serde(default)]
+    pub max_mana: f32,
+    #[serde(default = "default_max_energy")]
+    pub max_energy: f32,
+    #[serde(default = "default_max_hunger")]
+    pub max_hunger: f32,
+
+    /// Attack range in pixels (defaults by combat type)
+    #[serde(default)]
+    pub attack_range: Option<f32>,
+    #[serde(default = "default_attack_cooldown_ms")]
+    pub attack_cooldown_ms: u64,
+    #[serde(default = "default_move_speed")]
+    pub move_speed: f32,
+
+    /// Damage type dealt (defaults by combat type: ranged = pierce, magic = magic)
+    #[serde(default)]
+    pub damage_type: Option<DamageType>,
+    /// Fraction of each damage type ignored (see damage.rs)
+    #[serde(default)]
+    pub resistances: Resistances,
+    /// Chance (0-1) that a hit is critical
+    #[serde(default)]
+    pub crit_chance: f32,
+    #[serde(default = "default_crit_multiplier")]
+    pub crit_multiplier: f32,
+
+    /// HP restored per heal (required for healers)
+    #[serde(default)]
+    pub heal_power: f32,
+    /// Mana spent per heal
+    #[serde(default)]
+    pub heal_mana_cost: f32,
+    /// Time a heal is channeled before it lands
+    #[serde(default = "default_heal_channel_ms")]
+    pub heal_channel_ms: u64,
+
+    /// Energy / mana spent per attack (see resources.rs)
+    #[serde(default)]
+    pub attack_energy_cost: f32,
+    #[serde(default)]
+    pub attack_mana_cost: f32,
+    /// Energy / mana regenerated per second
+    #[serde(default)]
+    pub energy_regen: f32,
+    #[serde(default)]
+    pub mana_regen: f32,
+    /// HP regenerated per second out of combat (see regen.rs)
+    #[serde(default)]
+    pub hp_regen: f32,
+    /// Hunger lost per second (see hunger.rs)
+    #[serde(default)]
+    pub hunger_decay: f32,
+
+    /// How NPCs of this type pick their target (see threat.rs)
+    #[serde(default)]
+    pub target_strategy: TargetStrategy,
+    /// Archetype names the "preferred" strategy goes after first
+    #[serde(default)]
+    pub preferred_targets: Vec<String>,
+
+    /// PackedScene used by the host to instance this NPC
+    #[serde(default)]
+    pub scene_path: String,
+    /// Name generator style (defaults to the archetype name)
+    #[serde(default)]
+    pub name_style: Option<String>,
+    /// Relative chance to appear in monster waves (0 = never)
+    #[serde(default)]
+    pub wave_weight: u32,
+}
+
+fn default_max_energy() -> f32 {
+    100.0
+}
+
+fn default_max_hunger() -> f32 {
+    100.0
+}
+
+fn default_attack_cooldown_ms() -> u64 {
+    DEFAULT_ATTACK_COOLDOWN_MS
+}
+
+fn default_move_speed() -> f32 {
+    DEFAULT_MOVE_SPEED
+}
+
+fn default_crit_multiplier() -> f32 {
+    DEFAULT_CRIT_MULTIPLIER
+}
+
+fn default_heal_channel_ms() -> u64 {
+    DEFAULT_HEAL_CHANNEL_MS
+}
+
+/// Attack range for a combat type (used when an archetype doesn't set one)
+pub fn default_attack_range(static_state: NPCStaticState) -> f32 {
+    if static_state.contains(NPCStaticState::MELEE) {
+        30.0 // Melee range - close combat
+    } else if static_state.contains(NPCStaticState::RANGED) {
+        200.0 // Ranged range
+    } else if static_state.intersects(NPCStaticState::MAGIC | NPCStaticState::HEALER) {
+        150.0 // Magic / heal range
+    } else {
+        30.0 // Default - close combat
+    }
+}
+
+/// Top-level layout of an archetype definitions file
+#[derive(Deserialize)]
+struct ArchetypeFile {
+    archetypes: Vec<NPCArchetype>,
+}
+
+impl NPCArchetype {
+    /// Static flags (combat type + faction)
+    pub fn static_flags(&self) -> NPCStaticState {
+        let faction = match self.faction {
+            Faction::Ally => NPCStaticState::ALLY,
+            Faction::Monster => NPCStaticState::MONSTER,
+            Faction::Passive => NPCStaticState::PASSIVE,
+        };
+        let combat = match self.combat_type {
+            Some(CombatType::Melee) => NPCStaticState::MELEE,
+            Some(CombatType::Ranged) => NPCStaticState::RANGED,
+            Some(CombatType::Magic) => NPCStaticState::MAGIC,
+            Some(CombatType::Healer) => NPCStaticState::HEALER,
+            None => NPCStaticState::empty(),
+        };
+        faction | combat
+    }
+
+    /// Faction this archetype's NPCs join when they spawn
+    pub fn faction_name(&self) -> &str {
+        self.faction_name
+            .as_deref()
+            .unwrap_or_else(|| self.faction.as_str())
+    }
+
+    /// Attack range, falling back to the combat type default
+    pub fn attack_range(&self) -> f32 {
+        self.attack_range
+            .unwrap_or_else(|| default_attack_range(self.static_flags()))
+    }
+
+    /// Damage type this archetype deals, falling back to the combat type default
+    pub fn damage_type(&self) -> DamageType {
+        self.damage_type
+            .unwrap_or_else(|| DamageType::for_static(self.static_flags()))
+    }
+
+    /// Name generator style for this archetype
+    pub fn name_style(&self) -> &str {
+        self.name_style.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Fresh combat stats for a newly spawned NPC (all pools full)
+    pub fn combat_stats(&self) -> NPCCombatStats {
+        NPCCombatStats {
+            hp: self.max_hp,
+            max_hp: self.max_hp,
+            attack: self.attack,
+            defense: self.defense,
+            static_state: self.static_flags().bits() as i32,
+            emotional_state: 0, // Neutral
+            mana: self.max_mana,
+            max_mana: self.max_mana,
+            energy: self.max_energy,
+            max_energy: self.max_energy,
+            hunger: self.max_hunger,
+            max_hunger: self.max_hunger,
+            attack_range: self.attack_range(),
+            attack_cooldown_ms: self.attack_cooldown_ms,
+            move_speed: self.move_speed,
+            damage_type: self.damage_type(),
+            resistances: self.resistances,
+            crit_chance: self.crit_chance,
+            crit_multiplier: self.crit_multiplier,
+            heal_power: self.heal_power,
+            heal_mana_cost: self.heal_mana_cost,
+            heal_channel_ms: self.heal_channel_ms,
+            attack_energy_cost: self.attack_energy_cost,
+            attack_mana_cost: self.attack_mana_cost,
+            energy_regen: self.energy_regen,
+            mana_regen: self.mana_regen,
+            hp_regen: self.hp_regen,
+            hunger_decay: self.hunger_decay,
+            target_strategy: self.target_strategy,
+        }
+    }
+
+    /// Check the definition is usable, returns a readable reason if not
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("archetype name is empty".to_string());
+        }
+        let non_negative = |value: f32| value.is_finite() && value >= 0.0;
+        if !self.max_hp.is_finite() || self.max_hp <= 0.0 {
+            return Err(format!(
+                "'{}': max_hp must be > 0 (got {})",
+                self.name, self.max_hp
+            ));
+        }
+        for (field, value) in [
+            ("attack", self.attack),
+            ("defense", self.defense),
+            ("max_mana", self.max_mana),
+            ("max_energy", self.max_energy),
+            ("max_hunger", self.max_hunger),
+            ("move_speed", self.move_speed),
+            ("heal_power", self.heal_power),
+            ("heal_mana_cost", self.heal_mana_cost),
+            ("attack_energy_cost", self.attack_energy_cost),
+            ("attack_mana_cost", self.attack_mana_cost),
+            ("energy_regen", self.energy_regen),
+            ("mana_regen", self.mana_regen),
+            ("hp_regen", self.hp_regen),
+            ("hunger_decay", self.hunger_decay),
+        ] {
+            if !non_negative(value) {
+                return Err(format!(
+                    "'{}': {} must be >= 0 (got {})",
+                    self.name, field, value
+                ));
+            }
+        }
+        if let Some(range) = self.attack_range {
+            if !range.is_finite() || range <= 0.0 {
+                return Err(format!(
+                    "'{}': attack_range must be > 0 (got {})",
+                    self.name, range
+                ));
+            }
+        }
+        if !(0.0..=1.0).contains(&self.crit_chance) {
+            return Err(format!(
+                "'{}': crit_chance must be in [0, 1] (got {})",
+                self.name, self.crit_chance
+            ));
+        }
+        if !self.crit_multiplier.is_finite() || self.crit_multiplier < 1.0 {
+            return Err(format!(
+                "'{}': crit_multiplier must be >= 1 (got {})",
+                self.name, self.crit_multiplier
+            ));
+        }
+        self.resistances
+            .validate()
+            .map_err(|e| format!("'{}': {}", self.name, e))?;
+        if self.attack_cooldown_ms == 0 {
+            return Err(format!("'{}': attack_cooldown_ms must be > 0", self.name));
+        }
+        if self.faction != Faction::Passive && self.combat_type.is_none() {
+            return Err(format!(
+                "'{}': {:?} archetypes need a combat_type (melee/ranged/magic/healer)",
+                self.name, self.faction
+            ));
+        }
+        if self.combat_type == Some(CombatType::Healer) && self.heal_power <= 0.0 {
+            return Err(format!("'{}': healers need a heal_power > 0", self.name));
+        }
+        if self.target_strategy == TargetStrategy::Preferred && self.preferred_targets.is_empty() {
+            return Err(format!(
+                "'{}': the preferred target strategy needs preferred_targets",
+                self.name
+            ));
+        }
+        if self
+            .preferred_targets
+            .iter()
+            .any(|name| name.trim().is_empty())
+        {
+            return Err(format!(
+                "'{}': preferred_targets has an empty name",
+                self.name
+            ));
+        }
+        if let Some(faction_name) = &self.faction_name {
+            if faction_name.is_empty() || faction_name.chars().any(char::is_whitespace) {
+                return Err(format!(
+                    "'{}': faction_name must be a single word (got '{}')",
+                    self.name, faction_name
+                ));
+            }
+        }
+        if self.wave_weight > 0 && self.faction != Faction::Monster {
+            return Err(format!(
+                "'{}': only monsters can have a wave_weight",
+                self.name
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Registry of archetypes, kept in registration order
+/// (wave picks walk this order, so it stays stable for seeded runs)
+pub struct ArchetypeRegistry {
+    archetypes: RwLock<Vec<NPCArchetype>>,
+}
+
+impl ArchetypeRegistry {
+    /// Empty registry
+    pub fn new() -> Self {
+        Self {
+            archetypes: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Registry pre-loaded with the built-in archetypes
+    pub fn with_defaults() -> Self {
+        let registry = Self::new();
+        if let Err(e) = registry.load_json(DEFAULT_ARCHETYPES_JSON) {
+            sim_error!("[ARCHETYPES] Built-in archetypes failed to load: {}", e);
+        }
+        registry
+    }
+
+    /// Register (or replace by name) a single archetype after validating it
+    pub fn register(&self, archetype: NPCArchetype) -> Result<(), String> {
+        archetype.validate()?;
+        let mut archetypes = self.archetypes.write();
+        match archetypes.iter_mut().find(|a| a.name == archetype.name) {
+            Some(existing) => *existing = archetype,
+            None => archetypes.push(archetype),
+        }
+        Ok(())
+    }
+
+    /// Load a definitions file (see module docs for the layout)
+    /// Invalid archetypes are logged and skipped; returns how many were registered
+    pub fn load_json(&self, json: &str) -> Result<usize, String> {
+        let file: ArchetypeFile =
+            serde_json::from_str(json).map_err(|e| format!("invalid archetype JSON: {}", e))?;
+
+        let mut registered = 0;
+        for archetype in file.archetypes {
+            match self.register(archetype) {
+                Ok(()) => registered += 1,
+                Err(e) => sim_error!("[ARCHETYPES] Skipping archetype: {}", e),
+            }
+        }
+        Ok(registered)
+    }
+
+    /// Look up an archetype by name
+    pub fn get(&self, name: &str) -> Option<NPCArchetype> {
+        self.archetypes
+            .read()
+            .iter()
+            .find(|a| a.name == name)
+            .cloned()
+    }
+
+    /// Copies of every archetype, in registration order
+    pub fn all(&self) -> Vec<NPCArchetype> {
+        self.archetypes.read().clone()
+    }
+
+    /// Registered archetype names, in registration order
+    pub fn names(&self) -> Vec<String> {
+        self.archetypes
+            .read()
+            .iter()
+            .map(|a| a.name.clone())
+            .collect()
+    }
+
+    /// Pick a monster type for a wave, weighted by wave_weight
+    /// Returns None if no archetype can appear in waves
+    pub fn pick_wave_monster<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<String> {
+        let archetypes = self.archetypes.read();
+        let total: usize = archetypes.iter().map(|a| a.wave_weight as usize).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut roll = rng.random_range(0..total);
+        for archetype in archetypes.iter() {
+            let weight = archetype.wave_weight as usize;
+            if roll < weight {
+                return Some(archetype.name.clone());
+            }
+            roll -= weight;
+        }
+        None
+    }
+}
+
+impl Default for ArchetypeRegistry {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
@@ -0,0 +1,69 @@
+// ============================================================================
+// SIMULATION CLOCK - Injectable time source for cooldowns, timers and spawns
+// ============================================================================
+// The warehouse reads time only through a Clock. The default is a
+// ManualClock started at wall time and moved by the fixed-timestep driver
+// (see timestep.rs), so every timer runs on simulation time and battles can
+// be paused, sped up and fast-forwarded. SystemClock follows wall time for
+// hosts that tick on their own.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Time source for the simulation, in milliseconds
+pub trait Clock: Send + Sync {
+    /// Current simulation time in milliseconds
+    fn now_ms(&self) -> u64;
+
+    /// Move time forward by one fixed simulation step
+    /// Clocks that follow wall time ignore this
+    fn advance_step_ms(&self, _delta_ms: u64) {}
+}
+
+/// Wall-clock time (milliseconds since the Unix epoch)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+    }
+}
+
+/// Manually driven clock - time only moves on set_ms/advance_ms
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now_ms: AtomicU64,
+}
+
+impl ManualClock {
+    /// Create a manual clock starting at `start_ms`
+    pub fn new(start_ms: u64) -> Self {
+        Self {
+            now_ms: AtomicU64::new(start_ms),
+        }
+    }
+
+    /// Jump to an absolute time
+    pub fn set_ms(&self, now_ms: u64) {
+        self.now_ms.store(now_ms, Ordering::Relaxed);
+    }
+
+    /// Move time forward by `delta_ms`, returns the new time
+    pub fn advance_ms(&self, delta_ms: u64) -> u64 {
+        self.now_ms.fetch_add(delta_ms, Ordering::Relaxed) + delta_ms
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ms(&self) -> u64 {
+        self.now_ms.load(Ordering::Relaxed)
+    }
+
+    fn advance_step_ms(&self, delta_ms: u64) {
+        self.advance_ms(delta_ms);
+    }
+}
@@ -0,0 +1,648 @@
+// ============================================================================
+// COMBAT PHASE - Pairing, damage, healing and transient combat states
+// ============================================================================
+// Damage amounts come from the shared pipeline in damage.rs; attacks are paid
+// for with energy/mana first (resources.rs). HP, behavior and last attack go
+// through LiveState (see live.rs): the phase's components during a tick, the
+// warehouse maps for hits and heals the host reports between ticks.
+
+use bevy::math::Vec2;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use super::archetypes::DEFAULT_ATTACK_COOLDOWN_MS;
+use super::damage::DamageType;
+use super::ecs::NpcTable;
+use super::emotions::Emotion;
+use super::events::{CombatEvent, ProjectileKind, ARROW_SPEED};
+use super::hunger::HungerLevel;
+use super::live::LiveState;
+use super::log::sim_print;
+use super::replay::RecordedInput;
+use super::resources::{AttackKind, WEAK_ATTACK_MULTIPLIER};
+use super::spatial::{SpatialGrid, GRID_CELL_SIZE};
+use super::stats::{bytes_to_hex, NPCCombatStats, NPCState, NPCStaticState};
+use super::timers::TimerAction;
+use super::warehouse::{NPCDataWarehouse, NodeCommand, NpcRow};
+
+/// Attack animation length - ATTACKING is cleared after this long
+pub(super) const ATTACK_ANIM_DURATION_MS: u64 = 800;
+/// Hurt animation length - DAMAGED is cleared after this long
+pub(super) const DAMAGED_ANIM_DURATION_MS: u64 = 500;
+/// Death animation length - dead NPCs are despawned after this long
+pub(super) const DEATH_ANIM_DURATION_MS: u64 = 2000;
+
+impl NPCDataWarehouse {
+    /// PHASE 1: COMBAT - Calculate damage, update HP, set behavioral states
+    /// This phase ONLY handles combat logic and state changes
+    /// Returns combat events for GDScript to handle VFX/sounds
+    pub(super) fn run_combat_phase(&self, npcs: &mut NpcTable) -> Vec<CombatEvent> {
+        let mut events = Vec::new();
+        let now_ms = self.get_current_time_ms();
+
+        // Living NPCs registered for combat, sorted by ULID
+        let rows = npcs.rows();
+        if rows.is_empty() {
+            return events;
+        }
+
+        // Find combat pairs (proximity + hostility checks)
+        let combat_pairs = self.find_combat_pairs(&rows, npcs, now_ms);
+
+        if combat_pairs.is_empty() {
+            return events; // No combat happening (removed spam logging)
+        }
+
+        // Log when we first find combat pairs
+        static FIRST_COMBAT_LOG: AtomicBool = AtomicBool::new(false);
+        if !FIRST_COMBAT_LOG.swap(true, Ordering::Relaxed) {
+            sim_print!(
+                "[PHASE 1: COMBAT] *** FIRST COMBAT PAIRS FOUND! Processing {} combat pairs ***",
+                combat_pairs.len()
+            );
+        }
+
+        // Process each combat pair
+        for (attacker_ulid_bytes, target_ulid_bytes, _distance) in combat_pairs {
+            // DEFENSIVE: Validate attacker and target are different
+            if attacker_ulid_bytes == target_ulid_bytes {
+                let attacker_hex = bytes_to_hex(&attacker_ulid_bytes);
+                self.log_error_once(
+                    "self_attack",
+                    &attacker_hex,
+                    &format!(
+                        "[COMBAT ERROR] NPC {} is attacking itself! Skipping.",
+                        &attacker_hex[0..8]
+                    ),
+                );
+                continue;
+            }
+
+            // Check cooldown first - rejects the remaining pairs once an attacker has swung
+            if !self.check_attack_cooldown(npcs, &attacker_ulid_bytes, now_ms) {
+                continue; // Still on cooldown
+            }
+
+            // DEFENSIVE: Validate both NPCs exist and are alive
+            // Check DEAD flag first (most reliable - updated immediately when NPC dies)
+            if let Some(state) = npcs.behavior(&attacker_ulid_bytes) {
+                if state.contains(NPCState::DEAD) {
+                    // Silently skip - NPC died this tick or earlier
+                    continue;
+                }
+            }
+
+            // Also check HP as fallback
+            let attacker_stats = match self.get_combat_stats(&attacker_ulid_bytes) {
+                Some(stats) => stats,
+                None => {
+                    let attacker_hex = bytes_to_hex(&attacker_ulid_bytes);
+                    self.log_error_once(
+                        "missing_hp",
+                        &attacker_hex,
+                        &format!(
+                            "[COMBAT ERROR] Attacker {} has no HP stat. Skipping.",
+                            &attacker_hex[0..8]
+                        ),
+                    );
+                    continue;
+                }
+            };
+            if npcs
+                .health(&attacker_ulid_bytes)
+                .is_none_or(|health| health.hp <= 0.0)
+            {
+                // HP is 0 but DEAD flag not set yet - skip silently
+                continue;
+            }
+
+            let target_stats = match self.get_combat_stats(&target_ulid_bytes) {
+                Some(stats) => stats,
+                None => {
+                    let target_hex = bytes_to_hex(&target_ulid_bytes);
+                    self.log_error_once(
+                        "missing_hp",
+                        &target_hex,
+                        &format!(
+                            "[COMBAT ERROR] Target {} has no HP stat. Skipping.",
+                            &target_hex[0..8]
+                        ),
+                    );
+                    continue;
+                }
+            };
+            if npcs
+                .health(&target_ulid_bytes)
+                .is_none_or(|health| health.hp <= 0.0)
+            {
+                // Target already dead, skip silently (common case during combat)
+                continue;
+            }
+
+            // Get attacker static state to check if RANGED
+            let attacker_static_state = attacker_stats.static_flags();
+            let is_ranged = attacker_static_state.contains(NPCStaticState::RANGED);
+            let is_magic = attacker_static_state.contains(NPCStaticState::MAGIC);
+
+            // Get attacker and target positions (a missing position means the NPC
+            // was never placed - skip the pair rather than fighting at the origin)
+            let (attacker_pos, target_pos) = match (
+                npcs.position(&attacker_ulid_bytes),
+                npcs.position(&target_ulid_bytes),
+            ) {
+                (Some(a), Some(t)) => (a, t),
+                _ => {
+                    let attacker_hex = bytes_to_hex(&attacker_ulid_bytes);
+                    self.log_error_once(
+                        "missing_position",
+                        &attacker_hex,
+                        &format!(
+                            "[COMBAT ERROR] Pair with attacker {} has no position. Skipping.",
+                            &attacker_hex[0..8]
+                        ),
+                    );
+                    continue;
+                }
+            };
+
+            // Pay for the attack - out of energy rests, out of mana swings weakly
+            let attack_kind = self.pay_attack_cost(&attacker_ulid_bytes, &attacker_stats);
+            let attacker_stats = match attack_kind {
+                AttackKind::Full => attacker_stats,
+                AttackKind::Weak => NPCCombatStats {
+                    attack: attacker_stats.attack * WEAK_ATTACK_MULTIPLIER,
+                    damage_type: DamageType::Physical,
+                    ..attacker_stats
+                },
+                AttackKind::Rest => continue,
+            };
+
+            // Update attacker cooldown
+            npcs.set_last_attack_ms(&attacker_ulid_bytes, now_ms);
+
+            // Set ATTACKING state on attacker (Rust manages all states)
+            self.add_attacking_state(npcs, &attacker_ulid_bytes);
+
+            // Generate attack event (for animation)
+            events.push(CombatEvent::Attack {
+                attacker: attacker_ulid_bytes,
+                target: target_ulid_bytes,
+                target_pos,
+            });
+
+            // RANGED attacks (archers) use projectiles - GDScript handles collision and calls back
+            // (weak attacks land instantly)
+            if is_ranged && !is_magic && attack_kind == AttackKind::Full {
+                // Generate projectile event for GDScript to spawn arrow
+                // GDScript will read attacker position from attacker NPC node
+                events.push(CombatEvent::Projectile {
+                    attacker: attacker_ulid_bytes,
+                    target: target_ulid_bytes,
+                    kind: ProjectileKind::Arrow,
+                    origin: attacker_pos,
+                    target_pos,
+                    speed: ARROW_SPEED,
+                });
+                // Damage will be applied when GDScript calls projectile_hit()
+            } else {
+                // MELEE and MAGIC attacks: Apply damage instantly (see damage.rs)
+                let (damage, target_hp) = self.deal_hit(
+                    npcs,
+                    &attacker_ulid_bytes,
+                    &attacker_stats,
+                    &target_ulid_bytes,
+                    &target_stats,
+                );
+
+                // Log damage calculation for first few attacks
+                static DAMAGE_LOG_COUNT: AtomicU64 = AtomicU64::new(0);
+                if DAMAGE_LOG_COUNT.fetch_add(1, Ordering::Relaxed) < 10 {
+                    sim_print!(
+                        "[COMBAT] Attacker ATK: {:.1} vs Target DEF: {:.1} = {:.1} damage",
+                        attacker_stats.attack,
+                        target_stats.defense,
+                        damage
+                    );
+                }
+
+                // Handle target state based on HP
+                if target_hp <= 0.0 {
+                    // Mark target as dead (Rust manages all states)
+                    self.mark_dead(npcs, &target_ulid_bytes);
+
+                    // Generate death event
+                    events.push(CombatEvent::Death {
+                        attacker: attacker_ulid_bytes,
+                        target: target_ulid_bytes,
+                        amount: damage,
+                        target_pos,
+                    });
+                } else {
+                    // Set DAMAGED state on target (Rust manages all states)
+                    self.add_damaged_state(npcs, &target_ulid_bytes);
+
+                    // Generate damage event
+                    events.push(CombatEvent::Damage {
+                        attacker: attacker_ulid_bytes,
+                        target: target_ulid_bytes,
+                        amount: damage,
+                        target_pos,
+                    });
+                }
+            }
+        }
+
+        events
+    }
+
+    /// Find combat pairs based on proximity and faction hostility
+    /// Each NPC off cooldown queries the spatial grid out to its own attack range
+    /// Returns: Vec<(attacker_ulid, target_ulid, distance)>
+    fn find_combat_pairs(
+        &self,
+        npcs: &[NpcRow],
+        live: &NpcTable,
+        now_ms: u64,
+    ) -> Vec<([u8; 16], [u8; 16], f32)> {
+        let mut pairs = Vec::new();
+
+        // Debug: Log first close encounter
+        static FIRST_CLOSE_LOG: AtomicBool = AtomicBool::new(false);
+
+        let grid = SpatialGrid::build(npcs, GRID_CELL_SIZE);
+        let factions = self.faction_table();
+
+        for (i, a) in npcs.iter().enumerate() {
+            // Skip if dead (check behavioral state)
+            if a.state.contains(NPCState::DEAD) {
+                continue;
+            }
+
+            // PASSIVE NPCs never start a fight, whatever their faction, and
+            // healers spend their turns healing (see healing.rs)
+            if a.static_state
+                .intersects(NPCStaticState::PASSIVE | NPCStaticState::HEALER)
+            {
+                continue;
+            }
+
+            // Stunned NPCs skip their turn (see effects.rs) and frightened
+            // ones would rather run (see emotions.rs)
+            if self.is_stunned(&a.ulid) || self.is_frightened(&a.ulid) {
+                continue;
+            }
+
+            // NPCs still on cooldown cannot attack this tick - no pairs needed
+            if !self.check_attack_cooldown(live, &a.ulid, now_ms) {
+                continue;
+            }
+
+            // Attack range comes from the NPC's archetype
+            let range_a = a.attack_range;
+
+            // The NPC's target is the only pair while it is in range (see threat.rs)
+            let target_a = self.npc_target(&a.ulid);
+            let first_pair = pairs.len();
+
+            for j in grid.query_radius(a.pos, range_a) {
+                let b = &npcs[j];
+
+                // Skip self and dead targets (check behavioral state)
+                if i == j || b.state.contains(NPCState::DEAD) {
+                    continue;
+                }
+
+                // Check the relationship matrix
+                if !factions.is_hostile(a.faction, b.faction) {
+                    continue;
+                }
+
+                let distance = Self::distance(a.pos.x, a.pos.y, b.pos.x, b.pos.y);
+
+                // Debug: Log the first hostile NPCs found in range
+                if !FIRST_CLOSE_LOG.swap(true, Ordering::Relaxed) {
+                    sim_print!("[COMBAT PAIRS] Found hostile NPCs: {} at ({:.1},{:.1}) vs {} at ({:.1},{:.1}), distance: {:.1}, range: {}",
+                        bytes_to_hex(&a.ulid), a.pos.x, a.pos.y, bytes_to_hex(&b.ulid), b.pos.x, b.pos.y, distance, range_a);
+                }
+
+                if target_a == Some(b.ulid) {
+                    pairs.truncate(first_pair);
+                    pairs.push((i, j, distance));
+                    break;
+                }
+                pairs.push((i, j, distance));
+            }
+        }
+
+        // Process pairs in row order (lower row first, then its reply) so the
+        // attack order does not depend on grid layout
+        pairs.sort_unstable_by_key(|&(i, j, _)| (i.min(j), i.max(j), i > j));
+        pairs
+            .into_iter()
+            .map(|(i, j, distance)| (npcs[i].ulid, npcs[j].ulid, distance))
+            .collect()
+    }
+
+    /// Calculate distance between two points
+    pub(super) fn distance(x1: f32, y1: f32, x2: f32, y2: f32) -> f32 {
+        Vec2::new(x1, y1).distance(Vec2::new(x2, y2))
+    }
+
+    /// Check if attacker can attack (cooldown expired)
+    /// Cooldown length comes from the NPC's archetype (default: 1 attack per 3.5 seconds)
+    /// and is shorter while the NPC is happy (see emotions.rs)
+    pub(super) fn check_attack_cooldown(
+        &self,
+        npcs: &impl LiveState,
+        ulid_bytes: &[u8; 16],
+        now_ms: u64,
+    ) -> bool {
+        let cooldown_ms = match self.npc_combat_stats.get(ulid_bytes) {
+            Some(stats) => {
+                Emotion::from_id(stats.emotional_state).attack_cooldown_ms(stats.attack_cooldown_ms)
+            }
+            None => DEFAULT_ATTACK_COOLDOWN_MS,
+        };
+        match npcs.last_attack_ms(ulid_bytes) {
+            Some(last_attack_ms) => now_ms >= last_attack_ms + cooldown_ms,
+            None => true, // No cooldown record = can attack
+        }
+    }
+
+    /// Handle a projectile (arrow, spell) reaching its target
+    /// Damage goes through the shared pipeline with the attacker's current stats
+    /// (fallback stats dealing pierce damage if the attacker is gone)
+    /// Returns the damage/death event, or None if the target is already dead or gone
+    pub fn projectile_hit(
+        &self,
+        attacker_ulid_bytes: &[u8; 16],
+        target_ulid_bytes: &[u8; 16],
+    ) -> Option<CombatEvent> {
+        self.record_input(RecordedInput::ProjectileHit {
+            attacker: *attacker_ulid_bytes,
+            target: *target_ulid_bytes,
+        });
+
+        // Validate target is alive
+        let target_stats = match self.get_combat_stats(target_ulid_bytes) {
+            Some(stats) if stats.hp > 0.0 => stats,
+            _ => return None,
+        };
+
+        // Get attacker stats (attacker may have been despawned while the projectile was in flight)
+        let attacker_stats = self
+            .get_combat_stats(attacker_ulid_bytes)
+            .unwrap_or_else(|| NPCCombatStats {
+                damage_type: DamageType::Pierce,
+                ..NPCCombatStats::fallback()
+            });
+
+        let mut npcs = self.stored();
+        let (damage, new_target_hp) = self.deal_hit(
+            &mut npcs,
+            attacker_ulid_bytes,
+            &attacker_stats,
+            target_ulid_bytes,
+            &target_stats,
+        );
+
+        let target_pos = self
+            .get_npc_position_internal(target_ulid_bytes)
+            .unwrap_or(Vec2::ZERO);
+
+        let (attacker, target) = (*attacker_ulid_bytes, *target_ulid_bytes);
+        if new_target_hp <= 0.0 {
+            self.mark_dead(&mut npcs, target_ulid_bytes);
+            Some(CombatEvent::Death {
+                attacker,
+                target,
+                amount: damage,
+                target_pos,
+            })
+        } else {
+            self.add_damaged_state(&mut npcs, target_ulid_bytes);
+            Some(CombatEvent::Damage {
+                attacker,
+                target,
+                amount: damage,
+                target_pos,
+            })
+        }
+    }
+
+    /// Apply damage to target, return new HP
+    pub(super) fn apply_damage(
+        &self,
+        npcs: &mut impl LiveState,
+        ulid_bytes: &[u8; 16],
+        damage: f32,
+    ) -> f32 {
+        // Fallback if ULID not found
+        let Some(health) = npcs.health(ulid_bytes) else {
+            return 0.0;
+        };
+        let (new_hp, max_hp) = ((health.hp - damage).max(0.0), health.max_hp);
+        npcs.set_hp(ulid_bytes, new_hp);
+
+        // Let the host update the healthbar assigned to this NPC
+        self.push_node_command(NodeCommand::Damaged {
+            ulid: *ulid_bytes,
+            amount: damage,
+            hp: new_hp,
+            max_hp,
+        });
+
+        new_hp
+    }
+
+    /// Apply healing to target, return new HP
+    /// Increases HP without exceeding max_hp
+    /// Also increases hunger and energy (hidden stats)
+    pub fn apply_healing(
+        &self,
+        ulid_bytes: &[u8; 16],
+        heal_amount: f32,
+        hunger_gain: f32,
+        energy_gain: f32,
+    ) -> f32 {
+        self.record_input(RecordedInput::Heal {
+            target: *ulid_bytes,
+            amount: heal_amount,
+            hunger_gain,
+            energy_gain,
+        });
+        self.heal_npc(
+            &mut self.stored(),
+            ulid_bytes,
+            heal_amount,
+            hunger_gain,
+            energy_gain,
+        )
+    }
+
+    /// Heal without recording (the simulation's own healing)
+    pub(super) fn heal_npc(
+        &self,
+        npcs: &mut impl LiveState,
+        ulid_bytes: &[u8; 16],
+        heal_amount: f32,
+        hunger_gain: f32,
+        energy_gain: f32,
+    ) -> f32 {
+        // Fallback if ULID not found
+        let Some(health) = npcs.health(ulid_bytes) else {
+            return 0.0;
+        };
+        let (hunger_before, healed) = match self.npc_combat_stats.get_mut(ulid_bytes) {
+            Some(mut combat_stats) => {
+                let hunger_before = HungerLevel::of(combat_stats.hunger, combat_stats.max_hunger);
+
+                // Apply hunger and energy gains (cap at max values)
+                combat_stats.energy =
+                    (combat_stats.energy + energy_gain).min(combat_stats.max_energy);
+                combat_stats.hunger =
+                    (combat_stats.hunger + hunger_gain).min(combat_stats.max_hunger);
+
+                (hunger_before, *combat_stats)
+            }
+            None => return 0.0,
+        };
+        // Apply healing (cap at max_hp)
+        let hp = (health.hp + heal_amount).min(health.max_hp);
+        npcs.set_hp(ulid_bytes, hp);
+        if energy_gain != 0.0 {
+            self.push_resource_event(ulid_bytes, &healed);
+        }
+        if hunger_gain != 0.0 {
+            self.push_hunger_level_change(ulid_bytes, hunger_before, &healed);
+        }
+        if heal_amount > 0.0 {
+            self.feel_healed(ulid_bytes);
+        }
+
+        // Let the host update the healthbar with healing (green healing text)
+        self.push_node_command(NodeCommand::Healed {
+            ulid: *ulid_bytes,
+            amount: heal_amount,
+            hp,
+            max_hp: health.max_hp,
+        });
+
+        hp
+    }
+
+    /// Mark NPC as dead and remove from active combat
+    pub(super) fn mark_dead(&self, npcs: &mut impl LiveState, ulid_bytes: &[u8; 16]) {
+        // Set behavioral state to DEAD only (clear all other flags)
+        npcs.set_behavior(ulid_bytes, NPCState::DEAD);
+
+        let ulid_hex = bytes_to_hex(ulid_bytes);
+        sim_print!(
+            "[DEATH] Marked NPC {} as DEAD (state={})",
+            ulid_hex,
+            NPCState::DEAD.bits()
+        );
+
+        // IMPORTANT: Remove from active combat immediately
+        // This prevents dead NPCs from being included in combat processing next tick
+        self.active_combat_npcs.remove(ulid_bytes);
+
+        // Status effects, heal channels, emotions, regen visits and threat end
+        // with the NPC
+        self.clear_status_effects(ulid_bytes);
+        self.cancel_heal_channel(ulid_bytes);
+        self.clear_emotions(ulid_bytes);
+        self.forget_regen(ulid_bytes);
+        self.clear_threat(ulid_bytes);
+        self.drop_from_squad(npcs, ulid_bytes);
+
+        // Friends nearby saw it happen
+        self.feel_death(npcs, ulid_bytes);
+
+        // Schedule despawn after the death animation
+        self.schedule_timer(
+            DEATH_ANIM_DURATION_MS,
+            TimerAction::Despawn { ulid: *ulid_bytes },
+        );
+    }
+
+    /// Add ATTACKING state flag (set during attack)
+    pub(super) fn add_attacking_state(&self, npcs: &mut impl LiveState, ulid_bytes: &[u8; 16]) {
+        let current = npcs.behavior(ulid_bytes).unwrap_or(NPCState::empty());
+        // Add ATTACKING and remove IDLE (can't be idle while attacking)
+        let new_state = (current | NPCState::ATTACKING) - NPCState::IDLE;
+        npcs.set_behavior(ulid_bytes, new_state);
+
+        // Auto-clear once the attack animation has played (replaces a pending clear)
+        self.schedule_timer(
+            ATTACK_ANIM_DURATION_MS,
+            TimerAction::ClearState {
+                ulid: *ulid_bytes,
+                flag: NPCState::ATTACKING,
+            },
+        );
+
+        let ulid_hex = bytes_to_hex(ulid_bytes);
+        sim_print!(
+            "[ATTACK STATE] NPC {} - Setting ATTACKING flag (old_state={}, new_state={})",
+            &ulid_hex[0..8],
+            current.bits(),
+            new_state.bits()
+        );
+    }
+
+    /// Add DAMAGED state flag (set when taking damage)
+    pub(super) fn add_damaged_state(&self, npcs: &mut impl LiveState, ulid_bytes: &[u8; 16]) {
+        let current = npcs.behavior(ulid_bytes).unwrap_or(NPCState::empty());
+        // Add DAMAGED and remove IDLE (can't be idle while damaged)
+        let new_state = (current | NPCState::DAMAGED) - NPCState::IDLE;
+        npcs.set_behavior(ulid_bytes, new_state);
+
+        // Auto-clear once the hurt animation has played (replaces a pending clear)
+        self.schedule_timer(
+            DAMAGED_ANIM_DURATION_MS,
+            TimerAction::ClearState {
+                ulid: *ulid_bytes,
+                flag: NPCState::DAMAGED,
+            },
+        );
+
+        let ulid_hex = bytes_to_hex(ulid_bytes);
+        sim_print!(
+            "[DAMAGED STATE] NPC {} - Setting DAMAGED flag (old_state={}, new_state={})",
+            &ulid_hex[0..8],
+            current.bits(),
+            new_state.bits()
+        );
+    }
+
+    /// Remove a transient state flag and fall back to IDLE when nothing else is active
+    /// Note: IDLE | COMBAT is valid - it means "in combat stance but waiting between attacks"
+    pub(super) fn remove_transient_state(
+        &self,
+        npcs: &mut impl LiveState,
+        ulid_bytes: &[u8; 16],
+        flag: NPCState,
+    ) {
+        let current = npcs.behavior(ulid_bytes).unwrap_or(NPCState::empty());
+        let mut new_state = current - flag;
+
+        // Add IDLE back if not in any other active state
+        let active =
+            (NPCState::ATTACKING | NPCState::DAMAGED | NPCState::DEAD | NPCState::WALKING) - flag;
+        if !new_state.intersects(active) {
+            new_state |= NPCState::IDLE;
+        }
+
+        npcs.set_behavior(ulid_bytes, new_state);
+    }
+
+    /// Remove ATTACKING state flag (called by GDScript after attack animation finishes)
+    pub fn remove_attacking_state(&self, ulid_bytes: &[u8; 16]) {
+        self.remove_transient_state(&mut self.stored(), ulid_bytes, NPCState::ATTACKING);
+    }
+
+    /// Remove DAMAGED state flag (called by GDScript after hurt animation finishes)
+    pub fn remove_damaged_state(&self, ulid_bytes: &[u8; 16]) {
+        self.remove_transient_state(&mut self.stored(), ulid_bytes, NPCState::DAMAGED);
+    }
+}
@@ -0,0 +1,355 @@
+// ============================================================================
+// DAMAGE PIPELINE - One formula for every hit (melee, magic, projectiles)
+// ============================================================================
+// Every hit runs through the same steps:
+//   1. base       attack / attack_divisor - defense / defense_divisor,
+//                 at least min_damage (armor never blocks a hit outright)
+//   2. variance   base * (1 +/- variance), rolled only when variance > 0
+//   3. crit       * crit_multiplier on a crit_chance roll (attacker stats)
+//   4. resistance * (1 - resistance) for the hit's damage type (target stats)
+//   5. modifiers  registered hooks (buffs, equipment) adjust the amount
+// and never deals less than 0. The formula lives in DamageConfig; damage
+// types, resistances and crits come from the archetypes. Attack and defense
+// are scaled by the NPCs' status effects (effects.rs) before step 1.
+//
+// Each hit produces a DamageBreakdown. The last DAMAGE_LOG_CAPACITY of them
+// are kept for combat logs until the host takes them. Hits that don't kill
+// give the attacker threat on the target (threat.rs).
+//
+// Rolls only draw from the simulation RNG when their chance/variance is set,
+// so seeded runs without crits or variance are unaffected. Modifiers are code
+// and are not kept in recordings or snapshots.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::live::LiveState;
+use super::log::sim_print;
+use super::replay::RecordedInput;
+use super::stats::{NPCCombatStats, NPCStaticState};
+use super::threat::DAMAGE_THREAT;
+use super::warehouse::NPCDataWarehouse;
+
+/// Breakdowns kept for the host's combat log (oldest are dropped first)
+pub const DAMAGE_LOG_CAPACITY: usize = 256;
+
+/// Default crit multiplier for archetypes that don't set one
+pub const DEFAULT_CRIT_MULTIPLIER: f32 = 1.5;
+
+/// What kind of damage a hit deals (resistances apply per type)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DamageType {
+    #[default]
+    Physical,
+    Pierce,
+    Magic,
+}
+
+impl DamageType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DamageType::Physical => "physical",
+            DamageType::Pierce => "pierce",
+            DamageType::Magic => "magic",
+        }
+    }
+
+    /// Damage type for a combat type: ranged pierces, magic is magic
+    pub fn for_static(static_state: NPCStaticState) -> Self {
+        if static_state.contains(NPCStaticState::MAGIC) {
+            DamageType::Magic
+        } else if static_state.contains(NPCStaticState::RANGED) {
+            DamageType::Pierce
+        } else {
+            DamageType::Physical
+        }
+    }
+}
+
+/// Fraction of each damage type ignored (0.25 = 25% less, 1 = immune,
+/// negative = weakness)
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Resistances {
+    pub physical: f32,
+    pub pierce: f32,
+    pub magic: f32,
+}
+
+impl Resistances {
+    pub fn get(&self, damage_type: DamageType) -> f32 {
+        match damage_type {
+            DamageType::Physical => self.physical,
+            DamageType::Pierce => self.pierce,
+            DamageType::Magic => self.magic,
+        }
+    }
+
+    /// Check every value is usable
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("physical", self.physical),
+            ("pierce", self.pierce),
+            ("magic", self.magic),
+        ] {
+            if !value.is_finite() || value > 1.0 {
+                return Err(format!("{} resistance must be <= 1 (got {})", name, value));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Damage formula shared by every hit
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DamageConfig {
+    /// Attack is divided by this
+    pub attack_divisor: f32,
+    /// Defense is divided by this and subtracted
+    pub defense_divisor: f32,
+    /// Smallest base damage a hit can deal
+    pub min_damage: f32,
+    /// Random spread around the base damage (0.1 = +/-10%, 0 = none)
+    pub variance: f32,
+}
+
+impl Default for DamageConfig {
+    /// Slow, strategic fights: (attack / 6) - (defense / 8), minimum 1.5
+    fn default() -> Self {
+        Self {
+            attack_divisor: 6.0,
+            defense_divisor: 8.0,
+            min_damage: 1.5,
+            variance: 0.0,
+        }
+    }
+}
+
+impl DamageConfig {
+    /// Base damage before variance, crits, resistances and modifiers
+    pub fn base_damage(&self, attack: f32, defense: f32) -> f32 {
+        ((attack / self.attack_divisor) - (defense / self.defense_divisor)).max(self.min_damage)
+    }
+
+    /// Check the formula is usable
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = |value: f32| value.is_finite() && value > 0.0;
+        if !positive(self.attack_divisor) || !positive(self.defense_divisor) {
+            return Err("damage divisors must be > 0".to_string());
+        }
+        if !self.min_damage.is_finite() || self.min_damage < 0.0 {
+            return Err(format!("min_damage must be >= 0 (got {})", self.min_damage));
+        }
+        if !(0.0..1.0).contains(&self.variance) {
+            return Err(format!(
+                "variance must be in [0, 1) (got {})",
+                self.variance
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// How one hit's damage was worked out
+#[derive(Debug, Clone, PartialEq)]
+pub struct DamageBreakdown {
+    /// Simulation time of the hit
+    pub time_ms: u64,
+    pub attacker: [u8; 16],
+    pub target: [u8; 16],
+    pub damage_type: DamageType,
+    /// Attacker attack and target defense after status effects
+    pub attack: f32,
+    pub defense: f32,
+    /// Formula result (see DamageConfig::base_damage)
+    pub base: f32,
+    /// Variance factor rolled for this hit (1 = none)
+    pub variance: f32,
+    pub crit: bool,
+    /// Crit factor applied (1 without a crit)
+    pub crit_multiplier: f32,
+    /// Target resistance to the damage type
+    pub resistance: f32,
+    /// (modifier name, change in damage) in the order they ran
+    pub modifiers: Vec<(String, f32)>,
+    /// Damage dealt
+    pub amount: f32,
+}
+
+/// Hook that adjusts hits in flight (buffs, equipment, difficulty)
+/// Change `hit.amount`; everything else in the breakdown is for reading
+pub trait DamageModifier: Send + Sync {
+    fn modify(&self, hit: &mut DamageBreakdown);
+}
+
+impl<F> DamageModifier for F
+where
+    F: Fn(&mut DamageBreakdown) + Send + Sync,
+{
+    fn modify(&self, hit: &mut DamageBreakdown) {
+        self(hit)
+    }
+}
+
+/// Registered modifiers, run in registration order
+pub(crate) type DamageModifiers = Vec<(String, Arc<dyn DamageModifier>)>;
+
+impl NPCDataWarehouse {
+    /// Current damage formula
+    pub fn damage_config(&self) -> DamageConfig {
+        *self.damage_config.read()
+    }
+
+    /// Replace the damage formula
+    pub fn set_damage_config(&self, config: DamageConfig) -> Result<(), String> {
+        config.validate()?;
+        self.record_input(RecordedInput::DamageConfig { config });
+        *self.damage_config.write() = config;
+        sim_print!(
+            "NPCDataWarehouse: Damage formula set to attack/{} - defense/{} (min {}, variance {})",
+            config.attack_divisor,
+            config.defense_divisor,
+            config.min_damage,
+            config.variance
+        );
+        Ok(())
+    }
+
+    /// Base damage for an attack/defense pair (no rolls, resistances or modifiers)
+    pub fn preview_damage(&self, attack: f32, defense: f32) -> f32 {
+        self.damage_config().base_damage(attack, defense)
+    }
+
+    /// Register a damage modifier (replaces one with the same name)
+    /// Modifiers are code and can't be recorded, so this fails while recording
+    pub fn add_damage_modifier(
+        &self,
+        name: &str,
+        modifier: impl DamageModifier + 'static,
+    ) -> Result<(), String> {
+        if self.is_recording() {
+            return Err(format!(
+                "cannot add damage modifier '{}' while recording",
+                name
+            ));
+        }
+        let mut modifiers = self.damage_modifiers.write();
+        let modifier: Arc<dyn DamageModifier> = Arc::new(modifier);
+        match modifiers.iter_mut().find(|(existing, _)| existing == name) {
+            Some(entry) => entry.1 = modifier,
+            None => modifiers.push((name.to_string(), modifier)),
+        }
+        Ok(())
+    }
+
+    /// Remove a damage modifier - false if none had that name
+    /// Fails while recording (see add_damage_modifier)
+    pub fn remove_damage_modifier(&self, name: &str) -> Result<bool, String> {
+        if self.is_recording() {
+            return Err(format!(
+                "cannot remove damage modifier '{}' while recording",
+                name
+            ));
+        }
+        let mut modifiers = self.damage_modifiers.write();
+        let before = modifiers.len();
+        modifiers.retain(|(existing, _)| existing != name);
+        Ok(modifiers.len() != before)
+    }
+
+    /// Take the logged hit breakdowns, oldest first
+    pub fn take_damage_log(&self) -> Vec<DamageBreakdown> {
+        self.damage_log.lock().drain(..).collect()
+    }
+
+    /// Run a hit through the pipeline, apply it and log its breakdown
+    /// Returns (damage dealt, target HP after the hit)
+    pub(super) fn deal_hit(
+        &self,
+        npcs: &mut impl LiveState,
+        attacker: &[u8; 16],
+        attacker_stats: &NPCCombatStats,
+        target: &[u8; 16],
+        target_stats: &NPCCombatStats,
+    ) -> (f32, f32) {
+        let hit = self.resolve_hit(attacker, attacker_stats, target, target_stats);
+        let amount = hit.amount;
+        let hp = self.apply_damage(npcs, target, amount);
+        self.feel_hit(attacker, target, amount, target_stats, hp);
+        if hp > 0.0 {
+            self.add_threat(target, attacker, amount * DAMAGE_THREAT);
+        }
+
+        let mut log = self.damage_log.lock();
+        if log.len() >= DAMAGE_LOG_CAPACITY {
+            log.pop_front();
+        }
+        log.push_back(hit);
+        (amount, hp)
+    }
+
+    /// Work out a hit's damage without applying it
+    fn resolve_hit(
+        &self,
+        attacker: &[u8; 16],
+        attacker_stats: &NPCCombatStats,
+        target: &[u8; 16],
+        target_stats: &NPCCombatStats,
+    ) -> DamageBreakdown {
+        let config = self.damage_config();
+        let damage_type = attacker_stats.damage_type;
+        // Status effects scale attack and defense (see effects.rs)
+        let attack = attacker_stats.attack * self.status_modifiers(attacker).attack;
+        let defense = target_stats.defense * self.status_modifiers(target).defense;
+        let base = config.base_damage(attack, defense);
+
+        let (variance, crit) = {
+            let mut rng = self.rng();
+            let variance = if config.variance > 0.0 {
+                1.0 + rng.random_range(-config.variance..=config.variance)
+            } else {
+                1.0
+            };
+            let crit = attacker_stats.crit_chance > 0.0
+                && rng.random_bool(attacker_stats.crit_chance.min(1.0) as f64);
+            (variance, crit)
+        };
+        let crit_multiplier = if crit {
+            attacker_stats.crit_multiplier
+        } else {
+            1.0
+        };
+        let resistance = target_stats.resistances.get(damage_type);
+
+        let mut hit = DamageBreakdown {
+            time_ms: self.get_current_time_ms(),
+            attacker: *attacker,
+            target: *target,
+            damage_type,
+            attack,
+            defense,
+            base,
+            variance,
+            crit,
+            crit_multiplier,
+            resistance,
+            modifiers: Vec::new(),
+            amount: base * variance * crit_multiplier * (1.0 - resistance),
+        };
+
+        // Clone the list so a modifier may (un)register modifiers
+        let modifiers = self.damage_modifiers.read().clone();
+        for (name, modifier) in modifiers {
+            let before = hit.amount;
+            modifier.modify(&mut hit);
+            if !hit.amount.is_finite() {
+                hit.amount = before;
+            }
+            hit.modifiers.push((name, hit.amount - before));
+        }
+        hit.amount = hit.amount.max(0.0);
+        hit
+    }
+}
@@ -0,0 +1,731 @@
+// ============================================================================
+// SIMULATION ECS - Headless Bevy World/Schedule driving the phase systems
+// ============================================================================
+// The warehouse is the store the host sees between ticks (Godot reads it,
+// GDScript writes it). A tick runs five schedules:
+//   LoadPhase:       mirror the active NPCs into entities and load their live
+//                    state (HP, position, behavior, last attack) into
+//                    components, touching only values that changed
+//   CombatPhase:     status effects -> hunger -> combat -> healing, then
+//                    emotions, threat decay and regeneration
+//   MovementPhase:   initial spawn -> entities for the new NPCs -> movement
+//                    -> wave spawn -> ally spawn (the last three only while a
+//                    living NPC is in combat)
+//   WriteBackPhase:  copy the live state components changed this tick back
+//                    to the warehouse
+//   CleanupPhase:    fire due timers (despawns, ATTACKING/DAMAGED clears,
+//                    cooldowns) and shrink idle pools, on the warehouse
+// Between load and write-back the components are the live state: systems
+// change them through their queries (see live.rs) and the warehouse copies
+// stay as they were until the write-back.
+//
+// Systems that share the RNG or build on each other's results run chained in
+// a fixed order, so seeded runs replay deterministically. Emotions, threat
+// decay and regeneration draw nothing from the RNG and declare disjoint
+// access - Behavior read-only, Health written by regeneration alone, and
+// separate warehouse tables - so threat decay runs alongside the other two.
+// Regeneration waits for emotions, which read the energy it restores.
+
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use super::events::CombatEvent;
+use super::factions::FactionId;
+use super::live::LiveState;
+use super::log::sim_print;
+use super::replay::RecordedInput;
+use super::stats::{bytes_to_hex, NPCState, NPCStaticState};
+use super::warehouse::{NPCDataWarehouse, NpcRow};
+
+// ============================================================================
+// COMPONENTS
+// ============================================================================
+
+/// ULID of the NPC this entity mirrors
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NpcId(pub [u8; 16]);
+
+/// Simulated world position
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Position(pub Vec2);
+
+/// Immutable type flags (faction + combat type)
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaticFlags(pub NPCStaticState);
+
+/// Named faction (archetype faction or runtime override, see factions.rs)
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FactionMember(pub FactionId);
+
+/// Mutable behavioral state flags
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Behavior(pub NPCState);
+
+/// Current and maximum HP
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Health {
+    pub hp: f32,
+    pub max_hp: f32,
+}
+
+/// Simulation time (ms) of the last attack or heal cast (None: never)
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttackCooldown(pub Option<u64>);
+
+/// Attack range in pixels (from the NPC's archetype)
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct AttackRange(pub f32);
+
+/// Marker: NPC is registered with the combat system
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct InCombat;
+
+// ============================================================================
+// RESOURCES
+// ============================================================================
+
+/// Shared handle to the warehouse the systems read and write
+#[derive(Resource, Clone)]
+pub struct SimWarehouse(pub Arc<NPCDataWarehouse>);
+
+/// Delta time (seconds) for the current movement phase
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct TickDelta(pub f32);
+
+/// Output collected over a run - drained by the host after each phase/tick
+#[derive(Resource, Debug, Default)]
+pub struct TickOutput {
+    pub events: Vec<CombatEvent>,
+    pub death_positions: Vec<(f32, f32)>,
+}
+
+/// ULID -> entity lookup for the mirrored NPCs
+#[derive(Resource, Default)]
+struct NpcEntities(HashMap<[u8; 16], Entity>);
+
+// ============================================================================
+// SCHEDULES
+// ============================================================================
+
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LoadPhase;
+
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CombatPhase;
+
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MovementPhase;
+
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WriteBackPhase;
+
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CleanupPhase;
+
+/// Headless simulation world - owns the Bevy World and the phase schedules
+pub struct SimulationWorld {
+    world: World,
+}
+
+impl SimulationWorld {
+    pub fn new(warehouse: Arc<NPCDataWarehouse>) -> Self {
+        let mut world = World::new();
+        world.insert_resource(SimWarehouse(warehouse));
+        world.init_resource::<TickDelta>();
+        world.init_resource::<TickOutput>();
+        world.init_resource::<NpcEntities>();
+
+        let mut load = Schedule::new(LoadPhase);
+        load.add_systems(load_npc_entities);
+
+        let mut combat = Schedule::new(CombatPhase);
+        combat.add_systems(
+            (
+                status_effect_system,
+                hunger_system,
+                combat_system,
+                healing_system,
+                (
+                    emotion_system,
+                    threat_system,
+                    regen_system.after(emotion_system),
+                ),
+            )
+                .chain(),
+        );
+
+        let mut movement = Schedule::new(MovementPhase);
+        movement.add_systems(
+            (
+                initial_spawn_system,
+                adopt_new_npcs,
+                // Waves only roll while someone is on the field
+                (movement_system, wave_spawn_system, ally_spawn_system)
+                    .chain()
+                    .run_if(any_living_combat_npc),
+            )
+                .chain(),
+        );
+
+        let mut write_back = Schedule::new(WriteBackPhase);
+        write_back.add_systems(write_back_npc_state);
+
+        let mut cleanup = Schedule::new(CleanupPhase);
+        cleanup.add_systems(cleanup_system);
+
+        world.add_schedule(load);
+        world.add_schedule(combat);
+        world.add_schedule(movement);
+        world.add_schedule(write_back);
+        world.add_schedule(cleanup);
+
+        Self { world }
+    }
+
+    /// The warehouse driven by this world
+    pub fn warehouse(&self) -> &Arc<NPCDataWarehouse> {
+        &self.world.resource::<SimWarehouse>().0
+    }
+
+    /// Direct access to the ECS world (queries, extra systems)
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// Full tick: combat, movement, then cleanup
+    /// Returns (events, death_positions) where death_positions is Vec<(x, y)>
+    pub fn tick(&mut self, delta: f32) -> (Vec<CombatEvent>, Vec<(f32, f32)>) {
+        // Reduced logging
+        static TICK_LOG_COUNT: AtomicU64 = AtomicU64::new(0);
+        if TICK_LOG_COUNT
+            .fetch_add(1, Ordering::Relaxed)
+            .is_multiple_of(60)
+        {
+            // Log once per second
+            sim_print!("[TICK] === Starting three-phase tick ===");
+        }
+
+        let warehouse = self.warehouse().clone();
+        warehouse.record_tick_start(RecordedInput::Tick { delta });
+        self.world.resource_mut::<TickDelta>().0 = delta;
+        self.world.run_schedule(LoadPhase);
+        self.world.run_schedule(CombatPhase);
+        self.world.run_schedule(MovementPhase);
+        self.world.run_schedule(WriteBackPhase);
+        self.world.run_schedule(CleanupPhase);
+        warehouse.record_tick_end();
+
+        let output = std::mem::take(&mut *self.world.resource_mut::<TickOutput>());
+
+        // Reduced logging
+        static TICK_END_LOG: AtomicU64 = AtomicU64::new(0);
+        if TICK_END_LOG
+            .fetch_add(1, Ordering::Relaxed)
+            .is_multiple_of(60)
+        {
+            // Log once per second
+            sim_print!(
+                "[TICK] === Completed three-phase tick with {} total events ===",
+                output.events.len()
+            );
+        }
+
+        (output.events, output.death_positions)
+    }
+
+    /// Run only the combat phase, returns its events
+    pub fn run_combat_phase(&mut self) -> Vec<CombatEvent> {
+        let warehouse = self.warehouse().clone();
+        warehouse.record_tick_start(RecordedInput::CombatPhase);
+        self.world.run_schedule(LoadPhase);
+        self.world.run_schedule(CombatPhase);
+        self.world.run_schedule(WriteBackPhase);
+        warehouse.record_tick_end();
+        self.take_events()
+    }
+
+    /// Run only the movement phase (including spawning), returns its events
+    pub fn run_movement_phase(&mut self, delta: f32) -> Vec<CombatEvent> {
+        let warehouse = self.warehouse().clone();
+        warehouse.record_tick_start(RecordedInput::MovementPhase { delta });
+        self.world.resource_mut::<TickDelta>().0 = delta;
+        self.world.run_schedule(LoadPhase);
+        self.world.run_schedule(MovementPhase);
+        self.world.run_schedule(WriteBackPhase);
+        warehouse.record_tick_end();
+        self.take_events()
+    }
+
+    /// Animation step of the split tick: sprites follow the behavioral states
+    /// on the host side, so this only fires due timers (despawns of finished
+    /// death animations, transient state clears)
+    /// Death positions go to the warehouse queue (`pop_death_position`)
+    pub fn run_animation_phase(&mut self) {
+        let warehouse = self.warehouse().clone();
+        warehouse.record_tick_start(RecordedInput::AnimationPhase);
+        self.world.run_schedule(CleanupPhase);
+        warehouse.record_tick_end();
+
+        let death_positions =
+            std::mem::take(&mut self.world.resource_mut::<TickOutput>().death_positions);
+        for position in death_positions {
+            warehouse.death_position_queue.push(position);
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<CombatEvent> {
+        std::mem::take(&mut self.world.resource_mut::<TickOutput>().events)
+    }
+}
+
+// ============================================================================
+// NPC TABLE
+// ============================================================================
+
+/// Components a phase system works on: identity and type read-only, the
+/// live state mutable
+type NpcLiveView<'a> = (
+    &'a NpcId,
+    &'a StaticFlags,
+    &'a FactionMember,
+    &'a AttackRange,
+    Has<InCombat>,
+    &'a mut Health,
+    &'a mut Position,
+    &'a mut Behavior,
+    &'a mut AttackCooldown,
+);
+
+/// One NPC in an NpcTable
+struct LiveNpc<'w> {
+    ulid: [u8; 16],
+    static_state: NPCStaticState,
+    faction: FactionId,
+    attack_range: f32,
+    in_combat: bool,
+    health: Mut<'w, Health>,
+    position: Mut<'w, Position>,
+    behavior: Mut<'w, Behavior>,
+    cooldown: Mut<'w, AttackCooldown>,
+}
+
+/// Every mirrored NPC's live state for one system run, sorted by ULID (query
+/// order follows entity layout - sorting keeps seeded runs identical)
+/// Writes go straight to the components, and only when the value changes
+pub(crate) struct NpcTable<'w> {
+    npcs: Vec<LiveNpc<'w>>,
+}
+
+impl<'w> NpcTable<'w> {
+    fn new(query: &'w mut Query<NpcLiveView>) -> Self {
+        let mut npcs: Vec<LiveNpc<'w>> = query
+            .iter_mut()
+            .map(
+                |(id, flags, member, range, in_combat, health, position, behavior, cooldown)| {
+                    LiveNpc {
+                        ulid: id.0,
+                        static_state: flags.0,
+                        faction: member.0,
+                        attack_range: range.0,
+                        in_combat,
+                        health,
+                        position,
+                        behavior,
+                        cooldown,
+                    }
+                },
+            )
+            .collect();
+        npcs.sort_unstable_by_key(|npc| npc.ulid);
+        Self { npcs }
+    }
+
+    fn get(&self, ulid: &[u8; 16]) -> Option<&LiveNpc<'w>> {
+        let index = self.npcs.binary_search_by_key(ulid, |npc| npc.ulid).ok()?;
+        Some(&self.npcs[index])
+    }
+
+    fn get_mut(&mut self, ulid: &[u8; 16]) -> Option<&mut LiveNpc<'w>> {
+        let index = self.npcs.binary_search_by_key(ulid, |npc| npc.ulid).ok()?;
+        Some(&mut self.npcs[index])
+    }
+
+    /// Rows of the living NPCs registered for combat as they are now, sorted
+    /// by ULID (a phase's snapshot for its spatial grid and pairing)
+    pub fn rows(&self) -> Vec<NpcRow> {
+        self.npcs
+            .iter()
+            .filter(|npc| npc.in_combat && !npc.behavior.0.contains(NPCState::DEAD))
+            .map(|npc| NpcRow {
+                ulid: npc.ulid,
+                pos: npc.position.0,
+                static_state: npc.static_state,
+                faction: npc.faction,
+                state: npc.behavior.0,
+                attack_range: npc.attack_range,
+            })
+            .collect()
+    }
+}
+
+impl LiveState for NpcTable<'_> {
+    fn health(&self, ulid: &[u8; 16]) -> Option<Health> {
+        self.get(ulid).map(|npc| *npc.health)
+    }
+
+    fn set_hp(&mut self, ulid: &[u8; 16], hp: f32) {
+        if let Some(npc) = self.get_mut(ulid) {
+            let max_hp = npc.health.max_hp;
+            npc.health.set_if_neq(Health { hp, max_hp });
+        }
+    }
+
+    fn position(&self, ulid: &[u8; 16]) -> Option<Vec2> {
+        self.get(ulid).map(|npc| npc.position.0)
+    }
+
+    fn set_position(&mut self, ulid: &[u8; 16], pos: Vec2) {
+        if let Some(npc) = self.get_mut(ulid) {
+            npc.position.set_if_neq(Position(pos));
+        }
+    }
+
+    fn behavior(&self, ulid: &[u8; 16]) -> Option<NPCState> {
+        self.get(ulid).map(|npc| npc.behavior.0)
+    }
+
+    fn set_behavior(&mut self, ulid: &[u8; 16], state: NPCState) {
+        if let Some(npc) = self.get_mut(ulid) {
+            npc.behavior.set_if_neq(Behavior(state));
+        }
+    }
+
+    fn last_attack_ms(&self, ulid: &[u8; 16]) -> Option<u64> {
+        self.get(ulid).and_then(|npc| npc.cooldown.0)
+    }
+
+    fn set_last_attack_ms(&mut self, ulid: &[u8; 16], now_ms: u64) {
+        if let Some(npc) = self.get_mut(ulid) {
+            npc.cooldown.set_if_neq(AttackCooldown(Some(now_ms)));
+        }
+    }
+}
+
+// ============================================================================
+// SYSTEMS
+// ============================================================================
+
+/// Components the sync writes (plus whether the entity is marked InCombat)
+type NpcSyncView<'a> = (
+    &'a mut Position,
+    &'a mut StaticFlags,
+    &'a mut FactionMember,
+    &'a mut Behavior,
+    &'a mut Health,
+    &'a mut AttackCooldown,
+    &'a mut AttackRange,
+    Has<InCombat>,
+);
+
+/// Mirror the warehouse's active NPCs into entities and load their live state
+fn load_npc_entities(
+    mut commands: Commands,
+    warehouse: Res<SimWarehouse>,
+    mut entities: ResMut<NpcEntities>,
+    mut npcs: Query<NpcSyncView>,
+) {
+    sync_npc_entities(&mut commands, &warehouse.0, &mut entities, &mut npcs, true);
+}
+
+/// Give NPCs spawned since the load their entities, leaving the others' live
+/// state (changed by the combat phase, not yet written back) alone
+fn adopt_new_npcs(
+    mut commands: Commands,
+    warehouse: Res<SimWarehouse>,
+    mut entities: ResMut<NpcEntities>,
+    mut npcs: Query<NpcSyncView>,
+) {
+    sync_npc_entities(&mut commands, &warehouse.0, &mut entities, &mut npcs, false);
+}
+
+/// Spawn entities for new NPCs and, with `reload`, despawn the ones for NPCs
+/// that left and reload everyone else's components (only writing components
+/// whose value changed)
+fn sync_npc_entities(
+    commands: &mut Commands,
+    warehouse: &NPCDataWarehouse,
+    entities: &mut NpcEntities,
+    npcs: &mut Query<NpcSyncView>,
+    reload: bool,
+) {
+    // Pooled NPCs plus any registered for combat directly by GDScript
+    let mut ulids: Vec<[u8; 16]> = warehouse.active_npcs.iter().map(|e| *e.key()).collect();
+    ulids.extend(
+        warehouse
+            .active_combat_npcs
+            .iter()
+            .map(|e| *e.key())
+            .filter(|ulid| !warehouse.active_npcs.contains_key(ulid)),
+    );
+
+    let mut seen = HashSet::with_capacity(ulids.len());
+    for ulid in ulids {
+        let known = entities.0.get(&ulid).copied();
+        if known.is_some() && !reload {
+            continue;
+        }
+        let in_combat = warehouse.active_combat_npcs.contains_key(&ulid);
+
+        let Some(pos) = warehouse.get_npc_position_internal(&ulid) else {
+            if in_combat {
+                let ulid_hex = bytes_to_hex(&ulid);
+                warehouse.log_error_once(
+                    "missing_position",
+                    &ulid_hex,
+                    &format!(
+                        "[COMBAT ERROR] NPC {} has no position - skipping from combat",
+                        &ulid_hex[0..8]
+                    ),
+                );
+            }
+            continue;
+        };
+        let Some(stats) = warehouse.get_combat_stats(&ulid) else {
+            if in_combat {
+                let ulid_hex = bytes_to_hex(&ulid);
+                warehouse.log_error_once(
+                    "missing_stats",
+                    &ulid_hex,
+                    &format!(
+                        "[COMBAT ERROR] NPC {} has no combat stats - skipping from combat",
+                        &ulid_hex[0..8]
+                    ),
+                );
+            }
+            continue;
+        };
+        let static_flags = stats.static_flags();
+        let faction = warehouse
+            .npc_factions
+            .get(&ulid)
+            .map_or_else(|| FactionId::from_static(static_flags), |faction| *faction);
+        let state = warehouse
+            .get_behavioral_state(&ulid)
+            .unwrap_or(NPCState::empty());
+        let health = Health {
+            hp: stats.hp,
+            max_hp: stats.max_hp,
+        };
+        let cooldown = AttackCooldown(warehouse.npc_cooldown.get(&ulid).map(|v| *v.value()));
+        seen.insert(ulid);
+
+        match known {
+            Some(entity) => {
+                if let Ok((
+                    mut position,
+                    mut flags,
+                    mut member,
+                    mut behavior,
+                    mut hp,
+                    mut last_attack,
+                    mut range,
+                    has_combat,
+                )) = npcs.get_mut(entity)
+                {
+                    position.set_if_neq(Position(pos));
+                    flags.set_if_neq(StaticFlags(static_flags));
+                    member.set_if_neq(FactionMember(faction));
+                    behavior.set_if_neq(Behavior(state));
+                    hp.set_if_neq(health);
+                    last_attack.set_if_neq(cooldown);
+                    range.set_if_neq(AttackRange(stats.attack_range));
+                    if in_combat && !has_combat {
+                        commands.entity(entity).insert(InCombat);
+                    } else if !in_combat && has_combat {
+                        commands.entity(entity).remove::<InCombat>();
+                    }
+                }
+            }
+            None => {
+                let mut entity = commands.spawn((
+                    NpcId(ulid),
+                    Position(pos),
+                    StaticFlags(static_flags),
+                    FactionMember(faction),
+                    Behavior(state),
+                    health,
+                    cooldown,
+                    AttackRange(stats.attack_range),
+                ));
+                if in_combat {
+                    entity.insert(InCombat);
+                }
+                entities.0.insert(ulid, entity.id());
+            }
+        }
+    }
+
+    // Drop entities for NPCs that left the warehouse
+    if reload {
+        entities.0.retain(|ulid, entity| {
+            let keep = seen.contains(ulid);
+            if !keep {
+                commands.entity(*entity).despawn();
+            }
+            keep
+        });
+    }
+}
+
+/// Copy the live state changed since the last write-back to the warehouse
+fn write_back_npc_state(
+    warehouse: Res<SimWarehouse>,
+    health: Query<(&NpcId, &Health), Changed<Health>>,
+    positions: Query<(&NpcId, &Position), Changed<Position>>,
+    behaviors: Query<(&NpcId, &Behavior), Changed<Behavior>>,
+    cooldowns: Query<(&NpcId, &AttackCooldown), Changed<AttackCooldown>>,
+) {
+    let mut stored = warehouse.0.stored();
+    for (id, health) in &health {
+        stored.set_hp(&id.0, health.hp);
+    }
+    for (id, position) in &positions {
+        stored.set_position(&id.0, position.0);
+    }
+    for (id, behavior) in &behaviors {
+        stored.set_behavior(&id.0, behavior.0);
+    }
+    for (id, cooldown) in &cooldowns {
+        if let Some(last_attack_ms) = cooldown.0 {
+            stored.set_last_attack_ms(&id.0, last_attack_ms);
+        }
+    }
+}
+
+/// ULIDs of the living NPCs registered for combat, sorted
+fn living_ulids<'a>(npcs: impl Iterator<Item = (&'a NpcId, &'a Behavior)>) -> Vec<[u8; 16]> {
+    let mut ulids: Vec<[u8; 16]> = npcs
+        .filter(|(_, behavior)| !behavior.0.contains(NPCState::DEAD))
+        .map(|(id, _)| id.0)
+        .collect();
+    ulids.sort_unstable();
+    ulids
+}
+
+/// Tick status effects before anyone attacks (effects.rs)
+fn status_effect_system(
+    warehouse: Res<SimWarehouse>,
+    mut npcs: Query<NpcLiveView>,
+    mut output: ResMut<TickOutput>,
+) {
+    let now_ms = warehouse.0.get_current_time_ms();
+    let events = warehouse
+        .0
+        .run_status_effects(&mut NpcTable::new(&mut npcs), now_ms);
+    output.events.extend(events);
+}
+
+/// Decay hunger, feed the hungry and starve the starving (hunger.rs)
+fn hunger_system(
+    warehouse: Res<SimWarehouse>,
+    mut npcs: Query<NpcLiveView>,
+    mut output: ResMut<TickOutput>,
+) {
+    let events = warehouse.0.run_hunger(&mut NpcTable::new(&mut npcs));
+    output.events.extend(events);
+}
+
+/// PHASE 1: COMBAT over living NPCs registered for combat
+fn combat_system(
+    warehouse: Res<SimWarehouse>,
+    mut npcs: Query<NpcLiveView>,
+    mut output: ResMut<TickOutput>,
+) {
+    let events = warehouse.0.run_combat_phase(&mut NpcTable::new(&mut npcs));
+    output.events.extend(events);
+}
+
+/// Healers start channels and land finished heals after the hits (healing.rs)
+fn healing_system(
+    warehouse: Res<SimWarehouse>,
+    mut npcs: Query<NpcLiveView>,
+    mut output: ResMut<TickOutput>,
+) {
+    let events = warehouse.0.run_healers(&mut NpcTable::new(&mut npcs));
+    output.events.extend(events);
+}
+
+/// Let emotions fade and drift with hunger and energy once the tick's hits
+/// and heals have stirred them (emotions.rs)
+fn emotion_system(warehouse: Res<SimWarehouse>, npcs: Query<(&NpcId, &Behavior), With<InCombat>>) {
+    warehouse.0.run_emotions(&living_ulids(npcs.iter()));
+}
+
+/// Let threat fade once the tick's hits and heals have added to it (threat.rs)
+fn threat_system(warehouse: Res<SimWarehouse>, npcs: Query<(&NpcId, &Behavior), With<InCombat>>) {
+    warehouse.0.run_threat(&living_ulids(npcs.iter()));
+}
+
+/// Regenerate HP, mana and energy within the tick's budget, ready for the
+/// next tick's attacks (regen.rs)
+fn regen_system(
+    warehouse: Res<SimWarehouse>,
+    mut npcs: Query<(&NpcId, &Behavior, &mut Health), With<InCombat>>,
+) {
+    let mut npcs: Vec<([u8; 16], NPCState, Mut<Health>)> = npcs
+        .iter_mut()
+        .map(|(id, behavior, health)| (id.0, behavior.0, health))
+        .collect();
+    npcs.sort_unstable_by_key(|(ulid, _, _)| *ulid);
+    warehouse.0.run_regen(&mut npcs);
+}
+
+/// PHASE 2: MOVEMENT over living NPCs registered for combat
+fn movement_system(
+    warehouse: Res<SimWarehouse>,
+    delta: Res<TickDelta>,
+    mut npcs: Query<NpcLiveView>,
+) {
+    warehouse
+        .0
+        .run_movement_phase(&mut NpcTable::new(&mut npcs), delta.0);
+}
+
+/// PHASE 3: CLEANUP - fire due timers (dead NPCs whose death animation has
+/// played are despawned here), then shrink pools that sat idle too long
+fn cleanup_system(warehouse: Res<SimWarehouse>, mut output: ResMut<TickOutput>) {
+    let now_ms = warehouse.0.get_current_time_ms();
+    let death_positions = warehouse.0.run_due_timers(now_ms);
+    output.death_positions.extend(death_positions);
+    warehouse.0.shrink_idle_pools(now_ms);
+}
+
+/// Run condition: at least one living NPC is registered for combat
+fn any_living_combat_npc(npcs: Query<&Behavior, With<InCombat>>) -> bool {
+    npcs.iter()
+        .any(|behavior| !behavior.0.contains(NPCState::DEAD))
+}
+
+/// Initial spawn on the first tick (before movement)
+fn initial_spawn_system(warehouse: Res<SimWarehouse>, mut output: ResMut<TickOutput>) {
+    let now_ms = warehouse.0.get_current_time_ms();
+    let events = warehouse.0.check_initial_spawn(now_ms);
+    output.events.extend(events);
+}
+
+/// Monster waves (after movement)
+fn wave_spawn_system(warehouse: Res<SimWarehouse>, mut output: ResMut<TickOutput>) {
+    let now_ms = warehouse.0.get_current_time_ms();
+    let events = warehouse.0.check_spawn_wave(now_ms);
+    output.events.extend(events);
+}
+
+/// Gradual ally ramp-up (after monster waves)
+fn ally_spawn_system(warehouse: Res<SimWarehouse>, mut output: ResMut<TickOutput>) {
+    let now_ms = warehouse.0.get_current_time_ms();
+    let events = warehouse.0.check_ally_spawn(now_ms);
+    output.events.extend(events);
+}
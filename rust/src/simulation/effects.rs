@@ -0,0 +1,541 @@
+// ============================================================================
+// STATUS EFFECTS - Timed effects on NPCs (poison, burn, slow, stun, regen)
+// ============================================================================
+// An effect definition says how long the effect lasts, how often it ticks,
+// how repeated applications stack and what it does while active:
+//   - damage_per_tick / heal_per_tick  HP change on each tick (per stack,
+//                                      damage reduced by the target's resistance)
+//   - modifiers                        attack / defense / move speed multipliers
+//                                      (per stack, used by the damage pipeline and
+//                                      waypoint movement, on top of the hunger
+//                                      penalties from hunger.rs and the emotion
+//                                      multipliers from emotions.rs)
+//   - stun                             the NPC neither attacks nor moves
+//
+// Active effects are stored per ULID with absolute simulation times, and are
+// ticked at the start of the combat phase (NPCs in ULID order, effects in the
+// order they were applied) so seeded runs stay deterministic. Effects end with
+// the NPC: death, despawn and unregistering drop them without an event.
+//
+// Applying, ticking and expiring (running out or being removed) each queue a
+// StatusEffectEvent. A tick that kills its target also produces a Death combat
+// event credited to the effect's source, and ticks give their source threat
+// (see threat.rs).
+
+use serde::{Deserialize, Serialize};
+
+use super::damage::DamageType;
+use super::ecs::NpcTable;
+use super::events::CombatEvent;
+use super::live::LiveState;
+use super::log::sim_print;
+use super::replay::RecordedInput;
+use super::stats::{bytes_to_hex, NPCState};
+use super::threat::DAMAGE_THREAT;
+use super::warehouse::NPCDataWarehouse;
+
+/// What applying an effect to an NPC that already has it does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stacking {
+    /// Restart the duration (stacks stay as they are)
+    #[default]
+    Refresh,
+    /// Add a stack (up to max_stacks) and restart the duration
+    Stack,
+    /// Keep the running effect untouched
+    Ignore,
+}
+
+impl Stacking {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stacking::Refresh => "refresh",
+            Stacking::Stack => "stack",
+            Stacking::Ignore => "ignore",
+        }
+    }
+}
+
+/// Stat multipliers while an effect is active (1 = unchanged)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatModifiers {
+    pub attack: f32,
+    pub defense: f32,
+    pub move_speed: f32,
+}
+
+impl Default for StatModifiers {
+    fn default() -> Self {
+        Self {
+            attack: 1.0,
+            defense: 1.0,
+            move_speed: 1.0,
+        }
+    }
+}
+
+impl StatModifiers {
+    /// Combine with another set of multipliers, `stacks` times
+    fn stack(&mut self, other: &StatModifiers, stacks: u32) {
+        let stacks = stacks as i32;
+        self.attack *= other.attack.powi(stacks);
+        self.defense *= other.defense.powi(stacks);
+        self.move_speed *= other.move_speed.powi(stacks);
+    }
+}
+
+fn default_max_stacks() -> u32 {
+    1
+}
+
+/// One kind of status effect
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffectDef {
+    pub name: String,
+    /// How long the effect lasts (0 = until removed)
+    pub duration_ms: u64,
+    /// Time between ticks (0 = never ticks)
+    #[serde(default)]
+    pub tick_interval_ms: u64,
+    #[serde(default)]
+    pub stacking: Stacking,
+    #[serde(default = "default_max_stacks")]
+    pub max_stacks: u32,
+    /// Damage dealt on each tick, per stack
+    #[serde(default)]
+    pub damage_per_tick: f32,
+    /// Damage type of the ticks (the target's resistance applies)
+    #[serde(default)]
+    pub damage_type: DamageType,
+    /// HP restored on each tick, per stack
+    #[serde(default)]
+    pub heal_per_tick: f32,
+    /// Stat multipliers, per stack
+    #[serde(default)]
+    pub modifiers: StatModifiers,
+    /// Stunned NPCs neither attack nor move
+    #[serde(default)]
+    pub stun: bool,
+}
+
+impl StatusEffectDef {
+    /// Effect that only lasts (no ticks, modifiers or stun)
+    fn timed(name: &str, duration_ms: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            duration_ms,
+            tick_interval_ms: 0,
+            stacking: Stacking::Refresh,
+            max_stacks: 1,
+            damage_per_tick: 0.0,
+            damage_type: DamageType::Physical,
+            heal_per_tick: 0.0,
+            modifiers: StatModifiers::default(),
+            stun: false,
+        }
+    }
+
+    /// Built-in effects: poison, burn, slow, stun, regen
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self {
+                tick_interval_ms: 1000,
+                stacking: Stacking::Stack,
+                max_stacks: 5,
+                damage_per_tick: 2.0,
+                damage_type: DamageType::Pierce,
+                ..Self::timed("poison", 6000)
+            },
+            Self {
+                tick_interval_ms: 500,
+                stacking: Stacking::Stack,
+                max_stacks: 3,
+                damage_per_tick: 1.5,
+                damage_type: DamageType::Magic,
+                ..Self::timed("burn", 3000)
+            },
+            Self {
+                modifiers: StatModifiers {
+                    move_speed: 0.5,
+                    ..StatModifiers::default()
+                },
+                ..Self::timed("slow", 4000)
+            },
+            Self {
+                stacking: Stacking::Ignore,
+                stun: true,
+                ..Self::timed("stun", 1500)
+            },
+            Self {
+                tick_interval_ms: 1000,
+                heal_per_tick: 3.0,
+                ..Self::timed("regen", 10000)
+            },
+        ]
+    }
+
+    /// Check the definition is usable
+    pub fn validate(&self) -> Result<(), String> {
+        // Names appear in recordings
+        if self.name.is_empty() || self.name.chars().any(char::is_whitespace) {
+            return Err(format!(
+                "invalid status effect name '{}' (use a single word)",
+                self.name
+            ));
+        }
+        if self.max_stacks == 0 {
+            return Err(format!("'{}': max_stacks must be >= 1", self.name));
+        }
+        for (field, value) in [
+            ("damage_per_tick", self.damage_per_tick),
+            ("heal_per_tick", self.heal_per_tick),
+            ("attack modifier", self.modifiers.attack),
+            ("defense modifier", self.modifiers.defense),
+            ("move_speed modifier", self.modifiers.move_speed),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!(
+                    "'{}': {} must be >= 0 (got {})",
+                    self.name, field, value
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An effect running on an NPC
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveEffect {
+    pub name: String,
+    pub stacks: u32,
+    /// NPC that applied the effect (credited with kills)
+    pub source: Option<[u8; 16]>,
+    /// Simulation time the effect ends (None = until removed)
+    pub expires_ms: Option<u64>,
+    /// Simulation time of the next tick (None = never ticks)
+    pub next_tick_ms: Option<u64>,
+}
+
+/// A status effect change the host may want to react to
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatusEffectEvent {
+    /// An effect was applied (or refreshed / stacked)
+    Applied {
+        ulid: [u8; 16],
+        effect: String,
+        stacks: u32,
+    },
+    /// An effect ticked - hp_change is negative for damage
+    Ticked {
+        ulid: [u8; 16],
+        effect: String,
+        hp_change: f32,
+        hp: f32,
+    },
+    /// An effect ran out or was removed
+    Expired { ulid: [u8; 16], effect: String },
+}
+
+/// One due tick, applied once the NPC's effect list is released
+struct DueTick {
+    effect: String,
+    source: Option<[u8; 16]>,
+    damage: f32,
+    damage_type: DamageType,
+    heal: f32,
+}
+
+impl NPCDataWarehouse {
+    /// Register (or replace) a status effect definition
+    pub fn register_status_effect(&self, def: StatusEffectDef) -> Result<(), String> {
+        def.validate()?;
+        sim_print!(
+            "NPCDataWarehouse: Registered status effect '{}' ({}ms, {})",
+            def.name,
+            def.duration_ms,
+            def.stacking.as_str()
+        );
+        self.record_input(RecordedInput::RegisterStatusEffect { def: def.clone() });
+        self.status_effect_defs.insert(def.name.clone(), def);
+        Ok(())
+    }
+
+    /// Register (or replace) a status effect from its JSON object
+    pub fn register_status_effect_json(&self, json: &str) -> Result<(), String> {
+        let def: StatusEffectDef =
+            serde_json::from_str(json).map_err(|e| format!("invalid status effect JSON: {}", e))?;
+        self.register_status_effect(def)
+    }
+
+    /// A status effect definition by name
+    pub fn status_effect_def(&self, name: &str) -> Option<StatusEffectDef> {
+        self.status_effect_defs.get(name).map(|def| def.clone())
+    }
+
+    /// Every status effect definition, sorted by name
+    pub fn status_effect_defs(&self) -> Vec<StatusEffectDef> {
+        let mut defs: Vec<StatusEffectDef> = self
+            .status_effect_defs
+            .iter()
+            .map(|def| def.value().clone())
+            .collect();
+        defs.sort_by(|a, b| a.name.cmp(&b.name));
+        defs
+    }
+
+    /// Apply an effect to a living NPC, following the effect's stacking rule
+    /// Returns the effect's stack count afterwards
+    pub fn apply_status_effect(
+        &self,
+        ulid: &[u8; 16],
+        effect: &str,
+        source: Option<[u8; 16]>,
+    ) -> Result<u32, String> {
+        let def = self
+            .status_effect_def(effect)
+            .ok_or_else(|| format!("unknown status effect '{}'", effect))?;
+        let alive = self
+            .get_combat_stats(ulid)
+            .is_some_and(|stats| stats.hp > 0.0)
+            && !self
+                .get_behavioral_state(ulid)
+                .is_some_and(|state| state.contains(NPCState::DEAD));
+        if !alive {
+            return Err(format!("NPC {} is gone or dead", &bytes_to_hex(ulid)[..16]));
+        }
+        self.record_input(RecordedInput::StatusEffect {
+            ulid: *ulid,
+            effect: effect.to_string(),
+            source,
+        });
+
+        let now_ms = self.get_current_time_ms();
+        let expires_ms = (def.duration_ms > 0).then(|| now_ms + def.duration_ms);
+        let mut effects = self.npc_effects.entry(*ulid).or_default();
+        let stacks = match effects.iter_mut().find(|active| active.name == effect) {
+            Some(active) => {
+                if def.stacking == Stacking::Ignore {
+                    return Ok(active.stacks);
+                }
+                if def.stacking == Stacking::Stack {
+                    active.stacks = (active.stacks + 1).min(def.max_stacks);
+                }
+                active.expires_ms = expires_ms;
+                active.source = source.or(active.source);
+                active.stacks
+            }
+            None => {
+                effects.push(ActiveEffect {
+                    name: effect.to_string(),
+                    stacks: 1,
+                    source,
+                    expires_ms,
+                    next_tick_ms: (def.tick_interval_ms > 0).then(|| now_ms + def.tick_interval_ms),
+                });
+                1
+            }
+        };
+        drop(effects);
+
+        self.status_events.push(StatusEffectEvent::Applied {
+            ulid: *ulid,
+            effect: effect.to_string(),
+            stacks,
+        });
+        Ok(stacks)
+    }
+
+    /// Remove an effect from an NPC - false if it didn't have it
+    pub fn remove_status_effect(&self, ulid: &[u8; 16], effect: &str) -> bool {
+        self.record_input(RecordedInput::RemoveStatusEffect {
+            ulid: *ulid,
+            effect: effect.to_string(),
+        });
+        let removed = match self.npc_effects.get_mut(ulid) {
+            Some(mut effects) => {
+                let before = effects.len();
+                effects.retain(|active| active.name != effect);
+                effects.len() != before
+            }
+            None => false,
+        };
+        self.npc_effects
+            .remove_if(ulid, |_, effects| effects.is_empty());
+        if removed {
+            self.status_events.push(StatusEffectEvent::Expired {
+                ulid: *ulid,
+                effect: effect.to_string(),
+            });
+        }
+        removed
+    }
+
+    /// Effects running on an NPC, in the order they were applied
+    pub fn npc_status_effects(&self, ulid: &[u8; 16]) -> Vec<ActiveEffect> {
+        self.npc_effects
+            .get(ulid)
+            .map(|effects| effects.clone())
+            .unwrap_or_default()
+    }
+
+    /// Combined stat multipliers of an NPC's effects, hunger level and emotion
+    pub fn status_modifiers(&self, ulid: &[u8; 16]) -> StatModifiers {
+        let mut modifiers = self.hunger_modifiers(ulid);
+        modifiers.stack(&self.emotion_modifiers(ulid), 1);
+        if let Some(effects) = self.npc_effects.get(ulid) {
+            for active in effects.iter() {
+                if let Some(def) = self.status_effect_defs.get(&active.name) {
+                    modifiers.stack(&def.modifiers, active.stacks);
+                }
+            }
+        }
+        modifiers
+    }
+
+    /// Whether any of an NPC's effects stuns it
+    pub fn is_stunned(&self, ulid: &[u8; 16]) -> bool {
+        self.npc_effects.get(ulid).is_some_and(|effects| {
+            effects.iter().any(|active| {
+                self.status_effect_defs
+                    .get(&active.name)
+                    .is_some_and(|def| def.stun)
+            })
+        })
+    }
+
+    /// Pop the next status effect event (None when the queue is empty)
+    pub fn pop_status_event(&self) -> Option<StatusEffectEvent> {
+        self.status_events.pop()
+    }
+
+    /// Drop an NPC's effects without events (death, despawn)
+    pub(super) fn clear_status_effects(&self, ulid: &[u8; 16]) {
+        self.npc_effects.remove(ulid);
+    }
+
+    /// Run the ticks due by `now_ms` and expire finished effects
+    /// Returns Death events for NPCs killed by a tick
+    pub(super) fn run_status_effects(&self, npcs: &mut NpcTable, now_ms: u64) -> Vec<CombatEvent> {
+        let mut events = Vec::new();
+        let mut ulids: Vec<[u8; 16]> = self.npc_effects.iter().map(|entry| *entry.key()).collect();
+        ulids.sort_unstable();
+
+        for ulid in ulids {
+            let (ticks, expired) = {
+                let Some(mut effects) = self.npc_effects.get_mut(&ulid) else {
+                    continue;
+                };
+                let mut ticks = Vec::new();
+                for active in effects.iter_mut() {
+                    let Some(def) = self.status_effect_defs.get(&active.name) else {
+                        // Definition is gone - let the effect run out untouched
+                        continue;
+                    };
+                    while let Some(tick_ms) = active.next_tick_ms {
+                        if tick_ms > now_ms || active.expires_ms.is_some_and(|end| tick_ms > end) {
+                            break;
+                        }
+                        let stacks = active.stacks as f32;
+                        ticks.push(DueTick {
+                            effect: active.name.clone(),
+                            source: active.source,
+                            damage: def.damage_per_tick * stacks,
+                            damage_type: def.damage_type,
+                            heal: def.heal_per_tick * stacks,
+                        });
+                        active.next_tick_ms = Some(tick_ms + def.tick_interval_ms);
+                    }
+                }
+                let mut expired = Vec::new();
+                effects.retain(|active| {
+                    let running = active.expires_ms.is_none_or(|end| end > now_ms);
+                    if !running {
+                        expired.push(active.name.clone());
+                    }
+                    running
+                });
+                (ticks, expired)
+            };
+            self.npc_effects
+                .remove_if(&ulid, |_, effects| effects.is_empty());
+
+            let mut died = false;
+            for tick in ticks {
+                if let Some(death) = self.apply_status_tick(npcs, &ulid, tick) {
+                    events.push(death);
+                    died = true;
+                    break;
+                }
+            }
+            // Effects end silently with the NPC
+            if !died {
+                for effect in expired {
+                    self.status_events
+                        .push(StatusEffectEvent::Expired { ulid, effect });
+                }
+            }
+        }
+
+        events
+    }
+
+    /// Apply one tick's damage and healing - returns a Death event if it killed
+    fn apply_status_tick(
+        &self,
+        npcs: &mut NpcTable,
+        ulid: &[u8; 16],
+        tick: DueTick,
+    ) -> Option<CombatEvent> {
+        let stats = self.get_combat_stats(ulid)?;
+        let hp_before = npcs.health(ulid)?.hp;
+        if hp_before <= 0.0 {
+            return None;
+        }
+        let damage = tick.damage * (1.0 - stats.resistances.get(tick.damage_type));
+        let mut hp = hp_before;
+        if damage > 0.0 {
+            hp = self.apply_damage(npcs, ulid, damage);
+            if let Some(source) = tick.source.filter(|_| hp > 0.0) {
+                self.add_threat(ulid, &source, damage * DAMAGE_THREAT);
+            }
+        }
+        if tick.heal > 0.0 && hp > 0.0 {
+            let before = hp;
+            hp = self.heal_npc(npcs, ulid, tick.heal, 0.0, 0.0);
+            if let Some(source) = tick.source {
+                self.add_heal_threat(&source, ulid, hp - before);
+            }
+        }
+        self.status_events.push(StatusEffectEvent::Ticked {
+            ulid: *ulid,
+            effect: tick.effect.clone(),
+            hp_change: hp - hp_before,
+            hp,
+        });
+        if hp > 0.0 {
+            return None;
+        }
+
+        sim_print!(
+            "[STATUS] NPC {} killed by {}",
+            &bytes_to_hex(ulid)[..8],
+            tick.effect
+        );
+        self.mark_dead(npcs, ulid);
+        let target_pos = npcs.position(ulid).unwrap_or_default();
+        Some(CombatEvent::Death {
+            attacker: tick.source.unwrap_or(*ulid),
+            target: *ulid,
+            amount: damage,
+            target_pos,
+        })
+    }
+
+    /// Restore a saved effect (snapshot restore)
+    pub(super) fn restore_status_effect(&self, ulid: &[u8; 16], effect: ActiveEffect) {
+        self.npc_effects.entry(*ulid).or_default().push(effect);
+    }
+}
@@ -0,0 +1,412 @@
+// ============================================================================
+// EMOTIONS - What NPCs feel, and how it changes what they do
+// ============================================================================
+// Every NPC carries a score (0 - EMOTION_MAX) per emotion. Events raise them:
+//   - taking a hit          anger, plus fear for the share of max HP lost
+//                           (and LOW_HP_FEAR more below LOW_HP_FRACTION)
+//   - a killing blow        happiness for the attacker; it also calms anger
+//                           and fear
+//   - a friendly death      sadness and fear for living friends within
+//                           WITNESS_RADIUS
+//   - being healed          happiness; it also calms fear
+// Every EMOTION_STEP_INTERVAL_MS (living NPCs in combat, ULID order) the
+// scores decay toward 0 by EMOTION_DECAY_PER_SEC, then hunger and energy add
+// their drift: hungry NPCs get angry, starving ones sad, exhausted ones weary.
+//
+// The NPC's emotion is its highest score at or above EMOTION_THRESHOLD
+// (Neutral otherwise, ties go to the lower Emotion id) and is kept in
+// NPCCombatStats.emotional_state as the Emotion id. It drives behavior:
+//   - Frightened  starts no fights and keeps FLEE_DISTANCE from hostiles
+//                 (combat.rs, movement.rs)
+//   - Enraged     attack x ENRAGED_ATTACK_MULTIPLIER
+//   - Happy       move speed x HAPPY_SPEED_MULTIPLIER, attack cooldown divided by it
+//   - Weary       move speed x WEARY_SPEED_MULTIPLIER
+// The multipliers are folded into status_modifiers (see effects.rs).
+//
+// Emotion changes queue an EmotionEvent. Scores end with the NPC (death,
+// despawn, unregistering).
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+
+use super::effects::StatModifiers;
+use super::factions::Relation;
+use super::hunger::HungerLevel;
+use super::live::LiveState;
+use super::log::sim_print;
+use super::stats::{bytes_to_hex, NPCCombatStats};
+use super::warehouse::NPCDataWarehouse;
+
+/// Shortest simulation time between two emotion steps
+pub const EMOTION_STEP_INTERVAL_MS: u64 = 500;
+
+/// Highest score an emotion can reach
+pub const EMOTION_MAX: f32 = 100.0;
+
+/// Score an emotion needs to take over
+pub const EMOTION_THRESHOLD: f32 = 40.0;
+
+/// Score every emotion loses per second
+pub const EMOTION_DECAY_PER_SEC: f32 = 2.0;
+
+/// How far away a friendly death is felt (pixels)
+pub const WITNESS_RADIUS: f32 = 250.0;
+
+/// Share of max HP below which every hit adds LOW_HP_FEAR
+pub const LOW_HP_FRACTION: f32 = 0.3;
+
+/// Distance frightened NPCs keep from the nearest hostile
+pub const FLEE_DISTANCE: f32 = 250.0;
+
+/// Attack multiplier while enraged
+pub const ENRAGED_ATTACK_MULTIPLIER: f32 = 1.25;
+
+/// Move speed (and attack rate) multiplier while happy
+pub const HAPPY_SPEED_MULTIPLIER: f32 = 1.15;
+
+/// Move speed multiplier while weary
+pub const WEARY_SPEED_MULTIPLIER: f32 = 0.8;
+
+// Score changes per event (and per second for the drifts)
+const HIT_ANGER: f32 = 12.0;
+const HIT_FEAR_PER_HP_SHARE: f32 = 100.0;
+const LOW_HP_FEAR: f32 = 20.0;
+const KILL_HAPPINESS: f32 = 45.0;
+const KILL_CALM: f32 = 20.0;
+const WITNESS_SADNESS: f32 = 45.0;
+const WITNESS_FEAR: f32 = 10.0;
+const HEALED_HAPPINESS: f32 = 8.0;
+const HEALED_CALM: f32 = 10.0;
+const HUNGRY_ANGER_PER_SEC: f32 = 3.0;
+const STARVING_SADNESS_PER_SEC: f32 = 8.0;
+const EXHAUSTED_WEARINESS_PER_SEC: f32 = 8.0;
+/// Share of max energy below which an NPC grows weary
+const EXHAUSTED_ENERGY_FRACTION: f32 = 0.2;
+
+/// What an NPC feels (ids match NPCCombatStats.emotional_state)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Emotion {
+    #[default]
+    Neutral = 0,
+    Happy = 1,
+    Enraged = 2,
+    Frightened = 3,
+    Sad = 4,
+    Weary = 5,
+}
+
+impl Emotion {
+    /// Emotion for an emotional_state id (unknown ids are Neutral)
+    pub fn from_id(id: i32) -> Self {
+        match id {
+            1 => Emotion::Happy,
+            2 => Emotion::Enraged,
+            3 => Emotion::Frightened,
+            4 => Emotion::Sad,
+            5 => Emotion::Weary,
+            _ => Emotion::Neutral,
+        }
+    }
+
+    pub fn id(&self) -> i32 {
+        *self as i32
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Emotion::Neutral => "neutral",
+            Emotion::Happy => "happy",
+            Emotion::Enraged => "enraged",
+            Emotion::Frightened => "frightened",
+            Emotion::Sad => "sad",
+            Emotion::Weary => "weary",
+        }
+    }
+
+    /// Stat multipliers while feeling this
+    pub fn modifiers(&self) -> StatModifiers {
+        match self {
+            Emotion::Enraged => StatModifiers {
+                attack: ENRAGED_ATTACK_MULTIPLIER,
+                ..StatModifiers::default()
+            },
+            Emotion::Happy => StatModifiers {
+                move_speed: HAPPY_SPEED_MULTIPLIER,
+                ..StatModifiers::default()
+            },
+            Emotion::Weary => StatModifiers {
+                move_speed: WEARY_SPEED_MULTIPLIER,
+                ..StatModifiers::default()
+            },
+            _ => StatModifiers::default(),
+        }
+    }
+
+    /// Attack cooldown while feeling this (happy NPCs act faster)
+    pub fn attack_cooldown_ms(&self, base_ms: u64) -> u64 {
+        match self {
+            Emotion::Happy => (base_ms as f32 / HAPPY_SPEED_MULTIPLIER) as u64,
+            _ => base_ms,
+        }
+    }
+}
+
+/// Score per emotion (0 - EMOTION_MAX)
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmotionScores {
+    pub happiness: f32,
+    pub anger: f32,
+    pub fear: f32,
+    pub sadness: f32,
+    pub weariness: f32,
+}
+
+impl EmotionScores {
+    /// Strongest emotion at or above EMOTION_THRESHOLD (Neutral if none)
+    pub fn dominant(&self) -> Emotion {
+        let mut best = (Emotion::Neutral, EMOTION_THRESHOLD);
+        for (emotion, score) in [
+            (Emotion::Happy, self.happiness),
+            (Emotion::Enraged, self.anger),
+            (Emotion::Frightened, self.fear),
+            (Emotion::Sad, self.sadness),
+            (Emotion::Weary, self.weariness),
+        ] {
+            if score > best.1 || (score >= EMOTION_THRESHOLD && best.0 == Emotion::Neutral) {
+                best = (emotion, score);
+            }
+        }
+        best.0
+    }
+
+    /// Every score back at 0
+    pub fn is_calm(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn scores_mut(&mut self) -> [&mut f32; 5] {
+        [
+            &mut self.happiness,
+            &mut self.anger,
+            &mut self.fear,
+            &mut self.sadness,
+            &mut self.weariness,
+        ]
+    }
+
+    /// Keep every score within [0, EMOTION_MAX]
+    fn clamp(&mut self) {
+        for score in self.scores_mut() {
+            *score = score.clamp(0.0, EMOTION_MAX);
+        }
+    }
+}
+
+/// An NPC's emotion changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmotionEvent {
+    pub ulid: [u8; 16],
+    pub emotion: Emotion,
+    pub previous: Emotion,
+}
+
+impl NPCDataWarehouse {
+    /// Pop the next emotion change (None when the queue is empty)
+    pub fn pop_emotion_event(&self) -> Option<EmotionEvent> {
+        self.emotion_events.pop()
+    }
+
+    /// What an NPC feels (Neutral if it isn't registered for combat)
+    pub fn npc_emotion(&self, ulid: &[u8; 16]) -> Emotion {
+        self.npc_combat_stats
+            .get(ulid)
+            .map_or(Emotion::Neutral, |stats| {
+                Emotion::from_id(stats.emotional_state)
+            })
+    }
+
+    /// An NPC's score per emotion
+    pub fn npc_emotion_scores(&self, ulid: &[u8; 16]) -> EmotionScores {
+        self.npc_emotions
+            .get(ulid)
+            .map(|scores| *scores)
+            .unwrap_or_default()
+    }
+
+    /// Frightened NPCs run instead of fighting
+    pub(super) fn is_frightened(&self, ulid: &[u8; 16]) -> bool {
+        self.npc_emotion(ulid) == Emotion::Frightened
+    }
+
+    /// Multipliers for an NPC's emotion
+    pub(super) fn emotion_modifiers(&self, ulid: &[u8; 16]) -> StatModifiers {
+        self.npc_emotion(ulid).modifiers()
+    }
+
+    /// A hit landed through the damage pipeline (see damage.rs)
+    pub(super) fn feel_hit(
+        &self,
+        attacker: &[u8; 16],
+        target: &[u8; 16],
+        damage: f32,
+        target_stats: &NPCCombatStats,
+        hp: f32,
+    ) {
+        if hp <= 0.0 {
+            self.stir(attacker, |scores| {
+                scores.happiness += KILL_HAPPINESS;
+                scores.anger -= KILL_CALM;
+                scores.fear -= KILL_CALM;
+            });
+            return;
+        }
+        let mut fear = HIT_FEAR_PER_HP_SHARE * damage / target_stats.max_hp.max(1.0);
+        if hp < target_stats.max_hp * LOW_HP_FRACTION {
+            fear += LOW_HP_FEAR;
+        }
+        self.stir(target, |scores| {
+            scores.anger += HIT_ANGER;
+            scores.fear += fear;
+        });
+    }
+
+    /// An NPC was healed
+    pub(super) fn feel_healed(&self, ulid: &[u8; 16]) {
+        self.stir(ulid, |scores| {
+            scores.happiness += HEALED_HAPPINESS;
+            scores.fear -= HEALED_CALM;
+        });
+    }
+
+    /// An NPC died - its living friends nearby grieve (called from mark_dead)
+    pub(super) fn feel_death(&self, npcs: &impl LiveState, ulid: &[u8; 16]) {
+        let Some(pos) = npcs.position(ulid) else {
+            return;
+        };
+        let Some(faction) = self.npc_faction(ulid) else {
+            return;
+        };
+        let factions = self.faction_table();
+
+        let mut witnesses: Vec<[u8; 16]> = self
+            .active_combat_npcs
+            .iter()
+            .map(|entry| *entry.key())
+            .filter(|witness| {
+                witness != ulid
+                    && self.npc_faction(witness).is_some_and(|other| {
+                        factions.relation(faction, other) == Relation::Friendly
+                    })
+                    && npcs
+                        .position(witness)
+                        .is_some_and(|witness_pos| witness_pos.distance(pos) <= WITNESS_RADIUS)
+            })
+            .collect();
+        witnesses.sort_unstable();
+
+        for witness in witnesses {
+            self.stir(&witness, |scores| {
+                scores.sadness += WITNESS_SADNESS;
+                scores.fear += WITNESS_FEAR;
+            });
+        }
+    }
+
+    /// Drop an NPC's scores without an event (death, despawn)
+    pub(super) fn clear_emotions(&self, ulid: &[u8; 16]) {
+        self.npc_emotions.remove(ulid);
+    }
+
+    /// Put saved scores back (see snapshot.rs)
+    pub(super) fn restore_emotions(&self, ulid: &[u8; 16], scores: EmotionScores) {
+        if !scores.is_calm() {
+            self.npc_emotions.insert(*ulid, scores);
+        }
+    }
+
+    /// Decay every score for the time since the last step and add the
+    /// hunger / energy drift
+    /// `npcs` holds the living NPCs registered for combat, sorted by ULID
+    pub(super) fn run_emotions(&self, npcs: &[[u8; 16]]) {
+        let now_ms = self.get_current_time_ms();
+        let last_ms = self.last_emotion_step_ms.load(Ordering::Relaxed);
+        // First step (or the clock went back) - start counting from now
+        if last_ms == 0 || now_ms < last_ms {
+            self.last_emotion_step_ms.store(now_ms, Ordering::Relaxed);
+            return;
+        }
+        let elapsed_ms = now_ms - last_ms;
+        if elapsed_ms < EMOTION_STEP_INTERVAL_MS {
+            return;
+        }
+        self.last_emotion_step_ms.store(now_ms, Ordering::Relaxed);
+        let seconds = elapsed_ms as f32 / 1000.0;
+
+        for ulid in npcs {
+            let Some(stats) = self.get_combat_stats(ulid) else {
+                continue;
+            };
+            let hunger = HungerLevel::of(stats.hunger, stats.max_hunger);
+            let exhausted = stats.max_energy > 0.0
+                && stats.energy < stats.max_energy * EXHAUSTED_ENERGY_FRACTION;
+            if !self.npc_emotions.contains_key(ulid) && hunger == HungerLevel::Fed && !exhausted {
+                continue; // Calm and content
+            }
+
+            self.stir(ulid, |scores| {
+                for score in scores.scores_mut() {
+                    *score -= EMOTION_DECAY_PER_SEC * seconds;
+                }
+                match hunger {
+                    HungerLevel::Hungry => scores.anger += HUNGRY_ANGER_PER_SEC * seconds,
+                    HungerLevel::Starving => scores.sadness += STARVING_SADNESS_PER_SEC * seconds,
+                    HungerLevel::Fed => {}
+                }
+                if exhausted {
+                    scores.weariness += EXHAUSTED_WEARINESS_PER_SEC * seconds;
+                }
+            });
+        }
+    }
+
+    /// Change an NPC's scores, then update its emotion (queues an
+    /// EmotionEvent when it changes)
+    fn stir(&self, ulid: &[u8; 16], change: impl FnOnce(&mut EmotionScores)) {
+        if !self.npc_combat_stats.contains_key(ulid) {
+            return; // Gone (a projectile can outlive its shooter)
+        }
+        let emotion = {
+            let mut scores = self.npc_emotions.entry(*ulid).or_default();
+            change(&mut scores);
+            scores.clamp();
+            scores.dominant()
+        };
+        self.npc_emotions
+            .remove_if(ulid, |_, scores| scores.is_calm());
+
+        let previous = {
+            let Some(mut stats) = self.npc_combat_stats.get_mut(ulid) else {
+                return;
+            };
+            let previous = Emotion::from_id(stats.emotional_state);
+            stats.emotional_state = emotion.id();
+            previous
+        };
+        if emotion == previous {
+            return;
+        }
+        sim_print!(
+            "[EMOTION] NPC {} is now {} (was {})",
+            &bytes_to_hex(ulid)[..8],
+            emotion.as_str(),
+            previous.as_str()
+        );
+        self.emotion_events.push(EmotionEvent {
+            ulid: *ulid,
+            emotion,
+            previous,
+        });
+    }
+}
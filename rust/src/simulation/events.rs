@@ -0,0 +1,168 @@
+// ============================================================================
+// COMBAT EVENTS - What the simulation tells the host to render
+// ============================================================================
+// Rust determines all combat logic and outputs these events; the host only
+// plays animations, spawns projectiles and shows damage numbers.
+//
+// Events carry raw ULID bytes and container-local positions. The legacy JSON
+// form (flat struct with hex ULIDs and string fields) is kept by `to_json()`
+// for GDScript that still parses it.
+
+use bevy::math::Vec2;
+use serde::Serialize;
+
+use super::stats::bytes_to_hex;
+
+/// Arrow flight speed (pixels/second) the host should use for projectiles
+pub const ARROW_SPEED: f32 = 300.0;
+
+/// Kind of projectile a ranged attacker fires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectileKind {
+    Arrow,
+}
+
+impl ProjectileKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectileKind::Arrow => "arrow",
+        }
+    }
+}
+
+/// CombatEvent - Events generated by Rust combat system for the host to render
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CombatEvent {
+    /// Attacker started an attack (play its attack animation)
+    Attack {
+        attacker: [u8; 16],
+        target: [u8; 16],
+        target_pos: Vec2,
+    },
+    /// Ranged attacker fired a projectile - damage lands when the host
+    /// reports the hit through `projectile_hit()`
+    Projectile {
+        attacker: [u8; 16],
+        target: [u8; 16],
+        kind: ProjectileKind,
+        origin: Vec2,
+        target_pos: Vec2,
+        speed: f32,
+    },
+    /// Target took damage and survived
+    Damage {
+        attacker: [u8; 16],
+        target: [u8; 16],
+        amount: f32,
+        target_pos: Vec2,
+    },
+    /// Target was killed by this hit
+    Death {
+        attacker: [u8; 16],
+        target: [u8; 16],
+        amount: f32,
+        target_pos: Vec2,
+    },
+    /// A healer's channeled heal landed (`attacker` is the healer)
+    Heal {
+        attacker: [u8; 16],
+        target: [u8; 16],
+        amount: f32,
+        target_pos: Vec2,
+    },
+}
+
+/// Legacy flat event layout ("event_type" + string animations)
+#[derive(Serialize)]
+struct LegacyCombatEvent<'a> {
+    event_type: &'a str,
+    attacker_ulid: String,
+    target_ulid: String,
+    amount: f32,
+    attacker_animation: &'a str,
+    target_animation: String,
+    target_x: f32,
+    target_y: f32,
+}
+
+impl CombatEvent {
+    /// Event name: "attack", "projectile", "damage", "death" or "heal"
+    pub fn name(&self) -> &'static str {
+        match self {
+            CombatEvent::Attack { .. } => "attack",
+            CombatEvent::Projectile { .. } => "projectile",
+            CombatEvent::Damage { .. } => "damage",
+            CombatEvent::Death { .. } => "death",
+            CombatEvent::Heal { .. } => "heal",
+        }
+    }
+
+    pub fn attacker(&self) -> &[u8; 16] {
+        match self {
+            CombatEvent::Attack { attacker, .. }
+            | CombatEvent::Projectile { attacker, .. }
+            | CombatEvent::Damage { attacker, .. }
+            | CombatEvent::Death { attacker, .. }
+            | CombatEvent::Heal { attacker, .. } => attacker,
+        }
+    }
+
+    pub fn target(&self) -> &[u8; 16] {
+        match self {
+            CombatEvent::Attack { target, .. }
+            | CombatEvent::Projectile { target, .. }
+            | CombatEvent::Damage { target, .. }
+            | CombatEvent::Death { target, .. }
+            | CombatEvent::Heal { target, .. } => target,
+        }
+    }
+
+    pub fn target_pos(&self) -> Vec2 {
+        match self {
+            CombatEvent::Attack { target_pos, .. }
+            | CombatEvent::Projectile { target_pos, .. }
+            | CombatEvent::Damage { target_pos, .. }
+            | CombatEvent::Death { target_pos, .. }
+            | CombatEvent::Heal { target_pos, .. } => *target_pos,
+        }
+    }
+
+    /// Damage dealt, or HP restored for heals (0 for attack/projectile events)
+    pub fn amount(&self) -> f32 {
+        match self {
+            CombatEvent::Damage { amount, .. }
+            | CombatEvent::Death { amount, .. }
+            | CombatEvent::Heal { amount, .. } => *amount,
+            CombatEvent::Attack { .. } | CombatEvent::Projectile { .. } => 0.0,
+        }
+    }
+
+    /// Serialize to the legacy JSON layout for GDScript
+    /// Projectiles encode "attacker_y,speed" in target_animation as before
+    pub fn to_json(&self) -> String {
+        let (attacker_animation, target_animation) = match self {
+            CombatEvent::Attack { .. } => ("attack", String::new()),
+            CombatEvent::Projectile {
+                kind,
+                origin,
+                speed,
+                ..
+            } => (kind.as_str(), format!("{},{}", origin.y, speed)),
+            CombatEvent::Damage { .. } => ("", "hurt".to_string()),
+            CombatEvent::Death { .. } => ("", "death".to_string()),
+            CombatEvent::Heal { .. } => ("", "heal".to_string()),
+        };
+        let target_pos = self.target_pos();
+        let legacy = LegacyCombatEvent {
+            event_type: self.name(),
+            attacker_ulid: bytes_to_hex(self.attacker()),
+            target_ulid: bytes_to_hex(self.target()),
+            amount: self.amount(),
+            attacker_animation,
+            target_animation,
+            target_x: target_pos.x,
+            target_y: target_pos.y,
+        };
+        serde_json::to_string(&legacy).unwrap_or_else(|_| "{}".to_string())
+    }
+}
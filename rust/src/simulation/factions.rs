@@ -0,0 +1,417 @@
+// ============================================================================
+// FACTIONS - Named factions and the relationship matrix between them
+// ============================================================================
+// Every NPC belongs to a named faction. Whether two NPCs fight is decided by
+// the relationship between their factions (hostile, neutral or friendly), not
+// by the ALLY / MONSTER / PASSIVE bits, so hosts can add neutral wildlife,
+// rival monster tribes or charm a unit over to the other side at runtime.
+//
+// The built-in factions match the static faction bits: "ally" and "monster"
+// are hostile to each other, "passive" is neutral to everyone and each
+// faction is friendly to itself. A new faction can inherit the relations and
+// wander zone of an existing one (a tribe registered from "monster" fights
+// allies but not other monsters until told otherwise). Relations are
+// symmetric.
+//
+// An NPC's faction comes from its archetype ("faction_name", defaulting to
+// its "faction") and can be overridden per NPC until it despawns. NPCs
+// registered without an archetype use the faction of their static bits.
+// PASSIVE NPCs never attack, whatever their faction's relations.
+//
+// The table is copy-on-write: phases load it once per tick, edits swap in a
+// new copy. Relation and NPC faction changes queue a FactionEvent.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::archetypes::NPCArchetype;
+use super::log::sim_print;
+use super::replay::RecordedInput;
+use super::stats::{bytes_to_hex, NPCStaticState};
+use super::warehouse::NPCDataWarehouse;
+
+/// Relationship between two factions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Relation {
+    /// Members attack each other on sight
+    Hostile,
+    /// Members ignore each other
+    Neutral,
+    /// Members fight side by side
+    Friendly,
+}
+
+impl Relation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Relation::Hostile => "hostile",
+            Relation::Neutral => "neutral",
+            Relation::Friendly => "friendly",
+        }
+    }
+
+    /// Parse "hostile" / "neutral" / "friendly"
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "hostile" => Some(Relation::Hostile),
+            "neutral" => Some(Relation::Neutral),
+            "friendly" => Some(Relation::Friendly),
+            _ => None,
+        }
+    }
+}
+
+/// Index of a faction in the faction table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct FactionId(pub u16);
+
+impl FactionId {
+    pub const ALLY: FactionId = FactionId(0);
+    pub const MONSTER: FactionId = FactionId(1);
+    pub const PASSIVE: FactionId = FactionId(2);
+
+    /// Built-in faction matching an NPC's static faction bits
+    pub fn from_static(static_state: NPCStaticState) -> Self {
+        if static_state.contains(NPCStaticState::ALLY) {
+            FactionId::ALLY
+        } else if static_state.contains(NPCStaticState::MONSTER) {
+            FactionId::MONSTER
+        } else {
+            FactionId::PASSIVE
+        }
+    }
+}
+
+/// One named faction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FactionDef {
+    pub name: String,
+    /// Horizontal band idle members wander in, as fractions of the world
+    /// width (None = the whole world)
+    #[serde(default)]
+    pub wander_zone: Option<(f32, f32)>,
+}
+
+/// Every faction and the relation between each pair
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FactionTable {
+    factions: Vec<FactionDef>,
+    /// relations[a][b] (kept symmetric)
+    relations: Vec<Vec<Relation>>,
+}
+
+impl Default for FactionTable {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+impl FactionTable {
+    /// Built-in factions: ally (left side) vs monster (right side), passive
+    pub fn with_defaults() -> Self {
+        let mut table = Self {
+            factions: Vec::new(),
+            relations: Vec::new(),
+        };
+        table.add("ally", None);
+        table.add("monster", None);
+        table.add("passive", None);
+        table.factions[FactionId::ALLY.0 as usize].wander_zone = Some((0.0, 0.4));
+        table.factions[FactionId::MONSTER.0 as usize].wander_zone = Some((0.6, 1.0));
+        table.set_relation(FactionId::ALLY, FactionId::MONSTER, Relation::Hostile);
+        table
+    }
+
+    /// Factions in id order
+    pub fn factions(&self) -> &[FactionDef] {
+        &self.factions
+    }
+
+    pub fn id(&self, name: &str) -> Option<FactionId> {
+        self.factions
+            .iter()
+            .position(|faction| faction.name == name)
+            .map(|index| FactionId(index as u16))
+    }
+
+    /// Faction name ("" for an unknown id)
+    pub fn name(&self, id: FactionId) -> &str {
+        self.factions
+            .get(id.0 as usize)
+            .map_or("", |faction| faction.name.as_str())
+    }
+
+    /// Relation between two factions (unknown ids are neutral)
+    pub fn relation(&self, a: FactionId, b: FactionId) -> Relation {
+        self.relations
+            .get(a.0 as usize)
+            .and_then(|row| row.get(b.0 as usize))
+            .copied()
+            .unwrap_or(Relation::Neutral)
+    }
+
+    pub fn is_hostile(&self, a: FactionId, b: FactionId) -> bool {
+        self.relation(a, b) == Relation::Hostile
+    }
+
+    pub fn wander_zone(&self, id: FactionId) -> Option<(f32, f32)> {
+        self.factions.get(id.0 as usize)?.wander_zone
+    }
+
+    /// Add a faction, copying the relations and wander zone of `inherit`
+    /// (without one it is friendly to itself and neutral to everyone)
+    fn add(&mut self, name: &str, inherit: Option<FactionId>) -> FactionId {
+        let id = FactionId(self.factions.len() as u16);
+        let wander_zone = inherit.and_then(|base| self.wander_zone(base));
+        let mut row: Vec<Relation> = (0..self.factions.len())
+            .map(|other| match inherit {
+                Some(base) => self.relation(base, FactionId(other as u16)),
+                None => Relation::Neutral,
+            })
+            .collect();
+        row.push(match inherit {
+            Some(base) => self.relation(base, base),
+            None => Relation::Friendly,
+        });
+
+        for (other, relations) in self.relations.iter_mut().enumerate() {
+            relations.push(row[other]);
+        }
+        self.relations.push(row);
+        self.factions.push(FactionDef {
+            name: name.to_string(),
+            wander_zone,
+        });
+        id
+    }
+
+    /// Set the relation both ways - returns whether it changed
+    fn set_relation(&mut self, a: FactionId, b: FactionId, relation: Relation) -> bool {
+        if self.relation(a, b) == relation {
+            return false;
+        }
+        self.relations[a.0 as usize][b.0 as usize] = relation;
+        self.relations[b.0 as usize][a.0 as usize] = relation;
+        true
+    }
+
+    /// Whether a deserialized table is usable (square, symmetric matrix,
+    /// non-empty wander zones)
+    pub fn is_valid(&self) -> bool {
+        let n = self.factions.len();
+        self.relations.len() == n
+            && self.relations.iter().all(|row| row.len() == n)
+            && (0..n).all(|a| (0..n).all(|b| self.relations[a][b] == self.relations[b][a]))
+            && self
+                .factions
+                .iter()
+                .all(|faction| validate_wander_zone(faction.wander_zone).is_ok())
+    }
+}
+
+/// A faction change the host may want to react to
+#[derive(Debug, Clone, PartialEq)]
+pub enum FactionEvent {
+    /// Two factions' relation changed
+    RelationChanged {
+        a: String,
+        b: String,
+        relation: Relation,
+    },
+    /// An NPC was moved to another faction (charmed, converted)
+    NpcFactionChanged { ulid: [u8; 16], faction: String },
+}
+
+/// Faction names are single words (they appear in recordings)
+fn validate_faction_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.chars().any(char::is_whitespace) {
+        return Err(format!(
+            "invalid faction name '{}' (use a single word)",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Wander zones must be a non-empty band inside the world (0 <= from < to <= 1),
+/// otherwise there is nowhere to pick a waypoint from
+fn validate_wander_zone(zone: Option<(f32, f32)>) -> Result<(), String> {
+    match zone {
+        Some((from, to)) if !(0.0..=1.0).contains(&from) || !(0.0..=1.0).contains(&to) => Err(
+            format!("wander zone {}..{} is outside the world (0..1)", from, to),
+        ),
+        Some((from, to)) if to <= from => Err(format!("wander zone {}..{} is empty", from, to)),
+        _ => Ok(()),
+    }
+}
+
+impl NPCDataWarehouse {
+    /// Current faction table (cheap to hold for a whole phase)
+    pub fn faction_table(&self) -> Arc<FactionTable> {
+        self.factions.read().clone()
+    }
+
+    /// Edit a copy of the table and swap it in
+    fn update_factions<R>(&self, edit: impl FnOnce(&mut FactionTable) -> R) -> R {
+        let mut factions = self.factions.write();
+        let mut table = FactionTable::clone(&factions);
+        let result = edit(&mut table);
+        *factions = Arc::new(table);
+        result
+    }
+
+    /// Look up a faction id, with an error naming the faction
+    pub fn faction_id(&self, name: &str) -> Result<FactionId, String> {
+        self.faction_table()
+            .id(name)
+            .ok_or_else(|| format!("unknown faction '{}'", name))
+    }
+
+    /// Register a faction, inheriting the relations and wander zone of
+    /// `inherit`. Returns the existing id if the name is taken
+    pub fn register_faction(&self, name: &str, inherit: Option<&str>) -> Result<FactionId, String> {
+        validate_faction_name(name)?;
+        let base = inherit.map(|base| self.faction_id(base)).transpose()?;
+        if let Some(id) = self.faction_table().id(name) {
+            return Ok(id);
+        }
+        self.record_input(RecordedInput::RegisterFaction {
+            name: name.to_string(),
+            inherit: inherit.map(str::to_string),
+        });
+        let id = self.update_factions(|table| table.add(name, base));
+        sim_print!(
+            "NPCDataWarehouse: Registered faction '{}' (inherits: {})",
+            name,
+            inherit.unwrap_or("nothing")
+        );
+        Ok(id)
+    }
+
+    /// Set the relation between two factions (both ways)
+    pub fn set_faction_relation(&self, a: &str, b: &str, relation: Relation) -> Result<(), String> {
+        let (id_a, id_b) = (self.faction_id(a)?, self.faction_id(b)?);
+        self.record_input(RecordedInput::FactionRelation {
+            a: a.to_string(),
+            b: b.to_string(),
+            relation,
+        });
+        if self.update_factions(|table| table.set_relation(id_a, id_b, relation)) {
+            sim_print!(
+                "NPCDataWarehouse: Factions '{}' and '{}' are now {}",
+                a,
+                b,
+                relation.as_str()
+            );
+            self.faction_events.push(FactionEvent::RelationChanged {
+                a: a.to_string(),
+                b: b.to_string(),
+                relation,
+            });
+        }
+        Ok(())
+    }
+
+    /// Relation between two factions (None if either is unknown)
+    pub fn faction_relation(&self, a: &str, b: &str) -> Option<Relation> {
+        let table = self.faction_table();
+        Some(table.relation(table.id(a)?, table.id(b)?))
+    }
+
+    /// Set the band idle members wander in (fractions of the world width,
+    /// None = the whole world) - fails unless 0 <= from < to <= 1
+    pub fn set_faction_wander_zone(
+        &self,
+        name: &str,
+        zone: Option<(f32, f32)>,
+    ) -> Result<(), String> {
+        let id = self.faction_id(name)?;
+        validate_wander_zone(zone)?;
+        self.record_input(RecordedInput::FactionZone {
+            name: name.to_string(),
+            zone,
+        });
+        self.update_factions(|table| table.factions[id.0 as usize].wander_zone = zone);
+        Ok(())
+    }
+
+    /// Registered faction names, in id order
+    pub fn faction_names(&self) -> Vec<String> {
+        self.faction_table()
+            .factions()
+            .iter()
+            .map(|faction| faction.name.clone())
+            .collect()
+    }
+
+    /// Faction of an NPC (its override, or the faction of its static bits)
+    pub fn npc_faction(&self, ulid: &[u8; 16]) -> Option<FactionId> {
+        if let Some(faction) = self.npc_factions.get(ulid) {
+            return Some(*faction);
+        }
+        let stats = self.get_combat_stats(ulid)?;
+        Some(FactionId::from_static(stats.static_flags()))
+    }
+
+    /// Faction name of an NPC
+    pub fn npc_faction_name(&self, ulid: &[u8; 16]) -> Option<String> {
+        let faction = self.npc_faction(ulid)?;
+        Some(self.faction_table().name(faction).to_string())
+    }
+
+    /// Move an NPC to another faction until it despawns (charm, conversion)
+    pub fn set_npc_faction(&self, ulid: &[u8; 16], faction: &str) -> Result<(), String> {
+        let id = self.faction_id(faction)?;
+        if self.get_combat_stats(ulid).is_none() {
+            return Err(format!("unknown NPC {}", &bytes_to_hex(ulid)[..16]));
+        }
+        self.record_input(RecordedInput::NpcFaction {
+            ulid: *ulid,
+            faction: faction.to_string(),
+        });
+        if self.npc_factions.insert(*ulid, id) != Some(id) {
+            // A squad only holds one faction
+            self.drop_from_squad(&self.stored(), ulid);
+            self.faction_events.push(FactionEvent::NpcFactionChanged {
+                ulid: *ulid,
+                faction: faction.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Pop the next faction event (None when the queue is empty)
+    pub fn pop_faction_event(&self) -> Option<FactionEvent> {
+        self.faction_events.pop()
+    }
+
+    /// Faction of an archetype, registering it (inheriting from the
+    /// archetype's built-in faction) the first time it is seen
+    pub(super) fn archetype_faction(&self, archetype: &NPCArchetype) -> FactionId {
+        let name = archetype.faction_name();
+        if let Some(id) = self.faction_table().id(name) {
+            return id;
+        }
+        let base = FactionId::from_static(archetype.static_flags());
+        let id = self.update_factions(|table| match table.id(name) {
+            Some(id) => id,
+            None => table.add(name, Some(base)),
+        });
+        sim_print!(
+            "NPCDataWarehouse: Registered faction '{}' for archetype '{}'",
+            name,
+            archetype.name
+        );
+        id
+    }
+
+    /// Replace the faction table (snapshot restore)
+    pub(super) fn restore_faction_table(&self, table: &FactionTable) -> Result<(), String> {
+        if !table.is_valid() {
+            return Err("invalid faction table".to_string());
+        }
+        *self.factions.write() = Arc::new(table.clone());
+        Ok(())
+    }
+}
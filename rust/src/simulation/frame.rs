@@ -0,0 +1,126 @@
+// ============================================================================
+// SIM FRAME - Read-only render view published after every tick
+// ============================================================================
+// The host never reads the live warehouse maps while rendering. After each
+// tick the simulation captures what the nodes need (position, behavioral
+// state, facing) into an immutable frame; the runner publishes it through
+// an ArcSwap and the main thread applies the latest one to its nodes.
+
+use bevy::math::Vec2;
+use std::collections::HashMap;
+
+use super::stats::{NPCState, NPCStaticState};
+use super::warehouse::NPCDataWarehouse;
+
+/// Render state of one active NPC
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NpcFrame {
+    pub position: Vec2,
+    pub state: NPCState,
+    /// Sprite should face left (None = keep the current facing, e.g. dead)
+    pub face_left: Option<bool>,
+}
+
+impl NpcFrame {
+    /// Animation name for the behavioral state (matches the SpriteFrames names)
+    /// Priority: dead > hurt > attacking > walking > idle
+    pub fn animation(&self) -> &'static str {
+        if self.state.contains(NPCState::DEAD) {
+            "dead"
+        } else if self.state.contains(NPCState::DAMAGED) {
+            "hurt"
+        } else if self.state.contains(NPCState::ATTACKING) {
+            "attacking"
+        } else if self.state.contains(NPCState::WALKING) {
+            "walking"
+        } else {
+            "idle"
+        }
+    }
+}
+
+/// Everything the host needs to draw one simulation tick
+#[derive(Debug, Clone, Default)]
+pub struct SimFrame {
+    /// Ticks run by the runner when this frame was captured
+    pub tick: u64,
+    pub time_ms: u64,
+    pub world_min: Vec2,
+    pub world_max: Vec2,
+    pub npcs: HashMap<[u8; 16], NpcFrame>,
+}
+
+impl NPCDataWarehouse {
+    /// Capture the render view of every active NPC with a position and stats
+    pub fn capture_frame(&self, tick: u64) -> SimFrame {
+        let (world_min, world_max) = self.world_bounds();
+        let mut npcs = HashMap::with_capacity(self.active_npcs.len());
+
+        for entry in self.active_npcs.iter() {
+            let ulid = entry.key();
+            let Some(position) = self.get_npc_position_internal(ulid) else {
+                continue;
+            };
+            let Some(stats) = self.get_combat_stats(ulid) else {
+                continue;
+            };
+            let state = self.get_behavioral_state(ulid).unwrap_or(NPCState::IDLE);
+
+            npcs.insert(
+                *ulid,
+                NpcFrame {
+                    position,
+                    state,
+                    face_left: self.facing_left(ulid, position, state, stats.static_flags()),
+                },
+            );
+        }
+
+        SimFrame {
+            tick,
+            time_ms: self.get_current_time_ms(),
+            world_min,
+            world_max,
+            npcs,
+        }
+    }
+
+    /// Which way an NPC's sprite should face
+    fn facing_left(
+        &self,
+        ulid: &[u8; 16],
+        position: Vec2,
+        state: NPCState,
+        static_state: NPCStaticState,
+    ) -> Option<bool> {
+        // Keep current flip state for dead NPCs (death animation should stay as-is)
+        if state.contains(NPCState::DEAD) {
+            return None;
+        }
+
+        let move_dir = self.npc_move_directions.get(ulid).map(|v| *v.value());
+
+        if state.contains(NPCState::COMBAT) {
+            // PRIORITY: In combat - face the combat target
+            let target_pos = self
+                .npc_target(ulid)
+                .and_then(|target| self.get_npc_position_internal(&target));
+
+            return Some(match target_pos {
+                Some(target_pos) => target_pos.x < position.x,
+                // No target (or it is gone) - use move direction
+                None => move_dir.is_some_and(|dir| dir.x < 0.0),
+            });
+        }
+
+        if let Some(dir) = move_dir {
+            return Some(dir.x < 0.0);
+        }
+        if let Some(waypoint) = self.npc_waypoints.get(ulid).map(|v| *v.value()) {
+            return Some(waypoint.x < position.x);
+        }
+
+        // Default: face right for allies, face left for monsters
+        Some(static_state.contains(NPCStaticState::MONSTER))
+    }
+}
@@ -0,0 +1,222 @@
+// ============================================================================
+// HEALER AI - Channeled heals on the most injured friendly
+// ============================================================================
+// NPCs with the HEALER combat type never attack. Each combat phase a healer
+// that is off cooldown, not stunned and has the mana picks the most injured
+// friendly NPC (lowest HP fraction, itself included) within its attack range
+// and starts channeling: the mana is spent, the cooldown starts and ATTACKING
+// plays the cast animation right away, while the heal lands heal_channel_ms
+// later as a CombatEvent::Heal. Healers skip targets another healer is
+// already channeling on, so two clerics don't pile onto one ally.
+//
+// A heal fizzles if its target died, left or is back at full HP when it
+// lands. Landed heals earn the healer threat (see threat.rs). Stunning or
+// killing the healer cancels the channel (the mana stays spent). Keeping away
+// from hostiles is the movement phase's job.
+
+use super::ecs::NpcTable;
+use super::events::CombatEvent;
+use super::factions::{FactionTable, Relation};
+use super::live::LiveState;
+use super::log::sim_print;
+use super::spatial::{SpatialGrid, GRID_CELL_SIZE};
+use super::stats::{bytes_to_hex, NPCState, NPCStaticState};
+use super::warehouse::{NPCDataWarehouse, NpcRow};
+
+/// A heal being channeled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct HealChannel {
+    pub target: [u8; 16],
+    /// Simulation time the heal lands
+    pub lands_ms: u64,
+}
+
+impl NPCDataWarehouse {
+    /// Target of a healer's channeled heal and the time it lands
+    /// (None when the NPC isn't channeling)
+    pub fn heal_channel(&self, healer: &[u8; 16]) -> Option<([u8; 16], u64)> {
+        self.heal_channels
+            .get(healer)
+            .map(|channel| (channel.target, channel.lands_ms))
+    }
+
+    /// Drop a healer's channel without landing it (death, stun, despawn)
+    pub(super) fn cancel_heal_channel(&self, healer: &[u8; 16]) {
+        self.heal_channels.remove(healer);
+    }
+
+    /// Put a channel back as it was saved (see snapshot.rs)
+    pub(super) fn restore_heal_channel(&self, healer: &[u8; 16], target: [u8; 16], lands_ms: u64) {
+        self.heal_channels
+            .insert(*healer, HealChannel { target, lands_ms });
+    }
+
+    /// Start channels for idle healers, then land the heals that are due
+    pub(super) fn run_healers(&self, npcs: &mut NpcTable) -> Vec<CombatEvent> {
+        let now_ms = self.get_current_time_ms();
+
+        // Living NPCs registered for combat, sorted by ULID
+        let rows = npcs.rows();
+        if rows
+            .iter()
+            .any(|row| row.static_state.contains(NPCStaticState::HEALER))
+        {
+            let grid = SpatialGrid::build(&rows, GRID_CELL_SIZE);
+            let factions = self.faction_table();
+
+            for (i, healer) in rows.iter().enumerate() {
+                if !healer.static_state.contains(NPCStaticState::HEALER) {
+                    continue;
+                }
+                // Rows predate this phase's heals - check the live state
+                let dead = npcs
+                    .behavior(&healer.ulid)
+                    .is_none_or(|state| state.contains(NPCState::DEAD));
+                if dead {
+                    continue;
+                }
+
+                // A stun breaks the channel and blocks new ones
+                if self.is_stunned(&healer.ulid) {
+                    self.cancel_heal_channel(&healer.ulid);
+                    continue;
+                }
+
+                if self.heal_channels.contains_key(&healer.ulid)
+                    || !self.check_attack_cooldown(npcs, &healer.ulid, now_ms)
+                {
+                    continue;
+                }
+
+                let Some(stats) = self.get_combat_stats(&healer.ulid) else {
+                    continue;
+                };
+                if stats.heal_power <= 0.0 || stats.mana < stats.heal_mana_cost {
+                    continue; // Nothing to heal with
+                }
+
+                let Some(target) = self.pick_heal_target(&rows, npcs, &grid, &factions, i) else {
+                    continue;
+                };
+
+                if !self.spend_resources(&healer.ulid, stats.heal_mana_cost, 0.0) {
+                    continue;
+                }
+                npcs.set_last_attack_ms(&healer.ulid, now_ms);
+                self.add_attacking_state(npcs, &healer.ulid);
+                self.heal_channels.insert(
+                    healer.ulid,
+                    HealChannel {
+                        target,
+                        lands_ms: now_ms + stats.heal_channel_ms,
+                    },
+                );
+
+                sim_print!(
+                    "[HEAL] Healer {} channeling on {} ({}ms)",
+                    &bytes_to_hex(&healer.ulid)[0..8],
+                    &bytes_to_hex(&target)[0..8],
+                    stats.heal_channel_ms
+                );
+            }
+        }
+
+        self.land_heals(npcs, now_ms)
+    }
+
+    /// Most injured friendly within the healer's range that no other
+    /// healer is channeling on (ties go to the lower ULID)
+    fn pick_heal_target(
+        &self,
+        npcs: &[NpcRow],
+        live: &NpcTable,
+        grid: &SpatialGrid,
+        factions: &FactionTable,
+        healer_index: usize,
+    ) -> Option<[u8; 16]> {
+        let healer = &npcs[healer_index];
+        let mut best: Option<(f32, usize)> = None;
+
+        for j in grid.query_radius(healer.pos, healer.attack_range) {
+            let candidate = &npcs[j];
+            if candidate.state.contains(NPCState::DEAD)
+                || factions.relation(healer.faction, candidate.faction) != Relation::Friendly
+            {
+                continue;
+            }
+            let Some(health) = live.health(&candidate.ulid) else {
+                continue;
+            };
+            if health.max_hp <= 0.0 || health.hp <= 0.0 || health.hp >= health.max_hp {
+                continue;
+            }
+            let claimed = self
+                .heal_channels
+                .iter()
+                .any(|channel| *channel.key() != healer.ulid && channel.target == candidate.ulid);
+            if claimed {
+                continue;
+            }
+
+            let fraction = health.hp / health.max_hp;
+            let better = match best {
+                Some((best_fraction, best_j)) => {
+                    fraction < best_fraction || (fraction == best_fraction && j < best_j)
+                }
+                None => true,
+            };
+            if better {
+                best = Some((fraction, j));
+            }
+        }
+
+        best.map(|(_, j)| npcs[j].ulid)
+    }
+
+    /// Land every channel due by `now_ms`, in healer ULID order
+    fn land_heals(&self, npcs: &mut NpcTable, now_ms: u64) -> Vec<CombatEvent> {
+        let mut due: Vec<([u8; 16], [u8; 16])> = self
+            .heal_channels
+            .iter()
+            .filter(|channel| channel.lands_ms <= now_ms)
+            .map(|channel| (*channel.key(), channel.target))
+            .collect();
+        due.sort_unstable();
+
+        let mut events = Vec::new();
+        for (healer, target) in due {
+            self.heal_channels.remove(&healer);
+
+            let Some(healer_stats) = self.get_combat_stats(&healer) else {
+                continue;
+            };
+            let target_alive = self.active_npcs.contains_key(&target)
+                && !npcs
+                    .behavior(&target)
+                    .is_some_and(|state| state.contains(NPCState::DEAD));
+            let Some(target_health) = npcs.health(&target).filter(|_| target_alive) else {
+                continue; // Target died or left - the heal fizzles
+            };
+
+            let amount = healer_stats
+                .heal_power
+                .min(target_health.max_hp - target_health.hp);
+            if amount <= 0.0 {
+                continue; // Already back at full HP
+            }
+            self.heal_npc(npcs, &target, amount, 0.0, 0.0);
+            self.add_heal_threat(&healer, &target, amount);
+
+            let Some(target_pos) = npcs.position(&target) else {
+                continue;
+            };
+            events.push(CombatEvent::Heal {
+                attacker: healer,
+                target,
+                amount,
+                target_pos,
+            });
+        }
+        events
+    }
+}
@@ -0,0 +1,310 @@
+// ============================================================================
+// HUNGER - Hunger decay, starvation and the kingdom's food stockpile
+// ============================================================================
+// Each NPC's hunger falls by its archetype's hunger_decay per second of
+// simulation time, in steps of at least HUNGER_STEP_INTERVAL_MS for every
+// living NPC in combat (NPCs in ULID order). Hunger sets a level:
+//   - Fed       above HUNGRY_FRACTION of max_hunger
+//   - Hungry    at or below it - HUNGRY_MODIFIERS scale attack, defense and
+//               move speed (folded into status_modifiers, see effects.rs)
+//   - Starving  at 0 - STARVING_MODIFIERS, and STARVATION_DAMAGE_PER_SEC HP
+//               is lost every second (a starved NPC dies like any other)
+//
+// Kingdom NPCs (allies and passive pets, never monsters) that are hungry or
+// starving eat one unit from the food stockpile per step, restoring
+// FOOD_NUTRITION hunger. The host fills the stockpile with add_food and can
+// feed an NPC directly with feed_npc.
+//
+// Crossing into another level and every stockpile change queue a HungerEvent.
+
+use std::sync::atomic::Ordering;
+
+use super::ecs::NpcTable;
+use super::effects::StatModifiers;
+use super::events::CombatEvent;
+use super::live::LiveState;
+use super::log::sim_print;
+use super::replay::RecordedInput;
+use super::stats::{bytes_to_hex, NPCCombatStats, NPCState, NPCStaticState};
+use super::warehouse::NPCDataWarehouse;
+
+/// Shortest simulation time between two hunger steps
+pub const HUNGER_STEP_INTERVAL_MS: u64 = 1000;
+
+/// Share of max_hunger at or below which an NPC is hungry (and eats)
+pub const HUNGRY_FRACTION: f32 = 0.5;
+
+/// Hunger one unit of food restores
+pub const FOOD_NUTRITION: f32 = 40.0;
+
+/// HP a starving NPC loses per second
+pub const STARVATION_DAMAGE_PER_SEC: f32 = 2.0;
+
+/// Food in the stockpile when the kingdom is founded
+pub const DEFAULT_FOOD_STOCKPILE: u32 = 50;
+
+/// Stat multipliers while hungry
+pub const HUNGRY_MODIFIERS: StatModifiers = StatModifiers {
+    attack: 0.85,
+    defense: 0.9,
+    move_speed: 0.9,
+};
+
+/// Stat multipliers while starving
+pub const STARVING_MODIFIERS: StatModifiers = StatModifiers {
+    attack: 0.6,
+    defense: 0.75,
+    move_speed: 0.7,
+};
+
+/// How hungry an NPC is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HungerLevel {
+    Fed,
+    Hungry,
+    Starving,
+}
+
+impl HungerLevel {
+    /// Level for a hunger value (an NPC with no hunger pool is always fed)
+    pub fn of(hunger: f32, max_hunger: f32) -> Self {
+        if max_hunger <= 0.0 {
+            HungerLevel::Fed
+        } else if hunger <= 0.0 {
+            HungerLevel::Starving
+        } else if hunger <= max_hunger * HUNGRY_FRACTION {
+            HungerLevel::Hungry
+        } else {
+            HungerLevel::Fed
+        }
+    }
+
+    fn of_stats(stats: &NPCCombatStats) -> Self {
+        Self::of(stats.hunger, stats.max_hunger)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HungerLevel::Fed => "fed",
+            HungerLevel::Hungry => "hungry",
+            HungerLevel::Starving => "starving",
+        }
+    }
+
+    /// Stat multipliers at this level
+    pub fn modifiers(&self) -> StatModifiers {
+        match self {
+            HungerLevel::Fed => StatModifiers::default(),
+            HungerLevel::Hungry => HUNGRY_MODIFIERS,
+            HungerLevel::Starving => STARVING_MODIFIERS,
+        }
+    }
+}
+
+/// Hunger level crossing or stockpile change
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HungerEvent {
+    /// An NPC's hunger crossed into another level
+    Level {
+        ulid: [u8; 16],
+        level: HungerLevel,
+        hunger: f32,
+        max_hunger: f32,
+    },
+    /// The food stockpile changed (new total)
+    Stockpile { food: u32 },
+}
+
+impl NPCDataWarehouse {
+    /// Pop the next hunger event (None when the queue is empty)
+    pub fn pop_hunger_event(&self) -> Option<HungerEvent> {
+        self.hunger_events.pop()
+    }
+
+    /// Food units in the kingdom's stockpile
+    pub fn food_stockpile(&self) -> u32 {
+        self.food_stockpile.load(Ordering::Relaxed)
+    }
+
+    /// Put food into the stockpile, returns the new total
+    pub fn add_food(&self, units: u32) -> u32 {
+        self.record_input(RecordedInput::AddFood { units });
+        let food = self
+            .food_stockpile
+            .load(Ordering::Relaxed)
+            .saturating_add(units);
+        self.set_food_stockpile(food);
+        food
+    }
+
+    /// Hunger level of an NPC (None if it isn't registered for combat)
+    pub fn hunger_level(&self, ulid: &[u8; 16]) -> Option<HungerLevel> {
+        self.npc_combat_stats
+            .get(ulid)
+            .map(|stats| HungerLevel::of_stats(&stats))
+    }
+
+    /// Feed a living NPC one unit from the stockpile
+    /// Returns its hunger afterwards
+    pub fn feed_npc(&self, ulid: &[u8; 16]) -> Result<f32, String> {
+        let alive = self
+            .get_combat_stats(ulid)
+            .is_some_and(|stats| stats.hp > 0.0)
+            && !self
+                .get_behavioral_state(ulid)
+                .is_some_and(|state| state.contains(NPCState::DEAD));
+        if !alive {
+            return Err(format!("NPC {} is gone or dead", &bytes_to_hex(ulid)[..16]));
+        }
+        if self.food_stockpile() == 0 {
+            return Err("the food stockpile is empty".to_string());
+        }
+        self.record_input(RecordedInput::FeedNpc { ulid: *ulid });
+
+        self.take_food();
+        Ok(self.change_hunger(ulid, FOOD_NUTRITION).unwrap_or_default())
+    }
+
+    /// Multipliers for an NPC's hunger level
+    pub(super) fn hunger_modifiers(&self, ulid: &[u8; 16]) -> StatModifiers {
+        self.hunger_level(ulid)
+            .map_or_else(StatModifiers::default, |level| level.modifiers())
+    }
+
+    /// Queue a Level event if `before` differs from the NPC's current level
+    pub(super) fn push_hunger_level_change(
+        &self,
+        ulid: &[u8; 16],
+        before: HungerLevel,
+        stats: &NPCCombatStats,
+    ) {
+        let level = HungerLevel::of_stats(stats);
+        if level == before {
+            return;
+        }
+        sim_print!(
+            "[HUNGER] NPC {} is now {} ({:.1}/{:.1})",
+            &bytes_to_hex(ulid)[..8],
+            level.as_str(),
+            stats.hunger,
+            stats.max_hunger
+        );
+        self.hunger_events.push(HungerEvent::Level {
+            ulid: *ulid,
+            level,
+            hunger: stats.hunger,
+            max_hunger: stats.max_hunger,
+        });
+    }
+
+    /// Decay hunger for the time since the last step, let hungry kingdom NPCs
+    /// eat and starving ones lose HP
+    /// Returns Death events for NPCs that starved
+    pub(super) fn run_hunger(&self, npcs: &mut NpcTable) -> Vec<CombatEvent> {
+        let mut events = Vec::new();
+        let now_ms = self.get_current_time_ms();
+        let last_ms = self.last_hunger_step_ms.load(Ordering::Relaxed);
+        // First step (or the clock went back) - start counting from now
+        if last_ms == 0 || now_ms < last_ms {
+            self.last_hunger_step_ms.store(now_ms, Ordering::Relaxed);
+            return events;
+        }
+        let elapsed_ms = now_ms - last_ms;
+        if elapsed_ms < HUNGER_STEP_INTERVAL_MS {
+            return events;
+        }
+        self.last_hunger_step_ms.store(now_ms, Ordering::Relaxed);
+        let seconds = elapsed_ms as f32 / 1000.0;
+
+        // Living NPCs registered for combat, sorted by ULID
+        for row in npcs.rows() {
+            let Some(stats) = self.get_combat_stats(&row.ulid) else {
+                continue;
+            };
+            let dead = npcs.health(&row.ulid).is_none_or(|health| health.hp <= 0.0)
+                || npcs
+                    .behavior(&row.ulid)
+                    .is_none_or(|state| state.contains(NPCState::DEAD));
+            if dead {
+                continue;
+            }
+
+            let mut hunger = stats.hunger;
+            if stats.hunger_decay > 0.0 {
+                let Some(decayed) = self.change_hunger(&row.ulid, -stats.hunger_decay * seconds)
+                else {
+                    continue;
+                };
+                hunger = decayed;
+            }
+
+            // Kingdom NPCs eat from the stockpile once they get hungry
+            let kingdom = !stats.static_flags().contains(NPCStaticState::MONSTER);
+            if kingdom
+                && HungerLevel::of(hunger, stats.max_hunger) != HungerLevel::Fed
+                && self.take_food()
+            {
+                hunger = self
+                    .change_hunger(&row.ulid, FOOD_NUTRITION)
+                    .unwrap_or(hunger);
+            }
+
+            if HungerLevel::of(hunger, stats.max_hunger) != HungerLevel::Starving {
+                continue;
+            }
+            let damage = STARVATION_DAMAGE_PER_SEC * seconds;
+            if self.apply_damage(npcs, &row.ulid, damage) > 0.0 {
+                continue;
+            }
+
+            sim_print!("[HUNGER] NPC {} starved", &bytes_to_hex(&row.ulid)[..8]);
+            self.mark_dead(npcs, &row.ulid);
+            let target_pos = npcs.position(&row.ulid).unwrap_or_default();
+            events.push(CombatEvent::Death {
+                attacker: row.ulid,
+                target: row.ulid,
+                amount: damage,
+                target_pos,
+            });
+        }
+
+        events
+    }
+
+    /// Add to (or take from) an NPC's hunger, clamped to [0, max_hunger]
+    /// Returns the new hunger
+    fn change_hunger(&self, ulid: &[u8; 16], amount: f32) -> Option<f32> {
+        let (before, stats) = {
+            let mut stats = self.npc_combat_stats.get_mut(ulid)?;
+            let before = HungerLevel::of_stats(&stats);
+            stats.hunger = (stats.hunger + amount).clamp(0.0, stats.max_hunger.max(0.0));
+            (before, *stats)
+        };
+        self.push_hunger_level_change(ulid, before, &stats);
+        Some(stats.hunger)
+    }
+
+    /// Take one unit from the stockpile - false if it is empty
+    fn take_food(&self) -> bool {
+        let taken =
+            self.food_stockpile
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |food| {
+                    food.checked_sub(1)
+                });
+        match taken {
+            Ok(food) => {
+                self.hunger_events
+                    .push(HungerEvent::Stockpile { food: food - 1 });
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Replace the stockpile total (add_food, snapshot restore)
+    pub(super) fn set_food_stockpile(&self, food: u32) {
+        if self.food_stockpile.swap(food, Ordering::Relaxed) != food {
+            self.hunger_events.push(HungerEvent::Stockpile { food });
+        }
+    }
+}
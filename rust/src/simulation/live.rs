@@ -0,0 +1,85 @@
+// ============================================================================
+// LIVE STATE - HP, position, behavior and last attack of each NPC
+// ============================================================================
+// These four change every tick. Between ticks they live in the warehouse maps,
+// where the host reads and changes them. During a tick they live in the ECS
+// components (Health, Position, Behavior, AttackCooldown): the tick loads them
+// once, the phase systems change them through their queries, and the changed
+// ones go back to the warehouse once the movement phase is done (see ecs.rs).
+//
+// Code that runs on both sides - a hit from the combat phase or from an arrow
+// the host reports, a death from a hit or from starving - takes
+// `&mut impl LiveState` and works on whichever it is handed: the phase's
+// NpcTable during a tick, the warehouse's `Stored` view otherwise.
+
+use bevy::math::Vec2;
+
+use super::ecs::Health;
+use super::stats::NPCState;
+use super::warehouse::NPCDataWarehouse;
+
+/// Where an NPC's live state is read and changed
+pub(crate) trait LiveState {
+    /// HP and max HP (None if the NPC isn't registered for combat)
+    fn health(&self, ulid: &[u8; 16]) -> Option<Health>;
+    fn set_hp(&mut self, ulid: &[u8; 16], hp: f32);
+
+    fn position(&self, ulid: &[u8; 16]) -> Option<Vec2>;
+    fn set_position(&mut self, ulid: &[u8; 16], pos: Vec2);
+
+    fn behavior(&self, ulid: &[u8; 16]) -> Option<NPCState>;
+    fn set_behavior(&mut self, ulid: &[u8; 16], state: NPCState);
+
+    /// Simulation time (ms) of the NPC's last attack or heal cast
+    fn last_attack_ms(&self, ulid: &[u8; 16]) -> Option<u64>;
+    fn set_last_attack_ms(&mut self, ulid: &[u8; 16], now_ms: u64);
+}
+
+/// The warehouse maps - the live state between ticks
+pub(crate) struct Stored<'a>(&'a NPCDataWarehouse);
+
+impl NPCDataWarehouse {
+    /// Live state as stored between ticks (host calls, timers)
+    pub(super) fn stored(&self) -> Stored<'_> {
+        Stored(self)
+    }
+}
+
+impl LiveState for Stored<'_> {
+    fn health(&self, ulid: &[u8; 16]) -> Option<Health> {
+        self.0.npc_combat_stats.get(ulid).map(|stats| Health {
+            hp: stats.hp,
+            max_hp: stats.max_hp,
+        })
+    }
+
+    fn set_hp(&mut self, ulid: &[u8; 16], hp: f32) {
+        if let Some(mut stats) = self.0.npc_combat_stats.get_mut(ulid) {
+            stats.hp = hp;
+        }
+    }
+
+    fn position(&self, ulid: &[u8; 16]) -> Option<Vec2> {
+        self.0.get_npc_position_internal(ulid)
+    }
+
+    fn set_position(&mut self, ulid: &[u8; 16], pos: Vec2) {
+        self.0.npc_positions.insert(*ulid, pos);
+    }
+
+    fn behavior(&self, ulid: &[u8; 16]) -> Option<NPCState> {
+        self.0.get_behavioral_state(ulid)
+    }
+
+    fn set_behavior(&mut self, ulid: &[u8; 16], state: NPCState) {
+        self.0.npc_behavioral_state.insert(*ulid, state);
+    }
+
+    fn last_attack_ms(&self, ulid: &[u8; 16]) -> Option<u64> {
+        self.0.npc_cooldown.get(ulid).map(|v| *v.value())
+    }
+
+    fn set_last_attack_ms(&mut self, ulid: &[u8; 16], now_ms: u64) {
+        self.0.npc_cooldown.insert(*ulid, now_ms);
+    }
+}
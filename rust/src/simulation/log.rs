@@ -0,0 +1,57 @@
+// ============================================================================
+// SIMULATION LOGGING - Pluggable sink so the core never depends on an engine
+// ============================================================================
+// Hosts install a sink once at startup (the Godot layer forwards to
+// godot_print!/godot_warn!/godot_error!). Without a sink, log lines are
+// dropped - headless runs stay quiet.
+
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Severity of a simulation log line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+/// Log sink signature - receives the level and the formatted message
+pub type LogSink = fn(LogLevel, &str);
+
+static LOG_SINK: OnceLock<LogSink> = OnceLock::new();
+
+/// Install the log sink (first caller wins, later calls are ignored)
+pub fn set_log_sink(sink: LogSink) {
+    let _ = LOG_SINK.set(sink);
+}
+
+/// Forward a log line to the installed sink (no-op without one)
+pub fn emit(level: LogLevel, args: fmt::Arguments) {
+    if let Some(sink) = LOG_SINK.get() {
+        sink(level, &args.to_string());
+    }
+}
+
+/// Info-level log (replaces godot_print! inside the core)
+macro_rules! sim_print {
+    ($($arg:tt)*) => {
+        $crate::simulation::log::emit($crate::simulation::log::LogLevel::Info, format_args!($($arg)*))
+    };
+}
+
+/// Warning-level log (replaces godot_warn! inside the core)
+macro_rules! sim_warn {
+    ($($arg:tt)*) => {
+        $crate::simulation::log::emit($crate::simulation::log::LogLevel::Warn, format_args!($($arg)*))
+    };
+}
+
+/// Error-level log (replaces godot_error! inside the core)
+macro_rules! sim_error {
+    ($($arg:tt)*) => {
+        $crate::simulation::log::emit($crate::simulation::log::LogLevel::Error, format_args!($($arg)*))
+    };
+}
+
+pub(crate) use {sim_error, sim_print, sim_warn};
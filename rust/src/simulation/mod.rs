@@ -0,0 +1,64 @@
+//! Engine-independent NPC simulation core
+//!
+//! Combat, movement, spawning decisions and stats live here with no Godot
+//! dependency, so a full battle can run headless (`--no-default-features`).
+//! The Godot node layer in `npc_data_warehouse` sits on top of this module.
+
+pub mod archetypes;
+pub mod clock;
+pub mod damage;
+pub mod ecs;
+pub mod effects;
+pub mod emotions;
+pub mod events;
+pub mod factions;
+pub mod frame;
+pub mod hunger;
+pub mod log;
+pub mod pools;
+pub mod query;
+pub mod regen;
+pub mod replay;
+pub mod resources;
+pub mod runner;
+pub mod snapshot;
+pub mod squads;
+pub mod stats;
+pub mod threat;
+pub mod timers;
+pub mod warehouse;
+
+mod combat;
+mod healing;
+mod live;
+mod movement;
+mod spatial;
+mod spawning;
+mod timestep;
+
+pub use archetypes::{ArchetypeRegistry, CombatType, Faction, NPCArchetype};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use damage::{DamageBreakdown, DamageConfig, DamageModifier, DamageType, Resistances};
+pub use ecs::SimulationWorld;
+pub use effects::{ActiveEffect, Stacking, StatModifiers, StatusEffectDef, StatusEffectEvent};
+pub use emotions::{Emotion, EmotionEvent, EmotionScores};
+pub use events::{CombatEvent, ProjectileKind, ARROW_SPEED};
+pub use factions::{FactionDef, FactionEvent, FactionId, FactionTable, Relation};
+pub use frame::{NpcFrame, SimFrame};
+pub use hunger::{HungerEvent, HungerLevel};
+pub use log::{set_log_sink, LogLevel, LogSink};
+pub use pools::{PoolDefinition, PoolOverflow};
+pub use query::NpcFilter;
+pub use replay::{Divergence, InputRecording, RecordedInput, ReplayReport};
+pub use resources::ResourceEvent;
+pub use runner::{SimulationRunner, DEFAULT_TICK_HZ};
+pub use snapshot::{
+    EffectSnapshot, HealChannelSnapshot, NpcSnapshot, QueuedSpawnSnapshot, RegenSnapshot,
+    RespawnSnapshot, SquadMemberSnapshot, SquadSnapshot, ThreatSnapshot, WarehouseSnapshot,
+};
+pub use squads::{Formation, Squad, SquadEvent};
+pub use stats::{bytes_to_hex, NPCCombatStats, NPCState, NPCStaticState, NPCStats, UlidBytes};
+pub use threat::TargetStrategy;
+pub use timers::{TimerAction, TimerHandle};
+pub use timestep::{FIXED_STEP_HZ, MAX_TIME_SCALE};
+pub use warehouse::{NPCDataWarehouse, NodeCommand};
@@ -0,0 +1,430 @@
+// ============================================================================
+// MOVEMENT PHASE - Wandering, pursuit, squads and waypoint movement
+// ============================================================================
+
+use bevy::math::Vec2;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::archetypes::DEFAULT_MOVE_SPEED;
+use super::ecs::NpcTable;
+use super::emotions::FLEE_DISTANCE;
+use super::factions::FactionId;
+use super::live::LiveState;
+use super::log::sim_print;
+use super::spatial::{SpatialGrid, GRID_CELL_SIZE};
+use super::stats::{bytes_to_hex, NPCState, NPCStaticState};
+use super::threat::TARGET_ACQUIRE_RANGE;
+use super::timers::{NpcTimer, TimerAction};
+use super::warehouse::{NPCDataWarehouse, NpcRow};
+
+/// Minimum time between two wander waypoints (ms)
+const WANDER_COOLDOWN_MS: u64 = 3000;
+
+/// Distance ranged attackers keep from the nearest hostile
+const RANGED_SAFE_DISTANCE: f32 = 100.0;
+
+/// Distance healers keep from the nearest hostile (they never close in)
+const HEALER_SAFE_DISTANCE: f32 = 200.0;
+
+impl NPCDataWarehouse {
+    /// PHASE 2: MOVEMENT - Handle wandering, calculate directions, update positions
+    /// This phase ONLY handles movement and position updates
+    /// Spawning runs as separate systems around it (see ecs.rs)
+    pub(super) fn run_movement_phase(&self, npcs: &mut NpcTable, delta: f32) {
+        // Living NPCs registered for combat, sorted by ULID
+        let rows = npcs.rows();
+        if rows.is_empty() {
+            return;
+        }
+
+        // 1. Handle idle wandering (NPCs that are IDLE and not in combat will get random waypoints)
+        self.handle_idle_wandering(&rows, npcs);
+
+        // 2. Calculate movement directions for all NPCs (pursue each NPC's target)
+        self.calculate_movement_directions(&rows, npcs);
+
+        // 3. March squads and keep their members in formation (see squads.rs)
+        self.run_squads(npcs, delta);
+
+        // 4. Apply waypoint movement (move NPCs towards their waypoints)
+        self.apply_waypoint_movement(&rows, npcs, delta);
+    }
+
+    /// Handle idle wandering for NPCs that are IDLE and not in COMBAT
+    /// Sets random waypoints within world bounds for NPCs to wander around
+    fn handle_idle_wandering(&self, rows: &[NpcRow], npcs: &mut NpcTable) {
+        use rand::Rng;
+        let mut rng = self.rng();
+        let now_ms = self.get_current_time_ms();
+
+        // Load world bounds
+        let min_x = f32::from_bits(self.world_min_x.load(Ordering::Relaxed));
+        let max_x = f32::from_bits(self.world_max_x.load(Ordering::Relaxed));
+        let min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
+        let max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
+        let factions = self.faction_table();
+        // Map a fraction of the world width to an x coordinate
+        let at = |t: f32| {
+            if t <= 0.0 {
+                min_x
+            } else if t >= 1.0 {
+                max_x
+            } else {
+                min_x + (max_x - min_x) * t
+            }
+        };
+
+        for NpcRow {
+            ulid: ulid_bytes,
+            static_state,
+            faction,
+            ..
+        } in rows
+        {
+            // Skip if scheduled for despawn (check first - most important)
+            if self
+                .npc_timer_due_ms(ulid_bytes, NpcTimer::Despawn)
+                .is_some()
+            {
+                // Scheduled for despawn - skip all processing
+                continue;
+            }
+
+            // Get current behavioral state (the row may be stale after combat phase)
+            let behavioral_state = npcs.behavior(ulid_bytes).unwrap_or(NPCState::IDLE);
+
+            // Skip if DEAD
+            if behavioral_state.contains(NPCState::DEAD) {
+                continue;
+            }
+
+            // Squad members hold their slot instead (see squads.rs)
+            if self.npc_squads.contains_key(ulid_bytes) {
+                continue;
+            }
+
+            // Only wander if IDLE and NOT in COMBAT
+            let is_idle = behavioral_state.contains(NPCState::IDLE);
+            let in_combat = behavioral_state.contains(NPCState::COMBAT);
+            let is_passive = static_state.contains(NPCStaticState::PASSIVE);
+
+            if is_idle && !in_combat && !is_passive {
+                // Check if NPC already has a waypoint
+                let has_waypoint = self.npc_waypoints.contains_key(ulid_bytes);
+
+                // Check wander cooldown (don't wander too frequently)
+                // Can wander if current time is past the cooldown time
+                let can_wander = self
+                    .npc_timer_due_ms(ulid_bytes, NpcTimer::WanderCooldown)
+                    .is_none_or(|cooldown_until_ms| now_ms >= cooldown_until_ms);
+
+                if !has_waypoint && can_wander {
+                    // Faction wander zone (by default allies on the left, monsters on the right)
+                    let (wander_min_x, wander_max_x) = match factions.wander_zone(*faction) {
+                        Some((from, to)) => (at(from), at(to)),
+                        // No zone - full bounds
+                        None => (min_x, max_x),
+                    };
+
+                    // Generate random waypoint within faction bounds
+                    let target_x = rng.random_range(wander_min_x..wander_max_x);
+                    let target_y = rng.random_range(min_y..max_y);
+
+                    self.npc_waypoints
+                        .insert(*ulid_bytes, Vec2::new(target_x, target_y));
+
+                    // Update wander cooldown
+                    self.schedule_timer(
+                        WANDER_COOLDOWN_MS,
+                        TimerAction::EndWanderCooldown { ulid: *ulid_bytes },
+                    );
+
+                    // Set state to WALKING (remove IDLE, add WALKING, keep other flags like COMBAT if present)
+                    let new_state = (behavioral_state - (NPCState::IDLE | NPCState::ATTACKING))
+                        | NPCState::WALKING;
+                    sim_print!(
+                        "[RUST WANDER] ULID {} - Setting waypoint: old_state={}, new_state={}",
+                        bytes_to_hex(ulid_bytes),
+                        behavioral_state.bits(),
+                        new_state.bits()
+                    );
+                    npcs.set_behavior(ulid_bytes, new_state);
+                }
+            }
+        }
+    }
+
+    /// Calculate movement directions for all NPCs (pursue each NPC's target)
+    /// Writes the resulting waypoint and COMBAT state for each NPC
+    fn calculate_movement_directions(&self, npcs: &[NpcRow], live: &mut NpcTable) {
+        // Read bounds atomically (can be updated by GDScript from BackgroundManager)
+        let min_x = f32::from_bits(self.world_min_x.load(Ordering::Relaxed));
+        let max_x = f32::from_bits(self.world_max_x.load(Ordering::Relaxed));
+        let min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
+        let max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
+        let clamp_to_world =
+            |x: f32, y: f32| Vec2::new(x.clamp(min_x, max_x), y.clamp(min_y, max_y));
+
+        // Broadphase for nearest-hostile lookups
+        let grid = SpatialGrid::build(npcs, GRID_CELL_SIZE);
+        let factions = self.faction_table();
+
+        // Distinct living factions present - lets NPCs with no possible enemy
+        // skip the grid search entirely (e.g. between waves)
+        let mut present_factions: Vec<FactionId> = Vec::new();
+        for row in npcs {
+            if !row.state.contains(NPCState::DEAD) && !present_factions.contains(&row.faction) {
+                present_factions.push(row.faction);
+            }
+        }
+
+        // preferred_targets per archetype, looked up once per phase (see threat.rs)
+        let mut preferences: HashMap<String, Vec<String>> = HashMap::new();
+
+        for (
+            index,
+            NpcRow {
+                ulid: ulid_bytes_a,
+                pos: pos_a,
+                static_state: static_state_a,
+                faction: faction_a,
+                state: behavioral_state_a,
+                attack_range: range_a,
+            },
+        ) in npcs.iter().enumerate()
+        {
+            // Skip if dead
+            if behavioral_state_a.contains(NPCState::DEAD) {
+                continue;
+            }
+
+            // Skip if PASSIVE
+            if static_state_a.contains(NPCStaticState::PASSIVE) {
+                continue;
+            }
+
+            // Healers and frightened NPCs keep away from the nearest hostile;
+            // everyone else goes after the target their strategy picks (see threat.rs)
+            let is_healer = static_state_a.contains(NPCStaticState::HEALER);
+            let fleeing = self.is_frightened(ulid_bytes_a);
+            let holds_back = is_healer || fleeing;
+
+            let hostile_present = present_factions
+                .iter()
+                .any(|faction_b| factions.is_hostile(*faction_a, *faction_b));
+            let target = if !hostile_present {
+                None
+            } else if holds_back {
+                grid.nearest(*pos_a, |j| {
+                    let b = &npcs[j];
+                    b.ulid != *ulid_bytes_a // Skip self
+                        && !b.state.contains(NPCState::DEAD)
+                        && factions.is_hostile(*faction_a, b.faction)
+                })
+            } else {
+                self.pick_target(npcs, live, &grid, &factions, index, &mut preferences)
+            };
+            let pursued = target.map(|(j, distance)| (npcs[j].pos.x, npcs[j].pos.y, distance));
+            match target {
+                Some((j, distance)) if distance <= TARGET_ACQUIRE_RANGE => {
+                    self.npc_targets.insert(*ulid_bytes_a, npcs[j].ulid);
+                }
+                _ => {
+                    self.npc_targets.remove(ulid_bytes_a);
+                }
+            }
+
+            let current_state = *behavioral_state_a;
+
+            if let Some((target_x, target_y, distance)) = pursued {
+                // COMBAT ENGAGEMENT RANGE: Only enter combat if enemy is within 400px
+                // This prevents NPCs from permanently being in combat state when enemies are far away
+                if distance > TARGET_ACQUIRE_RANGE {
+                    // Enemy too far - clear combat state and let idle wandering take over
+                    if let Some(state) = live.behavior(ulid_bytes_a) {
+                        live.set_behavior(ulid_bytes_a, state - NPCState::COMBAT);
+                    }
+                    continue; // Skip movement calculation for distant enemies
+                }
+
+                // Update behavioral state to COMBAT only (remove IDLE)
+                // WALKING will be set in apply_waypoint_movement when actually moving
+                let pursuing_state = (current_state - NPCState::IDLE) | NPCState::COMBAT;
+
+                // RANGED units (archers) use kiting behavior, healers back off further
+                // and hold position instead of closing in, frightened NPCs flee
+                // (see emotions.rs)
+                if holds_back || static_state_a.contains(NPCStaticState::RANGED) {
+                    let min_safe_distance = if fleeing {
+                        FLEE_DISTANCE
+                    } else if is_healer {
+                        HEALER_SAFE_DISTANCE
+                    } else {
+                        RANGED_SAFE_DISTANCE
+                    };
+
+                    if distance < min_safe_distance {
+                        // TOO CLOSE - Retreat away from enemy (kiting)
+                        // Calculate retreat position: move away from target
+                        let dir_x = pos_a.x - target_x;
+                        let dir_y = pos_a.y - target_y;
+                        let dir_len = (dir_x * dir_x + dir_y * dir_y).sqrt();
+
+                        if dir_len > 0.01 {
+                            // Normalize and scale to retreat distance
+                            let retreat_distance = 150.0; // Retreat 150 pixels away
+                            let retreat_x = pos_a.x + (dir_x / dir_len) * retreat_distance;
+                            let retreat_y = pos_a.y + (dir_y / dir_len) * retreat_distance;
+
+                            // Store retreat waypoint (clamped to prevent NPCs from going off-screen)
+                            self.npc_waypoints
+                                .insert(*ulid_bytes_a, clamp_to_world(retreat_x, retreat_y));
+                            live.set_behavior(ulid_bytes_a, pursuing_state);
+                        }
+                    } else if distance > *range_a && !holds_back {
+                        // TOO FAR - Move toward target to get in range
+                        self.npc_waypoints
+                            .insert(*ulid_bytes_a, clamp_to_world(target_x, target_y));
+                        live.set_behavior(ulid_bytes_a, pursuing_state);
+                    } else {
+                        // OPTIMAL RANGE (100-200px) - Stop and shoot (healers: hold and heal)
+                        self.npc_waypoints.remove(ulid_bytes_a);
+
+                        // Update behavioral state to COMBAT only (remove WALKING, remove IDLE)
+                        let new_state =
+                            (current_state - NPCState::IDLE - NPCState::WALKING) | NPCState::COMBAT;
+                        live.set_behavior(ulid_bytes_a, new_state);
+                    }
+                } else {
+                    // MELEE/MAGIC units: Simple pursue behavior (original logic)
+                    if distance > *range_a {
+                        // Move toward target (clamp to world bounds)
+                        self.npc_waypoints
+                            .insert(*ulid_bytes_a, clamp_to_world(target_x, target_y));
+                        live.set_behavior(ulid_bytes_a, pursuing_state);
+                    } else {
+                        // In range - stop moving
+                        self.npc_waypoints.remove(ulid_bytes_a);
+
+                        // Update behavioral state: IDLE | COMBAT (remove WALKING)
+                        let new_state =
+                            (current_state - NPCState::WALKING) | NPCState::IDLE | NPCState::COMBAT;
+
+                        if current_state.contains(NPCState::WALKING) {
+                            static MELEE_IN_RANGE_LOG: AtomicU64 = AtomicU64::new(0);
+                            if MELEE_IN_RANGE_LOG.fetch_add(1, Ordering::Relaxed) < 10 {
+                                sim_print!("[COMBAT MOVEMENT] ULID {} - Melee in attack range, removing WALKING: {} -> {}",
+                                    bytes_to_hex(ulid_bytes_a), current_state.bits(), new_state.bits());
+                            }
+                        }
+
+                        live.set_behavior(ulid_bytes_a, new_state);
+                    }
+                }
+            } else {
+                // No enemies - only clear COMBAT state, don't touch idle wandering waypoints!
+                // Idle wandering waypoints are managed by handle_idle_wandering()
+                // We should only clear combat-related state here
+
+                // Only modify state if NPC was in COMBAT
+                if current_state.contains(NPCState::COMBAT) {
+                    // Remove COMBAT flag, but keep WALKING/IDLE as-is (for idle wandering)
+                    let mut new_state = current_state - NPCState::COMBAT;
+
+                    // IMPORTANT: If no movement state flags are set, add IDLE to prevent NONE state
+                    // This prevents archers/ranged units from going into NONE state after combat
+                    if !new_state
+                        .intersects(NPCState::WALKING | NPCState::IDLE | NPCState::ATTACKING)
+                    {
+                        new_state |= NPCState::IDLE;
+                    }
+
+                    live.set_behavior(ulid_bytes_a, new_state);
+                }
+                // If not in combat, leave state alone (might be idle wandering with WALKING state)
+            }
+        }
+    }
+
+    /// Apply waypoint movement - move NPCs towards their waypoints
+    /// Called every combat tick with delta time
+    /// Only the simulated position is updated; host layers interpolate their nodes toward it
+    /// Speed (pixels per second) comes from the NPC's archetype
+    fn apply_waypoint_movement(&self, rows: &[NpcRow], npcs: &mut NpcTable, delta_time: f32) {
+        // Load world bounds for clamping
+        let world_min_x = f32::from_bits(self.world_min_x.load(Ordering::Relaxed));
+        let world_max_x = f32::from_bits(self.world_max_x.load(Ordering::Relaxed));
+        let world_min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
+        let world_max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
+
+        for NpcRow {
+            ulid: ulid_bytes, ..
+        } in rows
+        {
+            // Get current position (the row may be stale after idle wandering)
+            let current = match npcs.position(ulid_bytes) {
+                Some(pos) => pos,
+                None => continue,
+            };
+
+            // Get waypoint (nothing to do if the NPC has none)
+            let waypoint = match self.npc_waypoints.get(ulid_bytes).map(|v| *v.value()) {
+                Some(waypoint) => waypoint,
+                None => continue,
+            };
+
+            // Stunned NPCs stay put until the stun wears off
+            if self.is_stunned(ulid_bytes) {
+                continue;
+            }
+
+            // Calculate direction to waypoint
+            let delta = waypoint - current;
+            let distance = delta.length();
+
+            if distance > 1.0 {
+                // Calculate target position with smooth movement (slows apply)
+                let move_speed = self
+                    .npc_combat_stats
+                    .get(ulid_bytes)
+                    .map_or(DEFAULT_MOVE_SPEED, |stats| stats.move_speed)
+                    * self.status_modifiers(ulid_bytes).move_speed;
+                let move_distance = move_speed * delta_time;
+                let move_ratio = (move_distance / distance).min(1.0);
+                let unclamped = current + delta * move_ratio;
+
+                // Clamp position to world bounds to prevent NPCs from leaving viewport
+                let target_x = unclamped.x.clamp(world_min_x, world_max_x);
+                let target_y = unclamped.y.clamp(world_min_y, world_max_y);
+
+                // Store normalized movement direction for sprite flipping
+                self.npc_move_directions
+                    .insert(*ulid_bytes, delta / distance);
+
+                // Update the NPC's position
+                npcs.set_position(ulid_bytes, Vec2::new(target_x, target_y));
+
+                // Set WALKING state since NPC is actually moving
+                // CRITICAL: Remove IDLE when adding WALKING (mutually exclusive)
+                if let Some(state) = npcs.behavior(ulid_bytes) {
+                    if !state.contains(NPCState::WALKING) {
+                        npcs.set_behavior(ulid_bytes, (state - NPCState::IDLE) | NPCState::WALKING);
+                    }
+                }
+            } else {
+                // Reached waypoint! Clear it and movement direction
+                self.npc_waypoints.remove(ulid_bytes);
+                self.npc_move_directions.remove(ulid_bytes);
+
+                // Set state to IDLE (remove WALKING and ATTACKING flags, add IDLE, keep other flags like COMBAT)
+                if let Some(state) = npcs.behavior(ulid_bytes) {
+                    npcs.set_behavior(
+                        ulid_bytes,
+                        (state - (NPCState::WALKING | NPCState::ATTACKING)) | NPCState::IDLE,
+                    );
+                }
+            }
+        }
+    }
+}
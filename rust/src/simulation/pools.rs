@@ -0,0 +1,526 @@
+// ============================================================================
+// POOL DEFINITIONS - Capacity and overflow policy per NPC type
+// ============================================================================
+// Every pool has a typed definition: the most NPCs of that type that may
+// exist (pooled slots, and so host nodes) and what happens to a spawn request
+// when none can be used:
+//   reject - the request is dropped (spawn returns None)
+//   queue  - the request waits and runs as soon as an NPC of that type is
+//            despawned (at most max_size requests wait per type)
+//
+// Slots are never created past max_size, nor past the node budget shared by
+// every pool (0 = no budget). Pools initialized without a definition get one
+// sized to their first initialization.
+//
+// Each pool also has a warm/idle policy:
+//   min_warm        - pooled slots to keep ready; hosts grow the pool toward it
+//                     a few slots per frame (see pools_to_warm) instead of
+//                     instancing a whole wave's worth of nodes at once
+//   max_idle        - pooled slots to keep once the pool has held more than
+//                     this for idle_timeout_ms; the oldest idle slots are freed
+//                     in the cleanup phase and hosts drop their nodes on
+//                     NodeCommand::Freed (null = never shrink)
+//
+// Definitions serialize as:
+//   { "npc_type": "goblin", "max_size": 10, "scene_path": "res://...",
+//     "overflow": "queue", "min_warm": 8, "max_idle": 12,
+//     "idle_timeout_ms": 30000 }
+// Live counts (current_active, available, queued) are filled in by get_pool().
+
+use bevy::math::Vec2;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::Ordering;
+
+use super::log::{sim_print, sim_warn};
+use super::replay::RecordedInput;
+use super::warehouse::{NPCDataWarehouse, NodeCommand};
+
+/// How long a pool may hold more than max_idle slots before it shrinks
+pub const DEFAULT_IDLE_TIMEOUT_MS: u64 = 30_000;
+
+fn default_idle_timeout_ms() -> u64 {
+    DEFAULT_IDLE_TIMEOUT_MS
+}
+
+/// What to do with a spawn request when the pool is at capacity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PoolOverflow {
+    /// Drop the request
+    #[default]
+    Reject,
+    /// Hold the request until an NPC of that type is despawned
+    Queue,
+}
+
+impl PoolOverflow {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PoolOverflow::Reject => "reject",
+            PoolOverflow::Queue => "queue",
+        }
+    }
+
+    /// Parse "reject" / "queue"
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "reject" => Some(PoolOverflow::Reject),
+            "queue" => Some(PoolOverflow::Queue),
+            _ => None,
+        }
+    }
+}
+
+/// Pool definition for an NPC type
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PoolDefinition {
+    pub npc_type: String,
+    /// Most NPCs of this type that may exist (pooled slots)
+    pub max_size: i32,
+    #[serde(default)]
+    pub scene_path: String,
+    #[serde(default)]
+    pub overflow: PoolOverflow,
+
+    /// Pooled slots to keep ready for spawns (grown a few per frame by the host)
+    #[serde(default)]
+    pub min_warm: i32,
+    /// Pooled slots kept once the excess has sat idle for idle_timeout_ms
+    /// (None keeps every slot)
+    #[serde(default)]
+    pub max_idle: Option<i32>,
+    #[serde(default = "default_idle_timeout_ms")]
+    pub idle_timeout_ms: u64,
+
+    /// Live counts, filled in by `get_pool` (ignored when registering)
+    #[serde(default)]
+    pub current_active: i32,
+    #[serde(default)]
+    pub available: i32,
+    #[serde(default)]
+    pub queued: i32,
+}
+
+impl PoolDefinition {
+    pub fn new(npc_type: &str, max_size: i32, scene_path: &str) -> Self {
+        Self {
+            npc_type: npc_type.to_string(),
+            max_size,
+            scene_path: scene_path.to_string(),
+            overflow: PoolOverflow::default(),
+            min_warm: 0,
+            max_idle: None,
+            idle_timeout_ms: DEFAULT_IDLE_TIMEOUT_MS,
+            current_active: 0,
+            available: 0,
+            queued: 0,
+        }
+    }
+
+    /// Serialize to JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Deserialize from JSON string
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("invalid pool definition JSON: {}", e))
+    }
+}
+
+impl NPCDataWarehouse {
+    /// Register (or update) the pool definition for an NPC type
+    /// An existing overflow and warm/idle policy is kept
+    pub fn register_pool(&self, npc_type: &str, max_size: i32, scene_path: &str) {
+        let mut definition = PoolDefinition::new(npc_type, max_size.max(0), scene_path);
+        if let Some(existing) = self.pool_definitions.get(npc_type) {
+            definition.overflow = existing.overflow;
+            definition.min_warm = existing.min_warm;
+            definition.max_idle = existing.max_idle;
+            definition.idle_timeout_ms = existing.idle_timeout_ms;
+        }
+        self.record_input(RecordedInput::RegisterPool {
+            definition: definition.clone(),
+        });
+        self.store_pool_definition(definition);
+    }
+
+    /// Register a pool definition from its JSON object
+    pub fn register_pool_json(&self, json: &str) -> Result<(), String> {
+        let mut definition = PoolDefinition::from_json(json)?;
+        if definition.npc_type.is_empty() {
+            return Err("pool definition has no npc_type".to_string());
+        }
+        definition.max_size = definition.max_size.max(0);
+        self.record_input(RecordedInput::RegisterPool {
+            definition: definition.clone(),
+        });
+        self.store_pool_definition(definition);
+        Ok(())
+    }
+
+    /// Store a pool definition without recording
+    pub(super) fn store_pool_definition(&self, definition: PoolDefinition) {
+        let slots = self.pool_slot_count(&definition.npc_type);
+        if slots > definition.max_size as usize {
+            sim_warn!(
+                "[RUST POOL] Pool '{}' already has {} slots (max {}) - extra slots stay but no more spawn past the max",
+                definition.npc_type,
+                slots,
+                definition.max_size
+            );
+        }
+        sim_print!(
+            "NPCDataWarehouse: Registered pool '{}' (max: {}, scene: {}, overflow: {})",
+            definition.npc_type,
+            definition.max_size,
+            definition.scene_path,
+            definition.overflow.as_str()
+        );
+        self.pool_definitions
+            .insert(definition.npc_type.clone(), definition);
+    }
+
+    /// Set the overflow policy of a registered pool
+    /// Switching to reject drops the requests already waiting
+    pub fn set_pool_overflow(&self, npc_type: &str, overflow: PoolOverflow) -> bool {
+        let Some(mut definition) = self.pool_definitions.get_mut(npc_type) else {
+            sim_warn!(
+                "[RUST POOL] Cannot set overflow - no pool registered for {}",
+                npc_type
+            );
+            return false;
+        };
+        definition.overflow = overflow;
+        drop(definition);
+        self.record_input(RecordedInput::PoolOverflow {
+            npc_type: npc_type.to_string(),
+            overflow,
+        });
+        if overflow == PoolOverflow::Reject {
+            self.queued_spawns.remove(npc_type);
+        }
+        true
+    }
+
+    /// Set the warm/idle policy of a registered pool
+    /// `max_idle` None never shrinks the pool
+    pub fn set_pool_policy(
+        &self,
+        npc_type: &str,
+        min_warm: i32,
+        max_idle: Option<i32>,
+        idle_timeout_ms: u64,
+    ) -> bool {
+        let Some(mut definition) = self.pool_definitions.get_mut(npc_type) else {
+            sim_warn!(
+                "[RUST POOL] Cannot set policy - no pool registered for {}",
+                npc_type
+            );
+            return false;
+        };
+        definition.min_warm = min_warm.max(0);
+        definition.max_idle = max_idle.map(|max_idle| max_idle.max(0));
+        definition.idle_timeout_ms = idle_timeout_ms;
+        drop(definition);
+        self.record_input(RecordedInput::PoolPolicy {
+            npc_type: npc_type.to_string(),
+            min_warm,
+            max_idle,
+            idle_timeout_ms,
+        });
+        true
+    }
+
+    /// Cap the pooled slots of every type together (0 = no budget)
+    /// Existing slots past the budget stay, no new ones are created
+    pub fn set_node_budget(&self, budget: usize) {
+        self.record_input(RecordedInput::NodeBudget { budget });
+        self.node_budget.store(budget, Ordering::Relaxed);
+    }
+
+    /// Shared slot budget (0 = no budget)
+    pub fn node_budget(&self) -> usize {
+        self.node_budget.load(Ordering::Relaxed)
+    }
+
+    /// Slots (pooled + spawned) that exist across every type
+    pub fn total_pool_slots(&self) -> usize {
+        self.inactive_npcs
+            .iter()
+            .map(|pool| pool.value().len())
+            .sum::<usize>()
+            + self.active_npcs.len()
+    }
+
+    /// Pool definition for an NPC type, with live counts
+    pub fn get_pool(&self, npc_type: &str) -> Option<PoolDefinition> {
+        let mut definition = self.pool_definitions.get(npc_type)?.clone();
+        definition.current_active = self.active_count_for_type(npc_type) as i32;
+        definition.available = self.available_slot_count(npc_type) as i32;
+        definition.queued = self
+            .queued_spawns
+            .get(npc_type)
+            .map_or(0, |queue| queue.len()) as i32;
+        Some(definition)
+    }
+
+    /// Registered pool types, sorted
+    pub fn pool_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .pool_definitions
+            .iter()
+            .map(|e| e.key().clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Pooled slots of an NPC type, ready to spawn
+    fn available_slot_count(&self, npc_type: &str) -> usize {
+        self.inactive_npcs
+            .get(npc_type)
+            .map_or(0, |pool| pool.len())
+    }
+
+    /// Slots (pooled + spawned) that exist for an NPC type
+    pub(super) fn pool_slot_count(&self, npc_type: &str) -> usize {
+        self.available_slot_count(npc_type) + self.active_count_for_type(npc_type)
+    }
+
+    /// New slots the node budget still allows
+    fn node_budget_room(&self) -> usize {
+        match self.node_budget() {
+            0 => usize::MAX,
+            budget => budget.saturating_sub(self.total_pool_slots()),
+        }
+    }
+
+    /// How many of `requested` new slots fit under the pool's max_size and
+    /// the node budget. Hosts call this before instancing nodes for a pool
+    pub fn pool_room(&self, npc_type: &str, requested: usize) -> usize {
+        let max_size = self
+            .pool_definitions
+            .get(npc_type)
+            .map(|definition| definition.max_size.max(0) as usize);
+        let room = match max_size {
+            Some(max_size) => max_size
+                .saturating_sub(self.pool_slot_count(npc_type))
+                .min(requested),
+            // No definition yet - the first initialization sizes it
+            None => requested,
+        };
+        room.min(self.node_budget_room())
+    }
+
+    /// New slots each pool needs to reach its min_warm, at most `limit` in
+    /// total. Handed out one per pool in turn (by type name) so every pool
+    /// warms up together. Hosts instance this many nodes and pass them to
+    /// initialize_npc_pool
+    pub fn pools_to_warm(&self, limit: usize) -> Vec<(String, usize)> {
+        let wanted: Vec<(String, usize)> = self
+            .pool_names()
+            .into_iter()
+            .filter_map(|npc_type| {
+                let min_warm = self.pool_definitions.get(&npc_type)?.min_warm.max(0) as usize;
+                let missing = min_warm.saturating_sub(self.available_slot_count(&npc_type));
+                let room = self.pool_room(&npc_type, missing);
+                (room > 0).then_some((npc_type, room))
+            })
+            .collect();
+
+        let mut granted = vec![0; wanted.len()];
+        let mut remaining = limit.min(self.node_budget_room());
+        while remaining > 0 {
+            let mut any = false;
+            for (grant, (_, room)) in granted.iter_mut().zip(&wanted) {
+                if remaining > 0 && *grant < *room {
+                    *grant += 1;
+                    remaining -= 1;
+                    any = true;
+                }
+            }
+            if !any {
+                break;
+            }
+        }
+
+        wanted
+            .into_iter()
+            .zip(granted)
+            .filter(|(_, grant)| *grant > 0)
+            .map(|((npc_type, _), grant)| (npc_type, grant))
+            .collect()
+    }
+
+    /// Free the oldest pooled slots of pools that held more than max_idle
+    /// (and min_warm) slots for their whole idle_timeout_ms
+    /// Runs in the cleanup phase so replays shrink at the same ticks
+    pub(super) fn shrink_idle_pools(&self, now_ms: u64) {
+        for npc_type in self.pool_names() {
+            let policy = self.pool_definitions.get(&npc_type).and_then(|definition| {
+                let max_idle = definition.max_idle?;
+                let keep = max_idle.max(definition.min_warm).max(0) as usize;
+                Some((keep, definition.idle_timeout_ms))
+            });
+            let Some((keep, idle_timeout_ms)) = policy else {
+                self.pool_idle_since.remove(&npc_type);
+                continue;
+            };
+            if self.available_slot_count(&npc_type) <= keep {
+                self.pool_idle_since.remove(&npc_type);
+                continue;
+            }
+
+            let since = *self
+                .pool_idle_since
+                .entry(npc_type.clone())
+                .or_insert(now_ms);
+            if now_ms.saturating_sub(since) < idle_timeout_ms {
+                continue;
+            }
+            self.pool_idle_since.remove(&npc_type);
+
+            // Slots return to the back of the pool, so the front idled longest
+            let freed: Vec<[u8; 16]> = match self.inactive_npcs.get_mut(&npc_type) {
+                Some(mut pool) => {
+                    let excess = pool.len().saturating_sub(keep);
+                    pool.drain(..excess).collect()
+                }
+                None => continue,
+            };
+            for ulid in &freed {
+                self.forget_pool_slot(ulid);
+                self.push_node_command(NodeCommand::Freed { ulid: *ulid });
+            }
+            sim_print!(
+                "[RUST POOL] Pool '{}' shrank by {} idle slots (keeping {})",
+                npc_type,
+                freed.len(),
+                keep
+            );
+        }
+    }
+
+    /// Drop everything the core keeps for a pooled slot that is leaving its pool
+    pub(super) fn forget_pool_slot(&self, ulid: &[u8; 16]) {
+        self.npc_names.remove(ulid);
+        self.npc_types.remove(ulid);
+        self.npc_combat_stats.remove(ulid);
+        self.npc_behavioral_state.remove(ulid);
+        self.npc_cooldown.remove(ulid);
+        self.npc_positions.remove(ulid);
+        self.npc_factions.remove(ulid);
+        self.clear_status_effects(ulid);
+        self.cancel_heal_channel(ulid);
+        self.clear_emotions(ulid);
+        self.forget_regen(ulid);
+        self.clear_threat(ulid);
+        self.drop_from_squad(&self.stored(), ulid);
+    }
+
+    /// Clamp a pool initialization to its capacity, creating the definition
+    /// on first use. Returns how many slots to create
+    pub(super) fn reserve_pool_slots(&self, npc_type: &str, requested: usize) -> usize {
+        if !self.pool_definitions.contains_key(npc_type) {
+            let max_size = self.pool_slot_count(npc_type) + requested;
+            self.store_pool_definition(PoolDefinition::new(npc_type, max_size as i32, ""));
+        }
+
+        let room = self.pool_room(npc_type, requested);
+        if room < requested {
+            sim_warn!(
+                "[RUST POOL] Pool '{}' is at capacity - creating {} of {} requested slots",
+                npc_type,
+                room,
+                requested
+            );
+        }
+        room
+    }
+
+    /// Whether a spawn of `npc_type` can take a slot now
+    /// If not, the request is queued or rejected by the pool's overflow policy
+    pub(super) fn admit_spawn(&self, npc_type: &str, position: Vec2) -> bool {
+        let Some(definition) = self
+            .pool_definitions
+            .get(npc_type)
+            .map(|d| d.value().clone())
+        else {
+            // Unregistered pools are only limited by their slots
+            return true;
+        };
+
+        let active = self.active_count_for_type(npc_type);
+        let has_slot = self
+            .inactive_npcs
+            .get(npc_type)
+            .is_some_and(|pool| !pool.is_empty());
+        if has_slot && active < definition.max_size.max(0) as usize {
+            return true;
+        }
+
+        match definition.overflow {
+            PoolOverflow::Reject => {
+                sim_warn!(
+                    "[RUST POOL] Pool '{}' at capacity ({} active, max {}) - spawn rejected",
+                    npc_type,
+                    active,
+                    definition.max_size
+                );
+            }
+            PoolOverflow::Queue => {
+                let mut queue = self.queued_spawns.entry(npc_type.to_string()).or_default();
+                if queue.len() < definition.max_size.max(0) as usize {
+                    queue.push_back(position);
+                    sim_print!(
+                        "[RUST POOL] Pool '{}' at capacity - spawn queued ({} waiting)",
+                        npc_type,
+                        queue.len()
+                    );
+                } else {
+                    sim_warn!(
+                        "[RUST POOL] Pool '{}' spawn queue is full ({}) - spawn rejected",
+                        npc_type,
+                        queue.len()
+                    );
+                }
+            }
+        }
+        false
+    }
+
+    /// Run the oldest queued spawn of `npc_type` (a slot was just freed)
+    pub(super) fn spawn_queued(&self, npc_type: &str) {
+        let position = {
+            let Some(mut queue) = self.queued_spawns.get_mut(npc_type) else {
+                return;
+            };
+            let Some(position) = queue.pop_front() else {
+                return;
+            };
+            position
+        };
+        sim_print!("[RUST POOL] Running queued spawn for '{}'", npc_type);
+        self.spawn_from_pool(npc_type, position);
+    }
+
+    /// Queued spawns as (npc type, position), sorted by type then queue order
+    pub(super) fn queued_spawn_list(&self) -> Vec<(String, Vec2)> {
+        let mut queued: Vec<(String, VecDeque<Vec2>)> = self
+            .queued_spawns
+            .iter()
+            .map(|e| (e.key().clone(), e.value().clone()))
+            .collect();
+        queued.sort_by(|a, b| a.0.cmp(&b.0));
+        queued
+            .into_iter()
+            .flat_map(|(npc_type, positions)| {
+                positions
+                    .into_iter()
+                    .map(move |position| (npc_type.clone(), position))
+            })
+            .collect()
+    }
+}
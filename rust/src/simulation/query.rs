@@ -0,0 +1,161 @@
+// ============================================================================
+// NPC QUERIES - Spatial and attribute lookups over the active NPCs
+// ============================================================================
+// Hosts ask questions like "who is within 120px of the cursor", "the three
+// nearest goblins" or "the most hurt ally near this healer" here instead of
+// keeping their own lists of nodes. Every query takes an `NpcFilter` (type,
+// faction, behavioral state mask, static state mask) and returns ULIDs.
+// The faction is the NPC's current one (see npc_faction), so charmed or
+// converted NPCs are found under the faction they now fight for.
+//
+// Positions are simulation (container-local) coordinates, the same space as
+// get_npc_position and rust_spawn_npc. Results are deterministic: candidates
+// are visited in ULID order and distance ties resolve to the lower ULID.
+// Dead NPCs are skipped unless the filter asks for the DEAD state.
+
+use bevy::math::Vec2;
+use std::cmp::Ordering;
+
+use super::factions::FactionId;
+use super::stats::{NPCState, NPCStaticState};
+use super::warehouse::NPCDataWarehouse;
+
+/// Which NPCs a query considers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NpcFilter {
+    /// Only this NPC type (None = any type)
+    pub npc_type: Option<String>,
+    /// Only members of this faction (None = any faction)
+    pub faction: Option<FactionId>,
+    /// Behavioral flags that must all be set (empty = any state)
+    pub state: NPCState,
+    /// Static flags that must all be set, e.g. ALLY or MONSTER | RANGED
+    pub static_state: NPCStaticState,
+}
+
+impl Default for NpcFilter {
+    fn default() -> Self {
+        Self {
+            npc_type: None,
+            faction: None,
+            state: NPCState::empty(),
+            static_state: NPCStaticState::empty(),
+        }
+    }
+}
+
+/// (ULID, position) of an NPC that passed the filter
+type Candidate = ([u8; 16], Vec2);
+
+/// Order by distance to `center`, ties by ULID
+fn by_distance(center: Vec2) -> impl Fn(&Candidate, &Candidate) -> Ordering {
+    move |a, b| {
+        a.1.distance_squared(center)
+            .total_cmp(&b.1.distance_squared(center))
+            .then_with(|| a.0.cmp(&b.0))
+    }
+}
+
+impl NPCDataWarehouse {
+    /// Active NPCs passing the filter, in ULID order
+    fn query_candidates(&self, filter: &NpcFilter) -> Vec<Candidate> {
+        let include_dead = filter.state.contains(NPCState::DEAD);
+        self.active_npc_ulids()
+            .into_iter()
+            .filter_map(|ulid| {
+                if let Some(npc_type) = &filter.npc_type {
+                    if self
+                        .npc_types
+                        .get(&ulid)
+                        .is_none_or(|t| t.value() != npc_type)
+                    {
+                        return None;
+                    }
+                }
+                if let Some(faction) = filter.faction {
+                    if self.npc_faction(&ulid) != Some(faction) {
+                        return None;
+                    }
+                }
+                let state = self
+                    .get_behavioral_state(&ulid)
+                    .unwrap_or(NPCState::empty());
+                if !state.contains(filter.state)
+                    || (!include_dead && state.contains(NPCState::DEAD))
+                {
+                    return None;
+                }
+                let stats = self.get_combat_stats(&ulid)?;
+                if !stats.static_flags().contains(filter.static_state) {
+                    return None;
+                }
+                let position = self.get_npc_position_internal(&ulid)?;
+                Some((ulid, position))
+            })
+            .collect()
+    }
+
+    /// NPCs passing the filter, in ULID order
+    pub fn query_matching(&self, filter: &NpcFilter) -> Vec<[u8; 16]> {
+        self.query_candidates(filter)
+            .into_iter()
+            .map(|(ulid, _)| ulid)
+            .collect()
+    }
+
+    /// NPCs within `radius` of `center`, nearest first
+    pub fn query_radius(&self, center: Vec2, radius: f32, filter: &NpcFilter) -> Vec<[u8; 16]> {
+        let mut found: Vec<Candidate> = self
+            .query_candidates(filter)
+            .into_iter()
+            .filter(|(_, position)| position.distance(center) <= radius)
+            .collect();
+        found.sort_by(by_distance(center));
+        found.into_iter().map(|(ulid, _)| ulid).collect()
+    }
+
+    /// NPCs inside the rectangle spanned by `min` and `max`, in ULID order
+    pub fn query_rect(&self, min: Vec2, max: Vec2, filter: &NpcFilter) -> Vec<[u8; 16]> {
+        let (min, max) = (min.min(max), min.max(max));
+        self.query_candidates(filter)
+            .into_iter()
+            .filter(|(_, position)| position.cmpge(min).all() && position.cmple(max).all())
+            .map(|(ulid, _)| ulid)
+            .collect()
+    }
+
+    /// Up to `count` NPCs nearest to `center`, nearest first
+    pub fn query_nearest(&self, center: Vec2, count: usize, filter: &NpcFilter) -> Vec<[u8; 16]> {
+        let mut found = self.query_candidates(filter);
+        found.sort_by(by_distance(center));
+        found.truncate(count);
+        found.into_iter().map(|(ulid, _)| ulid).collect()
+    }
+
+    /// NPC within `radius` of `center` with the lowest HP fraction (hp / max_hp)
+    /// Ties go to the nearer NPC
+    pub fn query_lowest_hp(
+        &self,
+        center: Vec2,
+        radius: f32,
+        filter: &NpcFilter,
+    ) -> Option<[u8; 16]> {
+        let mut found: Vec<(f32, Candidate)> = self
+            .query_candidates(filter)
+            .into_iter()
+            .filter(|(_, position)| position.distance(center) <= radius)
+            .filter_map(|candidate| {
+                let stats = self.get_combat_stats(&candidate.0)?;
+                let fraction = if stats.max_hp > 0.0 {
+                    stats.hp / stats.max_hp
+                } else {
+                    0.0
+                };
+                Some((fraction, candidate))
+            })
+            .collect();
+        let nearer = by_distance(center);
+        found.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| nearer(&a.1, &b.1)));
+        found.first().map(|(_, (ulid, _))| *ulid)
+    }
+}
@@ -0,0 +1,187 @@
+// ============================================================================
+// REGENERATION - Budgeted round-robin HP, mana and energy regeneration
+// ============================================================================
+// Every living NPC in combat sits in one round-robin queue, oldest visit
+// first. Each combat tick, after healing (so the regen is there for the next
+// tick's attacks), pops NPCs off the front (at most REGEN_BUDGET_PER_TICK
+// of them, and only those last visited REGEN_INTERVAL_MS or more ago),
+// regenerates them for the time since their own last visit and puts them at
+// the back. With more NPCs than the budget covers, visits simply get further
+// apart - every NPC still gets its full regen rate, and none waits longer than
+// the NPCs ahead of it. New NPCs join the back in ULID order; dead, despawned
+// and unregistered ones leave the queue.
+//
+// A visit restores:
+//   - mana and energy   mana_regen / energy_regen per second, always
+//   - HP                hp_regen per second, only for kingdom NPCs (never
+//                       monsters) out of combat (no COMBAT or DAMAGED flag),
+//                       so allies recover between waves
+//
+// Mana and energy changes queue a ResourceEvent per NPC (see resources.rs).
+// HP changes are batched into one NodeCommand::HealthBars per tick, so the
+// host touches each healthbar once without floating heal text. Stats are
+// copied out of their DashMap guards before anything is queued. HP goes to
+// the NPC's Health component (see live.rs); mana and energy stay in its
+// combat stats. Regen only reads behavior, so the ECS runs it alongside
+// threat decay.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::Mut;
+
+use super::ecs::Health;
+use super::resources::ResourceEvent;
+use super::stats::{NPCState, NPCStaticState};
+use super::warehouse::{NPCDataWarehouse, NodeCommand};
+
+/// Shortest simulation time between two visits of the same NPC
+pub const REGEN_INTERVAL_MS: u64 = 500;
+
+/// Most NPCs regenerated per combat tick
+pub const REGEN_BUDGET_PER_TICK: usize = 64;
+
+impl NPCDataWarehouse {
+    /// NPCs waiting in the regeneration queue
+    pub fn regen_queue_len(&self) -> usize {
+        self.regen_queue.lock().len()
+    }
+
+    /// Drop an NPC from the regeneration queue (death, despawn)
+    pub(super) fn forget_regen(&self, ulid: &[u8; 16]) {
+        if self.regen_last_ms.remove(ulid).is_some() {
+            self.regen_queue.lock().retain(|queued| queued != ulid);
+        }
+    }
+
+    /// Queue order with each NPC's last visit time (see snapshot.rs)
+    pub(super) fn regen_queue_entries(&self) -> Vec<([u8; 16], u64)> {
+        self.regen_queue
+            .lock()
+            .iter()
+            .filter_map(|ulid| {
+                self.regen_last_ms
+                    .get(ulid)
+                    .map(|last_ms| (*ulid, *last_ms))
+            })
+            .collect()
+    }
+
+    /// Replace the queue with saved entries, front first (see snapshot.rs)
+    pub(super) fn restore_regen_queue(&self, entries: &[([u8; 16], u64)]) {
+        let mut queue = self.regen_queue.lock();
+        queue.clear();
+        self.regen_last_ms.clear();
+        for &(ulid, last_ms) in entries {
+            if self.regen_last_ms.insert(ulid, last_ms).is_none() {
+                queue.push_back(ulid);
+            }
+        }
+    }
+
+    /// Enqueue new NPCs, then regenerate the ones due from the front of the
+    /// queue within the tick's budget
+    /// `npcs` holds the NPCs registered for combat with their behavior and
+    /// Health component, sorted by ULID
+    pub(super) fn run_regen(&self, npcs: &mut [([u8; 16], NPCState, Mut<Health>)]) {
+        let now_ms = self.get_current_time_ms();
+
+        let due: Vec<([u8; 16], u64)> = {
+            let mut queue = self.regen_queue.lock();
+            for (ulid, state, _) in npcs.iter() {
+                if state.contains(NPCState::DEAD) || self.regen_last_ms.contains_key(ulid) {
+                    continue;
+                }
+                self.regen_last_ms.insert(*ulid, now_ms);
+                queue.push_back(*ulid);
+            }
+            self.take_due_regen(&mut queue, now_ms)
+        };
+
+        let mut healthbars = Vec::new();
+        for (ulid, elapsed_ms) in due {
+            let Ok(i) = npcs.binary_search_by_key(&ulid, |(ulid, _, _)| *ulid) else {
+                continue; // Left combat while queued
+            };
+            let (_, state, health) = &mut npcs[i];
+            let seconds = elapsed_ms as f32 / 1000.0;
+            if let Some(hp) = self.regenerate(&ulid, *state, health, seconds) {
+                healthbars.push(hp);
+            }
+        }
+        if !healthbars.is_empty() {
+            self.push_node_command(NodeCommand::HealthBars {
+                updates: healthbars,
+            });
+        }
+    }
+
+    /// Rotate up to REGEN_BUDGET_PER_TICK due NPCs to the back of the queue
+    /// Returns them with the time since their last visit
+    fn take_due_regen(&self, queue: &mut VecDeque<[u8; 16]>, now_ms: u64) -> Vec<([u8; 16], u64)> {
+        let mut due = Vec::new();
+        while due.len() < REGEN_BUDGET_PER_TICK {
+            let Some(&ulid) = queue.front() else {
+                break;
+            };
+            let Some(last_ms) = self.regen_last_ms.get(&ulid).map(|v| *v) else {
+                queue.pop_front(); // Left without forget_regen
+                continue;
+            };
+            // The queue is in visit order - once the front isn't due, nobody is
+            // (a clock that went back restarts the count from now)
+            if now_ms >= last_ms && now_ms - last_ms < REGEN_INTERVAL_MS {
+                break;
+            }
+            queue.pop_front();
+            queue.push_back(ulid);
+            self.regen_last_ms.insert(ulid, now_ms);
+            if now_ms > last_ms {
+                due.push((ulid, now_ms - last_ms));
+            }
+        }
+        due
+    }
+
+    /// Regenerate one NPC for `seconds`
+    /// Returns its new HP and max HP if HP changed
+    fn regenerate(
+        &self,
+        ulid: &[u8; 16],
+        state: NPCState,
+        health: &mut Mut<Health>,
+        seconds: f32,
+    ) -> Option<([u8; 16], f32, f32)> {
+        if state.contains(NPCState::DEAD) || health.hp <= 0.0 {
+            return None;
+        }
+        let in_combat = state.intersects(NPCState::COMBAT | NPCState::DAMAGED);
+
+        let (event, healthbar) = {
+            let mut stats = self.npc_combat_stats.get_mut(ulid)?;
+            let (mana, energy) = (stats.mana, stats.energy);
+            if stats.mana < stats.max_mana {
+                stats.mana = (stats.mana + stats.mana_regen * seconds).min(stats.max_mana);
+            }
+            if stats.energy < stats.max_energy {
+                stats.energy = (stats.energy + stats.energy_regen * seconds).min(stats.max_energy);
+            }
+            let monster = stats.static_flags().contains(NPCStaticState::MONSTER);
+            let mut healthbar = None;
+            if !in_combat && !monster && health.hp < health.max_hp {
+                let hp = (health.hp + stats.hp_regen * seconds).min(health.max_hp);
+                if hp != health.hp {
+                    health.hp = hp;
+                    healthbar = Some((*ulid, hp, health.max_hp));
+                }
+            }
+
+            let event = (stats.mana != mana || stats.energy != energy)
+                .then(|| ResourceEvent::from_stats(*ulid, &stats));
+            (event, healthbar)
+        };
+        if let Some(event) = event {
+            self.resource_events.push(event);
+        }
+        healthbar
+    }
+}
@@ -0,0 +1,1280 @@
+// ============================================================================
+// INPUT RECORDING AND REPLAY - Reproduce a battle tick by tick
+// ============================================================================
+// While recording, every external input (ticks, spawns, despawns, projectile
+// hits, healing, position updates, world bounds, archetypes, pool setup,
+// combat registration, faction edits, damage formula changes, status effects,
+// food, forced targets, taunts, squad commands) is logged with the tick it arrived in and the simulation time.
+// After each tick a hash of the NPC state is logged too.
+//
+// The recording starts from a header holding the RNG seed, the pooled slots,
+// the archetypes and a snapshot of the battle, so it can be fed into a fresh
+// warehouse. Replay applies the inputs at their recorded times and compares
+// the state hash after every tick; the first mismatch is the divergence tick.
+//
+// The host clock keeps running while recording, but it is held still for the
+// length of each tick so every phase of a tick reads the same time.
+// With the simulation thread running, host inputs take the step lock (see
+// runner.rs), so they still land between two ticks.
+//
+// File format (one entry per line, ULIDs as hex, floats round-trip exactly):
+//   godo-replay 3
+//   {header JSON}
+//   <tick> <time_ms> <code> <args...>
+// Codes: T tick, C combat phase, M movement phase, A animation phase,
+//        S spawn, D despawn, P projectile hit, H heal, U position update,
+//        B world bounds, E spawning enabled, I initialize pool, N register faction,
+//        R faction relation, F NPC faction, Z faction wander zone,
+//        G damage formula, X apply status effect, Y remove status effect,
+//        O add food, Q feed NPC, V force target, W taunt,
+//        J squad edit (create / assign / remove / disband / formation),
+//        L squad order, K checksum
+//        a register archetype, e register status effect, p register pool
+//        (definitions as JSON), o pool overflow, w pool warm/idle policy,
+//        b node budget, c register for combat, u unregister from combat
+//
+// Damage modifiers are code, so they can't be recorded: adding or removing one
+// fails while recording, and so does loading a snapshot.
+
+use bevy::math::Vec2;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use super::archetypes::NPCArchetype;
+use super::clock::Clock;
+use super::damage::DamageConfig;
+use super::ecs::SimulationWorld;
+use super::effects::StatusEffectDef;
+use super::factions::Relation;
+use super::log::{sim_print, sim_warn};
+use super::pools::{PoolDefinition, PoolOverflow};
+use super::snapshot::WarehouseSnapshot;
+use super::squads::Formation;
+use super::stats::{bytes_to_hex, UlidBytes};
+use super::warehouse::NPCDataWarehouse;
+
+/// Recording format version (bump when the layout changes incompatibly)
+pub const RECORDING_VERSION: u32 = 3;
+
+/// First token of a recording file
+const RECORDING_MAGIC: &str = "godo-replay";
+
+/// One external input (or a recorder checksum)
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedInput {
+    /// Full tick (combat, movement, cleanup)
+    Tick {
+        delta: f32,
+    },
+    CombatPhase,
+    MovementPhase {
+        delta: f32,
+    },
+    AnimationPhase,
+    Spawn {
+        npc_type: String,
+        position: Vec2,
+    },
+    Despawn {
+        ulid: [u8; 16],
+    },
+    ProjectileHit {
+        attacker: [u8; 16],
+        target: [u8; 16],
+    },
+    Heal {
+        target: [u8; 16],
+        amount: f32,
+        hunger_gain: f32,
+        energy_gain: f32,
+    },
+    Position {
+        ulid: [u8; 16],
+        position: Vec2,
+    },
+    WorldBounds {
+        min_x: f32,
+        max_x: f32,
+        min_y: f32,
+        max_y: f32,
+    },
+    SpawningEnabled {
+        enabled: bool,
+    },
+    InitializePool {
+        npc_type: String,
+        pool_size: usize,
+    },
+    RegisterFaction {
+        name: String,
+        inherit: Option<String>,
+    },
+    FactionRelation {
+        a: String,
+        b: String,
+        relation: Relation,
+    },
+    NpcFaction {
+        ulid: [u8; 16],
+        faction: String,
+    },
+    FactionZone {
+        name: String,
+        zone: Option<(f32, f32)>,
+    },
+    DamageConfig {
+        config: DamageConfig,
+    },
+    StatusEffect {
+        ulid: [u8; 16],
+        effect: String,
+        source: Option<[u8; 16]>,
+    },
+    RemoveStatusEffect {
+        ulid: [u8; 16],
+        effect: String,
+    },
+    AddFood {
+        units: u32,
+    },
+    FeedNpc {
+        ulid: [u8; 16],
+    },
+    ForceTarget {
+        ulid: [u8; 16],
+        target: Option<[u8; 16]>,
+    },
+    Taunt {
+        ulid: [u8; 16],
+    },
+    CreateSquad {
+        name: String,
+        leader: [u8; 16],
+        formation: Formation,
+    },
+    AssignSquad {
+        name: String,
+        ulid: [u8; 16],
+    },
+    RemoveFromSquad {
+        ulid: [u8; 16],
+    },
+    DisbandSquad {
+        name: String,
+    },
+    SquadFormation {
+        name: String,
+        formation: Formation,
+    },
+    OrderSquad {
+        name: String,
+        target: Vec2,
+    },
+    RegisterArchetype {
+        archetype: NPCArchetype,
+    },
+    RegisterStatusEffect {
+        def: StatusEffectDef,
+    },
+    /// Pool definition as stored (register_pool / register_pool_json)
+    RegisterPool {
+        definition: PoolDefinition,
+    },
+    PoolOverflow {
+        npc_type: String,
+        overflow: PoolOverflow,
+    },
+    PoolPolicy {
+        npc_type: String,
+        min_warm: i32,
+        max_idle: Option<i32>,
+        idle_timeout_ms: u64,
+    },
+    NodeBudget {
+        budget: usize,
+    },
+    RegisterCombat {
+        ulid: [u8; 16],
+        static_state: i32,
+        behavioral_state: i32,
+        max_hp: f32,
+        attack: f32,
+        defense: f32,
+    },
+    UnregisterCombat {
+        ulid: [u8; 16],
+    },
+    /// State hash after the preceding tick/phase (written by the recorder)
+    Checksum {
+        hash: u64,
+    },
+}
+
+impl RecordedInput {
+    /// Inputs that start a new tick
+    fn starts_tick(&self) -> bool {
+        matches!(
+            self,
+            RecordedInput::Tick { .. } | RecordedInput::CombatPhase
+        )
+    }
+}
+
+/// A recorded input with the tick it arrived in and the simulation time
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedEntry {
+    pub tick: u64,
+    pub time_ms: u64,
+    pub input: RecordedInput,
+}
+
+/// Pooled slots of one NPC type: (ULID hex, name), in pool order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolSlots {
+    pub npc_type: String,
+    pub slots: Vec<(String, String)>,
+    /// Capacity and overflow policy
+    #[serde(default)]
+    pub definition: Option<PoolDefinition>,
+    /// When the pool started holding more than max_idle slots (ms)
+    #[serde(default)]
+    pub idle_since_ms: Option<u64>,
+}
+
+/// Everything needed to rebuild the warehouse as it was when recording began
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub version: u32,
+    pub seed: u64,
+    pub start_ms: u64,
+    pub spawning_enabled: bool,
+    /// Pooled slots allowed across every type (0 = no budget)
+    #[serde(default)]
+    pub node_budget: usize,
+    /// Damage formula in effect when recording began
+    #[serde(default)]
+    pub damage: DamageConfig,
+    pub archetypes: Vec<NPCArchetype>,
+    pub pools: Vec<PoolSlots>,
+    pub snapshot: WarehouseSnapshot,
+}
+
+/// A finished recording
+#[derive(Debug, Clone)]
+pub struct InputRecording {
+    pub header: RecordingHeader,
+    pub entries: Vec<RecordedEntry>,
+}
+
+/// First tick whose state hash didn't match the recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    pub tick: u64,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+/// Result of a replay
+#[derive(Debug, Clone, Default)]
+pub struct ReplayReport {
+    /// Ticks replayed (up to and including the divergent one)
+    pub ticks: u64,
+    /// Inputs applied
+    pub inputs: usize,
+    /// None if the replay matched the recording all the way through
+    pub divergence: Option<Divergence>,
+}
+
+/// Host clock wrapper installed while recording - pinned for the length of a tick
+struct TickClock {
+    inner: Arc<dyn Clock>,
+    pinned: AtomicBool,
+    pinned_ms: AtomicU64,
+}
+
+impl Clock for TickClock {
+    fn now_ms(&self) -> u64 {
+        if self.pinned.load(Ordering::Acquire) {
+            self.pinned_ms.load(Ordering::Relaxed)
+        } else {
+            self.inner.now_ms()
+        }
+    }
+
+    fn advance_step_ms(&self, delta_ms: u64) {
+        self.inner.advance_step_ms(delta_ms);
+    }
+}
+
+/// Active recording state (lives on the warehouse)
+pub(crate) struct InputRecorder {
+    recording: InputRecording,
+    tick: u64,
+    clock: Arc<TickClock>,
+}
+
+impl InputRecorder {
+    fn push(&mut self, input: RecordedInput) {
+        if input.starts_tick() {
+            self.tick += 1;
+        }
+        self.recording.entries.push(RecordedEntry {
+            tick: self.tick,
+            time_ms: self.clock.now_ms(),
+            input,
+        });
+    }
+}
+
+impl NPCDataWarehouse {
+    // ============================================================================
+    // RECORDING
+    // ============================================================================
+
+    /// Start recording inputs (restarts any recording in progress)
+    /// The RNG is reseeded with `seed` (or a fresh random seed) so the run can
+    /// be replayed. Returns the seed used.
+    pub fn start_recording(&self, seed: Option<u64>) -> u64 {
+        if self.is_recording() {
+            sim_warn!("[REPLAY] Recording already running - restarting");
+            self.stop_recording();
+        }
+
+        if !self.damage_modifiers.read().is_empty() {
+            sim_warn!("[REPLAY] Damage modifiers aren't recorded - the replay runs without them");
+        }
+
+        let seed = seed.unwrap_or_else(|| self.rng().random());
+        self.set_rng_seed(seed);
+
+        // Hold the clock while the header is captured so the snapshot timers
+        // line up exactly with start_ms
+        let inner = self.clock.read().clone();
+        let clock = Arc::new(TickClock {
+            pinned: AtomicBool::new(true),
+            pinned_ms: AtomicU64::new(inner.now_ms()),
+            inner,
+        });
+        self.set_clock(clock.clone());
+        let header = RecordingHeader {
+            version: RECORDING_VERSION,
+            seed,
+            start_ms: clock.now_ms(),
+            spawning_enabled: self.spawning_enabled.load(Ordering::Relaxed),
+            node_budget: self.node_budget(),
+            damage: self.damage_config(),
+            archetypes: self.archetypes.all(),
+            pools: self.pool_slots(),
+            snapshot: self.save_snapshot(),
+        };
+        clock.pinned.store(false, Ordering::Release);
+
+        *self.recorder.lock() = Some(InputRecorder {
+            recording: InputRecording {
+                header,
+                entries: Vec::new(),
+            },
+            tick: 0,
+            clock,
+        });
+        self.recording.store(true, Ordering::Release);
+        sim_print!("[REPLAY] Recording started (seed {})", seed);
+        seed
+    }
+
+    /// Stop recording and hand back what was recorded
+    pub fn stop_recording(&self) -> Option<InputRecording> {
+        self.recording.store(false, Ordering::Release);
+        let recorder = self.recorder.lock().take()?;
+
+        // Put the host clock back, unless the host installed another one meanwhile
+        let installed = self.clock.read().clone();
+        if std::ptr::addr_eq(Arc::as_ptr(&installed), Arc::as_ptr(&recorder.clock)) {
+            self.set_clock(recorder.clock.inner.clone());
+        }
+
+        sim_print!(
+            "[REPLAY] Recording stopped ({} ticks, {} entries)",
+            recorder.tick,
+            recorder.recording.entries.len()
+        );
+        Some(recorder.recording)
+    }
+
+    /// Check if inputs are being recorded
+    pub fn is_recording(&self) -> bool {
+        self.recording.load(Ordering::Acquire)
+    }
+
+    /// Log an external input (no-op unless recording)
+    pub(crate) fn record_input(&self, input: RecordedInput) {
+        if !self.is_recording() {
+            return;
+        }
+        if let Some(recorder) = self.recorder.lock().as_mut() {
+            recorder.push(input);
+        }
+    }
+
+    /// Log a tick/phase and hold the clock still until `record_tick_end`
+    pub(crate) fn record_tick_start(&self, input: RecordedInput) {
+        if !self.is_recording() {
+            return;
+        }
+        if let Some(recorder) = self.recorder.lock().as_mut() {
+            let clock = &recorder.clock;
+            clock
+                .pinned_ms
+                .store(clock.inner.now_ms(), Ordering::Relaxed);
+            clock.pinned.store(true, Ordering::Release);
+            recorder.push(input);
+        }
+    }
+
+    /// Log the state hash after a tick/phase and release the clock
+    pub(crate) fn record_tick_end(&self) {
+        if !self.is_recording() {
+            return;
+        }
+        let hash = self.state_hash();
+        if let Some(recorder) = self.recorder.lock().as_mut() {
+            recorder.push(RecordedInput::Checksum { hash });
+            recorder.clock.pinned.store(false, Ordering::Release);
+        }
+    }
+
+    /// Hash of the simulated state: per active NPC in ULID order its position,
+    /// HP, mana, energy, hunger, behavioral state, combat registration, attack
+    /// cooldown, status effects, heal channel, emotions, threat table and
+    /// targets; then the food stockpile, every squad (leader, formation, slots,
+    /// anchor, order), the pending timers, the regen queue and the RNG state
+    /// FNV-1a, so the value is stable across runs and builds
+    pub fn state_hash(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mut hash = FNV_OFFSET;
+        let mut feed = |bytes: &[u8]| {
+            for byte in bytes {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        };
+
+        for ulid in self.active_npc_ulids() {
+            let pos = self
+                .npc_positions
+                .get(&ulid)
+                .map(|v| *v.value())
+                .unwrap_or(Vec2::NAN);
+            let hp = self.get_npc_hp_internal(&ulid).unwrap_or(f32::NAN);
+            let state = self
+                .get_behavioral_state(&ulid)
+                .map_or(0, |state| state.bits());
+            feed(&ulid);
+            feed(&pos.x.to_bits().to_le_bytes());
+            feed(&pos.y.to_bits().to_le_bytes());
+            feed(&hp.to_bits().to_le_bytes());
+            feed(&state.to_le_bytes());
+            feed(&[self.active_combat_npcs.contains_key(&ulid) as u8]);
+            let last_attack = self.npc_cooldown.get(&ulid).map(|v| *v.value());
+            feed(&last_attack.unwrap_or(u64::MAX).to_le_bytes());
+
+            if let Some(stats) = self.get_combat_stats(&ulid) {
+                for value in [stats.mana, stats.energy, stats.hunger] {
+                    feed(&value.to_bits().to_le_bytes());
+                }
+            }
+            if let Some(effects) = self.npc_effects.get(&ulid) {
+                for effect in effects.value() {
+                    feed(effect.name.as_bytes());
+                    feed(&effect.stacks.to_le_bytes());
+                    feed(&effect.source.unwrap_or_default());
+                    feed(&effect.expires_ms.unwrap_or(u64::MAX).to_le_bytes());
+                    feed(&effect.next_tick_ms.unwrap_or(u64::MAX).to_le_bytes());
+                }
+            }
+            if let Some(channel) = self.heal_channels.get(&ulid) {
+                feed(&channel.target);
+                feed(&channel.lands_ms.to_le_bytes());
+            }
+            if let Some(scores) = self.npc_emotions.get(&ulid) {
+                let scores = scores.value();
+                for value in [
+                    scores.happiness,
+                    scores.anger,
+                    scores.fear,
+                    scores.sadness,
+                    scores.weariness,
+                ] {
+                    feed(&value.to_bits().to_le_bytes());
+                }
+            }
+            if let Some(table) = self.npc_threat.get(&ulid) {
+                for (source, threat) in table.value() {
+                    feed(source);
+                    feed(&threat.to_bits().to_le_bytes());
+                }
+            }
+            feed(&self.npc_target(&ulid).unwrap_or_default());
+            feed(&self.forced_target(&ulid).unwrap_or_default());
+        }
+
+        feed(&self.food_stockpile().to_le_bytes());
+        for squad in self.squads.lock().values() {
+            feed(squad.name.as_bytes());
+            feed(&squad.leader);
+            feed(squad.formation.as_str().as_bytes());
+            for (member, slot) in &squad.members {
+                feed(member);
+                feed(&slot.x.to_bits().to_le_bytes());
+                feed(&slot.y.to_bits().to_le_bytes());
+            }
+            let order = squad.order.unwrap_or(Vec2::NAN);
+            for value in [squad.anchor, squad.facing, order] {
+                feed(&value.x.to_bits().to_le_bytes());
+                feed(&value.y.to_bits().to_le_bytes());
+            }
+        }
+
+        for (due_ms, key) in self.live_timer_keys() {
+            feed(&due_ms.to_le_bytes());
+            feed(&key);
+        }
+        for (ulid, last_ms) in self.regen_queue_entries() {
+            feed(&ulid);
+            feed(&last_ms.to_le_bytes());
+        }
+        // Next draw of a copy of the RNG - differs whenever the draw count does
+        let next_draw: u64 = self.rng().clone().random();
+        feed(&next_draw.to_le_bytes());
+        hash
+    }
+
+    /// Every pooled slot by type (inactive slots in pool order, then active ones)
+    fn pool_slots(&self) -> Vec<PoolSlots> {
+        let active = self.active_npc_ulids();
+        let mut pools: Vec<PoolSlots> = self
+            .inactive_npcs
+            .iter()
+            .map(|entry| {
+                let npc_type = entry.key();
+                let slots = entry
+                    .value()
+                    .iter()
+                    .chain(active.iter().filter(|ulid| {
+                        self.npc_types
+                            .get(*ulid)
+                            .is_some_and(|t| t.value() == npc_type)
+                    }))
+                    .map(|ulid| {
+                        let name = self
+                            .npc_names
+                            .get(ulid)
+                            .map(|v| v.value().clone())
+                            .unwrap_or_default();
+                        (bytes_to_hex(ulid), name)
+                    })
+                    .collect();
+                PoolSlots {
+                    npc_type: npc_type.clone(),
+                    slots,
+                    definition: self
+                        .pool_definitions
+                        .get(npc_type)
+                        .map(|v| v.value().clone()),
+                    idle_since_ms: self.pool_idle_since.get(npc_type).map(|v| *v.value()),
+                }
+            })
+            .collect();
+        pools.sort_by(|a, b| a.npc_type.cmp(&b.npc_type));
+        pools
+    }
+
+    /// Rebuild a pool with exactly these slots (replay setup)
+    fn restore_pool_slots(&self, pool: &PoolSlots) -> Result<(), String> {
+        let mut ulids = Vec::with_capacity(pool.slots.len());
+        for (ulid_hex, name) in &pool.slots {
+            let ulid = *UlidBytes::from_hex_string(ulid_hex)?.as_bytes();
+            self.npc_names.insert(ulid, name.clone());
+            self.npc_types.insert(ulid, pool.npc_type.clone());
+            ulids.push(ulid);
+        }
+        self.inactive_npcs.insert(pool.npc_type.clone(), ulids);
+        if let Some(definition) = &pool.definition {
+            self.pool_definitions
+                .insert(pool.npc_type.clone(), definition.clone());
+        }
+        if let Some(since) = pool.idle_since_ms {
+            self.pool_idle_since.insert(pool.npc_type.clone(), since);
+        }
+        Ok(())
+    }
+}
+
+impl InputRecording {
+    // ============================================================================
+    // REPLAY
+    // ============================================================================
+
+    /// Feed the recording into a fresh warehouse and compare the state hash
+    /// after every tick. Stops at the first divergence.
+    pub fn replay(&self) -> Result<ReplayReport, String> {
+        let header = &self.header;
+        if header.version != RECORDING_VERSION {
+            return Err(format!(
+                "unsupported recording version {} (expected {})",
+                header.version, RECORDING_VERSION
+            ));
+        }
+
+        // Rebuild the warehouse as it was when recording began
+        let warehouse = Arc::new(NPCDataWarehouse::new(1000));
+        let clock = warehouse.use_manual_clock(header.start_ms);
+        for archetype in &header.archetypes {
+            warehouse.register_archetype(archetype.clone())?;
+        }
+        warehouse.set_node_budget(header.node_budget);
+        warehouse.set_damage_config(header.damage)?;
+        for pool in &header.pools {
+            warehouse.restore_pool_slots(pool)?;
+        }
+        warehouse.load_snapshot(&header.snapshot)?;
+        warehouse
+            .spawning_enabled
+            .store(header.spawning_enabled, Ordering::Relaxed);
+        warehouse.set_rng_seed(header.seed);
+
+        let mut simulation = SimulationWorld::new(warehouse.clone());
+        let mut report = ReplayReport::default();
+
+        for entry in &self.entries {
+            clock.set_ms(entry.time_ms);
+            match &entry.input {
+                RecordedInput::Tick { delta } => {
+                    simulation.tick(*delta);
+                }
+                RecordedInput::CombatPhase => {
+                    simulation.run_combat_phase();
+                }
+                RecordedInput::MovementPhase { delta } => {
+                    simulation.run_movement_phase(*delta);
+                }
+                RecordedInput::AnimationPhase => simulation.run_animation_phase(),
+                RecordedInput::Spawn { npc_type, position } => {
+                    warehouse.rust_spawn_npc(npc_type, *position);
+                }
+                RecordedInput::Despawn { ulid } => {
+                    warehouse.rust_despawn_npc(ulid);
+                }
+                RecordedInput::ProjectileHit { attacker, target } => {
+                    warehouse.projectile_hit(attacker, target);
+                }
+                RecordedInput::Heal {
+                    target,
+                    amount,
+                    hunger_gain,
+                    energy_gain,
+                } => {
+                    warehouse.apply_healing(target, *amount, *hunger_gain, *energy_gain);
+                }
+                RecordedInput::Position { ulid, position } => {
+                    warehouse.update_npc_position_internal(ulid, position.x, position.y);
+                }
+                RecordedInput::WorldBounds {
+                    min_x,
+                    max_x,
+                    min_y,
+                    max_y,
+                } => {
+                    let _ = warehouse.set_world_bounds(*min_x, *max_x, *min_y, *max_y);
+                }
+                RecordedInput::SpawningEnabled { enabled } => {
+                    warehouse.set_spawning_enabled(*enabled)
+                }
+                RecordedInput::InitializePool {
+                    npc_type,
+                    pool_size,
+                } => {
+                    warehouse.initialize_npc_pool(npc_type, *pool_size);
+                }
+                // Faction edits were validated when recorded
+                RecordedInput::RegisterFaction { name, inherit } => {
+                    let _ = warehouse.register_faction(name, inherit.as_deref());
+                }
+                RecordedInput::FactionRelation { a, b, relation } => {
+                    let _ = warehouse.set_faction_relation(a, b, *relation);
+                }
+                RecordedInput::NpcFaction { ulid, faction } => {
+                    let _ = warehouse.set_npc_faction(ulid, faction);
+                }
+                RecordedInput::FactionZone { name, zone } => {
+                    let _ = warehouse.set_faction_wander_zone(name, *zone);
+                }
+                RecordedInput::DamageConfig { config } => {
+                    let _ = warehouse.set_damage_config(*config);
+                }
+                RecordedInput::StatusEffect {
+                    ulid,
+                    effect,
+                    source,
+                } => {
+                    let _ = warehouse.apply_status_effect(ulid, effect, *source);
+                }
+                RecordedInput::RemoveStatusEffect { ulid, effect } => {
+                    warehouse.remove_status_effect(ulid, effect);
+                }
+                RecordedInput::AddFood { units } => {
+                    warehouse.add_food(*units);
+                }
+                RecordedInput::FeedNpc { ulid } => {
+                    let _ = warehouse.feed_npc(ulid);
+                }
+                RecordedInput::ForceTarget { ulid, target } => {
+                    let _ = warehouse.force_target(ulid, *target);
+                }
+                RecordedInput::Taunt { ulid } => {
+                    let _ = warehouse.taunt(ulid);
+                }
+                RecordedInput::CreateSquad {
+                    name,
+                    leader,
+                    formation,
+                } => {
+                    let _ = warehouse.create_squad(name, leader, *formation);
+                }
+                RecordedInput::AssignSquad { name, ulid } => {
+                    let _ = warehouse.assign_to_squad(name, ulid);
+                }
+                RecordedInput::RemoveFromSquad { ulid } => {
+                    warehouse.remove_from_squad(ulid);
+                }
+                RecordedInput::DisbandSquad { name } => {
+                    warehouse.disband_squad(name);
+                }
+                RecordedInput::SquadFormation { name, formation } => {
+                    let _ = warehouse.set_squad_formation(name, *formation);
+                }
+                RecordedInput::OrderSquad { name, target } => {
+                    let _ = warehouse.order_squad(name, *target);
+                }
+                RecordedInput::RegisterArchetype { archetype } => {
+                    let _ = warehouse.register_archetype(archetype.clone());
+                }
+                RecordedInput::RegisterStatusEffect { def } => {
+                    let _ = warehouse.register_status_effect(def.clone());
+                }
+                RecordedInput::RegisterPool { definition } => {
+                    warehouse.store_pool_definition(definition.clone());
+                }
+                RecordedInput::PoolOverflow { npc_type, overflow } => {
+                    warehouse.set_pool_overflow(npc_type, *overflow);
+                }
+                RecordedInput::PoolPolicy {
+                    npc_type,
+                    min_warm,
+                    max_idle,
+                    idle_timeout_ms,
+                } => {
+                    warehouse.set_pool_policy(npc_type, *min_warm, *max_idle, *idle_timeout_ms);
+                }
+                RecordedInput::NodeBudget { budget } => warehouse.set_node_budget(*budget),
+                RecordedInput::RegisterCombat {
+                    ulid,
+                    static_state,
+                    behavioral_state,
+                    max_hp,
+                    attack,
+                    defense,
+                } => {
+                    warehouse.register_npc_for_combat_internal(
+                        ulid,
+                        *static_state,
+                        *behavioral_state,
+                        *max_hp,
+                        *attack,
+                        *defense,
+                    );
+                }
+                RecordedInput::UnregisterCombat { ulid } => {
+                    warehouse.unregister_npc_from_combat_internal(ulid);
+                }
+                RecordedInput::Checksum { hash } => {
+                    report.ticks = entry.tick;
+                    let actual = warehouse.state_hash();
+                    if actual != *hash {
+                        report.divergence = Some(Divergence {
+                            tick: entry.tick,
+                            expected: *hash,
+                            actual,
+                        });
+                        sim_warn!(
+                            "[REPLAY] Diverged at tick {} (expected {:016x}, got {:016x})",
+                            entry.tick,
+                            hash,
+                            actual
+                        );
+                        return Ok(report);
+                    }
+                    continue;
+                }
+            }
+            report.inputs += 1;
+        }
+
+        sim_print!(
+            "[REPLAY] Replayed {} ticks ({} inputs) without divergence",
+            report.ticks,
+            report.inputs
+        );
+        Ok(report)
+    }
+
+    // ============================================================================
+    // FILE FORMAT
+    // ============================================================================
+
+    /// Serialize to the line-based recording format (see module docs)
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let header = serde_json::to_string(&self.header).unwrap_or_else(|_| "{}".to_string());
+        let _ = writeln!(out, "{} {}", RECORDING_MAGIC, RECORDING_VERSION);
+        let _ = writeln!(out, "{}", header);
+
+        for entry in &self.entries {
+            let _ = write!(out, "{} {} ", entry.tick, entry.time_ms);
+            let _ = match &entry.input {
+                RecordedInput::Tick { delta } => writeln!(out, "T {}", delta),
+                RecordedInput::CombatPhase => writeln!(out, "C"),
+                RecordedInput::MovementPhase { delta } => writeln!(out, "M {}", delta),
+                RecordedInput::AnimationPhase => writeln!(out, "A"),
+                // Type goes last so it may contain spaces
+                RecordedInput::Spawn { npc_type, position } => {
+                    writeln!(out, "S {} {} {}", position.x, position.y, npc_type)
+                }
+                RecordedInput::Despawn { ulid } => writeln!(out, "D {}", bytes_to_hex(ulid)),
+                RecordedInput::ProjectileHit { attacker, target } => {
+                    writeln!(out, "P {} {}", bytes_to_hex(attacker), bytes_to_hex(target))
+                }
+                RecordedInput::Heal {
+                    target,
+                    amount,
+                    hunger_gain,
+                    energy_gain,
+                } => writeln!(
+                    out,
+                    "H {} {} {} {}",
+                    bytes_to_hex(target),
+                    amount,
+                    hunger_gain,
+                    energy_gain
+                ),
+                RecordedInput::Position { ulid, position } => {
+                    writeln!(
+                        out,
+                        "U {} {} {}",
+                        bytes_to_hex(ulid),
+                        position.x,
+                        position.y
+                    )
+                }
+                RecordedInput::WorldBounds {
+                    min_x,
+                    max_x,
+                    min_y,
+                    max_y,
+                } => writeln!(out, "B {} {} {} {}", min_x, max_x, min_y, max_y),
+                RecordedInput::SpawningEnabled { enabled } => {
+                    writeln!(out, "E {}", *enabled as u8)
+                }
+                RecordedInput::InitializePool {
+                    npc_type,
+                    pool_size,
+                } => writeln!(out, "I {} {}", pool_size, npc_type),
+                RecordedInput::RegisterFaction { name, inherit } => match inherit {
+                    Some(inherit) => writeln!(out, "N {} {}", name, inherit),
+                    None => writeln!(out, "N {}", name),
+                },
+                RecordedInput::FactionRelation { a, b, relation } => {
+                    writeln!(out, "R {} {} {}", relation.as_str(), a, b)
+                }
+                RecordedInput::NpcFaction { ulid, faction } => {
+                    writeln!(out, "F {} {}", bytes_to_hex(ulid), faction)
+                }
+                RecordedInput::FactionZone { name, zone } => match zone {
+                    Some((min, max)) => writeln!(out, "Z {} {} {}", name, min, max),
+                    None => writeln!(out, "Z {}", name),
+                },
+                RecordedInput::DamageConfig { config } => writeln!(
+                    out,
+                    "G {} {} {} {}",
+                    config.attack_divisor,
+                    config.defense_divisor,
+                    config.min_damage,
+                    config.variance
+                ),
+                RecordedInput::StatusEffect {
+                    ulid,
+                    effect,
+                    source,
+                } => match source {
+                    Some(source) => writeln!(
+                        out,
+                        "X {} {} {}",
+                        bytes_to_hex(ulid),
+                        effect,
+                        bytes_to_hex(source)
+                    ),
+                    None => writeln!(out, "X {} {}", bytes_to_hex(ulid), effect),
+                },
+                RecordedInput::RemoveStatusEffect { ulid, effect } => {
+                    writeln!(out, "Y {} {}", bytes_to_hex(ulid), effect)
+                }
+                RecordedInput::AddFood { units } => writeln!(out, "O {}", units),
+                RecordedInput::FeedNpc { ulid } => writeln!(out, "Q {}", bytes_to_hex(ulid)),
+                RecordedInput::ForceTarget { ulid, target } => match target {
+                    Some(target) => {
+                        writeln!(out, "V {} {}", bytes_to_hex(ulid), bytes_to_hex(target))
+                    }
+                    None => writeln!(out, "V {}", bytes_to_hex(ulid)),
+                },
+                RecordedInput::Taunt { ulid } => writeln!(out, "W {}", bytes_to_hex(ulid)),
+                RecordedInput::CreateSquad {
+                    name,
+                    leader,
+                    formation,
+                } => writeln!(
+                    out,
+                    "J create {} {} {}",
+                    name,
+                    formation.as_str(),
+                    bytes_to_hex(leader)
+                ),
+                RecordedInput::AssignSquad { name, ulid } => {
+                    writeln!(out, "J assign {} {}", name, bytes_to_hex(ulid))
+                }
+                RecordedInput::RemoveFromSquad { ulid } => {
+                    writeln!(out, "J remove {}", bytes_to_hex(ulid))
+                }
+                RecordedInput::DisbandSquad { name } => writeln!(out, "J disband {}", name),
+                RecordedInput::SquadFormation { name, formation } => {
+                    writeln!(out, "J formation {} {}", name, formation.as_str())
+                }
+                RecordedInput::OrderSquad { name, target } => {
+                    writeln!(out, "L {} {} {}", name, target.x, target.y)
+                }
+                // Definitions are written as JSON to the end of the line
+                RecordedInput::RegisterArchetype { archetype } => {
+                    writeln!(out, "a {}", to_json(archetype))
+                }
+                RecordedInput::RegisterStatusEffect { def } => writeln!(out, "e {}", to_json(def)),
+                RecordedInput::RegisterPool { definition } => {
+                    writeln!(out, "p {}", to_json(definition))
+                }
+                RecordedInput::PoolOverflow { npc_type, overflow } => {
+                    writeln!(out, "o {} {}", overflow.as_str(), npc_type)
+                }
+                RecordedInput::PoolPolicy {
+                    npc_type,
+                    min_warm,
+                    max_idle,
+                    idle_timeout_ms,
+                } => match max_idle {
+                    Some(max_idle) => writeln!(
+                        out,
+                        "w {} {} {} {}",
+                        min_warm, max_idle, idle_timeout_ms, npc_type
+                    ),
+                    None => writeln!(out, "w {} - {} {}", min_warm, idle_timeout_ms, npc_type),
+                },
+                RecordedInput::NodeBudget { budget } => writeln!(out, "b {}", budget),
+                RecordedInput::RegisterCombat {
+                    ulid,
+                    static_state,
+                    behavioral_state,
+                    max_hp,
+                    attack,
+                    defense,
+                } => writeln!(
+                    out,
+                    "c {} {} {} {} {} {}",
+                    bytes_to_hex(ulid),
+                    static_state,
+                    behavioral_state,
+                    max_hp,
+                    attack,
+                    defense
+                ),
+                RecordedInput::UnregisterCombat { ulid } => {
+                    writeln!(out, "u {}", bytes_to_hex(ulid))
+                }
+                RecordedInput::Checksum { hash } => writeln!(out, "K {:016x}", hash),
+            };
+        }
+        out
+    }
+
+    /// Parse the line-based recording format
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines();
+
+        let magic = lines.next().unwrap_or_default();
+        let version = magic
+            .strip_prefix(RECORDING_MAGIC)
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .ok_or_else(|| "not a recording file".to_string())?;
+        if version != RECORDING_VERSION {
+            return Err(format!(
+                "unsupported recording version {} (expected {})",
+                version, RECORDING_VERSION
+            ));
+        }
+
+        let header: RecordingHeader = serde_json::from_str(lines.next().unwrap_or_default())
+            .map_err(|e| format!("invalid recording header: {}", e))?;
+
+        let mut entries = Vec::new();
+        for (index, line) in lines.enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            // +3: 1-based, after the magic and header lines
+            let entry = parse_entry(line).map_err(|e| format!("line {}: {}", index + 3, e))?;
+            entries.push(entry);
+        }
+
+        Ok(Self { header, entries })
+    }
+}
+
+/// Parse one "<tick> <time_ms> <code> <args...>" line
+fn parse_entry(line: &str) -> Result<RecordedEntry, String> {
+    let mut fields = line.splitn(4, ' ');
+    let mut next = || fields.next().ok_or_else(|| "missing field".to_string());
+
+    let tick = parse_field::<u64>(next()?)?;
+    let time_ms = parse_field::<u64>(next()?)?;
+    let code = next()?;
+    let rest = fields.next().unwrap_or_default();
+    let args: Vec<&str> = rest.split(' ').filter(|arg| !arg.is_empty()).collect();
+    let arg = |i: usize| {
+        args.get(i)
+            .copied()
+            .ok_or_else(|| format!("{}: missing argument {}", code, i))
+    };
+    let ulid = |i: usize| -> Result<[u8; 16], String> {
+        Ok(*UlidBytes::from_hex_string(arg(i)?)?.as_bytes())
+    };
+    let float = |i: usize| parse_field::<f32>(arg(i)?);
+
+    let input = match code {
+        "T" => RecordedInput::Tick { delta: float(0)? },
+        "C" => RecordedInput::CombatPhase,
+        "M" => RecordedInput::MovementPhase { delta: float(0)? },
+        "A" => RecordedInput::AnimationPhase,
+        "S" => {
+            let mut parts = rest.splitn(3, ' ');
+            let x = parse_field::<f32>(parts.next().unwrap_or_default())?;
+            let y = parse_field::<f32>(parts.next().unwrap_or_default())?;
+            RecordedInput::Spawn {
+                npc_type: parts.next().unwrap_or_default().to_string(),
+                position: Vec2::new(x, y),
+            }
+        }
+        "D" => RecordedInput::Despawn { ulid: ulid(0)? },
+        "P" => RecordedInput::ProjectileHit {
+            attacker: ulid(0)?,
+            target: ulid(1)?,
+        },
+        "H" => RecordedInput::Heal {
+            target: ulid(0)?,
+            amount: float(1)?,
+            hunger_gain: float(2)?,
+            energy_gain: float(3)?,
+        },
+        "U" => RecordedInput::Position {
+            ulid: ulid(0)?,
+            position: Vec2::new(float(1)?, float(2)?),
+        },
+        "B" => RecordedInput::WorldBounds {
+            min_x: float(0)?,
+            max_x: float(1)?,
+            min_y: float(2)?,
+            max_y: float(3)?,
+        },
+        "E" => RecordedInput::SpawningEnabled {
+            enabled: arg(0)? == "1",
+        },
+        "I" => {
+            let (size, npc_type) = rest
+                .split_once(' ')
+                .ok_or_else(|| "I: missing type".to_string())?;
+            RecordedInput::InitializePool {
+                npc_type: npc_type.to_string(),
+                pool_size: parse_field::<usize>(size)?,
+            }
+        }
+        "N" => RecordedInput::RegisterFaction {
+            name: arg(0)?.to_string(),
+            inherit: args.get(1).map(|inherit| inherit.to_string()),
+        },
+        "R" => {
+            let relation = arg(0)?;
+            RecordedInput::FactionRelation {
+                relation: Relation::parse(relation)
+                    .ok_or_else(|| format!("R: unknown relation '{}'", relation))?,
+                a: arg(1)?.to_string(),
+                b: arg(2)?.to_string(),
+            }
+        }
+        "F" => RecordedInput::NpcFaction {
+            ulid: ulid(0)?,
+            faction: arg(1)?.to_string(),
+        },
+        "Z" => RecordedInput::FactionZone {
+            name: arg(0)?.to_string(),
+            zone: match args.len() {
+                1 => None,
+                _ => Some((float(1)?, float(2)?)),
+            },
+        },
+        "G" => RecordedInput::DamageConfig {
+            config: DamageConfig {
+                attack_divisor: float(0)?,
+                defense_divisor: float(1)?,
+                min_damage: float(2)?,
+                variance: float(3)?,
+            },
+        },
+        "X" => RecordedInput::StatusEffect {
+            ulid: ulid(0)?,
+            effect: arg(1)?.to_string(),
+            source: match args.len() {
+                2 => None,
+                _ => Some(ulid(2)?),
+            },
+        },
+        "Y" => RecordedInput::RemoveStatusEffect {
+            ulid: ulid(0)?,
+            effect: arg(1)?.to_string(),
+        },
+        "O" => RecordedInput::AddFood {
+            units: parse_field::<u32>(arg(0)?)?,
+        },
+        "Q" => RecordedInput::FeedNpc { ulid: ulid(0)? },
+        "V" => RecordedInput::ForceTarget {
+            ulid: ulid(0)?,
+            target: match args.len() {
+                1 => None,
+                _ => Some(ulid(1)?),
+            },
+        },
+        "W" => RecordedInput::Taunt { ulid: ulid(0)? },
+        "J" => {
+            let formation = |i: usize| {
+                let name = arg(i)?;
+                Formation::parse(name).ok_or_else(|| format!("J: unknown formation '{}'", name))
+            };
+            match arg(0)? {
+                "create" => RecordedInput::CreateSquad {
+                    name: arg(1)?.to_string(),
+                    formation: formation(2)?,
+                    leader: ulid(3)?,
+                },
+                "assign" => RecordedInput::AssignSquad {
+                    name: arg(1)?.to_string(),
+                    ulid: ulid(2)?,
+                },
+                "remove" => RecordedInput::RemoveFromSquad { ulid: ulid(1)? },
+                "disband" => RecordedInput::DisbandSquad {
+                    name: arg(1)?.to_string(),
+                },
+                "formation" => RecordedInput::SquadFormation {
+                    name: arg(1)?.to_string(),
+                    formation: formation(2)?,
+                },
+                other => return Err(format!("J: unknown squad command '{}'", other)),
+            }
+        }
+        "L" => RecordedInput::OrderSquad {
+            name: arg(0)?.to_string(),
+            target: Vec2::new(float(1)?, float(2)?),
+        },
+        "a" => RecordedInput::RegisterArchetype {
+            archetype: from_json(code, rest)?,
+        },
+        "e" => RecordedInput::RegisterStatusEffect {
+            def: from_json(code, rest)?,
+        },
+        "p" => RecordedInput::RegisterPool {
+            definition: from_json(code, rest)?,
+        },
+        "o" => {
+            let (overflow, npc_type) = rest
+                .split_once(' ')
+                .ok_or_else(|| "o: missing type".to_string())?;
+            RecordedInput::PoolOverflow {
+                npc_type: npc_type.to_string(),
+                overflow: PoolOverflow::parse(overflow)
+                    .ok_or_else(|| format!("o: unknown overflow policy '{}'", overflow))?,
+            }
+        }
+        "w" => {
+            let mut parts = rest.splitn(4, ' ');
+            let mut part = || parts.next().unwrap_or_default();
+            let min_warm = parse_field::<i32>(part())?;
+            let max_idle = match part() {
+                "-" => None,
+                max_idle => Some(parse_field::<i32>(max_idle)?),
+            };
+            RecordedInput::PoolPolicy {
+                min_warm,
+                max_idle,
+                idle_timeout_ms: parse_field::<u64>(part())?,
+                npc_type: part().to_string(),
+            }
+        }
+        "b" => RecordedInput::NodeBudget {
+            budget: parse_field::<usize>(arg(0)?)?,
+        },
+        "c" => RecordedInput::RegisterCombat {
+            ulid: ulid(0)?,
+            static_state: parse_field::<i32>(arg(1)?)?,
+            behavioral_state: parse_field::<i32>(arg(2)?)?,
+            max_hp: float(3)?,
+            attack: float(4)?,
+            defense: float(5)?,
+        },
+        "u" => RecordedInput::UnregisterCombat { ulid: ulid(0)? },
+        "K" => RecordedInput::Checksum {
+            hash: u64::from_str_radix(arg(0)?, 16).map_err(|e| format!("K: {}", e))?,
+        },
+        other => return Err(format!("unknown entry code '{}'", other)),
+    };
+
+    Ok(RecordedEntry {
+        tick,
+        time_ms,
+        input,
+    })
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "{}".to_string())
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(code: &str, json: &str) -> Result<T, String> {
+    serde_json::from_str(json).map_err(|e| format!("{}: invalid JSON: {}", code, e))
+}
+
+fn parse_field<T: std::str::FromStr>(field: &str) -> Result<T, String>
+where
+    T::Err: std::fmt::Display,
+{
+    field
+        .parse::<T>()
+        .map_err(|e| format!("invalid value '{}': {}", field, e))
+}
@@ -0,0 +1,100 @@
+// ============================================================================
+// RESOURCES - Mana and energy costs, regeneration and change events
+// ============================================================================
+// Archetypes give each NPC an energy and/or mana cost per attack and a regen
+// rate per second. The combat phase pays for every attack before it swings:
+//   - not enough energy: the NPC rests (skips the attack) until it regenerates
+//   - enough energy but not enough mana: it makes a weak physical attack
+//     instead (WEAK_ATTACK_MULTIPLIER of its attack, never a projectile)
+// Healers pay their heal_mana_cost here too (see healing.rs).
+//
+// Regeneration is the budgeted regen scheduler's job (see regen.rs). Each
+// spend or regen visit that changes a value queues a ResourceEvent with the
+// new totals.
+
+use super::stats::NPCCombatStats;
+use super::warehouse::NPCDataWarehouse;
+
+/// Share of its attack an NPC without the mana for its attack still deals
+pub const WEAK_ATTACK_MULTIPLIER: f32 = 0.5;
+
+/// An NPC's mana or energy changed (new totals)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResourceEvent {
+    pub ulid: [u8; 16],
+    pub mana: f32,
+    pub max_mana: f32,
+    pub energy: f32,
+    pub max_energy: f32,
+}
+
+impl ResourceEvent {
+    pub(super) fn from_stats(ulid: [u8; 16], stats: &NPCCombatStats) -> Self {
+        Self {
+            ulid,
+            mana: stats.mana,
+            max_mana: stats.max_mana,
+            energy: stats.energy,
+            max_energy: stats.max_energy,
+        }
+    }
+}
+
+/// What an attacker can do after paying for its attack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AttackKind {
+    /// Paid in full - normal attack
+    Full,
+    /// Out of mana - weak physical attack
+    Weak,
+    /// Out of energy - no attack this turn
+    Rest,
+}
+
+impl NPCDataWarehouse {
+    /// Pop the next resource change (None when the queue is empty)
+    pub fn pop_resource_event(&self) -> Option<ResourceEvent> {
+        self.resource_events.pop()
+    }
+
+    /// Pay for an attack with the attacker's current stats
+    pub(super) fn pay_attack_cost(&self, ulid: &[u8; 16], stats: &NPCCombatStats) -> AttackKind {
+        if stats.energy < stats.attack_energy_cost {
+            return AttackKind::Rest;
+        }
+        if stats.mana >= stats.attack_mana_cost {
+            self.spend_resources(ulid, stats.attack_mana_cost, stats.attack_energy_cost);
+            AttackKind::Full
+        } else {
+            self.spend_resources(ulid, 0.0, stats.attack_energy_cost);
+            AttackKind::Weak
+        }
+    }
+
+    /// Take mana and energy from an NPC
+    /// Returns false (and spends nothing) if it has too little of either
+    pub(super) fn spend_resources(&self, ulid: &[u8; 16], mana: f32, energy: f32) -> bool {
+        let event = {
+            let Some(mut stats) = self.npc_combat_stats.get_mut(ulid) else {
+                return false;
+            };
+            if stats.mana < mana || stats.energy < energy {
+                return false;
+            }
+            if mana <= 0.0 && energy <= 0.0 {
+                return true; // Free
+            }
+            stats.mana -= mana;
+            stats.energy -= energy;
+            ResourceEvent::from_stats(*ulid, &stats)
+        };
+        self.resource_events.push(event);
+        true
+    }
+
+    /// Queue a change event with an NPC's current totals
+    pub(super) fn push_resource_event(&self, ulid: &[u8; 16], stats: &NPCCombatStats) {
+        self.resource_events
+            .push(ResourceEvent::from_stats(*ulid, stats));
+    }
+}
@@ -0,0 +1,306 @@
+// ============================================================================
+// SIMULATION RUNNER - Inline or background-thread ticking
+// ============================================================================
+// The runner owns the SimulationWorld and runs it in fixed steps (see
+// timestep.rs). Inline, the host reports real elapsed time each frame;
+// threaded, a worker wakes at a fixed rate and does the same.
+//
+// The host keeps changing the warehouse while the worker runs (spawns,
+// projectile hits, healing, setters). Each step holds the warehouse's step
+// lock, and host changes take it too (`hold_steps`), so they land between two
+// steps instead of in the middle of one. Inline, the lock is never contended.
+//
+// Either way every tick hands its results over the same way:
+// - combat events and death positions go through the warehouse's lock-free
+//   queues (`pop_combat_event` / `pop_death_position`)
+// - spawn/despawn/HP changes go through the node command queue
+// - positions and states land in a read-only SimFrame swapped in with ArcSwap
+//
+// The worker only touches the warehouse (DashMaps, atomics, queues), never
+// engine objects, so all node work stays on the host's main thread.
+//
+// WASM exports run single-threaded: `start_thread` declines and the host
+// keeps ticking inline.
+
+use arc_swap::ArcSwap;
+use parking_lot::MutexGuard;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use super::ecs::SimulationWorld;
+use super::frame::SimFrame;
+use super::log::{sim_error, sim_print, sim_warn};
+use super::warehouse::NPCDataWarehouse;
+
+/// Worker wake-up rate used when none is given (wake-ups per second)
+pub const DEFAULT_TICK_HZ: f32 = 60.0;
+
+/// State shared between the runner and its worker thread
+#[derive(Default)]
+struct RunnerShared {
+    frame: ArcSwap<SimFrame>,
+    ticks: AtomicU64,
+}
+
+/// Running worker thread - hands the world back when joined
+struct Worker {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<SimulationWorld>,
+    tick_hz: f32,
+}
+
+/// Owns the simulation and decides where it ticks
+pub struct SimulationRunner {
+    warehouse: Arc<NPCDataWarehouse>,
+    shared: Arc<RunnerShared>,
+    /// None while the worker owns the world
+    world: Option<SimulationWorld>,
+    worker: Option<Worker>,
+}
+
+impl SimulationRunner {
+    pub fn new(warehouse: Arc<NPCDataWarehouse>) -> Self {
+        Self {
+            world: Some(SimulationWorld::new(warehouse.clone())),
+            warehouse,
+            shared: Arc::new(RunnerShared::default()),
+            worker: None,
+        }
+    }
+
+    /// The warehouse driven by this runner
+    pub fn warehouse(&self) -> &Arc<NPCDataWarehouse> {
+        &self.warehouse
+    }
+
+    /// Whether the worker thread is ticking the simulation
+    pub fn is_threaded(&self) -> bool {
+        self.worker.is_some()
+    }
+
+    /// Wake-up rate of the worker thread (None when ticking inline)
+    pub fn tick_hz(&self) -> Option<f32> {
+        self.worker.as_ref().map(|worker| worker.tick_hz)
+    }
+
+    /// Fixed steps run so far (inline and threaded)
+    pub fn ticks(&self) -> u64 {
+        self.shared.ticks.load(Ordering::Acquire)
+    }
+
+    /// Latest published frame
+    pub fn frame(&self) -> Arc<SimFrame> {
+        self.shared.frame.load_full()
+    }
+
+    /// The world for inline phase calls (None while the worker owns it)
+    pub fn world_mut(&mut self) -> Option<&mut SimulationWorld> {
+        self.world.as_mut()
+    }
+
+    /// Feed real elapsed time to the accumulator and run the fixed steps due
+    /// (scaled by the time scale, none while paused) - results go to the
+    /// queues and a new frame. Does nothing while the worker thread is running.
+    /// Returns the number of steps run
+    pub fn advance(&mut self, real_delta_secs: f32) -> u32 {
+        let Some(world) = self.world.as_mut() else {
+            return 0;
+        };
+        let steps = self.warehouse.accumulate(real_delta_secs);
+        run_steps(world, &self.shared, steps, true);
+        steps
+    }
+
+    /// Run exactly `steps` fixed steps now, ignoring pause and time scale
+    /// Events are queued as usual (frame-by-frame debugging)
+    pub fn step(&mut self, steps: u32) -> u32 {
+        self.with_world(|world, shared| run_steps(world, shared, steps, true));
+        steps
+    }
+
+    /// Simulate `seconds` of battle time right away (AFK catch-up)
+    /// Runs the same fixed steps a live battle would, so the outcome matches;
+    /// the combat events and death effects of the skipped time are dropped.
+    /// Returns the number of steps run
+    pub fn fast_forward(&mut self, seconds: f32) -> u32 {
+        let steps = self.warehouse.steps_for(seconds);
+        self.with_world(|world, shared| run_steps(world, shared, steps, false));
+        steps
+    }
+
+    /// Run `f` on the world, pausing the worker thread around it if needed
+    fn with_world<R>(&mut self, f: impl FnOnce(&mut SimulationWorld, &RunnerShared) -> R) -> R {
+        let tick_hz = self.tick_hz();
+        self.stop_thread();
+        let world = self
+            .world
+            .as_mut()
+            .expect("stop_thread always hands the world back");
+        let result = f(world, &self.shared);
+        if let Some(tick_hz) = tick_hz {
+            self.start_thread(tick_hz);
+        }
+        result
+    }
+
+    /// Capture and publish a frame of the current warehouse state
+    /// (after inline phase calls or host-side changes such as loading a snapshot)
+    pub fn publish_frame(&self) {
+        let frame = self.warehouse.capture_frame(self.ticks());
+        self.shared.frame.store(Arc::new(frame));
+    }
+
+    /// Move the simulation onto a worker thread that wakes at a fixed rate
+    /// Returns false if already threaded, the rate is invalid, or threads
+    /// are unavailable (WASM)
+    pub fn start_thread(&mut self, tick_hz: f32) -> bool {
+        if self.worker.is_some() {
+            return false;
+        }
+        if !(tick_hz.is_finite() && tick_hz > 0.0) {
+            sim_warn!("[RUNNER] Invalid tick rate {} - staying inline", tick_hz);
+            return false;
+        }
+
+        #[cfg(target_family = "wasm")]
+        {
+            sim_warn!("[RUNNER] Threads are unavailable on WASM - ticking inline");
+            false
+        }
+
+        #[cfg(not(target_family = "wasm"))]
+        {
+            let Some(world) = self.world.take() else {
+                return false;
+            };
+            let stop = Arc::new(AtomicBool::new(false));
+            let shared = self.shared.clone();
+            let worker_stop = stop.clone();
+
+            let spawned = std::thread::Builder::new()
+                .name("godo-simulation".to_string())
+                .spawn(move || run_worker(world, shared, worker_stop, tick_hz));
+
+            match spawned {
+                Ok(handle) => {
+                    sim_print!("[RUNNER] Simulation thread started at {} Hz", tick_hz);
+                    self.worker = Some(Worker {
+                        stop,
+                        handle,
+                        tick_hz,
+                    });
+                    true
+                }
+                Err(e) => {
+                    // The closure (and the world inside it) is gone - rebuild it,
+                    // entities are mirrored from the warehouse on the next phase
+                    sim_error!("[RUNNER] Failed to spawn simulation thread: {}", e);
+                    self.world = Some(SimulationWorld::new(self.warehouse.clone()));
+                    false
+                }
+            }
+        }
+    }
+
+    /// Stop the worker thread and take the world back for inline ticking
+    pub fn stop_thread(&mut self) {
+        let Some(worker) = self.worker.take() else {
+            return;
+        };
+        worker.stop.store(true, Ordering::Release);
+
+        match worker.handle.join() {
+            Ok(world) => self.world = Some(world),
+            Err(_) => {
+                sim_error!("[RUNNER] Simulation thread panicked - rebuilding the world");
+                self.world = Some(SimulationWorld::new(self.warehouse.clone()));
+            }
+        }
+        sim_print!("[RUNNER] Simulation thread stopped");
+    }
+}
+
+impl NPCDataWarehouse {
+    /// Hold off the next fixed step while the guard lives (see module docs)
+    /// Not reentrant: don't tick, or call anything that takes it, while holding it
+    pub fn hold_steps(&self) -> MutexGuard<'_, ()> {
+        self.step_lock.lock()
+    }
+}
+
+impl Drop for SimulationRunner {
+    fn drop(&mut self) {
+        self.stop_thread();
+    }
+}
+
+/// Run `steps` fixed steps, then publish one frame
+/// Each step advances simulation time before ticking with the fixed delta.
+/// With `queue_events` false the visual results (combat events, death
+/// positions) are dropped; node commands are always kept.
+fn run_steps(world: &mut SimulationWorld, shared: &RunnerShared, steps: u32, queue_events: bool) {
+    if steps == 0 {
+        return;
+    }
+    let warehouse = world.warehouse().clone();
+    let delta = warehouse.fixed_delta();
+
+    for _ in 0..steps {
+        let _step = warehouse.hold_steps();
+        warehouse.advance_fixed_step();
+        let (events, death_positions) = world.tick(delta);
+
+        if queue_events {
+            for event in events {
+                warehouse.combat_event_queue.push(event);
+            }
+            for position in death_positions {
+                warehouse.death_position_queue.push(position);
+            }
+        }
+        shared.ticks.fetch_add(1, Ordering::AcqRel);
+    }
+
+    let _step = warehouse.hold_steps();
+    let tick = shared.ticks.load(Ordering::Acquire);
+    shared.frame.store(Arc::new(warehouse.capture_frame(tick)));
+}
+
+/// Worker loop - wake at a fixed rate, run the steps due, until asked to stop
+#[cfg(not(target_family = "wasm"))]
+fn run_worker(
+    mut world: SimulationWorld,
+    shared: Arc<RunnerShared>,
+    stop: Arc<AtomicBool>,
+    tick_hz: f32,
+) -> SimulationWorld {
+    use std::time::{Duration, Instant};
+
+    let period = Duration::from_secs_f32(1.0 / tick_hz);
+    let mut last_wake = Instant::now();
+    let mut next_wake = last_wake + period;
+
+    while !stop.load(Ordering::Acquire) {
+        let now = Instant::now();
+        if next_wake > now {
+            std::thread::sleep(next_wake - now);
+        }
+
+        let now = Instant::now();
+        let steps = world
+            .warehouse()
+            .accumulate((now - last_wake).as_secs_f32());
+        last_wake = now;
+        run_steps(&mut world, &shared, steps, true);
+
+        // Schedule from the ideal time; if far behind (hitch) the accumulator
+        // already dropped the backlog, so restart the schedule from now
+        next_wake += period;
+        if next_wake < now {
+            next_wake = now + period;
+        }
+    }
+
+    world
+}
@@ -0,0 +1,761 @@
+// ============================================================================
+// SNAPSHOTS - Save and restore a whole battle
+// ============================================================================
+// A snapshot captures every active NPC plus the spawn timers, pending
+// respawns, queued pool spawns, world bounds, the faction table, the status
+// effect definitions and the squads, with each NPC's target and threat table.
+// Timers, status effects and heal channels are stored relative to the moment
+// of saving (elapsed / remaining ms), so a battle resumes with the same
+// cooldowns whatever clock the next session runs on. Callback timers are not
+// saved.
+//
+// Restoring despawns everything, then pulls one pooled slot per saved NPC.
+// A slot that already carries the saved ULID is reused; otherwise a free slot
+// of the same type is re-keyed to it (pools get fresh ULIDs every session),
+// so the host must initialize its pools before loading.
+
+use bevy::math::Vec2;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::Ordering;
+
+use super::combat::{ATTACK_ANIM_DURATION_MS, DAMAGED_ANIM_DURATION_MS};
+use super::effects::{ActiveEffect, StatusEffectDef};
+use super::emotions::EmotionScores;
+use super::factions::FactionTable;
+use super::log::{sim_print, sim_warn};
+use super::squads::{Formation, Squad};
+use super::stats::{bytes_to_hex, NPCCombatStats, NPCState, UlidBytes};
+use super::timers::{NpcTimer, TimerAction};
+use super::warehouse::{validate_world_bounds, NPCDataWarehouse, NodeCommand};
+
+/// Snapshot format version (bump when the layout changes incompatibly)
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// One active NPC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NpcSnapshot {
+    /// ULID as hex (32 chars)
+    pub ulid: String,
+    pub npc_type: String,
+    pub name: String,
+    pub stats: NPCCombatStats,
+    /// NPCState bits
+    pub state: u32,
+    pub position: (f32, f32),
+    #[serde(default)]
+    pub waypoint: Option<(f32, f32)>,
+    #[serde(default)]
+    pub move_direction: Option<(f32, f32)>,
+    /// Current target ULID as hex
+    #[serde(default, alias = "aggro_target")]
+    pub target: Option<String>,
+    /// Target forced by the player, ULID as hex
+    #[serde(default)]
+    pub forced_target: Option<String>,
+    /// Threat table, in source ULID order
+    #[serde(default)]
+    pub threat: Vec<ThreatSnapshot>,
+    /// Registered with the combat system
+    pub in_combat: bool,
+
+    /// Time since the last attack (None = never attacked)
+    #[serde(default)]
+    pub attack_elapsed_ms: Option<u64>,
+    /// Time since ATTACKING / DAMAGED were set
+    #[serde(default)]
+    pub attacking_elapsed_ms: Option<u64>,
+    #[serde(default)]
+    pub damaged_elapsed_ms: Option<u64>,
+    /// Time left until a dead NPC is despawned
+    #[serde(default)]
+    pub despawn_in_ms: Option<u64>,
+    /// Time left before the NPC may start wandering
+    #[serde(default)]
+    pub wander_cooldown_ms: Option<u64>,
+    /// Named faction (None = the faction of its static bits)
+    #[serde(default)]
+    pub faction: Option<String>,
+    /// Running status effects, in the order they were applied
+    #[serde(default)]
+    pub effects: Vec<EffectSnapshot>,
+    /// Heal being channeled (healers only)
+    #[serde(default)]
+    pub heal_channel: Option<HealChannelSnapshot>,
+    /// Score per emotion (None = feels nothing)
+    #[serde(default)]
+    pub emotions: Option<EmotionScores>,
+}
+
+/// A status effect running on an NPC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectSnapshot {
+    pub name: String,
+    pub stacks: u32,
+    /// ULID (hex) of the NPC that applied it
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Time left (None = until removed)
+    #[serde(default)]
+    pub remaining_ms: Option<u64>,
+    /// Time until the next tick (None = never ticks)
+    #[serde(default)]
+    pub next_tick_in_ms: Option<u64>,
+}
+
+/// A heal being channeled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealChannelSnapshot {
+    /// Target ULID as hex
+    pub target: String,
+    /// Time left until the heal lands
+    pub lands_in_ms: u64,
+}
+
+/// Threat one enemy has on an NPC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatSnapshot {
+    /// Source ULID as hex
+    pub source: String,
+    pub threat: f32,
+}
+
+/// A squad and its slots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SquadSnapshot {
+    pub name: String,
+    /// Leader ULID as hex
+    pub leader: String,
+    pub formation: Formation,
+    /// Members in slot order
+    pub members: Vec<SquadMemberSnapshot>,
+    pub anchor: (f32, f32),
+    pub facing: (f32, f32),
+    /// Where the squad is marching (None = holding its ground)
+    #[serde(default)]
+    pub order: Option<(f32, f32)>,
+}
+
+/// One squad member and its slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SquadMemberSnapshot {
+    /// ULID as hex
+    pub ulid: String,
+    /// Slot offset from the anchor (forward, sideways)
+    pub slot: (f32, f32),
+}
+
+/// An NPC's place in the regeneration queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegenSnapshot {
+    /// ULID as hex
+    pub ulid: String,
+    /// Time since its last regen visit
+    pub elapsed_ms: u64,
+}
+
+/// A respawn scheduled but not yet run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RespawnSnapshot {
+    pub npc_type: String,
+    pub position: (f32, f32),
+    /// Time left until the respawn
+    pub in_ms: u64,
+}
+
+/// A spawn request waiting for a free pool slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedSpawnSnapshot {
+    pub npc_type: String,
+    pub position: (f32, f32),
+}
+
+/// Whole-warehouse snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarehouseSnapshot {
+    pub version: u32,
+    /// Simulation time when the snapshot was taken (informational)
+    pub saved_at_ms: u64,
+
+    /// World bounds (min_x, max_x, min_y, max_y)
+    pub world_bounds: (f32, f32, f32, f32),
+
+    /// Spawn timers - time since the last monster wave / ally spawn
+    pub wave_elapsed_ms: u64,
+    pub ally_spawn_elapsed_ms: u64,
+    pub initial_spawn_done: bool,
+
+    /// Active NPCs, in ULID order
+    pub npcs: Vec<NpcSnapshot>,
+
+    /// Pending respawns, in firing order
+    #[serde(default)]
+    pub respawns: Vec<RespawnSnapshot>,
+
+    /// Spawns waiting for a free pool slot, by type then queue order
+    #[serde(default)]
+    pub queued_spawns: Vec<QueuedSpawnSnapshot>,
+
+    /// Factions and their relations (None = keep the current table)
+    #[serde(default)]
+    pub factions: Option<FactionTable>,
+
+    /// Status effect definitions, registered over the current ones
+    #[serde(default)]
+    pub status_effects: Vec<StatusEffectDef>,
+
+    /// Regeneration queue, front first
+    #[serde(default)]
+    pub regen_queue: Vec<RegenSnapshot>,
+
+    /// Time since the last hunger step (None = not started)
+    #[serde(default)]
+    pub hunger_elapsed_ms: Option<u64>,
+
+    /// Kingdom food stockpile (None = keep the current one)
+    #[serde(default)]
+    pub food_stockpile: Option<u32>,
+
+    /// Time since the last emotion step (None = not started)
+    #[serde(default)]
+    pub emotion_elapsed_ms: Option<u64>,
+
+    /// Time since the last threat decay step (None = not started)
+    #[serde(default)]
+    pub threat_elapsed_ms: Option<u64>,
+
+    /// Squads, in name order
+    #[serde(default)]
+    pub squads: Vec<SquadSnapshot>,
+}
+
+impl NPCDataWarehouse {
+    /// Capture every active NPC, the spawn timers and the world bounds
+    pub fn save_snapshot(&self) -> WarehouseSnapshot {
+        let now_ms = self.get_current_time_ms();
+        let elapsed = |timestamp: u64| now_ms.saturating_sub(timestamp);
+        let remaining = |ulid: &[u8; 16], slot: NpcTimer| {
+            self.npc_timer_due_ms(ulid, slot)
+                .map(|at_ms| at_ms.saturating_sub(now_ms))
+        };
+        // ATTACKING / DAMAGED are saved as time since set (duration minus time left)
+        let state_elapsed = |ulid: &[u8; 16], flag: NPCState, duration_ms: u64| {
+            remaining(ulid, NpcTimer::ClearState(flag))
+                .map(|left_ms| duration_ms.saturating_sub(left_ms))
+        };
+
+        let factions = self.faction_table();
+        let npcs = self
+            .active_npc_ulids()
+            .into_iter()
+            .filter_map(|ulid| {
+                let stats = self.get_combat_stats(&ulid)?;
+                let position = self.npc_positions.get(&ulid).map(|v| *v.value())?;
+                let ulid_hex = bytes_to_hex(&ulid);
+
+                Some(NpcSnapshot {
+                    npc_type: self
+                        .npc_types
+                        .get(&ulid)
+                        .map(|v| v.value().clone())
+                        .unwrap_or_default(),
+                    name: self
+                        .npc_names
+                        .get(&ulid)
+                        .map(|v| v.value().clone())
+                        .unwrap_or_default(),
+                    stats,
+                    state: self
+                        .get_behavioral_state(&ulid)
+                        .unwrap_or(NPCState::IDLE)
+                        .bits(),
+                    position: (position.x, position.y),
+                    waypoint: self.npc_waypoints.get(&ulid).map(|v| (v.x, v.y)),
+                    move_direction: self.npc_move_directions.get(&ulid).map(|v| (v.x, v.y)),
+                    target: self.npc_target(&ulid).map(|target| bytes_to_hex(&target)),
+                    forced_target: self
+                        .forced_target(&ulid)
+                        .map(|target| bytes_to_hex(&target)),
+                    threat: self
+                        .npc_threat
+                        .get(&ulid)
+                        .map(|table| {
+                            table
+                                .iter()
+                                .map(|(source, threat)| ThreatSnapshot {
+                                    source: bytes_to_hex(source),
+                                    threat: *threat,
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    in_combat: self.active_combat_npcs.contains_key(&ulid),
+                    attack_elapsed_ms: self
+                        .npc_cooldown
+                        .get(&ulid)
+                        .map(|v| *v.value())
+                        .filter(|&last_attack_ms| last_attack_ms > 0)
+                        .map(elapsed),
+                    attacking_elapsed_ms: state_elapsed(
+                        &ulid,
+                        NPCState::ATTACKING,
+                        ATTACK_ANIM_DURATION_MS,
+                    ),
+                    damaged_elapsed_ms: state_elapsed(
+                        &ulid,
+                        NPCState::DAMAGED,
+                        DAMAGED_ANIM_DURATION_MS,
+                    ),
+                    despawn_in_ms: remaining(&ulid, NpcTimer::Despawn),
+                    wander_cooldown_ms: remaining(&ulid, NpcTimer::WanderCooldown),
+                    faction: self
+                        .npc_factions
+                        .get(&ulid)
+                        .map(|v| factions.name(*v.value()).to_string()),
+                    effects: self
+                        .npc_status_effects(&ulid)
+                        .into_iter()
+                        .map(|effect| EffectSnapshot {
+                            name: effect.name,
+                            stacks: effect.stacks,
+                            source: effect.source.map(|source| bytes_to_hex(&source)),
+                            remaining_ms: effect
+                                .expires_ms
+                                .map(|at_ms| at_ms.saturating_sub(now_ms)),
+                            next_tick_in_ms: effect
+                                .next_tick_ms
+                                .map(|at_ms| at_ms.saturating_sub(now_ms)),
+                        })
+                        .collect(),
+                    heal_channel: self.heal_channel(&ulid).map(|(target, lands_ms)| {
+                        HealChannelSnapshot {
+                            target: bytes_to_hex(&target),
+                            lands_in_ms: lands_ms.saturating_sub(now_ms),
+                        }
+                    }),
+                    emotions: self.npc_emotions.get(&ulid).map(|scores| *scores),
+                    ulid: ulid_hex,
+                })
+            })
+            .collect();
+
+        let (world_min, world_max) = self.world_bounds();
+        WarehouseSnapshot {
+            version: SNAPSHOT_VERSION,
+            saved_at_ms: now_ms,
+            world_bounds: (world_min.x, world_max.x, world_min.y, world_max.y),
+            wave_elapsed_ms: elapsed(self.last_spawn_time_ms.load(Ordering::Relaxed)),
+            ally_spawn_elapsed_ms: elapsed(self.last_ally_spawn_time_ms.load(Ordering::Relaxed)),
+            initial_spawn_done: self.initial_spawn_done.load(Ordering::Relaxed),
+            npcs,
+            respawns: self
+                .pending_respawns()
+                .into_iter()
+                .map(|(due_ms, npc_type, position)| RespawnSnapshot {
+                    npc_type,
+                    position: (position.x, position.y),
+                    in_ms: due_ms.saturating_sub(now_ms),
+                })
+                .collect(),
+            queued_spawns: self
+                .queued_spawn_list()
+                .into_iter()
+                .map(|(npc_type, position)| QueuedSpawnSnapshot {
+                    npc_type,
+                    position: (position.x, position.y),
+                })
+                .collect(),
+            factions: Some(FactionTable::clone(&factions)),
+            status_effects: self.status_effect_defs(),
+            regen_queue: self
+                .regen_queue_entries()
+                .into_iter()
+                .map(|(ulid, last_ms)| RegenSnapshot {
+                    ulid: bytes_to_hex(&ulid),
+                    elapsed_ms: elapsed(last_ms),
+                })
+                .collect(),
+            hunger_elapsed_ms: Some(self.last_hunger_step_ms.load(Ordering::Relaxed))
+                .filter(|&last_ms| last_ms > 0)
+                .map(elapsed),
+            food_stockpile: Some(self.food_stockpile()),
+            emotion_elapsed_ms: Some(self.last_emotion_step_ms.load(Ordering::Relaxed))
+                .filter(|&last_ms| last_ms > 0)
+                .map(elapsed),
+            threat_elapsed_ms: Some(self.last_threat_step_ms.load(Ordering::Relaxed))
+                .filter(|&last_ms| last_ms > 0)
+                .map(elapsed),
+            squads: self
+                .squad_list()
+                .into_iter()
+                .map(|squad| SquadSnapshot {
+                    leader: bytes_to_hex(&squad.leader),
+                    formation: squad.formation,
+                    members: squad
+                        .members
+                        .iter()
+                        .map(|(ulid, slot)| SquadMemberSnapshot {
+                            ulid: bytes_to_hex(ulid),
+                            slot: (slot.x, slot.y),
+                        })
+                        .collect(),
+                    anchor: (squad.anchor.x, squad.anchor.y),
+                    facing: (squad.facing.x, squad.facing.y),
+                    order: squad.order.map(|order| (order.x, order.y)),
+                    name: squad.name,
+                })
+                .collect(),
+        }
+    }
+
+    /// Snapshot serialized as JSON
+    pub fn save_snapshot_json(&self) -> String {
+        serde_json::to_string(&self.save_snapshot()).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Replace the current battle with a snapshot
+    /// Every active NPC is despawned first; saved NPCs whose pool is missing
+    /// or exhausted are skipped with a warning
+    /// Returns how many NPCs were restored
+    pub fn load_snapshot(&self, snapshot: &WarehouseSnapshot) -> Result<usize, String> {
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(format!(
+                "unsupported snapshot version {} (expected {})",
+                snapshot.version, SNAPSHOT_VERSION
+            ));
+        }
+
+        // The recording can't describe a wholesale state swap
+        if self.is_recording() {
+            return Err("cannot load a snapshot while recording".to_string());
+        }
+
+        // Parse every ULID up front so a bad file leaves the battle untouched
+        let mut parsed = Vec::with_capacity(snapshot.npcs.len());
+        for npc in &snapshot.npcs {
+            let ulid = *UlidBytes::from_hex_string(&npc.ulid)?.as_bytes();
+            let parse = |hex: &Option<String>| -> Result<Option<[u8; 16]>, String> {
+                match hex {
+                    Some(hex) => Ok(Some(*UlidBytes::from_hex_string(hex)?.as_bytes())),
+                    None => Ok(None),
+                }
+            };
+            let target = parse(&npc.target)?;
+            let forced_target = parse(&npc.forced_target)?;
+            let mut threat = Vec::with_capacity(npc.threat.len());
+            for entry in &npc.threat {
+                threat.push((
+                    *UlidBytes::from_hex_string(&entry.source)?.as_bytes(),
+                    entry.threat,
+                ));
+            }
+            let mut effects = Vec::with_capacity(npc.effects.len());
+            for effect in &npc.effects {
+                let source = match &effect.source {
+                    Some(hex) => Some(*UlidBytes::from_hex_string(hex)?.as_bytes()),
+                    None => None,
+                };
+                effects.push((effect, source));
+            }
+            let heal_channel = match &npc.heal_channel {
+                Some(channel) => Some((
+                    *UlidBytes::from_hex_string(&channel.target)?.as_bytes(),
+                    channel.lands_in_ms,
+                )),
+                None => None,
+            };
+            parsed.push((
+                ulid,
+                (target, forced_target, threat),
+                effects,
+                heal_channel,
+                npc,
+            ));
+        }
+        let mut regen_queue = Vec::with_capacity(snapshot.regen_queue.len());
+        for entry in &snapshot.regen_queue {
+            let ulid = *UlidBytes::from_hex_string(&entry.ulid)?.as_bytes();
+            regen_queue.push((ulid, entry.elapsed_ms));
+        }
+        let mut squads = Vec::with_capacity(snapshot.squads.len());
+        for squad in &snapshot.squads {
+            let mut members = Vec::with_capacity(squad.members.len());
+            for member in &squad.members {
+                members.push((
+                    *UlidBytes::from_hex_string(&member.ulid)?.as_bytes(),
+                    Vec2::new(member.slot.0, member.slot.1),
+                ));
+            }
+            squads.push(Squad {
+                name: squad.name.clone(),
+                leader: *UlidBytes::from_hex_string(&squad.leader)?.as_bytes(),
+                formation: squad.formation,
+                members,
+                anchor: Vec2::new(squad.anchor.0, squad.anchor.1),
+                facing: Vec2::new(squad.facing.0, squad.facing.1).normalize_or(Vec2::X),
+                order: squad.order.map(|(x, y)| Vec2::new(x, y)),
+            });
+        }
+        let (min_x, max_x, min_y, max_y) = snapshot.world_bounds;
+        validate_world_bounds(min_x, max_x, min_y, max_y)?;
+        for def in &snapshot.status_effects {
+            def.validate()?;
+        }
+        if let Some(factions) = &snapshot.factions {
+            self.restore_faction_table(factions)?;
+        }
+        for def in &snapshot.status_effects {
+            self.register_status_effect(def.clone())?;
+        }
+
+        // Clear the current battle (queued spawns first, so freed slots stay free)
+        self.queued_spawns.clear();
+        self.squads.lock().clear();
+        self.npc_squads.clear();
+        for ulid in self.active_npc_ulids() {
+            self.despawn_to_pool(&ulid);
+        }
+        self.npc_threat.clear();
+        self.npc_targets.clear();
+        self.forced_targets.clear();
+        self.npc_waypoints.clear();
+        self.npc_move_directions.clear();
+        self.npc_effects.clear();
+        self.heal_channels.clear();
+        self.npc_emotions.clear();
+        self.clear_timers();
+
+        let now_ms = self.get_current_time_ms();
+        let since = |elapsed_ms: u64| now_ms.saturating_sub(elapsed_ms);
+
+        self.store_world_bounds(min_x, max_x, min_y, max_y);
+        self.last_spawn_time_ms
+            .store(since(snapshot.wave_elapsed_ms), Ordering::Relaxed);
+        self.last_ally_spawn_time_ms
+            .store(since(snapshot.ally_spawn_elapsed_ms), Ordering::Relaxed);
+        self.initial_spawn_done
+            .store(snapshot.initial_spawn_done, Ordering::Relaxed);
+        self.last_hunger_step_ms.store(
+            snapshot.hunger_elapsed_ms.map_or(0, since),
+            Ordering::Relaxed,
+        );
+        if let Some(food) = snapshot.food_stockpile {
+            self.set_food_stockpile(food);
+        }
+        self.last_emotion_step_ms.store(
+            snapshot.emotion_elapsed_ms.map_or(0, since),
+            Ordering::Relaxed,
+        );
+        self.last_threat_step_ms.store(
+            snapshot.threat_elapsed_ms.map_or(0, since),
+            Ordering::Relaxed,
+        );
+
+        let mut restored = Vec::with_capacity(parsed.len());
+        for (ulid, threat, effects, heal_channel, npc) in parsed {
+            if !self.take_pool_slot(&npc.npc_type, &ulid) {
+                continue;
+            }
+            let position = Vec2::new(npc.position.0, npc.position.1);
+
+            self.npc_names.insert(ulid, npc.name.clone());
+            self.npc_types.insert(ulid, npc.npc_type.clone());
+            self.npc_combat_stats.insert(ulid, npc.stats);
+            self.npc_behavioral_state
+                .insert(ulid, NPCState::from_bits_retain(npc.state));
+            self.npc_positions.insert(ulid, position);
+            self.npc_cooldown
+                .insert(ulid, npc.attack_elapsed_ms.map_or(0, since));
+            self.restore_npc_faction(&ulid, npc);
+
+            if let Some((x, y)) = npc.waypoint {
+                self.npc_waypoints.insert(ulid, Vec2::new(x, y));
+            }
+            if let Some((x, y)) = npc.move_direction {
+                self.npc_move_directions.insert(ulid, Vec2::new(x, y));
+            }
+            if let Some(elapsed_ms) = npc.attacking_elapsed_ms {
+                self.schedule_timer_at(
+                    since(elapsed_ms) + ATTACK_ANIM_DURATION_MS,
+                    TimerAction::ClearState {
+                        ulid,
+                        flag: NPCState::ATTACKING,
+                    },
+                );
+            }
+            if let Some(elapsed_ms) = npc.damaged_elapsed_ms {
+                self.schedule_timer_at(
+                    since(elapsed_ms) + DAMAGED_ANIM_DURATION_MS,
+                    TimerAction::ClearState {
+                        ulid,
+                        flag: NPCState::DAMAGED,
+                    },
+                );
+            }
+            if let Some(despawn_in_ms) = npc.despawn_in_ms {
+                self.schedule_timer(despawn_in_ms, TimerAction::Despawn { ulid });
+            }
+            if let Some(wander_cooldown_ms) = npc.wander_cooldown_ms {
+                self.schedule_timer(wander_cooldown_ms, TimerAction::EndWanderCooldown { ulid });
+            }
+            if npc.in_combat {
+                self.active_combat_npcs.insert(ulid, ());
+            }
+            for (effect, source) in effects {
+                self.restore_status_effect(
+                    &ulid,
+                    ActiveEffect {
+                        name: effect.name.clone(),
+                        stacks: effect.stacks.max(1),
+                        source,
+                        expires_ms: effect.remaining_ms.map(|in_ms| now_ms + in_ms),
+                        next_tick_ms: effect.next_tick_in_ms.map(|in_ms| now_ms + in_ms),
+                    },
+                );
+            }
+            if let Some(scores) = npc.emotions {
+                self.restore_emotions(&ulid, scores);
+            }
+
+            self.active_npcs.insert(ulid, ());
+            self.push_node_command(NodeCommand::Spawned {
+                ulid,
+                npc_type: npc.npc_type.clone(),
+                position,
+            });
+            restored.push((ulid, threat, heal_channel));
+        }
+
+        for respawn in &snapshot.respawns {
+            self.schedule_timer(
+                respawn.in_ms,
+                TimerAction::Respawn {
+                    npc_type: respawn.npc_type.clone(),
+                    position: Vec2::new(respawn.position.0, respawn.position.1),
+                },
+            );
+        }
+
+        for queued in &snapshot.queued_spawns {
+            self.queued_spawns
+                .entry(queued.npc_type.clone())
+                .or_default()
+                .push_back(Vec2::new(queued.position.0, queued.position.1));
+        }
+
+        // Targets, threat, heal channels, the regen queue and squads last -
+        // only keep those pointing at a restored NPC
+        for (ulid, (target, forced_target, threat), heal_channel) in &restored {
+            if let Some(target) = target.filter(|target| self.active_npcs.contains_key(target)) {
+                self.npc_targets.insert(*ulid, target);
+            }
+            if let Some(target) =
+                forced_target.filter(|target| self.active_npcs.contains_key(target))
+            {
+                self.forced_targets.insert(*ulid, target);
+            }
+            let table: BTreeMap<[u8; 16], f32> = threat
+                .iter()
+                .filter(|(source, _)| self.active_npcs.contains_key(source))
+                .copied()
+                .collect();
+            if !table.is_empty() {
+                self.npc_threat.insert(*ulid, table);
+            }
+            if let Some((target, lands_in_ms)) = heal_channel {
+                if self.active_npcs.contains_key(target) {
+                    self.restore_heal_channel(ulid, *target, now_ms + lands_in_ms);
+                }
+            }
+        }
+        let regen_queue: Vec<([u8; 16], u64)> = regen_queue
+            .into_iter()
+            .filter(|(ulid, _)| self.active_npcs.contains_key(ulid))
+            .map(|(ulid, elapsed_ms)| (ulid, since(elapsed_ms)))
+            .collect();
+        self.restore_regen_queue(&regen_queue);
+        self.restore_squads(squads);
+
+        sim_print!(
+            "NPCDataWarehouse: Restored {}/{} NPCs from snapshot",
+            restored.len(),
+            snapshot.npcs.len()
+        );
+        Ok(restored.len())
+    }
+
+    /// Restore a snapshot from JSON (see `load_snapshot`)
+    pub fn load_snapshot_json(&self, json: &str) -> Result<usize, String> {
+        let snapshot: WarehouseSnapshot =
+            serde_json::from_str(json).map_err(|e| format!("invalid snapshot JSON: {}", e))?;
+        self.load_snapshot(&snapshot)
+    }
+
+    /// Take a pooled slot of `npc_type` for a restored NPC, re-keying a free
+    /// slot to `ulid` if the pool doesn't already hold it
+    fn take_pool_slot(&self, npc_type: &str, ulid: &[u8; 16]) -> bool {
+        let Some(mut pool_entry) = self.inactive_npcs.get_mut(npc_type) else {
+            sim_warn!(
+                "[RUST POOL] Cannot restore {} - no pool for that type (initialize pools before loading)",
+                npc_type
+            );
+            return false;
+        };
+
+        if let Some(index) = pool_entry.iter().position(|slot| slot == ulid) {
+            pool_entry.remove(index);
+            return true;
+        }
+
+        // The ULID must not belong to another NPC already
+        if self.npc_types.contains_key(ulid) {
+            sim_warn!(
+                "[RUST POOL] Cannot restore {} - ULID {} is used by another NPC",
+                npc_type,
+                &bytes_to_hex(ulid)[..16]
+            );
+            return false;
+        }
+
+        let Some(slot) = pool_entry.pop() else {
+            sim_warn!(
+                "[RUST POOL] Cannot restore {} - pool exhausted (consider increasing pool size)",
+                npc_type
+            );
+            return false;
+        };
+        drop(pool_entry);
+
+        // Move the slot over to the saved ULID
+        self.forget_pool_slot(&slot);
+        self.push_node_command(NodeCommand::Rekeyed {
+            from: slot,
+            to: *ulid,
+        });
+        true
+    }
+
+    /// Put a restored NPC back in its saved faction, falling back to the
+    /// faction of its archetype
+    fn restore_npc_faction(&self, ulid: &[u8; 16], npc: &NpcSnapshot) {
+        let saved = npc.faction.as_deref().and_then(|name| {
+            let faction = self.faction_table().id(name);
+            if faction.is_none() {
+                sim_warn!(
+                    "[FACTIONS] Unknown faction '{}' for restored NPC {} - using its archetype's",
+                    name,
+                    &npc.ulid[..16]
+                );
+            }
+            faction
+        });
+        let faction = saved.or_else(|| {
+            self.archetypes
+                .get(&npc.npc_type)
+                .map(|archetype| self.archetype_faction(&archetype))
+        });
+        if let Some(faction) = faction {
+            self.npc_factions.insert(*ulid, faction);
+        }
+    }
+}
@@ -0,0 +1,287 @@
+// ============================================================================
+// SPATIAL GRID - Uniform-grid broadphase for range and nearest-hostile queries
+// ============================================================================
+// Rebuilt from the per-tick NPC rows, so it never goes stale against the
+// warehouse. Cells store row indices in ascending order, which keeps query
+// results in ULID order (the rows are sorted) and the simulation deterministic.
+
+use bevy::math::Vec2;
+use std::collections::HashMap;
+
+use super::warehouse::NpcRow;
+
+/// Cell size in pixels - roughly half the largest attack range, so a range
+/// query touches a handful of cells
+pub const GRID_CELL_SIZE: f32 = 100.0;
+
+type CellKey = (i32, i32);
+
+/// Uniform grid over NPC row indices
+pub(crate) struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<CellKey, Vec<usize>>,
+    positions: Vec<Vec2>,
+    /// Bounding box of occupied cells (min, max) - limits nearest searches
+    extent: Option<(CellKey, CellKey)>,
+}
+
+impl SpatialGrid {
+    /// Build a grid over the rows (row index = position in `npcs`)
+    pub fn build(npcs: &[NpcRow], cell_size: f32) -> Self {
+        let mut grid = Self {
+            cell_size,
+            cells: HashMap::with_capacity(npcs.len()),
+            positions: Vec::with_capacity(npcs.len()),
+            extent: None,
+        };
+
+        for (index, row) in npcs.iter().enumerate() {
+            let pos = row.pos;
+            let key = grid.cell_of(pos);
+            grid.positions.push(pos);
+            grid.cells.entry(key).or_default().push(index);
+            grid.extent = Some(match grid.extent {
+                Some((min, max)) => (
+                    (min.0.min(key.0), min.1.min(key.1)),
+                    (max.0.max(key.0), max.1.max(key.1)),
+                ),
+                None => (key, key),
+            });
+        }
+
+        grid
+    }
+
+    fn cell_of(&self, pos: Vec2) -> CellKey {
+        (
+            (pos.x / self.cell_size).floor() as i32,
+            (pos.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Row indices within `radius` of `pos`, in ascending index order
+    pub fn query_radius(&self, pos: Vec2, radius: f32) -> Vec<usize> {
+        let mut found = Vec::new();
+        let (min_cx, min_cy) = self.cell_of(pos - Vec2::splat(radius));
+        let (max_cx, max_cy) = self.cell_of(pos + Vec2::splat(radius));
+
+        for cx in min_cx..=max_cx {
+            for cy in min_cy..=max_cy {
+                if let Some(indices) = self.cells.get(&(cx, cy)) {
+                    found.extend(
+                        indices
+                            .iter()
+                            .copied()
+                            .filter(|&i| self.positions[i].distance(pos) <= radius),
+                    );
+                }
+            }
+        }
+
+        found.sort_unstable();
+        found
+    }
+
+    /// Nearest row accepted by `filter`, searching outward ring by ring
+    /// Ties resolve to the lowest row index (same as a linear scan)
+    /// Returns: Option<(row_index, distance)>
+    pub fn nearest<F>(&self, pos: Vec2, mut filter: F) -> Option<(usize, f32)>
+    where
+        F: FnMut(usize) -> bool,
+    {
+        let ((min_cx, min_cy), (max_cx, max_cy)) = self.extent?;
+        let (cx, cy) = self.cell_of(pos);
+        let mut best: Option<(usize, f32)> = None;
+        let mut ring = 0i32;
+
+        loop {
+            // Perimeter cells of this ring, clipped to the occupied extent
+            let x_range = (cx - ring).max(min_cx)..=(cx + ring).min(max_cx);
+            let y_range = (cy - ring + 1).max(min_cy)..=(cy + ring - 1).min(max_cy);
+            let mut ring_cells: Vec<CellKey> = Vec::new();
+            for y in [cy - ring, cy + ring] {
+                if (min_cy..=max_cy).contains(&y) {
+                    ring_cells.extend(x_range.clone().map(|x| (x, y)));
+                }
+                if ring == 0 {
+                    break;
+                }
+            }
+            if ring > 0 {
+                for x in [cx - ring, cx + ring] {
+                    if (min_cx..=max_cx).contains(&x) {
+                        ring_cells.extend(y_range.clone().map(|y| (x, y)));
+                    }
+                }
+            }
+
+            for key in ring_cells {
+                let Some(indices) = self.cells.get(&key) else {
+                    continue;
+                };
+                for &index in indices {
+                    let distance = self.positions[index].distance(pos);
+                    let closer = match best {
+                        Some((best_index, best_distance)) => {
+                            distance < best_distance
+                                || (distance == best_distance && index < best_index)
+                        }
+                        None => true,
+                    };
+                    if closer && filter(index) {
+                        best = Some((index, distance));
+                    }
+                }
+            }
+
+            // Anything on the next ring is at least ring * cell_size away
+            if let Some((_, best_distance)) = best {
+                if best_distance < ring as f32 * self.cell_size {
+                    return best;
+                }
+            }
+
+            // Ring covers every occupied cell - nothing left to search
+            if cx - ring <= min_cx
+                && cx + ring >= max_cx
+                && cy - ring <= min_cy
+                && cy + ring >= max_cy
+            {
+                return best;
+            }
+
+            ring += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::factions::FactionId;
+    use crate::simulation::stats::{NPCState, NPCStaticState};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn rows(positions: &[Vec2]) -> Vec<NpcRow> {
+        positions
+            .iter()
+            .enumerate()
+            .map(|(i, &pos)| NpcRow {
+                ulid: (i as u128).to_be_bytes(),
+                pos,
+                static_state: NPCStaticState::empty(),
+                faction: FactionId::ALLY,
+                state: NPCState::IDLE,
+                attack_range: 0.0,
+            })
+            .collect()
+    }
+
+    /// Linear scan: nearest accepted index, ties to the lowest index
+    fn brute_nearest(
+        positions: &[Vec2],
+        pos: Vec2,
+        filter: impl Fn(usize) -> bool,
+    ) -> Option<(usize, f32)> {
+        let mut best: Option<(usize, f32)> = None;
+        for (index, p) in positions.iter().enumerate() {
+            let distance = p.distance(pos);
+            if filter(index) && best.is_none_or(|(_, d)| distance < d) {
+                best = Some((index, distance));
+            }
+        }
+        best
+    }
+
+    fn brute_radius(positions: &[Vec2], pos: Vec2, radius: f32) -> Vec<usize> {
+        (0..positions.len())
+            .filter(|&i| positions[i].distance(pos) <= radius)
+            .collect()
+    }
+
+    #[test]
+    fn matches_linear_scan_on_random_positions() {
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..50 {
+            let count = rng.random_range(1..80);
+            // Half the layouts sit on a 50px lattice, so exact ties are common
+            let snap = rng.random_bool(0.5);
+            let positions: Vec<Vec2> = (0..count)
+                .map(|_| {
+                    let p = Vec2::new(
+                        rng.random_range(-900.0..900.0),
+                        rng.random_range(-600.0..600.0),
+                    );
+                    if snap {
+                        (p / 50.0).round() * 50.0
+                    } else {
+                        p
+                    }
+                })
+                .collect();
+            let grid = SpatialGrid::build(&rows(&positions), GRID_CELL_SIZE);
+
+            for _ in 0..40 {
+                // Mostly inside the extent, sometimes far outside it
+                let spread = if rng.random_bool(0.2) {
+                    20_000.0
+                } else {
+                    1_000.0
+                };
+                let mut pos = Vec2::new(
+                    rng.random_range(-spread..spread),
+                    rng.random_range(-spread..spread),
+                );
+                if snap {
+                    pos = (pos / 50.0).round() * 50.0;
+                }
+                let modulus = rng.random_range(1..4);
+                let filter = |i: usize| i.is_multiple_of(modulus);
+                assert_eq!(
+                    grid.nearest(pos, filter),
+                    brute_nearest(&positions, pos, filter)
+                );
+
+                let radius = rng.random_range(0.0..400.0);
+                assert_eq!(
+                    grid.query_radius(pos, radius),
+                    brute_radius(&positions, pos, radius)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn exact_ties_go_to_the_lowest_index() {
+        // Four points at distance 150 around the origin, in different cells,
+        // listed so the lowest index is not the first cell searched
+        let positions = [
+            Vec2::new(150.0, 0.0),
+            Vec2::new(0.0, -150.0),
+            Vec2::new(-150.0, 0.0),
+            Vec2::new(0.0, 150.0),
+        ];
+        let grid = SpatialGrid::build(&rows(&positions), GRID_CELL_SIZE);
+        assert_eq!(grid.nearest(Vec2::ZERO, |_| true), Some((0, 150.0)));
+        assert_eq!(grid.nearest(Vec2::ZERO, |i| i != 0), Some((1, 150.0)));
+        assert_eq!(grid.nearest(Vec2::ZERO, |i| i >= 2), Some((2, 150.0)));
+    }
+
+    #[test]
+    fn far_queries_and_empty_grids() {
+        let positions = [Vec2::new(-250.0, -250.0), Vec2::new(-240.0, -260.0)];
+        let grid = SpatialGrid::build(&rows(&positions), GRID_CELL_SIZE);
+        let far = Vec2::new(50_000.0, -80_000.0);
+        assert_eq!(
+            grid.nearest(far, |_| true),
+            brute_nearest(&positions, far, |_| true)
+        );
+        assert_eq!(grid.nearest(far, |_| false), None);
+        assert!(grid.query_radius(far, 1_000.0).is_empty());
+
+        let empty = SpatialGrid::build(&[], GRID_CELL_SIZE);
+        assert_eq!(empty.nearest(Vec2::ZERO, |_| true), None);
+        assert!(empty.query_radius(Vec2::ZERO, 1_000.0).is_empty());
+    }
+}
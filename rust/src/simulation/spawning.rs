@@ -0,0 +1,313 @@
+// ============================================================================
+// SPAWNING - Initial spawn, monster waves and ally ramp-up
+// ============================================================================
+
+use bevy::math::Vec2;
+use std::sync::atomic::AtomicU64;
+
+use super::events::CombatEvent;
+use super::log::{sim_print, sim_warn};
+use super::stats::NPCStaticState;
+use super::warehouse::NPCDataWarehouse;
+
+impl NPCDataWarehouse {
+    /// Check if this is the first combat tick and spawn minimal entities for debugging
+    /// Spawns: 1 warrior, 1 archer, 2 random monsters
+    pub(super) fn check_initial_spawn(&self, now_ms: u64) -> Vec<CombatEvent> {
+        use std::sync::atomic::Ordering;
+
+        // Check if initial spawn already done
+        if self.initial_spawn_done.load(Ordering::Relaxed) {
+            return Vec::new(); // Already spawned
+        }
+
+        sim_print!("[RUST SPAWN] check_initial_spawn called - checking prerequisites...");
+
+        // Check if the host has opened spawning (scene container set in Godot)
+        if !self.spawning_enabled.load(Ordering::Relaxed) {
+            sim_print!("[RUST SPAWN] Spawning not enabled yet - skipping spawn");
+            // Host not ready yet - likely still in title/intro scene
+            // Don't mark as done, just skip this tick
+            return Vec::new();
+        }
+
+        sim_print!("[RUST SPAWN] Prerequisites met - starting initial spawn!");
+
+        // Mark initial spawn as done FIRST to prevent race conditions
+        self.initial_spawn_done.store(true, Ordering::Relaxed);
+
+        // Set the timers so regular spawning doesn't trigger immediately
+        self.last_spawn_time_ms.store(now_ms, Ordering::Relaxed);
+        self.last_ally_spawn_time_ms
+            .store(now_ms, Ordering::Relaxed);
+
+        sim_print!("[RUST SPAWN] Initial spawn starting...");
+
+        // Calculate spawn positions and center point
+        let world_min_x = f32::from_bits(self.world_min_x.load(Ordering::Relaxed));
+        let world_max_x = f32::from_bits(self.world_max_x.load(Ordering::Relaxed));
+        let world_min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
+        let world_max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
+        let center_x = (world_min_x + world_max_x) / 2.0;
+
+        // Calculate spawn positions based on world bounds
+        let ally_spawn_x = world_min_x + (world_max_x - world_min_x) * 0.25; // 25% from left
+        let archer_spawn_x = world_min_x + (world_max_x - world_min_x) * 0.20; // 20% from left (behind warriors)
+        let monster_spawn_x = world_min_x + (world_max_x - world_min_x) * 0.75; // 75% from left (right side)
+
+        // Scatter comes from the simulation RNG (locked per draw - spawning re-locks it)
+        use rand::Rng;
+
+        // Spawn 6 warriors on left side - scattered vertically to avoid stacking
+        for i in 0..6 {
+            let base_y = world_min_y + (world_max_y - world_min_y) * (i as f32 / 5.0); // Evenly distribute
+            let scatter = self.rng().random_range(-15.0..15.0); // Add random scatter
+            let warrior_y = (base_y + scatter).clamp(world_min_y + 20.0, world_max_y - 20.0);
+            let scatter_x = self.rng().random_range(-10.0..10.0); // Small horizontal scatter
+            let warrior_pos = Vec2::new(ally_spawn_x + scatter_x, warrior_y);
+            let warrior_ulid = self.spawn_from_pool("warrior", warrior_pos);
+
+            // Give warrior initial waypoint toward center-right (to meet monsters)
+            if let Some(ulid_bytes) = warrior_ulid {
+                self.npc_waypoints
+                    .insert(ulid_bytes, Vec2::new(center_x - 100.0, warrior_y));
+            }
+        }
+        sim_print!(
+            "[RUST SPAWN] 6 warriors spawned (scattered) at x≈{} with waypoints toward center",
+            ally_spawn_x
+        );
+
+        // Spawn 6 archers on left side - scattered to avoid stacking with warriors
+        for i in 0..6 {
+            let base_y = world_min_y + (world_max_y - world_min_y) * (i as f32 / 5.0); // Evenly distribute
+            let scatter = self.rng().random_range(-15.0..15.0); // Add random scatter
+            let archer_y = (base_y + scatter).clamp(world_min_y + 20.0, world_max_y - 20.0);
+            let scatter_x = self.rng().random_range(-10.0..10.0); // Small horizontal scatter
+            let archer_pos = Vec2::new(archer_spawn_x + scatter_x, archer_y);
+            let archer_ulid = self.spawn_from_pool("archer", archer_pos);
+
+            // Give archer waypoint toward center-right (stays behind warriors)
+            if let Some(ulid_bytes) = archer_ulid {
+                self.npc_waypoints
+                    .insert(ulid_bytes, Vec2::new(center_x - 150.0, archer_y));
+            }
+        }
+        sim_print!(
+            "[RUST SPAWN] 6 archers spawned (scattered) at x≈{} with waypoints toward center",
+            archer_spawn_x
+        );
+
+        // Spawn 8 random monsters on right side - scattered to avoid stacking
+        // (types are picked from archetypes with a wave_weight)
+        for i in 0..8 {
+            let Some(monster_type) = self.archetypes.pick_wave_monster(&mut *self.rng()) else {
+                sim_warn!("[RUST SPAWN] No wave archetypes registered - skipping monsters");
+                break;
+            };
+            let base_y = world_min_y + (world_max_y - world_min_y) * (i as f32 / 7.0); // Evenly distribute
+            let scatter = self.rng().random_range(-15.0..15.0); // Add random scatter
+            let monster_y = (base_y + scatter).clamp(world_min_y + 20.0, world_max_y - 20.0);
+            let scatter_x = self.rng().random_range(-10.0..10.0); // Small horizontal scatter
+            let monster_pos = Vec2::new(monster_spawn_x + scatter_x, monster_y);
+
+            let monster_ulid = self.spawn_from_pool(&monster_type, monster_pos);
+
+            // Give monster waypoint toward center-left (to meet allies)
+            if let Some(ulid_bytes) = monster_ulid {
+                self.npc_waypoints
+                    .insert(ulid_bytes, Vec2::new(center_x + 100.0, monster_y));
+            }
+        }
+        sim_print!(
+            "[RUST SPAWN] 8 monsters spawned (scattered) at x≈{} with waypoints toward center",
+            monster_spawn_x
+        );
+
+        sim_print!(
+            "[RUST SPAWN] Initial spawn complete: 6 warriors, 6 archers, 8 monsters (12 vs 8)"
+        );
+
+        Vec::new() // No events needed - NPCs are spawned directly
+    }
+
+    /// Check if we should spawn a new wave of monsters
+    /// Returns spawn events for GDScript to handle
+    pub(super) fn check_spawn_wave(&self, now_ms: u64) -> Vec<CombatEvent> {
+        use std::sync::atomic::Ordering;
+        let events = Vec::new();
+
+        // Check if the host has opened spawning (scene container set in Godot)
+        if !self.spawning_enabled.load(Ordering::Relaxed) {
+            return events; // Spawning not enabled yet
+        }
+
+        // Count active monsters (NPCs with MONSTER faction)
+        let monster_count = self
+            .active_combat_npcs
+            .iter()
+            .filter(|entry| {
+                self.get_combat_stats(entry.key())
+                    .is_some_and(|stats| stats.static_flags().contains(NPCStaticState::MONSTER))
+            })
+            .count();
+
+        // Check if we need a new wave (low monster count or interval elapsed)
+        let last_spawn = self.last_spawn_time_ms.load(Ordering::Relaxed);
+        let time_since_spawn = now_ms.saturating_sub(last_spawn);
+
+        let should_spawn = (monster_count < self.min_active_monsters as usize)
+            || (time_since_spawn >= self.spawn_interval_ms);
+
+        if should_spawn {
+            // Update last spawn time
+            self.last_spawn_time_ms.store(now_ms, Ordering::Relaxed);
+
+            // Generate wave size (random between min and max)
+            use rand::Rng;
+            let wave_size = self
+                .rng()
+                .random_range(self.min_wave_size..=self.max_wave_size);
+
+            sim_print!(
+                "[RUST SPAWN] Spawning wave of {} monsters (current: {})",
+                wave_size,
+                monster_count
+            );
+
+            // Get spawn positions
+            let world_min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
+            let world_max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
+
+            // Spawn each monster directly (right side of visible screen)
+            for _ in 0..wave_size {
+                // Monster type from the archetype registry (weighted by wave_weight)
+                let Some(monster_type) = self.archetypes.pick_wave_monster(&mut *self.rng()) else {
+                    sim_warn!("[RUST SPAWN] No wave archetypes registered - skipping wave");
+                    break;
+                };
+                let spawn_pos = Vec2::new(
+                    1050.0, // Right side of screen
+                    self.rng().random_range(world_min_y..world_max_y),
+                );
+
+                if let Some(_ulid) = self.spawn_from_pool(&monster_type, spawn_pos) {
+                    // Note: spawn_from_pool already registers for combat via register_npc_with_stats
+                }
+            }
+        }
+
+        events
+    }
+
+    /// Check if we should spawn allies (warriors, archers, clerics)
+    /// Returns spawn events for GDScript to handle
+    /// Spawns gradually to ramp up (one ally every 3 seconds until cap reached)
+    pub(super) fn check_ally_spawn(&self, now_ms: u64) -> Vec<CombatEvent> {
+        use std::sync::atomic::Ordering;
+        let events = Vec::new();
+
+        // Check if the host has opened spawning (scene container set in Godot)
+        if !self.spawning_enabled.load(Ordering::Relaxed) {
+            return events; // Spawning not enabled yet
+        }
+
+        // Count active warriors, archers and clerics
+        let mut warrior_count = 0;
+        let mut archer_count = 0;
+        let mut cleric_count = 0;
+
+        for entry in self.active_combat_npcs.iter() {
+            if let Some(stats) = self.get_combat_stats(entry.key()) {
+                let state = stats.static_flags();
+                // Check if ALLY faction
+                if state.contains(NPCStaticState::ALLY) {
+                    // Check combat type
+                    if state.contains(NPCStaticState::MELEE) {
+                        warrior_count += 1;
+                    } else if state.contains(NPCStaticState::RANGED) {
+                        archer_count += 1;
+                    } else if state.contains(NPCStaticState::HEALER) {
+                        cleric_count += 1;
+                    }
+                }
+            }
+        }
+
+        // Check if enough time has passed since last ally spawn
+        let last_ally_spawn = self.last_ally_spawn_time_ms.load(Ordering::Relaxed);
+        let time_since_spawn = now_ms.saturating_sub(last_ally_spawn);
+
+        // Debug logging every 5 seconds
+        static LAST_DEBUG_LOG: AtomicU64 = AtomicU64::new(0);
+        if now_ms.abs_diff(LAST_DEBUG_LOG.load(Ordering::Relaxed)) > 5000 {
+            sim_print!(
+                "[ALLY SPAWN CHECK] Warriors: {}/{}, Archers: {}/{}, Clerics: {}/{}, Time since spawn: {}ms (need {}ms)",
+                warrior_count,
+                self.max_warriors,
+                archer_count,
+                self.max_archers,
+                cleric_count,
+                self.max_clerics,
+                time_since_spawn,
+                self.ally_spawn_interval_ms
+            );
+            LAST_DEBUG_LOG.store(now_ms, Ordering::Relaxed);
+        }
+
+        if time_since_spawn < self.ally_spawn_interval_ms {
+            return events; // Not time yet
+        }
+
+        // Spawn one ally at a time (warrior, archer or cleric, alternating priority)
+        // Prioritize whichever is further from cap
+        let warrior_deficit = self.max_warriors - warrior_count;
+        let archer_deficit = self.max_archers - archer_count;
+        let cleric_deficit = self.max_clerics - cleric_count;
+
+        if warrior_deficit > 0 || archer_deficit > 0 || cleric_deficit > 0 {
+            // Update last spawn time
+            self.last_ally_spawn_time_ms
+                .store(now_ms, Ordering::Relaxed);
+
+            // Decide which to spawn (prioritize bigger deficit)
+            let ally_type =
+                if warrior_deficit >= archer_deficit.max(cleric_deficit) && warrior_deficit > 0 {
+                    "warrior"
+                } else if archer_deficit >= cleric_deficit && archer_deficit > 0 {
+                    "archer"
+                } else if cleric_deficit > 0 {
+                    "cleric"
+                } else {
+                    return events; // All at cap
+                };
+
+            // Get spawn positions
+            let world_min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
+            let world_max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
+
+            // Spawn ally on left side (visible screen area)
+            use rand::Rng;
+            let spawn_pos = Vec2::new(
+                150.0, // Left side of screen
+                self.rng().random_range(world_min_y..world_max_y),
+            );
+
+            if let Some(_ulid) = self.spawn_from_pool(ally_type, spawn_pos) {
+                // Note: spawn_from_pool already registers for combat via register_npc_with_stats
+                sim_print!(
+                    "[RUST SPAWN] Spawned {} (Warriors: {}/{}, Archers: {}/{}, Clerics: {}/{})",
+                    ally_type,
+                    warrior_count,
+                    self.max_warriors,
+                    archer_count,
+                    self.max_archers,
+                    cleric_count,
+                    self.max_clerics
+                );
+            }
+        }
+
+        events
+    }
+}
@@ -0,0 +1,598 @@
+// ============================================================================
+// SQUADS - Named groups that march and hold a formation together
+// ============================================================================
+// A squad is a named group of NPCs from one faction with a leader.
+// Its formation lays the members out around an anchor point, facing the way
+// the squad was last ordered to march:
+//   - line     side by side, the leader in the middle
+//   - wedge    the leader at the tip, the others in rows fanning out behind
+//   - column   single file behind the leader
+//   - ranked   melee in a front line, ranged units and healers in a second
+//              line RANK_SPACING behind it
+// Slots are handed out again whenever the members, the formation or the
+// orders change: the leader keeps the first slot of its line and every other
+// slot goes to the nearest member still without one, so nobody crosses the
+// formation to reach its place.
+//
+// Every movement phase (after targets are picked, see movement.rs) an ordered
+// squad's anchor advances toward its target at the speed of its slowest
+// member, and waits while a member out of combat is more than FORMATION_SLACK
+// from its slot. Members walk to their slots instead of wandering and hold
+// them in combat too, shooting whatever comes in range, until their target
+// comes within SQUAD_LEASH of their slot; then they go after it, but never
+// further than SQUAD_LEASH from the slot. The squad holds a front line
+// instead of breaking into a swarm.
+//
+// Membership ends with the NPC (death, despawn, unregistering) or a faction
+// change. A leader that leaves hands the squad to the next member in slot
+// order and a squad that loses its last member is disbanded. Arrivals, leader
+// changes and disbands queue a SquadEvent.
+
+use bevy::math::Vec2;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+
+use super::archetypes::DEFAULT_MOVE_SPEED;
+use super::live::LiveState;
+use super::log::sim_print;
+use super::replay::RecordedInput;
+use super::stats::{bytes_to_hex, NPCState, NPCStaticState};
+use super::warehouse::NPCDataWarehouse;
+
+/// Distance between neighbouring slots (pixels)
+pub const FORMATION_SPACING: f32 = 40.0;
+
+/// Distance between the front and back lines of a ranked formation (pixels)
+pub const RANK_SPACING: f32 = 60.0;
+
+/// How far a member out of combat may lag behind its slot before the squad
+/// waits for it (pixels)
+pub const FORMATION_SLACK: f32 = 60.0;
+
+/// How far members in combat may leave their slot (pixels)
+pub const SQUAD_LEASH: f32 = 120.0;
+
+/// Most members a squad can have
+pub const MAX_SQUAD_SIZE: usize = 24;
+
+/// Distance from its slot at which a member counts as in place (pixels)
+const SLOT_TOLERANCE: f32 = 2.0;
+
+/// How a squad lays out its members
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Formation {
+    /// Side by side, the leader in the middle
+    #[default]
+    Line,
+    /// Leader at the tip, rows fanning out behind
+    Wedge,
+    /// Single file behind the leader
+    Column,
+    /// Melee in front, ranged units and healers in a line behind
+    Ranked,
+}
+
+impl Formation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Formation::Line => "line",
+            Formation::Wedge => "wedge",
+            Formation::Column => "column",
+            Formation::Ranked => "ranked",
+        }
+    }
+
+    /// Parse "line" / "wedge" / "column" / "ranked"
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "line" => Some(Formation::Line),
+            "wedge" => Some(Formation::Wedge),
+            "column" => Some(Formation::Column),
+            "ranked" => Some(Formation::Ranked),
+            _ => None,
+        }
+    }
+
+    /// Slot offsets (forward, sideways) from the anchor for `front` members
+    /// in the front line and `back` in the back line, each flagged with the
+    /// line it belongs to (only ranked formations have a back line)
+    fn places(&self, front: usize, back: usize) -> Vec<(bool, Vec2)> {
+        let count = front + back;
+        match self {
+            Formation::Line => (0..count)
+                .map(|i| (false, Vec2::new(0.0, line_place(i))))
+                .collect(),
+            Formation::Wedge => (0..count)
+                .map(|i| {
+                    let row = i.div_ceil(2) as f32 * FORMATION_SPACING;
+                    (false, Vec2::new(-row, line_place(i)))
+                })
+                .collect(),
+            Formation::Column => (0..count)
+                .map(|i| (false, Vec2::new(-(i as f32) * FORMATION_SPACING, 0.0)))
+                .collect(),
+            Formation::Ranked => {
+                // With no melee the back line moves up to the front
+                let back_x = if front > 0 { -RANK_SPACING } else { 0.0 };
+                (0..front)
+                    .map(|i| (false, Vec2::new(0.0, line_place(i))))
+                    .chain((0..back).map(|i| (true, Vec2::new(back_x, line_place(i)))))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Sideways offset of the i-th place in a line: middle, right, left,
+/// further right, further left...
+fn line_place(i: usize) -> f32 {
+    let offset = i.div_ceil(2) as f32 * FORMATION_SPACING;
+    if i % 2 == 1 {
+        offset
+    } else {
+        -offset
+    }
+}
+
+/// Squad names are single words (they appear in recordings)
+fn validate_squad_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.chars().any(char::is_whitespace) {
+        return Err(format!("invalid squad name '{}' (use a single word)", name));
+    }
+    Ok(())
+}
+
+/// A named group of NPCs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Squad {
+    pub name: String,
+    pub leader: [u8; 16],
+    pub formation: Formation,
+    /// Members in slot order (the leader first), each with its slot offset
+    /// (forward, sideways) from the anchor
+    pub members: Vec<([u8; 16], Vec2)>,
+    /// Point the formation is laid out around
+    pub anchor: Vec2,
+    /// Direction the formation faces (unit length)
+    pub facing: Vec2,
+    /// Where the squad is marching (None = holding its ground)
+    pub order: Option<Vec2>,
+}
+
+impl Squad {
+    /// Position of a slot offset in the world
+    pub fn slot_position(&self, offset: Vec2) -> Vec2 {
+        self.anchor + self.facing * offset.x + self.facing.perp() * offset.y
+    }
+
+    /// Slot offset of a member (None if it isn't one)
+    pub fn slot_of(&self, ulid: &[u8; 16]) -> Option<Vec2> {
+        self.members
+            .iter()
+            .find(|(member, _)| member == ulid)
+            .map(|(_, offset)| *offset)
+    }
+}
+
+/// A squad arrived, changed leader or was disbanded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SquadEvent {
+    /// A squad reached the point it was ordered to
+    Arrived { name: String },
+    /// A squad's leader left and the next member took over
+    LeaderChanged { name: String, leader: [u8; 16] },
+    /// A squad was disbanded (or lost its last member)
+    Disbanded { name: String },
+}
+
+impl NPCDataWarehouse {
+    /// Form a squad led by `leader` (taking it out of its current squad)
+    pub fn create_squad(
+        &self,
+        name: &str,
+        leader: &[u8; 16],
+        formation: Formation,
+    ) -> Result<(), String> {
+        validate_squad_name(name)?;
+        if self.squads.lock().contains_key(name) {
+            return Err(format!("squad '{}' already exists", name));
+        }
+        let anchor = self.squad_candidate(leader)?;
+        self.record_input(RecordedInput::CreateSquad {
+            name: name.to_string(),
+            leader: *leader,
+            formation,
+        });
+
+        self.drop_from_squad(&self.stored(), leader);
+        // Face the far side of the world from the faction's wander zone (by
+        // default allies face right, monsters left)
+        let facing = match self
+            .npc_faction(leader)
+            .and_then(|faction| self.faction_table().wander_zone(faction))
+        {
+            Some((from, to)) if from + to > 1.0 => Vec2::NEG_X,
+            _ => Vec2::X,
+        };
+        self.npc_squads.insert(*leader, name.to_string());
+        self.squads.lock().insert(
+            name.to_string(),
+            Squad {
+                name: name.to_string(),
+                leader: *leader,
+                formation,
+                members: vec![(*leader, Vec2::ZERO)],
+                anchor,
+                facing,
+                order: None,
+            },
+        );
+        sim_print!(
+            "[SQUADS] Squad '{}' formed around {} ({})",
+            name,
+            &bytes_to_hex(leader)[..8],
+            formation.as_str()
+        );
+        Ok(())
+    }
+
+    /// Add an NPC to a squad (taking it out of its current squad)
+    pub fn assign_to_squad(&self, name: &str, ulid: &[u8; 16]) -> Result<(), String> {
+        self.squad_candidate(ulid)?;
+        {
+            let squads = self.squads.lock();
+            let squad = squads
+                .get(name)
+                .ok_or_else(|| format!("unknown squad '{}'", name))?;
+            if squad.slot_of(ulid).is_some() {
+                return Ok(());
+            }
+            if squad.members.len() >= MAX_SQUAD_SIZE {
+                return Err(format!(
+                    "squad '{}' is full ({} members)",
+                    name, MAX_SQUAD_SIZE
+                ));
+            }
+            if self.npc_faction(ulid) != self.npc_faction(&squad.leader) {
+                return Err(format!(
+                    "NPC {} is not in the faction of squad '{}'",
+                    &bytes_to_hex(ulid)[..16],
+                    name
+                ));
+            }
+        }
+        self.record_input(RecordedInput::AssignSquad {
+            name: name.to_string(),
+            ulid: *ulid,
+        });
+
+        self.drop_from_squad(&self.stored(), ulid);
+        let mut squads = self.squads.lock();
+        if let Some(squad) = squads.get_mut(name) {
+            squad.members.push((*ulid, Vec2::ZERO));
+            self.assign_slots(&self.stored(), squad);
+            self.npc_squads.insert(*ulid, name.to_string());
+        }
+        Ok(())
+    }
+
+    /// Take an NPC out of its squad
+    /// Returns false if it wasn't in one
+    pub fn remove_from_squad(&self, ulid: &[u8; 16]) -> bool {
+        if !self.npc_squads.contains_key(ulid) {
+            return false;
+        }
+        self.record_input(RecordedInput::RemoveFromSquad { ulid: *ulid });
+        self.drop_from_squad(&self.stored(), ulid);
+        true
+    }
+
+    /// Disband a squad - its members go back to acting on their own
+    /// Returns false if there is no such squad
+    pub fn disband_squad(&self, name: &str) -> bool {
+        let Some(squad) = self.squads.lock().remove(name) else {
+            return false;
+        };
+        self.record_input(RecordedInput::DisbandSquad {
+            name: name.to_string(),
+        });
+        for (ulid, _) in &squad.members {
+            self.npc_squads.remove(ulid);
+        }
+        self.squad_events.push(SquadEvent::Disbanded {
+            name: name.to_string(),
+        });
+        sim_print!("[SQUADS] Squad '{}' disbanded", name);
+        true
+    }
+
+    /// Order a squad to march to `target`, facing the way it marches
+    pub fn order_squad(&self, name: &str, target: Vec2) -> Result<(), String> {
+        if !self.squads.lock().contains_key(name) {
+            return Err(format!("unknown squad '{}'", name));
+        }
+        self.record_input(RecordedInput::OrderSquad {
+            name: name.to_string(),
+            target,
+        });
+
+        let min_x = f32::from_bits(self.world_min_x.load(Ordering::Relaxed));
+        let max_x = f32::from_bits(self.world_max_x.load(Ordering::Relaxed));
+        let min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
+        let max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
+        let target = Vec2::new(target.x.clamp(min_x, max_x), target.y.clamp(min_y, max_y));
+
+        let mut squads = self.squads.lock();
+        if let Some(squad) = squads.get_mut(name) {
+            let heading = target - squad.anchor;
+            if heading.length() > 1.0 {
+                squad.facing = heading.normalize();
+            }
+            squad.order = Some(target);
+            self.assign_slots(&self.stored(), squad);
+            sim_print!(
+                "[SQUADS] Squad '{}' marching to ({:.0}, {:.0})",
+                name,
+                target.x,
+                target.y
+            );
+        }
+        Ok(())
+    }
+
+    /// Change a squad's formation
+    pub fn set_squad_formation(&self, name: &str, formation: Formation) -> Result<(), String> {
+        if !self.squads.lock().contains_key(name) {
+            return Err(format!("unknown squad '{}'", name));
+        }
+        self.record_input(RecordedInput::SquadFormation {
+            name: name.to_string(),
+            formation,
+        });
+
+        let mut squads = self.squads.lock();
+        if let Some(squad) = squads.get_mut(name) {
+            squad.formation = formation;
+            self.assign_slots(&self.stored(), squad);
+        }
+        Ok(())
+    }
+
+    /// Squad names, in name order
+    pub fn squad_names(&self) -> Vec<String> {
+        self.squads.lock().keys().cloned().collect()
+    }
+
+    /// A squad's current layout (None if there is no such squad)
+    pub fn squad(&self, name: &str) -> Option<Squad> {
+        self.squads.lock().get(name).cloned()
+    }
+
+    /// Every squad, in name order
+    pub fn squad_list(&self) -> Vec<Squad> {
+        self.squads.lock().values().cloned().collect()
+    }
+
+    /// Name of the squad an NPC belongs to
+    pub fn npc_squad(&self, ulid: &[u8; 16]) -> Option<String> {
+        self.npc_squads.get(ulid).map(|v| v.value().clone())
+    }
+
+    /// Pop the next squad event (None when the queue is empty)
+    pub fn pop_squad_event(&self) -> Option<SquadEvent> {
+        self.squad_events.pop()
+    }
+
+    /// Take an NPC out of its squad, handing over the lead or disbanding the
+    /// squad as needed (death, despawn, faction change)
+    pub(super) fn drop_from_squad(&self, npcs: &impl LiveState, ulid: &[u8; 16]) {
+        let Some((_, name)) = self.npc_squads.remove(ulid) else {
+            return;
+        };
+        let mut squads = self.squads.lock();
+        let Some(squad) = squads.get_mut(&name) else {
+            return;
+        };
+        squad.members.retain(|(member, _)| member != ulid);
+        if squad.members.is_empty() {
+            squads.remove(&name);
+            sim_print!("[SQUADS] Squad '{}' lost its last member", name);
+            self.squad_events.push(SquadEvent::Disbanded { name });
+            return;
+        }
+        if squad.leader == *ulid {
+            squad.leader = squad.members[0].0;
+            self.squad_events.push(SquadEvent::LeaderChanged {
+                name: name.clone(),
+                leader: squad.leader,
+            });
+        }
+        self.assign_slots(npcs, squad);
+    }
+
+    /// Advance ordered squads and keep members on (or near) their slots
+    /// Runs after movement directions are picked, before waypoints are walked
+    pub(super) fn run_squads(&self, npcs: &mut impl LiveState, delta: f32) {
+        let mut squads = self.squads.lock();
+        if squads.is_empty() {
+            return;
+        }
+        let min_x = f32::from_bits(self.world_min_x.load(Ordering::Relaxed));
+        let max_x = f32::from_bits(self.world_max_x.load(Ordering::Relaxed));
+        let min_y = f32::from_bits(self.world_min_y.load(Ordering::Relaxed));
+        let max_y = f32::from_bits(self.world_max_y.load(Ordering::Relaxed));
+        let clamp_to_world =
+            |pos: Vec2| Vec2::new(pos.x.clamp(min_x, max_x), pos.y.clamp(min_y, max_y));
+
+        for squad in squads.values_mut() {
+            // March - unless someone out of combat fell behind
+            if let Some(target) = squad.order {
+                let in_place = squad.members.iter().all(|(ulid, offset)| {
+                    npcs.behavior(ulid)
+                        .is_some_and(|state| state.contains(NPCState::COMBAT))
+                        || npcs.position(ulid).is_none_or(|pos| {
+                            pos.distance(clamp_to_world(squad.slot_position(*offset)))
+                                <= FORMATION_SLACK
+                        })
+                });
+                if in_place {
+                    let speed = squad
+                        .members
+                        .iter()
+                        .map(|(ulid, _)| {
+                            self.npc_combat_stats
+                                .get(ulid)
+                                .map_or(DEFAULT_MOVE_SPEED, |stats| stats.move_speed)
+                                * self.status_modifiers(ulid).move_speed
+                        })
+                        .fold(f32::INFINITY, f32::min);
+                    let step = speed * delta;
+                    let remaining = target - squad.anchor;
+                    let distance = remaining.length();
+                    if distance <= step {
+                        squad.anchor = target;
+                        squad.order = None;
+                        sim_print!("[SQUADS] Squad '{}' arrived", squad.name);
+                        self.squad_events.push(SquadEvent::Arrived {
+                            name: squad.name.clone(),
+                        });
+                    } else if step > 0.0 {
+                        squad.anchor += remaining / distance * step;
+                    }
+                }
+            }
+
+            // Send members to their slots
+            for (ulid, offset) in &squad.members {
+                let Some(state) = npcs.behavior(ulid) else {
+                    continue;
+                };
+                let Some(pos) = npcs.position(ulid) else {
+                    continue;
+                };
+                if state.contains(NPCState::DEAD) {
+                    continue;
+                }
+                let slot = clamp_to_world(squad.slot_position(*offset));
+                let engaged = state.contains(NPCState::COMBAT)
+                    && self
+                        .npc_target(ulid)
+                        .and_then(|target| npcs.position(&target))
+                        .is_some_and(|target_pos| target_pos.distance(slot) <= SQUAD_LEASH);
+                if engaged {
+                    // Fight, but don't leave the line
+                    let wanted = self.npc_waypoints.get(ulid).map_or(pos, |v| *v.value());
+                    let reach = wanted - slot;
+                    if reach.length() > SQUAD_LEASH {
+                        self.npc_waypoints
+                            .insert(*ulid, slot + reach.normalize() * SQUAD_LEASH);
+                    }
+                } else if pos.distance(slot) > SLOT_TOLERANCE {
+                    self.npc_waypoints.insert(*ulid, slot);
+                } else if self.npc_waypoints.remove(ulid).is_some() {
+                    // In place - stand (ranged members keep shooting from here)
+                    self.npc_move_directions.remove(ulid);
+                    npcs.set_behavior(ulid, (state - NPCState::WALKING) | NPCState::IDLE);
+                }
+            }
+        }
+    }
+
+    /// Put restored squads back (snapshot restore), keeping only members
+    /// that were restored
+    pub(super) fn restore_squads(&self, squads: Vec<Squad>) {
+        let mut restored = self.squads.lock();
+        for mut squad in squads {
+            let before = squad.members.len();
+            squad.members.retain(|(ulid, _)| {
+                self.active_npcs.contains_key(ulid) && !self.npc_squads.contains_key(ulid)
+            });
+            if squad.members.is_empty() || restored.contains_key(&squad.name) {
+                continue;
+            }
+            if squad.slot_of(&squad.leader).is_none() {
+                squad.leader = squad.members[0].0;
+            }
+            if squad.members.len() != before {
+                self.assign_slots(&self.stored(), &mut squad);
+            }
+            for (ulid, _) in &squad.members {
+                self.npc_squads.insert(*ulid, squad.name.clone());
+            }
+            restored.insert(squad.name.clone(), squad);
+        }
+    }
+
+    /// Hand out the formation's slots: the leader takes the first slot of its
+    /// line, every other slot goes to the nearest member without one (ties
+    /// go to the lower ULID)
+    fn assign_slots(&self, npcs: &impl LiveState, squad: &mut Squad) {
+        let back_line = |ulid: &[u8; 16]| {
+            squad.formation == Formation::Ranked
+                && self.get_combat_stats(ulid).is_some_and(|stats| {
+                    stats
+                        .static_flags()
+                        .intersects(NPCStaticState::RANGED | NPCStaticState::HEALER)
+                })
+        };
+        let mut waiting: Vec<([u8; 16], bool, Vec2)> = squad
+            .members
+            .iter()
+            .filter(|(ulid, _)| *ulid != squad.leader)
+            .map(|(ulid, _)| {
+                (
+                    *ulid,
+                    back_line(ulid),
+                    npcs.position(ulid).unwrap_or(squad.anchor),
+                )
+            })
+            .collect();
+        waiting.sort_unstable_by_key(|(ulid, _, _)| *ulid);
+        let leader_back = back_line(&squad.leader);
+        let back = waiting.iter().filter(|(_, back, _)| *back).count() + leader_back as usize;
+        let front = squad.members.len() - back;
+
+        let mut places = squad.formation.places(front, back);
+        let leader_place = places
+            .iter()
+            .position(|(back, _)| *back == leader_back)
+            .unwrap_or(0);
+        let mut members = vec![(squad.leader, places.remove(leader_place).1)];
+        for (back, offset) in places {
+            let slot = squad.slot_position(offset);
+            let mut nearest: Option<(usize, f32)> = None;
+            for (i, (_, member_back, pos)) in waiting.iter().enumerate() {
+                let distance = pos.distance(slot);
+                if *member_back == back && nearest.is_none_or(|(_, best)| distance < best) {
+                    nearest = Some((i, distance));
+                }
+            }
+            if let Some((i, _)) = nearest {
+                members.push((waiting.remove(i).0, offset));
+            }
+        }
+        squad.members = members;
+    }
+
+    /// Position of an NPC that can join a squad (registered for combat,
+    /// alive and not PASSIVE)
+    fn squad_candidate(&self, ulid: &[u8; 16]) -> Result<Vec2, String> {
+        let passive = self
+            .get_combat_stats(ulid)
+            .is_some_and(|stats| stats.static_flags().contains(NPCStaticState::PASSIVE));
+        match self.get_npc_position_internal(ulid) {
+            Some(pos)
+                if self.active_combat_npcs.contains_key(ulid)
+                    && self.is_alive(ulid)
+                    && !passive =>
+            {
+                Ok(pos)
+            }
+            _ => Err(format!(
+                "NPC {} can't join a squad (gone, dead, passive or not in combat)",
+                &bytes_to_hex(ulid)[..16]
+            )),
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use bitflags::bitflags;
+use serde::{Deserialize, Serialize};
+
+use super::archetypes::{
+    default_attack_range, DEFAULT_ATTACK_COOLDOWN_MS, DEFAULT_HEAL_CHANNEL_MS, DEFAULT_MOVE_SPEED,
+};
+use super::damage::{DamageType, Resistances, DEFAULT_CRIT_MULTIPLIER};
+use super::threat::TargetStrategy;
+
+// ============================================================================
+// ULID CONVERSION HELPERS
+// ============================================================================
+
+/// Convert byte ULID to hex string (32 chars)
+pub fn bytes_to_hex(bytes: &[u8; 16]) -> String {
+    format!("{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+        bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
+        bytes[8], bytes[9], bytes[10], bytes[11], bytes[12], bytes[13], bytes[14], bytes[15])
+}
+
+// ============================================================================
+// NPC STATS - Combat stats (per-type templates live in archetypes.rs)
+// ============================================================================
+
+/// NPC combat stats - single source of truth for all NPC stats
+/// Stored in ByteMap for efficient lookup by ULID
+/// Used during initialization, combat, and UI display
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct NPCCombatStats {
+    pub hp: f32,
+    pub max_hp: f32,
+    pub attack: f32,
+    pub defense: f32,
+    pub static_state: i32,    // Combat type + faction bitflags (immutable)
+    pub emotional_state: i32, // Emotion id (see emotions.rs)
+    pub mana: f32,
+    pub max_mana: f32,
+    pub energy: f32,
+    pub max_energy: f32,
+    #[serde(default = "default_hunger")]
+    pub hunger: f32,
+    #[serde(default = "default_max_hunger")]
+    pub max_hunger: f32,
+    #[serde(default = "default_stat_attack_range")]
+    pub attack_range: f32,
+    #[serde(default = "default_stat_attack_cooldown_ms")]
+    pub attack_cooldown_ms: u64,
+    #[serde(default = "default_stat_move_speed")]
+    pub move_speed: f32,
+    /// Damage pipeline inputs (see damage.rs)
+    #[serde(default)]
+    pub damage_type: DamageType,
+    #[serde(default)]
+    pub resistances: Resistances,
+    #[serde(default)]
+    pub crit_chance: f32,
+    #[serde(default = "default_stat_crit_multiplier")]
+    pub crit_multiplier: f32,
+    /// Healer AI inputs (see healing.rs) - HP restored per heal, mana each
+    /// heal costs and how long it is channeled
+    #[serde(default)]
+    pub heal_power: f32,
+    #[serde(default)]
+    pub heal_mana_cost: f32,
+    #[serde(default = "default_stat_heal_channel_ms")]
+    pub heal_channel_ms: u64,
+    /// Resource model (see resources.rs) - cost per attack and regen per second
+    /// (regeneration runs in regen.rs)
+    #[serde(default)]
+    pub attack_energy_cost: f32,
+    #[serde(default)]
+    pub attack_mana_cost: f32,
+    #[serde(default)]
+    pub energy_regen: f32,
+    #[serde(default)]
+    pub mana_regen: f32,
+    /// HP regenerated per second out of combat (see regen.rs)
+    #[serde(default)]
+    pub hp_regen: f32,
+    /// Hunger lost per second (see hunger.rs)
+    #[serde(default)]
+    pub hunger_decay: f32,
+    /// How the NPC picks its target (see threat.rs)
+    #[serde(default)]
+    pub target_strategy: TargetStrategy,
+}
+
+// Default values for backwards compatibility with old saved data
+fn default_hunger() -> f32 {
+    100.0
+}
+
+fn default_max_hunger() -> f32 {
+    100.0
+}
+
+fn default_stat_attack_range() -> f32 {
+    default_attack_range(NPCStaticState::MELEE)
+}
+
+fn default_stat_attack_cooldown_ms() -> u64 {
+    DEFAULT_ATTACK_COOLDOWN_MS
+}
+
+fn default_stat_move_speed() -> f32 {
+    DEFAULT_MOVE_SPEED
+}
+
+fn default_stat_crit_multiplier() -> f32 {
+    DEFAULT_CRIT_MULTIPLIER
+}
+
+fn default_stat_heal_channel_ms() -> u64 {
+    DEFAULT_HEAL_CHANNEL_MS
+}
+
+impl NPCCombatStats {
+    /// Basic stats for an NPC type with no registered archetype
+    pub fn fallback() -> Self {
+        NPCCombatStats {
+            hp: 100.0,
+            max_hp: 100.0,
+            attack: 10.0,
+            defense: 5.0,
+            static_state: NPCStaticState::PASSIVE.bits() as i32,
+            emotional_state: 0,
+            mana: 0.0,
+            max_mana: 0.0,
+            energy: 100.0,
+            max_energy: 100.0,
+            hunger: 100.0,
+            max_hunger: 100.0,
+            attack_range: default_attack_range(NPCStaticState::PASSIVE),
+            attack_cooldown_ms: DEFAULT_ATTACK_COOLDOWN_MS,
+            move_speed: DEFAULT_MOVE_SPEED,
+            damage_type: DamageType::Physical,
+            resistances: Resistances::default(),
+            crit_chance: 0.0,
+            crit_multiplier: DEFAULT_CRIT_MULTIPLIER,
+            heal_power: 0.0,
+            heal_mana_cost: 0.0,
+            heal_channel_ms: DEFAULT_HEAL_CHANNEL_MS,
+            attack_energy_cost: 0.0,
+            attack_mana_cost: 0.0,
+            energy_regen: 0.0,
+            mana_regen: 0.0,
+            hp_regen: 0.0,
+            hunger_decay: 0.0,
+            target_strategy: TargetStrategy::HighestThreat,
+        }
+    }
+
+    /// Static state as typed flags (combat type + faction)
+    pub fn static_flags(&self) -> NPCStaticState {
+        NPCStaticState::from_bits_retain(self.static_state as u32)
+    }
+}
+
+// ============================================================================
+// NPCState bitflags - BEHAVIORAL states only (dynamic, changes during gameplay)
+bitflags! {
+    /// NPC behavioral state flags - these change dynamically during gameplay
+    ///
+    /// Matches the GDScript NPCState enum in npc_manager.gd
+    /// Each NPC can have multiple behavioral states combined via bitwise OR
+    ///
+    /// Example: IDLE (idle behavior)
+    ///          WALKING | COMBAT (moving during combat)
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct NPCState: u32 {
+        // Behavioral States (bits 0-5) - Change during gameplay (SIMPLIFIED)
+        const IDLE       = 1 << 0;   // 1:  Idle (not moving, not attacking)
+        const WALKING    = 1 << 1;   // 2:  Walking/moving
+        const ATTACKING  = 1 << 2;   // 4:  Currently attacking (animation playing)
+        const COMBAT     = 1 << 3;   // 8:  Engaged in combat
+        const DAMAGED    = 1 << 4;   // 16: Taking damage - plays hurt/damage animation
+        const DEAD       = 1 << 5;   // 32: Dead - plays death animation
+    }
+}
+
+// Implement Send + Sync for thread safety
+unsafe impl Send for NPCState {}
+unsafe impl Sync for NPCState {}
+
+// NPCStaticState bitflags - IMMUTABLE properties set once at spawn
+bitflags! {
+    /// Static/immutable NPC properties (set once, never change during gameplay)
+    ///
+    /// These flags determine the NPC's core identity and should NEVER be modified.
+    /// Matches the GDScript NPCStaticState enum in npc_manager.gd
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct NPCStaticState: u32 {
+        // Combat Types (0-3) - Each NPC has exactly ONE combat type (immutable)
+        const MELEE   = 1 << 0;  // 1:   Melee attacker (warriors, knights, goblins)
+        const RANGED  = 1 << 1;  // 2:   Ranged attacker (archers, crossbowmen)
+        const MAGIC   = 1 << 2;  // 4:   Magic attacker (mages, wizards)
+        const HEALER  = 1 << 3;  // 8:   Healer/support (clerics, druids)
+
+        // Factions (4-6) - Determines who can attack whom (immutable)
+        const ALLY    = 1 << 4;  // 16:  Player's allies - don't attack each other
+        const MONSTER = 1 << 5;  // 32:  Hostile creatures - attack allies
+        const PASSIVE = 1 << 6;  // 64:  Doesn't attack/do damage (chickens, critters)
+    }
+}
+
+// Thread-safe for HolyMap
+unsafe impl Send for NPCStaticState {}
+unsafe impl Sync for NPCStaticState {}
+
+impl NPCStaticState {
+    /// Convert to i32 for GDScript interop
+    pub fn to_i32(self) -> i32 {
+        self.bits() as i32
+    }
+
+    /// Convert from i32 (from GDScript)
+    pub fn from_i32(value: i32) -> Option<Self> {
+        NPCStaticState::from_bits(value as u32)
+    }
+
+    /// Convert from i32 with fallback to ALLY
+    pub fn from_i32_or_ally(value: i32) -> Self {
+        NPCStaticState::from_bits(value as u32).unwrap_or(NPCStaticState::ALLY)
+    }
+}
+
+// Helper methods for NPCState
+impl NPCState {
+    /// Convert to i32 for GDScript interop (Godot uses int for enums)
+    pub fn to_i32(self) -> i32 {
+        self.bits() as i32
+    }
+
+    /// Convert from i32 (from GDScript)
+    pub fn from_i32(value: i32) -> Option<Self> {
+        NPCState::from_bits(value as u32)
+    }
+
+    /// Convert from i32 (from GDScript) with fallback to IDLE
+    pub fn from_i32_or_idle(value: i32) -> Self {
+        NPCState::from_bits(value as u32).unwrap_or(NPCState::IDLE)
+    }
+}
+
+/// ULID wrapper for binary storage (16 bytes)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UlidBytes([u8; 16]);
+
+impl UlidBytes {
+    /// Create from ulid crate's Ulid
+    pub fn from_ulid(ulid: ulid::Ulid) -> Self {
+        UlidBytes(ulid.to_bytes())
+    }
+
+    /// Create from raw bytes (16 bytes)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() != 16 {
+            return Err(format!(
+                "Invalid ULID length: expected 16 bytes, got {}",
+                bytes.len()
+            ));
+        }
+        let mut arr = [0u8; 16];
+        arr.copy_from_slice(bytes);
+        Ok(UlidBytes(arr))
+    }
+
+    /// Convert to ulid crate's Ulid
+    pub fn to_ulid(&self) -> ulid::Ulid {
+        ulid::Ulid::from_bytes(self.0)
+    }
+
+    /// Convert to lowercase hex string (32 chars) for HolyMap keys
+    pub fn to_hex_string(&self) -> String {
+        self.0.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Parse from hex string (from GDScript - legacy support)
+    pub fn from_hex_string(hex: &str) -> Result<Self, String> {
+        if hex.len() != 32 {
+            return Err(format!(
+                "Invalid hex ULID length: expected 32 chars, got {}",
+                hex.len()
+            ));
+        }
+
+        let mut bytes = [0u8; 16];
+        for i in 0..16 {
+            let byte_str = &hex[i * 2..i * 2 + 2];
+            bytes[i] = u8::from_str_radix(byte_str, 16)
+                .map_err(|e| format!("Invalid hex at position {}: {}", i * 2, e))?;
+        }
+        Ok(UlidBytes(bytes))
+    }
+
+    /// Get raw bytes
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+}
+
+// Thread-safe for HolyMap
+unsafe impl Send for UlidBytes {}
+unsafe impl Sync for UlidBytes {}
+
+/// NPCStats - Structured stat data matching GDScript NPCStats class
+///
+/// This struct mirrors the NPCStats Resource in GDScript and provides
+/// automatic JSON serialization via serde.
+///
+/// Matches: afk/nodes/npc/npc_stats.gd
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NPCStats {
+    /// Unique identifier (hex string format for JSON compatibility)
+    pub ulid: String,
+
+    /// NPC's generated name (e.g., "Aldric Ironwood")
+    pub npc_name: String,
+
+    /// NPC type (e.g., "warrior", "archer")
+    pub npc_type: String,
+
+    /// Current health points
+    pub hp: f32,
+
+    /// Maximum health points
+    pub max_hp: f32,
+
+    /// Current mana
+    pub mana: f32,
+
+    /// Maximum mana
+    pub max_mana: f32,
+
+    /// Current energy/stamina
+    pub energy: f32,
+
+    /// Maximum energy/stamina
+    pub max_energy: f32,
+
+    /// Hunger level (0 = starving, 100 = full)
+    pub hunger: f32,
+
+    /// Physical attack power
+    pub attack: f32,
+
+    /// Physical defense/damage reduction
+    pub defense: f32,
+
+    /// Current emotional state (Emotion id, see emotions.rs)
+    pub emotion: i32,
+}
+
+impl NPCStats {
+    /// Create new NPCStats from JSON string
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Serialize to JSON string
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+}
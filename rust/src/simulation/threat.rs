@@ -0,0 +1,435 @@
+// ============================================================================
+// THREAT - Per-NPC threat tables and target selection
+// ============================================================================
+// Every NPC keeps a threat table: how much threat each enemy has earned
+// against it. Threat comes from:
+//   - damage    the attacker gains DAMAGE_THREAT per point dealt (hits,
+//               projectiles and status effect ticks with a source)
+//   - healing   the healer gains HEAL_THREAT per point healed with every
+//               hostile NPC that has threat on the NPC it healed
+//   - taunts    every hostile within TAUNT_RADIUS of the taunter puts it
+//               TAUNT_THREAT above its current top threat
+// Every THREAT_STEP_INTERVAL_MS (living NPCs in combat, ULID order) threat
+// loses THREAT_DECAY_PER_SEC of itself per second; entries under THREAT_MIN
+// are dropped.
+//
+// The movement phase picks each NPC's target among the living hostiles within
+// TARGET_ACQUIRE_RANGE with its archetype's TargetStrategy (the nearest
+// hostile anywhere when none are that close). Under the threat-based
+// strategies the current target keeps its place until another NPC passes
+// TARGET_SWITCH_MARGIN times its threat, so targets don't flicker. A target
+// forced by the player (force_target) wins while it lives and stays hostile.
+// NPCs chase and face their target and attack it whenever it is in range;
+// other hostiles in range are only attacked when it isn't (see combat.rs).
+//
+// Threat tables and targets end with the NPC, and nobody keeps threat on (or
+// targets) an NPC that died or left.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+
+use super::ecs::NpcTable;
+use super::factions::FactionTable;
+use super::live::LiveState;
+use super::log::sim_print;
+use super::replay::RecordedInput;
+use super::spatial::SpatialGrid;
+use super::stats::{bytes_to_hex, NPCState};
+use super::warehouse::{NPCDataWarehouse, NpcRow};
+
+/// Threat gained per point of damage dealt
+pub const DAMAGE_THREAT: f32 = 1.0;
+
+/// Threat gained per point healed
+pub const HEAL_THREAT: f32 = 0.5;
+
+/// Threat a taunt puts the taunter above the current top threat
+pub const TAUNT_THREAT: f32 = 50.0;
+
+/// How far a taunt reaches (pixels)
+pub const TAUNT_RADIUS: f32 = 200.0;
+
+/// Shortest simulation time between two threat decay steps
+pub const THREAT_STEP_INTERVAL_MS: u64 = 500;
+
+/// Share of its threat an entry loses per second
+pub const THREAT_DECAY_PER_SEC: f32 = 0.05;
+
+/// Threat below which an entry is dropped
+pub const THREAT_MIN: f32 = 0.5;
+
+/// Threat another NPC needs, as a multiple of the current target's, to take
+/// its place
+pub const TARGET_SWITCH_MARGIN: f32 = 1.1;
+
+/// Distance within which NPCs pick targets (pixels) - farther hostiles don't
+/// pull NPCs into combat (see movement.rs)
+pub const TARGET_ACQUIRE_RANGE: f32 = 400.0;
+
+/// How an archetype picks its target among the hostiles in range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetStrategy {
+    /// Most threat (nearest when nobody has any)
+    #[default]
+    HighestThreat,
+    /// Lowest share of max HP
+    LowestHp,
+    /// Closest
+    Nearest,
+    /// One of the archetype's preferred_targets types, then most threat
+    Preferred,
+}
+
+impl TargetStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TargetStrategy::HighestThreat => "highest_threat",
+            TargetStrategy::LowestHp => "lowest_hp",
+            TargetStrategy::Nearest => "nearest",
+            TargetStrategy::Preferred => "preferred",
+        }
+    }
+}
+
+impl NPCDataWarehouse {
+    /// Target an NPC is going after (None when it has none)
+    pub fn npc_target(&self, ulid: &[u8; 16]) -> Option<[u8; 16]> {
+        self.npc_targets.get(ulid).map(|v| *v.value())
+    }
+
+    /// Target forced on an NPC with force_target (None when it picks its own)
+    pub fn forced_target(&self, ulid: &[u8; 16]) -> Option<[u8; 16]> {
+        self.forced_targets.get(ulid).map(|v| *v.value())
+    }
+
+    /// An NPC's threat table, highest threat first (ties go to the lower ULID)
+    pub fn npc_threat(&self, ulid: &[u8; 16]) -> Vec<([u8; 16], f32)> {
+        let mut entries: Vec<([u8; 16], f32)> = self
+            .npc_threat
+            .get(ulid)
+            .map(|table| {
+                table
+                    .iter()
+                    .map(|(source, threat)| (*source, *threat))
+                    .collect()
+            })
+            .unwrap_or_default();
+        entries.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        entries
+    }
+
+    /// Make an NPC go after `target` until it dies or stops being hostile,
+    /// or hand targeting back to its strategy (None)
+    pub fn force_target(&self, ulid: &[u8; 16], target: Option<[u8; 16]>) -> Result<(), String> {
+        if !self.is_alive(ulid) {
+            return Err(format!("NPC {} is gone or dead", &bytes_to_hex(ulid)[..16]));
+        }
+        if let Some(target) = target {
+            if target == *ulid {
+                return Err("an NPC can't target itself".to_string());
+            }
+            if !self.is_alive(&target) {
+                return Err(format!(
+                    "target {} is gone or dead",
+                    &bytes_to_hex(&target)[..16]
+                ));
+            }
+            let factions = self.faction_table();
+            let hostile = match (self.npc_faction(ulid), self.npc_faction(&target)) {
+                (Some(a), Some(b)) => factions.is_hostile(a, b),
+                _ => false,
+            };
+            if !hostile {
+                return Err(format!(
+                    "target {} is not hostile to NPC {}",
+                    &bytes_to_hex(&target)[..16],
+                    &bytes_to_hex(ulid)[..16]
+                ));
+            }
+        }
+        self.record_input(RecordedInput::ForceTarget {
+            ulid: *ulid,
+            target,
+        });
+
+        match target {
+            Some(target) => {
+                self.forced_targets.insert(*ulid, target);
+                self.npc_targets.insert(*ulid, target);
+                sim_print!(
+                    "[THREAT] NPC {} forced onto {}",
+                    &bytes_to_hex(ulid)[..8],
+                    &bytes_to_hex(&target)[..8]
+                );
+            }
+            None => {
+                self.forced_targets.remove(ulid);
+            }
+        }
+        Ok(())
+    }
+
+    /// Taunt every living hostile within TAUNT_RADIUS of `taunter`
+    /// Returns how many NPCs were taunted
+    pub fn taunt(&self, taunter: &[u8; 16]) -> Result<usize, String> {
+        let (Some(pos), Some(faction)) = (
+            self.get_npc_position_internal(taunter),
+            self.npc_faction(taunter),
+        ) else {
+            return Err(format!("NPC {} is gone", &bytes_to_hex(taunter)[..16]));
+        };
+        if !self.is_alive(taunter) {
+            return Err(format!("NPC {} is dead", &bytes_to_hex(taunter)[..16]));
+        }
+        self.record_input(RecordedInput::Taunt { ulid: *taunter });
+
+        let factions = self.faction_table();
+        let mut taunted: Vec<[u8; 16]> = self
+            .active_combat_npcs
+            .iter()
+            .map(|entry| *entry.key())
+            .filter(|ulid| {
+                ulid != taunter
+                    && self
+                        .npc_faction(ulid)
+                        .is_some_and(|other| factions.is_hostile(other, faction))
+                    && self
+                        .get_npc_position_internal(ulid)
+                        .is_some_and(|other_pos| other_pos.distance(pos) <= TAUNT_RADIUS)
+                    && self.is_alive(ulid)
+            })
+            .collect();
+        taunted.sort_unstable();
+
+        for ulid in &taunted {
+            let mut table = self.npc_threat.entry(*ulid).or_default();
+            let top = table.values().fold(0.0f32, |top, threat| top.max(*threat));
+            table.insert(*taunter, top + TAUNT_THREAT);
+        }
+        sim_print!(
+            "[THREAT] NPC {} taunted {} hostiles",
+            &bytes_to_hex(taunter)[..8],
+            taunted.len()
+        );
+        Ok(taunted.len())
+    }
+
+    /// Give `source` threat on `npc`
+    pub(super) fn add_threat(&self, npc: &[u8; 16], source: &[u8; 16], threat: f32) {
+        if npc == source || threat <= 0.0 {
+            return;
+        }
+        *self
+            .npc_threat
+            .entry(*npc)
+            .or_default()
+            .entry(*source)
+            .or_insert(0.0) += threat;
+    }
+
+    /// Give a healer threat with every hostile that has threat on the NPC it
+    /// healed
+    pub(super) fn add_heal_threat(&self, healer: &[u8; 16], target: &[u8; 16], amount: f32) {
+        let threat = amount * HEAL_THREAT;
+        if threat <= 0.0 {
+            return;
+        }
+        let Some(healer_faction) = self.npc_faction(healer) else {
+            return;
+        };
+        let factions = self.faction_table();
+        for mut table in self.npc_threat.iter_mut() {
+            let owner = *table.key();
+            if owner == *healer || !table.contains_key(target) {
+                continue;
+            }
+            let hostile = self
+                .npc_faction(&owner)
+                .is_some_and(|faction| factions.is_hostile(faction, healer_faction));
+            if hostile {
+                *table.entry(*healer).or_insert(0.0) += threat;
+            }
+        }
+    }
+
+    /// Drop an NPC's threat table and target, and everyone's threat on it and
+    /// targeting of it (death, despawn)
+    pub(super) fn clear_threat(&self, ulid: &[u8; 16]) {
+        self.npc_threat.remove(ulid);
+        self.npc_targets.remove(ulid);
+        self.forced_targets.remove(ulid);
+        self.npc_threat.retain(|_, table| {
+            table.remove(ulid);
+            !table.is_empty()
+        });
+        self.npc_targets.retain(|_, target| target != ulid);
+        self.forced_targets.retain(|_, target| target != ulid);
+    }
+
+    /// Decay threat for the time since the last step
+    /// `npcs` holds the living NPCs registered for combat, sorted by ULID
+    pub(super) fn run_threat(&self, npcs: &[[u8; 16]]) {
+        let now_ms = self.get_current_time_ms();
+        let last_ms = self.last_threat_step_ms.load(Ordering::Relaxed);
+        // First step (or the clock went back) - start counting from now
+        if last_ms == 0 || now_ms < last_ms {
+            self.last_threat_step_ms.store(now_ms, Ordering::Relaxed);
+            return;
+        }
+        let elapsed_ms = now_ms - last_ms;
+        if elapsed_ms < THREAT_STEP_INTERVAL_MS {
+            return;
+        }
+        self.last_threat_step_ms.store(now_ms, Ordering::Relaxed);
+        let retained = (1.0 - THREAT_DECAY_PER_SEC).powf(elapsed_ms as f32 / 1000.0);
+
+        for ulid in npcs {
+            let emptied = {
+                let Some(mut table) = self.npc_threat.get_mut(ulid) else {
+                    continue;
+                };
+                table.retain(|_, threat| {
+                    *threat *= retained;
+                    *threat >= THREAT_MIN
+                });
+                table.is_empty()
+            };
+            if emptied {
+                self.npc_threat.remove(ulid);
+            }
+        }
+    }
+
+    /// Pick the target for `npcs[index]` (see module docs)
+    /// `preferences` caches each archetype's preferred_targets over a phase
+    /// Returns the target's row index and its distance
+    pub(super) fn pick_target(
+        &self,
+        npcs: &[NpcRow],
+        live: &NpcTable,
+        grid: &SpatialGrid,
+        factions: &FactionTable,
+        index: usize,
+        preferences: &mut HashMap<String, Vec<String>>,
+    ) -> Option<(usize, f32)> {
+        let npc = &npcs[index];
+        let hostile = |j: usize| {
+            let b = &npcs[j];
+            j != index
+                && !b.state.contains(NPCState::DEAD)
+                && factions.is_hostile(npc.faction, b.faction)
+        };
+
+        // A forced target wins while it lives and stays hostile (rows are sorted by ULID)
+        if let Some(forced) = self.forced_target(&npc.ulid) {
+            match npcs
+                .binary_search_by(|row| row.ulid.cmp(&forced))
+                .ok()
+                .filter(|&j| hostile(j))
+            {
+                Some(j) => return Some((j, npc.pos.distance(npcs[j].pos))),
+                None => {
+                    self.forced_targets.remove(&npc.ulid);
+                }
+            }
+        }
+
+        let candidates: Vec<usize> = grid
+            .query_radius(npc.pos, TARGET_ACQUIRE_RANGE)
+            .into_iter()
+            .filter(|&j| hostile(j))
+            .collect();
+        if candidates.is_empty() {
+            return grid.nearest(npc.pos, hostile);
+        }
+
+        let strategy = self
+            .get_combat_stats(&npc.ulid)
+            .map_or_else(TargetStrategy::default, |stats| stats.target_strategy);
+        let threat = self
+            .npc_threat
+            .get(&npc.ulid)
+            .map(|table| table.clone())
+            .unwrap_or_default();
+        let current = self.npc_target(&npc.ulid);
+        let preferred: &[String] = match strategy {
+            TargetStrategy::Preferred => self.preferred_targets(&npc.ulid, preferences),
+            _ => &[],
+        };
+
+        // (preferred type, strategy score) - higher wins, then nearer, then lower row
+        let score = |j: usize| -> (bool, f32) {
+            let b = &npcs[j];
+            let threat_on = || {
+                let threat = threat.get(&b.ulid).copied().unwrap_or(0.0);
+                if current == Some(b.ulid) {
+                    threat * TARGET_SWITCH_MARGIN
+                } else {
+                    threat
+                }
+            };
+            match strategy {
+                TargetStrategy::HighestThreat => (false, threat_on()),
+                TargetStrategy::LowestHp => {
+                    let fraction = live
+                        .health(&b.ulid)
+                        .filter(|health| health.max_hp > 0.0)
+                        .map_or(1.0, |health| health.hp / health.max_hp);
+                    (false, -fraction)
+                }
+                TargetStrategy::Nearest => (false, 0.0),
+                TargetStrategy::Preferred => {
+                    let is_preferred = self
+                        .npc_types
+                        .get(&b.ulid)
+                        .is_some_and(|npc_type| preferred.contains(npc_type.value()));
+                    (is_preferred, threat_on())
+                }
+            }
+        };
+
+        let mut best: Option<((bool, f32), f32, usize)> = None;
+        for j in candidates {
+            let key = score(j);
+            let distance = npc.pos.distance(npcs[j].pos);
+            let better = match best {
+                Some((best_key, best_distance, _)) => {
+                    key > best_key || (key == best_key && distance < best_distance)
+                }
+                None => true,
+            };
+            if better {
+                best = Some((key, distance, j));
+            }
+        }
+        best.map(|(_, distance, j)| (j, distance))
+    }
+
+    /// Preferred target types of an NPC's archetype, looked up once per type
+    fn preferred_targets<'a>(
+        &self,
+        ulid: &[u8; 16],
+        preferences: &'a mut HashMap<String, Vec<String>>,
+    ) -> &'a [String] {
+        let Some(npc_type) = self.npc_types.get(ulid).map(|v| v.value().clone()) else {
+            return &[];
+        };
+        preferences.entry(npc_type).or_insert_with_key(|npc_type| {
+            self.archetypes
+                .get(npc_type)
+                .map(|archetype| archetype.preferred_targets)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Registered, living and not flagged DEAD
+    pub(super) fn is_alive(&self, ulid: &[u8; 16]) -> bool {
+        self.get_combat_stats(ulid)
+            .is_some_and(|stats| stats.hp > 0.0)
+            && self
+                .get_behavioral_state(ulid)
+                .is_some_and(|state| !state.contains(NPCState::DEAD))
+    }
+}
@@ -0,0 +1,309 @@
+// ============================================================================
+// TIMER SCHEDULER - Typed actions keyed by simulation time
+// ============================================================================
+// Anything that should happen "N ms from now" (despawn after the death
+// animation, clearing ATTACKING/DAMAGED, the end of a wander cooldown, a
+// respawn) is scheduled here instead of being polled for every NPC each tick.
+// Timers sit in a min-heap ordered by (due time, handle): firing only touches
+// the expired ones, and timers due at the same time fire in the order they
+// were scheduled, so replays stay deterministic.
+//
+// Cancelling drops the timer from the live set and its heap entry is skipped
+// when it comes up. Per-NPC timers (one of each kind per NPC) are indexed by
+// ULID, so scheduling again replaces the pending one and despawning an NPC
+// drops all of its timers.
+//
+// Due timers fire in the cleanup phase (see ecs.rs).
+
+use bevy::math::Vec2;
+use parking_lot::MutexGuard;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use super::log::sim_print;
+use super::stats::{bytes_to_hex, NPCState};
+use super::warehouse::NPCDataWarehouse;
+
+/// Handle to a scheduled timer (see `cancel_timer`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimerHandle(u64);
+
+/// What a timer does when it fires
+pub enum TimerAction {
+    /// Return a dead NPC to its pool (its position drives the death effect)
+    Despawn { ulid: [u8; 16] },
+    /// Clear a transient state flag (ATTACKING / DAMAGED) once its animation has played
+    ClearState { ulid: [u8; 16], flag: NPCState },
+    /// Wander cooldown is over - the NPC may pick a new waypoint
+    EndWanderCooldown { ulid: [u8; 16] },
+    /// Spawn an NPC of `npc_type` from its pool
+    Respawn { npc_type: String, position: Vec2 },
+    /// Run arbitrary code against the warehouse (not kept in snapshots)
+    Callback(Box<dyn FnOnce(&NPCDataWarehouse) + Send>),
+}
+
+/// Per-NPC timer kinds - an NPC has at most one pending timer of each
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum NpcTimer {
+    Despawn,
+    ClearState(NPCState),
+    WanderCooldown,
+}
+
+impl TimerAction {
+    /// The NPC and slot this action occupies (None for global actions)
+    fn npc_slot(&self) -> Option<([u8; 16], NpcTimer)> {
+        match self {
+            TimerAction::Despawn { ulid } => Some((*ulid, NpcTimer::Despawn)),
+            TimerAction::ClearState { ulid, flag } => Some((*ulid, NpcTimer::ClearState(*flag))),
+            TimerAction::EndWanderCooldown { ulid } => Some((*ulid, NpcTimer::WanderCooldown)),
+            TimerAction::Respawn { .. } | TimerAction::Callback(_) => None,
+        }
+    }
+}
+
+struct Timer {
+    due_ms: u64,
+    action: TimerAction,
+}
+
+/// Pending timers (lives on the warehouse)
+#[derive(Default)]
+pub(crate) struct TimerScheduler {
+    next_handle: u64,
+    /// (due time, handle) - may still hold cancelled timers, skipped when popped
+    queue: BinaryHeap<Reverse<(u64, TimerHandle)>>,
+    live: HashMap<TimerHandle, Timer>,
+    by_npc: HashMap<[u8; 16], Vec<(NpcTimer, TimerHandle)>>,
+}
+
+impl TimerScheduler {
+    fn schedule(&mut self, due_ms: u64, action: TimerAction) -> TimerHandle {
+        let handle = TimerHandle(self.next_handle);
+        self.next_handle += 1;
+
+        if let Some((ulid, slot)) = action.npc_slot() {
+            let slots = self.by_npc.entry(ulid).or_default();
+            if let Some(index) = slots.iter().position(|(kind, _)| *kind == slot) {
+                let (_, replaced) = slots.swap_remove(index);
+                self.live.remove(&replaced);
+            }
+            slots.push((slot, handle));
+        }
+
+        self.queue.push(Reverse((due_ms, handle)));
+        self.live.insert(handle, Timer { due_ms, action });
+
+        // Rebuild once cancelled entries outnumber the live ones
+        if self.queue.len() > 2 * self.live.len() + 64 {
+            self.queue = self
+                .live
+                .iter()
+                .map(|(handle, timer)| Reverse((timer.due_ms, *handle)))
+                .collect();
+        }
+        handle
+    }
+
+    fn cancel(&mut self, handle: TimerHandle) -> Option<Timer> {
+        let timer = self.live.remove(&handle)?;
+        if let Some((ulid, _)) = timer.action.npc_slot() {
+            if let Some(slots) = self.by_npc.get_mut(&ulid) {
+                slots.retain(|(_, pending)| *pending != handle);
+                if slots.is_empty() {
+                    self.by_npc.remove(&ulid);
+                }
+            }
+        }
+        Some(timer)
+    }
+
+    fn npc_timer(&self, ulid: &[u8; 16], slot: NpcTimer) -> Option<TimerHandle> {
+        self.by_npc
+            .get(ulid)?
+            .iter()
+            .find(|(kind, _)| *kind == slot)
+            .map(|(_, handle)| *handle)
+    }
+
+    /// Remove every timer due at `now_ms`, in firing order
+    fn take_due(&mut self, now_ms: u64) -> Vec<TimerAction> {
+        let mut due = Vec::new();
+        while let Some(&Reverse((due_ms, handle))) = self.queue.peek() {
+            if due_ms > now_ms {
+                break;
+            }
+            self.queue.pop();
+            if let Some(timer) = self.cancel(handle) {
+                due.push(timer.action);
+            }
+        }
+        due
+    }
+}
+
+impl NPCDataWarehouse {
+    fn timers(&self) -> MutexGuard<'_, TimerScheduler> {
+        self.timers.lock()
+    }
+
+    /// Schedule `action` to run after `delay_ms` of simulation time
+    /// Per-NPC actions replace the NPC's pending timer of the same kind
+    pub fn schedule_timer(&self, delay_ms: u64, action: TimerAction) -> TimerHandle {
+        let due_ms = self.get_current_time_ms().saturating_add(delay_ms);
+        self.schedule_timer_at(due_ms, action)
+    }
+
+    /// Schedule `action` at an absolute simulation time (ms)
+    pub fn schedule_timer_at(&self, due_ms: u64, action: TimerAction) -> TimerHandle {
+        self.timers().schedule(due_ms, action)
+    }
+
+    /// Cancel a pending timer - false if it already fired or was cancelled
+    pub fn cancel_timer(&self, handle: TimerHandle) -> bool {
+        self.timers().cancel(handle).is_some()
+    }
+
+    /// Simulation time (ms) a pending timer fires at
+    pub fn timer_due_ms(&self, handle: TimerHandle) -> Option<u64> {
+        self.timers().live.get(&handle).map(|timer| timer.due_ms)
+    }
+
+    /// Number of pending timers
+    pub fn pending_timer_count(&self) -> usize {
+        self.timers().live.len()
+    }
+
+    /// Due time of an NPC's pending timer of the given kind
+    pub(super) fn npc_timer_due_ms(&self, ulid: &[u8; 16], slot: NpcTimer) -> Option<u64> {
+        let timers = self.timers();
+        let handle = timers.npc_timer(ulid, slot)?;
+        timers.live.get(&handle).map(|timer| timer.due_ms)
+    }
+
+    /// Cancel an NPC's pending timer of the given kind
+    pub(super) fn cancel_npc_timer(&self, ulid: &[u8; 16], slot: NpcTimer) {
+        let mut timers = self.timers();
+        if let Some(handle) = timers.npc_timer(ulid, slot) {
+            timers.cancel(handle);
+        }
+    }
+
+    /// Cancel every pending timer of an NPC (its slot is going back to the pool)
+    pub(super) fn cancel_npc_timers(&self, ulid: &[u8; 16]) {
+        let mut timers = self.timers();
+        for (_, handle) in timers.by_npc.remove(ulid).unwrap_or_default() {
+            timers.live.remove(&handle);
+        }
+    }
+
+    /// Drop every pending timer
+    pub(super) fn clear_timers(&self) {
+        let mut timers = self.timers();
+        // Keep handles unique across the reset
+        let next_handle = timers.next_handle;
+        *timers = TimerScheduler {
+            next_handle,
+            ..TimerScheduler::default()
+        };
+    }
+
+    /// Pending respawns as (due time, npc type, position), in firing order
+    pub(super) fn pending_respawns(&self) -> Vec<(u64, String, Vec2)> {
+        let timers = self.timers();
+        let mut respawns: Vec<_> = timers
+            .live
+            .iter()
+            .filter_map(|(handle, timer)| match &timer.action {
+                TimerAction::Respawn { npc_type, position } => {
+                    Some((timer.due_ms, *handle, npc_type.clone(), *position))
+                }
+                _ => None,
+            })
+            .collect();
+        respawns.sort_unstable_by_key(|(due_ms, handle, ..)| (*due_ms, *handle));
+        respawns
+            .into_iter()
+            .map(|(due_ms, _, npc_type, position)| (due_ms, npc_type, position))
+            .collect()
+    }
+
+    /// Live timers as (due time, action key), sorted - feeds the replay state hash
+    /// Handles are left out: a warehouse restored from a snapshot numbers them
+    /// differently
+    pub(super) fn live_timer_keys(&self) -> Vec<(u64, Vec<u8>)> {
+        let timers = self.timers();
+        let mut keys: Vec<(u64, Vec<u8>)> = timers
+            .live
+            .values()
+            .map(|timer| {
+                let mut key = Vec::with_capacity(24);
+                match &timer.action {
+                    TimerAction::Despawn { ulid } => {
+                        key.push(0);
+                        key.extend_from_slice(ulid);
+                    }
+                    TimerAction::ClearState { ulid, flag } => {
+                        key.push(1);
+                        key.extend_from_slice(ulid);
+                        key.extend_from_slice(&flag.bits().to_le_bytes());
+                    }
+                    TimerAction::EndWanderCooldown { ulid } => {
+                        key.push(2);
+                        key.extend_from_slice(ulid);
+                    }
+                    TimerAction::Respawn { npc_type, position } => {
+                        key.push(3);
+                        key.extend_from_slice(&position.x.to_bits().to_le_bytes());
+                        key.extend_from_slice(&position.y.to_bits().to_le_bytes());
+                        key.extend_from_slice(npc_type.as_bytes());
+                    }
+                    TimerAction::Callback(_) => key.push(4),
+                }
+                (timer.due_ms, key)
+            })
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// Fire every timer due at `now_ms`
+    /// Returns the positions (x, y) of dead NPCs despawned, for death effects
+    pub(super) fn run_due_timers(&self, now_ms: u64) -> Vec<(f32, f32)> {
+        // Take the batch first - actions may schedule (or cancel) timers
+        let due = self.timers().take_due(now_ms);
+        let mut death_positions = Vec::new();
+
+        for action in due {
+            match action {
+                TimerAction::Despawn { ulid } => {
+                    sim_print!("[RUST CLEANUP] Despawning dead NPC: {:?}", &ulid[..8]);
+                    if let Some(pos) = self.get_npc_position_internal(&ulid) {
+                        death_positions.push((pos.x, pos.y));
+                    }
+                    self.despawn_to_pool(&ulid);
+                }
+                TimerAction::ClearState { ulid, flag } => {
+                    // Skip NPCs without a behavioral state
+                    if !self.npc_behavioral_state.contains_key(&ulid) {
+                        continue;
+                    }
+                    self.remove_transient_state(&mut self.stored(), &ulid, flag);
+                    sim_print!(
+                        "[ANIM CLEAR] NPC {} - Clearing state flag {} after its animation",
+                        &bytes_to_hex(&ulid)[0..8],
+                        flag.bits()
+                    );
+                }
+                // Nothing to do - the cooldown is simply no longer pending
+                TimerAction::EndWanderCooldown { .. } => {}
+                TimerAction::Respawn { npc_type, position } => {
+                    self.spawn_from_pool(&npc_type, position);
+                }
+                TimerAction::Callback(callback) => callback(self),
+            }
+        }
+
+        death_positions
+    }
+}
@@ -0,0 +1,125 @@
+// ============================================================================
+// FIXED TIMESTEP - Simulation time, time scale and pause
+// ============================================================================
+// The host reports real elapsed time; the accumulator turns it (scaled) into
+// whole fixed steps. Each step first advances the simulation clock by the
+// step length, then ticks with the same fixed delta, so cooldowns, despawn
+// timers and movement all share one timeline.
+//
+// The same number of steps always produces the same battle whether they ran
+// at 1x, 4x or in one fast_forward() call. Steps are ms-rounded on the clock
+// with the remainder carried, so 60 Hz advances 16/17/17 ms repeatedly.
+
+use parking_lot::MutexGuard;
+
+use super::log::sim_warn;
+use super::warehouse::NPCDataWarehouse;
+
+/// Fixed simulation rate (steps per simulated second)
+pub const FIXED_STEP_HZ: f64 = 60.0;
+
+/// Fastest allowed time scale
+pub const MAX_TIME_SCALE: f32 = 16.0;
+
+/// Steps one advance() may run before the backlog is dropped
+/// (a long frame hitch should not freeze the game while it catches up)
+const MAX_STEPS_PER_ADVANCE: u32 = 64;
+
+/// Accumulator state (lives on the warehouse)
+#[derive(Debug)]
+pub(crate) struct Timestep {
+    step_ms: f64,
+    /// Scaled real time not yet turned into steps
+    accumulator_ms: f64,
+    /// Sub-millisecond remainder of the clock advance
+    clock_carry_ms: f64,
+    time_scale: f32,
+    paused: bool,
+}
+
+impl Default for Timestep {
+    fn default() -> Self {
+        Self {
+            step_ms: 1000.0 / FIXED_STEP_HZ,
+            accumulator_ms: 0.0,
+            clock_carry_ms: 0.0,
+            time_scale: 1.0,
+            paused: false,
+        }
+    }
+}
+
+impl NPCDataWarehouse {
+    fn timestep(&self) -> MutexGuard<'_, Timestep> {
+        self.timestep.lock()
+    }
+
+    /// Fixed step length in seconds (the delta every tick runs with)
+    pub fn fixed_delta(&self) -> f32 {
+        (self.timestep().step_ms / 1000.0) as f32
+    }
+
+    /// Battle speed multiplier (1.0 = real time, clamped to 0..=16)
+    pub fn set_time_scale(&self, scale: f32) {
+        let scale = if scale.is_finite() { scale } else { 1.0 };
+        self.timestep().time_scale = scale.clamp(0.0, MAX_TIME_SCALE);
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.timestep().time_scale
+    }
+
+    /// Stop turning real time into steps (step/fast_forward still work)
+    pub fn pause(&self) {
+        self.timestep().paused = true;
+    }
+
+    pub fn resume(&self) {
+        self.timestep().paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.timestep().paused
+    }
+
+    /// Add real elapsed time (scaled) and return how many fixed steps are due
+    pub fn accumulate(&self, real_delta_secs: f32) -> u32 {
+        let mut timestep = self.timestep();
+        if timestep.paused || real_delta_secs.is_nan() || real_delta_secs <= 0.0 {
+            return 0;
+        }
+
+        timestep.accumulator_ms += real_delta_secs as f64 * 1000.0 * timestep.time_scale as f64;
+        let due = (timestep.accumulator_ms / timestep.step_ms).floor();
+        if due > MAX_STEPS_PER_ADVANCE as f64 {
+            sim_warn!(
+                "[TIMESTEP] {} steps behind - dropping the backlog",
+                due as u64 - MAX_STEPS_PER_ADVANCE as u64
+            );
+            timestep.accumulator_ms = 0.0;
+            return MAX_STEPS_PER_ADVANCE;
+        }
+        timestep.accumulator_ms -= due * timestep.step_ms;
+        due as u32
+    }
+
+    /// Number of fixed steps covering `seconds` of simulation time
+    pub fn steps_for(&self, seconds: f32) -> u32 {
+        if seconds.is_nan() || seconds <= 0.0 {
+            return 0;
+        }
+        (seconds as f64 * 1000.0 / self.timestep().step_ms).round() as u32
+    }
+
+    /// Move simulation time forward by one fixed step (call right before ticking it)
+    pub(crate) fn advance_fixed_step(&self) {
+        let whole_ms = {
+            let mut timestep = self.timestep();
+            timestep.clock_carry_ms += timestep.step_ms;
+            let whole_ms = timestep.clock_carry_ms.floor();
+            timestep.clock_carry_ms -= whole_ms;
+            whole_ms as u64
+        };
+        self.clock.read().advance_step_ms(whole_ms);
+    }
+}
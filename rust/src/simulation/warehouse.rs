@@ -0,0 +1,1301 @@
+use bevy::math::Vec2;
+use crossbeam_queue::SegQueue;
+use dashmap::DashMap;
+use parking_lot::{Mutex, MutexGuard, RwLock};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use super::archetypes::{
+    default_attack_range, ArchetypeRegistry, NPCArchetype, DEFAULT_ATTACK_COOLDOWN_MS,
+    DEFAULT_HEAL_CHANNEL_MS, DEFAULT_MOVE_SPEED,
+};
+use super::clock::{Clock, ManualClock, SystemClock};
+use super::damage::{
+    DamageBreakdown, DamageConfig, DamageModifiers, DamageType, Resistances,
+    DEFAULT_CRIT_MULTIPLIER,
+};
+use super::effects::{ActiveEffect, StatusEffectDef, StatusEffectEvent};
+use super::emotions::{EmotionEvent, EmotionScores};
+use super::events::CombatEvent;
+use super::factions::{FactionEvent, FactionId, FactionTable};
+use super::healing::HealChannel;
+use super::hunger::{HungerEvent, DEFAULT_FOOD_STOCKPILE};
+use super::log::{sim_error, sim_print, sim_warn};
+use super::pools::PoolDefinition;
+use super::replay::{InputRecorder, RecordedInput};
+use super::resources::ResourceEvent;
+use super::squads::{Squad, SquadEvent};
+use super::stats::{bytes_to_hex, NPCCombatStats, NPCState, NPCStaticState};
+use super::threat::TargetStrategy;
+use super::timers::{NpcTimer, TimerAction, TimerScheduler};
+use super::timestep::Timestep;
+
+/// Snapshot of one living NPC in combat, taken from its components when a
+/// phase function starts (see `ecs::NpcTable::rows`)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct NpcRow {
+    pub ulid: [u8; 16],
+    pub pos: Vec2,
+    pub static_state: NPCStaticState,
+    pub faction: FactionId,
+    pub state: NPCState,
+    pub attack_range: f32,
+}
+
+// World bounds constants - NPCs stay within these coordinates during combat
+// These bounds match the typical viewport size (1280x720) with margins for parallax
+pub(crate) const WORLD_MIN_X: f32 = 50.0; // Left edge with margin
+pub(crate) const WORLD_MAX_X: f32 = 1230.0; // Right edge with margin
+pub(crate) const WORLD_MIN_Y: f32 = 100.0; // Top of playable area
+pub(crate) const WORLD_MAX_Y: f32 = 650.0; // Below bottom of screen
+
+/// NodeCommand - Scene-side work produced by the simulation core
+///
+/// The core never touches scene nodes. When a host layer is attached
+/// (see `set_record_node_commands`), every spawn, despawn and HP change is
+/// queued here and the host drains the queue after each tick to keep its
+/// nodes and healthbars in step with the simulation.
+#[derive(Debug, Clone)]
+pub enum NodeCommand {
+    /// NPC left the pool and entered the world at `position`
+    Spawned {
+        ulid: [u8; 16],
+        npc_type: String,
+        position: Vec2,
+    },
+    /// NPC was removed from the world and returned to the pool
+    Despawned { ulid: [u8; 16] },
+    /// Pooled slot `from` now belongs to ULID `to` (snapshot restore)
+    Rekeyed { from: [u8; 16], to: [u8; 16] },
+    /// Pooled slot was freed (its pool shrank) - the host drops its node
+    Freed { ulid: [u8; 16] },
+    /// NPC took damage (drives healthbar updates)
+    Damaged {
+        ulid: [u8; 16],
+        amount: f32,
+        hp: f32,
+        max_hp: f32,
+    },
+    /// NPC was healed (drives healthbar updates)
+    Healed {
+        ulid: [u8; 16],
+        amount: f32,
+        hp: f32,
+        max_hp: f32,
+    },
+    /// HP regenerated this tick - (ulid, hp, max_hp) per NPC, healthbars only
+    HealthBars { updates: Vec<([u8; 16], f32, f32)> },
+}
+
+/// NPCDataWarehouse - High-performance NPC pool and state management
+///
+/// This is the Rust-based replacement for NPCManager's Dictionary-based pools.
+/// Uses DashMap for lock-free reads and fast concurrent writes.
+///
+/// Key Design:
+/// - Lock-free reads for combat/AI queries (90%+ of operations)
+/// - Fast concurrent writes for spawn/despawn
+/// - WASM-safe with threading support
+/// - Migrates logic from GDScript to Rust for better performance
+pub struct NPCDataWarehouse {
+    /// Main storage using DashMap for concurrent access
+    pub(crate) storage: DashMap<String, String>,
+
+    /// Combat event queue - lock-free MPMC queue for Rust → GDScript communication
+    pub(crate) combat_event_queue: Arc<SegQueue<CombatEvent>>,
+
+    /// Positions of dead NPCs removed by cleanup (drives death effects)
+    pub(crate) death_position_queue: SegQueue<(f32, f32)>,
+
+    /// Combat thread running flag
+    pub(crate) combat_thread_running: Arc<AtomicBool>,
+
+    /// Active NPCs in combat system - Set of ULIDs for iteration
+    pub(crate) active_combat_npcs: DashMap<[u8; 16], ()>,
+
+    /// Error tracking - prevents spam by logging each error type once per NPC
+    /// Key format: "error_type:ulid" -> "1"
+    pub(crate) error_log: DashMap<String, String>,
+
+    /// Spawn wave management - track last spawn time and counts
+    /// Rust manages ALL spawning (allies + monsters) with gradual ramp-up
+    pub(crate) last_spawn_time_ms: Arc<AtomicU64>,
+    pub(crate) last_ally_spawn_time_ms: Arc<AtomicU64>, // Separate timer for allies
+
+    /// Monster spawn configuration
+    pub(crate) spawn_interval_ms: u64, // Time between monster waves (default 10 seconds)
+    pub(crate) min_wave_size: i32, // Minimum monsters per wave (8 monsters)
+    pub(crate) max_wave_size: i32, // Maximum monsters per wave (8 monsters)
+    pub(crate) min_active_monsters: i32, // Spawn new wave when below this count (3 monsters)
+
+    /// Ally spawn configuration (warriors, archers, clerics) - 14 total allies max
+    pub(crate) ally_spawn_interval_ms: u64, // Time between ally spawns (3 seconds for gradual ramp-up)
+    pub(crate) max_warriors: i32, // Max warrior count (6 warriors)
+    pub(crate) max_archers: i32,  // Max archer count (6 archers)
+    pub(crate) max_clerics: i32,  // Max cleric count (2 clerics)
+
+    /// Initial spawn flag (spawn minimal entities on first tick)
+    pub(crate) initial_spawn_done: Arc<AtomicBool>,
+
+    /// Spawning gate - automatic spawning waits until the host is ready
+    /// (the Godot layer opens it once the scene container is set)
+    pub(crate) spawning_enabled: AtomicBool,
+
+    /// World bounds for clamping waypoints (set by GDScript from BackgroundManager)
+    /// These can be updated dynamically when background changes
+    pub(crate) world_min_x: Arc<AtomicU32>, // Stored as f32 bits
+    pub(crate) world_max_x: Arc<AtomicU32>,
+    pub(crate) world_min_y: Arc<AtomicU32>,
+    pub(crate) world_max_y: Arc<AtomicU32>,
+
+    // ============================================================================
+    // NPC POOL SLOTS - Pooled ULIDs, independent of any scene nodes
+    // ============================================================================
+    /// Active NPCs (currently spawned into the world)
+    pub(crate) active_npcs: DashMap<[u8; 16], ()>,
+
+    /// Inactive NPCs (in pool, ready to spawn)
+    /// Key: NPC type -> Vec of pooled ULIDs (ULID and name survive pool reuse)
+    pub(crate) inactive_npcs: DashMap<String, Vec<[u8; 16]>>,
+
+    /// Pool definitions by NPC type - capacity and overflow policy (see pools.rs)
+    pub(crate) pool_definitions: DashMap<String, PoolDefinition>,
+
+    /// Spawn requests waiting for a free slot (pools with the queue policy)
+    pub(crate) queued_spawns: DashMap<String, VecDeque<Vec2>>,
+
+    /// When each pool first held more idle slots than its max_idle (ms)
+    pub(crate) pool_idle_since: DashMap<String, u64>,
+
+    /// Pooled slots allowed across every type (0 = no budget)
+    pub(crate) node_budget: AtomicUsize,
+
+    // ============================================================================
+    // ARCHETYPES - Data-driven NPC type definitions (stats, faction, spawning)
+    // ============================================================================
+    /// Registered NPC types (built-ins plus anything loaded at runtime)
+    pub(crate) archetypes: ArchetypeRegistry,
+
+    // ============================================================================
+    // DAMAGE - Shared damage formula, modifier hooks and hit log (see damage.rs)
+    // ============================================================================
+    pub(crate) damage_config: RwLock<DamageConfig>,
+    pub(crate) damage_modifiers: RwLock<DamageModifiers>,
+    /// Recent hit breakdowns for combat logs (bounded, oldest dropped)
+    pub(crate) damage_log: Mutex<VecDeque<DamageBreakdown>>,
+
+    // ============================================================================
+    // STATUS EFFECTS - Effect definitions and per-NPC active effects (see effects.rs)
+    // ============================================================================
+    pub(crate) status_effect_defs: DashMap<String, StatusEffectDef>,
+    /// Active effects per NPC, in the order they were applied
+    pub(crate) npc_effects: DashMap<[u8; 16], Vec<ActiveEffect>>,
+    /// Applied / ticked / expired effects, drained by the host
+    pub(crate) status_events: SegQueue<StatusEffectEvent>,
+
+    // ============================================================================
+    // HEALING - Heals being channeled, by healer (see healing.rs)
+    // ============================================================================
+    pub(crate) heal_channels: DashMap<[u8; 16], HealChannel>,
+
+    // ============================================================================
+    // RESOURCES - Mana/energy change events (see resources.rs)
+    // ============================================================================
+    /// Mana and energy changes, drained by the host
+    pub(crate) resource_events: SegQueue<ResourceEvent>,
+
+    // ============================================================================
+    // REGENERATION - Round-robin regen queue (see regen.rs)
+    // ============================================================================
+    /// NPCs in visit order, oldest visit at the front
+    pub(crate) regen_queue: Mutex<VecDeque<[u8; 16]>>,
+    /// Simulation time of each queued NPC's last visit
+    pub(crate) regen_last_ms: DashMap<[u8; 16], u64>,
+
+    // ============================================================================
+    // HUNGER - Hunger step clock, food stockpile and events (see hunger.rs)
+    // ============================================================================
+    /// Simulation time of the last hunger step (0 = not started)
+    pub(crate) last_hunger_step_ms: AtomicU64,
+    /// Food units the kingdom's NPCs eat from
+    pub(crate) food_stockpile: AtomicU32,
+    /// Hunger level crossings and stockpile changes, drained by the host
+    pub(crate) hunger_events: SegQueue<HungerEvent>,
+
+    // ============================================================================
+    // EMOTIONS - Emotion scores, step clock and change events (see emotions.rs)
+    // ============================================================================
+    /// Score per emotion, only for NPCs that feel something
+    pub(crate) npc_emotions: DashMap<[u8; 16], EmotionScores>,
+    /// Simulation time of the last emotion step (0 = not started)
+    pub(crate) last_emotion_step_ms: AtomicU64,
+    /// Emotion changes, drained by the host
+    pub(crate) emotion_events: SegQueue<EmotionEvent>,
+
+    // ============================================================================
+    // THREAT - Threat tables, targets and decay clock (see threat.rs)
+    // ============================================================================
+    /// Threat per enemy, only for NPCs someone has threat on
+    pub(crate) npc_threat: DashMap<[u8; 16], BTreeMap<[u8; 16], f32>>,
+    /// Target each NPC is going after
+    pub(crate) npc_targets: DashMap<[u8; 16], [u8; 16]>,
+    /// Targets forced by the player (force_target)
+    pub(crate) forced_targets: DashMap<[u8; 16], [u8; 16]>,
+    /// Simulation time of the last threat decay step (0 = not started)
+    pub(crate) last_threat_step_ms: AtomicU64,
+
+    // ============================================================================
+    // SQUADS - Named groups holding a formation (see squads.rs)
+    // ============================================================================
+    /// Squads by name
+    pub(crate) squads: Mutex<BTreeMap<String, Squad>>,
+    /// Squad each member belongs to
+    pub(crate) npc_squads: DashMap<[u8; 16], String>,
+    /// Arrivals, leader changes and disbands, drained by the host
+    pub(crate) squad_events: SegQueue<SquadEvent>,
+
+    // ============================================================================
+    // FACTIONS - Named factions and their relationship matrix (see factions.rs)
+    // ============================================================================
+    /// Copy-on-write faction table, loaded once per phase
+    pub(crate) factions: RwLock<Arc<FactionTable>>,
+    /// Per-NPC faction (from the archetype or set at runtime) - NPCs without
+    /// an entry use the faction of their static bits
+    pub(crate) npc_factions: DashMap<[u8; 16], FactionId>,
+    /// Relation and NPC faction changes, drained by the host
+    pub(crate) faction_events: SegQueue<FactionEvent>,
+
+    // ============================================================================
+    // NODE COMMAND QUEUE - Drained by the host scene layer
+    // ============================================================================
+    /// Only record commands when a host layer is attached (headless runs skip the queue)
+    pub(crate) record_node_commands: AtomicBool,
+    pub(crate) node_commands: SegQueue<NodeCommand>,
+
+    // ============================================================================
+    // DETERMINISM - Time source and seeded RNG shared by every phase
+    // ============================================================================
+    /// Simulation time source (moved by the fixed-timestep driver by default)
+    pub(crate) clock: RwLock<Arc<dyn Clock>>,
+
+    /// Fixed-timestep accumulator, time scale and pause (see timestep.rs)
+    pub(crate) timestep: Mutex<Timestep>,
+
+    /// Held for each fixed step and by host-side changes (see runner.rs)
+    pub(crate) step_lock: Mutex<()>,
+
+    /// Pending timers by simulation time - despawns, state clears, cooldowns (see timers.rs)
+    pub(crate) timers: Mutex<TimerScheduler>,
+
+    /// Simulation RNG - wandering, spawn scatter, wave composition, names and ULIDs
+    /// Same seed + same inputs + same tick deltas = same battle
+    pub(crate) rng: Mutex<StdRng>,
+
+    /// Input recorder (see replay.rs) - the flag keeps the hot path lock-free
+    pub(crate) recording: AtomicBool,
+    pub(crate) recorder: Mutex<Option<InputRecorder>>,
+
+    // ============================================================================
+    // TYPED COMPONENT STORAGE - One DashMap per component, keyed by ULID bytes
+    // ============================================================================
+    /// NPC positions (ULID bytes -> world position)
+    pub(crate) npc_positions: DashMap<[u8; 16], Vec2>,
+
+    /// NPC metadata (ULID bytes -> value string)
+    pub(crate) npc_names: DashMap<[u8; 16], String>, // ULID -> generated name
+    pub(crate) npc_types: DashMap<[u8; 16], String>, // ULID -> npc_type (warrior, archer, etc.)
+
+    /// NPC combat stats (ULID bytes -> NPCCombatStats by value)
+    /// Contains: hp, max_hp, attack, defense, static_state, resources
+    pub(crate) npc_combat_stats: DashMap<[u8; 16], NPCCombatStats>,
+
+    /// NPC dynamic state (ULID bytes -> typed value)
+    pub(crate) npc_behavioral_state: DashMap<[u8; 16], NPCState>,
+    pub(crate) npc_cooldown: DashMap<[u8; 16], u64>, // Timestamp (ms) of the last attack
+
+    /// NPC movement data (ULID bytes -> world coordinates)
+    pub(crate) npc_waypoints: DashMap<[u8; 16], Vec2>, // Target position for movement
+    pub(crate) npc_move_directions: DashMap<[u8; 16], Vec2>, // Normalized movement direction
+}
+
+/// World bounds must enclose some area - waypoints and spawn points are
+/// sampled from min..max on both axes
+pub(super) fn validate_world_bounds(
+    min_x: f32,
+    max_x: f32,
+    min_y: f32,
+    max_y: f32,
+) -> Result<(), String> {
+    let finite = [min_x, max_x, min_y, max_y].iter().all(|v| v.is_finite());
+    if !finite || min_x >= max_x || min_y >= max_y {
+        return Err(format!(
+            "invalid world bounds X({} to {}), Y({} to {})",
+            min_x, max_x, min_y, max_y
+        ));
+    }
+    Ok(())
+}
+
+impl NPCDataWarehouse {
+    /// Read the current behavioral state of an NPC
+    pub fn get_behavioral_state(&self, ulid: &[u8; 16]) -> Option<NPCState> {
+        self.npc_behavioral_state.get(ulid).map(|v| *v.value())
+    }
+
+    /// Read a copy of the combat stats of an NPC
+    pub fn get_combat_stats(&self, ulid: &[u8; 16]) -> Option<NPCCombatStats> {
+        self.npc_combat_stats.get(ulid).map(|v| *v.value())
+    }
+
+    /// Create a new NPCDataWarehouse with the specified sync interval
+    pub fn new(sync_interval_ms: u64) -> Self {
+        sim_print!(
+            "NPCDataWarehouse: Initializing with {}ms sync interval",
+            sync_interval_ms
+        );
+
+        Self {
+            storage: DashMap::new(),
+            combat_event_queue: Arc::new(SegQueue::new()),
+            death_position_queue: SegQueue::new(),
+            combat_thread_running: Arc::new(AtomicBool::new(false)),
+            active_combat_npcs: DashMap::new(),
+            error_log: DashMap::new(),
+            last_spawn_time_ms: Arc::new(AtomicU64::new(0)),
+            last_ally_spawn_time_ms: Arc::new(AtomicU64::new(0)),
+            spawn_interval_ms: 10000,     // 10 seconds between monster waves
+            min_wave_size: 8,             // Start with 8 monsters per wave
+            max_wave_size: 8,             // Fixed at 8 monsters per wave
+            min_active_monsters: 3,       // Spawn new wave when below 3 monsters
+            ally_spawn_interval_ms: 3000, // 3 seconds between ally spawns (gradual ramp-up)
+            max_warriors: 6,              // Cap at 6 warriors
+            max_archers: 6,               // Cap at 6 archers
+            max_clerics: 2,               // Cap at 2 clerics (14 total allies)
+            initial_spawn_done: Arc::new(AtomicBool::new(false)),
+            // Initialize with default world bounds (will be updated by GDScript from BackgroundManager)
+            world_min_x: Arc::new(AtomicU32::new(WORLD_MIN_X.to_bits())),
+            world_max_x: Arc::new(AtomicU32::new(WORLD_MAX_X.to_bits())),
+            world_min_y: Arc::new(AtomicU32::new(WORLD_MIN_Y.to_bits())),
+            world_max_y: Arc::new(AtomicU32::new(WORLD_MAX_Y.to_bits())),
+            // Automatic spawning waits for the host (Godot opens it on set_scene_container)
+            spawning_enabled: AtomicBool::new(false),
+            // Initialize NPC pool slots
+            active_npcs: DashMap::new(),
+            inactive_npcs: DashMap::new(),
+            pool_definitions: DashMap::new(),
+            queued_spawns: DashMap::new(),
+            pool_idle_since: DashMap::new(),
+            node_budget: AtomicUsize::new(0),
+
+            // Built-in archetypes; hosts can load more at runtime
+            archetypes: ArchetypeRegistry::with_defaults(),
+
+            // Default formula, no modifiers
+            damage_config: RwLock::new(DamageConfig::default()),
+            damage_modifiers: RwLock::new(Vec::new()),
+            damage_log: Mutex::new(VecDeque::new()),
+
+            // Built-in poison / burn / slow / stun / regen
+            status_effect_defs: StatusEffectDef::defaults()
+                .into_iter()
+                .map(|def| (def.name.clone(), def))
+                .collect(),
+            npc_effects: DashMap::new(),
+            status_events: SegQueue::new(),
+            heal_channels: DashMap::new(),
+            resource_events: SegQueue::new(),
+            regen_queue: Mutex::new(VecDeque::new()),
+            regen_last_ms: DashMap::new(),
+            last_hunger_step_ms: AtomicU64::new(0),
+            food_stockpile: AtomicU32::new(DEFAULT_FOOD_STOCKPILE),
+            hunger_events: SegQueue::new(),
+            npc_emotions: DashMap::new(),
+            last_emotion_step_ms: AtomicU64::new(0),
+            emotion_events: SegQueue::new(),
+            npc_threat: DashMap::new(),
+            npc_targets: DashMap::new(),
+            forced_targets: DashMap::new(),
+            last_threat_step_ms: AtomicU64::new(0),
+            squads: Mutex::new(BTreeMap::new()),
+            npc_squads: DashMap::new(),
+            squad_events: SegQueue::new(),
+
+            // Built-in ally / monster / passive factions
+            factions: RwLock::new(Arc::new(FactionTable::with_defaults())),
+            npc_factions: DashMap::new(),
+            faction_events: SegQueue::new(),
+
+            // Node commands are only recorded once a host layer asks for them
+            record_node_commands: AtomicBool::new(false),
+            node_commands: SegQueue::new(),
+
+            // Simulation time starts at wall time and only moves with fixed steps;
+            // OS-seeded RNG until the host asks for determinism
+            clock: RwLock::new(Arc::new(ManualClock::new(SystemClock.now_ms()))),
+            timestep: Mutex::new(Timestep::default()),
+            step_lock: Mutex::new(()),
+            timers: Mutex::new(TimerScheduler::default()),
+            rng: Mutex::new(StdRng::from_os_rng()),
+            recording: AtomicBool::new(false),
+            recorder: Mutex::new(None),
+
+            // Initialize DashMap storage directly (no wrappers)
+            npc_positions: DashMap::new(),
+            npc_names: DashMap::new(),
+            npc_types: DashMap::new(),
+            npc_combat_stats: DashMap::new(),
+            npc_behavioral_state: DashMap::new(),
+            npc_cooldown: DashMap::new(),
+            npc_waypoints: DashMap::new(),
+            npc_move_directions: DashMap::new(),
+        }
+    }
+
+    /// Store active NPC data
+    pub fn store_npc(&self, ulid: &str, npc_data: &str) {
+        let key = format!("active:{}", ulid);
+        let value = npc_data.to_string();
+        self.storage.insert(key, value);
+    }
+
+    /// Get active NPC data
+    pub fn get_npc(&self, ulid: &str) -> Option<String> {
+        let key = format!("active:{}", ulid);
+        self.storage.get(&key).map(|v| v.value().clone())
+    }
+
+    /// Remove NPC from active pool
+    pub fn remove_npc(&self, ulid: &str) -> bool {
+        let key = format!("active:{}", ulid);
+        self.storage.remove(&key).is_some()
+    }
+
+    /// Store AI state for an NPC
+    pub fn store_ai_state(&self, ulid: &str, ai_data: &str) {
+        let key = format!("ai:{}", ulid);
+        let value = ai_data.to_string();
+        self.storage.insert(key, value);
+    }
+
+    /// Get AI state for an NPC
+    pub fn get_ai_state(&self, ulid: &str) -> Option<String> {
+        let key = format!("ai:{}", ulid);
+        self.storage.get(&key).map(|v| v.value().clone())
+    }
+
+    /// Store combat state for an NPC
+    pub fn store_combat_state(&self, ulid: &str, combat_data: &str) {
+        let key = format!("combat:{}", ulid);
+        let value = combat_data.to_string();
+        self.storage.insert(key, value);
+    }
+
+    /// Get combat state for an NPC
+    pub fn get_combat_state(&self, ulid: &str) -> Option<String> {
+        let key = format!("combat:{}", ulid);
+        self.storage.get(&key).map(|v| v.value().clone())
+    }
+
+    // ============================================================================
+    // ARCHETYPES - Runtime registration of NPC types
+    // ============================================================================
+
+    /// Register (or replace) an archetype after validating it
+    pub fn register_archetype(&self, archetype: NPCArchetype) -> Result<(), String> {
+        let name = archetype.name.clone();
+        self.archetypes.register(archetype.clone())?;
+        self.archetype_faction(&archetype);
+        self.record_input(RecordedInput::RegisterArchetype { archetype });
+        sim_print!("NPCDataWarehouse: Registered archetype '{}'", name);
+        Ok(())
+    }
+
+    /// Register a single archetype from its JSON object
+    pub fn register_archetype_json(&self, json: &str) -> Result<(), String> {
+        let archetype: NPCArchetype =
+            serde_json::from_str(json).map_err(|e| format!("invalid archetype JSON: {}", e))?;
+        self.register_archetype(archetype)
+    }
+
+    /// Load an archetype definitions file ({"archetypes": [...]})
+    /// Returns how many archetypes were registered (invalid ones are skipped)
+    pub fn load_archetypes_json(&self, json: &str) -> Result<usize, String> {
+        let registered = self.archetypes.load_json(json)?;
+        for name in self.archetypes.names() {
+            if let Some(archetype) = self.archetypes.get(&name) {
+                self.archetype_faction(&archetype);
+                self.record_input(RecordedInput::RegisterArchetype { archetype });
+            }
+        }
+        sim_print!("NPCDataWarehouse: Loaded {} archetypes", registered);
+        Ok(registered)
+    }
+
+    /// Look up an archetype by NPC type
+    pub fn get_archetype(&self, npc_type: &str) -> Option<NPCArchetype> {
+        self.archetypes.get(npc_type)
+    }
+
+    /// Registered NPC types, in registration order
+    pub fn archetype_names(&self) -> Vec<String> {
+        self.archetypes.names()
+    }
+
+    // ============================================================================
+    // NPC POOL MANAGEMENT - Pooled ULIDs, spawn and despawn
+    // ============================================================================
+
+    /// Pre-populate the inactive pool with NPC slots of a given type
+    /// Each slot gets a ULID and a generated name that survive pool reuse.
+    /// Returns the pooled ULIDs so a host layer can bind scene nodes to them.
+    /// Never creates slots past the pool's max_size (see pools.rs)
+    pub fn initialize_npc_pool(&self, npc_type: &str, pool_size: usize) -> Vec<[u8; 16]> {
+        self.record_input(RecordedInput::InitializePool {
+            npc_type: npc_type.to_string(),
+            pool_size,
+        });
+        let pool_size = self.reserve_pool_slots(npc_type, pool_size);
+        sim_print!(
+            "[RUST POOL] Initializing pool for {} (size: {})",
+            npc_type,
+            pool_size
+        );
+
+        // Name style comes from the archetype (unknown types get generic names)
+        let name_style = self
+            .archetypes
+            .get(npc_type)
+            .map(|archetype| archetype.name_style().to_string())
+            .unwrap_or_else(|| npc_type.to_string());
+
+        // Create pool of inactive NPC slots
+        let mut pool_vec = Vec::with_capacity(pool_size);
+        for _ in 0..pool_size {
+            // Generate ULID as 16 bytes
+            let ulid = self.next_ulid();
+
+            // Generate a unique name for this NPC (kept across respawns)
+            let name = crate::name_generator::generate_name(&name_style, &mut *self.rng());
+            sim_print!(
+                "[RUST NPC] Created {} '{}' with ULID: {}",
+                npc_type,
+                name,
+                &bytes_to_hex(&ulid)[..16]
+            );
+
+            self.npc_names.insert(ulid, name);
+            self.npc_types.insert(ulid, npc_type.to_string());
+            pool_vec.push(ulid);
+        }
+
+        self.inactive_npcs
+            .entry(npc_type.to_string())
+            .or_default()
+            .extend(pool_vec.iter().copied());
+        sim_print!(
+            "[RUST POOL] Created {} inactive NPCs for type {}",
+            pool_vec.len(),
+            npc_type
+        );
+
+        // New slots take spawns that were waiting for room
+        for _ in 0..pool_vec.len() {
+            self.spawn_queued(npc_type);
+        }
+
+        pool_vec
+    }
+
+    /// Open or close automatic spawning (initial spawn, monster waves, ally ramp-up)
+    pub fn set_spawning_enabled(&self, enabled: bool) {
+        self.record_input(RecordedInput::SpawningEnabled { enabled });
+        self.spawning_enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Record spawn/despawn/HP commands for a host scene layer to drain
+    pub fn set_record_node_commands(&self, enabled: bool) {
+        self.record_node_commands.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Pop the next pending node command (None when the queue is empty)
+    pub fn pop_node_command(&self) -> Option<NodeCommand> {
+        self.node_commands.pop()
+    }
+
+    /// Pop the next combat event queued by the simulation runner
+    pub fn pop_combat_event(&self) -> Option<CombatEvent> {
+        self.combat_event_queue.pop()
+    }
+
+    /// Pop the next death position (x, y) queued by the simulation runner
+    pub fn pop_death_position(&self) -> Option<(f32, f32)> {
+        self.death_position_queue.pop()
+    }
+
+    /// Queue a node command if a host layer is attached
+    pub(crate) fn push_node_command(&self, command: NodeCommand) {
+        if self.record_node_commands.load(Ordering::Relaxed) {
+            self.node_commands.push(command);
+        }
+    }
+
+    /// Spawn an NPC from the inactive pool
+    /// Returns the ULID bytes of the spawned NPC, or None if pool is empty
+    pub fn rust_spawn_npc(&self, npc_type: &str, position: Vec2) -> Option<[u8; 16]> {
+        self.record_input(RecordedInput::Spawn {
+            npc_type: npc_type.to_string(),
+            position,
+        });
+        self.spawn_from_pool(npc_type, position)
+    }
+
+    /// Spawn without recording (the simulation's own spawns)
+    pub(super) fn spawn_from_pool(&self, npc_type: &str, position: Vec2) -> Option<[u8; 16]> {
+        sim_print!(
+            "[RUST SPAWN DEBUG] rust_spawn_npc called for type: {}",
+            npc_type
+        );
+
+        // Enforce the pool's capacity (queues or rejects by its overflow policy)
+        if !self.admit_spawn(npc_type, position) {
+            return None;
+        }
+
+        // Get an inactive NPC slot from the pool
+        let ulid = {
+            let mut pool_entry = match self.inactive_npcs.get_mut(npc_type) {
+                Some(entry) => entry,
+                None => {
+                    // Pool doesn't exist yet - might be during initialization
+                    sim_warn!(
+                        "[RUST POOL] No pool found for NPC type: {} (may not be initialized yet)",
+                        npc_type
+                    );
+                    return None;
+                }
+            };
+
+            match pool_entry.pop() {
+                Some(ulid) => ulid,
+                None => {
+                    sim_warn!("[RUST POOL] Pool empty for NPC type: {} (all NPCs in use, consider increasing pool size)", npc_type);
+                    return None;
+                }
+            }
+        };
+
+        // Register for combat using the archetype stats for this NPC type
+        let npc_stats = match self.archetypes.get(npc_type) {
+            Some(archetype) => {
+                self.npc_factions
+                    .insert(ulid, self.archetype_faction(&archetype));
+                archetype.combat_stats()
+            }
+            None => {
+                sim_warn!(
+                    "[RUST POOL] No archetype registered for NPC type: {} - using fallback stats",
+                    npc_type
+                );
+                NPCCombatStats::fallback()
+            }
+        };
+        let ulid_hex = bytes_to_hex(&ulid);
+        sim_print!(
+            "[RUST SPAWN DEBUG] About to register NPC {} with ULID: {}",
+            npc_type,
+            ulid_hex
+        );
+        self.register_npc_with_stats(&ulid, &npc_stats);
+
+        // Store position for combat system (typed, no string encoding)
+        self.npc_positions.insert(ulid, position);
+
+        // Set initial wander cooldown so NPC stays idle for a bit after spawning (5-10 seconds)
+        let initial_idle_time = self.rng().random_range(5000..10000); // 5-10 seconds in milliseconds
+        self.schedule_timer(initial_idle_time, TimerAction::EndWanderCooldown { ulid });
+
+        // Move to active set
+        self.active_npcs.insert(ulid, ());
+        self.push_node_command(NodeCommand::Spawned {
+            ulid,
+            npc_type: npc_type.to_string(),
+            position,
+        });
+
+        // Log spawn with first 8 bytes in hex and static_state
+        let static_flags = npc_stats.static_flags();
+        sim_print!(
+            "[RUST POOL] Spawned {} at {:?} with ULID {} (ally={}, monster={}, static_state={})",
+            npc_type,
+            position,
+            &ulid_hex[..16],
+            static_flags.contains(NPCStaticState::ALLY),
+            static_flags.contains(NPCStaticState::MONSTER),
+            npc_stats.static_state
+        );
+
+        Some(ulid)
+    }
+
+    /// Despawn an NPC and return it to the inactive pool
+    pub fn rust_despawn_npc(&self, ulid: &[u8]) -> bool {
+        // Convert ulid slice to array for ByteMap access
+        let ulid_array: [u8; 16] = match ulid.try_into() {
+            Ok(array) => array,
+            Err(_) => {
+                sim_warn!(
+                    "[RUST POOL] Cannot despawn - invalid ULID length: {}",
+                    ulid.len()
+                );
+                return false;
+            }
+        };
+        self.record_input(RecordedInput::Despawn { ulid: ulid_array });
+        self.despawn_to_pool(&ulid_array)
+    }
+
+    /// Despawn without recording (the simulation's own despawns)
+    pub(super) fn despawn_to_pool(&self, ulid_array: &[u8; 16]) -> bool {
+        let ulid_array = *ulid_array;
+        let ulid_hex = bytes_to_hex(&ulid_array);
+
+        // Remove from active set
+        if self.active_npcs.remove(&ulid_array).is_none() {
+            sim_warn!(
+                "[RUST POOL] Cannot despawn - NPC not found: {}",
+                &ulid_hex[..16]
+            );
+            return false;
+        }
+        self.push_node_command(NodeCommand::Despawned { ulid: ulid_array });
+
+        // Leave combat and drop pending timers so a respawned slot starts clean
+        self.active_combat_npcs.remove(&ulid_array);
+        self.cancel_npc_timers(&ulid_array);
+        self.npc_waypoints.remove(&ulid_array);
+        self.npc_move_directions.remove(&ulid_array);
+        // Charms, conversions, status effects, heal channels, emotions and
+        // regen visits end with the NPC
+        self.npc_factions.remove(&ulid_array);
+        self.clear_status_effects(&ulid_array);
+        self.cancel_heal_channel(&ulid_array);
+        self.clear_emotions(&ulid_array);
+        self.forget_regen(&ulid_array);
+        // Nobody keeps chasing this slot (it may come back as a different NPC)
+        self.clear_threat(&ulid_array);
+        self.drop_from_squad(&self.stored(), &ulid_array);
+
+        // Reset NPC stats (HP back to max, remove DEAD state)
+        if let Some(mut combat_stats) = self.npc_combat_stats.get_mut(&ulid_array) {
+            combat_stats.hp = combat_stats.max_hp; // Reset HP to max
+        }
+
+        // Behavioral state: reset to no flags (0)
+        self.npc_behavioral_state
+            .insert(ulid_array, NPCState::empty());
+
+        // Cooldown: reset to 0
+        self.npc_cooldown.insert(ulid_array, 0);
+
+        // Note: Keep name and type - they don't change when pooled NPCs respawn
+        // Note: Static state (faction, combat type) never changes
+
+        // Return to inactive pool
+        let npc_type = self
+            .npc_types
+            .get(&ulid_array)
+            .map(|v| v.value().clone())
+            .unwrap_or_default();
+        if let Some(mut pool_entry) = self.inactive_npcs.get_mut(&npc_type) {
+            pool_entry.push(ulid_array);
+            drop(pool_entry);
+            sim_print!(
+                "[RUST POOL] Despawned {} '{}' (ULID: {}) - Reset and returned to pool",
+                npc_type,
+                self.npc_names
+                    .get(&ulid_array)
+                    .map(|v| v.value().clone())
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                &ulid_hex[..16]
+            );
+
+            // A slot is free - run the oldest spawn waiting for it
+            self.spawn_queued(&npc_type);
+            true
+        } else {
+            sim_error!(
+                "[RUST POOL] Cannot return NPC to pool - pool not found for type: {}",
+                npc_type
+            );
+            false
+        }
+    }
+
+    /// ULIDs of all currently spawned NPCs, in ULID order
+    pub fn active_npc_ulids(&self) -> Vec<[u8; 16]> {
+        let mut ulids: Vec<[u8; 16]> = self.active_npcs.iter().map(|e| *e.key()).collect();
+        ulids.sort_unstable();
+        ulids
+    }
+
+    /// Count currently spawned NPCs of a given type
+    pub fn active_count_for_type(&self, npc_type: &str) -> usize {
+        self.active_npcs
+            .iter()
+            .filter(|entry| {
+                self.npc_types
+                    .get(entry.key())
+                    .is_some_and(|t| t.value() == npc_type)
+            })
+            .count()
+    }
+
+    /// Current world bounds as (min, max) corners
+    pub fn world_bounds(&self) -> (Vec2, Vec2) {
+        (
+            Vec2::new(
+                f32::from_bits(self.world_min_x.load(Ordering::Relaxed)),
+                f32::from_bits(self.world_min_y.load(Ordering::Relaxed)),
+            ),
+            Vec2::new(
+                f32::from_bits(self.world_max_x.load(Ordering::Relaxed)),
+                f32::from_bits(self.world_max_y.load(Ordering::Relaxed)),
+            ),
+        )
+    }
+
+    /// Update world bounds (called when background changes)
+    /// Fails unless both axes are finite and non-empty (min < max)
+    pub fn set_world_bounds(
+        &self,
+        min_x: f32,
+        max_x: f32,
+        min_y: f32,
+        max_y: f32,
+    ) -> Result<(), String> {
+        validate_world_bounds(min_x, max_x, min_y, max_y)?;
+        self.record_input(RecordedInput::WorldBounds {
+            min_x,
+            max_x,
+            min_y,
+            max_y,
+        });
+        self.store_world_bounds(min_x, max_x, min_y, max_y);
+        Ok(())
+    }
+
+    /// Update world bounds without recording
+    pub(super) fn store_world_bounds(&self, min_x: f32, max_x: f32, min_y: f32, max_y: f32) {
+        self.world_min_x.store(min_x.to_bits(), Ordering::Relaxed);
+        self.world_max_x.store(max_x.to_bits(), Ordering::Relaxed);
+        self.world_min_y.store(min_y.to_bits(), Ordering::Relaxed);
+        self.world_max_y.store(max_y.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Register NPC for combat using pre-extracted combat stats
+    fn register_npc_with_stats(&self, ulid: &[u8; 16], stats: &NPCCombatStats) {
+        let registered = self.register_combat(
+            ulid,
+            stats.static_state,
+            NPCState::IDLE.bits() as i32, // behavioral_state starts as IDLE
+            stats.max_hp,
+            stats.attack,
+            stats.defense,
+        );
+
+        // Registration passed validation - keep the full archetype stats
+        // (resource pools, attack range, cooldown, speed)
+        if registered {
+            self.npc_combat_stats.insert(*ulid, *stats);
+        }
+    }
+
+    /// Check if NPC exists in active pool
+    pub fn has_npc(&self, ulid: &str) -> bool {
+        let key = format!("active:{}", ulid);
+        self.storage.contains_key(&key)
+    }
+
+    /// Get total number of entries in storage
+    pub fn total_entries(&self) -> usize {
+        self.storage.len()
+    }
+
+    /// Get number of entries in storage (DashMap)
+    pub fn read_store_count(&self) -> usize {
+        self.storage.len()
+    }
+
+    /// Get number of entries in write store (DashMap)
+    pub fn write_store_count(&self) -> usize {
+        self.storage.len()
+    }
+
+    /// Manually trigger sync from write store to read store - no-op for DashMap
+    pub fn sync(&self) {
+        // DashMap doesn't need syncing - it's always consistent
+    }
+
+    /// Clear all data (use with caution!)
+    pub fn clear_all(&self) {
+        self.storage.clear();
+        sim_print!("NPCDataWarehouse: All data cleared");
+    }
+
+    // ============================================================================
+    // RUST-FIRST COMBAT SYSTEM - Internal Implementation
+    // ============================================================================
+    // These methods are called by the GodotNPCDataWarehouse wrapper (npc_data_warehouse.rs)
+    // Rust owns all combat logic. GDScript only renders visual feedback.
+
+    /// Update NPC position - public for Arc access
+    pub fn update_npc_position_internal(&self, ulid: &[u8; 16], x: f32, y: f32) {
+        self.record_input(RecordedInput::Position {
+            ulid: *ulid,
+            position: Vec2::new(x, y),
+        });
+
+        // DEFENSIVE: Validate position values are finite
+        if !x.is_finite() {
+            let ulid_hex = bytes_to_hex(ulid);
+            self.log_error_once(
+                "invalid_position_x",
+                &ulid_hex,
+                &format!(
+                    "[COMBAT ERROR] Cannot update position for {} - invalid x: {}",
+                    ulid_hex, x
+                ),
+            );
+            return;
+        }
+        if !y.is_finite() {
+            let ulid_hex = bytes_to_hex(ulid);
+            self.log_error_once(
+                "invalid_position_y",
+                &ulid_hex,
+                &format!(
+                    "[COMBAT ERROR] Cannot update position for {} - invalid y: {}",
+                    ulid_hex, y
+                ),
+            );
+            return;
+        }
+
+        self.npc_positions.insert(*ulid, Vec2::new(x, y));
+    }
+
+    /// Get NPC position - public for Arc access
+    pub fn get_npc_position_internal(&self, ulid: &[u8; 16]) -> Option<Vec2> {
+        let pos = self.npc_positions.get(ulid).map(|v| *v.value())?;
+
+        // DEFENSIVE: Validate stored values are finite
+        if !pos.is_finite() {
+            let ulid_hex = bytes_to_hex(ulid);
+            self.log_error_once(
+                "nan_position",
+                &ulid_hex,
+                &format!(
+                    "[COMBAT ERROR] NPC {} has invalid position: ({}, {})",
+                    ulid_hex, pos.x, pos.y
+                ),
+            );
+            return None;
+        }
+        Some(pos)
+    }
+
+    // ============================================================================
+    // COMBAT REGISTRATION - Track NPCs in combat system
+    // ============================================================================
+
+    /// Register NPC for combat tracking
+    /// Stores combat-relevant components for combat tick access
+    pub fn register_npc_for_combat_internal(
+        &self,
+        ulid: &[u8; 16],
+        static_state: i32,
+        behavioral_state: i32,
+        max_hp: f32,
+        attack: f32,
+        defense: f32,
+    ) {
+        let registered = self.register_combat(
+            ulid,
+            static_state,
+            behavioral_state,
+            max_hp,
+            attack,
+            defense,
+        );
+        if registered {
+            self.record_input(RecordedInput::RegisterCombat {
+                ulid: *ulid,
+                static_state,
+                behavioral_state,
+                max_hp,
+                attack,
+                defense,
+            });
+        }
+    }
+
+    /// Validate and store combat stats (not recorded)
+    /// Returns false if the registration was rejected
+    fn register_combat(
+        &self,
+        ulid: &[u8; 16],
+        static_state: i32,
+        behavioral_state: i32,
+        max_hp: f32,
+        attack: f32,
+        defense: f32,
+    ) -> bool {
+        // Convert to hex only for error messages (combat system uses bytes internally)
+        let ulid_hex_for_logging = bytes_to_hex(ulid);
+        let ulid_str = &ulid_hex_for_logging;
+
+        // DEFENSIVE: Validate stats are finite and non-negative
+        if !max_hp.is_finite() || max_hp < 0.0 {
+            self.log_error_once(
+                "invalid_hp",
+                ulid_str,
+                &format!(
+                    "[COMBAT ERROR] Cannot register NPC {} - invalid max_hp: {}",
+                    ulid_str, max_hp
+                ),
+            );
+            return false;
+        }
+        if !attack.is_finite() || attack < 0.0 {
+            self.log_error_once(
+                "invalid_attack",
+                ulid_str,
+                &format!(
+                    "[COMBAT ERROR] Cannot register NPC {} - invalid attack: {}",
+                    ulid_str, attack
+                ),
+            );
+            return false;
+        }
+        if !defense.is_finite() || defense < 0.0 {
+            self.log_error_once(
+                "invalid_defense",
+                ulid_str,
+                &format!(
+                    "[COMBAT ERROR] Cannot register NPC {} - invalid defense: {}",
+                    ulid_str, defense
+                ),
+            );
+            return false;
+        }
+
+        let static_flags = NPCStaticState::from_bits_retain(static_state as u32);
+
+        // DEFENSIVE: Validate exactly one combat type is set
+        let combat_type_count = (static_flags
+            & (NPCStaticState::MELEE
+                | NPCStaticState::RANGED
+                | NPCStaticState::MAGIC
+                | NPCStaticState::HEALER))
+            .bits()
+            .count_ones();
+
+        if combat_type_count != 1 {
+            self.log_error_once("invalid_combat_type", ulid_str, &format!("[COMBAT ERROR] Cannot register NPC {} - must have exactly one combat type (MELEE/RANGED/MAGIC/HEALER), found: {}", ulid_str, combat_type_count));
+            return false;
+        }
+
+        // DEFENSIVE: Validate exactly one faction is set (ALLY, MONSTER, or PASSIVE)
+        let faction_count = (static_flags
+            & (NPCStaticState::ALLY | NPCStaticState::MONSTER | NPCStaticState::PASSIVE))
+            .bits()
+            .count_ones();
+
+        if faction_count != 1 {
+            self.log_error_once("invalid_faction", ulid_str, &format!("[COMBAT ERROR] Cannot register NPC {} - must have exactly one faction (ALLY/MONSTER/PASSIVE), found: {}", ulid_str, faction_count));
+            return false;
+        }
+
+        // All validations passed - register NPC for combat
+        let combat_stats = NPCCombatStats {
+            hp: max_hp,
+            max_hp,
+            attack,
+            defense,
+            static_state,
+            emotional_state: 0, // Default neutral emotion
+            mana: 0.0,          // Will be set by NPC type later if needed
+            max_mana: 0.0,
+            energy: 100.0, // Default full energy
+            max_energy: 100.0,
+            hunger: 100.0, // Default full hunger
+            max_hunger: 100.0,
+            attack_range: default_attack_range(static_flags),
+            attack_cooldown_ms: DEFAULT_ATTACK_COOLDOWN_MS,
+            move_speed: DEFAULT_MOVE_SPEED,
+            damage_type: DamageType::for_static(static_flags),
+            resistances: Resistances::default(),
+            crit_chance: 0.0,
+            crit_multiplier: DEFAULT_CRIT_MULTIPLIER,
+            heal_power: 0.0,
+            heal_mana_cost: 0.0,
+            heal_channel_ms: DEFAULT_HEAL_CHANNEL_MS,
+            attack_energy_cost: 0.0,
+            attack_mana_cost: 0.0,
+            energy_regen: 0.0,
+            mana_regen: 0.0,
+            hp_regen: 0.0,
+            hunger_decay: 0.0,
+            target_strategy: TargetStrategy::HighestThreat,
+        };
+        self.npc_combat_stats.insert(*ulid, combat_stats);
+        sim_print!(
+            "[RUST STATE] register_npc_for_combat_internal ULID {} - setting state to {}",
+            ulid_str,
+            behavioral_state
+        );
+        self.npc_behavioral_state
+            .insert(*ulid, NPCState::from_bits_retain(behavioral_state as u32));
+        self.npc_cooldown.insert(*ulid, 0);
+
+        self.active_combat_npcs.insert(*ulid, ());
+        true
+    }
+
+    /// Unregister NPC from combat (on death/despawn)
+    /// Cleans up all combat-related components for this NPC
+    pub fn unregister_npc_from_combat_internal(&self, ulid: &[u8; 16]) {
+        self.record_input(RecordedInput::UnregisterCombat { ulid: *ulid });
+        self.npc_positions.remove(ulid);
+        self.npc_cooldown.remove(ulid);
+        self.cancel_npc_timer(ulid, NpcTimer::ClearState(NPCState::ATTACKING));
+        self.cancel_npc_timer(ulid, NpcTimer::ClearState(NPCState::DAMAGED));
+        self.npc_waypoints.remove(ulid);
+        self.npc_move_directions.remove(ulid);
+        self.npc_factions.remove(ulid);
+        self.clear_status_effects(ulid);
+        self.cancel_heal_channel(ulid);
+        self.clear_emotions(ulid);
+        self.forget_regen(ulid);
+        self.clear_threat(ulid);
+        self.drop_from_squad(&self.stored(), ulid);
+
+        // Clean up error_log entries for this NPC
+        // We need to remove all error types for this ulid
+        let ulid_hex = bytes_to_hex(ulid);
+        let error_types = [
+            "invalid_ulid",
+            "invalid_hp",
+            "invalid_attack",
+            "invalid_defense",
+            "invalid_combat_type",
+            "invalid_faction",
+            "invalid_position_x",
+            "invalid_position_y",
+            "nan_position",
+            "missing_position",
+            "dead_attacker",
+            "dead_target",
+            "missing_hp",
+            "self_attack",
+            "self_attack_pair",
+        ];
+
+        for error_type in error_types {
+            self.error_log
+                .remove(&format!("{}:{}", error_type, ulid_hex));
+        }
+
+        // Remove from active NPCs set
+        self.active_combat_npcs.remove(ulid);
+    }
+
+    /// Log error once per error_type:ulid combination using error_log
+    /// This prevents spam by tracking which errors have been logged
+    pub(super) fn log_error_once(&self, error_type: &str, ulid: &str, message: &str) {
+        let key = format!("{}:{}", error_type, ulid);
+
+        // Check if we've already logged this error
+        if self.error_log.get(&key).is_none() {
+            // First time seeing this error - log it and mark as seen
+            sim_error!("{}", message);
+            self.error_log.insert(key, "1".to_string());
+        }
+        // If already logged, silently skip (no spam)
+    }
+
+    /// Get NPC current HP
+    pub fn get_npc_hp_internal(&self, ulid: &[u8; 16]) -> Option<f32> {
+        self.npc_combat_stats.get(ulid).map(|v| v.hp)
+    }
+
+    // ============================================================================
+    // TICK PHASES
+    // ============================================================================
+    // The phases run as Bevy systems (see ecs.rs), in this order:
+    // 1. Combat Phase: Calculate damage, update HP, set states (ATTACKING, DAMAGED, DEAD)
+    // 2. Movement Phase: Handle wandering, calculate directions, update positions
+    // 3. Cleanup Phase: Fire due timers (despawns, transient state clears, cooldowns)
+    // Sprites are driven by the host layer from the resulting behavioral states.
+
+    /// Get current simulation time in milliseconds (from the installed clock)
+    pub fn get_current_time_ms(&self) -> u64 {
+        self.clock.read().now_ms()
+    }
+
+    // ============================================================================
+    // CLOCK AND RNG - Deterministic simulation controls
+    // ============================================================================
+
+    /// Install a time source (all cooldowns, timers and spawn waves read it)
+    pub fn set_clock(&self, clock: Arc<dyn Clock>) {
+        *self.clock.write() = clock;
+    }
+
+    /// Switch to a manual clock starting at `start_ms` and return its handle
+    pub fn use_manual_clock(&self, start_ms: u64) -> Arc<ManualClock> {
+        let clock = Arc::new(ManualClock::new(start_ms));
+        self.set_clock(clock.clone());
+        clock
+    }
+
+    /// Reseed the simulation RNG
+    pub fn set_rng_seed(&self, seed: u64) {
+        *self.rng.lock() = StdRng::seed_from_u64(seed);
+        sim_print!("NPCDataWarehouse: RNG seeded with {}", seed);
+    }
+
+    /// Lock the simulation RNG (keep the guard short - spawning re-locks it)
+    pub(super) fn rng(&self) -> MutexGuard<'_, StdRng> {
+        self.rng.lock()
+    }
+
+    /// Generate a ULID from the simulation clock and RNG
+    pub(super) fn next_ulid(&self) -> [u8; 16] {
+        let random: u128 = self.rng().random();
+        ulid::Ulid::from_parts(self.get_current_time_ms(), random).to_bytes()
+    }
+
+    // ============================================================================
+    // COMBAT THREAD LIFECYCLE
+    // ============================================================================
+
+    /// Mark the combat system as running
+    /// Ticking itself belongs to `SimulationRunner` (inline or on its worker thread)
+    pub fn start_combat_thread(self: &Arc<Self>) {
+        self.combat_thread_running.store(true, Ordering::Relaxed);
+    }
+
+    /// Mark the combat system as stopped
+    pub fn stop_combat_thread(&self) {
+        self.combat_thread_running.store(false, Ordering::Relaxed);
+    }
+
+    /// Check if combat system is enabled
+    pub fn is_combat_enabled(&self) -> bool {
+        self.combat_thread_running.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,65 @@
+//! Shared setup for the headless simulation tests
+
+#![allow(dead_code)]
+
+use bevy::math::Vec2;
+use godo::simulation::{CombatEvent, ManualClock, NPCDataWarehouse, SimulationWorld};
+use std::sync::Arc;
+
+/// Simulated time per tick (60 Hz)
+pub const TICK_MS: u64 = 16;
+
+/// A warehouse on a manual clock with a fixed seed, world bounds set,
+/// automatic spawning off and pools for every archetype used in the tests
+pub fn battle_world(seed: u64) -> (Arc<NPCDataWarehouse>, SimulationWorld, Arc<ManualClock>) {
+    let warehouse = Arc::new(NPCDataWarehouse::new(1000));
+    let world = SimulationWorld::new(warehouse.clone());
+    warehouse.set_rng_seed(seed);
+    let clock = warehouse.use_manual_clock(1_000_000);
+    warehouse.set_spawning_enabled(false);
+    warehouse
+        .set_world_bounds(0.0, 1280.0, 0.0, 720.0)
+        .expect("bounds");
+    for npc_type in ["warrior", "archer", "cleric", "goblin"] {
+        warehouse.initialize_npc_pool(npc_type, 8);
+    }
+    (warehouse, world, clock)
+}
+
+/// Spawn `count` NPCs of a type in a column starting at `at`
+pub fn spawn_group(
+    warehouse: &NPCDataWarehouse,
+    npc_type: &str,
+    count: usize,
+    at: Vec2,
+) -> Vec<[u8; 16]> {
+    (0..count)
+        .map(|i| {
+            warehouse
+                .rust_spawn_npc(npc_type, at + Vec2::new(0.0, 50.0 * i as f32))
+                .expect("pool slot")
+        })
+        .collect()
+}
+
+/// Advance the clock and run one tick, standing in for the host: projectiles
+/// land as soon as they are fired
+pub fn step(
+    warehouse: &NPCDataWarehouse,
+    world: &mut SimulationWorld,
+    clock: &ManualClock,
+) -> Vec<CombatEvent> {
+    clock.advance_ms(TICK_MS);
+    let (mut events, _) = world.tick(TICK_MS as f32 / 1000.0);
+    let landed: Vec<CombatEvent> = events
+        .iter()
+        .filter_map(|event| match event {
+            CombatEvent::Projectile {
+                attacker, target, ..
+            } => warehouse.projectile_hit(attacker, target),
+            _ => None,
+        })
+        .collect();
+    events.extend(landed);
+    events
+}
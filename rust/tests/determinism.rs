@@ -0,0 +1,56 @@
+//! Same seed + same inputs + same tick deltas = same battle
+
+mod common;
+
+use bevy::math::Vec2;
+
+use common::{battle_world, spawn_group, step};
+
+/// Ten seconds of simulated time: long enough for several exchanges
+const TICKS: usize = 600;
+
+/// ULID, HP and position of every active NPC, in ULID order
+type Outcome = Vec<([u8; 16], f32, Vec2)>;
+
+fn run(seed: u64) -> (u64, Outcome) {
+    let (warehouse, mut world, clock) = battle_world(seed);
+    spawn_group(&warehouse, "warrior", 2, Vec2::new(400.0, 250.0));
+    spawn_group(&warehouse, "archer", 2, Vec2::new(320.0, 275.0));
+    spawn_group(&warehouse, "cleric", 1, Vec2::new(300.0, 350.0));
+    spawn_group(&warehouse, "goblin", 4, Vec2::new(700.0, 200.0));
+
+    for _ in 0..TICKS {
+        step(&warehouse, &mut world, &clock);
+    }
+
+    let outcome = warehouse
+        .active_npc_ulids()
+        .into_iter()
+        .map(|ulid| {
+            let hp = warehouse.get_npc_hp_internal(&ulid).expect("hp");
+            let pos = warehouse.get_npc_position_internal(&ulid).expect("pos");
+            (ulid, hp, pos)
+        })
+        .collect();
+    (warehouse.state_hash(), outcome)
+}
+
+#[test]
+fn same_seed_same_battle() {
+    let (hash_a, outcome_a) = run(7);
+    let (hash_b, outcome_b) = run(7);
+    assert_eq!(hash_a, hash_b);
+    assert_eq!(outcome_a, outcome_b);
+}
+
+#[test]
+fn different_seed_different_battle() {
+    let (hash_a, outcome_a) = run(7);
+    let (hash_b, outcome_b) = run(8);
+    assert_ne!(hash_a, hash_b);
+    // ULIDs come from the seeded RNG too, so compare the bodies alone
+    let bodies = |outcome: &Outcome| -> Vec<(f32, Vec2)> {
+        outcome.iter().map(|(_, hp, pos)| (*hp, *pos)).collect()
+    };
+    assert_ne!(bodies(&outcome_a), bodies(&outcome_b));
+}
@@ -0,0 +1,35 @@
+//! Queries filtered by faction follow an NPC's current faction
+
+mod common;
+
+use bevy::math::Vec2;
+use godo::simulation::NpcFilter;
+
+use common::{battle_world, spawn_group};
+
+#[test]
+fn faction_filter_follows_faction_changes() {
+    let (warehouse, _world, _clock) = battle_world(3);
+    let warriors = spawn_group(&warehouse, "warrior", 2, Vec2::new(300.0, 300.0));
+    let goblins = spawn_group(&warehouse, "goblin", 2, Vec2::new(400.0, 300.0));
+    let allies = NpcFilter {
+        faction: Some(warehouse.faction_id("ally").unwrap()),
+        ..NpcFilter::default()
+    };
+    let monsters = NpcFilter {
+        faction: Some(warehouse.faction_id("monster").unwrap()),
+        ..NpcFilter::default()
+    };
+
+    let mut expected = warriors.clone();
+    expected.sort_unstable();
+    assert_eq!(warehouse.query_matching(&allies), expected);
+
+    // A charmed goblin is found with the allies, and no longer with the monsters
+    warehouse.set_npc_faction(&goblins[0], "ally").unwrap();
+    let found = warehouse.query_radius(Vec2::new(400.0, 300.0), 500.0, &allies);
+    assert_eq!(found.len(), 3);
+    assert_eq!(found[0], goblins[0]);
+    assert_eq!(warehouse.query_matching(&monsters), vec![goblins[1]]);
+    assert!(warehouse.faction_id("pirates").is_err());
+}
@@ -0,0 +1,63 @@
+//! AFK catch-up: fast_forward must end where live stepping would have
+//!
+//! Melee fighters and a healer only - arrows land when the host reports the
+//! hit, which no host does here.
+
+mod common;
+
+use bevy::math::Vec2;
+use godo::simulation::SimulationRunner;
+
+use common::{battle_world, spawn_group};
+
+/// Real time per host frame
+const FRAME_SECS: f32 = 1.0 / 60.0;
+
+fn battle(seed: u64) -> SimulationRunner {
+    let (warehouse, _world, _clock) = battle_world(seed);
+    spawn_group(&warehouse, "warrior", 3, Vec2::new(400.0, 250.0));
+    spawn_group(&warehouse, "cleric", 1, Vec2::new(300.0, 300.0));
+    spawn_group(&warehouse, "goblin", 4, Vec2::new(700.0, 220.0));
+    SimulationRunner::new(warehouse)
+}
+
+#[test]
+fn fast_forward_matches_live_stepping() {
+    // Live: 1x, a pause, then 4x, one advance per host frame
+    let mut live = battle(21);
+    let warehouse = live.warehouse().clone();
+    for _ in 0..300 {
+        live.advance(FRAME_SECS);
+    }
+    warehouse.pause();
+    let before_pause = live.ticks();
+    for _ in 0..30 {
+        assert_eq!(live.advance(FRAME_SECS), 0);
+    }
+    assert_eq!(live.ticks(), before_pause);
+    warehouse.resume();
+    warehouse.set_time_scale(4.0);
+    for _ in 0..150 {
+        live.advance(FRAME_SECS);
+    }
+    let steps = live.ticks() as u32;
+    assert!(steps > 800, "only {} steps ran", steps);
+
+    // Catch-up: the same simulated time in one call
+    let mut caught_up = battle(21);
+    assert_eq!(caught_up.fast_forward(steps as f32 / 60.0), steps);
+    assert_eq!(caught_up.ticks(), live.ticks());
+
+    let other = caught_up.warehouse();
+    assert_eq!(other.get_current_time_ms(), warehouse.get_current_time_ms());
+    assert_eq!(other.active_npc_ulids(), warehouse.active_npc_ulids());
+    assert_eq!(other.state_hash(), warehouse.state_hash());
+
+    // And the fight actually happened
+    let hurt = warehouse.active_npc_ulids().iter().any(|ulid| {
+        warehouse
+            .get_combat_stats(ulid)
+            .is_some_and(|stats| stats.hp < stats.max_hp)
+    });
+    assert!(hurt);
+}
@@ -0,0 +1,65 @@
+//! A full battle with no engine: allies against goblins until one side falls
+
+mod common;
+
+use bevy::math::Vec2;
+use godo::simulation::CombatEvent;
+use std::collections::HashSet;
+
+use common::{battle_world, spawn_group, step};
+
+/// Ticks the battle may take (two minutes of simulated time)
+const MAX_TICKS: usize = 60 * 120;
+
+#[test]
+fn allies_beat_goblins_headless() {
+    let (warehouse, mut world, clock) = battle_world(42);
+    let mut allies = spawn_group(&warehouse, "warrior", 3, Vec2::new(400.0, 250.0));
+    allies.extend(spawn_group(
+        &warehouse,
+        "archer",
+        2,
+        Vec2::new(320.0, 275.0),
+    ));
+    let goblins = spawn_group(&warehouse, "goblin", 3, Vec2::new(700.0, 250.0));
+
+    // Dead NPCs go back to their pool, so deaths are tracked from the events
+    let mut kills: Vec<([u8; 16], [u8; 16])> = Vec::new();
+    let mut dead: HashSet<[u8; 16]> = HashSet::new();
+    let side_down =
+        |side: &[[u8; 16]], dead: &HashSet<[u8; 16]>| side.iter().all(|ulid| dead.contains(ulid));
+    let mut ticks = 0;
+    while !side_down(&allies, &dead) && !side_down(&goblins, &dead) {
+        assert!(
+            ticks < MAX_TICKS,
+            "battle still running after {} ticks",
+            ticks
+        );
+        for event in step(&warehouse, &mut world, &clock) {
+            if let CombatEvent::Death {
+                attacker, target, ..
+            } = event
+            {
+                assert!(dead.insert(target), "NPC killed twice");
+                kills.push((attacker, target));
+            }
+        }
+        ticks += 1;
+    }
+
+    assert!(side_down(&goblins, &dead), "goblins should lose");
+    assert!(!side_down(&allies, &dead), "some allies should survive");
+
+    // Every goblin died once, to an ally, and every kill is accounted for
+    for goblin in &goblins {
+        let killers: Vec<_> = kills
+            .iter()
+            .filter(|(_, target)| target == goblin)
+            .map(|(attacker, _)| attacker)
+            .collect();
+        assert_eq!(killers.len(), 1);
+        assert!(allies.contains(killers[0]));
+    }
+    let ally_deaths = allies.iter().filter(|ally| dead.contains(*ally)).count();
+    assert_eq!(kills.len(), goblins.len() + ally_deaths);
+}
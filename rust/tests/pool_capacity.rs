@@ -0,0 +1,75 @@
+//! Spawns past a pool's max_size wait or are dropped by its overflow policy
+
+mod common;
+
+use bevy::math::Vec2;
+use godo::simulation::PoolOverflow;
+
+use common::battle_world;
+
+#[test]
+fn queued_spawn_runs_when_a_slot_frees() {
+    let (warehouse, _world, _clock) = battle_world(5);
+    warehouse.register_pool("goblin", 2, "");
+    assert!(warehouse.set_pool_overflow("goblin", PoolOverflow::Queue));
+
+    let first = warehouse.rust_spawn_npc("goblin", Vec2::new(600.0, 200.0));
+    let second = warehouse.rust_spawn_npc("goblin", Vec2::new(600.0, 300.0));
+    assert!(first.is_some() && second.is_some());
+
+    // Over max_size: the requests wait, at most max_size of them
+    let waiting = Vec2::new(900.0, 400.0);
+    assert_eq!(warehouse.rust_spawn_npc("goblin", waiting), None);
+    assert_eq!(
+        warehouse.rust_spawn_npc("goblin", Vec2::new(950.0, 450.0)),
+        None
+    );
+    assert_eq!(
+        warehouse.rust_spawn_npc("goblin", Vec2::new(990.0, 490.0)),
+        None
+    );
+    let pool = warehouse.get_pool("goblin").unwrap();
+    assert_eq!((pool.current_active, pool.queued), (2, 2));
+
+    // Freeing a slot runs the oldest waiting request right away
+    assert!(warehouse.rust_despawn_npc(&first.unwrap()));
+    let pool = warehouse.get_pool("goblin").unwrap();
+    assert_eq!((pool.current_active, pool.queued), (2, 1));
+    let spawned: Vec<Vec2> = warehouse
+        .active_npc_ulids()
+        .iter()
+        .filter(|ulid| Some(**ulid) != second)
+        .filter_map(|ulid| warehouse.get_npc_position_internal(ulid))
+        .collect();
+    assert_eq!(spawned, vec![waiting]);
+}
+
+#[test]
+fn switching_to_reject_drops_waiting_spawns() {
+    let (warehouse, _world, _clock) = battle_world(5);
+    warehouse.register_pool("goblin", 1, "");
+    warehouse.set_pool_overflow("goblin", PoolOverflow::Queue);
+
+    let first = warehouse
+        .rust_spawn_npc("goblin", Vec2::new(600.0, 200.0))
+        .unwrap();
+    assert_eq!(
+        warehouse.rust_spawn_npc("goblin", Vec2::new(700.0, 200.0)),
+        None
+    );
+    assert_eq!(warehouse.get_pool("goblin").unwrap().queued, 1);
+
+    assert!(warehouse.set_pool_overflow("goblin", PoolOverflow::Reject));
+    assert_eq!(warehouse.get_pool("goblin").unwrap().queued, 0);
+
+    // Nothing runs when the slot frees, and new requests are refused outright
+    warehouse.rust_despawn_npc(&first);
+    assert_eq!(warehouse.get_pool("goblin").unwrap().current_active, 0);
+    let again = warehouse.rust_spawn_npc("goblin", Vec2::new(600.0, 200.0));
+    assert!(again.is_some());
+    assert_eq!(
+        warehouse.rust_spawn_npc("goblin", Vec2::new(700.0, 200.0)),
+        None
+    );
+    assert_eq!(warehouse.get_pool("goblin").unwrap().queued, 0);
+}
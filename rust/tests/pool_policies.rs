@@ -0,0 +1,69 @@
+//! Node budget and idle shrinking of the NPC pools
+
+mod common;
+
+use godo::simulation::NodeCommand;
+
+use common::{battle_world, step};
+
+#[test]
+fn node_budget_caps_pool_room() {
+    let (warehouse, _world, _clock) = battle_world(6);
+    warehouse.register_pool("goblin", 20, "");
+    assert_eq!(warehouse.pool_room("goblin", 5), 5);
+
+    // Two more slots in total, whatever the pool's own max_size allows
+    let total = warehouse.total_pool_slots();
+    warehouse.set_node_budget(total + 2);
+    assert_eq!(warehouse.pool_room("goblin", 5), 2);
+    assert_eq!(warehouse.pool_room("warrior", 5), 0);
+
+    warehouse.set_pool_policy("goblin", 12, None, 1000);
+    assert_eq!(warehouse.pools_to_warm(10), vec![("goblin".to_string(), 2)]);
+
+    assert_eq!(warehouse.initialize_npc_pool("goblin", 5).len(), 2);
+    assert_eq!(warehouse.total_pool_slots(), total + 2);
+    assert_eq!(warehouse.pool_room("goblin", 5), 0);
+    assert!(warehouse.pools_to_warm(10).is_empty());
+
+    warehouse.set_node_budget(0);
+    assert_eq!(warehouse.pool_room("goblin", 5), 5);
+}
+
+#[test]
+fn idle_pools_shrink_after_the_timeout() {
+    let (warehouse, mut world, clock) = battle_world(6);
+    warehouse.set_record_node_commands(true);
+    // Keeps max(max_idle, min_warm) = 3 of the 8 pooled clerics
+    warehouse.set_pool_policy("cleric", 3, Some(2), 1000);
+    // Keeps max(max_idle, min_warm) = 5 of the 8 pooled archers
+    warehouse.set_pool_policy("archer", 1, Some(5), 1000);
+    let available = |npc_type: &str| warehouse.get_pool(npc_type).unwrap().available;
+
+    // 60 ticks (~0.96s) is short of the timeout
+    for _ in 0..60 {
+        step(&warehouse, &mut world, &clock);
+    }
+    assert_eq!((available("cleric"), available("archer")), (8, 8));
+
+    for _ in 0..10 {
+        step(&warehouse, &mut world, &clock);
+    }
+    assert_eq!((available("cleric"), available("archer")), (3, 5));
+    // Pools without max_idle never shrink
+    assert_eq!(available("warrior"), 8);
+
+    let mut freed = 0;
+    while let Some(command) = warehouse.pop_node_command() {
+        if matches!(command, NodeCommand::Freed { .. }) {
+            freed += 1;
+        }
+    }
+    assert_eq!(freed, 5 + 3);
+
+    // Nothing more to free once the pools are down to what they keep
+    for _ in 0..120 {
+        step(&warehouse, &mut world, &clock);
+    }
+    assert_eq!((available("cleric"), available("archer")), (3, 5));
+}
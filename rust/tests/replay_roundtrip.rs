@@ -0,0 +1,138 @@
+//! Record a short battle, write it out as text, read it back and replay it
+
+mod common;
+
+use bevy::math::Vec2;
+use godo::simulation::{
+    DamageBreakdown, Formation, InputRecording, NPCState, NPCStaticState, PoolOverflow, TimerAction,
+};
+
+use common::{battle_world, spawn_group, step};
+
+/// Five seconds of simulated time
+const TICKS: u64 = 300;
+
+#[test]
+fn recorded_battle_replays_from_text() {
+    let (warehouse, mut world, clock) = battle_world(11);
+    warehouse.start_recording(Some(11));
+
+    // Inputs touching every part of the hashed state
+    let warriors = spawn_group(&warehouse, "warrior", 3, Vec2::new(400.0, 250.0));
+    spawn_group(&warehouse, "cleric", 1, Vec2::new(300.0, 300.0));
+    let goblins = spawn_group(&warehouse, "goblin", 3, Vec2::new(700.0, 250.0));
+    warehouse
+        .create_squad("vanguard", &warriors[0], Formation::Wedge)
+        .unwrap();
+    for warrior in &warriors[1..] {
+        warehouse.assign_to_squad("vanguard", warrior).unwrap();
+    }
+    warehouse
+        .order_squad("vanguard", Vec2::new(600.0, 260.0))
+        .unwrap();
+    warehouse
+        .apply_status_effect(&goblins[0], "poison", Some(warriors[0]))
+        .unwrap();
+    warehouse
+        .force_target(&warriors[1], Some(goblins[2]))
+        .unwrap();
+    warehouse.add_food(5);
+
+    for _ in 0..TICKS {
+        step(&warehouse, &mut world, &clock);
+    }
+    let recording = warehouse.stop_recording().expect("recording");
+
+    let text = recording.to_text();
+    let parsed = InputRecording::from_text(&text).expect("parse");
+    assert_eq!(parsed.to_text(), text);
+
+    let report = parsed.replay().expect("replay");
+    assert_eq!(report.divergence, None);
+    assert_eq!(report.ticks, TICKS);
+}
+
+#[test]
+fn definitions_and_pool_edits_replay_from_text() {
+    let (warehouse, mut world, clock) = battle_world(12);
+    warehouse.start_recording(Some(12));
+
+    warehouse
+        .register_status_effect_json(
+            r#"{"name": "bleed", "duration_ms": 3000, "tick_interval_ms": 250,
+                "stacking": "stack", "max_stacks": 4, "damage_per_tick": 3.0}"#,
+        )
+        .unwrap();
+    warehouse
+        .register_archetype_json(
+            r#"{"name": "brute", "faction": "monster", "combat_type": "melee",
+                "max_hp": 180.0, "attack": 14.0, "defense": 6.0}"#,
+        )
+        .unwrap();
+    warehouse.register_pool("brute", 3, "res://brute.tscn");
+    assert!(warehouse.set_pool_overflow("brute", PoolOverflow::Queue));
+    assert!(warehouse.set_pool_policy("brute", 1, None, 500));
+    warehouse.set_node_budget(64);
+    warehouse.initialize_npc_pool("brute", 3);
+
+    let warriors = spawn_group(&warehouse, "warrior", 3, Vec2::new(400.0, 250.0));
+    let brutes = spawn_group(&warehouse, "brute", 2, Vec2::new(650.0, 250.0));
+    warehouse
+        .apply_status_effect(&warriors[0], "bleed", Some(brutes[0]))
+        .unwrap();
+    warehouse.register_npc_for_combat_internal(
+        &brutes[1],
+        (NPCStaticState::MONSTER | NPCStaticState::MELEE).bits() as i32,
+        NPCState::IDLE.bits() as i32,
+        400.0,
+        20.0,
+        2.0,
+    );
+
+    // Neither can be written to the recording
+    assert!(warehouse
+        .add_damage_modifier("double", |hit: &mut DamageBreakdown| hit.amount *= 2.0)
+        .is_err());
+    assert!(warehouse.remove_damage_modifier("double").is_err());
+    assert!(warehouse.load_snapshot(&warehouse.save_snapshot()).is_err());
+
+    for _ in 0..120 {
+        step(&warehouse, &mut world, &clock);
+    }
+    warehouse.unregister_npc_from_combat_internal(&brutes[0]);
+    for _ in 0..60 {
+        step(&warehouse, &mut world, &clock);
+    }
+    let recording = warehouse.stop_recording().expect("recording");
+
+    let text = recording.to_text();
+    let parsed = InputRecording::from_text(&text).expect("parse");
+    assert_eq!(parsed.entries, recording.entries);
+
+    let report = parsed.replay().expect("replay");
+    assert_eq!(report.divergence, None);
+    assert_eq!(report.ticks, 180);
+}
+
+#[test]
+fn state_hash_covers_rng_and_timers() {
+    // No NPCs yet: only the RNG state tells these apart
+    let (first, ..) = battle_world(21);
+    let (second, ..) = battle_world(22);
+    assert_ne!(first.state_hash(), second.state_hash());
+
+    first.set_rng_seed(23);
+    second.set_rng_seed(23);
+    assert_eq!(first.state_hash(), second.state_hash());
+
+    let handle = second.schedule_timer(
+        500,
+        TimerAction::Respawn {
+            npc_type: "goblin".to_string(),
+            position: Vec2::new(100.0, 100.0),
+        },
+    );
+    assert_ne!(first.state_hash(), second.state_hash());
+    second.cancel_timer(handle);
+    assert_eq!(first.state_hash(), second.state_hash());
+}
@@ -0,0 +1,50 @@
+//! Host inputs sent while the simulation thread ticks land between steps, so
+//! a recording made with the thread running replays exactly
+
+mod common;
+
+use bevy::math::Vec2;
+use godo::simulation::{CombatEvent, SimulationRunner};
+use std::time::Duration;
+
+use common::{battle_world, spawn_group};
+
+#[test]
+fn threaded_recording_replays_exactly() {
+    let (warehouse, _world, _clock) = battle_world(31);
+    let mut runner = SimulationRunner::new(warehouse.clone());
+    warehouse.start_recording(Some(31));
+    spawn_group(&warehouse, "archer", 2, Vec2::new(300.0, 250.0));
+    let warriors = spawn_group(&warehouse, "warrior", 2, Vec2::new(420.0, 250.0));
+    let goblins = spawn_group(&warehouse, "goblin", 4, Vec2::new(700.0, 220.0));
+
+    assert!(runner.start_thread(240.0));
+    for frame in 0..300 {
+        std::thread::sleep(Duration::from_millis(3));
+
+        // Stand in for the host: land arrows, heal, nudge a goblin
+        let _step = warehouse.hold_steps();
+        while let Some(event) = warehouse.pop_combat_event() {
+            if let CombatEvent::Projectile {
+                attacker, target, ..
+            } = event
+            {
+                warehouse.projectile_hit(&attacker, &target);
+            }
+        }
+        warehouse.apply_healing(&warriors[frame % 2], 0.5, 0.0, 0.0);
+        if frame % 25 == 0 {
+            let goblin = &goblins[frame / 25 % goblins.len()];
+            if let Some(pos) = warehouse.get_npc_position_internal(goblin) {
+                warehouse.update_npc_position_internal(goblin, pos.x - 5.0, pos.y);
+            }
+        }
+    }
+    runner.stop_thread();
+    let recording = warehouse.stop_recording().expect("recording");
+
+    let report = recording.replay().expect("replay");
+    assert_eq!(report.divergence, None);
+    assert_eq!(report.ticks, runner.ticks());
+    assert!(report.ticks > 20, "only {} ticks ran", report.ticks);
+}
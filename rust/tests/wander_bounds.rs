@@ -0,0 +1,63 @@
+//! Wander zones and world bounds that would leave nowhere to wander are refused
+
+mod common;
+
+use bevy::math::Vec2;
+use serde_json::Value;
+
+use common::{battle_world, spawn_group, step};
+
+#[test]
+fn empty_wander_zones_are_rejected() {
+    let (warehouse, _world, _clock) = battle_world(1);
+    assert!(warehouse
+        .set_faction_wander_zone("ally", Some((0.5, 0.5)))
+        .is_err());
+    assert!(warehouse
+        .set_faction_wander_zone("ally", Some((0.7, 0.2)))
+        .is_err());
+    assert!(warehouse
+        .set_faction_wander_zone("ally", Some((0.2, 1.5)))
+        .is_err());
+    assert!(warehouse
+        .set_faction_wander_zone("ally", Some((0.1, 0.3)))
+        .is_ok());
+    assert!(warehouse.set_faction_wander_zone("ally", None).is_ok());
+}
+
+#[test]
+fn degenerate_world_bounds_are_rejected() {
+    let (warehouse, _world, _clock) = battle_world(1);
+    assert!(warehouse
+        .set_world_bounds(0.0, 1280.0, 300.0, 300.0)
+        .is_err());
+    assert!(warehouse
+        .set_world_bounds(500.0, 100.0, 0.0, 720.0)
+        .is_err());
+    assert!(warehouse
+        .set_world_bounds(0.0, f32::NAN, 0.0, 720.0)
+        .is_err());
+    assert!(warehouse.set_world_bounds(0.0, 640.0, 0.0, 360.0).is_ok());
+}
+
+#[test]
+fn snapshots_with_empty_ranges_are_rejected() {
+    let (warehouse, mut world, clock) = battle_world(1);
+    spawn_group(&warehouse, "warrior", 2, Vec2::new(200.0, 300.0));
+    step(&warehouse, &mut world, &clock);
+    let saved: Value = serde_json::from_str(&warehouse.save_snapshot_json()).unwrap();
+
+    let mut bad_bounds = saved.clone();
+    bad_bounds["world_bounds"] = serde_json::json!([0.0, 1280.0, 400.0, 100.0]);
+    assert!(warehouse
+        .load_snapshot_json(&bad_bounds.to_string())
+        .is_err());
+
+    let mut bad_zone = saved.clone();
+    bad_zone["factions"]["factions"][0]["wander_zone"] = serde_json::json!([0.4, 0.4]);
+    assert!(warehouse.load_snapshot_json(&bad_zone.to_string()).is_err());
+
+    // The battle is untouched by the refused loads
+    assert_eq!(warehouse.active_npc_ulids().len(), 2);
+    assert!(warehouse.load_snapshot_json(&saved.to_string()).is_ok());
+}